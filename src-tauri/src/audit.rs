@@ -0,0 +1,167 @@
+//! Capture settings audit
+//!
+//! Finds every profile overriding ShadowPlay or an Ansel setting and says who
+//! owns the override: Nvidiot (manifest or import/export history), the driver's
+//! predefined data, or some other tool.
+
+use std::collections::HashSet;
+use serde::{Deserialize, Serialize};
+use crate::manifest;
+use crate::nvapi::ffi::SHADOWPLAY_SETTING_ID;
+use crate::nvapi::profiles::{enumerate_profiles, find_profile_by_name};
+use crate::nvapi::applications::enumerate_applications;
+use crate::nvapi::resolve::executable_key;
+use crate::nvapi::settings::get_all_settings;
+use crate::nvapi::types::ModifiedBy;
+use crate::transfer;
+
+/// Ansel settings, from NvApiDriverSettings.h
+pub const ANSEL_ALLOW_ID: u32 = 0x1035DB89;
+pub const ANSEL_ENABLE_ID: u32 = 0x1075D972;
+pub const ANSEL_WHITELISTED_ID: u32 = 0x1085DA8A;
+
+/// Settings that affect capture or the overlay, with display names
+pub const CAPTURE_SETTINGS: &[(u32, &str)] = &[
+    (SHADOWPLAY_SETTING_ID, "ShadowPlay capture"),
+    (ANSEL_ALLOW_ID, "Ansel allow"),
+    (ANSEL_ENABLE_ID, "Ansel enable"),
+    (ANSEL_WHITELISTED_ID, "Ansel whitelisted"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OverrideOwner {
+    Nvidiot,
+    External,
+    Predefined,
+}
+
+/// One capture-related setting set on one profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureOverride {
+    pub profile_name: String,
+    pub executables: Vec<String>,
+    pub setting_id: u32,
+    pub setting_name: String,
+    pub value: Option<u32>,
+    pub owner: OverrideOwner,
+}
+
+/// Capture overrides grouped by owner
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureAudit {
+    pub managed: Vec<CaptureOverride>,
+    pub external: Vec<CaptureOverride>,
+    pub predefined: Vec<CaptureOverride>,
+}
+
+/// Decide who owns an override. `known` holds lowercased executables from the
+/// manifest and history. Nvidiot only ever writes the ShadowPlay setting, so a
+/// user-set Ansel value is external even on a profile we manage.
+pub fn classify(setting_id: u32, is_predefined: bool, executables: &[String], known: &HashSet<String>) -> OverrideOwner {
+    if is_predefined {
+        return OverrideOwner::Predefined;
+    }
+    let ours = setting_id == SHADOWPLAY_SETTING_ID
+        && executables.iter().any(|exe| known.contains(&executable_key(exe)));
+    if ours { OverrideOwner::Nvidiot } else { OverrideOwner::External }
+}
+
+fn known_executables() -> HashSet<String> {
+    manifest::entries().into_iter()
+        .map(|e| executable_key(&e.executable))
+        .chain(transfer::recorded_executables())
+        .collect()
+}
+
+/// Scan every profile for capture-related settings and group them by owner
+pub fn audit_capture_settings() -> Result<CaptureAudit, String> {
+    let known = known_executables();
+    let mut audit = CaptureAudit::default();
+
+    for profile in enumerate_profiles().map_err(|e| e.to_string())? {
+        let handle = find_profile_by_name(&profile.name).map_err(|e| e.to_string())?;
+        let settings = get_all_settings(handle).map_err(|e| e.to_string())?;
+
+        let found: Vec<_> = settings.into_iter()
+            .filter_map(|s| {
+                let (_, name) = CAPTURE_SETTINGS.iter().find(|(id, _)| *id == s.setting_id)?;
+                Some((s, *name))
+            })
+            .collect();
+        if found.is_empty() {
+            continue;
+        }
+
+        let executables: Vec<String> = enumerate_applications(handle, &profile.name)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|a| a.executable)
+            .collect();
+
+        for (setting, setting_name) in found {
+            let owner = classify(setting.setting_id, setting.is_current_predefined, &executables, &known);
+            let entry = CaptureOverride {
+                profile_name: profile.name.clone(),
+                executables: executables.clone(),
+                setting_id: setting.setting_id,
+                setting_name: setting_name.to_string(),
+                value: setting.dword_value,
+                owner,
+            };
+            match owner {
+                OverrideOwner::Nvidiot => audit.managed.push(entry),
+                OverrideOwner::External => audit.external.push(entry),
+                OverrideOwner::Predefined => audit.predefined.push(entry),
+            }
+        }
+    }
+
+    Ok(audit)
+}
+
+/// Adopt an external override into the managed set without changing the driver
+pub fn claim_override(executable: &str) -> Result<(), String> {
+    manifest::record_executable(executable, true, ModifiedBy::Manual)
+}
+
+/// Stop managing an executable without changing the driver. Returns whether it was managed.
+pub fn release_override(executable: &str) -> Result<bool, String> {
+    let removed = manifest::remove(executable).map_err(|e| e.to_string())?;
+    transfer::forget(executable);
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn known(executables: &[&str]) -> HashSet<String> {
+        executables.iter().map(|exe| executable_key(exe)).collect()
+    }
+
+    fn executables(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn predefined_settings_belong_to_the_driver() {
+        let owner = classify(SHADOWPLAY_SETTING_ID, true, &executables(&["game.exe"]), &known(&["game.exe"]));
+        assert_eq!(owner, OverrideOwner::Predefined);
+    }
+
+    #[test]
+    fn shadowplay_on_a_known_executable_is_ours_ignoring_case() {
+        let owner = classify(SHADOWPLAY_SETTING_ID, false, &executables(&["other.exe", "GAME.EXE"]), &known(&["game.exe"]));
+        assert_eq!(owner, OverrideOwner::Nvidiot);
+    }
+
+    #[test]
+    fn unknown_executables_and_ansel_settings_are_external() {
+        let known = known(&["game.exe"]);
+        assert_eq!(classify(SHADOWPLAY_SETTING_ID, false, &executables(&["other.exe"]), &known), OverrideOwner::External);
+        assert_eq!(classify(SHADOWPLAY_SETTING_ID, false, &[], &known), OverrideOwner::External);
+        assert_eq!(classify(ANSEL_ENABLE_ID, false, &executables(&["game.exe"]), &known), OverrideOwner::External);
+    }
+}
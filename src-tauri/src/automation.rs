@@ -0,0 +1,92 @@
+//! Global pause switch for automatic behavior
+//!
+//! Subsystems keep running while paused but must check `is_paused` before acting.
+//! Nothing is queued for later: a paused loop skips its tick outright, so there
+//! is no backlog to drain on pause. Temporary unblacklists that come due while
+//! paused are the one exception and are held rather than dropped, because
+//! dropping one would leave capture enabled for good; they apply on resume.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use crate::config;
+use crate::journal::{self, JournalCategory, JournalSeverity};
+use crate::reverts;
+use crate::storage::StorageError;
+use crate::tray;
+
+/// Emitted whenever the paused flag changes
+pub const AUTOMATION_STATE_EVENT: &str = "automation-state-changed";
+/// Emitted once on resume so listeners can run a single catch-up refresh
+pub const AUTOMATION_RESUMED_EVENT: &str = "automation-resumed";
+
+/// Subsystems that honor the pause switch
+const SUBSYSTEMS: &[&str] = &[
+    "focus-watcher",
+    "auto-refresh",
+    "rules-engine",
+    "revert-scheduler",
+    "revert-loop-watcher",
+    "maintenance",
+];
+
+static PAUSED: AtomicBool = AtomicBool::new(false);
+
+/// State of a single automatic subsystem
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationSubsystem {
+    pub name: String,
+    pub active: bool,
+}
+
+/// Current automation state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationState {
+    pub paused: bool,
+    pub subsystems: Vec<AutomationSubsystem>,
+}
+
+/// Restore the persisted paused flag
+pub fn init() {
+    PAUSED.store(config::get().automation_paused, Ordering::SeqCst);
+}
+
+/// Whether automatic behavior is currently paused
+pub fn is_paused() -> bool {
+    PAUSED.load(Ordering::SeqCst)
+}
+
+/// Get the current automation state
+pub fn get_state() -> AutomationState {
+    let paused = is_paused();
+    AutomationState {
+        paused,
+        subsystems: SUBSYSTEMS.iter()
+            .map(|name| AutomationSubsystem {
+                name: name.to_string(),
+                active: !paused,
+            })
+            .collect(),
+    }
+}
+
+/// Pause or resume all automatic behavior and persist the choice
+pub fn set_paused(app: &AppHandle, paused: bool) -> Result<AutomationState, StorageError> {
+    let was_paused = PAUSED.swap(paused, Ordering::SeqCst);
+    config::update(|c| c.automation_paused = paused)?;
+
+    let state = get_state();
+    tray::set_paused(paused);
+    if was_paused != paused {
+        journal::append(JournalCategory::Task, JournalSeverity::Info, if paused { "automation.paused" } else { "automation.resumed" }, serde_json::json!({
+            "heldReverts": reverts::get_pending_reverts().len(),
+        }));
+        let _ = app.emit(AUTOMATION_STATE_EVENT, &state);
+        if was_paused {
+            let _ = app.emit(AUTOMATION_RESUMED_EVENT, ());
+        }
+    }
+    Ok(state)
+}
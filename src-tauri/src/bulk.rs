@@ -0,0 +1,227 @@
+//! Bulk setting changes
+//!
+//! Applies one DWORD value to every profile matching a filter. A dry run returns
+//! the planned changes; a real run applies them with a single save and records
+//! the batch, with each profile's previous value, so it can be undone as a unit.
+
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::config;
+use crate::manifest::now_ms;
+use crate::nvapi::NvApiError;
+use crate::nvapi::profiles::{enumerate_profiles, find_profile_by_name};
+use crate::nvapi::session::{is_dirty, reload_settings, save_settings, with_session_held};
+use crate::nvapi::settings::{delete_setting, get_dword_override, set_dword_setting};
+use crate::storage::{self, StorageError};
+
+const BULK_HISTORY_FILE: &str = "bulk-history.json";
+
+/// Bulk operations kept for undo, oldest dropped first
+const HISTORY_LIMIT: usize = 50;
+
+/// Which profiles a bulk change applies to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProfileFilter {
+    /// Case-insensitive substring of the profile name
+    pub name_contains: Option<String>,
+    /// Skip NVIDIA's predefined profiles; they are only included when this is false
+    pub only_user_profiles: bool,
+    /// Skip profiles without applications
+    pub only_with_apps: bool,
+}
+
+impl Default for ProfileFilter {
+    fn default() -> Self {
+        Self { name_contains: None, only_user_profiles: true, only_with_apps: false }
+    }
+}
+
+/// One profile's part in a bulk change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkProfileChange {
+    pub profile_name: String,
+    /// The profile's own value before the change; None means it had no override
+    pub previous_value: Option<u32>,
+    /// Why this profile wasn't changed, if it failed
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkSetResult {
+    /// History entry for undo; None for dry runs and batches where nothing changed
+    pub operation_id: Option<u64>,
+    pub dry_run: bool,
+    pub setting_id: u32,
+    pub value: u32,
+    pub changes: Vec<BulkProfileChange>,
+}
+
+/// A bulk change that was applied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkOperation {
+    pub id: u64,
+    pub setting_id: u32,
+    pub value: u32,
+    pub applied_ms: u64,
+    /// Profiles that were changed, with their previous values
+    pub changes: Vec<BulkProfileChange>,
+    pub undone: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct BulkHistory {
+    operations: Vec<BulkOperation>,
+    next_id: u64,
+}
+
+static HISTORY: Lazy<Mutex<BulkHistory>> = Lazy::new(|| Mutex::new(BulkHistory::default()));
+
+/// Held for the whole of a bulk change or undo so two batches never interleave
+static BULK_LOCK: Mutex<()> = Mutex::new(());
+
+/// Load bulk operation history from app data
+pub fn load() -> Result<(), StorageError> {
+    if let Some(history) = storage::load::<BulkHistory>(BULK_HISTORY_FILE)? {
+        *HISTORY.lock().unwrap() = history;
+    }
+    Ok(())
+}
+
+/// Names of the profiles matching `filter`, refusing more than the configured cap
+fn matching_profiles(filter: &ProfileFilter) -> Result<Vec<String>, String> {
+    let needle = filter.name_contains.as_deref().map(str::to_lowercase);
+    let names: Vec<String> = enumerate_profiles().map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|p| !filter.only_user_profiles || !p.is_predefined)
+        .filter(|p| !filter.only_with_apps || p.application_count > 0)
+        .filter(|p| needle.as_deref().map_or(true, |n| p.name.to_lowercase().contains(n)))
+        .map(|p| p.name)
+        .collect();
+
+    let limit = config::get().bulk_limits.max_profiles;
+    if names.len() > limit as usize {
+        return Err(format!(
+            "The filter matches {} profiles, more than the limit of {}; narrow the filter",
+            names.len(), limit
+        ));
+    }
+    Ok(names)
+}
+
+/// Set `setting_id` to `value` on every profile matching `filter`. With `dry_run`
+/// only the planned changes are returned. Otherwise all changes are staged and
+/// saved at once with the session held, so no other command can save half the
+/// batch or reload it away, and recorded for `undo_bulk_setting`; profiles that
+/// fail are reported and skipped. Unsaved changes from elsewhere are refused
+/// rather than swept into the batch's save.
+pub fn bulk_set_setting(filter: &ProfileFilter, setting_id: u32, value: u32, dry_run: bool) -> Result<BulkSetResult, String> {
+    let _lock = BULK_LOCK.lock().unwrap();
+    let profile_names = matching_profiles(filter)?;
+
+    let changes = with_session_held(|| {
+        refuse_unsaved_changes(dry_run)?;
+        let changes: Vec<BulkProfileChange> = profile_names.into_iter()
+            .map(|profile_name| {
+                let change = find_profile_by_name(&profile_name).and_then(|handle| {
+                    let previous = get_dword_override(handle, setting_id)?;
+                    if !dry_run {
+                        set_dword_setting(handle, setting_id, value)?;
+                    }
+                    Ok(previous)
+                });
+                match change {
+                    Ok(previous_value) => BulkProfileChange { profile_name, previous_value, error: None },
+                    Err(e) => BulkProfileChange { profile_name, previous_value: None, error: Some(e.to_string()) },
+                }
+            })
+            .collect();
+        if !dry_run && changes.iter().any(|c| c.error.is_none()) {
+            save_or_reload()?;
+        }
+        Ok(changes)
+    }).map_err(|e| e.to_string())?;
+
+    let mut result = BulkSetResult { operation_id: None, dry_run, setting_id, value, changes };
+    let applied: Vec<BulkProfileChange> = result.changes.iter().filter(|c| c.error.is_none()).cloned().collect();
+    if dry_run || applied.is_empty() {
+        return Ok(result);
+    }
+
+    result.operation_id = Some(record_operation(setting_id, value, applied));
+    Ok(result)
+}
+
+/// Refuse a real run while the session holds changes the batch didn't make
+fn refuse_unsaved_changes(dry_run: bool) -> Result<(), NvApiError> {
+    if !dry_run && is_dirty() {
+        return Err(NvApiError::OperationAborted("save or discard the unsaved changes first".to_string()));
+    }
+    Ok(())
+}
+
+/// Save the batch, or reload so half of it isn't left in the session
+fn save_or_reload() -> Result<(), NvApiError> {
+    save_settings().inspect_err(|_| {
+        let _ = reload_settings();
+    })
+}
+
+fn record_operation(setting_id: u32, value: u32, changes: Vec<BulkProfileChange>) -> u64 {
+    let mut history = HISTORY.lock().unwrap();
+    history.next_id += 1;
+    let id = history.next_id;
+    history.operations.push(BulkOperation { id, setting_id, value, applied_ms: now_ms(), changes, undone: false });
+    if history.operations.len() > HISTORY_LIMIT {
+        let excess = history.operations.len() - HISTORY_LIMIT;
+        history.operations.drain(..excess);
+    }
+    storage::save_or_journal(BULK_HISTORY_FILE, &*history);
+    id
+}
+
+/// Restore every profile changed by a bulk operation to its previous value, saving once.
+/// Returns the per-profile results; profiles that no longer exist are reported.
+pub fn undo_bulk_setting(operation_id: u64) -> Result<Vec<BulkProfileChange>, String> {
+    let _lock = BULK_LOCK.lock().unwrap();
+
+    let operation = HISTORY.lock().unwrap().operations.iter()
+        .find(|o| o.id == operation_id)
+        .cloned()
+        .ok_or_else(|| format!("Bulk operation {} not found", operation_id))?;
+    if operation.undone {
+        return Err(format!("Bulk operation {} was already undone", operation_id));
+    }
+
+    let results = with_session_held(|| {
+        refuse_unsaved_changes(false)?;
+        let results: Vec<BulkProfileChange> = operation.changes.into_iter()
+            .map(|change| {
+                let restored = find_profile_by_name(&change.profile_name).and_then(|handle| match change.previous_value {
+                    Some(previous) => set_dword_setting(handle, operation.setting_id, previous),
+                    None => delete_setting(handle, operation.setting_id),
+                });
+                BulkProfileChange { error: restored.err().map(|e: NvApiError| e.to_string()), ..change }
+            })
+            .collect();
+        save_or_reload()?;
+        Ok(results)
+    }).map_err(|e| e.to_string())?;
+
+    let mut history = HISTORY.lock().unwrap();
+    if let Some(op) = history.operations.iter_mut().find(|o| o.id == operation_id) {
+        op.undone = true;
+    }
+    storage::save_or_journal(BULK_HISTORY_FILE, &*history);
+    Ok(results)
+}
+
+/// Recorded bulk operations, oldest first
+pub fn history() -> Vec<BulkOperation> {
+    HISTORY.lock().unwrap().operations.clone()
+}
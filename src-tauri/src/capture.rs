@@ -0,0 +1,504 @@
+//! "Why is this being captured" explanation
+//!
+//! Gathers the facts that decide whether ShadowPlay records an executable, then
+//! composes them into a verdict. Composition is pure over the gathered facts so
+//! the same input always yields the same explanation. When another profile has
+//! a name close to the game's, the explanation points at `compare_profiles`
+//! for the two, since near-duplicate profiles are a common cause of one game
+//! capturing while its twin doesn't.
+
+use serde::{Deserialize, Serialize};
+use crate::nvapi::applications::find_application;
+use crate::nvapi::error::NvApiError;
+use crate::nvapi::ffi::{NvDRSProfileHandle, SHADOWPLAY_DISABLED, SHADOWPLAY_ENABLED, SHADOWPLAY_SETTING_ID};
+use crate::nvapi::profiles::{find_profile_fuzzy, get_base_profile, get_profile_info_by_handle};
+use crate::nvapi::settings::find_dword_setting;
+use crate::nvapi::types::GpuProductLine;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CaptureVerdict {
+    WillCapture,
+    WillNotCapture,
+    Unknown,
+}
+
+/// How a fact bears on the verdict
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FactEffect {
+    Allows,
+    Blocks,
+    /// Informational; doesn't change the verdict
+    Neutral,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureFact {
+    pub source: String,
+    pub detail: String,
+    pub effect: FactEffect,
+}
+
+/// Two profiles worth passing to `compare_profiles`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuggestedComparison {
+    pub profile_a: String,
+    pub profile_b: String,
+}
+
+/// Verdict plus the facts behind it, in precedence order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureExplanation {
+    pub executable: String,
+    pub verdict: CaptureVerdict,
+    pub facts: Vec<CaptureFact>,
+    /// Set when a profile with a similar name exists
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compare_with: Option<SuggestedComparison>,
+}
+
+/// Name similarity from which another profile counts as a sibling
+const SIBLING_PROFILE_SIMILARITY: f32 = 0.7;
+
+/// Result of reading the ShadowPlay setting from one profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingLookup {
+    Set(u32),
+    NotSet,
+    Unreadable(String),
+}
+
+/// Everything the verdict depends on, gathered before composing
+#[derive(Debug, Clone)]
+pub struct CaptureFacts {
+    pub executable: String,
+    /// Profile the driver applies to the executable, if any
+    pub profile: Option<(String, SettingLookup)>,
+    /// GPU product lines the profile applies to
+    pub profile_gpu_support: Vec<GpuProductLine>,
+    /// False when the profile's mask leaves out an installed GPU
+    pub profile_applies_to_gpu: Option<bool>,
+    /// Set when the application lookup itself failed
+    pub lookup_error: Option<String>,
+    pub base_profile: SettingLookup,
+    pub running_process_ids: Vec<u32>,
+    /// Whether the NVIDIA overlay is running; None where it can't be checked
+    pub overlay_running: Option<bool>,
+    /// Whether one of the running processes owns the fullscreen foreground
+    /// window; None when the executable isn't running
+    pub fullscreen: Option<bool>,
+    pub game_dvr_enabled: Option<bool>,
+    /// Another user profile whose name is close to the application profile's
+    pub sibling_profile: Option<String>,
+}
+
+fn describe_value(value: u32) -> &'static str {
+    match value {
+        SHADOWPLAY_DISABLED => "capture disabled",
+        SHADOWPLAY_ENABLED => "capture enabled",
+        _ => "unrecognized value",
+    }
+}
+
+/// Verdict for an explicit setting value
+fn value_verdict(value: u32) -> (CaptureVerdict, FactEffect) {
+    match value {
+        SHADOWPLAY_DISABLED => (CaptureVerdict::WillNotCapture, FactEffect::Blocks),
+        SHADOWPLAY_ENABLED => (CaptureVerdict::WillCapture, FactEffect::Allows),
+        _ => (CaptureVerdict::Unknown, FactEffect::Neutral),
+    }
+}
+
+fn fact(source: &str, detail: String, effect: FactEffect) -> CaptureFact {
+    CaptureFact { source: source.to_string(), detail, effect }
+}
+
+/// Combine gathered facts into a verdict. The application's own profile wins;
+/// without a value there, the base profile decides, and an unset base profile
+/// means the driver default, which captures. Nothing is recorded while the
+/// overlay isn't running, whatever the profiles say.
+pub fn compose(facts: &CaptureFacts) -> CaptureExplanation {
+    let mut list = Vec::new();
+    let mut verdict = None;
+
+    if let Some(error) = &facts.lookup_error {
+        list.push(fact("application-profile", format!("Lookup failed: {}", error), FactEffect::Neutral));
+        verdict = Some(CaptureVerdict::Unknown);
+    }
+
+    match &facts.profile {
+        Some((name, SettingLookup::Set(value))) => {
+            let (profile_verdict, effect) = value_verdict(*value);
+            list.push(fact(
+                "application-profile",
+                format!("Profile '{}' sets 0x{:08X} ({})", name, value, describe_value(*value)),
+                effect,
+            ));
+            verdict = verdict.or(Some(profile_verdict));
+        }
+        Some((name, SettingLookup::NotSet)) => list.push(fact(
+            "application-profile",
+            format!("Profile '{}' doesn't set ShadowPlay; falls through to the base profile", name),
+            FactEffect::Neutral,
+        )),
+        Some((name, SettingLookup::Unreadable(error))) => {
+            list.push(fact(
+                "application-profile",
+                format!("Profile '{}' setting could not be read: {}", name, error),
+                FactEffect::Neutral,
+            ));
+            verdict = verdict.or(Some(CaptureVerdict::Unknown));
+        }
+        None if facts.lookup_error.is_none() => list.push(fact(
+            "application-profile",
+            "No DRS profile applies; falls through to the base profile".to_string(),
+            FactEffect::Neutral,
+        )),
+        None => {}
+    }
+
+    if facts.profile_applies_to_gpu == Some(false) {
+        let lines: Vec<&str> = facts.profile_gpu_support.iter()
+            .map(|line| match line {
+                GpuProductLine::Geforce => "GeForce",
+                GpuProductLine::Quadro => "Quadro",
+                GpuProductLine::Nvs => "NVS",
+            })
+            .collect();
+        list.push(fact(
+            "gpu-support",
+            format!(
+                "The profile only applies to {} GPUs and doesn't cover every installed GPU; the setting may be ignored on the one rendering the game",
+                if lines.is_empty() { "no".to_string() } else { lines.join("/") },
+            ),
+            FactEffect::Neutral,
+        ));
+    }
+
+    // The base profile is listed even when overridden, so the full chain is visible
+    let base_verdict = match &facts.base_profile {
+        SettingLookup::Set(value) => {
+            let (base_verdict, effect) = value_verdict(*value);
+            let effect = if verdict.is_some() { FactEffect::Neutral } else { effect };
+            list.push(fact(
+                "base-profile",
+                format!("Base profile sets 0x{:08X} ({})", value, describe_value(*value)),
+                effect,
+            ));
+            base_verdict
+        }
+        SettingLookup::NotSet => {
+            let effect = if verdict.is_some() { FactEffect::Neutral } else { FactEffect::Allows };
+            list.push(fact(
+                "base-profile",
+                "Base profile doesn't set ShadowPlay; the driver default captures".to_string(),
+                effect,
+            ));
+            CaptureVerdict::WillCapture
+        }
+        SettingLookup::Unreadable(error) => {
+            list.push(fact("base-profile", format!("Could not be read: {}", error), FactEffect::Neutral));
+            CaptureVerdict::Unknown
+        }
+    };
+    let mut verdict = verdict.unwrap_or(base_verdict);
+
+    match facts.overlay_running {
+        Some(true) => list.push(fact("overlay", "The NVIDIA overlay is running".to_string(), FactEffect::Neutral)),
+        Some(false) => {
+            list.push(fact(
+                "overlay",
+                "The NVIDIA overlay isn't running, so ShadowPlay can't record anything".to_string(),
+                FactEffect::Blocks,
+            ));
+            verdict = CaptureVerdict::WillNotCapture;
+        }
+        None => {}
+    }
+
+    if facts.running_process_ids.is_empty() {
+        list.push(fact("process", "Not running".to_string(), FactEffect::Neutral));
+    } else {
+        list.push(fact(
+            "process",
+            format!(
+                "Running as pid {}; DRS changes apply from the next launch",
+                facts.running_process_ids.iter().map(|p| p.to_string()).collect::<Vec<_>>().join(", "),
+            ),
+            FactEffect::Neutral,
+        ));
+    }
+
+    match facts.fullscreen {
+        Some(true) => list.push(fact("fullscreen", "Fullscreen in the foreground".to_string(), FactEffect::Neutral)),
+        Some(false) => list.push(fact(
+            "fullscreen",
+            "Not fullscreen in the foreground; only desktop capture records it while windowed".to_string(),
+            FactEffect::Neutral,
+        )),
+        None => {}
+    }
+
+    if facts.game_dvr_enabled == Some(true) {
+        list.push(fact(
+            "windows-game-dvr",
+            "Windows Game DVR is enabled and may capture independently of ShadowPlay".to_string(),
+            FactEffect::Neutral,
+        ));
+    }
+
+    let compare_with = match (&facts.profile, &facts.sibling_profile) {
+        (Some((name, _)), Some(sibling)) => {
+            list.push(fact(
+                "sibling-profile",
+                format!("Profile '{}' has a similar name; comparing the two shows where their settings differ", sibling),
+                FactEffect::Neutral,
+            ));
+            Some(SuggestedComparison { profile_a: name.clone(), profile_b: sibling.clone() })
+        }
+        _ => None,
+    };
+
+    CaptureExplanation { executable: facts.executable.clone(), verdict, facts: list, compare_with }
+}
+
+fn read_setting(profile_handle: NvDRSProfileHandle) -> SettingLookup {
+    match find_dword_setting(profile_handle, SHADOWPLAY_SETTING_ID) {
+        Ok(Some(value)) => SettingLookup::Set(value),
+        Ok(None) => SettingLookup::NotSet,
+        Err(e) => SettingLookup::Unreadable(e.to_string()),
+    }
+}
+
+/// The user profile with the name closest to `profile_name`, if close enough
+fn find_sibling_profile(profile_name: &str) -> Option<String> {
+    find_profile_fuzzy(profile_name).ok()?
+        .into_iter()
+        .find(|(p, score)| *score >= SIBLING_PROFILE_SIMILARITY && !p.name.eq_ignore_ascii_case(profile_name))
+        .map(|(p, _)| p.name)
+}
+
+/// Resolve a pid to its executable name; anything that isn't a running pid is
+/// taken as an executable name
+fn resolve_target(executable_or_pid: &str) -> (String, Vec<u32>) {
+    #[cfg(target_os = "windows")]
+    {
+        use crate::windows::{get_running_processes, processes::get_process_info};
+
+        if let Ok(pid) = executable_or_pid.trim().parse::<u32>() {
+            if let Some((name, _)) = get_process_info(pid) {
+                return (name, vec![pid]);
+            }
+        }
+
+        use crate::nvapi::resolve::{basename, executable_key};
+
+        let executable = executable_or_pid.trim().to_string();
+        let name = executable_key(basename(&executable));
+        let pids = get_running_processes().into_iter()
+            .filter(|p| executable_key(&p.process_name) == name)
+            .map(|p| p.process_id)
+            .collect();
+        (executable, pids)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        (executable_or_pid.trim().to_string(), Vec::new())
+    }
+}
+
+/// Gather the facts for an executable name, path, or running pid
+pub fn gather(executable_or_pid: &str) -> CaptureFacts {
+    let (executable, running_process_ids) = resolve_target(executable_or_pid);
+
+    let mut profile_gpu_support = Vec::new();
+    let mut profile_applies_to_gpu = None;
+    let (profile, lookup_error) = match find_application(&executable) {
+        Ok((handle, _)) => {
+            let name = match get_profile_info_by_handle(handle) {
+                Ok(info) => {
+                    profile_gpu_support = info.gpu_support;
+                    profile_applies_to_gpu = info.applies_to_current_gpu;
+                    info.name
+                }
+                Err(_) => "(unknown)".to_string(),
+            };
+            (Some((name, read_setting(handle))), None)
+        }
+        Err(NvApiError::ApplicationNotFound(_)) => (None, None),
+        Err(e) => (None, Some(e.to_string())),
+    };
+
+    let sibling_profile = profile.as_ref().and_then(|(name, _)| find_sibling_profile(name));
+
+    let base_profile = match get_base_profile() {
+        Ok(handle) => read_setting(handle),
+        Err(e) => SettingLookup::Unreadable(e.to_string()),
+    };
+
+    #[cfg(target_os = "windows")]
+    let (overlay_running, fullscreen, game_dvr_enabled) = {
+        use crate::windows::{display::foreground_fullscreen_process_id, processes::is_nvidia_overlay_running};
+
+        let fullscreen = (!running_process_ids.is_empty()).then(|| {
+            foreground_fullscreen_process_id().is_some_and(|pid| running_process_ids.contains(&pid))
+        });
+        (Some(is_nvidia_overlay_running()), fullscreen, Some(crate::windows::is_game_dvr_enabled()))
+    };
+    #[cfg(not(target_os = "windows"))]
+    let (overlay_running, fullscreen, game_dvr_enabled) = (None, None, None);
+
+    CaptureFacts {
+        executable,
+        profile,
+        profile_gpu_support,
+        profile_applies_to_gpu,
+        lookup_error,
+        base_profile,
+        running_process_ids,
+        overlay_running,
+        fullscreen,
+        game_dvr_enabled,
+        sibling_profile,
+    }
+}
+
+/// Explain whether ShadowPlay will record an executable or pid, and why
+pub fn explain_capture(executable_or_pid: &str) -> CaptureExplanation {
+    compose(&gather(executable_or_pid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn facts(profile: Option<SettingLookup>, base_profile: SettingLookup) -> CaptureFacts {
+        CaptureFacts {
+            executable: "game.exe".to_string(),
+            profile: profile.map(|lookup| ("Game".to_string(), lookup)),
+            profile_gpu_support: Vec::new(),
+            profile_applies_to_gpu: None,
+            lookup_error: None,
+            base_profile,
+            running_process_ids: Vec::new(),
+            overlay_running: None,
+            fullscreen: None,
+            game_dvr_enabled: None,
+            sibling_profile: None,
+        }
+    }
+
+    fn effect_of(explanation: &CaptureExplanation, source: &str) -> FactEffect {
+        explanation.facts.iter().find(|f| f.source == source).unwrap().effect
+    }
+
+    #[test]
+    fn the_application_profile_wins_over_the_base_profile() {
+        let explanation = compose(&facts(
+            Some(SettingLookup::Set(SHADOWPLAY_DISABLED)),
+            SettingLookup::Set(SHADOWPLAY_ENABLED),
+        ));
+        assert_eq!(explanation.verdict, CaptureVerdict::WillNotCapture);
+        assert_eq!(effect_of(&explanation, "application-profile"), FactEffect::Blocks);
+        assert_eq!(effect_of(&explanation, "base-profile"), FactEffect::Neutral);
+    }
+
+    #[test]
+    fn an_unset_chain_falls_through_to_the_capturing_default() {
+        let explanation = compose(&facts(Some(SettingLookup::NotSet), SettingLookup::NotSet));
+        assert_eq!(explanation.verdict, CaptureVerdict::WillCapture);
+        assert_eq!(effect_of(&explanation, "base-profile"), FactEffect::Allows);
+
+        let base_disables = compose(&facts(None, SettingLookup::Set(SHADOWPLAY_DISABLED)));
+        assert_eq!(base_disables.verdict, CaptureVerdict::WillNotCapture);
+    }
+
+    #[test]
+    fn unreadable_or_unrecognized_settings_are_unknown() {
+        let unreadable = compose(&facts(Some(SettingLookup::Unreadable("denied".to_string())), SettingLookup::NotSet));
+        assert_eq!(unreadable.verdict, CaptureVerdict::Unknown);
+
+        let unrecognized = compose(&facts(Some(SettingLookup::Set(7)), SettingLookup::NotSet));
+        assert_eq!(unrecognized.verdict, CaptureVerdict::Unknown);
+
+        let mut failed = facts(None, SettingLookup::Set(SHADOWPLAY_DISABLED));
+        failed.lookup_error = Some("driver busy".to_string());
+        assert_eq!(compose(&failed).verdict, CaptureVerdict::Unknown);
+    }
+
+    #[test]
+    fn informational_facts_are_listed_without_changing_the_verdict() {
+        let mut gathered = facts(Some(SettingLookup::Set(SHADOWPLAY_DISABLED)), SettingLookup::NotSet);
+        gathered.profile_gpu_support = vec![GpuProductLine::Geforce];
+        gathered.profile_applies_to_gpu = Some(false);
+        gathered.running_process_ids = vec![42];
+        gathered.overlay_running = Some(true);
+        gathered.fullscreen = Some(true);
+        gathered.game_dvr_enabled = Some(true);
+        gathered.sibling_profile = Some("Game (2)".to_string());
+        let explanation = compose(&gathered);
+
+        assert_eq!(explanation.verdict, CaptureVerdict::WillNotCapture);
+        let sources: Vec<&str> = explanation.facts.iter().map(|f| f.source.as_str()).collect();
+        assert_eq!(sources, [
+            "application-profile", "gpu-support", "base-profile", "overlay", "process", "fullscreen",
+            "windows-game-dvr", "sibling-profile",
+        ]);
+        assert!(explanation.facts[1].detail.contains("GeForce"));
+        assert!(explanation.facts[4].detail.contains("42"));
+        assert_eq!(explanation.compare_with, Some(SuggestedComparison {
+            profile_a: "Game".to_string(),
+            profile_b: "Game (2)".to_string(),
+        }));
+    }
+
+    #[test]
+    fn a_stopped_overlay_blocks_every_chain() {
+        let lookups = || [
+            SettingLookup::Set(SHADOWPLAY_ENABLED),
+            SettingLookup::Set(SHADOWPLAY_DISABLED),
+            SettingLookup::Set(7),
+            SettingLookup::NotSet,
+            SettingLookup::Unreadable("denied".to_string()),
+        ];
+        for profile in lookups().into_iter().map(Some).chain([None]) {
+            for base in lookups() {
+                let mut gathered = facts(profile.clone(), base);
+                let unchecked = compose(&gathered).verdict;
+                gathered.overlay_running = Some(true);
+                assert_eq!(compose(&gathered).verdict, unchecked);
+
+                gathered.overlay_running = Some(false);
+                let explanation = compose(&gathered);
+                assert_eq!(explanation.verdict, CaptureVerdict::WillNotCapture, "{:?}", gathered);
+                assert_eq!(effect_of(&explanation, "overlay"), FactEffect::Blocks);
+            }
+        }
+    }
+
+    #[test]
+    fn fullscreen_is_only_reported_for_running_games() {
+        let mut gathered = facts(None, SettingLookup::NotSet);
+        assert!(compose(&gathered).facts.iter().all(|f| f.source != "fullscreen"));
+
+        gathered.running_process_ids = vec![7];
+        gathered.fullscreen = Some(false);
+        let explanation = compose(&gathered);
+        assert_eq!(explanation.verdict, CaptureVerdict::WillCapture);
+        assert_eq!(effect_of(&explanation, "fullscreen"), FactEffect::Neutral);
+        assert!(explanation.facts.iter().find(|f| f.source == "fullscreen").unwrap().detail.starts_with("Not fullscreen"));
+    }
+
+    #[test]
+    fn composing_is_deterministic() {
+        let gathered = facts(Some(SettingLookup::NotSet), SettingLookup::Set(SHADOWPLAY_ENABLED));
+        let (a, b) = (compose(&gathered), compose(&gathered));
+        assert_eq!(a.verdict, b.verdict);
+        assert_eq!(
+            a.facts.iter().map(|f| &f.detail).collect::<Vec<_>>(),
+            b.facts.iter().map(|f| &f.detail).collect::<Vec<_>>(),
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Chunked command responses
+//!
+//! Very large lists (tens of thousands of DRS applications) can exceed what the
+//! webview accepts in a single IPC message, which makes the command look hung.
+//! List commands take an optional channel; when one is passed the result is
+//! streamed in fixed-size chunks followed by a summary, and the command itself
+//! returns an empty list. Without a channel the whole list is returned as before.
+//! The summary also says how fresh the snapshot is, which a plain list can't carry.
+
+use serde::Serialize;
+use tauri::ipc::Channel;
+use crate::nvapi::types::Freshness;
+
+/// Items per chunk. Measured by serializing 40,000 synthetic DrsApplication rows
+/// (a fifth with full paths, a quarter with friendly names) as chunk messages:
+/// 139 bytes per row on average, so a 2000-row chunk is about 280 KB, against
+/// 5.5 MB for the whole list in one response. Serialization time was the same
+/// (about 8 ms per pass) for every chunk size from 500 to 40,000, so the size
+/// only trades message count against message size: 2000 keeps a 40k-row list
+/// to 20 messages, each far below the multi-megabyte responses that stalled.
+/// An AvailableSetting is about 50 bytes, so its chunks are smaller still.
+pub const CHUNK_SIZE: usize = 2000;
+
+/// A message on a chunked response channel
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ChunkMessage<T> {
+    Chunk { index: u32, items: Vec<T> },
+    /// Sent last; `total` lets the receiver check nothing was dropped
+    Done { chunks: u32, total: u32, freshness: Freshness },
+}
+
+/// Return `items` directly, or stream them over `channel` when one is given
+pub fn respond<T: Serialize + Clone>(
+    items: Vec<T>,
+    channel: Option<Channel<ChunkMessage<T>>>,
+    freshness: Freshness,
+) -> Result<Vec<T>, String> {
+    let Some(channel) = channel else {
+        return Ok(items);
+    };
+
+    let total = items.len() as u32;
+    let mut chunks = 0;
+    for chunk in items.chunks(CHUNK_SIZE) {
+        channel.send(ChunkMessage::Chunk { index: chunks, items: chunk.to_vec() })
+            .map_err(|e| format!("Failed to send chunk {}: {}", chunks, e))?;
+        chunks += 1;
+    }
+    channel.send(ChunkMessage::Done { chunks, total, freshness })
+        .map_err(|e| format!("Failed to send chunk summary: {}", e))?;
+    Ok(Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use serde_json::Value;
+    use tauri::ipc::InvokeResponseBody;
+    use crate::nvapi::types::FreshnessSource;
+
+    fn freshness() -> Freshness {
+        Freshness { session_generation: 1, cache_built_at_ms: None, cache_age_ms: None, source: FreshnessSource::Live }
+    }
+
+    /// A channel that keeps every message it is sent, decoded
+    pub(super) fn recording_channel<T>() -> (Channel<ChunkMessage<T>>, Arc<Mutex<Vec<Value>>>) {
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        let sink = messages.clone();
+        let channel = Channel::new(move |body| {
+            if let InvokeResponseBody::Json(json) = body {
+                sink.lock().unwrap().push(serde_json::from_str(&json).unwrap());
+            }
+            Ok(())
+        });
+        (channel, messages)
+    }
+
+    #[test]
+    fn without_a_channel_the_list_is_returned_whole() {
+        assert_eq!(respond(vec![1u32, 2, 3], None, freshness()).unwrap(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn with_a_channel_items_stream_in_chunks_then_a_summary() {
+        let (channel, messages) = recording_channel();
+        let items: Vec<u32> = (0..(2 * CHUNK_SIZE + 1) as u32).collect();
+        assert!(respond(items, Some(channel), freshness()).unwrap().is_empty());
+
+        let messages = messages.lock().unwrap();
+        assert_eq!(messages.len(), 4);
+        for (index, message) in messages[..3].iter().enumerate() {
+            assert_eq!(message["type"], "chunk");
+            assert_eq!(message["index"], index as u64);
+        }
+        assert_eq!(messages[0]["items"].as_array().unwrap().len(), CHUNK_SIZE);
+        assert_eq!(messages[2]["items"], serde_json::json!([2 * CHUNK_SIZE]));
+        assert_eq!(messages[3]["type"], "done");
+        assert_eq!(messages[3]["chunks"], 3);
+        assert_eq!(messages[3]["total"], (2 * CHUNK_SIZE + 1) as u64);
+    }
+
+    #[test]
+    fn an_empty_list_sends_only_the_summary() {
+        let (channel, messages) = recording_channel();
+        respond(Vec::new(), Some(channel), freshness()).unwrap();
+        let messages = messages.lock().unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0]["chunks"], 0);
+    }
+}
+
+#[cfg(all(test, target_os = "windows"))]
+mod mock_tests {
+    use std::collections::HashSet;
+    use serde_json::Value;
+    use super::tests::recording_channel;
+    use super::CHUNK_SIZE;
+    use crate::nvapi::mock::{self, Store};
+
+    /// Items from a recorded chunked response, checking chunk indices and sizes
+    /// and that the summary's total matches what arrived
+    fn reassemble(messages: &[Value]) -> Vec<Value> {
+        let (done, chunks) = messages.split_last().expect("a summary");
+        let mut items = Vec::new();
+        for (index, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk["type"], "chunk");
+            assert_eq!(chunk["index"], index as u64);
+            let chunk_items = chunk["items"].as_array().unwrap();
+            // Only the last chunk may be short
+            if index + 1 < chunks.len() {
+                assert_eq!(chunk_items.len(), CHUNK_SIZE);
+            } else {
+                assert!(!chunk_items.is_empty() && chunk_items.len() <= CHUNK_SIZE);
+            }
+            items.extend(chunk_items.iter().cloned());
+        }
+        assert_eq!(done["type"], "done");
+        assert_eq!(done["chunks"], chunks.len() as u64);
+        assert_eq!(done["total"], items.len() as u64);
+        items
+    }
+
+    #[test]
+    fn applications_from_a_large_store_are_neither_split_nor_duplicated() {
+        let _serial = mock::serial();
+        mock::install(Store::synthetic(6000));
+
+        let whole = tauri::async_runtime::block_on(crate::get_all_applications(None)).unwrap();
+        assert!(whole.len() > 3 * CHUNK_SIZE, "only {} applications", whole.len());
+
+        let (channel, messages) = recording_channel();
+        assert!(tauri::async_runtime::block_on(crate::get_all_applications(Some(channel))).unwrap().is_empty());
+        let streamed = reassemble(&messages.lock().unwrap());
+
+        assert_eq!(streamed, serde_json::to_value(&whole).unwrap().as_array().unwrap().clone());
+        let keys: HashSet<(String, String)> = streamed.iter()
+            .map(|app| (app["profileName"].as_str().unwrap().to_string(), app["executable"].as_str().unwrap().to_string()))
+            .collect();
+        assert_eq!(keys.len(), streamed.len());
+    }
+
+    #[test]
+    fn available_settings_stream_in_chunks_in_catalog_order() {
+        let _serial = mock::serial();
+        mock::install(Store::default());
+        let catalog: Vec<(u32, String)> = (0..(2 * CHUNK_SIZE + 7) as u32)
+            .map(|i| (0x1000_0000 + i, format!("Setting {}", i)))
+            .collect();
+        mock::set_setting_catalog(catalog.clone());
+
+        let (channel, messages) = recording_channel();
+        assert!(tauri::async_runtime::block_on(crate::get_available_settings(Some(channel))).unwrap().is_empty());
+        let streamed = reassemble(&messages.lock().unwrap());
+
+        let received: Vec<(u32, String)> = streamed.iter()
+            .map(|s| (s["settingId"].as_u64().unwrap() as u32, s["name"].as_str().unwrap().to_string()))
+            .collect();
+        assert_eq!(received, catalog);
+    }
+}
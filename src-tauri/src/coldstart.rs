@@ -0,0 +1,178 @@
+//! Cold-start blacklist snapshot
+//!
+//! A full application enumeration has to finish before the blacklist view can
+//! render, so every start used to wait for it. After each successful
+//! enumeration, the applications the view shows, with their ShadowPlay status,
+//! are written to disk with the driver version and a fingerprint of the DRS
+//! store files. The first `get_blacklist_snapshot` of a run answers from that
+//! file, marked stale, while a background enumeration runs and emits the
+//! corrected snapshot. The file is ignored when the driver or the store files
+//! changed, and it only ever feeds this view: mutation pre-checks read DRS live.
+
+use std::fs;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::UNIX_EPOCH;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::journal::{self, JournalCategory, JournalSeverity};
+use crate::manifest::{self, now_ms};
+use crate::nvapi::NvApiError;
+use crate::nvapi::applications::get_all_applications;
+use crate::nvapi::ffi::driver_version;
+use crate::nvapi::scope::store_candidates;
+use crate::nvapi::session::is_read_only;
+use crate::nvapi::types::DrsApplication;
+use crate::storage::{self, StorageError};
+use crate::tasks::TaskSupervisor;
+
+const SNAPSHOT_FILE: &str = "blacklist-snapshot.json";
+
+/// Emitted with a live BlacklistSnapshot once the background enumeration finishes
+pub const BLACKLIST_SNAPSHOT_EVENT: &str = "blacklist-snapshot";
+
+/// Size and modification time of one DRS store file; both None if it doesn't exist
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoreStamp {
+    modified_ms: Option<u64>,
+    len: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct StoredSnapshot {
+    driver_version: u32,
+    store_fingerprint: Vec<StoreStamp>,
+    /// Applications as the blacklist view shows them, without provenance,
+    /// which comes from the manifest when the snapshot is served
+    applications: Vec<DrsApplication>,
+    taken_at_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlacklistSnapshot {
+    pub applications: Vec<DrsApplication>,
+    /// Served from the previous run's file; a live snapshot follows as an event
+    pub stale: bool,
+    pub taken_at_ms: u64,
+}
+
+static STORED: Lazy<Mutex<Option<StoredSnapshot>>> = Lazy::new(|| Mutex::new(None));
+
+/// Set once the stale snapshot was served, so only the first request of a run gets it
+static SERVED_STALE: AtomicBool = AtomicBool::new(false);
+
+/// Load the previous run's snapshot from app data
+pub fn load() -> Result<(), StorageError> {
+    if let Some(snapshot) = storage::load::<StoredSnapshot>(SNAPSHOT_FILE)? {
+        *STORED.lock().unwrap() = Some(snapshot);
+    }
+    Ok(())
+}
+
+fn store_fingerprint() -> Vec<StoreStamp> {
+    store_candidates().iter()
+        .map(|candidate| match fs::metadata(&candidate.path) {
+            Ok(metadata) => StoreStamp {
+                modified_ms: metadata.modified().ok()
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_millis() as u64),
+                len: Some(metadata.len()),
+            },
+            Err(_) => StoreStamp::default(),
+        })
+        .collect()
+}
+
+/// What the view shows of each application, for telling whether a snapshot changed
+fn view_of(applications: &[DrsApplication]) -> Vec<(&str, &str, bool)> {
+    applications.iter()
+        .map(|a| (a.executable.as_str(), a.profile_name.as_str(), a.is_blacklisted))
+        .collect()
+}
+
+/// Persist the result of a full application enumeration
+pub fn record(applications: &[DrsApplication]) {
+    // A backup session's profiles say nothing about the live store
+    if is_read_only() {
+        return;
+    }
+
+    let snapshot = StoredSnapshot {
+        driver_version: driver_version().unwrap_or(0),
+        store_fingerprint: store_fingerprint(),
+        applications: applications.iter()
+            .map(|a| DrsApplication { provenance: None, ..a.clone() })
+            .collect(),
+        taken_at_ms: now_ms(),
+    };
+
+    let mut stored = STORED.lock().unwrap();
+    let unchanged = stored.as_ref().is_some_and(|s| {
+        s.driver_version == snapshot.driver_version
+            && s.store_fingerprint == snapshot.store_fingerprint
+            && view_of(&s.applications) == view_of(&snapshot.applications)
+    });
+    if unchanged {
+        return;
+    }
+    storage::save_or_journal(SNAPSHOT_FILE, &snapshot);
+    *stored = Some(snapshot);
+}
+
+/// The stored snapshot, if it was taken with this driver and these store files
+fn valid_stored() -> Option<StoredSnapshot> {
+    let stored = STORED.lock().unwrap().clone()?;
+    let matches = stored.driver_version == driver_version().unwrap_or(0)
+        && stored.store_fingerprint == store_fingerprint();
+    matches.then_some(stored)
+}
+
+fn live() -> Result<BlacklistSnapshot, NvApiError> {
+    let mut applications = get_all_applications()?;
+    record(&applications);
+    manifest::attach_provenance(&mut applications);
+    Ok(BlacklistSnapshot {
+        applications,
+        stale: false,
+        taken_at_ms: now_ms(),
+    })
+}
+
+/// Applications for the blacklist view. The first call of a run answers from the
+/// previous run's file when it still matches, and emits the live snapshot later.
+pub fn get_blacklist_snapshot(app: &AppHandle) -> Result<BlacklistSnapshot, String> {
+    if SERVED_STALE.swap(true, Ordering::SeqCst) {
+        return live().map_err(|e| e.to_string());
+    }
+    let Some(stored) = valid_stored() else {
+        return live().map_err(|e| e.to_string());
+    };
+
+    let handle = app.clone();
+    let spawned = app.state::<TaskSupervisor>().spawn("blacklist-snapshot", move |_token| {
+        match live() {
+            Ok(snapshot) => {
+                let _ = handle.emit(BLACKLIST_SNAPSHOT_EVENT, snapshot);
+            }
+            Err(e) => journal::append(JournalCategory::Task, JournalSeverity::Warning, "snapshot.refresh_failed", serde_json::json!({
+                "error": e.to_string(),
+            })),
+        }
+    });
+    // Without the refresh nothing would ever correct a stale answer
+    if spawned.is_none() {
+        return live().map_err(|e| e.to_string());
+    }
+
+    let mut applications = stored.applications;
+    manifest::attach_provenance(&mut applications);
+    Ok(BlacklistSnapshot {
+        applications,
+        stale: true,
+        taken_at_ms: stored.taken_at_ms,
+    })
+}
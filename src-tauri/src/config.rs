@@ -0,0 +1,189 @@
+//! Persistent app configuration
+//!
+//! Loaded once at startup and written through the storage helper on every change.
+
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::nvapi::ffi::NVAPI_UNICODE_STRING_MAX;
+use crate::nvapi::naming::DEFAULT_PROFILE_NAME_TEMPLATE;
+use crate::storage::{self, StorageError};
+
+const CONFIG_FILE: &str = "config.json";
+
+/// User-facing app configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppConfig {
+    pub automation_paused: bool,
+    pub first_run_completed: bool,
+    pub rule_limits: RuleLimits,
+    pub cache_limits: CacheLimits,
+    pub profile_naming: ProfileNaming,
+    pub bulk_limits: BulkLimits,
+    pub command_limits: CommandLimits,
+    pub revert_loop_detection: RevertLoopDetection,
+    /// Replace window titles with process names everywhere they would leave the backend
+    pub privacy_mode: bool,
+    /// Global hotkey that toggles the panic button; None leaves it unbound
+    pub panic_hotkey: Option<PanicHotkey>,
+}
+
+/// A key with modifiers, as virtual key codes
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PanicHotkey {
+    pub virtual_key: u32,
+    /// Alt, Ctrl, Shift or Windows keys; at least one is required
+    pub modifiers: Vec<u32>,
+}
+
+/// When another tool undoing our writes counts as a revert loop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RevertLoopDetection {
+    /// A flip back later than this after our write is treated as deliberate
+    pub window_minutes: u32,
+    /// Quick reverts within a day that flag the executable
+    pub min_reverts: u32,
+    /// How often recent writes are compared against the driver store
+    pub check_interval_secs: u32,
+    /// When a blacklist keeps being undone, move the executable into a
+    /// dedicated override profile and blacklist it there
+    pub escalate_to_override_profile: bool,
+}
+
+impl Default for RevertLoopDetection {
+    fn default() -> Self {
+        Self { window_minutes: 5, min_reverts: 3, check_interval_secs: 30, escalate_to_override_profile: false }
+    }
+}
+
+/// Protective limits on what the frontend may ask of the command layer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CommandLimits {
+    /// Calls per second each mutating command sustains
+    pub mutations_per_second: u32,
+    /// Calls a mutating command may make in a burst before the rate applies
+    pub mutation_burst: u32,
+    /// Most items a list argument may hold
+    pub max_batch_items: u32,
+    /// Longest executable or profile name accepted, in characters
+    pub max_name_chars: u32,
+    /// Longest file path accepted, in characters
+    pub max_path_chars: u32,
+    /// Longest file content passed in directly instead of by path, in characters
+    pub max_content_chars: u32,
+}
+
+impl Default for CommandLimits {
+    fn default() -> Self {
+        Self {
+            mutations_per_second: 10,
+            mutation_burst: 50,
+            max_batch_items: 5000,
+            // Longer names don't fit an NvAPI_UnicodeString with its terminator
+            max_name_chars: (NVAPI_UNICODE_STRING_MAX - 1) as u32,
+            // DRS file paths are passed to NVAPI in the same string type
+            max_path_chars: (NVAPI_UNICODE_STRING_MAX - 1) as u32,
+            max_content_chars: 8 * 1024 * 1024,
+        }
+    }
+}
+
+/// Guard rails for settings applied to many profiles at once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BulkLimits {
+    /// Most profiles one bulk change may touch; larger matches are refused
+    pub max_profiles: u32,
+}
+
+impl Default for BulkLimits {
+    fn default() -> Self {
+        Self { max_profiles: 100 }
+    }
+}
+
+/// How Nvidiot names the profiles it creates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ProfileNaming {
+    /// Name template; `{exe}` is the executable's file name and `{name}` the same without extension
+    pub template: String,
+}
+
+impl Default for ProfileNaming {
+    fn default() -> Self {
+        Self { template: DEFAULT_PROFILE_NAME_TEMPLATE.to_string() }
+    }
+}
+
+/// Size caps for in-memory caches. Window titles are read on demand and never
+/// kept, so there is no title history to cap.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CacheLimits {
+    /// Executable metadata results kept before the least recently used are evicted
+    pub metadata_entries: u32,
+    /// Encoded icon bytes kept before the least recently used icons are evicted
+    pub icon_bytes: u64,
+}
+
+impl Default for CacheLimits {
+    fn default() -> Self {
+        Self { metadata_entries: 2000, icon_bytes: 8 * 1024 * 1024 }
+    }
+}
+
+/// Safety limits for profiles created automatically by rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RuleLimits {
+    /// Profile creations allowed per rule in a rolling hour
+    pub per_rule_per_hour: u32,
+    /// Profile creations allowed across all rules in a rolling hour
+    pub global_per_hour: u32,
+    /// Directories whose executables rules never act on. `%VAR%` is expanded.
+    pub denied_directories: Vec<String>,
+}
+
+impl Default for RuleLimits {
+    fn default() -> Self {
+        Self {
+            per_rule_per_hour: 5,
+            global_per_hour: 20,
+            denied_directories: vec![
+                "%SystemRoot%".to_string(),
+                "%ProgramFiles%\\Common Files".to_string(),
+                "%ProgramFiles(x86)%\\Common Files".to_string(),
+                "%TEMP%".to_string(),
+                "%SystemRoot%\\Temp".to_string(),
+            ],
+        }
+    }
+}
+
+static CONFIG: Lazy<Mutex<AppConfig>> = Lazy::new(|| Mutex::new(AppConfig::default()));
+
+/// Load the config from app data, keeping defaults if none was saved yet
+pub fn load() -> Result<(), StorageError> {
+    if let Some(config) = storage::load::<AppConfig>(CONFIG_FILE)? {
+        *CONFIG.lock().unwrap() = config;
+    }
+    Ok(())
+}
+
+/// Get a copy of the current config
+pub fn get() -> AppConfig {
+    CONFIG.lock().unwrap().clone()
+}
+
+/// Modify the config and persist it
+pub fn update<F: FnOnce(&mut AppConfig)>(f: F) -> Result<AppConfig, StorageError> {
+    let mut config = CONFIG.lock().unwrap();
+    f(&mut config);
+    storage::save(CONFIG_FILE, &*config)?;
+    Ok(config.clone())
+}
@@ -0,0 +1,254 @@
+//! Games with several executables
+//!
+//! Many games ship separate DX11, DX12 and Vulkan binaries. A registered game
+//! keeps all of them in one profile: registration creates or reuses the
+//! profile, attaches every executable, blacklists the profile once and saves
+//! once, then records the group in the manifest next to the per-executable
+//! entries. An executable already living in another profile is reported for
+//! that executable instead of failing the whole registration, unless none of
+//! them could be added, which would leave an empty profile.
+
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+use crate::manifest::{self, now_ms, ManagedEntry, ManagedGame};
+use crate::nvapi::NvApiError;
+use crate::nvapi::applications::{create_application, delete_application, find_application};
+use crate::nvapi::error::NVAPI_EXECUTABLE_ALREADY_IN_USE;
+use crate::nvapi::ffi::{NvDRSProfileHandle, SHADOWPLAY_DISABLED, SHADOWPLAY_SETTING_ID};
+use crate::nvapi::naming::profile_name_for;
+use crate::nvapi::profiles::{create_profile_if_not_exists, get_profile_info_by_handle};
+use crate::nvapi::resolve::executable_key;
+use crate::nvapi::session::{reload_settings, save_settings};
+use crate::nvapi::settings::set_dword_setting;
+use crate::nvapi::types::ModifiedBy;
+
+/// Held across a whole registration or change so two never interleave
+static GAMES_LOCK: Mutex<()> = Mutex::new(());
+
+/// Where one executable of a game stands
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AttachStatus {
+    /// Added to the game's profile by this operation
+    Attached,
+    /// Already in the game's profile
+    AlreadyAttached,
+    /// The driver has the executable in a different profile, which wins
+    InOtherProfile { profile_name: String },
+    /// Recorded for the game but no longer in DRS
+    Missing,
+    Failed { error: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutableStatus {
+    pub executable: String,
+    pub status: AttachStatus,
+}
+
+/// A registered game with the state of each of its executables
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagedGameStatus {
+    pub name: String,
+    pub profile_name: String,
+    pub created_at_ms: u64,
+    pub executables: Vec<ExecutableStatus>,
+}
+
+/// State of an executable relative to the game's profile, without changing anything
+fn current_status(profile_handle: NvDRSProfileHandle, executable: &str) -> AttachStatus {
+    match find_application(executable) {
+        Ok((handle, _)) if handle == profile_handle => AttachStatus::AlreadyAttached,
+        Ok((handle, _)) => AttachStatus::InOtherProfile {
+            profile_name: get_profile_info_by_handle(handle)
+                .map(|p| p.name)
+                .unwrap_or_else(|_| "(unknown)".to_string()),
+        },
+        Err(NvApiError::ApplicationNotFound(_)) => AttachStatus::Missing,
+        Err(e) => AttachStatus::Failed { error: e.to_string() },
+    }
+}
+
+/// Add an executable to the game's profile unless the driver already has it. Not saved.
+pub fn attach(profile_handle: NvDRSProfileHandle, executable: &str, game_name: &str) -> AttachStatus {
+    match current_status(profile_handle, executable) {
+        AttachStatus::Missing => match create_application(profile_handle, executable, game_name, None) {
+            Ok(_) => AttachStatus::Attached,
+            // Another profile claimed the name under a spelling the lookup missed
+            Err(NvApiError::ApplicationCreationFailed(NVAPI_EXECUTABLE_ALREADY_IN_USE)) => {
+                AttachStatus::InOtherProfile { profile_name: "(unknown)".to_string() }
+            }
+            Err(e) => AttachStatus::Failed { error: e.to_string() },
+        },
+        status => status,
+    }
+}
+
+fn in_profile(status: &AttachStatus) -> bool {
+    matches!(status, AttachStatus::Attached | AttachStatus::AlreadyAttached)
+}
+
+/// Drop a half-staged batch from the session, passing the error through
+fn discard(error: String) -> String {
+    let _ = reload_settings();
+    error
+}
+
+/// Save the batch, or drop it from the session if saving fails
+fn save_or_reload() -> Result<(), String> {
+    save_settings().map_err(|e| discard(e.to_string()))
+}
+
+/// Why an executable didn't end up in the game's profile
+fn refusal(status: &ExecutableStatus) -> String {
+    match &status.status {
+        AttachStatus::InOtherProfile { profile_name } => format!("{} is in profile '{}'", status.executable, profile_name),
+        AttachStatus::Failed { error } => format!("{}: {}", status.executable, error),
+        _ => format!("{} was not added", status.executable),
+    }
+}
+
+/// Record the game and an entry for each executable now in its profile
+fn record(game: ManagedGame, statuses: &[ExecutableStatus]) -> Result<(), String> {
+    let entries = statuses.iter()
+        .filter(|s| s.status == AttachStatus::Attached)
+        .map(|s| ManagedEntry::new(&s.executable, game.profile_name.clone(), false, ModifiedBy::Manual))
+        .collect();
+    manifest::record_all(entries).map_err(|e| e.to_string())?;
+    manifest::record_game(game).map_err(|e| e.to_string())
+}
+
+/// Register a game: put every executable in one blacklisted profile, saving once.
+/// Registering an existing name adds the new executables to it.
+pub fn register_game(name: &str, executables: &[String]) -> Result<ManagedGameStatus, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Game name is empty".to_string());
+    }
+    if executables.is_empty() {
+        return Err("At least one executable is required".to_string());
+    }
+    let _lock = GAMES_LOCK.lock().unwrap();
+
+    let existing = manifest::game(name);
+    let profile_name = existing.as_ref()
+        .map(|g| g.profile_name.clone())
+        .unwrap_or_else(|| profile_name_for(name));
+    let (profile_handle, _created) = create_profile_if_not_exists(&profile_name).map_err(|e| e.to_string())?;
+
+    let mut seen = std::collections::HashSet::new();
+    let statuses: Vec<ExecutableStatus> = executables.iter()
+        .map(|e| e.trim())
+        .filter(|e| !e.is_empty() && seen.insert(executable_key(e)))
+        .map(|executable| ExecutableStatus {
+            executable: executable.to_string(),
+            status: attach(profile_handle, executable, name),
+        })
+        .collect();
+
+    // A profile without any of the game's executables would blacklist nothing
+    let profile_empty = existing.as_ref().map_or(true, |g| g.executables.is_empty());
+    if profile_empty && !statuses.iter().any(|s| in_profile(&s.status)) {
+        let reasons: Vec<String> = statuses.iter().map(refusal).collect();
+        return Err(discard(format!("None of the executables could be added to the game's profile: {}", reasons.join("; "))));
+    }
+
+    set_dword_setting(profile_handle, SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED).map_err(|e| discard(e.to_string()))?;
+    save_or_reload()?;
+
+    let mut game = existing.unwrap_or_else(|| ManagedGame {
+        name: name.to_string(),
+        profile_name: profile_name.clone(),
+        executables: Vec::new(),
+        created_at_ms: now_ms(),
+    });
+    for status in statuses.iter().filter(|s| in_profile(&s.status)) {
+        let key = executable_key(&status.executable);
+        if !game.executables.iter().any(|e| executable_key(e) == key) {
+            game.executables.push(status.executable.clone());
+        }
+    }
+    let result = ManagedGameStatus {
+        name: game.name.clone(),
+        profile_name: game.profile_name.clone(),
+        created_at_ms: game.created_at_ms,
+        executables: statuses.clone(),
+    };
+    record(game, &statuses)?;
+    Ok(result)
+}
+
+/// A registered game with the current state of each executable
+pub fn get_managed_entry(name: &str) -> Result<ManagedGameStatus, String> {
+    let game = manifest::game(name).ok_or_else(|| format!("Game '{}' is not registered", name))?;
+    let profile_handle = crate::nvapi::profiles::find_profile_by_name(&game.profile_name).map_err(|e| e.to_string())?;
+
+    let executables = game.executables.iter()
+        .map(|executable| ExecutableStatus {
+            executable: executable.clone(),
+            status: current_status(profile_handle, executable),
+        })
+        .collect();
+    Ok(ManagedGameStatus {
+        name: game.name,
+        profile_name: game.profile_name,
+        created_at_ms: game.created_at_ms,
+        executables,
+    })
+}
+
+/// Add one executable to a registered game
+pub fn add_executable_to_entry(name: &str, executable: &str) -> Result<ExecutableStatus, String> {
+    let game = manifest::game(name).ok_or_else(|| format!("Game '{}' is not registered", name))?;
+    register_game(&game.name, &[executable.to_string()])?
+        .executables
+        .into_iter()
+        .next()
+        .ok_or_else(|| "Executable name is empty".to_string())
+}
+
+/// Take an executable out of a registered game and its profile. Returns
+/// whether it was part of the game. An executable the driver keeps in another
+/// profile is only dropped from the group.
+pub fn remove_executable_from_entry(name: &str, executable: &str) -> Result<bool, String> {
+    let _lock = GAMES_LOCK.lock().unwrap();
+    let mut game = manifest::game(name).ok_or_else(|| format!("Game '{}' is not registered", name))?;
+
+    let key = executable_key(executable);
+    let Some(position) = game.executables.iter().position(|e| executable_key(e) == key) else {
+        return Ok(false);
+    };
+    let recorded = game.executables.remove(position);
+
+    let profile_handle = crate::nvapi::profiles::find_profile_by_name(&game.profile_name).map_err(|e| e.to_string())?;
+    if current_status(profile_handle, &recorded) == AttachStatus::AlreadyAttached {
+        delete_application(profile_handle, &recorded).map_err(|e| e.to_string())?;
+        save_or_reload()?;
+    }
+
+    manifest::remove(&recorded).map_err(|e| e.to_string())?;
+    manifest::record_game(game).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+#[cfg(all(test, target_os = "windows"))]
+mod mock_tests {
+    use super::*;
+    use crate::nvapi::mock::{self, Profile, Store};
+    use crate::nvapi::session::is_dirty;
+
+    #[test]
+    fn a_game_none_of_whose_executables_can_be_added_is_refused() {
+        let _serial = mock::serial();
+        mock::install(Store::default().with_profile(Profile::new("Elsewhere").with_application("claimed.exe")));
+
+        let error = register_game("Unclaimable", &["claimed.exe".to_string()]).unwrap_err();
+        assert!(error.contains("claimed.exe is in profile 'Elsewhere'"), "{}", error);
+        assert!(!is_dirty());
+        assert_eq!(mock::saves(), 0);
+        assert!(mock::disk().profile(&profile_name_for("Unclaimable")).is_none());
+        assert!(manifest::game("Unclaimable").is_none());
+    }
+}
@@ -0,0 +1,127 @@
+//! Global panic hotkey
+//!
+//! The configured panic hotkey is bound with RegisterHotKey on a supervised
+//! thread. A hotkey registered without a window posts WM_HOTKEY to the thread
+//! that registered it, so the same thread polls its queue and rebinds when the
+//! config changes. Pressing the hotkey toggles the panic button.
+
+use crate::config::PanicHotkey;
+
+/// Modifier virtual key codes and their RegisterHotKey flags
+const MODIFIER_FLAGS: [(u32, u32); 5] = [
+    (0x12, 0x0001), // VK_MENU, MOD_ALT
+    (0x11, 0x0002), // VK_CONTROL, MOD_CONTROL
+    (0x10, 0x0004), // VK_SHIFT, MOD_SHIFT
+    (0x5B, 0x0008), // VK_LWIN, MOD_WIN
+    (0x5C, 0x0008), // VK_RWIN, MOD_WIN
+];
+
+/// RegisterHotKey flags for the hotkey's modifiers, checking the hotkey can be bound
+pub fn modifier_flags(hotkey: &PanicHotkey) -> Result<u32, String> {
+    if hotkey.virtual_key == 0 || hotkey.virtual_key > 0xFE {
+        return Err(format!("{:#x} is not a virtual key code", hotkey.virtual_key));
+    }
+    if MODIFIER_FLAGS.iter().any(|(vk, _)| *vk == hotkey.virtual_key) {
+        return Err("The hotkey's key can't be a modifier".to_string());
+    }
+    if hotkey.modifiers.is_empty() {
+        // A bare key would fire whenever it is typed in a game
+        return Err("The hotkey needs at least one modifier".to_string());
+    }
+    hotkey.modifiers.iter().try_fold(0, |flags, modifier| {
+        MODIFIER_FLAGS.iter()
+            .find(|(vk, _)| vk == modifier)
+            .map(|(_, flag)| flags | flag)
+            .ok_or_else(|| format!("{:#x} is not Alt, Ctrl, Shift or a Windows key", modifier))
+    })
+}
+
+#[cfg(target_os = "windows")]
+pub fn start_watcher(app: &tauri::AppHandle) {
+    use std::time::Duration;
+    use tauri::Manager;
+    use windows::Win32::UI::Input::KeyboardAndMouse::{RegisterHotKey, UnregisterHotKey, HOT_KEY_MODIFIERS, MOD_NOREPEAT};
+    use windows::Win32::UI::WindowsAndMessaging::{PeekMessageW, MSG, PM_REMOVE, WM_HOTKEY};
+    use crate::config;
+    use crate::journal::{self, JournalCategory, JournalSeverity};
+    use crate::panicbutton;
+    use crate::tasks::TaskSupervisor;
+
+    const HOTKEY_ID: i32 = 1;
+    const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+    let supervisor = app.state::<TaskSupervisor>();
+    let app = app.clone();
+    supervisor.spawn("panic-hotkey", move |token| {
+        // What the config asked for last, and whether the system accepted it
+        let mut wanted: Option<PanicHotkey> = None;
+        let mut registered = false;
+
+        while token.sleep(POLL_INTERVAL) {
+            let configured = config::get().panic_hotkey;
+            if configured != wanted {
+                if registered {
+                    unsafe { let _ = UnregisterHotKey(None, HOTKEY_ID); }
+                    registered = false;
+                }
+                if let Some(hotkey) = &configured {
+                    let bound = modifier_flags(hotkey).and_then(|flags| unsafe {
+                        RegisterHotKey(None, HOTKEY_ID, HOT_KEY_MODIFIERS(flags) | MOD_NOREPEAT, hotkey.virtual_key)
+                            .map_err(|e| e.message())
+                    });
+                    // Usually another program already owns the combination
+                    if let Err(reason) = &bound {
+                        journal::append(JournalCategory::Watcher, JournalSeverity::Warning, "capture.hotkey_unavailable", serde_json::json!({
+                            "virtualKey": hotkey.virtual_key,
+                            "modifiers": hotkey.modifiers,
+                            "reason": reason,
+                        }));
+                    }
+                    registered = bound.is_ok();
+                }
+                wanted = configured;
+            }
+
+            let mut msg = MSG::default();
+            while unsafe { PeekMessageW(&mut msg, None, WM_HOTKEY, WM_HOTKEY, PM_REMOVE) }.as_bool() {
+                if msg.wParam.0 == HOTKEY_ID as usize {
+                    // Failures are shown in the tray by the panic button
+                    let _ = panicbutton::toggle(&app);
+                }
+            }
+        }
+
+        if registered {
+            unsafe { let _ = UnregisterHotKey(None, HOTKEY_ID); }
+        }
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn start_watcher(_app: &tauri::AppHandle) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hotkey(virtual_key: u32, modifiers: &[u32]) -> PanicHotkey {
+        PanicHotkey { virtual_key, modifiers: modifiers.to_vec() }
+    }
+
+    #[test]
+    fn modifiers_combine_into_flags() {
+        // Ctrl+Shift+F12
+        assert_eq!(modifier_flags(&hotkey(0x7B, &[0x11, 0x10])), Ok(0x0006));
+        // Both Windows keys map to the same flag
+        assert_eq!(modifier_flags(&hotkey(0x50, &[0x5B, 0x5C, 0x12])), Ok(0x0009));
+    }
+
+    #[test]
+    fn unbindable_hotkeys_are_refused() {
+        assert!(modifier_flags(&hotkey(0x7B, &[])).is_err());
+        assert!(modifier_flags(&hotkey(0x7B, &[0x41])).is_err());
+        assert!(modifier_flags(&hotkey(0x12, &[0x11])).is_err());
+        assert!(modifier_flags(&hotkey(0, &[0x11])).is_err());
+        assert!(modifier_flags(&hotkey(0x100, &[0x11])).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+//! Session activity journal
+//!
+//! Subsystems append what they did (mutations, rule firings, watcher
+//! detections, verification results, recoveries) to one bounded in-memory
+//! ring, and the frontend follows it through a single event instead of a
+//! dozen. Every entry gets a sequence number that keeps increasing across
+//! wraparound, so `since` can tell a reader who fell behind that entries were
+//! dropped. Entries carry a template code and structured params rather than
+//! text, so the UI words and localizes them. Nothing is persisted; mutations
+//! are already kept in the manifest and the import history.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, RwLock};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Runtime};
+use crate::manifest::now_ms;
+use crate::privacy;
+
+/// Event emitted with each appended entry
+pub const JOURNAL_APPENDED_EVENT: &str = "journal-appended";
+
+/// Entries kept; older ones are dropped first
+pub const JOURNAL_CAPACITY: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JournalCategory {
+    Mutation,
+    Rule,
+    Watcher,
+    Verification,
+    Recovery,
+    Import,
+    Storage,
+    Task,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum JournalSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalEntry {
+    /// Starts at 1 and increases by one per entry for the session
+    pub seq: u64,
+    pub at_ms: u64,
+    pub category: JournalCategory,
+    pub severity: JournalSeverity,
+    /// Message template, e.g. "rule.triggered"
+    pub code: String,
+    pub params: serde_json::Value,
+}
+
+/// Entries after a sequence number
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JournalPage {
+    pub entries: Vec<JournalEntry>,
+    /// Sequence number of the newest entry, 0 before the first; pass it back as `since_seq`
+    pub latest_seq: u64,
+    /// Entries after `since_seq` were dropped from the ring before this read
+    pub truncated: bool,
+}
+
+/// Bounded ring of entries, oldest first
+#[derive(Debug)]
+pub struct Journal {
+    entries: VecDeque<JournalEntry>,
+    capacity: usize,
+    latest_seq: u64,
+}
+
+impl Journal {
+    pub fn new(capacity: usize) -> Self {
+        Self { entries: VecDeque::new(), capacity: capacity.max(1), latest_seq: 0 }
+    }
+
+    /// Add an entry, dropping the oldest when full
+    pub fn push(&mut self, at_ms: u64, category: JournalCategory, severity: JournalSeverity, code: &str, params: serde_json::Value) -> JournalEntry {
+        self.latest_seq += 1;
+        let entry = JournalEntry { seq: self.latest_seq, at_ms, category, severity, code: code.to_string(), params };
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry.clone());
+        entry
+    }
+
+    /// Entries with a sequence number above `since_seq`, or all of them
+    pub fn since(&self, since_seq: Option<u64>) -> JournalPage {
+        let after = since_seq.unwrap_or(0);
+        let oldest = self.entries.front().map_or(self.latest_seq + 1, |e| e.seq);
+        JournalPage {
+            entries: self.entries.iter().filter(|e| e.seq > after).cloned().collect(),
+            latest_seq: self.latest_seq,
+            // The next entry the reader expected is older than anything kept
+            truncated: after + 1 < oldest && after < self.latest_seq,
+        }
+    }
+}
+
+static JOURNAL: Lazy<Mutex<Journal>> = Lazy::new(|| Mutex::new(Journal::new(JOURNAL_CAPACITY)));
+
+type Announcer = Box<dyn Fn(&JournalEntry) + Send + Sync>;
+
+/// Emits through whichever app was initialized last, so tests can point it at a mock app
+static ANNOUNCER: RwLock<Option<Announcer>> = RwLock::new(None);
+
+/// Keep the handle entries are announced through. Called once from the Tauri setup hook.
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    let app = app.clone();
+    *ANNOUNCER.write().unwrap() = Some(Box::new(move |entry| {
+        let _ = app.emit(JOURNAL_APPENDED_EVENT, entry);
+    }));
+}
+
+/// Append an entry and announce it. Params are usually the payload of the
+/// subsystem's own event. Before `init` the entry is kept but not announced.
+pub fn append(category: JournalCategory, severity: JournalSeverity, code: &str, params: impl Serialize) {
+    let params = privacy::redact(&params);
+    let entry = JOURNAL.lock().unwrap().push(now_ms(), category, severity, code, params);
+    if let Some(announce) = ANNOUNCER.read().unwrap().as_ref() {
+        announce(&entry);
+    }
+}
+
+/// Error when `failed`, otherwise info
+pub fn severity_for(failed: bool) -> JournalSeverity {
+    if failed { JournalSeverity::Error } else { JournalSeverity::Info }
+}
+
+/// Entries after `since_seq`, or every entry still kept
+pub fn since(since_seq: Option<u64>) -> JournalPage {
+    JOURNAL.lock().unwrap().since(since_seq)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal_with(capacity: usize, entries: u64) -> Journal {
+        let mut journal = Journal::new(capacity);
+        for at_ms in 0..entries {
+            journal.push(at_ms, JournalCategory::Mutation, JournalSeverity::Info, "test.entry", serde_json::Value::Null);
+        }
+        journal
+    }
+
+    fn seqs(page: &JournalPage) -> Vec<u64> {
+        page.entries.iter().map(|e| e.seq).collect()
+    }
+
+    #[test]
+    fn an_empty_journal_reads_as_empty_and_complete() {
+        let page = Journal::new(4).since(None);
+        assert!(page.entries.is_empty());
+        assert_eq!(page.latest_seq, 0);
+        assert!(!page.truncated);
+    }
+
+    #[test]
+    fn readers_get_only_entries_after_their_sequence_number() {
+        let journal = journal_with(4, 3);
+        assert_eq!(seqs(&journal.since(None)), [1, 2, 3]);
+        assert_eq!(seqs(&journal.since(Some(2))), [3]);
+
+        let caught_up = journal.since(Some(3));
+        assert!(caught_up.entries.is_empty());
+        assert_eq!(caught_up.latest_seq, 3);
+        assert!(!caught_up.truncated);
+    }
+
+    #[test]
+    fn a_full_ring_drops_the_oldest_and_flags_readers_who_fell_behind() {
+        let journal = journal_with(3, 5);
+        assert_eq!(seqs(&journal.since(None)), [3, 4, 5]);
+        assert!(journal.since(Some(1)).truncated);
+        assert!(!journal.since(Some(2)).truncated);
+        assert!(!journal.since(Some(5)).truncated);
+    }
+
+    #[test]
+    fn a_zero_capacity_still_keeps_the_newest_entry() {
+        let journal = journal_with(0, 2);
+        assert_eq!(seqs(&journal.since(None)), [2]);
+    }
+
+    #[test]
+    fn severity_follows_failure() {
+        assert_eq!(severity_for(true), JournalSeverity::Error);
+        assert_eq!(severity_for(false), JournalSeverity::Info);
+    }
+}
@@ -3,108 +3,2162 @@
 //! A Tauri application for managing NVIDIA ShadowPlay application profiles.
 
 mod nvapi;
+mod storage;
+mod config;
+mod automation;
+mod manifest;
+mod rules;
+mod presets;
+mod transfer;
+mod metadata;
+mod audit;
+mod tasks;
+mod capture;
+mod reverts;
+mod status;
+mod chunked;
+mod maintenance;
+mod questions;
+mod bulk;
+mod wizard;
+mod games;
+mod limits;
+mod coldstart;
+mod journal;
+mod revertloop;
+mod npi;
+mod panicbutton;
+mod privacy;
+mod tray;
+mod hotkey;
 
 #[cfg(target_os = "windows")]
 mod windows;
 
+use std::collections::HashMap;
+use tauri::{Emitter, Manager, WindowEvent};
+use tauri::ipc::Channel;
+use chunked::ChunkMessage;
 use nvapi::{
-    types::{DrsProfile, DrsApplication, RunningProcess, FocusApplication, BlacklistResult, NvApiStatus},
-    profiles, applications, settings, session,
+    types::{DrsProfile, DrsApplication, DrsSetting, ProfileSortField, ApplicationSortField, RunningProcess, ProcessAccessibility, FocusApplication, FocusApplicationExtended, MonitorInfo, ProcessBlacklistStatus, BlacklistResult, BlacklistQuery, PowerMode, PowerModeStatus, NvApiStatus, Diagnostics, CacheStats, AvailableSetting, Freshness, ResolutionTrace, FirstRunScan, BackupDiff, BackupRestoreEntry, ProfileComparison, UnsavedChanges, DrsStoreInfo, ScopeExplanation, LegacyMigration, ProfileDeletion, WindowsGamingState, StartupProgram, InstalledProgram, AddedExecutable, ValidationReport, FilterType, ManagedSortField, ModifiedBy, ApplicationCreated},
+    profiles, applications, settings, session, ffi, export, resolve, backup, compare, scope, reconcile, partition,
 };
 
-/// Get all DRS profiles
+/// Get all DRS profiles, optionally with their ShadowPlay status.
+/// With a channel the profiles are streamed in chunks instead.
 #[tauri::command]
-async fn get_profiles() -> Result<Vec<DrsProfile>, String> {
-    profiles::enumerate_profiles().map_err(|e| e.to_string())
+async fn get_profiles(
+    include_shadowplay: Option<bool>,
+    channel: Option<Channel<ChunkMessage<DrsProfile>>>,
+) -> Result<Vec<DrsProfile>, String> {
+    let include_shadowplay = include_shadowplay.unwrap_or(false);
+    let profiles = profiles::enumerate_profiles_with_status(include_shadowplay)
+        .map_err(|e| e.to_string())?;
+    // Statuses come from the per-generation cache; the profile list itself is live
+    let freshness = session::freshness(include_shadowplay.then(session::generation_started_at_ms));
+    chunked::respond(profiles, channel, freshness)
 }
 
-/// Get all applications across all profiles
+/// Get up to five user profiles with names closest to the query, best first
 #[tauri::command]
-async fn get_all_applications() -> Result<Vec<DrsApplication>, String> {
-    applications::get_all_applications().map_err(|e| e.to_string())
+async fn find_profile_fuzzy(query: String) -> Result<Vec<DrsProfile>, String> {
+    limits::check_name("query", &query)?;
+    Ok(profiles::find_profile_fuzzy(&query)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(profile, _)| profile)
+        .collect())
 }
 
-/// Get all running processes with visible windows
+/// Get profiles sorted by the given field
 #[tauri::command]
-async fn get_running_processes() -> Result<Vec<RunningProcess>, String> {
+async fn get_profiles_sorted(
+    sort_by: ProfileSortField,
+    ascending: bool,
+    include_predefined: bool,
+) -> Result<Vec<DrsProfile>, String> {
+    profiles::get_profiles_sorted(sort_by, ascending, include_predefined)
+        .map_err(|e| e.to_string())
+}
+
+/// Get a profile's applications sorted by the given field
+#[tauri::command]
+async fn list_profile_applications_sorted(
+    profile_name: String,
+    sort_by: ApplicationSortField,
+    ascending: bool,
+) -> Result<Vec<DrsApplication>, String> {
+    limits::check_name("profileName", &profile_name)?;
+    applications::list_applications_sorted(&profile_name, sort_by, ascending)
+        .map_err(|e| e.to_string())
+}
+
+/// Get all applications across all profiles, streamed in chunks when a channel is passed
+#[tauri::command]
+async fn get_all_applications(
+    channel: Option<Channel<ChunkMessage<DrsApplication>>>,
+) -> Result<Vec<DrsApplication>, String> {
+    let mut apps = applications::get_all_applications().map_err(|e| e.to_string())?;
+    coldstart::record(&apps);
+    manifest::attach_provenance(&mut apps);
+    let freshness = session::freshness(Some(applications::applications_cache_built_at_ms()));
+    chunked::respond(apps, channel, freshness)
+}
+
+/// Applications for the blacklist view. Right after startup this may be the
+/// previous run's answer, marked stale, with a live one following as an event.
+#[tauri::command]
+async fn get_blacklist_snapshot(app: tauri::AppHandle) -> Result<coldstart::BlacklistSnapshot, String> {
+    coldstart::get_blacklist_snapshot(&app)
+}
+
+/// Get all applications grouped by profile name
+#[tauri::command]
+async fn get_all_applications_grouped() -> Result<HashMap<String, Vec<DrsApplication>>, String> {
+    let mut grouped = applications::get_all_applications_grouped().map_err(|e| e.to_string())?;
+    for apps in grouped.values_mut() {
+        manifest::attach_provenance(apps);
+    }
+    Ok(grouped)
+}
+
+/// Get all applications; with include_settings, blacklist status is read per application
+#[tauri::command]
+async fn get_all_applications_detailed(
+    include_settings: bool,
+    channel: Option<Channel<ChunkMessage<DrsApplication>>>,
+) -> Result<Vec<DrsApplication>, String> {
+    let mut apps = applications::get_all_applications_detailed(include_settings).map_err(|e| e.to_string())?;
+    manifest::attach_provenance(&mut apps);
+    let freshness = session::freshness(Some(applications::applications_cache_built_at_ms()));
+    chunked::respond(apps, channel, freshness)
+}
+
+/// Get every setting the driver knows about, streamed in chunks when a channel is passed
+#[tauri::command]
+async fn get_available_settings(
+    channel: Option<Channel<ChunkMessage<AvailableSetting>>>,
+) -> Result<Vec<AvailableSetting>, String> {
+    let available = settings::available_settings().map_err(|e| e.to_string())?;
+    // Read from the driver on every call; nothing is cached
+    chunked::respond(available, channel, session::freshness(None))
+}
+
+/// Count applications registered across all profiles without loading them
+#[tauri::command]
+async fn get_total_application_count() -> Result<u32, String> {
+    applications::get_total_application_count().map_err(|e| e.to_string())
+}
+
+/// Rebuild the cached predefined applications with a full enumeration
+#[tauri::command]
+async fn refresh_predefined_cache() -> Result<usize, String> {
+    applications::refresh_predefined_cache().map_err(|e| e.to_string())
+}
+
+/// Get DRS details for a single executable
+#[tauri::command]
+async fn get_application_info(executable: String) -> Result<DrsApplication, String> {
+    limits::check_name("executable", &executable)?;
+    let mut app = applications::get_application_info(&executable).map_err(|e| e.to_string())?;
+    manifest::attach_provenance(std::slice::from_mut(&mut app));
+    app.match_mode = applications::get_application_match_mode(&app.executable);
+    Ok(app)
+}
+
+/// Look up DRS entries for many executables at once, every match per executable
+#[tauri::command]
+async fn join_executables(executables: Vec<String>) -> Result<HashMap<String, Vec<DrsApplication>>, String> {
+    limits::check_batch("executables", executables.len())?;
+    applications::join_executables(&executables).map_err(|e| e.to_string())
+}
+
+/// Change the launcher executable of an existing application
+#[tauri::command]
+async fn set_application_launcher(limiter: tauri::State<'_, limits::CommandLimiter>, executable: String, launcher: String) -> Result<(), String> {
+    limits::check_name("executable", &executable)?;
+    limits::check_name("launcher", &launcher)?;
+    limiter.check("set_application_launcher")?;
+    applications::set_application_launcher(&executable, &launcher).map_err(|e| e.to_string())
+}
+
+/// Check whether Nvidiot can query a process, e.g. to flag elevated games
+#[tauri::command]
+async fn check_process_accessible(process_id: u32) -> Result<ProcessAccessibility, String> {
+    #[cfg(target_os = "windows")]
+    {
+        Ok(windows::check_process_accessible(process_id))
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = process_id;
+        Err("Not supported on this platform".to_string())
+    }
+}
+
+/// Get all running processes with visible windows, optionally marking those using an NVIDIA GPU
+#[tauri::command]
+async fn get_running_processes(
+    include_memory: Option<bool>,
+    include_gpu_usage: Option<bool>,
+) -> Result<Vec<RunningProcess>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        Ok(windows::get_running_processes_with(
+            include_memory.unwrap_or(false),
+            include_gpu_usage.unwrap_or(false),
+        ))
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (include_memory, include_gpu_usage);
+        Ok(Vec::new())
+    }
+}
+
+/// Choose the base filter for the running process list
+#[tauri::command]
+async fn set_process_filter(limiter: tauri::State<'_, limits::CommandLimiter>, filter_type: FilterType) -> Result<(), String> {
+    limiter.check("set_process_filter")?;
+    #[cfg(target_os = "windows")]
+    {
+        windows::filter::set_process_filter(filter_type);
+        Ok(())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = filter_type;
+        Err("Not supported on this platform".to_string())
+    }
+}
+
+/// Hide processes with this executable name from the process list
+#[tauri::command]
+async fn add_process_name_filter(limiter: tauri::State<'_, limits::CommandLimiter>, name: String) -> Result<bool, String> {
+    limits::check_name("name", &name)?;
+    limiter.check("add_process_name_filter")?;
+    #[cfg(target_os = "windows")]
+    {
+        Ok(windows::filter::add_process_name_filter(&name))
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = name;
+        Err("Not supported on this platform".to_string())
+    }
+}
+
+/// Hide windows of this class from the process list
+#[tauri::command]
+async fn add_window_class_filter(limiter: tauri::State<'_, limits::CommandLimiter>, class: String) -> Result<bool, String> {
+    limits::check_name("class", &class)?;
+    limiter.check("add_window_class_filter")?;
+    #[cfg(target_os = "windows")]
+    {
+        Ok(windows::filter::add_window_class_filter(&class))
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = class;
+        Err("Not supported on this platform".to_string())
+    }
+}
+
+/// Search running processes by name and/or window title, best matches first
+#[tauri::command]
+async fn search_running_processes(query: String, search_title: bool, search_name: bool) -> Result<Vec<RunningProcess>, String> {
+    limits::check_name("query", &query)?;
+    #[cfg(target_os = "windows")]
+    {
+        Ok(windows::search_running_processes(&query, search_title, search_name))
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (query, search_title, search_name);
+        Ok(Vec::new())
+    }
+}
+
+/// Press and release a key in a process's window, holding optional modifier keys.
+/// ShadowPlay toggles recording with Alt+F9 by default.
+#[tauri::command]
+async fn inject_hotkey_to_process(limiter: tauri::State<'_, limits::CommandLimiter>, process_id: u32, virtual_key: u32, modifiers: Option<Vec<u32>>) -> Result<(), String> {
+    limiter.check("inject_hotkey_to_process")?;
+    #[cfg(target_os = "windows")]
+    {
+        match modifiers {
+            Some(modifiers) if !modifiers.is_empty() => {
+                windows::send_key_combo_to_pid(process_id, &modifiers, virtual_key)
+            }
+            _ => windows::send_keypress_to_pid(process_id, virtual_key),
+        }
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (process_id, virtual_key, modifiers);
+        Err("Not supported on this platform".to_string())
+    }
+}
+
+/// Send ShadowPlay's default recording toggle (Alt+F9) to a process
+#[tauri::command]
+async fn toggle_recording_in_process(limiter: tauri::State<'_, limits::CommandLimiter>, process_id: u32) -> Result<(), String> {
+    limiter.check("toggle_recording_in_process")?;
+    #[cfg(target_os = "windows")]
+    {
+        use windows::input::{VK_F9, VK_MENU};
+        windows::send_key_combo_to_pid(process_id, &[VK_MENU], VK_F9)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = process_id;
+        Err("Not supported on this platform".to_string())
+    }
+}
+
+/// Get cached version or PE metadata for executables without blocking. Missing or
+/// stale entries come back pending and arrive later via "metadata-ready".
+#[tauri::command]
+async fn get_executable_metadata(
+    app: tauri::AppHandle,
+    paths: Vec<String>,
+    kind: metadata::MetadataKind,
+) -> Result<Vec<metadata::MetadataLookup>, String> {
+    limits::check_batch("paths", paths.len())?;
+    Ok(metadata::lookup(&app, &paths, kind))
+}
+
+/// List programs that start with Windows
+#[tauri::command]
+async fn get_startup_programs() -> Result<Vec<StartupProgram>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        Ok(windows::list_startup_programs())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err("Not supported on this platform".to_string())
+    }
+}
+
+/// List installed programs with candidate executables. The install folders
+/// are walked on a blocking pool with "installed-scan-progress" events, then,
+/// unless `join` is false, joined with DRS in one pass to fill `in_drs`. With
+/// `not_in_drs`, executables already in DRS are dropped, along with programs
+/// left without any; that needs the join.
+#[tauri::command]
+async fn list_installed_programs(
+    app: tauri::AppHandle,
+    join: Option<bool>,
+    not_in_drs: Option<bool>,
+) -> Result<Vec<InstalledProgram>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        let join = join.unwrap_or(true);
+        let not_in_drs = not_in_drs.unwrap_or(false);
+        if not_in_drs && !join {
+            return Err("notInDrs needs the DRS join".to_string());
+        }
+
+        let mut programs = tauri::async_runtime::spawn_blocking(move || {
+            windows::installed::scan_installed(windows::installed::read_installed_programs(), join, |scanned, total| {
+                let _ = app.emit(windows::installed::INSTALLED_SCAN_PROGRESS_EVENT, nvapi::types::ScanProgress { scanned, total });
+            })
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+        if not_in_drs {
+            for program in &mut programs {
+                let known = program.in_drs.take().unwrap_or_default();
+                program.executables.retain(|exe| !known.contains(exe));
+                program.in_drs = Some(Vec::new());
+            }
+            programs.retain(|p| !p.executables.is_empty());
+        }
+        Ok(programs)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (app, join, not_in_drs);
+        Err("Not supported on this platform".to_string())
+    }
+}
+
+/// Blacklist the game behind a dropped executable, .lnk or Steam/Epic .url shortcut
+#[tauri::command]
+async fn add_executable_from_path(limiter: tauri::State<'_, limits::CommandLimiter>, path: String) -> Result<AddedExecutable, String> {
+    limiter.check("add_executable_from_path")?;
+    limits::check_path("path", &path)?;
+    #[cfg(target_os = "windows")]
+    {
+        let resolved = windows::shortcut::resolve_path(&path).map_err(|e| e.to_string())?;
+        limits::check_name("executable", &resolved.executable)?;
+        let result = settings::blacklist_application(&resolved.executable).map_err(|e| e.to_string())?;
+        if result.success {
+            manifest::record_executable_or_journal(&resolved.executable, false, ModifiedBy::Manual);
+        }
+        note_change(true, &result);
+        Ok(AddedExecutable { resolved, result })
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = path;
+        Err("Not supported on this platform".to_string())
+    }
+}
+
+/// Get the binary architecture a process runs as ("X86", "X64", "Arm64" or "Arm32")
+#[tauri::command]
+async fn get_process_architecture(process_id: u32) -> Result<Option<String>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        Ok(windows::get_process_architecture(process_id).map(|arch| format!("{:?}", arch)))
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = process_id;
+        Err("Not supported on this platform".to_string())
+    }
+}
+
+/// Get the blacklist status of every running process with a window
+#[tauri::command]
+async fn get_running_process_blacklist_status() -> Result<Vec<ProcessBlacklistStatus>, String> {
+    settings::get_running_process_blacklist_status().map_err(|e| e.to_string())
+}
+
+/// Get the currently focused application
+#[tauri::command]
+async fn get_focus_application() -> Result<Option<FocusApplication>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        Ok(windows::get_focus_application())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Ok(None)
+    }
+}
+
+/// Get the device name of the monitor showing the foreground window
+#[tauri::command]
+async fn get_focus_monitor() -> Result<Option<String>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        Ok(windows::get_foreground_window_monitor_name())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err("Not supported on this platform".to_string())
+    }
+}
+
+/// List connected monitors
+#[tauri::command]
+async fn list_monitors() -> Result<Vec<MonitorInfo>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        Ok(windows::list_monitors())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err("Not supported on this platform".to_string())
+    }
+}
+
+/// Get the focused application with CPU usage and GPU memory in use
+#[tauri::command]
+async fn get_focus_application_extended() -> Result<Option<FocusApplicationExtended>, String> {
+    #[cfg(target_os = "windows")]
+    {
+        Ok(windows::get_focus_application_extended())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Ok(None)
+    }
+}
+
+/// Create a new profile for an executable, confirming after save that the
+/// application attached unless `verify` is false
+#[tauri::command]
+async fn create_profile(
+    limiter: tauri::State<'_, limits::CommandLimiter>,
+    executable: String,
+    profile_name: String,
+    verify: Option<bool>,
+) -> Result<ApplicationCreated, String> {
+    limits::check_name("executable", &executable)?;
+    limits::check_name("profileName", &profile_name)?;
+    limiter.check("create_profile")?;
+    let result = applications::create_profile_for_executable(&executable, &profile_name, verify.unwrap_or(true))
+        .map_err(|e| e.to_string());
+    journaled("profile.created", serde_json::json!({ "profileName": profile_name, "executable": executable }), result)
+}
+
+/// Create a profile with initial DWORD settings given as [setting_id, value] pairs
+#[tauri::command]
+async fn create_profile_with_settings(
+    limiter: tauri::State<'_, limits::CommandLimiter>,
+    profile_name: String,
+    settings: Vec<[u32; 2]>,
+) -> Result<DrsProfile, String> {
+    limits::check_name("profileName", &profile_name)?;
+    limits::check_batch("settings", settings.len())?;
+    limiter.check("create_profile_with_settings")?;
+    let settings: Vec<(u32, u32)> = settings.into_iter().map(|[id, value]| (id, value)).collect();
+    let created = profiles::create_profile_with_settings(&profile_name, &settings).map_err(|e| e.to_string());
+    let profile_handle = journaled("profile.created", serde_json::json!({ "profileName": profile_name, "settings": settings }), created)?;
+    profiles::get_profile_info_by_handle(profile_handle).map_err(|e| e.to_string())
+}
+
+/// Get all profiles created by Nvidiot
+#[tauri::command]
+async fn get_nvidiot_created_profiles() -> Result<Vec<DrsProfile>, String> {
+    profiles::get_nvidiot_profiles().map_err(|e| e.to_string())
+}
+
+/// Estimate a profile's size in the DRS database
+#[tauri::command]
+async fn estimate_profile_size(profile_name: String) -> Result<u64, String> {
+    limits::check_name("profileName", &profile_name)?;
+    profiles::estimate_profile_size(&profile_name).map_err(|e| e.to_string())
+}
+
+/// Run integrity checks on every user profile in the DRS database
+#[tauri::command]
+async fn validate_drs_database() -> Result<ValidationReport, String> {
+    profiles::validate_all_profiles().map_err(|e| e.to_string())
+}
+
+/// Delete every profile created by Nvidiot, listing any that couldn't be deleted
+#[tauri::command]
+async fn delete_all_nvidiot_profiles(limiter: tauri::State<'_, limits::CommandLimiter>) -> Result<ProfileDeletion, String> {
+    limiter.check("delete_all_nvidiot_profiles")?;
+    profiles::delete_nvidiot_profiles().map_err(|e| e.to_string())
+}
+
+/// Delete every user-created profile, listing any that couldn't be deleted; refuses unless confirm is true
+#[tauri::command]
+async fn factory_reset_drs_profiles(limiter: tauri::State<'_, limits::CommandLimiter>, confirm: bool) -> Result<ProfileDeletion, String> {
+    limiter.check("factory_reset_drs_profiles")?;
+    profiles::delete_all_user_profiles(confirm).map_err(|e| e.to_string())
+}
+
+/// Merge profiles created by older versions into the current "Nvidiot - {exe}" naming
+#[tauri::command]
+async fn migrate_legacy_profiles(limiter: tauri::State<'_, limits::CommandLimiter>) -> Result<LegacyMigration, String> {
+    limiter.check("migrate_legacy_profiles")?;
+    reconcile::migrate_legacy_profiles().map_err(|e| e.to_string())
+}
+
+/// Ensure a profile exists, returning true if it was created
+#[tauri::command]
+async fn ensure_profile_exists(limiter: tauri::State<'_, limits::CommandLimiter>, profile_name: String) -> Result<bool, String> {
+    limits::check_name("profileName", &profile_name)?;
+    limiter.check("ensure_profile_exists")?;
+    let (_handle, created) = profiles::create_profile_if_not_exists(&profile_name)
+        .map_err(|e| e.to_string())?;
+    if created {
+        let saved = session::save_settings().map_err(|e| e.to_string());
+        journaled("profile.created", serde_json::json!({ "profileName": profile_name }), saved)?;
+    }
+    Ok(created)
+}
+
+/// Explain how an executable resolves to DRS entries and running processes
+#[tauri::command]
+async fn resolve_application(executable: String) -> Result<ResolutionTrace, String> {
+    limits::check_name("executable", &executable)?;
+    #[allow(unused_mut)]
+    let mut trace = resolve::resolve_application(&executable);
+
+    #[cfg(target_os = "windows")]
+    {
+        let name = resolve::basename(&executable);
+        trace.running_process_ids = windows::get_running_processes()
+            .into_iter()
+            .filter(|p| {
+                p.process_name.eq_ignore_ascii_case(name)
+                    || p.executable_path.as_deref().is_some_and(|path| path.eq_ignore_ascii_case(&executable))
+            })
+            .map(|p| p.process_id)
+            .collect();
+    }
+
+    Ok(trace)
+}
+
+/// Blacklist an application (disable ShadowPlay for it)
+#[tauri::command]
+async fn blacklist_application(limiter: tauri::State<'_, limits::CommandLimiter>, executable: String) -> Result<BlacklistResult, String> {
+    limits::check_name("executable", &executable)?;
+    limiter.check("blacklist_application")?;
+    let result = settings::blacklist_application(&executable).map_err(|e| e.to_string())?;
+    if result.success {
+        manifest::record_executable_or_journal(&executable, false, ModifiedBy::Manual);
+    }
+    note_change(true, &result);
+    Ok(result)
+}
+
+/// Unblacklist an application (enable ShadowPlay for it)
+#[tauri::command]
+async fn unblacklist_application(limiter: tauri::State<'_, limits::CommandLimiter>, executable: String) -> Result<BlacklistResult, String> {
+    limits::check_name("executable", &executable)?;
+    limiter.check("unblacklist_application")?;
+    let result = settings::unblacklist_application(&executable).map_err(|e| e.to_string())?;
+    if result.success {
+        manifest::touch(&executable, ModifiedBy::Manual);
+    }
+    note_change(false, &result);
+    Ok(result)
+}
+
+/// Blacklist an application if ShadowPlay is enabled for it, otherwise unblacklist it
+#[tauri::command]
+async fn toggle_shadowplay_for_application(limiter: tauri::State<'_, limits::CommandLimiter>, executable: String) -> Result<BlacklistResult, String> {
+    limits::check_name("executable", &executable)?;
+    limiter.check("toggle_shadowplay_for_application")?;
+    let result = settings::toggle_shadowplay(&executable).map_err(|e| e.to_string())?;
+    record_toggle(&executable, &result);
+    Ok(result)
+}
+
+/// Toggle ShadowPlay for the executable of a running process
+#[tauri::command]
+async fn toggle_shadowplay_for_process(limiter: tauri::State<'_, limits::CommandLimiter>, process_id: u32) -> Result<BlacklistResult, String> {
+    limiter.check("toggle_shadowplay_for_process")?;
+    #[cfg(target_os = "windows")]
+    {
+        let (executable, _path) = windows::processes::get_process_info(process_id)
+            .ok_or_else(|| format!("Process {} not found or not accessible", process_id))?;
+        let result = settings::toggle_shadowplay(&executable).map_err(|e| e.to_string())?;
+        record_toggle(&executable, &result);
+        Ok(result)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = process_id;
+        Err("Not supported on this platform".to_string())
+    }
+}
+
+/// Manifest bookkeeping for a toggle, matching the blacklist and unblacklist commands
+fn record_toggle(executable: &str, result: &BlacklistResult) {
+    note_change(result.previous_status == Some(false), result);
+    if !result.success {
+        return;
+    }
+    if result.previous_status == Some(false) {
+        manifest::record_executable_or_journal(executable, false, ModifiedBy::Manual);
+    } else {
+        manifest::touch(executable, ModifiedBy::Manual);
+    }
+}
+
+/// Journal a mutating command under `code`: its arguments, and the error if it failed
+fn journaled<T>(code: &str, mut params: serde_json::Value, result: Result<T, String>) -> Result<T, String> {
+    if let Err(e) = &result {
+        params["error"] = serde_json::json!(e);
+    }
+    journal::append(journal::JournalCategory::Mutation, journal::severity_for(result.is_err()), code, params);
+    result
+}
+
+/// Journal a blacklist change made from the UI
+fn note_change(disabled: bool, result: &BlacklistResult) {
+    let code = if disabled { "application.blacklisted" } else { "application.unblacklisted" };
+    journal::append(journal::JournalCategory::Mutation, journal::severity_for(!result.success), code, result);
+}
+
+/// Whether one executable is blacklisted, answered from cache when possible
+#[tauri::command]
+async fn is_blacklisted(executable: String) -> Result<BlacklistQuery, String> {
+    limits::check_name("executable", &executable)?;
+    settings::is_blacklisted(&executable).map_err(|e| e.to_string())
+}
+
+/// Power management mode the driver uses for an executable
+#[tauri::command]
+async fn get_power_mode(executable: String) -> Result<PowerModeStatus, String> {
+    limits::check_name("executable", &executable)?;
+    settings::get_power_mode(&executable).map_err(|e| e.to_string())
+}
+
+/// Set an executable's power management mode, creating its profile entry if needed
+#[tauri::command]
+async fn set_power_mode(limiter: tauri::State<'_, limits::CommandLimiter>, executable: String, mode: PowerMode) -> Result<PowerModeStatus, String> {
+    limits::check_name("executable", &executable)?;
+    limiter.check("set_power_mode")?;
+    let result = settings::set_power_mode(&executable, mode).map_err(|e| e.to_string());
+    journaled("powerMode.set", serde_json::json!({ "executable": executable, "mode": mode }), result)
+}
+
+/// Unblacklist an application and blacklist it again after the given minutes
+#[tauri::command]
+async fn temporarily_unblacklist(
+    app: tauri::AppHandle,
+    limiter: tauri::State<'_, limits::CommandLimiter>,
+    executable: String,
+    duration_minutes: u32,
+) -> Result<reverts::PendingRevert, String> {
+    limits::check_name("executable", &executable)?;
+    limiter.check("temporarily_unblacklist")?;
+    reverts::temporarily_unblacklist(&app, &executable, duration_minutes)
+}
+
+/// Get temporary unblacklists waiting to be reverted
+#[tauri::command]
+async fn get_pending_reverts() -> Vec<reverts::PendingRevert> {
+    reverts::get_pending_reverts()
+}
+
+/// Cancel a pending revert, keeping the application unblacklisted
+#[tauri::command]
+async fn cancel_revert(limiter: tauri::State<'_, limits::CommandLimiter>, executable: String) -> Result<bool, String> {
+    limits::check_name("executable", &executable)?;
+    limiter.check("cancel_revert")?;
+    reverts::cancel_revert(&executable).map_err(|e| e.to_string())
+}
+
+/// Apply a DWORD setting to all profiles. Predefined profiles are only
+/// touched when `include_predefined` is explicitly true.
+#[tauri::command]
+async fn apply_setting_to_all_profiles(
+    limiter: tauri::State<'_, limits::CommandLimiter>,
+    setting_id: u32,
+    value: u32,
+    include_predefined: Option<bool>,
+) -> Result<u32, String> {
+    limiter.check("apply_setting_to_all_profiles")?;
+    let result = if include_predefined.unwrap_or(false) {
+        settings::apply_to_all_profiles(setting_id, value, true)
+    } else {
+        settings::apply_to_all_user_profiles(setting_id, value)
+    };
+    result.map_err(|e| e.to_string())
+}
+
+/// Set a DWORD setting on every profile matching a filter. With `dry_run` only the
+/// planned changes are returned; otherwise the batch is saved once and can be undone.
+#[tauri::command]
+async fn bulk_set_setting(
+    limiter: tauri::State<'_, limits::CommandLimiter>,
+    filter: bulk::ProfileFilter,
+    setting_id: u32,
+    value: u32,
+    dry_run: bool,
+) -> Result<bulk::BulkSetResult, String> {
+    if dry_run {
+        return bulk::bulk_set_setting(&filter, setting_id, value, true);
+    }
+    limiter.check("bulk_set_setting")?;
+    let result = bulk::bulk_set_setting(&filter, setting_id, value, false);
+    let params = serde_json::json!({
+        "filter": filter,
+        "settingId": setting_id,
+        "value": value,
+        "operationId": result.as_ref().ok().and_then(|r| r.operation_id),
+    });
+    journaled("bulk.applied", params, result)
+}
+
+/// Restore the profiles changed by a bulk operation to their previous values
+#[tauri::command]
+async fn undo_bulk_setting(limiter: tauri::State<'_, limits::CommandLimiter>, operation_id: u64) -> Result<Vec<bulk::BulkProfileChange>, String> {
+    limiter.check("undo_bulk_setting")?;
+    journaled("bulk.undone", serde_json::json!({ "operationId": operation_id }), bulk::undo_bulk_setting(operation_id))
+}
+
+/// Get recorded bulk operations, oldest first
+#[tauri::command]
+async fn get_bulk_history() -> Vec<bulk::BulkOperation> {
+    bulk::history()
+}
+
+/// Get the guard rails applied to bulk setting changes
+#[tauri::command]
+async fn get_bulk_limits() -> config::BulkLimits {
+    config::get().bulk_limits
+}
+
+/// Replace the guard rails applied to bulk setting changes
+#[tauri::command]
+async fn set_bulk_limits(limiter: tauri::State<'_, limits::CommandLimiter>, limits: config::BulkLimits) -> Result<config::BulkLimits, String> {
+    limiter.check("set_bulk_limits")?;
+    config::update(|c| c.bulk_limits = limits)
+        .map(|c| c.bulk_limits)
+        .map_err(|e| e.to_string())
+}
+
+/// Get when reverts by another tool count as a loop
+#[tauri::command]
+async fn get_revert_loop_detection() -> config::RevertLoopDetection {
+    config::get().revert_loop_detection
+}
+
+/// Replace when reverts by another tool count as a loop
+#[tauri::command]
+async fn set_revert_loop_detection(limiter: tauri::State<'_, limits::CommandLimiter>, settings: config::RevertLoopDetection) -> Result<config::RevertLoopDetection, String> {
+    limiter.check("set_revert_loop_detection")?;
+    config::update(|c| c.revert_loop_detection = settings)
+        .map(|c| c.revert_loop_detection)
+        .map_err(|e| e.to_string())
+}
+
+/// Export profiles as a batch file that restores them with nvprofileupdate
+#[tauri::command]
+async fn export_profiles_as_bat(profile_names: Vec<String>, dest_path: String) -> Result<(), String> {
+    limits::check_batch("profileNames", profile_names.len())?;
+    for profile_name in &profile_names {
+        limits::check_name("profileNames", profile_name)?;
+    }
+    limits::check_path("destPath", &dest_path)?;
+    export::export_profiles_as_bat(&profile_names, &dest_path).map_err(|e| e.to_string())
+}
+
+/// Find applications already blacklisted by other tools. Returns None once first run is complete.
+#[tauri::command]
+async fn first_run_scan() -> Result<Option<FirstRunScan>, String> {
+    if config::get().first_run_completed {
+        return Ok(None);
+    }
+
+    let (managed, external): (Vec<_>, Vec<_>) = applications::get_all_applications()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|app| app.is_blacklisted)
+        .partition(|app| manifest::is_managed(&app.executable));
+
+    Ok(Some(FirstRunScan {
+        external,
+        managed_count: managed.len() as u32,
+    }))
+}
+
+/// Adopt externally blacklisted applications into the manifest without touching the driver.
+/// Completes first run; pass an empty list to dismiss onboarding.
+#[tauri::command]
+async fn adopt_external_entries(limiter: tauri::State<'_, limits::CommandLimiter>, executables: Vec<String>) -> Result<u32, String> {
+    limits::check_batch("executables", executables.len())?;
+    limiter.check("adopt_external_entries")?;
+    let mut entries = Vec::new();
+    for executable in &executables {
+        // Entries that no longer resolve (e.g. removed since the scan) are skipped
+        let Ok(app) = applications::get_application_info(executable) else {
+            continue;
+        };
+        entries.push(manifest::ManagedEntry::new(executable, app.profile_name, true, ModifiedBy::Manual));
+    }
+
+    let adopted = entries.len() as u32;
+    manifest::record_all(entries).map_err(|e| e.to_string())?;
+    config::update(|c| c.first_run_completed = true).map_err(|e| e.to_string())?;
+    Ok(adopted)
+}
+
+/// Get the executables Nvidiot manages, optionally sorted by creation or last change
+#[tauri::command]
+async fn get_managed_entries(sort_by: Option<ManagedSortField>, ascending: Option<bool>) -> Vec<manifest::ManagedEntry> {
+    manifest::sorted_entries(sort_by, ascending.unwrap_or(false))
+}
+
+/// Find every ShadowPlay or Ansel override and group it by who set it
+#[tauri::command]
+async fn audit_capture_settings() -> Result<audit::CaptureAudit, String> {
+    audit::audit_capture_settings()
+}
+
+/// Adopt an external capture override into the managed set
+#[tauri::command]
+async fn claim_override(limiter: tauri::State<'_, limits::CommandLimiter>, executable: String) -> Result<(), String> {
+    limits::check_name("executable", &executable)?;
+    limiter.check("claim_override")?;
+    audit::claim_override(&executable)
+}
+
+/// Drop an executable from the managed set, leaving its driver value in place
+#[tauri::command]
+async fn release_override(limiter: tauri::State<'_, limits::CommandLimiter>, executable: String) -> Result<bool, String> {
+    limits::check_name("executable", &executable)?;
+    limiter.check("release_override")?;
+    audit::release_override(&executable)
+}
+
+/// Get all automation rules
+#[tauri::command]
+async fn get_rules() -> Vec<rules::Rule> {
+    rules::get_rules()
+}
+
+/// Add an automation rule
+#[tauri::command]
+async fn add_rule(limiter: tauri::State<'_, limits::CommandLimiter>, rule: rules::NewRule) -> Result<rules::Rule, String> {
+    limiter.check("add_rule")?;
+    rules::add_rule(rule).map_err(|e| e.to_string())
+}
+
+/// Remove an automation rule, reverting its action if it is applied
+#[tauri::command]
+async fn remove_rule(limiter: tauri::State<'_, limits::CommandLimiter>, rule_id: u32) -> Result<bool, String> {
+    limiter.check("remove_rule")?;
+    rules::remove_rule(rule_id)
+}
+
+/// Enable or disable an automation rule
+#[tauri::command]
+async fn set_rule_enabled(limiter: tauri::State<'_, limits::CommandLimiter>, rule_id: u32, enabled: bool) -> Result<Option<rules::Rule>, String> {
+    limiter.check("set_rule_enabled")?;
+    rules::set_rule_enabled(rule_id, enabled).map_err(|e| e.to_string())
+}
+
+/// Get rules suspended for exceeding a creation quota
+#[tauri::command]
+async fn get_suspended_rules() -> Vec<rules::Rule> {
+    rules::get_suspended_rules()
+}
+
+/// Resume a suspended rule
+#[tauri::command]
+async fn resume_rule(limiter: tauri::State<'_, limits::CommandLimiter>, rule_id: u32) -> Result<Option<rules::Rule>, String> {
+    limiter.check("resume_rule")?;
+    rules::resume_rule(rule_id).map_err(|e| e.to_string())
+}
+
+/// Get the safety limits applied to rules
+#[tauri::command]
+async fn get_rule_limits() -> config::RuleLimits {
+    config::get().rule_limits
+}
+
+/// Replace the safety limits applied to rules
+#[tauri::command]
+async fn set_rule_limits(limiter: tauri::State<'_, limits::CommandLimiter>, limits: config::RuleLimits) -> Result<config::RuleLimits, String> {
+    limiter.check("set_rule_limits")?;
+    config::update(|c| c.rule_limits = limits)
+        .map(|c| c.rule_limits)
+        .map_err(|e| e.to_string())
+}
+
+/// Get the size caps applied to in-memory caches
+#[tauri::command]
+async fn get_cache_limits() -> config::CacheLimits {
+    config::get().cache_limits
+}
+
+/// Replace the size caps applied to in-memory caches
+#[tauri::command]
+async fn set_cache_limits(limiter: tauri::State<'_, limits::CommandLimiter>, limits: config::CacheLimits) -> Result<config::CacheLimits, String> {
+    limiter.check("set_cache_limits")?;
+    config::update(|c| c.cache_limits = limits)
+        .map(|c| c.cache_limits)
+        .map_err(|e| e.to_string())
+}
+
+/// Get the template used to name new profiles
+#[tauri::command]
+async fn get_profile_naming() -> config::ProfileNaming {
+    config::get().profile_naming
+}
+
+/// Replace the profile name template after checking its placeholders
+#[tauri::command]
+async fn set_profile_naming(limiter: tauri::State<'_, limits::CommandLimiter>, naming: config::ProfileNaming) -> Result<config::ProfileNaming, String> {
+    limiter.check("set_profile_naming")?;
+    nvapi::naming::validate_template(&naming.template)?;
+    config::update(|c| c.profile_naming = naming)
+        .map(|c| c.profile_naming)
+        .map_err(|e| e.to_string())
+}
+
+/// Rename every profile Nvidiot created to match the current template
+#[tauri::command]
+async fn rename_managed_profiles(limiter: tauri::State<'_, limits::CommandLimiter>) -> Result<manifest::ProfileRenameReport, String> {
+    limiter.check("rename_managed_profiles")?;
+    let result = manifest::rename_managed_profiles();
+    let renamed = result.as_ref().map(|r| r.renamed.clone()).unwrap_or_default();
+    journaled("profiles.renamed", serde_json::json!({ "renamed": renamed }), result)
+}
+
+/// Validate a rule preset (file path or JSON) and show what it would add; installs it when confirm is set
+#[tauri::command]
+async fn install_preset(limiter: tauri::State<'_, limits::CommandLimiter>, path_or_json: String, confirm: bool) -> Result<presets::PresetInstallPlan, String> {
+    if confirm {
+        limiter.check("install_preset")?;
+    }
+    presets::install_preset(&path_or_json, confirm)
+}
+
+/// Export the current rules as a shareable preset
+#[tauri::command]
+async fn export_rules_preset(metadata: presets::PresetMetadata) -> Result<String, String> {
+    presets::export_preset(metadata)
+}
+
+/// Export the blacklist state of managed executables to a file
+#[tauri::command]
+async fn export_blacklist(dest_path: String) -> Result<transfer::BlacklistFile, String> {
+    limits::check_path("destPath", &dest_path)?;
+    transfer::export_blacklist(&dest_path)
+}
+
+/// Import a blacklist file; returns conflicts and a token instead of applying when
+/// entries were changed outside Nvidiot since they were last written
+#[tauri::command]
+async fn import_blacklist(
+    app: tauri::AppHandle,
+    limiter: tauri::State<'_, limits::CommandLimiter>,
+    path: String,
+    mode: transfer::ImportMode,
+) -> Result<transfer::ImportOutcome, String> {
+    limiter.check("import_blacklist")?;
+    limits::check_path("path", &path)?;
+    let outcome = transfer::import_blacklist(&path, mode)?;
+    transfer::ask_about_conflicts(&app, &outcome);
+    transfer::announce_verification(&app, &outcome);
+    transfer::journal_outcome("import.applied", &outcome);
+    Ok(outcome)
+}
+
+/// Import blacklist content passed in directly instead of a file path
+#[tauri::command]
+async fn import_blacklist_from_json(
+    app: tauri::AppHandle,
+    limiter: tauri::State<'_, limits::CommandLimiter>,
+    content: String,
+    encoding: Option<transfer::ContentEncoding>,
+    mode: transfer::ImportMode,
+) -> Result<transfer::ImportOutcome, String> {
+    limiter.check("import_blacklist_from_json")?;
+    limits::check_content("content", &content)?;
+    let outcome = transfer::import_blacklist_from_str(&content, encoding.unwrap_or_default(), mode)?;
+    transfer::ask_about_conflicts(&app, &outcome);
+    transfer::announce_verification(&app, &outcome);
+    transfer::journal_outcome("import.applied", &outcome);
+    Ok(outcome)
+}
+
+/// Finish a pending import with a resolution per conflicting executable
+#[tauri::command]
+async fn resolve_import(
+    app: tauri::AppHandle,
+    limiter: tauri::State<'_, limits::CommandLimiter>,
+    token: u64,
+    resolutions: HashMap<String, transfer::ImportResolution>,
+) -> Result<transfer::ImportOutcome, String> {
+    limits::check_batch("resolutions", resolutions.len())?;
+    limiter.check("resolve_import")?;
+    let outcome = transfer::resolve_import(token, resolutions)?;
+    transfer::announce_verification(&app, &outcome);
+    transfer::journal_outcome("import.resolved", &outcome);
+    Ok(outcome)
+}
+
+/// Drop a pending import
+#[tauri::command]
+async fn cancel_import(token: u64) -> bool {
+    transfer::cancel_import(token)
+}
+
+/// Import profiles from an nvidiaProfileInspector full-configuration export with one save
+#[tauri::command]
+async fn import_npi_config(
+    app: tauri::AppHandle,
+    limiter: tauri::State<'_, limits::CommandLimiter>,
+    path: String,
+    options: Option<npi::NpiImportOptions>,
+) -> Result<npi::NpiImportReport, String> {
+    limiter.check("import_npi_config")?;
+    limits::check_path("path", &path)?;
+    npi::import_npi_config(&app, &path, &options.unwrap_or_default())
+}
+
+/// Disable ShadowPlay everywhere at once, remembering what to restore
+#[tauri::command]
+async fn panic_disable_capture(app: tauri::AppHandle, limiter: tauri::State<'_, limits::CommandLimiter>) -> Result<panicbutton::PanicState, String> {
+    limiter.check("panic_disable_capture")?;
+    panicbutton::panic_disable_capture(&app)
+}
+
+/// Put back the capture state from before the panic button
+#[tauri::command]
+async fn restore_capture_state(app: tauri::AppHandle, limiter: tauri::State<'_, limits::CommandLimiter>) -> Result<panicbutton::RestoreReport, String> {
+    limiter.check("restore_capture_state")?;
+    panicbutton::restore_capture_state(&app)
+}
+
+/// Whether capture is held off by the panic button, and what a restore would put back
+#[tauri::command]
+async fn get_panic_state() -> panicbutton::PanicState {
+    panicbutton::state()
+}
+
+/// Get the global hotkey that toggles the panic button, if one is bound
+#[tauri::command]
+async fn get_panic_hotkey() -> Option<config::PanicHotkey> {
+    config::get().panic_hotkey
+}
+
+/// Bind the panic button to a global hotkey, or unbind it with None
+#[tauri::command]
+async fn set_panic_hotkey(limiter: tauri::State<'_, limits::CommandLimiter>, hotkey: Option<config::PanicHotkey>) -> Result<Option<config::PanicHotkey>, String> {
+    limiter.check("set_panic_hotkey")?;
+    if let Some(hotkey) = &hotkey {
+        hotkey::modifier_flags(hotkey)?;
+    }
+    config::update(|c| c.panic_hotkey = hotkey)
+        .map(|c| c.panic_hotkey)
+        .map_err(|e| e.to_string())
+}
+
+/// Get questions from background features still waiting for an answer
+#[tauri::command]
+async fn get_pending_questions(
+    app: tauri::AppHandle,
+    queue: tauri::State<'_, questions::QuestionQueue>,
+) -> Result<Vec<questions::Question>, String> {
+    Ok(queue.pending(&app))
+}
+
+/// Answer a pending question; the response is passed to the subsystem that asked
+#[tauri::command]
+async fn answer_question(
+    app: tauri::AppHandle,
+    queue: tauri::State<'_, questions::QuestionQueue>,
+    limiter: tauri::State<'_, limits::CommandLimiter>,
+    id: questions::QuestionId,
+    response: serde_json::Value,
+) -> Result<(), String> {
+    limiter.check("answer_question")?;
+    queue.answer(&app, id, response)
+}
+
+/// Get recently answered or expired questions
+#[tauri::command]
+async fn get_question_history(
+    queue: tauri::State<'_, questions::QuestionQueue>,
+) -> Result<Vec<questions::QuestionHistoryEntry>, String> {
+    Ok(queue.history())
+}
+
+/// Pause or resume all automatic behavior
+#[tauri::command]
+async fn set_automation_paused(app: tauri::AppHandle, limiter: tauri::State<'_, limits::CommandLimiter>, paused: bool) -> Result<automation::AutomationState, String> {
+    limiter.check("set_automation_paused")?;
+    automation::set_paused(&app, paused).map_err(|e| e.to_string())
+}
+
+/// Get which automatic subsystems are active or paused
+#[tauri::command]
+async fn get_automation_state() -> automation::AutomationState {
+    automation::get_state()
+}
+
+fn windows_game_mode_enabled() -> bool {
     #[cfg(target_os = "windows")]
     {
-        Ok(windows::get_running_processes())
+        windows::is_windows_game_mode_enabled()
     }
     #[cfg(not(target_os = "windows"))]
     {
-        Ok(Vec::new())
+        false
     }
 }
 
-/// Get the currently focused application
+fn nvapi_status() -> NvApiStatus {
+    match session::check_nvapi() {
+        Ok(_) => NvApiStatus {
+            available: true,
+            error: None,
+            is_windows_game_mode_enabled: windows_game_mode_enabled(),
+        },
+        Err(e) => NvApiStatus {
+            available: false,
+            error: Some(e.to_string()),
+            is_windows_game_mode_enabled: windows_game_mode_enabled(),
+        },
+    }
+}
+
+/// Explain whether ShadowPlay will record an executable (name, path or pid) and why
 #[tauri::command]
-async fn get_focus_application() -> Result<Option<FocusApplication>, String> {
+async fn explain_capture(executable_or_pid: String) -> Result<capture::CaptureExplanation, String> {
+    limits::check_name("executableOrPid", &executable_or_pid)?;
+    Ok(capture::explain_capture(&executable_or_pid))
+}
+
+/// Diagnose overlay problems with a game and recommend fixes
+#[tauri::command]
+async fn diagnose_game(executable: String) -> Result<wizard::GameDiagnosis, String> {
+    limits::check_name("executable", &executable)?;
+    Ok(wizard::diagnose_game(&executable))
+}
+
+/// Apply the selected recommendations from diagnose_game as one batch
+#[tauri::command]
+async fn apply_recommendations(
+    app: tauri::AppHandle,
+    limiter: tauri::State<'_, limits::CommandLimiter>,
+    executable: String,
+    selected_actions: Vec<String>,
+) -> Result<Vec<wizard::ActionResult>, String> {
+    limits::check_name("executable", &executable)?;
+    limits::check_batch("selectedActions", selected_actions.len())?;
+    limiter.check("apply_recommendations")?;
+    let (results, verification) = wizard::apply_recommendations(&executable, &selected_actions)?;
+    if let Some(verification) = verification {
+        nvapi::verify::emit_summary(&app, &verification);
+    }
+    Ok(results)
+}
+
+/// Register a game's executables in one blacklisted profile, saving once
+#[tauri::command]
+async fn register_game(limiter: tauri::State<'_, limits::CommandLimiter>, name: String, executables: Vec<String>) -> Result<games::ManagedGameStatus, String> {
+    limits::check_name("name", &name)?;
+    limits::check_batch("executables", executables.len())?;
+    for executable in &executables {
+        limits::check_name("executables", executable)?;
+    }
+    limiter.check("register_game")?;
+    let result = games::register_game(&name, &executables);
+    journaled("game.registered", serde_json::json!({ "name": name, "executables": executables }), result)
+}
+
+/// A registered game and where each of its executables stands
+#[tauri::command]
+async fn get_managed_entry(name: String) -> Result<games::ManagedGameStatus, String> {
+    limits::check_name("name", &name)?;
+    games::get_managed_entry(&name)
+}
+
+/// Add an executable to a registered game
+#[tauri::command]
+async fn add_executable_to_entry(limiter: tauri::State<'_, limits::CommandLimiter>, name: String, executable: String) -> Result<games::ExecutableStatus, String> {
+    limits::check_name("name", &name)?;
+    limits::check_name("executable", &executable)?;
+    limiter.check("add_executable_to_entry")?;
+    games::add_executable_to_entry(&name, &executable)
+}
+
+/// Remove an executable from a registered game and its profile
+#[tauri::command]
+async fn remove_executable_from_entry(limiter: tauri::State<'_, limits::CommandLimiter>, name: String, executable: String) -> Result<bool, String> {
+    limits::check_name("name", &name)?;
+    limits::check_name("executable", &executable)?;
+    limiter.check("remove_executable_from_entry")?;
+    games::remove_executable_from_entry(&name, &executable)
+}
+
+/// Check NVAPI availability
+#[tauri::command]
+async fn check_nvapi_status() -> NvApiStatus {
+    nvapi_status()
+}
+
+/// Whether every DRS function NVAPI can provide was found, optional ones included
+#[tauri::command]
+async fn is_nvapi_drs_complete() -> Result<bool, String> {
     #[cfg(target_os = "windows")]
     {
-        Ok(windows::get_focus_application())
+        ffi::get_nvapi().map(|api| api.is_drs_api_complete()).map_err(|e| e.to_string())
     }
     #[cfg(not(target_os = "windows"))]
     {
-        Ok(None)
+        Err("Not supported on this platform".to_string())
     }
 }
 
-/// Create a new profile for an executable
+/// Whether the DRS functions needed to change settings were found; mutations are refused otherwise
 #[tauri::command]
-async fn create_profile(executable: String, profile_name: String) -> Result<(), String> {
-    applications::create_profile_for_executable(&executable, &profile_name)
-        .map_err(|e| e.to_string())
+async fn is_nvapi_minimal_ready() -> Result<bool, String> {
+    #[cfg(target_os = "windows")]
+    {
+        ffi::get_nvapi().map(|api| api.is_minimal_drs_api_available()).map_err(|e| e.to_string())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err("Not supported on this platform".to_string())
+    }
 }
 
-/// Blacklist an application (disable ShadowPlay for it)
+/// Get Windows Game Mode and Game DVR state, warning if Game DVR overlaps ShadowPlay
 #[tauri::command]
-async fn blacklist_application(executable: String) -> Result<BlacklistResult, String> {
-    settings::blacklist_application(&executable).map_err(|e| e.to_string())
+async fn get_windows_gaming_state() -> Result<WindowsGamingState, String> {
+    #[cfg(target_os = "windows")]
+    {
+        let game_dvr_enabled = windows::is_game_dvr_enabled();
+        // ShadowPlay is active globally unless the base profile disables it
+        let shadowplay_active = profiles::get_base_profile()
+            .and_then(settings::get_shadowplay_status)
+            .map(|disabled| !disabled)
+            .unwrap_or(false);
+
+        Ok(WindowsGamingState {
+            game_mode_enabled: windows::is_windows_game_mode_enabled(),
+            game_dvr_enabled,
+            warning: (game_dvr_enabled && shadowplay_active).then(|| {
+                "Game DVR and ShadowPlay are both enabled; they may conflict over capture and hotkeys".to_string()
+            }),
+        })
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err("Not supported on this platform".to_string())
+    }
 }
 
-/// Unblacklist an application (enable ShadowPlay for it)
+/// Compact, versioned health summary for external monitoring
 #[tauri::command]
-async fn unblacklist_application(executable: String) -> Result<BlacklistResult, String> {
-    settings::unblacklist_application(&executable).map_err(|e| e.to_string())
+async fn get_status_summary() -> status::StatusSummary {
+    status::get_status_summary()
 }
 
-/// Check NVAPI availability
+/// Generation and cache age the next application listing would be served with
 #[tauri::command]
-async fn check_nvapi_status() -> NvApiStatus {
-    match session::check_nvapi() {
-        Ok(_) => NvApiStatus {
-            available: true,
-            error: None,
-        },
-        Err(e) => NvApiStatus {
-            available: false,
-            error: Some(e.to_string()),
-        },
+async fn get_freshness() -> Freshness {
+    session::freshness(Some(applications::applications_cache_built_at_ms()))
+}
+
+/// Journal entries after `since_seq`, or every entry still kept
+#[tauri::command]
+async fn get_journal(since_seq: Option<u64>) -> journal::JournalPage {
+    journal::since(since_seq)
+}
+
+/// Collect diagnostics for support requests, without window titles in privacy mode
+#[tauri::command]
+async fn get_diagnostics(supervisor: tauri::State<'_, tasks::TaskSupervisor>) -> Result<serde_json::Value, String> {
+    Ok(privacy::redact(&diagnostics(&supervisor)))
+}
+
+/// Caches largest first, with their combined size
+fn rank_caches(mut caches: Vec<CacheStats>) -> (Vec<CacheStats>, u64) {
+    caches.sort_by(|a, b| b.approx_bytes.cmp(&a.approx_bytes));
+    let total = caches.iter().map(|c| c.approx_bytes).sum();
+    (caches, total)
+}
+
+fn diagnostics(supervisor: &tasks::TaskSupervisor) -> Diagnostics {
+    let (caches, total_cache_bytes) = rank_caches(vec![
+        settings::profile_status_cache_stats(),
+        settings::executable_status_cache_stats(),
+        applications::negative_lookup_cache_stats(),
+        metadata::cache_stats(),
+    ]);
+
+    Diagnostics {
+        nvapi: nvapi_status(),
+        probe: ffi::probe_functions(),
+        session_generation: session::generation(),
+        total_cache_bytes,
+        caches,
+        unclassified_statuses: nvapi::statuses::unclassified_statuses(),
+        background_tasks: supervisor.running(),
+    }
+}
+
+/// Drop every cache that can be recomputed
+#[tauri::command]
+async fn clear_caches() {
+    settings::clear_profile_status_cache();
+    settings::clear_executable_status_cache();
+    applications::clear_negative_lookup_cache();
+    metadata::clear_cache();
+}
+
+/// List driver-dependent features and whether this driver supports them, plus command limits
+#[tauri::command]
+async fn get_backend_capabilities() -> limits::BackendCapabilities {
+    let mut features = ffi::feature_availability();
+    features.push(privacy::window_titles_capability());
+    features.push(nvapi::applications::match_fields_capability());
+    limits::BackendCapabilities {
+        features,
+        limits: config::get().command_limits,
     }
 }
 
+/// Whether window titles are replaced with process names
+#[tauri::command]
+async fn get_privacy_mode() -> bool {
+    privacy::enabled()
+}
+
+/// Turn privacy mode on or off; title rules and title search are off while it is on
+#[tauri::command]
+async fn set_privacy_mode(limiter: tauri::State<'_, limits::CommandLimiter>, enabled: bool) -> Result<bool, String> {
+    limiter.check("set_privacy_mode")?;
+    config::update(|c| c.privacy_mode = enabled)
+        .map(|c| c.privacy_mode)
+        .map_err(|e| e.to_string())
+}
+
+/// Get the argument size and rate limits applied to commands
+#[tauri::command]
+async fn get_command_limits() -> config::CommandLimits {
+    config::get().command_limits
+}
+
+/// Replace the argument size and rate limits applied to commands
+#[tauri::command]
+async fn set_command_limits(limiter: tauri::State<'_, limits::CommandLimiter>, limits: config::CommandLimits) -> Result<config::CommandLimits, String> {
+    limiter.check("set_command_limits")?;
+    config::update(|c| c.command_limits = limits)
+        .map(|c| c.command_limits)
+        .map_err(|e| e.to_string())
+}
+
+/// Get the known DRS store files and which one the last save changed
+#[tauri::command]
+async fn get_drs_store_info() -> DrsStoreInfo {
+    scope::get_drs_store_info()
+}
+
+/// Explain where DRS changes are landing, with the paths involved
+#[tauri::command]
+async fn get_scope_explanation() -> ScopeExplanation {
+    scope::get_scope_explanation()
+}
+
 /// Reload DRS settings from disk
 #[tauri::command]
-async fn reload_settings() -> Result<(), String> {
+async fn reload_settings(limiter: tauri::State<'_, limits::CommandLimiter>) -> Result<(), String> {
+    limiter.check("reload_settings")?;
     session::reload_settings().map_err(|e| e.to_string())
 }
 
+/// Run every maintenance job now, regardless of idle state
+#[tauri::command]
+async fn run_maintenance_now(limiter: tauri::State<'_, limits::CommandLimiter>) -> Result<maintenance::MaintenanceReport, String> {
+    limiter.check("run_maintenance_now")?;
+    maintenance::run_now()
+}
+
+/// Get each maintenance job's last run and outcome
+#[tauri::command]
+async fn get_maintenance_report() -> maintenance::MaintenanceReport {
+    maintenance::report()
+}
+
+/// Load DRS settings from a file into the current session, replacing unsaved changes
+#[tauri::command]
+async fn load_drs_from_file(limiter: tauri::State<'_, limits::CommandLimiter>, path: String) -> Result<(), String> {
+    limiter.check("load_drs_from_file")?;
+    limits::check_path("path", &path)?;
+    session::load_settings_from_file(&path).map_err(|e| e.to_string())
+}
+
+/// Write the current DRS settings to a file without touching the driver store
+#[tauri::command]
+async fn save_drs_to_file(limiter: tauri::State<'_, limits::CommandLimiter>, path: String) -> Result<(), String> {
+    limiter.check("save_drs_to_file")?;
+    limits::check_path("path", &path)?;
+    session::save_settings_to_file(&path).map_err(|e| e.to_string())
+}
+
+/// Retry a save that failed, e.g. after closing another tool holding a DRS session
+#[tauri::command]
+async fn retry_save(limiter: tauri::State<'_, limits::CommandLimiter>) -> Result<(), String> {
+    limiter.check("retry_save")?;
+    session::save_settings().map_err(|e| e.to_string())
+}
+
+/// Save DRS settings and reload them to confirm the driver accepted them
+#[tauri::command]
+async fn flush_settings(limiter: tauri::State<'_, limits::CommandLimiter>) -> Result<(), String> {
+    limiter.check("flush_settings")?;
+    session::flush().map_err(|e| e.to_string())
+}
+
+/// Open a DRS backup file for read-only browsing, returning its session id
+#[tauri::command]
+async fn open_backup_readonly(path: String) -> Result<session::SessionId, String> {
+    limits::check_path("path", &path)?;
+    session::open_backup_readonly(&path).map_err(|e| e.to_string())
+}
+
+/// Close a backup session opened with open_backup_readonly
+#[tauri::command]
+async fn close_backup(session_id: session::SessionId) -> bool {
+    session::close_backup(session_id)
+}
+
+/// Get all profiles in a backup session
+#[tauri::command]
+async fn get_backup_profiles(session_id: session::SessionId, include_shadowplay: Option<bool>) -> Result<Vec<DrsProfile>, String> {
+    session::with_backup_session(session_id, || {
+        profiles::enumerate_profiles_with_status(include_shadowplay.unwrap_or(false))
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Get all applications in a backup session
+#[tauri::command]
+async fn get_backup_applications(session_id: session::SessionId) -> Result<Vec<DrsApplication>, String> {
+    session::with_backup_session(session_id, applications::get_all_applications)
+        .map_err(|e| e.to_string())
+}
+
+/// Get the settings stored in a profile of a backup session
+#[tauri::command]
+async fn get_backup_profile_settings(session_id: session::SessionId, profile_name: String) -> Result<Vec<DrsSetting>, String> {
+    limits::check_name("profileName", &profile_name)?;
+    session::with_backup_session(session_id, || {
+        let profile_handle = profiles::find_profile_by_name(&profile_name)?;
+        settings::get_all_settings(profile_handle)
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Compare the live DRS store against a backup file
+#[tauri::command]
+async fn diff_against_backup(path: String) -> Result<BackupDiff, String> {
+    limits::check_path("path", &path)?;
+    backup::diff_against_backup(&path).map_err(|e| e.to_string())
+}
+
+/// Compare two profiles' settings, applications and effective ShadowPlay value.
+/// An empty name is the base profile.
+#[tauri::command]
+async fn compare_profiles(profile_a: String, profile_b: String) -> Result<ProfileComparison, String> {
+    limits::check_name("profileA", &profile_a)?;
+    limits::check_name("profileB", &profile_b)?;
+    compare::compare_profiles(&profile_a, &profile_b).map_err(|e| e.to_string())
+}
+
+/// Restore selected differences from a backup in one batch
+#[tauri::command]
+async fn apply_backup_entries(limiter: tauri::State<'_, limits::CommandLimiter>, entries: Vec<BackupRestoreEntry>) -> Result<u32, String> {
+    limits::check_batch("entries", entries.len())?;
+    limiter.check("apply_backup_entries")?;
+    backup::apply_backup_entries(&entries).map_err(|e| e.to_string())
+}
+
+/// Emitted instead of closing the window while DRS changes are unsaved
+const UNSAVED_CHANGES_EVENT: &str = "unsaved-changes";
+
+/// Save pending DRS changes, then exit
+#[tauri::command]
+async fn save_and_exit(app: tauri::AppHandle) -> Result<(), String> {
+    session::save_settings().map_err(|e| e.to_string())?;
+    app.exit(0);
+    Ok(())
+}
+
+/// Drop pending DRS changes, then exit
+#[tauri::command]
+async fn discard_and_exit(app: tauri::AppHandle) {
+    session::clear_dirty();
+    app.exit(0);
+}
+
+/// Keep the app open after an unsaved-changes prompt, returning what is still pending
+#[tauri::command]
+async fn cancel_exit() -> Vec<String> {
+    session::pending_mutations()
+}
+
+/// What to report when the window is asked to close; None lets it close
+fn unsaved_changes_on_close() -> Option<UnsavedChanges> {
+    session::is_dirty().then(|| UnsavedChanges {
+        operations: session::pending_mutations(),
+    })
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        .manage(tasks::TaskSupervisor::default())
+        .manage(questions::QuestionQueue::default())
+        .manage(limits::CommandLimiter::default())
+        .setup(|app| {
+            storage::init(app.handle())?;
+            journal::init(app.handle());
+            config::load()?;
+            manifest::load()?;
+            rules::load()?;
+            transfer::load()?;
+            metadata::load()?;
+            partition::load()?;
+            reverts::load()?;
+            maintenance::load()?;
+            bulk::load()?;
+            coldstart::load()?;
+            panicbutton::load()?;
+            automation::init();
+            rules::start_watcher(app.handle());
+            reverts::start_scheduler(app.handle());
+            nvapi::health::start_watcher(app.handle());
+            maintenance::start_scheduler(app.handle());
+            revertloop::start_watcher(app.handle());
+            app.state::<questions::QuestionQueue>().register_handler(
+                transfer::IMPORT_CONFLICTS_QUESTION,
+                std::sync::Arc::new(transfer::ImportConflictsHandler),
+            );
+            questions::start_expiry_sweeper(app.handle());
+            tray::init(app.handle())?;
+            hotkey::start_watcher(app.handle());
+            Ok(())
+        })
+        .on_window_event(|window, event| {
+            if let WindowEvent::CloseRequested { api, .. } = event {
+                if let Some(unsaved) = unsaved_changes_on_close() {
+                    api.prevent_close();
+                    let _ = window.emit(UNSAVED_CHANGES_EVENT, unsaved);
+                }
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             get_profiles,
+            get_profiles_sorted,
+            find_profile_fuzzy,
             get_all_applications,
+            get_all_applications_grouped,
+            get_blacklist_snapshot,
+            list_profile_applications_sorted,
+            get_all_applications_detailed,
+            get_available_settings,
+            get_total_application_count,
+            refresh_predefined_cache,
+            get_application_info,
+            join_executables,
+            set_application_launcher,
             get_running_processes,
+            check_process_accessible,
+            search_running_processes,
+            set_process_filter,
+            add_process_name_filter,
+            add_window_class_filter,
+            get_running_process_blacklist_status,
+            get_process_architecture,
             get_focus_application,
+            get_focus_application_extended,
+            get_focus_monitor,
+            list_monitors,
+            get_startup_programs,
+            list_installed_programs,
+            add_executable_from_path,
+            get_executable_metadata,
+            inject_hotkey_to_process,
+            toggle_recording_in_process,
             create_profile,
+            create_profile_with_settings,
+            ensure_profile_exists,
+            get_nvidiot_created_profiles,
+            estimate_profile_size,
+            delete_all_nvidiot_profiles,
+            factory_reset_drs_profiles,
+            validate_drs_database,
+            migrate_legacy_profiles,
             blacklist_application,
             unblacklist_application,
+            toggle_shadowplay_for_application,
+            toggle_shadowplay_for_process,
+            is_blacklisted,
+            get_power_mode,
+            set_power_mode,
+            temporarily_unblacklist,
+            get_pending_reverts,
+            cancel_revert,
+            resolve_application,
+            explain_capture,
+            diagnose_game,
+            apply_recommendations,
+            register_game,
+            get_managed_entry,
+            add_executable_to_entry,
+            remove_executable_from_entry,
+            apply_setting_to_all_profiles,
+            bulk_set_setting,
+            undo_bulk_setting,
+            get_bulk_history,
+            get_bulk_limits,
+            set_bulk_limits,
+            get_revert_loop_detection,
+            set_revert_loop_detection,
+            export_profiles_as_bat,
             check_nvapi_status,
+            is_nvapi_drs_complete,
+            is_nvapi_minimal_ready,
+            get_windows_gaming_state,
+            get_diagnostics,
+            get_status_summary,
+            get_freshness,
+            get_journal,
+            clear_caches,
+            get_backend_capabilities,
+            get_privacy_mode,
+            set_privacy_mode,
+            get_command_limits,
+            set_command_limits,
+            get_drs_store_info,
+            get_scope_explanation,
             reload_settings,
+            retry_save,
+            run_maintenance_now,
+            get_maintenance_report,
+            load_drs_from_file,
+            save_drs_to_file,
+            flush_settings,
+            open_backup_readonly,
+            close_backup,
+            get_backup_profiles,
+            get_backup_applications,
+            get_backup_profile_settings,
+            diff_against_backup,
+            compare_profiles,
+            apply_backup_entries,
+            first_run_scan,
+            adopt_external_entries,
+            get_managed_entries,
+            audit_capture_settings,
+            claim_override,
+            release_override,
+            get_rules,
+            add_rule,
+            remove_rule,
+            set_rule_enabled,
+            get_suspended_rules,
+            resume_rule,
+            get_rule_limits,
+            set_rule_limits,
+            get_cache_limits,
+            set_cache_limits,
+            get_profile_naming,
+            set_profile_naming,
+            rename_managed_profiles,
+            install_preset,
+            export_rules_preset,
+            export_blacklist,
+            import_blacklist,
+            import_blacklist_from_json,
+            resolve_import,
+            cancel_import,
+            import_npi_config,
+            panic_disable_capture,
+            restore_capture_state,
+            get_panic_state,
+            get_panic_hotkey,
+            set_panic_hotkey,
+            get_pending_questions,
+            answer_question,
+            get_question_history,
+            set_automation_paused,
+            get_automation_state,
+            save_and_exit,
+            discard_and_exit,
+            cancel_exit,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                // Stop background tasks before the DRS session goes away
+                app.state::<tasks::TaskSupervisor>().shutdown(tasks::TASK_SHUTDOWN_TIMEOUT);
+                session::shutdown();
+            }
+        });
+}
+
+/// The command layer driven through Tauri's IPC against the mock DRS backend.
+/// Assertions are on the JSON the frontend receives and on the events the
+/// mock app emits.
+#[cfg(all(test, target_os = "windows"))]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use tauri::ipc::{CallbackFn, InvokeBody};
+    use tauri::test::{get_ipc_response, mock_builder, mock_context, noop_assets, MockRuntime, INVOKE_KEY};
+    use std::sync::{Arc, Mutex};
+    use tauri::webview::InvokeRequest;
+    use tauri::{App, Listener, WebviewWindow, WebviewWindowBuilder};
+    use crate::nvapi::ffi::{
+        POWER_MANAGEMENT_SETTING_ID, PREFERRED_PSTATE_PREFER_MAX, SHADOWPLAY_DISABLED, SHADOWPLAY_ENABLED,
+        SHADOWPLAY_SETTING_ID,
+    };
+    use crate::nvapi::mock::{self, Profile, Store};
+
+    struct Harness {
+        _app: App<MockRuntime>,
+        webview: WebviewWindow<MockRuntime>,
+        /// Payloads of every journal-appended event, in emission order
+        journal_events: Arc<Mutex<Vec<Value>>>,
+    }
+
+    impl Harness {
+        fn new(disk: Store) -> Self {
+            storage::init_at(std::env::temp_dir().join(format!("nvidiot-ipc-tests-{}", std::process::id())));
+            mock::install(disk);
+
+            let app = mock_builder()
+                .manage(limits::CommandLimiter::default())
+                .invoke_handler(tauri::generate_handler![
+                    blacklist_application,
+                    unblacklist_application,
+                    is_blacklisted,
+                    get_freshness,
+                    get_journal,
+                    get_managed_entries,
+                    bulk_set_setting,
+                    undo_bulk_setting,
+                    get_bulk_history,
+                    add_window_class_filter,
+                    add_executable_to_entry,
+                    export_profiles_as_bat,
+                    search_running_processes,
+                    export_blacklist,
+                    import_blacklist,
+                    open_backup_readonly,
+                    diff_against_backup,
+                ])
+                .build(mock_context(noop_assets()))
+                .expect("mock app");
+            let webview = WebviewWindowBuilder::new(&app, "main", Default::default())
+                .build()
+                .expect("mock webview");
+
+            journal::init(app.handle());
+            let journal_events = Arc::new(Mutex::new(Vec::new()));
+            let received = journal_events.clone();
+            app.listen(journal::JOURNAL_APPENDED_EVENT, move |event| {
+                received.lock().unwrap().push(serde_json::from_str(event.payload()).expect("JSON payload"));
+            });
+            Self { _app: app, webview, journal_events }
+        }
+
+        fn invoke(&self, cmd: &str, args: Value) -> Result<Value, Value> {
+            get_ipc_response(&self.webview, InvokeRequest {
+                cmd: cmd.into(),
+                callback: CallbackFn(0),
+                error: CallbackFn(1),
+                url: "http://tauri.localhost".parse().unwrap(),
+                body: InvokeBody::Json(args),
+                headers: Default::default(),
+                invoke_key: INVOKE_KEY.to_string(),
+            })
+            .map(|body| body.deserialize::<Value>().expect("JSON response"))
+        }
+
+        fn ok(&self, cmd: &str, args: Value) -> Value {
+            self.invoke(cmd, args).unwrap_or_else(|e| panic!("{} failed: {}", cmd, e))
+        }
+
+        fn generation(&self) -> u64 {
+            self.ok("get_freshness", json!({}))["sessionGeneration"].as_u64().unwrap()
+        }
+
+        fn latest_seq(&self) -> u64 {
+            self.ok("get_journal", json!({}))["latestSeq"].as_u64().unwrap()
+        }
+
+        /// Codes and executables of the application entries journalled after `since_seq`, in order
+        fn journal_since(&self, since_seq: u64) -> Vec<(String, Value)> {
+            application_entries(self.ok("get_journal", json!({ "sinceSeq": since_seq }))["entries"].as_array().unwrap())
+        }
+
+        /// Codes and executables of the application entries announced by event after `since_seq`, in order
+        fn events_since(&self, since_seq: u64) -> Vec<(String, Value)> {
+            let events = self.journal_events.lock().unwrap();
+            let newer: Vec<Value> = events.iter().filter(|e| e["seq"].as_u64().unwrap() > since_seq).cloned().collect();
+            application_entries(&newer)
+        }
+    }
+
+    fn application_entries(entries: &[Value]) -> Vec<(String, Value)> {
+        entries
+            .iter()
+            .filter(|e| e["code"].as_str().is_some_and(|c| c.starts_with("application.")))
+            .map(|e| (e["code"].as_str().unwrap().to_string(), e["params"]["executable"].clone()))
+            .collect()
+    }
+
+    #[test]
+    fn blacklist_and_unblacklist_round_trip() {
+        let _serial = mock::serial();
+        let h = Harness::new(Store::default());
+        let exe = "ipc-roundtrip.exe";
+        let profile_name = nvapi::naming::profile_name_for(exe);
+        let generation = h.generation();
+        let seq = h.latest_seq();
+
+        let result = h.ok("blacklist_application", json!({ "executable": exe }));
+        assert_eq!(result, json!({
+            "success": true,
+            "executable": exe,
+            "message": format!("Created profile '{}' and blacklisted application", profile_name),
+            "affectedProfileName": profile_name,
+            "scope": "unknown",
+        }));
+        assert!(h.generation() > generation);
+        assert_eq!(h.events_since(seq), vec![("application.blacklisted".to_string(), json!(exe))]);
+
+        let disk = mock::disk();
+        let profile = disk.profile(&profile_name).expect("profile saved");
+        assert_eq!(profile.dword(SHADOWPLAY_SETTING_ID), Some(SHADOWPLAY_DISABLED));
+        assert_eq!(profile.applications.len(), 1);
+        assert_eq!(mock::saves(), 1);
+
+        let query = h.ok("is_blacklisted", json!({ "executable": exe }));
+        assert_eq!(query["blacklisted"], json!(true));
+
+        let managed = h.ok("get_managed_entries", json!({}));
+        let entry = managed.as_array().unwrap().iter()
+            .find(|e| e["executable"] == json!(exe))
+            .expect("manifest entry");
+        assert_eq!(entry["profileName"], json!(profile_name));
+        assert_eq!(entry["lastModifiedBy"], json!({ "type": "manual" }));
+
+        let generation = h.generation();
+        let result = h.ok("unblacklist_application", json!({ "executable": exe }));
+        assert_eq!(result["success"], json!(true));
+        assert_eq!(result["message"], json!("Application unblacklisted successfully"));
+        assert!(h.generation() > generation);
+        assert_eq!(mock::disk().profile(&profile_name).unwrap().dword(SHADOWPLAY_SETTING_ID), Some(SHADOWPLAY_ENABLED));
+
+        let query = h.ok("is_blacklisted", json!({ "executable": exe }));
+        assert_eq!(query["blacklisted"], json!(false));
+
+        let expected = vec![
+            ("application.blacklisted".to_string(), json!(exe)),
+            ("application.unblacklisted".to_string(), json!(exe)),
+        ];
+        assert_eq!(h.events_since(seq), expected);
+        assert_eq!(h.journal_since(seq), expected);
+    }
+
+    #[test]
+    fn bulk_change_is_recorded_and_undone() {
+        let _serial = mock::serial();
+        let h = Harness::new(Store::default()
+            .with_profile(Profile::new("IPC Game A").with_application("ipc-a.exe").with_dword(POWER_MANAGEMENT_SETTING_ID, 0))
+            .with_profile(Profile::new("IPC Game B").with_application("ipc-b.exe")));
+        let filter = json!({ "nameContains": "ipc game" });
+        let seq = h.latest_seq();
+
+        let result = h.ok("bulk_set_setting", json!({
+            "filter": filter,
+            "settingId": POWER_MANAGEMENT_SETTING_ID,
+            "value": PREFERRED_PSTATE_PREFER_MAX,
+            "dryRun": false,
+        }));
+        let operation_id = result["operationId"].as_u64().expect("recorded for undo");
+        assert_eq!(result["changes"], json!([
+            { "profileName": "IPC Game A", "previousValue": 0, "error": null },
+            { "profileName": "IPC Game B", "previousValue": null, "error": null },
+        ]));
+        assert_eq!(mock::saves(), 1);
+        assert_eq!(mock::disk().profile("IPC Game B").unwrap().dword(POWER_MANAGEMENT_SETTING_ID), Some(PREFERRED_PSTATE_PREFER_MAX));
+
+        let history = h.ok("get_bulk_history", json!({}));
+        let recorded = history.as_array().unwrap().last().unwrap();
+        assert_eq!(recorded["id"], json!(operation_id));
+        assert_eq!(recorded["undone"], json!(false));
+
+        let undone = h.ok("undo_bulk_setting", json!({ "operationId": operation_id }));
+        assert!(undone.as_array().unwrap().iter().all(|c| c["error"].is_null()));
+        let disk = mock::disk();
+        assert_eq!(disk.profile("IPC Game A").unwrap().dword(POWER_MANAGEMENT_SETTING_ID), Some(0));
+        assert_eq!(disk.profile("IPC Game B").unwrap().dword(POWER_MANAGEMENT_SETTING_ID), None);
+
+        let history = h.ok("get_bulk_history", json!({}));
+        assert_eq!(history.as_array().unwrap().last().unwrap()["undone"], json!(true));
+
+        let journal = h.ok("get_journal", json!({ "sinceSeq": seq }));
+        let codes: Vec<&str> = journal["entries"].as_array().unwrap().iter()
+            .filter_map(|e| e["code"].as_str())
+            .filter(|c| c.starts_with("bulk."))
+            .collect();
+        assert_eq!(codes, ["bulk.applied", "bulk.undone"]);
+        assert_eq!(
+            h.invoke("undo_bulk_setting", json!({ "operationId": operation_id })),
+            Err(json!(format!("Bulk operation {} was already undone", operation_id))),
+        );
+    }
+
+    #[test]
+    fn dry_run_changes_nothing() {
+        let _serial = mock::serial();
+        let h = Harness::new(Store::default().with_profile(Profile::new("IPC Dry Run").with_application("ipc-dry.exe")));
+        let generation = h.generation();
+
+        let result = h.ok("bulk_set_setting", json!({
+            "filter": { "nameContains": "ipc dry" },
+            "settingId": POWER_MANAGEMENT_SETTING_ID,
+            "value": PREFERRED_PSTATE_PREFER_MAX,
+            "dryRun": true,
+        }));
+        assert_eq!(result["operationId"], Value::Null);
+        assert_eq!(result["dryRun"], json!(true));
+        assert_eq!(result["changes"].as_array().unwrap().len(), 1);
+        assert_eq!(mock::saves(), 0);
+        assert_eq!(h.generation(), generation);
+    }
+
+    #[test]
+    fn bulk_change_refuses_to_sweep_in_unsaved_changes() {
+        let _serial = mock::serial();
+        let h = Harness::new(Store::default()
+            .with_profile(Profile::new("IPC Staged Elsewhere"))
+            .with_profile(Profile::new("IPC Bulk Target").with_application("ipc-target.exe")));
+        let staged = profiles::find_profile_by_name("IPC Staged Elsewhere").unwrap();
+        settings::set_dword_setting(staged, SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED).unwrap();
+
+        let error = h.invoke("bulk_set_setting", json!({
+            "filter": { "nameContains": "ipc bulk target" },
+            "settingId": POWER_MANAGEMENT_SETTING_ID,
+            "value": PREFERRED_PSTATE_PREFER_MAX,
+            "dryRun": false,
+        })).unwrap_err();
+        assert!(error.as_str().unwrap().contains("unsaved changes"), "{}", error);
+        assert_eq!(mock::saves(), 0);
+        assert!(session::is_dirty());
+    }
+
+    #[test]
+    fn failures_are_mapped_to_strings() {
+        let _serial = mock::serial();
+        let h = Harness::new(Store::default());
+
+        let too_long = "x".repeat(config::get().command_limits.max_name_chars as usize + 1);
+        let error = h.invoke("blacklist_application", json!({ "executable": too_long })).unwrap_err();
+        assert!(error.as_str().unwrap().starts_with("argument-too-long: executable"), "{}", error);
+
+        let error = h.invoke("blacklist_application", json!({})).unwrap_err();
+        assert!(error.as_str().unwrap().contains("executable"), "{}", error);
+
+        // Nothing to unblacklist is a result, not an error
+        let result = h.ok("unblacklist_application", json!({ "executable": "ipc-unknown.exe" }));
+        assert_eq!(result, json!({
+            "success": false,
+            "executable": "ipc-unknown.exe",
+            "message": "Application not found in driver settings",
+            "affectedProfileName": null,
+            "scope": "unknown",
+        }));
+        assert_eq!(h.ok("is_blacklisted", json!({ "executable": "ipc-unknown.exe" }))["blacklisted"], Value::Null);
+        assert_eq!(mock::saves(), 0);
+    }
+
+    #[test]
+    fn every_name_argument_is_capped() {
+        let _serial = mock::serial();
+        let h = Harness::new(Store::default());
+        let too_long = "x".repeat(config::get().command_limits.max_name_chars as usize + 1);
+        let rejected = |cmd: &str, args: Value, argument: &str| {
+            let error = h.invoke(cmd, args).unwrap_err();
+            assert!(error.as_str().unwrap().starts_with(&format!("argument-too-long: {}", argument)), "{}: {}", cmd, error);
+        };
+
+        rejected("add_window_class_filter", json!({ "class": too_long }), "class");
+        rejected("add_executable_to_entry", json!({ "name": too_long, "executable": "ipc-game.exe" }), "name");
+        rejected(
+            "export_profiles_as_bat",
+            json!({ "profileNames": ["IPC Short", too_long], "destPath": "unused.bat" }),
+            "profileNames",
+        );
+        assert_eq!(mock::saves(), 0);
+    }
+
+    #[test]
+    fn every_query_and_path_argument_is_capped() {
+        let _serial = mock::serial();
+        let h = Harness::new(Store::default());
+        let limits = config::get().command_limits;
+        let long_query = "x".repeat(limits.max_name_chars as usize + 1);
+        let long_path = format!("C:\\{}", "x".repeat(limits.max_path_chars as usize));
+        let rejected = |cmd: &str, args: Value, argument: &str| {
+            let error = h.invoke(cmd, args).unwrap_err();
+            assert!(error.as_str().unwrap().starts_with(&format!("argument-too-long: {}", argument)), "{}: {}", cmd, error);
+        };
+
+        rejected("search_running_processes", json!({ "query": long_query, "searchTitle": true, "searchName": true }), "query");
+        rejected("export_blacklist", json!({ "destPath": long_path }), "destPath");
+        rejected("import_blacklist", json!({ "path": long_path, "mode": "Merge" }), "path");
+        rejected("open_backup_readonly", json!({ "path": long_path }), "path");
+        rejected("diff_against_backup", json!({ "path": long_path }), "path");
+        rejected(
+            "export_profiles_as_bat",
+            json!({ "profileNames": ["IPC Short"], "destPath": long_path }),
+            "destPath",
+        );
+        assert_eq!(mock::saves(), 0);
+    }
+
+    #[test]
+    fn imported_content_and_its_entries_are_capped() {
+        let _serial = mock::serial();
+        let h = Harness::new(Store::default());
+        let limits = config::get().command_limits;
+        let rejected = |content: String, prefix: &str| {
+            let error = h.invoke("import_blacklist_from_json", json!({ "content": content, "mode": "Merge" })).unwrap_err();
+            assert!(error.as_str().unwrap().starts_with(prefix), "{}", error);
+        };
+        let file = |executables: Vec<String>| {
+            let entries: Vec<Value> = executables.into_iter()
+                .map(|executable| json!({ "executable": executable, "blacklisted": true }))
+                .collect();
+            json!({ "entries": entries }).to_string()
+        };
+
+        rejected(" ".repeat(limits.max_content_chars as usize + 1), "argument-too-long: content");
+        rejected(file(vec!["x".repeat(limits.max_name_chars as usize + 1)]), "argument-too-long: executable");
+        rejected(
+            file((0..=limits.max_batch_items).map(|i| format!("ipc-{}.exe", i)).collect()),
+            "too-many-items: entries",
+        );
+        assert_eq!(mock::saves(), 0);
+    }
+
+    #[test]
+    fn closing_is_held_back_only_while_changes_are_unsaved() {
+        let _serial = mock::serial();
+        let _h = Harness::new(Store::default().with_profile(Profile::new("IPC Close Guard")));
+        session::clear_dirty();
+        assert!(unsaved_changes_on_close().is_none());
+
+        let profile = profiles::find_profile_by_name("IPC Close Guard").unwrap();
+        settings::set_dword_setting(profile, SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED).unwrap();
+        let unsaved = unsaved_changes_on_close().expect("close held back");
+        assert_eq!(unsaved.operations.len(), 1);
+        assert!(unsaved.operations[0].starts_with("Set setting"), "{:?}", unsaved.operations);
+
+        session::save_settings().unwrap();
+        assert!(unsaved_changes_on_close().is_none());
+
+        // A DRS file loaded into the session isn't in the driver store until saved
+        mock::write_file("C:\\Backups\\ipc-close.nip", Store::default());
+        session::load_settings_from_file("C:\\Backups\\ipc-close.nip").unwrap();
+        assert_eq!(
+            unsaved_changes_on_close().map(|u| u.operations),
+            Some(vec!["Loaded settings from 'C:\\Backups\\ipc-close.nip'".to_string()])
+        );
+        session::clear_dirty();
+    }
+
+    #[test]
+    fn diagnostics_rank_caches_largest_first_with_the_total() {
+        let _serial = mock::serial();
+        mock::install(Store::default());
+        let stats = |name: &str, approx_bytes: u64| CacheStats {
+            name: name.to_string(),
+            hits: 0,
+            misses: 0,
+            negative_hits: 0,
+            entries: 1,
+            approx_bytes,
+        };
+        let (caches, total) = rank_caches(vec![stats("small", 10), stats("large", 3000), stats("empty", 0), stats("medium", 200)]);
+        let names: Vec<&str> = caches.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["large", "medium", "small", "empty"]);
+        assert_eq!(total, 3210);
+
+        let report = diagnostics(&tasks::TaskSupervisor::default());
+        assert!(report.caches.windows(2).all(|pair| pair[0].approx_bytes >= pair[1].approx_bytes));
+        assert_eq!(report.total_cache_bytes, report.caches.iter().map(|c| c.approx_bytes).sum::<u64>());
+    }
 }
@@ -0,0 +1,228 @@
+//! Command argument and rate limits
+//!
+//! A frontend loop once called blacklist_application hundreds of times a
+//! second and wedged the driver store. Mutating commands take a token from a
+//! per-command bucket kept in managed state, list arguments are capped in
+//! length, and names, search queries, file paths and file contents are capped
+//! in characters. All checks run before any NVAPI work. Rejections are
+//! `LimitError`s whose messages start with a stable kind, so the frontend can
+//! tell them apart from driver errors.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use serde::Serialize;
+use thiserror::Error;
+use crate::config::{self, CommandLimits};
+use crate::nvapi::types::FeatureAvailability;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum LimitError {
+    #[error("rate-limited: {command} was called too often; retry in {} ms", .retry_after.as_millis())]
+    RateLimited { command: &'static str, retry_after: Duration },
+
+    #[error("too-many-items: {argument} has {count} items, more than the limit of {max}")]
+    TooManyItems { argument: &'static str, count: usize, max: u32 },
+
+    #[error("argument-too-long: {argument} is {length} characters, more than the limit of {max}")]
+    ArgumentTooLong { argument: &'static str, length: usize, max: u32 },
+}
+
+/// Driver-dependent features and the limits commands enforce
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackendCapabilities {
+    pub features: Vec<FeatureAvailability>,
+    pub limits: CommandLimits,
+}
+
+/// Tokens available to one command
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn full(burst: f64, now: Instant) -> Self {
+        Self { tokens: burst, refilled_at: now }
+    }
+
+    /// Refill for the time passed and take one token, or say how long until one is available
+    fn take(&mut self, now: Instant, per_second: f64, burst: f64) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second).min(burst);
+        self.refilled_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        if per_second <= 0.0 {
+            return Err(Duration::MAX);
+        }
+        Err(Duration::from_secs_f64((1.0 - self.tokens) / per_second))
+    }
+}
+
+/// Per-command token buckets for mutating commands, kept in managed state
+#[derive(Default)]
+pub struct CommandLimiter {
+    buckets: Mutex<HashMap<&'static str, Bucket>>,
+}
+
+impl CommandLimiter {
+    /// Take a token for `command` at `now` under the given limits. Separate from
+    /// `check` so the clock can be supplied.
+    pub fn check_at(&self, command: &'static str, now: Instant, limits: &CommandLimits) -> Result<(), LimitError> {
+        let per_second = limits.mutations_per_second as f64;
+        // A burst below one would refuse every call
+        let burst = limits.mutation_burst.max(1) as f64;
+
+        let mut buckets = self.buckets.lock().unwrap();
+        buckets.entry(command)
+            .or_insert_with(|| Bucket::full(burst, now))
+            .take(now, per_second, burst)
+            .map_err(|retry_after| LimitError::RateLimited { command, retry_after })
+    }
+
+    /// Take a token for a mutating command under the configured limits
+    pub fn check(&self, command: &'static str) -> Result<(), String> {
+        self.check_at(command, Instant::now(), &config::get().command_limits)
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Refuse a list argument longer than the configured maximum
+pub fn check_batch(argument: &'static str, count: usize) -> Result<(), String> {
+    let max = config::get().command_limits.max_batch_items;
+    if count > max as usize {
+        return Err(LimitError::TooManyItems { argument, count, max }.to_string());
+    }
+    Ok(())
+}
+
+/// Refuse an executable or profile name longer than the configured maximum
+pub fn check_name(argument: &'static str, value: &str) -> Result<(), String> {
+    let max = config::get().command_limits.max_name_chars;
+    let length = value.chars().count();
+    if length > max as usize {
+        return Err(LimitError::ArgumentTooLong { argument, length, max }.to_string());
+    }
+    Ok(())
+}
+
+/// Refuse a file path longer than the configured maximum
+pub fn check_path(argument: &'static str, value: &str) -> Result<(), String> {
+    let max = config::get().command_limits.max_path_chars;
+    let length = value.chars().count();
+    if length > max as usize {
+        return Err(LimitError::ArgumentTooLong { argument, length, max }.to_string());
+    }
+    Ok(())
+}
+
+/// Refuse file content passed in directly that is longer than the configured maximum
+pub fn check_content(argument: &'static str, value: &str) -> Result<(), String> {
+    let max = config::get().command_limits.max_content_chars;
+    let length = value.chars().count();
+    if length > max as usize {
+        return Err(LimitError::ArgumentTooLong { argument, length, max }.to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(per_second: u32, burst: u32) -> CommandLimits {
+        CommandLimits { mutations_per_second: per_second, mutation_burst: burst, ..CommandLimits::default() }
+    }
+
+    #[test]
+    fn burst_is_spent_then_refused() {
+        let limiter = CommandLimiter::default();
+        let limits = limits(10, 3);
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(limiter.check_at("blacklist_application", now, &limits), Ok(()));
+        }
+        assert_eq!(
+            limiter.check_at("blacklist_application", now, &limits),
+            Err(LimitError::RateLimited { command: "blacklist_application", retry_after: Duration::from_millis(100) }),
+        );
+    }
+
+    #[test]
+    fn tokens_refill_over_time() {
+        let limiter = CommandLimiter::default();
+        let limits = limits(2, 1);
+        let start = Instant::now();
+        assert!(limiter.check_at("add_rule", start, &limits).is_ok());
+        assert!(limiter.check_at("add_rule", start + Duration::from_millis(250), &limits).is_err());
+        assert!(limiter.check_at("add_rule", start + Duration::from_millis(500), &limits).is_ok());
+        // A long pause refills up to the burst, not beyond it
+        let later = start + Duration::from_secs(60);
+        assert!(limiter.check_at("add_rule", later, &limits).is_ok());
+        assert!(limiter.check_at("add_rule", later, &limits).is_err());
+    }
+
+    #[test]
+    fn commands_have_separate_buckets() {
+        let limiter = CommandLimiter::default();
+        let limits = limits(1, 1);
+        let now = Instant::now();
+        assert!(limiter.check_at("add_rule", now, &limits).is_ok());
+        assert!(limiter.check_at("add_rule", now, &limits).is_err());
+        assert!(limiter.check_at("remove_rule", now, &limits).is_ok());
+    }
+
+    #[test]
+    fn zero_rate_never_refills() {
+        let limiter = CommandLimiter::default();
+        let limits = limits(0, 1);
+        let now = Instant::now();
+        assert!(limiter.check_at("add_rule", now, &limits).is_ok());
+        assert_eq!(
+            limiter.check_at("add_rule", now + Duration::from_secs(3600), &limits),
+            Err(LimitError::RateLimited { command: "add_rule", retry_after: Duration::MAX }),
+        );
+    }
+
+    #[test]
+    fn zero_burst_still_allows_one_call() {
+        let limiter = CommandLimiter::default();
+        let limits = limits(1, 0);
+        let now = Instant::now();
+        assert!(limiter.check_at("add_rule", now, &limits).is_ok());
+        assert!(limiter.check_at("add_rule", now, &limits).is_err());
+    }
+
+    #[test]
+    fn messages_start_with_their_kind() {
+        let rate = LimitError::RateLimited { command: "add_rule", retry_after: Duration::from_millis(1500) };
+        assert_eq!(rate.to_string(), "rate-limited: add_rule was called too often; retry in 1500 ms");
+        let items = LimitError::TooManyItems { argument: "executables", count: 3, max: 2 };
+        assert!(items.to_string().starts_with("too-many-items: "));
+        let name = LimitError::ArgumentTooLong { argument: "executable", length: 3, max: 2 };
+        assert!(name.to_string().starts_with("argument-too-long: "));
+    }
+
+    #[test]
+    fn batch_and_name_limits_are_inclusive() {
+        let limits = CommandLimits::default();
+        assert!(check_batch("executables", limits.max_batch_items as usize).is_ok());
+        let err = check_batch("executables", limits.max_batch_items as usize + 1).unwrap_err();
+        assert!(err.starts_with("too-many-items: executables"));
+
+        let longest = "é".repeat(limits.max_name_chars as usize);
+        assert!(check_name("executable", &longest).is_ok());
+        let err = check_name("executable", &format!("{longest}x")).unwrap_err();
+        assert!(err.starts_with("argument-too-long: executable"));
+
+        let longest = "é".repeat(limits.max_path_chars as usize);
+        assert!(check_path("path", &longest).is_ok());
+        let err = check_path("path", &format!("{longest}x")).unwrap_err();
+        assert!(err.starts_with("argument-too-long: path"));
+    }
+}
@@ -0,0 +1,295 @@
+//! Idle-time maintenance
+//!
+//! Housekeeping that shouldn't compete with interactive use: pruning history,
+//! purging orphaned cache entries and re-checking caches against the driver.
+//! Each job implements `Maintenance` and is listed in `registry`. The scheduler
+//! runs due jobs only after the user has been idle for a while and never while a
+//! fullscreen window has focus. A run is cancelled as soon as input resumes;
+//! jobs check their token between work units and keep whatever they finished.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::journal::{self, JournalCategory, JournalSeverity};
+use crate::manifest::now_ms;
+use crate::metadata;
+use crate::nvapi::{ffi, partition};
+use crate::rules;
+use crate::storage::{self, StorageError};
+use crate::tasks::CancellationToken;
+
+const MAINTENANCE_FILE: &str = "maintenance.json";
+
+/// How long a completed job waits before it is due again
+const RUN_INTERVAL_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// Rough cost of a job; cheaper jobs run first so short idle periods still get work done
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MaintenanceCost {
+    Low,
+    Medium,
+    High,
+}
+
+/// A unit of idle-time housekeeping
+pub trait Maintenance: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn estimated_cost(&self) -> MaintenanceCost;
+    /// Do the work, returning early once `token` is cancelled. Returns a short summary.
+    fn run(&self, token: &CancellationToken) -> Result<String, String>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MaintenanceOutcome {
+    Completed { summary: String },
+    /// Interrupted by user input or shutdown; retried at the next idle period
+    Cancelled,
+    Failed { error: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JobRecord {
+    /// Last time the job ran to completion
+    last_completed_ms: Option<u64>,
+    last_run_ms: u64,
+    last_outcome: MaintenanceOutcome,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct MaintenanceStore {
+    jobs: HashMap<String, JobRecord>,
+}
+
+/// State of one maintenance job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceJobReport {
+    pub name: String,
+    pub estimated_cost: MaintenanceCost,
+    pub last_completed_ms: Option<u64>,
+    pub last_run_ms: Option<u64>,
+    pub last_outcome: Option<MaintenanceOutcome>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceReport {
+    pub running: bool,
+    pub jobs: Vec<MaintenanceJobReport>,
+}
+
+static STORE: Lazy<Mutex<MaintenanceStore>> = Lazy::new(|| Mutex::new(MaintenanceStore::default()));
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+struct PruneRuleHistory;
+
+impl Maintenance for PruneRuleHistory {
+    fn name(&self) -> &'static str {
+        "prune-rule-history"
+    }
+
+    fn estimated_cost(&self) -> MaintenanceCost {
+        MaintenanceCost::Low
+    }
+
+    fn run(&self, _token: &CancellationToken) -> Result<String, String> {
+        let removed = rules::prune_history().map_err(|e| e.to_string())?;
+        Ok(format!("Removed {} expired profile creation records", removed))
+    }
+}
+
+struct VerifyPredefinedCache;
+
+impl Maintenance for VerifyPredefinedCache {
+    fn name(&self) -> &'static str {
+        "verify-predefined-cache"
+    }
+
+    fn estimated_cost(&self) -> MaintenanceCost {
+        MaintenanceCost::Low
+    }
+
+    fn run(&self, _token: &CancellationToken) -> Result<String, String> {
+        let version = ffi::driver_version().ok_or("Driver version is unavailable")?;
+        Ok(if partition::discard_if_stale(version) {
+            "Discarded predefined application cache built for another driver".to_string()
+        } else {
+            "Predefined application cache matches the driver".to_string()
+        })
+    }
+}
+
+struct PurgeOrphanedMetadata;
+
+impl Maintenance for PurgeOrphanedMetadata {
+    fn name(&self) -> &'static str {
+        "purge-orphaned-metadata"
+    }
+
+    fn estimated_cost(&self) -> MaintenanceCost {
+        MaintenanceCost::Medium
+    }
+
+    fn run(&self, token: &CancellationToken) -> Result<String, String> {
+        let removed = metadata::purge_orphaned(token);
+        Ok(format!("Removed {} metadata entries for missing files", removed))
+    }
+}
+
+/// Every maintenance job, cheapest first
+fn registry() -> Vec<Box<dyn Maintenance>> {
+    let mut jobs: Vec<Box<dyn Maintenance>> = vec![
+        Box::new(PruneRuleHistory),
+        Box::new(VerifyPredefinedCache),
+        Box::new(PurgeOrphanedMetadata),
+    ];
+    jobs.sort_by_key(|job| job.estimated_cost());
+    jobs
+}
+
+/// Load job history from app data
+pub fn load() -> Result<(), StorageError> {
+    if let Some(store) = storage::load::<MaintenanceStore>(MAINTENANCE_FILE)? {
+        *STORE.lock().unwrap() = store;
+    }
+    Ok(())
+}
+
+fn is_due(name: &str, now: u64) -> bool {
+    STORE.lock().unwrap().jobs.get(name)
+        .and_then(|r| r.last_completed_ms)
+        .map_or(true, |last| now.saturating_sub(last) >= RUN_INTERVAL_MS)
+}
+
+fn record(name: &str, outcome: MaintenanceOutcome) {
+    let now = now_ms();
+    let mut store = STORE.lock().unwrap();
+    let previous = store.jobs.get(name).and_then(|r| r.last_completed_ms);
+    let last_completed_ms = match outcome {
+        MaintenanceOutcome::Completed { .. } => Some(now),
+        _ => previous,
+    };
+    store.jobs.insert(name.to_string(), JobRecord { last_completed_ms, last_run_ms: now, last_outcome: outcome });
+    storage::save_or_journal(MAINTENANCE_FILE, &*store);
+}
+
+/// Clears RUNNING even if a job panics
+struct RunGuard;
+
+impl Drop for RunGuard {
+    fn drop(&mut self) {
+        RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Run jobs until they finish or `token` is cancelled. `only_due` skips jobs that
+/// completed within the run interval. Errors if a run is already in progress.
+fn run_jobs(token: &CancellationToken, only_due: bool) -> Result<(), String> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        return Err("Maintenance is already running".to_string());
+    }
+    let _guard = RunGuard;
+
+    let now = now_ms();
+    for job in registry() {
+        if token.is_cancelled() {
+            break;
+        }
+        if only_due && !is_due(job.name(), now) {
+            continue;
+        }
+
+        let outcome = match job.run(token) {
+            _ if token.is_cancelled() => MaintenanceOutcome::Cancelled,
+            Ok(summary) => MaintenanceOutcome::Completed { summary },
+            Err(error) => MaintenanceOutcome::Failed { error },
+        };
+        record(job.name(), outcome);
+    }
+    Ok(())
+}
+
+/// Run every job now, ignoring idle state and the run interval
+pub fn run_now() -> Result<MaintenanceReport, String> {
+    run_jobs(&CancellationToken::default(), false)?;
+    Ok(report())
+}
+
+/// Each job with its last run and outcome
+pub fn report() -> MaintenanceReport {
+    let store = STORE.lock().unwrap();
+    let jobs = registry().into_iter()
+        .map(|job| {
+            let record = store.jobs.get(job.name());
+            MaintenanceJobReport {
+                name: job.name().to_string(),
+                estimated_cost: job.estimated_cost(),
+                last_completed_ms: record.and_then(|r| r.last_completed_ms),
+                last_run_ms: record.map(|r| r.last_run_ms),
+                last_outcome: record.map(|r| r.last_outcome.clone()),
+            }
+        })
+        .collect();
+    MaintenanceReport { running: RUNNING.load(Ordering::SeqCst), jobs }
+}
+
+/// Start the background task that runs due jobs while the user is idle
+#[cfg(target_os = "windows")]
+pub fn start_scheduler(app: &tauri::AppHandle) {
+    use std::time::Duration;
+    use tauri::Manager;
+    use crate::automation;
+    use crate::tasks::TaskSupervisor;
+    use crate::windows::{idle_duration, is_foreground_fullscreen};
+
+    /// Idle time required before maintenance starts
+    const IDLE_THRESHOLD: Duration = Duration::from_secs(10 * 60);
+    const POLL_INTERVAL: Duration = Duration::from_secs(30);
+    /// How often a running job's idle state is re-checked
+    const INPUT_CHECK_INTERVAL: Duration = Duration::from_millis(250);
+
+    let is_idle = || {
+        idle_duration().is_some_and(|idle| idle >= IDLE_THRESHOLD) && !is_foreground_fullscreen()
+    };
+
+    let supervisor = app.state::<TaskSupervisor>();
+    supervisor.spawn("maintenance", move |token| {
+        while token.sleep(POLL_INTERVAL) {
+            if automation::is_paused() || crate::nvapi::health::is_driver_changing() || !is_idle() {
+                continue;
+            }
+
+            // Cancel the run as soon as input resumes, automation is paused
+            // or the app shuts down
+            let run_token = CancellationToken::default();
+            let finished = AtomicBool::new(false);
+            std::thread::scope(|scope| {
+                scope.spawn(|| {
+                    while !finished.load(Ordering::SeqCst) {
+                        if token.is_cancelled() || automation::is_paused() || !is_idle() {
+                            run_token.cancel();
+                            return;
+                        }
+                        std::thread::sleep(INPUT_CHECK_INTERVAL);
+                    }
+                });
+
+                if let Err(e) = run_jobs(&run_token, true) {
+                    journal::append(JournalCategory::Task, JournalSeverity::Warning, "maintenance.skipped", serde_json::json!({
+                        "error": e,
+                    }));
+                }
+                finished.store(true, Ordering::SeqCst);
+            });
+        }
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn start_scheduler(_app: &tauri::AppHandle) {}
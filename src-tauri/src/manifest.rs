@@ -0,0 +1,434 @@
+//! Managed-entries manifest
+//!
+//! Records which executables Nvidiot manages, so cleanup and auditing features
+//! can tell our DRS changes apart from those made by other tools.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::config;
+use crate::journal::{self, JournalCategory, JournalSeverity};
+use crate::nvapi::NvApiError;
+use crate::nvapi::applications::get_application_info;
+use crate::nvapi::naming::{is_safe_profile_name, profile_name_for, render_profile_name, validate_template};
+use crate::nvapi::profiles::{find_profile_by_name, get_profile_info_by_handle, rename_profile};
+use crate::nvapi::resolve::executable_key;
+use crate::nvapi::session::{reload_settings, save_settings};
+use crate::nvapi::types::{DrsApplication, ManagedSortField, ModifiedBy, Provenance};
+use crate::storage::{self, StorageError};
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// An executable whose DRS state Nvidiot manages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagedEntry {
+    pub executable: String,
+    pub profile_name: String,
+    /// When the entry was first recorded (milliseconds since the Unix epoch)
+    pub created_at_ms: u64,
+    /// True if the entry was created by another tool and adopted later
+    pub adopted: bool,
+    /// When Nvidiot last changed the entry; entries from older manifests use the creation time
+    #[serde(default)]
+    pub last_modified_at_ms: u64,
+    #[serde(default)]
+    pub last_modified_by: ModifiedBy,
+    /// Another tool kept undoing our change soon after it was made
+    #[serde(default)]
+    pub revert_loop_detected: bool,
+}
+
+impl ManagedEntry {
+    pub fn new(executable: &str, profile_name: String, adopted: bool, by: ModifiedBy) -> Self {
+        let now = now_ms();
+        Self {
+            executable: executable.to_string(),
+            profile_name,
+            created_at_ms: now,
+            adopted,
+            last_modified_at_ms: now,
+            last_modified_by: by,
+            revert_loop_detected: false,
+        }
+    }
+
+    pub fn provenance(&self) -> Provenance {
+        Provenance {
+            created_at_ms: self.created_at_ms,
+            last_modified_at_ms: self.last_modified_at_ms,
+            last_modified_by: self.last_modified_by.clone(),
+        }
+    }
+}
+
+/// A profile name change made, or skipped, by `rename_managed_profiles`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileRename {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileRenameFailure {
+    pub profile_name: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileRenameReport {
+    pub renamed: Vec<ProfileRename>,
+    /// Skipped because another profile already has the new name
+    pub collisions: Vec<ProfileRename>,
+    pub failed: Vec<ProfileRenameFailure>,
+    /// Profiles whose name already matches the template
+    pub unchanged: u32,
+}
+
+/// Several executables of one game sharing a profile, registered together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ManagedGame {
+    pub name: String,
+    pub profile_name: String,
+    pub executables: Vec<String>,
+    pub created_at_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct Manifest {
+    entries: Vec<ManagedEntry>,
+    games: Vec<ManagedGame>,
+    /// Position of each entry keyed by executable_key, rebuilt on load
+    #[serde(skip)]
+    index: HashMap<String, usize>,
+}
+
+impl Manifest {
+    fn rebuild_index(&mut self) {
+        self.index = self.entries.iter()
+            .enumerate()
+            .map(|(i, e)| (executable_key(&e.executable), i))
+            .collect();
+    }
+
+    fn find(&self, executable: &str) -> Option<&ManagedEntry> {
+        self.index.get(&executable_key(executable)).map(|&i| &self.entries[i])
+    }
+
+    fn find_mut(&mut self, executable: &str) -> Option<&mut ManagedEntry> {
+        let i = *self.index.get(&executable_key(executable))?;
+        Some(&mut self.entries[i])
+    }
+
+    fn push(&mut self, entry: ManagedEntry) {
+        self.index.insert(executable_key(&entry.executable), self.entries.len());
+        self.entries.push(entry);
+    }
+}
+
+static MANIFEST: Lazy<Mutex<Manifest>> = Lazy::new(|| Mutex::new(Manifest::default()));
+
+/// Current time in milliseconds since the Unix epoch
+pub fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Load the manifest from app data
+pub fn load() -> Result<(), StorageError> {
+    if let Some(mut manifest) = storage::load::<Manifest>(MANIFEST_FILE)? {
+        for entry in &mut manifest.entries {
+            if entry.last_modified_at_ms == 0 {
+                entry.last_modified_at_ms = entry.created_at_ms;
+            }
+        }
+        manifest.rebuild_index();
+        *MANIFEST.lock().unwrap() = manifest;
+    }
+    Ok(())
+}
+
+/// Get all managed entries
+pub fn entries() -> Vec<ManagedEntry> {
+    MANIFEST.lock().unwrap().entries.clone()
+}
+
+/// Get managed entries sorted by creation or last modification time.
+/// Without a sort field entries keep the order they were recorded in.
+pub fn sorted_entries(sort_by: Option<ManagedSortField>, ascending: bool) -> Vec<ManagedEntry> {
+    let mut entries = entries();
+    if let Some(field) = sort_by {
+        entries.sort_by(|a, b| {
+            let ordering = match field {
+                ManagedSortField::Created => a.created_at_ms.cmp(&b.created_at_ms),
+                ManagedSortField::Modified => a.last_modified_at_ms.cmp(&b.last_modified_at_ms),
+            };
+            let ordering = if ascending { ordering } else { ordering.reverse() };
+            ordering.then_with(|| executable_key(&a.executable).cmp(&executable_key(&b.executable)))
+        });
+    }
+    entries
+}
+
+/// Find the managed entry for an executable
+pub fn get(executable: &str) -> Option<ManagedEntry> {
+    MANIFEST.lock().unwrap().find(executable).cloned()
+}
+
+/// Fill in provenance for the applications Nvidiot manages
+pub fn attach_provenance(applications: &mut [DrsApplication]) {
+    let manifest = MANIFEST.lock().unwrap();
+    for app in applications {
+        app.provenance = manifest.find(&app.executable).map(ManagedEntry::provenance);
+    }
+}
+
+/// Names of the profiles Nvidiot created for managed executables
+pub fn managed_profile_names() -> HashSet<String> {
+    MANIFEST.lock().unwrap().entries.iter()
+        .filter(|e| !e.adopted)
+        .map(|e| e.profile_name.clone())
+        .collect()
+}
+
+/// Whether an executable is managed by Nvidiot
+pub fn is_managed(executable: &str) -> bool {
+    get(executable).is_some()
+}
+
+/// Add or update entries and persist the manifest once
+pub fn record_all(new_entries: Vec<ManagedEntry>) -> Result<(), StorageError> {
+    let mut manifest = MANIFEST.lock().unwrap();
+    for entry in new_entries {
+        match manifest.find_mut(&entry.executable) {
+            // Keep the original creation time and origin
+            Some(existing) => {
+                existing.profile_name = entry.profile_name;
+                existing.last_modified_at_ms = entry.last_modified_at_ms;
+                existing.last_modified_by = entry.last_modified_by;
+            }
+            None => manifest.push(entry),
+        }
+    }
+    storage::save(MANIFEST_FILE, &*manifest)
+}
+
+/// Remove an entry, returning whether it existed
+pub fn remove(executable: &str) -> Result<bool, StorageError> {
+    let mut manifest = MANIFEST.lock().unwrap();
+    let before = manifest.entries.len();
+    let key = executable_key(executable);
+    manifest.entries.retain(|e| executable_key(&e.executable) != key);
+    if manifest.entries.len() == before {
+        return Ok(false);
+    }
+    manifest.rebuild_index();
+    storage::save(MANIFEST_FILE, &*manifest)?;
+    Ok(true)
+}
+
+/// Forget the entries and games whose profiles were deleted, returning how
+/// many entries were removed
+pub fn remove_profiles(profile_names: &[String]) -> Result<u32, StorageError> {
+    let deleted: HashSet<String> = profile_names.iter().map(|n| n.to_lowercase()).collect();
+    let mut manifest = MANIFEST.lock().unwrap();
+    let before = manifest.entries.len();
+    let games_before = manifest.games.len();
+    manifest.entries.retain(|e| !deleted.contains(&e.profile_name.to_lowercase()));
+    manifest.games.retain(|g| !deleted.contains(&g.profile_name.to_lowercase()));
+
+    let removed = (before - manifest.entries.len()) as u32;
+    if removed == 0 && manifest.games.len() == games_before {
+        return Ok(0);
+    }
+    manifest.rebuild_index();
+    storage::save(MANIFEST_FILE, &*manifest)?;
+    Ok(removed)
+}
+
+/// Add or update a single entry
+pub fn record(entry: ManagedEntry) -> Result<(), StorageError> {
+    record_all(vec![entry])
+}
+
+/// Find a registered game by name, ignoring case
+pub fn game(name: &str) -> Option<ManagedGame> {
+    MANIFEST.lock().unwrap().games.iter()
+        .find(|g| g.name.eq_ignore_ascii_case(name))
+        .cloned()
+}
+
+/// Add or replace a game, matched by name
+pub fn record_game(game: ManagedGame) -> Result<(), StorageError> {
+    let mut manifest = MANIFEST.lock().unwrap();
+    match manifest.games.iter_mut().find(|g| g.name.eq_ignore_ascii_case(&game.name)) {
+        Some(existing) => *existing = game,
+        None => manifest.games.push(game),
+    }
+    storage::save(MANIFEST_FILE, &*manifest)
+}
+
+/// Record an executable after a driver change that already succeeded, so a
+/// failure is journaled rather than failing the change
+pub fn record_executable_or_journal(executable: &str, adopted: bool, by: ModifiedBy) {
+    if let Err(e) = record_executable(executable, adopted, by) {
+        journal::append(JournalCategory::Storage, JournalSeverity::Warning, "manifest.record_failed", serde_json::json!({
+            "executable": executable,
+            "error": e,
+        }));
+    }
+}
+
+/// Record an executable using its current DRS profile. A profile Nvidiot
+/// created under a name some drivers can't look up again is renamed first.
+pub fn record_executable(executable: &str, adopted: bool, by: ModifiedBy) -> Result<(), String> {
+    let app = get_application_info(executable).map_err(|e| e.to_string())?;
+    let adopted = get(executable).map_or(adopted, |existing| existing.adopted);
+    let profile_name = if adopted {
+        app.profile_name
+    } else {
+        migrate_unsafe_profile_name(executable, app.profile_name)
+    };
+    record(ManagedEntry::new(executable, profile_name, adopted, by))
+        .map_err(|e| e.to_string())
+}
+
+/// Rename a profile from an older naming scheme to the current safe name,
+/// returning whichever name the profile ends up with. Only profiles Nvidiot
+/// created are touched, and failures keep the old name.
+fn migrate_unsafe_profile_name(executable: &str, profile_name: String) -> String {
+    if is_safe_profile_name(&profile_name) {
+        return profile_name;
+    }
+    let ours = find_profile_by_name(&profile_name)
+        .and_then(get_profile_info_by_handle)
+        .is_ok_and(|p| !p.is_predefined && p.user_created_by_nvidiot);
+    if !ours {
+        return profile_name;
+    }
+    let target = profile_name_for(executable);
+    if target == profile_name || find_profile_by_name(&target).is_ok() {
+        return profile_name;
+    }
+
+    match rename_profile(&profile_name, &target).and_then(|_| save_settings()) {
+        Ok(()) => target,
+        Err(e) => {
+            let _ = reload_settings();
+            journal::append(JournalCategory::Mutation, JournalSeverity::Warning, "profile.rename_failed", serde_json::json!({
+                "profileName": profile_name,
+                "target": target,
+                "error": e.to_string(),
+            }));
+            profile_name
+        }
+    }
+}
+
+/// Note a change to an already managed executable. Unmanaged executables are ignored.
+pub fn touch(executable: &str, by: ModifiedBy) {
+    let mut manifest = MANIFEST.lock().unwrap();
+    let Some(entry) = manifest.find_mut(executable) else {
+        return;
+    };
+    entry.last_modified_at_ms = now_ms();
+    entry.last_modified_by = by;
+    storage::save_or_journal(MANIFEST_FILE, &*manifest);
+}
+
+/// Flag a managed executable as caught in a revert loop. Returns whether it
+/// was newly flagged; unmanaged executables are not flagged.
+pub fn flag_revert_loop(executable: &str) -> bool {
+    let mut manifest = MANIFEST.lock().unwrap();
+    let Some(entry) = manifest.find_mut(executable) else {
+        return false;
+    };
+    if entry.revert_loop_detected {
+        return false;
+    }
+    entry.revert_loop_detected = true;
+    storage::save_or_journal(MANIFEST_FILE, &*manifest);
+    true
+}
+
+/// Rename every profile Nvidiot created to match the configured template, saving
+/// the driver settings and the manifest once. Adopted profiles keep their names,
+/// and a profile shared by several executables is named for the first one recorded.
+pub fn rename_managed_profiles() -> Result<ProfileRenameReport, String> {
+    let template = config::get().profile_naming.template;
+    validate_template(&template)?;
+
+    let mut targets: Vec<ProfileRename> = Vec::new();
+    for entry in entries().into_iter().filter(|e| !e.adopted) {
+        if targets.iter().any(|t| t.from == entry.profile_name) {
+            continue;
+        }
+        let to = render_profile_name(&template, &entry.executable);
+        targets.push(ProfileRename { from: entry.profile_name, to });
+    }
+
+    let mut report = ProfileRenameReport::default();
+    // Names given out in this batch, compared without case like the driver does
+    let mut claimed: HashSet<String> = HashSet::new();
+    for target in targets {
+        if target.from == target.to {
+            report.unchanged += 1;
+            continue;
+        }
+
+        let key = target.to.to_lowercase();
+        let taken = claimed.contains(&key) || match find_profile_by_name(&target.to) {
+            Ok(_) => !target.from.eq_ignore_ascii_case(&target.to),
+            Err(NvApiError::ProfileNotFound(_)) => false,
+            Err(e) => {
+                report.failed.push(ProfileRenameFailure { profile_name: target.from, error: e.to_string() });
+                continue;
+            }
+        };
+        if taken {
+            report.collisions.push(target);
+            continue;
+        }
+
+        match rename_profile(&target.from, &target.to) {
+            Ok(()) => {
+                claimed.insert(key);
+                report.renamed.push(target);
+            }
+            Err(e) => report.failed.push(ProfileRenameFailure { profile_name: target.from, error: e.to_string() }),
+        }
+    }
+
+    if report.renamed.is_empty() {
+        return Ok(report);
+    }
+
+    if let Err(e) = save_settings() {
+        // Drop the unsaved renames so the session matches the manifest again
+        let _ = reload_settings();
+        return Err(e.to_string());
+    }
+
+    let renames: HashMap<&str, &str> = report.renamed.iter()
+        .map(|r| (r.from.as_str(), r.to.as_str()))
+        .collect();
+    let now = now_ms();
+    let mut manifest = MANIFEST.lock().unwrap();
+    for entry in manifest.entries.iter_mut().filter(|e| !e.adopted) {
+        if let Some(to) = renames.get(entry.profile_name.as_str()) {
+            entry.profile_name = to.to_string();
+            entry.last_modified_at_ms = now;
+            entry.last_modified_by = ModifiedBy::Manual;
+        }
+    }
+    storage::save(MANIFEST_FILE, &*manifest).map_err(|e| e.to_string())?;
+    Ok(report)
+}
@@ -0,0 +1,648 @@
+//! Executable metadata queue
+//!
+//! Version resources, PE headers, signatures and icons are read off the calling thread. Lookups
+//! return whatever is cached right away and queue the rest; a small worker pool
+//! processes each (path, kind) once and emits "metadata-ready" as results land.
+//! Results are cached with the file's mtime, so an updated executable is re-read.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::{self, File};
+use std::io::Read;
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::config;
+use crate::manifest::now_ms;
+use crate::nvapi::types::CacheStats;
+use crate::storage::{self, StorageError};
+use crate::tasks::{CancellationToken, TaskSupervisor};
+
+const METADATA_FILE: &str = "metadata-cache.json";
+
+/// Event emitted when a queued lookup finishes
+pub const METADATA_READY_EVENT: &str = "metadata-ready";
+
+/// Maximum jobs processed at once
+const MAX_WORKERS: usize = 2;
+
+/// Bytes read from the start of a file when looking for the PE header
+const PE_HEADER_READ_BYTES: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MetadataKind {
+    Version,
+    Pe,
+    Signer,
+    Icon,
+}
+
+/// Strings from an executable's version resource
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionInfo {
+    pub product_name: Option<String>,
+    pub file_description: Option<String>,
+    pub company_name: Option<String>,
+    pub file_version: Option<String>,
+}
+
+/// Fields from an executable's PE header
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeInfo {
+    /// "x86", "x64", "arm64", or the raw machine value in hex
+    pub machine: String,
+    pub is_64_bit: bool,
+    /// True for GUI-subsystem executables, false for console and others
+    pub is_gui: bool,
+    /// Link time in seconds since the Unix epoch (not always meaningful with reproducible builds)
+    pub link_timestamp: u32,
+}
+
+/// Publisher of a signed executable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignerInfo {
+    /// Subject name of the signing certificate, e.g. "Valve Corp."
+    pub subject: String,
+}
+
+/// An executable's first large icon
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IconInfo {
+    /// "data:image/png;base64,..." for use as an image source
+    pub data_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum MetadataFields {
+    Version(VersionInfo),
+    Pe(PeInfo),
+    Signer(SignerInfo),
+    Icon(IconInfo),
+}
+
+/// Result of a lookup: cached fields if any, and whether a fresh read is queued
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataLookup {
+    pub key: String,
+    pub kind: MetadataKind,
+    pub fields: Option<MetadataFields>,
+    pub pending: bool,
+}
+
+/// Payload of the metadata-ready event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataReady {
+    pub key: String,
+    pub kind: MetadataKind,
+    /// None when the file has no such metadata or could not be read
+    pub fields: Option<MetadataFields>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheEntry {
+    key: String,
+    kind: MetadataKind,
+    mtime_ms: u64,
+    fields: Option<MetadataFields>,
+    last_used_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct MetadataCache {
+    entries: Vec<CacheEntry>,
+}
+
+#[derive(Default)]
+struct Queue {
+    jobs: VecDeque<(String, MetadataKind)>,
+    /// Keys queued or being processed, for dedupe
+    queued: HashSet<(String, MetadataKind)>,
+    workers: usize,
+}
+
+impl Queue {
+    /// Queue a job unless the same (path, kind) is already queued or running.
+    /// True when it was queued.
+    fn push(&mut self, path: &str, kind: MetadataKind) -> bool {
+        if !self.queued.insert((cache_key(path), kind)) {
+            return false;
+        }
+        self.jobs.push_back((path.to_string(), kind));
+        true
+    }
+
+    /// Allow the key to be queued again once its job is done
+    fn finish(&mut self, key: &str, kind: MetadataKind) {
+        self.queued.remove(&(key.to_string(), kind));
+    }
+}
+
+static CACHE: Lazy<Mutex<HashMap<(String, MetadataKind), CacheEntry>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static QUEUE: Lazy<Mutex<Queue>> = Lazy::new(|| Mutex::new(Queue::default()));
+
+/// Load the cache from app data
+pub fn load() -> Result<(), StorageError> {
+    if let Some(cache) = storage::load::<MetadataCache>(METADATA_FILE)? {
+        *CACHE.lock().unwrap() = cache.entries.into_iter()
+            .map(|e| ((e.key.clone(), e.kind), e))
+            .collect();
+    }
+    Ok(())
+}
+
+fn persist(cache: &HashMap<(String, MetadataKind), CacheEntry>) {
+    let snapshot = MetadataCache { entries: cache.values().cloned().collect() };
+    storage::save_or_journal(METADATA_FILE, &snapshot);
+}
+
+/// Cache key for a path: paths are case-insensitive on Windows
+fn cache_key(path: &str) -> String {
+    path.to_lowercase()
+}
+
+fn file_mtime_ms(path: &str) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    modified.duration_since(UNIX_EPOCH).ok().map(|d| d.as_millis() as u64)
+}
+
+/// Parse the machine type, bitness and subsystem from a PE file's headers
+pub fn read_pe_info(path: &str) -> Option<PeInfo> {
+    let mut header = Vec::with_capacity(PE_HEADER_READ_BYTES);
+    File::open(path).ok()?
+        .take(PE_HEADER_READ_BYTES as u64)
+        .read_to_end(&mut header).ok()?;
+    parse_pe_header(&header)
+}
+
+fn parse_pe_header(bytes: &[u8]) -> Option<PeInfo> {
+    let u16_at = |at: usize| bytes.get(at..at + 2).map(|b| u16::from_le_bytes([b[0], b[1]]));
+    let u32_at = |at: usize| bytes.get(at..at + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]));
+
+    if bytes.get(..2)? != b"MZ" {
+        return None;
+    }
+    let pe = u32_at(0x3C)? as usize;
+    if bytes.get(pe..pe + 4)? != b"PE\0\0" {
+        return None;
+    }
+
+    // COFF header follows the signature; the optional header follows its 20 bytes
+    let machine = u16_at(pe + 4)?;
+    let link_timestamp = u32_at(pe + 8)?;
+    let optional = pe + 24;
+    let is_64_bit = u16_at(optional)? == 0x20B;
+    // Subsystem sits at the same offset in PE32 and PE32+ optional headers
+    let subsystem = u16_at(optional + 68)?;
+
+    let machine = match machine {
+        0x014C => "x86".to_string(),
+        0x8664 => "x64".to_string(),
+        0xAA64 => "arm64".to_string(),
+        other => format!("0x{:04X}", other),
+    };
+
+    Some(PeInfo { machine, is_64_bit, is_gui: subsystem == 2, link_timestamp })
+}
+
+fn extract(path: &str, kind: MetadataKind) -> Option<MetadataFields> {
+    match kind {
+        MetadataKind::Pe => read_pe_info(path).map(MetadataFields::Pe),
+        MetadataKind::Version => {
+            #[cfg(target_os = "windows")]
+            {
+                crate::windows::version::get_version_info(path).map(MetadataFields::Version)
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                None
+            }
+        }
+        MetadataKind::Signer => {
+            #[cfg(target_os = "windows")]
+            {
+                crate::windows::signature::get_signer_subject(path)
+                    .map(|subject| MetadataFields::Signer(SignerInfo { subject }))
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                None
+            }
+        }
+        MetadataKind::Icon => {
+            #[cfg(target_os = "windows")]
+            {
+                crate::windows::icon::get_icon_data_url(path).map(|data_url| MetadataFields::Icon(IconInfo { data_url }))
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                None
+            }
+        }
+    }
+}
+
+/// Drop the least recently used entries beyond `max_entries`
+fn evict(cache: &mut HashMap<(String, MetadataKind), CacheEntry>, max_entries: usize) {
+    if cache.len() <= max_entries {
+        return;
+    }
+    let mut by_age: Vec<_> = cache.iter().map(|(k, e)| (e.last_used_ms, k.clone())).collect();
+    by_age.sort();
+    let excess = cache.len() - max_entries;
+    for (_, key) in by_age.into_iter().take(excess) {
+        cache.remove(&key);
+    }
+}
+
+/// Encoded length of an entry's icon, 0 for other kinds
+fn icon_len(entry: &CacheEntry) -> usize {
+    match &entry.fields {
+        Some(MetadataFields::Icon(icon)) => icon.data_url.len(),
+        _ => 0,
+    }
+}
+
+/// Drop the least recently used icons until the encoded icons fit in `max_bytes`
+fn evict_icons(cache: &mut HashMap<(String, MetadataKind), CacheEntry>, max_bytes: u64) {
+    let mut total: u64 = cache.values().map(|e| icon_len(e) as u64).sum();
+    if total <= max_bytes {
+        return;
+    }
+    let mut by_age: Vec<_> = cache.iter()
+        .filter(|(_, e)| e.kind == MetadataKind::Icon)
+        .map(|(k, e)| (e.last_used_ms, k.clone()))
+        .collect();
+    by_age.sort();
+    for (_, key) in by_age {
+        if total <= max_bytes {
+            break;
+        }
+        if let Some(entry) = cache.remove(&key) {
+            total -= icon_len(&entry) as u64;
+        }
+    }
+}
+
+fn opt_len(value: &Option<String>) -> usize {
+    value.as_ref().map_or(0, |v| v.len())
+}
+
+/// Approximate bytes held by one cache entry, counted twice for the key copy in the map
+fn entry_bytes(entry: &CacheEntry) -> u64 {
+    let fields = match &entry.fields {
+        Some(MetadataFields::Version(v)) => {
+            opt_len(&v.product_name) + opt_len(&v.file_description) + opt_len(&v.company_name) + opt_len(&v.file_version)
+        }
+        Some(MetadataFields::Pe(pe)) => pe.machine.len(),
+        Some(MetadataFields::Signer(signer)) => signer.subject.len(),
+        Some(MetadataFields::Icon(icon)) => icon.data_url.len(),
+        None => 0,
+    };
+    (std::mem::size_of::<((String, MetadataKind), CacheEntry)>() + entry.key.len() * 2 + fields) as u64
+}
+
+/// Size of the metadata cache. Lookups aren't counted as hits or misses.
+pub fn cache_stats() -> CacheStats {
+    let cache = CACHE.lock().unwrap();
+    CacheStats {
+        name: "executable-metadata".to_string(),
+        hits: 0,
+        misses: 0,
+        negative_hits: 0,
+        entries: cache.len() as u64,
+        approx_bytes: cache.values().map(entry_bytes).sum(),
+    }
+}
+
+/// Drop every cached result, in memory and on disk
+pub fn clear_cache() {
+    let mut cache = CACHE.lock().unwrap();
+    cache.clear();
+    persist(&cache);
+}
+
+/// Drop cached results for files that no longer exist, checking `token` between
+/// files. Entries found before a cancellation are still removed. Returns the count removed.
+pub fn purge_orphaned(token: &CancellationToken) -> usize {
+    let keys: Vec<_> = CACHE.lock().unwrap().keys().cloned().collect();
+    let mut orphaned = Vec::new();
+    for key in keys {
+        if token.is_cancelled() {
+            break;
+        }
+        if fs::metadata(&key.0).is_err() {
+            orphaned.push(key);
+        }
+    }
+
+    if orphaned.is_empty() {
+        return 0;
+    }
+    let mut cache = CACHE.lock().unwrap();
+    for key in &orphaned {
+        cache.remove(key);
+    }
+    persist(&cache);
+    orphaned.len()
+}
+
+fn run_worker(app: AppHandle, token: CancellationToken) {
+    loop {
+        let job = {
+            let mut queue = QUEUE.lock().unwrap();
+            // Queued jobs are dropped on shutdown; finished ones are already persisted
+            match queue.jobs.pop_front().filter(|_| !token.is_cancelled()) {
+                Some(job) => job,
+                None => {
+                    queue.workers -= 1;
+                    return;
+                }
+            }
+        };
+
+        let (path, kind) = job;
+        let ready = process(&path, kind);
+        QUEUE.lock().unwrap().finish(&ready.key, kind);
+        let _ = app.emit(METADATA_READY_EVENT, ready);
+    }
+}
+
+/// Read one (path, kind) and cache the result against the file's current mtime
+fn process(path: &str, kind: MetadataKind) -> MetadataReady {
+    let key = cache_key(path);
+    let fields = extract(path, kind);
+
+    let mut cache = CACHE.lock().unwrap();
+    cache.insert((key.clone(), kind), CacheEntry {
+        key: key.clone(),
+        kind,
+        mtime_ms: file_mtime_ms(path).unwrap_or(0),
+        fields: fields.clone(),
+        last_used_ms: now_ms(),
+    });
+    let limits = config::get().cache_limits;
+    evict(&mut cache, limits.metadata_entries as usize);
+    evict_icons(&mut cache, limits.icon_bytes);
+    persist(&cache);
+
+    MetadataReady { key, kind, fields }
+}
+
+/// Queue a lookup unless the same (path, kind) is already queued or running
+fn enqueue(app: &AppHandle, path: &str, kind: MetadataKind) {
+    let mut queue = QUEUE.lock().unwrap();
+    if !queue.push(path, kind) {
+        return;
+    }
+
+    if queue.workers < MAX_WORKERS {
+        let worker_app = app.clone();
+        let spawned = app.state::<TaskSupervisor>()
+            .spawn("metadata-worker", move |token| run_worker(worker_app, token));
+        if spawned.is_some() {
+            queue.workers += 1;
+        }
+    }
+}
+
+/// Return cached metadata for each path without blocking, queueing reads for
+/// anything missing or stale
+pub fn lookup(app: &AppHandle, paths: &[String], kind: MetadataKind) -> Vec<MetadataLookup> {
+    let now = now_ms();
+    paths.iter()
+        .map(|path| {
+            let key = cache_key(path);
+            let cached = {
+                let mut cache = CACHE.lock().unwrap();
+                cache.get_mut(&(key.clone(), kind)).map(|entry| {
+                    entry.last_used_ms = now;
+                    (entry.mtime_ms, entry.fields.clone())
+                })
+            };
+
+            let fresh = matches!(&cached, Some((mtime, _)) if file_mtime_ms(path).unwrap_or(0) == *mtime);
+            if !fresh {
+                enqueue(app, path, kind);
+            }
+
+            MetadataLookup {
+                key,
+                kind,
+                fields: cached.and_then(|(_, fields)| fields),
+                pending: !fresh,
+            }
+        })
+        .collect()
+}
+
+/// Jobs waiting for or held by a worker
+pub fn queue_depth() -> usize {
+    QUEUE.lock().unwrap().queued.len()
+}
+
+/// Cached fields for a path if the cache is fresh for the file's current mtime.
+/// Never queues a read. The inner None means the file has no such metadata.
+pub fn cached(path: &str, kind: MetadataKind) -> Option<Option<MetadataFields>> {
+    let cache = CACHE.lock().unwrap();
+    let entry = cache.get(&(cache_key(path), kind))?;
+    (file_mtime_ms(path).unwrap_or(0) == entry.mtime_ms).then(|| entry.fields.clone())
+}
+
+/// Cached signer subject for a path, without queueing a signature check
+pub fn cached_signer(path: &str) -> Option<String> {
+    match cached(path, MetadataKind::Signer)? {
+        Some(MetadataFields::Signer(signer)) => Some(signer.subject),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+
+    /// Headers of a PE file: the DOS stub pointing at 0x80, then COFF and optional header fields
+    fn pe_bytes(machine: u16, optional_magic: u16, subsystem: u16) -> Vec<u8> {
+        let mut bytes = vec![0u8; 0x200];
+        bytes[..2].copy_from_slice(b"MZ");
+        bytes[0x3C..0x40].copy_from_slice(&0x80u32.to_le_bytes());
+        bytes[0x80..0x84].copy_from_slice(b"PE\0\0");
+        bytes[0x84..0x86].copy_from_slice(&machine.to_le_bytes());
+        bytes[0x88..0x8C].copy_from_slice(&1_700_000_000u32.to_le_bytes());
+        bytes[0x98..0x9A].copy_from_slice(&optional_magic.to_le_bytes());
+        bytes[0x98 + 68..0x98 + 70].copy_from_slice(&subsystem.to_le_bytes());
+        bytes
+    }
+
+    /// A fresh file for one test
+    fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("nvidiot-metadata-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    fn entry(key: &str, last_used_ms: u64, fields: Option<MetadataFields>) -> CacheEntry {
+        let kind = match &fields {
+            Some(MetadataFields::Version(_)) => MetadataKind::Version,
+            Some(MetadataFields::Signer(_)) => MetadataKind::Signer,
+            Some(MetadataFields::Icon(_)) => MetadataKind::Icon,
+            _ => MetadataKind::Pe,
+        };
+        CacheEntry { key: key.to_string(), kind, mtime_ms: 0, fields, last_used_ms }
+    }
+
+    fn icon(data_url_len: usize) -> Option<MetadataFields> {
+        Some(MetadataFields::Icon(IconInfo { data_url: "x".repeat(data_url_len) }))
+    }
+
+    #[test]
+    fn pe_headers_give_machine_bitness_and_subsystem() {
+        let x64_gui = parse_pe_header(&pe_bytes(0x8664, 0x20B, 2)).unwrap();
+        assert_eq!(x64_gui.machine, "x64");
+        assert!(x64_gui.is_64_bit);
+        assert!(x64_gui.is_gui);
+        assert_eq!(x64_gui.link_timestamp, 1_700_000_000);
+
+        let x86_console = parse_pe_header(&pe_bytes(0x014C, 0x10B, 3)).unwrap();
+        assert_eq!(x86_console.machine, "x86");
+        assert!(!x86_console.is_64_bit);
+        assert!(!x86_console.is_gui);
+
+        assert_eq!(parse_pe_header(&pe_bytes(0x01C4, 0x10B, 2)).unwrap().machine, "0x01C4");
+    }
+
+    #[test]
+    fn files_that_are_not_pe_or_are_cut_short_are_rejected() {
+        assert!(parse_pe_header(b"").is_none());
+        assert!(parse_pe_header(b"#!/bin/sh\n").is_none());
+
+        let mut no_signature = pe_bytes(0x8664, 0x20B, 2);
+        no_signature[0x80] = b'X';
+        assert!(parse_pe_header(&no_signature).is_none());
+
+        // The subsystem field lies past the end
+        assert!(parse_pe_header(&pe_bytes(0x8664, 0x20B, 2)[..0xC0]).is_none());
+    }
+
+    #[test]
+    fn a_duplicate_request_is_processed_once() {
+        let mut queue = Queue::default();
+        assert!(queue.push("C:\\Games\\Dedupe.exe", MetadataKind::Pe));
+        assert!(!queue.push("c:\\games\\dedupe.EXE", MetadataKind::Pe));
+        // Another kind for the same file is a separate job
+        assert!(queue.push("C:\\Games\\Dedupe.exe", MetadataKind::Version));
+
+        let mut processed = Vec::new();
+        while let Some((path, kind)) = queue.jobs.pop_front() {
+            // A request arriving while the job runs is still a duplicate
+            assert!(!queue.push(&path, kind));
+            processed.push((path.clone(), kind));
+            queue.finish(&cache_key(&path), kind);
+        }
+        assert_eq!(processed, [
+            ("C:\\Games\\Dedupe.exe".to_string(), MetadataKind::Pe),
+            ("C:\\Games\\Dedupe.exe".to_string(), MetadataKind::Version),
+        ]);
+
+        // Once finished, the key can be requested again
+        assert!(queue.push("C:\\Games\\Dedupe.exe", MetadataKind::Pe));
+    }
+
+    #[test]
+    fn a_changed_mtime_forces_a_re_read() {
+        let path = temp_file("mtime.exe", &pe_bytes(0x014C, 0x10B, 2));
+        let path_str = path.to_string_lossy().to_string();
+
+        let ready = process(&path_str, MetadataKind::Pe);
+        assert!(matches!(&ready.fields, Some(MetadataFields::Pe(pe)) if pe.machine == "x86"));
+        assert!(matches!(cached(&path_str, MetadataKind::Pe), Some(Some(MetadataFields::Pe(_)))));
+
+        // The file is updated in place; the cached result no longer counts
+        fs::write(&path, pe_bytes(0x8664, 0x20B, 2)).unwrap();
+        fs::File::options().write(true).open(&path).unwrap()
+            .set_modified(SystemTime::now() + Duration::from_secs(60)).unwrap();
+        assert!(cached(&path_str, MetadataKind::Pe).is_none());
+
+        process(&path_str, MetadataKind::Pe);
+        assert!(matches!(cached(&path_str, MetadataKind::Pe), Some(Some(MetadataFields::Pe(pe))) if pe.machine == "x64"));
+        let _ = fs::remove_file(&path);
+    }
+
+    #[test]
+    fn eviction_keeps_the_most_recently_used_within_the_cap() {
+        let mut cache: HashMap<(String, MetadataKind), CacheEntry> = (0..5)
+            .map(|i| {
+                let key = format!("c:\\evict\\{}.exe", i);
+                ((key.clone(), MetadataKind::Pe), entry(&key, [30, 10, 50, 20, 40][i], None))
+            })
+            .collect();
+
+        evict(&mut cache, 5);
+        assert_eq!(cache.len(), 5);
+
+        evict(&mut cache, 3);
+        let mut kept: Vec<u64> = cache.values().map(|e| e.last_used_ms).collect();
+        kept.sort();
+        assert_eq!(kept, [30, 40, 50]);
+
+        evict(&mut cache, 0);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn entry_size_counts_the_key_twice_and_owned_field_strings() {
+        let base = std::mem::size_of::<((String, MetadataKind), CacheEntry)>() as u64;
+        assert_eq!(entry_bytes(&entry("c:\\a.exe", 0, None)), base + 16);
+
+        let version = Some(MetadataFields::Version(VersionInfo {
+            product_name: Some("Game".to_string()),
+            file_description: None,
+            company_name: Some("Studio".to_string()),
+            file_version: Some("1.0".to_string()),
+        }));
+        assert_eq!(entry_bytes(&entry("c:\\a.exe", 0, version)), base + 16 + 13);
+
+        let signer = Some(MetadataFields::Signer(SignerInfo { subject: "CN=Studio".to_string() }));
+        assert_eq!(entry_bytes(&entry("c:\\a.exe", 0, signer)), base + 16 + 9);
+
+        // Icons are counted by their encoded length
+        assert_eq!(entry_bytes(&entry("c:\\a.exe", 0, icon(5000))), base + 16 + 5000);
+    }
+
+    #[test]
+    fn icon_eviction_drops_the_oldest_icons_until_they_fit() {
+        let mut cache: HashMap<(String, MetadataKind), CacheEntry> = HashMap::new();
+        for (name, last_used_ms, fields) in [
+            ("old.exe", 10, icon(400)),
+            ("mid.exe", 20, icon(300)),
+            ("new.exe", 30, icon(200)),
+            // Other kinds are older still but not icons
+            ("pe.exe", 1, None),
+        ] {
+            let e = entry(name, last_used_ms, fields);
+            cache.insert((e.key.clone(), e.kind), e);
+        }
+
+        evict_icons(&mut cache, 900);
+        assert_eq!(cache.len(), 4);
+
+        evict_icons(&mut cache, 600);
+        let mut kept: Vec<&str> = cache.values().map(|e| e.key.as_str()).collect();
+        kept.sort();
+        assert_eq!(kept, ["mid.exe", "new.exe", "pe.exe"]);
+
+        evict_icons(&mut cache, 0);
+        assert_eq!(cache.values().map(|e| e.key.as_str()).collect::<Vec<_>>(), ["pe.exe"]);
+    }
+}
@@ -0,0 +1,698 @@
+//! Import from nvidiaProfileInspector's full configuration export
+//!
+//! NPI can export every customized profile as one XML file, and those files
+//! run to thousands of profiles, so they are read through a small pull
+//! tokenizer one profile at a time and never held as a document. The reader
+//! is tolerant of the variations between NPI versions: UTF-8 or UTF-16 with a
+//! BOM (NPI writes utf-16), element names in any case and with namespace
+//! prefixes, both the `Executeables` spelling NPI uses and `Executables`,
+//! values in decimal or 0x-hex, and exports from before `ValueType` existed,
+//! whose values are all DWORDs.
+//!
+//! By default only profiles carrying a capture setting from the audit catalog
+//! are imported, and only those settings are written. Everything is staged and
+//! saved once, then the ShadowPlay values are verified like a blacklist import.
+//! A ShadowPlay value that conflicts by the blacklist import's rules is left
+//! alone and reported, unless the caller asks to take the imported value.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use crate::audit::CAPTURE_SETTINGS;
+use crate::games::{self, AttachStatus, ExecutableStatus};
+use crate::journal::{self, JournalCategory};
+use crate::manifest;
+use crate::nvapi::ffi::{SHADOWPLAY_DISABLED, SHADOWPLAY_SETTING_ID};
+use crate::nvapi::profiles::create_profile_if_not_exists;
+use crate::nvapi::session::{reload_settings, save_settings};
+use crate::nvapi::settings::set_dword_setting;
+use crate::nvapi::types::{BatchVerification, ModifiedBy};
+use crate::nvapi::verify::{self, verify_batch};
+use crate::transfer::{self, BlacklistFileEntry, ImportConflict};
+
+/// Emitted while a file is read and applied
+pub const NPI_IMPORT_PROGRESS_EVENT: &str = "npi-import-progress";
+
+/// Profiles read between progress events
+const PROGRESS_EVERY: u32 = 100;
+
+/// One setting of an exported profile
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NpiSetting {
+    pub id: u32,
+    pub name: Option<String>,
+    pub value: String,
+    /// Missing in exports from older NPI versions
+    pub value_type: Option<String>,
+}
+
+impl NpiSetting {
+    /// The value as a DWORD, or None for string and binary settings
+    pub fn dword(&self) -> Option<u32> {
+        if self.value_type.as_deref().is_some_and(|t| !t.eq_ignore_ascii_case("dword")) {
+            return None;
+        }
+        parse_number(&self.value)
+    }
+}
+
+/// One exported profile
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NpiProfile {
+    pub name: String,
+    pub executables: Vec<String>,
+    pub settings: Vec<NpiSetting>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NpiImportOptions {
+    /// Import every profile and every DWORD setting, not only capture settings
+    pub import_all: bool,
+    /// Write imported ShadowPlay values that conflict with changes made outside Nvidiot
+    pub take_imported_conflicts: bool,
+}
+
+/// Payload of the npi-import-progress event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NpiImportProgress {
+    pub profiles_read: u32,
+    pub profiles_matched: u32,
+    pub done: bool,
+}
+
+/// What happened to one imported profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NpiProfileResult {
+    pub profile_name: String,
+    pub created: bool,
+    pub executables: Vec<ExecutableStatus>,
+    pub settings_written: u32,
+    /// String, binary and (without `import_all`) non-catalog settings
+    pub settings_skipped: u32,
+    /// ShadowPlay values left alone because the executable was changed outside Nvidiot
+    pub conflicts: Vec<ImportConflict>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NpiImportReport {
+    pub profiles_read: u32,
+    pub profiles: Vec<NpiProfileResult>,
+    pub saved: bool,
+    pub save_error: Option<String>,
+    pub verification: Option<BatchVerification>,
+    /// Why the written ShadowPlay values couldn't be read back, if they couldn't
+    pub verification_error: Option<String>,
+    /// Disabled executables that couldn't be recorded as managed
+    pub manifest_errors: Vec<String>,
+}
+
+/// Decimal or 0x-prefixed hex
+pub fn parse_number(value: &str) -> Option<u32> {
+    let value = value.trim();
+    match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => value.parse().ok(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+}
+
+/// UTF-8 bytes of the input, transcoding UTF-16 as it is read
+struct Utf8Stream<R: BufRead> {
+    reader: R,
+    encoding: Encoding,
+    /// Encoded bytes of the last UTF-16 character not yet returned
+    pending: Vec<u8>,
+}
+
+impl<R: BufRead> Utf8Stream<R> {
+    fn new(mut reader: R) -> io::Result<Self> {
+        let (encoding, bom_len) = match reader.fill_buf()? {
+            [0xEF, 0xBB, 0xBF, ..] => (Encoding::Utf8, 3),
+            [0xFF, 0xFE, ..] => (Encoding::Utf16Le, 2),
+            [0xFE, 0xFF, ..] => (Encoding::Utf16Be, 2),
+            // UTF-16 without a BOM still starts with "<" and a zero byte
+            [b'<', 0, ..] => (Encoding::Utf16Le, 0),
+            [0, b'<', ..] => (Encoding::Utf16Be, 0),
+            _ => (Encoding::Utf8, 0),
+        };
+        reader.consume(bom_len);
+        Ok(Self { reader, encoding, pending: Vec::new() })
+    }
+
+    fn next_raw(&mut self) -> io::Result<Option<u8>> {
+        let byte = self.reader.fill_buf()?.first().copied();
+        if byte.is_some() {
+            self.reader.consume(1);
+        }
+        Ok(byte)
+    }
+
+    fn next_unit(&mut self) -> io::Result<Option<u16>> {
+        let (Some(a), Some(b)) = (self.next_raw()?, self.next_raw()?) else {
+            return Ok(None);
+        };
+        Ok(Some(match self.encoding {
+            Encoding::Utf16Be => u16::from_be_bytes([a, b]),
+            _ => u16::from_le_bytes([a, b]),
+        }))
+    }
+
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        if self.encoding == Encoding::Utf8 {
+            return self.next_raw();
+        }
+        if self.pending.is_empty() {
+            let Some(unit) = self.next_unit()? else {
+                return Ok(None);
+            };
+            let mut units = vec![unit];
+            if (0xD800..0xDC00).contains(&unit) {
+                units.extend(self.next_unit()?);
+            }
+            let c = char::decode_utf16(units).next()
+                .and_then(|r| r.ok())
+                .unwrap_or(char::REPLACEMENT_CHARACTER);
+            let mut buffer = [0u8; 4];
+            // Stored reversed so bytes can be popped in order
+            self.pending = c.encode_utf8(&mut buffer).bytes().rev().collect();
+        }
+        Ok(self.pending.pop())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum XmlEvent {
+    /// Lowercased local name
+    Start(String),
+    End(String),
+    Text(String),
+}
+
+/// Replace the predefined and numeric entities
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find(';') else {
+            break;
+        };
+        let entity = &rest[1..end];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X"))
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Lowercased element name without its namespace prefix
+fn local_name(tag: &str) -> String {
+    let name = tag.split(|c: char| c.is_whitespace() || c == '/').next().unwrap_or_default();
+    name.rsplit(':').next().unwrap_or_default().to_lowercase()
+}
+
+/// Pull tokenizer; only markup NPI writes is understood
+struct XmlReader<R: BufRead> {
+    input: Utf8Stream<R>,
+    peeked: Option<u8>,
+    pending_end: Option<String>,
+}
+
+impl<R: BufRead> XmlReader<R> {
+    fn new(reader: R) -> io::Result<Self> {
+        Ok(Self { input: Utf8Stream::new(reader)?, peeked: None, pending_end: None })
+    }
+
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        match self.peeked.take() {
+            Some(byte) => Ok(Some(byte)),
+            None => self.input.next_byte(),
+        }
+    }
+
+    fn next_event(&mut self) -> Result<Option<XmlEvent>, String> {
+        if let Some(name) = self.pending_end.take() {
+            return Ok(Some(XmlEvent::End(name)));
+        }
+        loop {
+            let Some(first) = self.next_byte().map_err(|e| e.to_string())? else {
+                return Ok(None);
+            };
+
+            if first != b'<' {
+                let mut text = vec![first];
+                while let Some(byte) = self.next_byte().map_err(|e| e.to_string())? {
+                    if byte == b'<' {
+                        self.peeked = Some(byte);
+                        break;
+                    }
+                    text.push(byte);
+                }
+                return Ok(Some(XmlEvent::Text(decode_entities(&String::from_utf8_lossy(&text)))));
+            }
+
+            let mut tag = Vec::new();
+            loop {
+                let byte = self.next_byte().map_err(|e| e.to_string())?
+                    .ok_or("The file ends inside a tag")?;
+                if byte == b'>' {
+                    // Comments and CDATA may contain '>'
+                    let open = (tag.starts_with(b"!--") && !tag.ends_with(b"--"))
+                        || (tag.starts_with(b"![CDATA[") && !tag.ends_with(b"]]"));
+                    if !open {
+                        break;
+                    }
+                }
+                tag.push(byte);
+            }
+            let tag = String::from_utf8_lossy(&tag).into_owned();
+
+            if let Some(cdata) = tag.strip_prefix("![CDATA[") {
+                return Ok(Some(XmlEvent::Text(cdata.trim_end_matches("]]").to_string())));
+            }
+            if tag.starts_with('?') || tag.starts_with('!') {
+                continue;
+            }
+            if let Some(name) = tag.strip_prefix('/') {
+                return Ok(Some(XmlEvent::End(local_name(name))));
+            }
+            let name = local_name(&tag);
+            if tag.trim_end().ends_with('/') {
+                self.pending_end = Some(name.clone());
+            }
+            return Ok(Some(XmlEvent::Start(name)));
+        }
+    }
+}
+
+/// Read profiles from an NPI export one at a time, handing each to
+/// `on_profile`. Returns how many were read.
+pub fn read_profiles(reader: impl BufRead, mut on_profile: impl FnMut(NpiProfile)) -> Result<u32, String> {
+    let mut xml = XmlReader::new(reader).map_err(|e| e.to_string())?;
+    let mut stack: Vec<String> = Vec::new();
+    let mut text = String::new();
+    let mut profile: Option<NpiProfile> = None;
+    let mut setting: Option<NpiSetting> = None;
+    let mut count = 0;
+
+    while let Some(event) = xml.next_event()? {
+        match event {
+            XmlEvent::Start(name) => {
+                match name.as_str() {
+                    "profile" => profile = Some(NpiProfile::default()),
+                    "profilesetting" => setting = Some(NpiSetting::default()),
+                    _ => {}
+                }
+                stack.push(name);
+                text.clear();
+            }
+            XmlEvent::Text(t) => text.push_str(&t),
+            XmlEvent::End(name) => {
+                stack.pop();
+                let parent = stack.last().map(String::as_str);
+                let value = text.trim().to_string();
+                text.clear();
+
+                match (name.as_str(), parent) {
+                    ("profilename", Some("profile")) => {
+                        if let Some(p) = profile.as_mut() {
+                            p.name = value;
+                        }
+                    }
+                    ("string", Some("executeables" | "executables")) => {
+                        if let Some(p) = profile.as_mut().filter(|_| !value.is_empty()) {
+                            p.executables.push(value);
+                        }
+                    }
+                    ("settingid", _) => {
+                        if let Some(s) = setting.as_mut() {
+                            s.id = parse_number(&value).unwrap_or(0);
+                        }
+                    }
+                    ("settingvalue", _) => {
+                        if let Some(s) = setting.as_mut() {
+                            s.value = value;
+                        }
+                    }
+                    ("valuetype", _) => {
+                        if let Some(s) = setting.as_mut() {
+                            s.value_type = Some(value);
+                        }
+                    }
+                    ("settingnameinfo", _) => {
+                        if let Some(s) = setting.as_mut() {
+                            s.name = (!value.is_empty()).then_some(value);
+                        }
+                    }
+                    ("profilesetting", _) => {
+                        if let (Some(p), Some(s)) = (profile.as_mut(), setting.take()) {
+                            if s.id != 0 {
+                                p.settings.push(s);
+                            }
+                        }
+                    }
+                    ("profile", _) => {
+                        if let Some(p) = profile.take() {
+                            count += 1;
+                            on_profile(p);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+    // A file cut between tags still parses; the open elements give it away
+    if let Some(open) = stack.last() {
+        return Err(format!("The file ends before <{}> is closed", open));
+    }
+    Ok(count)
+}
+
+fn is_catalog_setting(id: u32) -> bool {
+    CAPTURE_SETTINGS.iter().any(|&(known, _)| known == id)
+}
+
+/// DWORD settings to write, and how many others were skipped
+fn settings_to_write(profile: &NpiProfile, import_all: bool) -> (Vec<(u32, u32)>, u32) {
+    let writable: Vec<(u32, u32)> = profile.settings.iter()
+        .filter(|s| import_all || is_catalog_setting(s.id))
+        .filter_map(|s| s.dword().map(|value| (s.id, value)))
+        .collect();
+    let skipped = (profile.settings.len() - writable.len()) as u32;
+    (writable, skipped)
+}
+
+fn in_profile(status: &AttachStatus) -> bool {
+    matches!(status, AttachStatus::Attached | AttachStatus::AlreadyAttached)
+}
+
+/// Stage one profile; ShadowPlay writes are added to `written` as (executable, value)
+fn stage_profile(profile: &NpiProfile, writes: Vec<(u32, u32)>, skipped: u32, options: &NpiImportOptions, written: &mut Vec<(String, u32)>) -> NpiProfileResult {
+    let mut result = NpiProfileResult {
+        profile_name: profile.name.clone(),
+        created: false,
+        executables: Vec::new(),
+        settings_written: 0,
+        settings_skipped: skipped,
+        conflicts: Vec::new(),
+        errors: Vec::new(),
+    };
+    if profile.name.is_empty() {
+        result.errors.push("The profile has no name".to_string());
+        return result;
+    }
+    let handle = match create_profile_if_not_exists(&profile.name) {
+        Ok((handle, created)) => {
+            result.created = created;
+            handle
+        }
+        Err(e) => {
+            result.errors.push(e.to_string());
+            return result;
+        }
+    };
+
+    result.executables = profile.executables.iter()
+        .map(|executable| ExecutableStatus {
+            executable: executable.clone(),
+            status: games::attach(handle, executable, &profile.name),
+        })
+        .collect();
+    let attached: Vec<&str> = result.executables.iter()
+        .filter(|e| in_profile(&e.status))
+        .map(|e| e.executable.as_str())
+        .collect();
+
+    for (id, value) in writes {
+        if id == SHADOWPLAY_SETTING_ID && !options.take_imported_conflicts {
+            let targets: Vec<BlacklistFileEntry> = attached.iter()
+                .map(|executable| BlacklistFileEntry { executable: executable.to_string(), blacklisted: value == SHADOWPLAY_DISABLED })
+                .collect();
+            match transfer::find_conflicts(&targets) {
+                Ok(conflicts) if conflicts.is_empty() => {}
+                Ok(conflicts) => {
+                    result.conflicts = conflicts;
+                    result.settings_skipped += 1;
+                    continue;
+                }
+                Err(e) => {
+                    result.errors.push(e);
+                    continue;
+                }
+            }
+        }
+        match set_dword_setting(handle, id, value) {
+            Ok(()) => {
+                result.settings_written += 1;
+                if id == SHADOWPLAY_SETTING_ID {
+                    written.extend(attached.iter().map(|executable| (executable.to_string(), value)));
+                }
+            }
+            Err(e) => result.errors.push(format!("Setting 0x{:08X}: {}", id, e)),
+        }
+    }
+    result
+}
+
+/// Import an NPI full-configuration export with one save
+pub fn import_npi_config(app: &AppHandle, path: &str, options: &NpiImportOptions) -> Result<NpiImportReport, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let mut profiles = Vec::new();
+    let mut written = Vec::new();
+    let mut read = 0;
+
+    read_profiles(BufReader::new(file), |profile| {
+        read += 1;
+        let (writes, skipped) = settings_to_write(&profile, options.import_all);
+        if options.import_all || !writes.is_empty() {
+            profiles.push(stage_profile(&profile, writes, skipped, options, &mut written));
+        }
+        if read % PROGRESS_EVERY == 0 {
+            let _ = app.emit(NPI_IMPORT_PROGRESS_EVENT, NpiImportProgress {
+                profiles_read: read,
+                profiles_matched: profiles.len() as u32,
+                done: false,
+            });
+        }
+    })
+    .inspect_err(|_| {
+        // Don't leave half a file in the session
+        let _ = reload_settings();
+    })?;
+
+    let _ = app.emit(NPI_IMPORT_PROGRESS_EVENT, NpiImportProgress {
+        profiles_read: read,
+        profiles_matched: profiles.len() as u32,
+        done: true,
+    });
+
+    let mut report = NpiImportReport {
+        profiles_read: read,
+        profiles,
+        saved: false,
+        save_error: None,
+        verification: None,
+        verification_error: None,
+        manifest_errors: Vec::new(),
+    };
+    let staged = report.profiles.iter().any(|p| p.created || p.settings_written > 0)
+        || report.profiles.iter().flat_map(|p| &p.executables).any(|e| e.status == AttachStatus::Attached);
+    if staged {
+        if let Err(e) = save_settings() {
+            let _ = reload_settings();
+            report.save_error = Some(e.to_string());
+            return Ok(report);
+        }
+        report.saved = true;
+    }
+
+    if !written.is_empty() {
+        match verify_batch(&written) {
+            Ok(verification) => {
+                verify::emit_summary(app, &verification);
+                report.verification = Some(verification);
+            }
+            Err(e) => report.verification_error = Some(e.to_string()),
+        }
+        for (executable, value) in &written {
+            if *value == SHADOWPLAY_DISABLED {
+                if let Err(e) = manifest::record_executable(executable, false, ModifiedBy::Import) {
+                    report.manifest_errors.push(format!("{}: {}", executable, e));
+                }
+            }
+        }
+        transfer::record_history(written.iter().map(|(executable, value)| (executable.clone(), *value == SHADOWPLAY_DISABLED)));
+    }
+
+    let failed = report.save_error.is_some()
+        || report.verification_error.is_some()
+        || !report.manifest_errors.is_empty()
+        || report.profiles.iter().any(|p| !p.errors.is_empty());
+    journal::append(JournalCategory::Import, journal::severity_for(failed), "npi.imported", serde_json::json!({
+        "profilesRead": report.profiles_read,
+        "profilesImported": report.profiles.len(),
+        "saved": report.saved,
+        "verificationError": report.verification_error,
+        "manifestErrors": report.manifest_errors,
+    }));
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// What NPI 2.x writes: UTF-16 with a BOM, ValueType on every setting
+    const CURRENT_EXPORT: &str = r#"<?xml version="1.0" encoding="utf-16"?>
+<ArrayOfProfile xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance" xmlns:xsd="http://www.w3.org/2001/XMLSchema">
+  <Profile>
+    <ProfileName>Some Game &amp; Friends</ProfileName>
+    <Executeables>
+      <string>game.exe</string>
+      <string>launcher.exe</string>
+    </Executeables>
+    <Settings>
+      <ProfileSetting>
+        <SettingNameInfo>ShadowPlay</SettingNameInfo>
+        <SettingID>2157797216</SettingID>
+        <SettingValue>268435456</SettingValue>
+        <ValueType>Dword</ValueType>
+      </ProfileSetting>
+      <ProfileSetting>
+        <SettingNameInfo />
+        <SettingID>0x10F9DC81</SettingID>
+        <SettingValue>C:\Games</SettingValue>
+        <ValueType>String</ValueType>
+      </ProfileSetting>
+    </Settings>
+  </Profile>
+  <Profile>
+    <ProfileName>Empty</ProfileName>
+    <Executeables />
+    <Settings />
+  </Profile>
+</ArrayOfProfile>"#;
+
+    /// Exports from before ValueType, with the later spelling and a namespace prefix
+    const OLD_EXPORT: &str = r#"<?xml version="1.0"?>
+<!-- exported by an early build -->
+<npi:ARRAYOFPROFILE xmlns:npi="urn:npi">
+  <npi:Profile>
+    <npi:ProfileName>Old Game</npi:ProfileName>
+    <npi:Executables><npi:string>old.exe</npi:string></npi:Executables>
+    <npi:Settings>
+      <npi:ProfileSetting>
+        <npi:SettingID>0x809D5F60</npi:SettingID>
+        <npi:SettingValue>0x10000000</npi:SettingValue>
+      </npi:ProfileSetting>
+    </npi:Settings>
+  </npi:Profile>
+</npi:ARRAYOFPROFILE>"#;
+
+    fn utf16le_with_bom(text: &str) -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(u16::to_le_bytes));
+        bytes
+    }
+
+    fn read(bytes: &[u8]) -> Result<Vec<NpiProfile>, String> {
+        let mut profiles = Vec::new();
+        let count = read_profiles(bytes, |p| profiles.push(p))?;
+        assert_eq!(count as usize, profiles.len());
+        Ok(profiles)
+    }
+
+    #[test]
+    fn reads_the_current_schema() {
+        let profiles = read(&utf16le_with_bom(CURRENT_EXPORT)).unwrap();
+        assert_eq!(profiles.len(), 2);
+
+        let game = &profiles[0];
+        assert_eq!(game.name, "Some Game & Friends");
+        assert_eq!(game.executables, ["game.exe", "launcher.exe"]);
+        assert_eq!(game.settings.len(), 2);
+        assert_eq!(game.settings[0].id, SHADOWPLAY_SETTING_ID);
+        assert_eq!(game.settings[0].name.as_deref(), Some("ShadowPlay"));
+        assert_eq!(game.settings[0].dword(), Some(SHADOWPLAY_DISABLED));
+        assert_eq!(game.settings[1].name, None);
+        assert_eq!(game.settings[1].dword(), None);
+
+        assert_eq!(profiles[1], NpiProfile { name: "Empty".to_string(), ..Default::default() });
+    }
+
+    #[test]
+    fn reads_the_old_schema() {
+        let profiles = read(OLD_EXPORT.as_bytes()).unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].name, "Old Game");
+        assert_eq!(profiles[0].executables, ["old.exe"]);
+        // Without a ValueType every value is a DWORD
+        assert_eq!(profiles[0].settings[0].value_type, None);
+        assert_eq!(profiles[0].settings[0].dword(), Some(SHADOWPLAY_DISABLED));
+    }
+
+    #[test]
+    fn refuses_a_truncated_file() {
+        let truncated = &CURRENT_EXPORT[..CURRENT_EXPORT.find("<ValueType").unwrap() + 4];
+        assert_eq!(read(truncated.as_bytes()).unwrap_err(), "The file ends inside a tag");
+    }
+
+    #[test]
+    fn refuses_a_file_cut_between_elements() {
+        let truncated = &CURRENT_EXPORT[..CURRENT_EXPORT.find("</SettingValue>").unwrap() + "</SettingValue>".len()];
+        assert_eq!(read(truncated.as_bytes()).unwrap_err(), "The file ends before <profilesetting> is closed");
+    }
+
+    #[test]
+    fn only_catalog_dwords_are_written_by_default() {
+        let profiles = read(CURRENT_EXPORT.as_bytes()).unwrap();
+        assert_eq!(settings_to_write(&profiles[0], false), (vec![(SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED)], 1));
+        assert_eq!(settings_to_write(&profiles[0], true), (vec![(SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED)], 1));
+    }
+
+    #[test]
+    fn numbers_are_decimal_or_hex() {
+        assert_eq!(parse_number("268435456"), Some(0x10000000));
+        assert_eq!(parse_number(" 0x10000000 "), Some(0x10000000));
+        assert_eq!(parse_number("0XfF"), Some(255));
+        assert_eq!(parse_number("-1"), None);
+        assert_eq!(parse_number("0x"), None);
+        assert_eq!(parse_number("4294967296"), None);
+    }
+}
@@ -2,15 +2,28 @@
 //!
 //! Handles enumerating and creating applications within profiles.
 
-use super::error::{NvApiError, NVAPI_OK, NVAPI_END_ENUMERATION, NVAPI_EXECUTABLE_NOT_FOUND};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use super::error::{NvApiError, NVAPI_OK, NVAPI_END_ENUMERATION, NVAPI_EXECUTABLE_NOT_FOUND, NVAPI_INCOMPATIBLE_STRUCT_VERSION};
+use super::health::checked;
 use super::ffi::{
-    get_nvapi, wchar_to_string, string_to_wchar,
-    NvDRSProfileHandle, NvdrsApplication, NvdrsProfile, NVDRS_APPLICATION_VER,
+    driver_version, get_nvapi, require_optional, wchar_to_string, string_to_wchar, with_application_batch,
+    NvDRSProfileHandle, NvdrsApplication, NvdrsApplicationV4, NvdrsProfile, NVDRS_APPLICATION_VER, NVDRS_PROFILE_VER,
+    NVDRS_APPLICATION_FLAG_COMMAND_LINE, NVDRS_APPLICATION_FLAG_METRO, NvApiFunction, NVAPI_UNICODE_STRING_MAX,
 };
-use super::session::get_session;
-use super::profiles::{enumerate_profiles, find_profile_by_name};
-use super::settings::get_shadowplay_status;
-use super::types::DrsApplication;
+use super::session::{generation, generation_started_at_ms, get_session, get_session_mut, is_read_only, record_mutation, with_session_held};
+use super::profiles::{enumerate_profiles, find_profile_by_name, get_profile_info_by_handle};
+use super::settings::{get_blacklist_origin, get_cached_blacklist_origin, get_cached_shadowplay_status, get_shadowplay_status};
+use super::partition::{self, CachedProfile, PredefinedPartition};
+use super::resolve::{basename, executable_key};
+use super::types::{
+    ApplicationCreated, ApplicationMatchMode, ApplicationSortField, CacheStats, DrsApplication, FeatureAvailability,
+    SignerMatchFallback,
+};
+use crate::journal::{self, JournalCategory, JournalSeverity};
 
 /// Enumerate applications in a specific profile
 #[cfg(target_os = "windows")]
@@ -35,16 +48,20 @@ pub fn enumerate_applications(profile_handle: NvDRSProfileHandle, profile_name:
         }
     };
 
+    // ShadowPlay is a profile-level setting, so look it up once for all apps
+    let profile_is_predefined = profile_info.is_predefined != 0;
+    let is_blacklisted = get_cached_shadowplay_status(profile_handle, profile_name, profile_is_predefined).unwrap_or(false);
+    let blacklist_origin = get_cached_blacklist_origin(profile_handle, profile_name, profile_is_predefined);
+
     let mut applications = Vec::new();
     let mut start_index: u32 = 0;
 
-    unsafe {
+    with_application_batch(|apps| unsafe {
         while start_index < num_apps {
             // Enumerate in batches
-            let mut apps: [NvdrsApplication; 32] = std::array::from_fn(|_| NvdrsApplication::default());
-            let mut count: u32 = 32;
+            let mut count = apps.len() as u32;
 
-            let status = enum_apps(session, profile_handle, start_index, &mut count, apps.as_mut_ptr());
+            let status = checked(enum_apps(session, profile_handle, start_index, &mut count, apps.as_mut_ptr()));
 
             if status == NVAPI_END_ENUMERATION || count == 0 {
                 break;
@@ -54,25 +71,22 @@ pub fn enumerate_applications(profile_handle: NvDRSProfileHandle, profile_name:
                 break;
             }
 
-            for i in 0..(count as usize) {
-                let app = &apps[i];
-                let executable = wchar_to_string(&app.app_name);
-
-                // Check blacklist status for this app
-                let is_blacklisted = get_shadowplay_status(profile_handle).unwrap_or(false);
-
+            for app in &apps[..count as usize] {
                 applications.push(DrsApplication {
                     name: wchar_to_string(&app.user_friendly_name),
-                    executable: executable.clone(),
+                    executable: wchar_to_string(&app.app_name),
                     profile_name: profile_name.to_string(),
                     is_predefined: app.is_predefined != 0,
                     is_blacklisted,
+                    blacklist_origin,
+                    provenance: None,
+                    match_mode: None,
                 });
             }
 
             start_index += count;
         }
-    }
+    });
 
     Ok(applications)
 }
@@ -83,14 +97,44 @@ pub fn enumerate_applications(_profile_handle: NvDRSProfileHandle, _profile_name
 }
 
 /// Get all applications across all profiles
-/// This returns ALL profiles as searchable entries, plus any explicitly registered applications
-#[cfg(target_os = "windows")]
+/// This returns ALL profiles as searchable entries, plus any explicitly registered applications.
+/// Predefined profiles come from the partition cache when it is still valid.
 pub fn get_all_applications() -> Result<Vec<DrsApplication>, NvApiError> {
-    use super::ffi::{get_nvapi, NvdrsProfile, wchar_to_string};
-    use super::session::get_session;
-    use super::error::NVAPI_OK;
-    use super::settings::get_shadowplay_status;
+    get_all_applications_partitioned(false)
+}
 
+/// Same entries as `get_all_applications`, grouped by profile name.
+/// Each group keeps the enumeration order.
+pub fn get_all_applications_grouped() -> Result<HashMap<String, Vec<DrsApplication>>, NvApiError> {
+    Ok(group_by_profile(get_all_applications()?))
+}
+
+fn group_by_profile(apps: Vec<DrsApplication>) -> HashMap<String, Vec<DrsApplication>> {
+    let mut grouped: HashMap<String, Vec<DrsApplication>> = HashMap::new();
+    for app in apps {
+        grouped.entry(app.profile_name.clone()).or_default().push(app);
+    }
+    grouped
+}
+
+/// When the oldest cache behind `get_all_applications` was built: the
+/// predefined partition, or the profile statuses of the current generation
+pub fn applications_cache_built_at_ms() -> u64 {
+    let statuses_since = generation_started_at_ms();
+    partition::built_at_ms()
+        .filter(|&built_at| built_at > 0)
+        .map_or(statuses_since, |built_at| built_at.min(statuses_since))
+}
+
+/// Re-enumerate every predefined profile and rebuild the partition cache.
+/// Returns the number of predefined entries cached.
+pub fn refresh_predefined_cache() -> Result<usize, NvApiError> {
+    get_all_applications_partitioned(true)?;
+    Ok(partition::cached_application_count())
+}
+
+#[cfg(target_os = "windows")]
+fn get_all_applications_partitioned(force_full: bool) -> Result<Vec<DrsApplication>, NvApiError> {
     let api = get_nvapi()?;
     let session = get_session()?;
 
@@ -99,15 +143,18 @@ pub fn get_all_applications() -> Result<Vec<DrsApplication>, NvApiError> {
     let get_profile_info = api.drs_get_profile_info
         .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_GetProfileInfo".to_string()))?;
 
-    let mut all_apps = Vec::new();
+    // Header pass: cheap, and tells us whether the cached partition still fits
+    let mut headers: Vec<(NvDRSProfileHandle, String, bool, u32)> = Vec::new();
     let mut index: u32 = 0;
+    // Reused across iterations; only the version needs resetting before each call
+    let mut profile_info = NvdrsProfile::default();
 
     unsafe {
         loop {
-            let mut profile_handle: super::ffi::NvDRSProfileHandle = std::ptr::null_mut();
-            let status = enum_profiles(session, index, &mut profile_handle);
+            let mut profile_handle: NvDRSProfileHandle = std::ptr::null_mut();
+            let status = checked(enum_profiles(session, index, &mut profile_handle));
 
-            if status == super::error::NVAPI_END_ENUMERATION {
+            if status == NVAPI_END_ENUMERATION {
                 break;
             }
             if status != NVAPI_OK {
@@ -115,50 +162,243 @@ pub fn get_all_applications() -> Result<Vec<DrsApplication>, NvApiError> {
                 continue;
             }
 
-            // Get profile info
-            let mut profile_info = NvdrsProfile::default();
-            let status = get_profile_info(session, profile_handle, &mut profile_info);
+            profile_info.version = NVDRS_PROFILE_VER;
+            if get_profile_info(session, profile_handle, &mut profile_info) == NVAPI_OK {
+                headers.push((
+                    profile_handle,
+                    wchar_to_string(&profile_info.profile_name),
+                    profile_info.is_predefined != 0,
+                    profile_info.num_of_apps,
+                ));
+            }
 
-            if status == NVAPI_OK {
-                let profile_name = wchar_to_string(&profile_info.profile_name);
-                let is_predefined = profile_info.is_predefined != 0;
-                let is_blacklisted = get_shadowplay_status(profile_handle).unwrap_or(false);
+            index += 1;
+        }
+    }
 
-                if profile_info.num_of_apps > 0 {
-                    // Profile has registered applications - enumerate them
-                    if let Ok(apps) = enumerate_applications(profile_handle, &profile_name) {
-                        all_apps.extend(apps);
-                    }
-                } else {
-                    // Profile has no registered apps - add the profile itself as an entry
-                    // Use profile name as both name and executable (common pattern for game profiles)
-                    all_apps.push(DrsApplication {
-                        name: profile_name.clone(),
-                        executable: profile_name.clone(),
-                        profile_name: profile_name,
-                        is_predefined,
-                        is_blacklisted,
-                    });
+    let driver_version = driver_version().unwrap_or(0);
+    let predefined_count = headers.iter().filter(|h| h.2).count() as u32;
+    let mut cached = if force_full {
+        None
+    } else {
+        partition::take_valid(driver_version, predefined_count)
+    };
+    let cached_built_at_ms = cached.as_ref().map(|c| c.built_at_ms);
+    let mut rebuilt = PredefinedPartition {
+        driver_version,
+        profile_count: predefined_count,
+        profiles: HashMap::new(),
+        built_at_ms: 0,
+    };
+    let mut changed = cached.is_none();
+
+    let mut all_apps = Vec::new();
+    for (profile_handle, profile_name, is_predefined, num_of_apps) in headers {
+        let is_blacklisted = get_cached_shadowplay_status(profile_handle, &profile_name, is_predefined).unwrap_or(false);
+        let blacklist_origin = get_cached_blacklist_origin(profile_handle, &profile_name, is_predefined);
+
+        if num_of_apps == 0 {
+            // Profile has no registered apps - add the profile itself as an entry
+            // Use profile name as both name and executable (common pattern for game profiles)
+            all_apps.push(DrsApplication {
+                name: profile_name.clone(),
+                executable: profile_name.clone(),
+                profile_name,
+                is_predefined,
+                is_blacklisted,
+                blacklist_origin,
+                provenance: None,
+                match_mode: None,
+            });
+            continue;
+        }
+
+        if !is_predefined {
+            if let Ok(apps) = enumerate_applications(profile_handle, &profile_name) {
+                all_apps.extend(apps);
+            }
+            continue;
+        }
+
+        let reused = cached.as_mut()
+            .and_then(|c| c.profiles.remove(&profile_name))
+            .filter(|c| c.application_count == num_of_apps);
+        let entry = match reused {
+            Some(mut entry) => {
+                // Blacklist status is a live setting, not part of the cached structure
+                for app in &mut entry.applications {
+                    app.is_blacklisted = is_blacklisted;
+                    app.blacklist_origin = blacklist_origin;
                 }
+                entry
+            }
+            None => {
+                changed = true;
+                let Ok(applications) = enumerate_applications(profile_handle, &profile_name) else {
+                    continue;
+                };
+                CachedProfile { application_count: num_of_apps, applications }
             }
+        };
 
-            index += 1;
-        }
+        all_apps.extend(entry.applications.iter().cloned());
+        rebuilt.profiles.insert(profile_name, entry);
     }
 
+    // Cached profiles that were never matched mean the set changed under us
+    changed |= cached.is_some_and(|c| !c.profiles.is_empty());
+    rebuilt.built_at_ms = match cached_built_at_ms {
+        // Partitions saved before the timestamp existed read as 0
+        Some(built_at) if !changed && built_at > 0 => built_at,
+        _ => crate::manifest::now_ms(),
+    };
+    partition::store(rebuilt, changed);
+
     Ok(all_apps)
 }
 
 #[cfg(not(target_os = "windows"))]
-pub fn get_all_applications() -> Result<Vec<DrsApplication>, NvApiError> {
+fn get_all_applications_partitioned(_force_full: bool) -> Result<Vec<DrsApplication>, NvApiError> {
     Err(NvApiError::NotSupported)
 }
 
-/// Find an application by executable name
+/// Count the entries `get_all_applications` lists, from profile headers alone
+/// without enumerating each profile's applications. A profile with no
+/// applications counts once, for the entry standing in for the profile itself.
+pub fn get_total_application_count() -> Result<u32, NvApiError> {
+    Ok(enumerate_profiles()?.iter().map(|p| p.application_count.max(1)).sum())
+}
+
+/// Get all applications, optionally reading ShadowPlay status per application.
+/// The fast path reports the cached profile-level status. With `include_settings`,
+/// each executable is resolved the way the driver would and its profile's setting
+/// is read uncached, at the cost of a few NVAPI calls per application.
+pub fn get_all_applications_detailed(include_settings: bool) -> Result<Vec<DrsApplication>, NvApiError> {
+    let mut applications = get_all_applications()?;
+    if !include_settings {
+        return Ok(applications);
+    }
+
+    for app in &mut applications {
+        // Profile-only entries have no executable to resolve; keep the profile status
+        let Ok((profile_handle, _)) = find_application(&app.executable) else {
+            continue;
+        };
+        if let Ok(disabled) = get_shadowplay_status(profile_handle) {
+            app.is_blacklisted = disabled;
+        }
+    }
+
+    Ok(applications)
+}
+
+/// List a profile's applications in a stable order instead of driver order.
+/// Booleans sort false first, so descending puts blacklisted or predefined entries first.
+pub fn list_applications_sorted(
+    profile_name: &str,
+    sort_by: ApplicationSortField,
+    ascending: bool,
+) -> Result<Vec<DrsApplication>, NvApiError> {
+    let profile_handle = find_profile_by_name(profile_name)?;
+    let mut applications = enumerate_applications(profile_handle, profile_name)?;
+
+    applications.sort_by(|a, b| {
+        let ordering = match sort_by {
+            ApplicationSortField::ExecutableName => a.executable.to_lowercase().cmp(&b.executable.to_lowercase()),
+            ApplicationSortField::FriendlyName => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            ApplicationSortField::BlacklistStatus => a.is_blacklisted.cmp(&b.is_blacklisted),
+            ApplicationSortField::IsPredefined => a.is_predefined.cmp(&b.is_predefined),
+        };
+        let ordering = if ascending { ordering } else { ordering.reverse() };
+        // Break ties by executable so equal keys don't fall back to driver order
+        ordering.then_with(|| a.executable.to_lowercase().cmp(&b.executable.to_lowercase()))
+    });
+
+    Ok(applications)
+}
+
+/// How long a miss answers repeated lookups; a generation bump drops it sooner
+const NEGATIVE_LOOKUP_TTL: Duration = Duration::from_secs(30);
+
+/// Misses kept before the cache is cleared and refilled
+const NEGATIVE_LOOKUP_LIMIT: usize = 4096;
+
+/// Executables FindApplicationByName reported missing, keyed by the exact name
+/// looked up, since some drivers match case-sensitively. Browsers and chat apps
+/// are checked on every enumeration and focus change and never have profiles.
+/// Entries only count for the generation they were recorded in, so creating an
+/// application, reloading or any other mutation makes them stale at once.
+static NEGATIVE_LOOKUPS: Lazy<Mutex<HashMap<String, (u64, Instant)>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static LOOKUP_HITS: AtomicU64 = AtomicU64::new(0);
+static LOOKUP_MISSES: AtomicU64 = AtomicU64::new(0);
+static NEGATIVE_LOOKUP_HITS: AtomicU64 = AtomicU64::new(0);
+
+fn is_known_missing(executable: &str) -> bool {
+    // Backup sessions share the generation counter with the live one; don't mix their answers
+    if is_read_only() {
+        return false;
+    }
+    let current = generation();
+    NEGATIVE_LOOKUPS.lock().unwrap()
+        .get(executable)
+        .is_some_and(|&(recorded_generation, recorded_at)| {
+            recorded_generation == current && recorded_at.elapsed() < NEGATIVE_LOOKUP_TTL
+        })
+}
+
+fn remember_missing(executable: &str, generation: u64) {
+    if is_read_only() {
+        return;
+    }
+    let mut lookups = NEGATIVE_LOOKUPS.lock().unwrap();
+    if lookups.len() >= NEGATIVE_LOOKUP_LIMIT {
+        lookups.clear();
+    }
+    lookups.insert(executable.to_string(), (generation, Instant::now()));
+}
+
+/// Hit/miss counters and size of the negative application lookup cache.
+/// Hits and misses count FindApplicationByName calls that found or missed the executable.
+pub fn negative_lookup_cache_stats() -> CacheStats {
+    let current = generation();
+    let lookups = NEGATIVE_LOOKUPS.lock().unwrap();
+    let entries = lookups.values()
+        .filter(|&&(recorded_generation, recorded_at)| {
+            recorded_generation == current && recorded_at.elapsed() < NEGATIVE_LOOKUP_TTL
+        })
+        .count() as u64;
+    let approx_bytes = lookups.keys()
+        .map(|key| (std::mem::size_of::<(String, (u64, Instant))>() + key.len()) as u64)
+        .sum();
+
+    CacheStats {
+        name: "application-negative-lookups".to_string(),
+        hits: LOOKUP_HITS.load(Ordering::Relaxed),
+        misses: LOOKUP_MISSES.load(Ordering::Relaxed),
+        negative_hits: NEGATIVE_LOOKUP_HITS.load(Ordering::Relaxed),
+        entries,
+        approx_bytes,
+    }
+}
+
+/// Drop every remembered miss
+pub fn clear_negative_lookup_cache() {
+    NEGATIVE_LOOKUPS.lock().unwrap().clear();
+}
+
+/// Find an application by executable name. Recent misses are answered from
+/// the negative lookup cache without calling NVAPI.
 #[cfg(target_os = "windows")]
 pub fn find_application(executable: &str) -> Result<(NvDRSProfileHandle, NvdrsApplication), NvApiError> {
+    if is_known_missing(executable) {
+        NEGATIVE_LOOKUP_HITS.fetch_add(1, Ordering::Relaxed);
+        return Err(NvApiError::ApplicationNotFound(executable.to_string()));
+    }
+
     let api = get_nvapi()?;
     let session = get_session()?;
+    // Taken before the lookup, so a mutation made meanwhile makes the miss stale
+    let lookup_generation = generation();
 
     let find_fn = api.drs_find_application_by_name
         .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_FindApplicationByName".to_string()))?;
@@ -170,15 +410,18 @@ pub fn find_application(executable: &str) -> Result<(NvDRSProfileHandle, NvdrsAp
         let mut profile_handle: NvDRSProfileHandle = std::ptr::null_mut();
         let mut app = NvdrsApplication::default();
 
-        let status = find_fn(session, wide_name.as_ptr(), &mut profile_handle, &mut app);
+        let status = checked(find_fn(session, wide_name.as_ptr(), &mut profile_handle, &mut app));
 
         if status == NVAPI_EXECUTABLE_NOT_FOUND {
+            LOOKUP_MISSES.fetch_add(1, Ordering::Relaxed);
+            remember_missing(executable, lookup_generation);
             return Err(NvApiError::ApplicationNotFound(executable.to_string()));
         }
         if status != NVAPI_OK {
             return Err(NvApiError::NvApiStatus(status));
         }
 
+        LOOKUP_HITS.fetch_add(1, Ordering::Relaxed);
         Ok((profile_handle, app))
     }
 }
@@ -188,12 +431,266 @@ pub fn find_application(_executable: &str) -> Result<(NvDRSProfileHandle, NvdrsA
     Err(NvApiError::NotSupported)
 }
 
-/// Create a new application in a profile
+/// How long a full-scan index serves consecutive case-insensitive lookups
+const SCAN_INDEX_TTL: Duration = Duration::from_secs(10);
+
+/// Every application's profile handle and exact DRS name, from one full scan.
+/// Names rather than whole NvdrsApplication entries (about 12 KB each) keep it small.
+struct ScanIndex {
+    built_at: Instant,
+    generation: u64,
+    entries: Vec<(NvDRSProfileHandle, String)>,
+}
+
+// SAFETY: the handles are only passed back to NVAPI under the session lock,
+// and the index is dropped whenever the session generation changes
+unsafe impl Send for ScanIndex {}
+
+static SCAN_INDEX: Lazy<Mutex<Option<ScanIndex>>> = Lazy::new(|| Mutex::new(None));
+
+/// Call `f` with every application in every profile, as raw NVAPI entries
 #[cfg(target_os = "windows")]
-pub fn create_application(profile_handle: NvDRSProfileHandle, executable: &str, friendly_name: &str) -> Result<(), NvApiError> {
+fn for_each_application_internal(mut f: impl FnMut(NvDRSProfileHandle, &NvdrsApplication)) -> Result<(), NvApiError> {
     let api = get_nvapi()?;
     let session = get_session()?;
 
+    let enum_profiles = api.drs_enum_profiles
+        .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_EnumProfiles".to_string()))?;
+    let enum_apps = api.drs_enum_applications
+        .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_EnumApplications".to_string()))?;
+
+    let mut index: u32 = 0;
+    unsafe {
+        loop {
+            let mut profile_handle: NvDRSProfileHandle = std::ptr::null_mut();
+            let status = checked(enum_profiles(session, index, &mut profile_handle));
+            if status == NVAPI_END_ENUMERATION {
+                break;
+            }
+            index += 1;
+            if status != NVAPI_OK {
+                continue;
+            }
+
+            with_application_batch(|apps| {
+                let mut start_index: u32 = 0;
+                loop {
+                    let mut count = apps.len() as u32;
+                    let status = checked(enum_apps(session, profile_handle, start_index, &mut count, apps.as_mut_ptr()));
+                    if status != NVAPI_OK || count == 0 {
+                        break;
+                    }
+                    for app in &apps[..count as usize] {
+                        f(profile_handle, app);
+                    }
+                    start_index += count;
+                }
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn for_each_application_internal(_f: impl FnMut(NvDRSProfileHandle, &NvdrsApplication)) -> Result<(), NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Exact DRS name of an application matching `executable` case-insensitively,
+/// using a recent full scan when there is one
+fn scan_for_application(executable: &str) -> Result<Option<String>, NvApiError> {
+    let key = executable_key(executable);
+    let mut index = SCAN_INDEX.lock().unwrap();
+
+    let fresh = index.as_ref()
+        .is_some_and(|i| i.generation == generation() && i.built_at.elapsed() < SCAN_INDEX_TTL);
+    if !fresh {
+        let mut entries = Vec::new();
+        for_each_application_internal(|handle, app| entries.push((handle, wchar_to_string(&app.app_name))))?;
+        *index = Some(ScanIndex { built_at: Instant::now(), generation: generation(), entries });
+    }
+
+    Ok(index.as_ref()
+        .and_then(|i| i.entries.iter().find(|(_, name)| executable_key(name) == key))
+        .map(|(_, name)| name.clone()))
+}
+
+/// Find an application by executable name, ignoring case. Some drivers match
+/// FindApplicationByName case-sensitively, so a miss falls back to a full scan
+/// and retries with the name as DRS stores it.
+pub fn find_application_case_insensitive(executable: &str) -> Result<(NvDRSProfileHandle, NvdrsApplication), NvApiError> {
+    match find_application(executable) {
+        Err(NvApiError::ApplicationNotFound(_)) => {}
+        result => return result,
+    }
+
+    match scan_for_application(executable)? {
+        Some(stored) if stored != executable => find_application(&stored),
+        _ => Err(NvApiError::ApplicationNotFound(executable.to_string())),
+    }
+}
+
+/// Get a fully-populated application entry for an executable
+pub fn get_application_info(executable: &str) -> Result<DrsApplication, NvApiError> {
+    let (profile_handle, app) = find_application(executable)?;
+    let profile = get_profile_info_by_handle(profile_handle)?;
+    let blacklist_origin = get_blacklist_origin(profile_handle, profile.is_predefined)?;
+
+    Ok(DrsApplication {
+        name: wchar_to_string(&app.user_friendly_name),
+        executable: wchar_to_string(&app.app_name),
+        profile_name: profile.name,
+        is_predefined: app.is_predefined != 0,
+        is_blacklisted: blacklist_origin.is_some(),
+        blacklist_origin,
+        provenance: None,
+        match_mode: None,
+    })
+}
+
+/// Capability entry for reading the v4 match fields
+pub const MATCH_FIELDS_FEATURE: &str = "application-match-fields";
+
+/// Whether the driver accepts NVDRS_APPLICATION_V4: 0 not probed yet, 1 yes, 2 no
+static MATCH_FIELDS_SUPPORT: AtomicU8 = AtomicU8::new(0);
+
+/// What an entry is matched on, from the v4 flags and fields
+pub fn match_mode_of(app: &NvdrsApplicationV4) -> ApplicationMatchMode {
+    if app.flags & NVDRS_APPLICATION_FLAG_METRO != 0 {
+        ApplicationMatchMode::Metro
+    } else if app.flags & NVDRS_APPLICATION_FLAG_COMMAND_LINE != 0 || app.command_line[0] != 0 {
+        ApplicationMatchMode::CommandLine
+    } else if app.file_in_folder[0] != 0 {
+        ApplicationMatchMode::FileInFolder
+    } else {
+        ApplicationMatchMode::Name
+    }
+}
+
+/// Look an executable up with the v4 structure. None when it isn't found or
+/// the driver rejects the structure version, which is remembered for the session.
+#[cfg(target_os = "windows")]
+fn find_application_v4(executable: &str) -> Option<NvdrsApplicationV4> {
+    if MATCH_FIELDS_SUPPORT.load(Ordering::Relaxed) == 2 {
+        return None;
+    }
+    let api = get_nvapi().ok()?;
+    let session = get_session().ok()?;
+    let find_fn = api.drs_find_application_by_name?;
+
+    let mut wide_name = [0u16; NVAPI_UNICODE_STRING_MAX];
+    string_to_wchar(executable, &mut wide_name);
+    let mut profile_handle: NvDRSProfileHandle = std::ptr::null_mut();
+    let mut app = NvdrsApplicationV4::default();
+
+    // The v4 struct starts with the older layout, so the same entry point
+    // fills it once the version field says v4
+    let status = unsafe {
+        checked(find_fn(session, wide_name.as_ptr(), &mut profile_handle, &mut app as *mut NvdrsApplicationV4 as *mut NvdrsApplication))
+    };
+    match status {
+        NVAPI_INCOMPATIBLE_STRUCT_VERSION => {
+            MATCH_FIELDS_SUPPORT.store(2, Ordering::Relaxed);
+            None
+        }
+        NVAPI_OK => {
+            MATCH_FIELDS_SUPPORT.store(1, Ordering::Relaxed);
+            Some(app)
+        }
+        // Drivers check the version before the lookup, so a miss still proves support
+        NVAPI_EXECUTABLE_NOT_FOUND => {
+            MATCH_FIELDS_SUPPORT.store(1, Ordering::Relaxed);
+            None
+        }
+        _ => None,
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn find_application_v4(_executable: &str) -> Option<NvdrsApplicationV4> {
+    None
+}
+
+/// How DRS matches an executable's entry; None when the driver lacks the v4 fields
+pub fn get_application_match_mode(executable: &str) -> Option<ApplicationMatchMode> {
+    find_application_v4(executable).map(|app| match_mode_of(&app))
+}
+
+/// Whether match modes can be read, probing the driver on first use
+pub fn match_fields_capability() -> FeatureAvailability {
+    if MATCH_FIELDS_SUPPORT.load(Ordering::Relaxed) == 0 {
+        // Any lookup settles the question; the name needn't exist
+        let _ = find_application_v4("nvidiot-match-fields-probe.exe");
+    }
+    FeatureAvailability {
+        feature: MATCH_FIELDS_FEATURE.to_string(),
+        available: MATCH_FIELDS_SUPPORT.load(Ordering::Relaxed) == 1,
+        missing_functions: Vec::new(),
+    }
+}
+
+/// Look up many executables with a single pass over DRS, holding the session
+/// lock once for the whole pass. Executables are matched by basename,
+/// case-insensitively. An executable listed by several profiles gets every
+/// match, in enumeration order; ones not in DRS are omitted.
+pub fn join_executables(executables: &[String]) -> Result<HashMap<String, Vec<DrsApplication>>, NvApiError> {
+    let all = with_session_held(get_all_applications)?;
+    let mut index: HashMap<String, Vec<DrsApplication>> = HashMap::new();
+    for app in all {
+        index.entry(executable_key(basename(&app.executable))).or_default().push(app);
+    }
+
+    Ok(executables.iter()
+        .filter_map(|exe| {
+            let apps = index.get(&executable_key(basename(exe)))?;
+            Some((exe.clone(), apps.clone()))
+        })
+        .collect())
+}
+
+/// Why an entry asked to match on a signer is matched by name instead.
+/// NVDRS_APPLICATION_V4 has no signer or publisher field, so even a driver
+/// with the v4 match fields can only match such an entry by name.
+pub fn signer_fallback(match_fields_available: bool) -> SignerMatchFallback {
+    if match_fields_available {
+        SignerMatchFallback::NoSignerField
+    } else {
+        SignerMatchFallback::MatchFieldsUnsupported
+    }
+}
+
+/// Create a new application in a profile. With a `signer` (e.g. from the
+/// metadata layer), the entry is matched on it where the driver's match
+/// fields allow; otherwise it falls back to name matching and says why.
+pub fn create_application(
+    profile_handle: NvDRSProfileHandle,
+    executable: &str,
+    friendly_name: &str,
+    signer: Option<&str>,
+) -> Result<ApplicationCreated, NvApiError> {
+    // Only probe the driver when a signer could use the match fields
+    let fallback = signer.map(|_| signer_fallback(match_fields_capability().available));
+    create_application_with_launcher(profile_handle, executable, friendly_name, "")?;
+    if let Some(reason) = fallback {
+        journal::append(JournalCategory::Mutation, JournalSeverity::Info, "application.signerMatchFallback", serde_json::json!({
+            "executable": executable,
+            "reason": reason,
+        }));
+    }
+    Ok(ApplicationCreated { match_mode: ApplicationMatchMode::Name, signer_fallback: fallback })
+}
+
+/// Create a new application in a profile with a launcher executable
+#[cfg(target_os = "windows")]
+pub fn create_application_with_launcher(
+    profile_handle: NvDRSProfileHandle,
+    executable: &str,
+    friendly_name: &str,
+    launcher: &str,
+) -> Result<(), NvApiError> {
+    let api = get_nvapi()?;
+    let session = get_session_mut()?;
+
     let create_fn = api.drs_create_application
         .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_CreateApplication".to_string()))?;
 
@@ -203,6 +700,7 @@ pub fn create_application(profile_handle: NvDRSProfileHandle, executable: &str,
     };
     string_to_wchar(executable, &mut app.app_name);
     string_to_wchar(friendly_name, &mut app.user_friendly_name);
+    string_to_wchar(launcher, &mut app.launcher);
 
     unsafe {
         let status = create_fn(session, profile_handle, &mut app);
@@ -212,33 +710,516 @@ pub fn create_application(profile_handle: NvDRSProfileHandle, executable: &str,
         }
     }
 
+    record_mutation(format!("Created application '{}'", executable));
+    Ok(())
+}
+
+/// Create an application from already-encoded fields, so names read from DRS
+/// are written back with their exact code units
+#[cfg(target_os = "windows")]
+fn create_application_raw(profile_handle: NvDRSProfileHandle, app: &mut NvdrsApplication) -> Result<(), NvApiError> {
+    let api = get_nvapi()?;
+    let session = get_session_mut()?;
+
+    let create_fn = api.drs_create_application
+        .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_CreateApplication".to_string()))?;
+
+    app.version = NVDRS_APPLICATION_VER;
+    unsafe {
+        let status = create_fn(session, profile_handle, app);
+
+        if status != NVAPI_OK {
+            return Err(NvApiError::ApplicationCreationFailed(status));
+        }
+    }
+
+    record_mutation(format!("Created application '{}'", wchar_to_string(&app.app_name)));
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn create_application_with_launcher(
+    _profile_handle: NvDRSProfileHandle,
+    _executable: &str,
+    _friendly_name: &str,
+    _launcher: &str,
+) -> Result<(), NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Remove an application from a profile
+#[cfg(target_os = "windows")]
+pub fn delete_application(profile_handle: NvDRSProfileHandle, executable: &str) -> Result<(), NvApiError> {
+    let api = get_nvapi()?;
+    let session = get_session_mut()?;
+
+    let delete_fn = require_optional(api.drs_delete_application, NvApiFunction::DrsDeleteApplication, "delete-application")?;
+
+    let mut name = [0u16; NVAPI_UNICODE_STRING_MAX];
+    string_to_wchar(executable, &mut name);
+
+    unsafe {
+        let status = delete_fn(session, profile_handle, name.as_ptr());
+        if status != NVAPI_OK {
+            return Err(NvApiError::NvApiStatus(status));
+        }
+    }
+
+    record_mutation(format!("Deleted application '{}'", executable));
+    Ok(())
+}
+
+/// Remove an application by its name exactly as DRS returned it
+#[cfg(target_os = "windows")]
+fn delete_application_raw(profile_handle: NvDRSProfileHandle, app_name: &[u16; NVAPI_UNICODE_STRING_MAX]) -> Result<(), NvApiError> {
+    let api = get_nvapi()?;
+    let session = get_session_mut()?;
+
+    let delete_fn = require_optional(api.drs_delete_application, NvApiFunction::DrsDeleteApplication, "delete-application")?;
+
+    unsafe {
+        let status = delete_fn(session, profile_handle, app_name.as_ptr());
+        if status != NVAPI_OK {
+            return Err(NvApiError::NvApiStatus(status));
+        }
+    }
+
+    record_mutation(format!("Deleted application '{}'", wchar_to_string(app_name)));
     Ok(())
 }
 
 #[cfg(not(target_os = "windows"))]
-pub fn create_application(_profile_handle: NvDRSProfileHandle, _executable: &str, _friendly_name: &str) -> Result<(), NvApiError> {
+pub fn delete_application(_profile_handle: NvDRSProfileHandle, _executable: &str) -> Result<(), NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Update an existing application's fields in place
+#[cfg(target_os = "windows")]
+pub fn set_application_info(executable: &str, updated_app: NvdrsApplication) -> Result<(), NvApiError> {
+    let api = get_nvapi()?;
+    let session = get_session_mut()?;
+
+    let set_info = api.drs_set_application_info
+        .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_SetApplicationInfo".to_string()))?;
+
+    let (profile_handle, _app) = find_application(executable)?;
+    let mut app = NvdrsApplication {
+        version: NVDRS_APPLICATION_VER,
+        ..updated_app
+    };
+
+    unsafe {
+        let status = set_info(session, profile_handle, &mut app);
+        if status != NVAPI_OK {
+            return Err(NvApiError::SetApplicationInfoFailed(status));
+        }
+    }
+
+    record_mutation(format!("Updated application '{}'", executable));
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn set_application_info(_executable: &str, _updated_app: NvdrsApplication) -> Result<(), NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Change the launcher of an existing application.
+/// Falls back to delete + recreate on drivers without NvAPI_DRS_SetApplicationInfo;
+/// if the recreate fails the session is reloaded so the entry isn't lost.
+#[cfg(target_os = "windows")]
+pub fn set_application_launcher(executable: &str, launcher: &str) -> Result<(), NvApiError> {
+    use super::session::{reload_settings, save_settings};
+
+    let api = get_nvapi()?;
+    let (profile_handle, mut app) = find_application(executable)?;
+
+    match api.drs_set_application_info {
+        Some(_) => {
+            app.launcher = [0u16; NVAPI_UNICODE_STRING_MAX];
+            string_to_wchar(launcher, &mut app.launcher);
+            set_application_info(executable, app)?;
+        }
+        None => {
+            // Reuse the original code units; re-encoding the decoded names would
+            // corrupt unpaired surrogates and the recreated entry wouldn't match
+            let mut recreated = NvdrsApplication {
+                app_name: app.app_name,
+                user_friendly_name: app.user_friendly_name,
+                ..Default::default()
+            };
+            string_to_wchar(launcher, &mut recreated.launcher);
+            // The entry can't be staged before the delete, as both would have the same name
+            delete_application_raw(profile_handle, &app.app_name)?;
+            if let Err(e) = create_application_raw(profile_handle, &mut recreated) {
+                // Drop the unsaved delete rather than leave the entry missing
+                let _ = reload_settings();
+                return Err(e);
+            }
+        }
+    }
+
+    save_settings()?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn set_application_launcher(_executable: &str, _launcher: &str) -> Result<(), NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Move an executable's entry out of its current profile into `profile_name`,
+/// creating that profile if needed. Nothing is saved; if the move fails
+/// halfway the session is reloaded so the entry isn't lost.
+#[cfg(target_os = "windows")]
+pub fn move_application_to_profile(executable: &str, profile_name: &str) -> Result<NvDRSProfileHandle, NvApiError> {
+    use super::profiles::create_profile_if_not_exists;
+    use super::session::reload_settings;
+
+    let (current, app) = find_application(executable)?;
+    // Keep the original code units, as set_application_launcher does
+    let mut moved = NvdrsApplication {
+        app_name: app.app_name,
+        user_friendly_name: app.user_friendly_name,
+        launcher: app.launcher,
+        ..Default::default()
+    };
+    // An executable can only be in one profile, so the old entry goes first
+    delete_application_raw(current, &app.app_name)?;
+    let created = create_profile_if_not_exists(profile_name)
+        .and_then(|(handle, _)| create_application_raw(handle, &mut moved).map(|_| handle));
+    if created.is_err() {
+        let _ = reload_settings();
+    }
+    created
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn move_application_to_profile(_executable: &str, _profile_name: &str) -> Result<NvDRSProfileHandle, NvApiError> {
     Err(NvApiError::NotSupported)
 }
 
-/// Create a profile for an executable (combines create_profile + create_application)
+/// Create a profile for an executable (combines create_profile + create_application).
+/// With `verify`, the saved result is checked with `verify_profile_creation`; batch
+/// flows can skip it and verify once at the end.
 #[cfg(target_os = "windows")]
-pub fn create_profile_for_executable(executable: &str, profile_name: &str) -> Result<(), NvApiError> {
+pub fn create_profile_for_executable(executable: &str, profile_name: &str, verify: bool) -> Result<ApplicationCreated, NvApiError> {
     use super::profiles::create_profile;
     use super::session::save_settings;
 
     // Create the profile
     let profile_handle = create_profile(profile_name)?;
 
-    // Add the application to it
-    create_application(profile_handle, executable, profile_name)?;
+    // Add the application to it, offering the signer if one is already known
+    let signer = crate::metadata::cached_signer(executable);
+    let created = create_application(profile_handle, executable, profile_name, signer.as_deref())?;
 
     // Save settings
     save_settings()?;
 
-    Ok(())
+    if verify {
+        verify_profile_creation(executable, profile_name)?;
+    }
+    Ok(created)
 }
 
 #[cfg(not(target_os = "windows"))]
-pub fn create_profile_for_executable(_executable: &str, _profile_name: &str) -> Result<(), NvApiError> {
+pub fn create_profile_for_executable(_executable: &str, _profile_name: &str, _verify: bool) -> Result<ApplicationCreated, NvApiError> {
     Err(NvApiError::NotSupported)
 }
+
+/// What a saved profile creation looks like. Err describes the mismatch.
+fn observe_creation(executable: &str, profile_name: &str) -> Result<(), String> {
+    let handle = find_profile_by_name(profile_name)
+        .map_err(|e| format!("profile '{}' not found after save: {}", profile_name, e))?;
+    let profile = get_profile_info_by_handle(handle).map_err(|e| e.to_string())?;
+    if profile.application_count == 0 {
+        return Err(format!("profile '{}' has no applications after save", profile_name));
+    }
+
+    let (app_profile, _) = find_application(executable)
+        .map_err(|e| format!("'{}' does not resolve after save: {}", executable, e))?;
+    let resolved = get_profile_info_by_handle(app_profile).map_err(|e| e.to_string())?;
+    if resolved.name != profile.name {
+        return Err(format!("'{}' resolves to profile '{}' instead of '{}'", executable, resolved.name, profile.name));
+    }
+    Ok(())
+}
+
+/// Confirm a just-created profile holds the executable after save. Some drivers
+/// report success but drop the application; the profile is then deleted if it
+/// was left empty, and VerificationFailed describes what was observed.
+pub fn verify_profile_creation(executable: &str, profile_name: &str) -> Result<(), NvApiError> {
+    let Err(observed) = observe_creation(executable, profile_name) else {
+        return Ok(());
+    };
+
+    let empty = find_profile_by_name(profile_name)
+        .and_then(get_profile_info_by_handle)
+        .is_ok_and(|p| p.application_count == 0);
+    if empty {
+        let rolled_back = super::profiles::delete_profile(profile_name)
+            .and_then(|_| super::session::save_settings());
+        if let Err(e) = rolled_back {
+            journal::append(JournalCategory::Mutation, JournalSeverity::Warning, "profile.cleanup_failed", serde_json::json!({
+                "profileName": profile_name,
+                "error": e.to_string(),
+            }));
+        }
+    }
+
+    Err(NvApiError::VerificationFailed(observed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn app(profile_name: &str, executable: &str) -> DrsApplication {
+        DrsApplication {
+            name: String::new(),
+            executable: executable.to_string(),
+            profile_name: profile_name.to_string(),
+            is_predefined: false,
+            is_blacklisted: false,
+            blacklist_origin: None,
+            provenance: None,
+            match_mode: None,
+        }
+    }
+
+    fn executables(apps: &[DrsApplication]) -> Vec<&str> {
+        apps.iter().map(|a| a.executable.as_str()).collect()
+    }
+
+    #[test]
+    fn grouping_keeps_enumeration_order_within_each_profile() {
+        let grouped = group_by_profile(vec![
+            app("Game", "b.exe"),
+            app("Other", "x.exe"),
+            app("Game", "a.exe"),
+            app("Game", "c.exe"),
+        ]);
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(executables(&grouped["Game"]), ["b.exe", "a.exe", "c.exe"]);
+        assert_eq!(executables(&grouped["Other"]), ["x.exe"]);
+    }
+
+    #[test]
+    fn grouping_nothing_gives_no_groups() {
+        assert!(group_by_profile(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn a_signer_falls_back_to_name_matching_with_the_reason() {
+        assert_eq!(signer_fallback(false), SignerMatchFallback::MatchFieldsUnsupported);
+        assert_eq!(signer_fallback(true), SignerMatchFallback::NoSignerField);
+    }
+}
+
+#[cfg(all(test, target_os = "windows"))]
+mod mock_tests {
+    use super::*;
+    use crate::nvapi::mock::{self, Profile, Store};
+    use serde_json::Value;
+    use crate::nvapi::ffi::{reset_enum_batches, scribble_enum_batches, ENUM_BATCH_SIZE};
+    use crate::nvapi::session::session_lock_count;
+
+    const LARGE_STORE_PROFILES: usize = 6000;
+
+    /// Every application, plus the settings of every 100th profile, as JSON
+    fn enumerate_large_store() -> Value {
+        let applications = get_all_applications().unwrap();
+        let settings: Vec<Vec<crate::nvapi::types::DrsSetting>> = (0..LARGE_STORE_PROFILES)
+            .step_by(100)
+            .map(|i| {
+                let profile = crate::nvapi::profiles::find_profile_by_name(&format!("Synthetic {}", i)).unwrap();
+                crate::nvapi::settings::get_all_settings(profile).unwrap()
+            })
+            .collect();
+        serde_json::json!({ "applications": applications, "settings": settings })
+    }
+
+    #[test]
+    fn reused_enumeration_buffers_give_the_same_results_as_fresh_ones() {
+        let _serial = mock::serial();
+        mock::install(Store::synthetic(LARGE_STORE_PROFILES));
+
+        // Fresh buffers are what every call had before they were reused
+        reset_enum_batches();
+        let fresh = enumerate_large_store();
+        assert_eq!(
+            fresh["applications"].as_array().unwrap().len(),
+            mock::disk().profiles.iter().map(|p| p.applications.len()).sum::<usize>()
+        );
+        // Synthetic 500 spans two setting batches
+        assert_eq!(fresh["settings"][5].as_array().unwrap().len(), ENUM_BATCH_SIZE + 3);
+
+        let reused = enumerate_large_store();
+        assert!(fresh == reused, "a second pass over the reused buffers differs");
+
+        scribble_enum_batches();
+        let scribbled = enumerate_large_store();
+        assert!(fresh == scribbled, "leftover bytes in the reused buffers leaked into the results");
+    }
+
+    #[test]
+    fn verified_creation_keeps_the_profile() {
+        let _serial = mock::serial();
+        mock::install(Store::default());
+
+        create_profile_for_executable("verify-created.exe", "Verify Created", true).unwrap();
+
+        let disk = mock::disk();
+        let profile = disk.profile("Verify Created").expect("profile saved");
+        assert_eq!(profile.applications.len(), 1);
+        assert_eq!(profile.applications[0].name, "verify-created.exe");
+        assert_eq!(mock::saves(), 1);
+    }
+
+    #[test]
+    fn a_signer_the_driver_cannot_match_on_falls_back_to_the_name() {
+        let _serial = mock::serial();
+        mock::install(Store::default().with_profile(Profile::new("Signed Game")));
+        let profile = find_profile_by_name("Signed Game").unwrap();
+
+        // The mock, like older drivers, rejects the v4 structure
+        let signed = create_application(profile, "signed-game.exe", "", Some("CN=Studio")).unwrap();
+        assert_eq!(signed.match_mode, ApplicationMatchMode::Name);
+        assert_eq!(signed.signer_fallback, Some(SignerMatchFallback::MatchFieldsUnsupported));
+        assert!(!match_fields_capability().available);
+
+        let unsigned = create_application(profile, "unsigned-game.exe", "", None).unwrap();
+        assert_eq!(unsigned.signer_fallback, None);
+        assert!(find_application("signed-game.exe").is_ok());
+    }
+
+    #[test]
+    fn a_dropped_attach_rolls_the_empty_profile_back() {
+        let _serial = mock::serial();
+        mock::install(Store::default());
+        mock::drop_application_attach();
+
+        let result = create_profile_for_executable("verify-dropped.exe", "Verify Dropped", true);
+        match result {
+            Err(NvApiError::VerificationFailed(observed)) => {
+                assert!(observed.contains("has no applications"), "{}", observed);
+            }
+            other => panic!("expected a verification failure, got {:?}", other),
+        }
+        // Created, then deleted again and saved
+        assert!(mock::disk().profile("Verify Dropped").is_none());
+        assert_eq!(mock::saves(), 2);
+    }
+
+    #[test]
+    fn skipping_verification_leaves_the_check_to_the_caller() {
+        let _serial = mock::serial();
+        mock::install(Store::default());
+        mock::drop_application_attach();
+
+        create_profile_for_executable("verify-batch.exe", "Verify Batch", false).unwrap();
+        assert!(mock::disk().profile("Verify Batch").unwrap().applications.is_empty());
+
+        // The batch verifies once at the end, which finds and removes the empty profile
+        assert!(matches!(
+            verify_profile_creation("verify-batch.exe", "Verify Batch"),
+            Err(NvApiError::VerificationFailed(_)),
+        ));
+        assert!(mock::disk().profile("Verify Batch").is_none());
+    }
+
+    #[test]
+    fn grouping_and_the_header_count_agree_with_the_flat_list() {
+        let _serial = mock::serial();
+        mock::install(Store::default()
+            .with_profile(Profile::new("Count Two").with_application("count-a.exe").with_application("count-b.exe"))
+            .with_profile(Profile::new("Count Empty"))
+            .with_profile(Profile::new("Count Shipped").predefined().with_application("count-c.exe")));
+
+        let all = get_all_applications().unwrap();
+        let grouped = get_all_applications_grouped().unwrap();
+        assert_eq!(all.len(), 4);
+        assert_eq!(grouped.values().map(Vec::len).sum::<usize>(), all.len());
+        assert_eq!(get_total_application_count().unwrap() as usize, all.len());
+
+        assert_eq!(grouped["Count Two"].len(), 2);
+        assert_eq!(grouped["Count Shipped"].len(), 1);
+        // The profile without applications is listed once, standing in for itself
+        let empty = &grouped["Count Empty"];
+        assert_eq!(empty.len(), 1);
+        assert_eq!(empty[0].executable, "Count Empty");
+    }
+
+    fn is_missing(executable: &str) -> bool {
+        matches!(find_application(executable), Err(NvApiError::ApplicationNotFound(_)))
+    }
+
+    #[test]
+    fn a_remembered_miss_goes_stale_once_the_application_is_created() {
+        let _serial = mock::serial();
+        mock::install(Store::default().with_profile(Profile::new("Lookup Target")));
+        clear_negative_lookup_cache();
+
+        assert!(is_missing("lookup-late.exe"));
+        assert_eq!(negative_lookup_cache_stats().entries, 1);
+
+        // The repeat is answered from the cache
+        let negative_hits = negative_lookup_cache_stats().negative_hits;
+        assert!(is_missing("lookup-late.exe"));
+        assert_eq!(negative_lookup_cache_stats().negative_hits, negative_hits + 1);
+
+        let profile = find_profile_by_name("Lookup Target").unwrap();
+        create_application(profile, "lookup-late.exe", "", None).unwrap();
+
+        // The creation bumped the generation, so the miss no longer counts
+        assert_eq!(negative_lookup_cache_stats().entries, 0);
+        let lookup_hits = negative_lookup_cache_stats().hits;
+        let (found_in, app) = find_application("lookup-late.exe").expect("found after creation");
+        assert_eq!(found_in, profile);
+        assert_eq!(wchar_to_string(&app.app_name), "lookup-late.exe");
+        assert_eq!(negative_lookup_cache_stats().hits, lookup_hits + 1);
+    }
+
+    #[test]
+    fn joining_many_executables_takes_one_pass_over_drs() {
+        let _serial = mock::serial();
+        mock::install(Store::default()
+            .with_profile(Profile::new("Join Plain").with_application("join-plain.exe"))
+            .with_profile(Profile::new("Join Nested").with_application("bin\\Join-Nested.exe"))
+            .with_profile(Profile::new("Join Empty")));
+
+        let executables: Vec<String> = ["C:\\Games\\join-plain.exe", "D:\\join-nested.EXE", "join-absent.exe", "join-plain.exe"]
+            .iter()
+            .map(|e| e.to_string())
+            .collect();
+        let profile_passes = mock::calls(NvApiFunction::DrsEnumProfiles);
+        let lookups = mock::calls(NvApiFunction::DrsFindApplicationByName);
+        let locks = session_lock_count();
+
+        let joined = join_executables(&executables).unwrap();
+        let profiles_of = |exe: &str| joined[exe].iter().map(|a| a.profile_name.as_str()).collect::<Vec<_>>();
+        assert_eq!(joined.len(), 3);
+        assert_eq!(profiles_of("C:\\Games\\join-plain.exe"), ["Join Plain"]);
+        assert_eq!(profiles_of("join-plain.exe"), ["Join Plain"]);
+        assert_eq!(profiles_of("D:\\join-nested.EXE"), ["Join Nested"]);
+        assert!(!joined.contains_key("join-absent.exe"));
+
+        // Three profiles and the end of enumeration, once, and no lookup per executable
+        assert_eq!(mock::calls(NvApiFunction::DrsEnumProfiles) - profile_passes, 4);
+        assert_eq!(mock::calls(NvApiFunction::DrsFindApplicationByName), lookups);
+        assert_eq!(session_lock_count() - locks, 1);
+    }
+
+    #[test]
+    fn a_basename_listed_by_several_profiles_keeps_every_match() {
+        let _serial = mock::serial();
+        mock::install(Store::default()
+            .with_profile(Profile::new("Join First").with_application("join-shared.exe"))
+            .with_profile(Profile::new("Join Second").with_application("sub\\JOIN-SHARED.exe")));
+
+        let joined = join_executables(&["E:\\join-shared.exe".to_string()]).unwrap();
+        let matches: Vec<_> = joined["E:\\join-shared.exe"].iter().map(|a| a.profile_name.as_str()).collect();
+        assert_eq!(matches, ["Join First", "Join Second"]);
+    }
+}
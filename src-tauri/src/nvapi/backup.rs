@@ -0,0 +1,247 @@
+//! Backup comparison
+//!
+//! Diffs the live DRS store against a backup file opened in a read-only session,
+//! and restores selected differences back into the live store.
+
+use std::collections::{BTreeSet, HashMap};
+use super::error::NvApiError;
+use super::ffi::{SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED, SHADOWPLAY_ENABLED};
+use super::applications::{create_application, enumerate_applications, find_application};
+use super::profiles::{create_profile_if_not_exists, enumerate_profiles_with_status, find_profile_by_name};
+use super::resolve::executable_key;
+use super::session::{close_backup, flush, open_backup_readonly, reload_settings, with_backup_session};
+use super::settings::set_dword_setting;
+use super::types::{BackupDiff, BackupRestoreEntry, DrsApplication, DrsProfile, ShadowPlayDifference};
+
+/// Profiles and applications of one DRS store
+#[derive(Default)]
+struct Snapshot {
+    profiles: HashMap<String, DrsProfile>,
+    /// Keyed by (profile name, lowercase executable)
+    applications: HashMap<(String, String), DrsApplication>,
+}
+
+/// Read every profile and its applications from the current session
+fn take_snapshot() -> Result<Snapshot, NvApiError> {
+    let mut snapshot = Snapshot::default();
+
+    for profile in enumerate_profiles_with_status(true)? {
+        if profile.application_count > 0 {
+            let handle = find_profile_by_name(&profile.name)?;
+            for app in enumerate_applications(handle, &profile.name)? {
+                snapshot.applications.insert((profile.name.clone(), executable_key(&app.executable)), app);
+            }
+        }
+        snapshot.profiles.insert(profile.name.clone(), profile);
+    }
+
+    Ok(snapshot)
+}
+
+fn diff_snapshots(live: &Snapshot, backup: &Snapshot) -> BackupDiff {
+    let mut diff = BackupDiff::default();
+
+    let profile_names: BTreeSet<&String> = live.profiles.keys().chain(backup.profiles.keys()).collect();
+    for name in profile_names {
+        let live_profile = live.profiles.get(name);
+        let backup_profile = backup.profiles.get(name);
+
+        match (live_profile, backup_profile) {
+            (Some(_), None) => diff.profiles_only_in_live.push(name.clone()),
+            (None, Some(_)) => diff.profiles_only_in_backup.push(name.clone()),
+            _ => {}
+        }
+
+        let live_status = live_profile.and_then(|p| p.is_blacklisted);
+        let backup_status = backup_profile.and_then(|p| p.is_blacklisted);
+        if live_status != backup_status {
+            diff.shadowplay_differences.push(ShadowPlayDifference {
+                profile_name: name.clone(),
+                live: live_status,
+                backup: backup_status,
+            });
+        }
+    }
+
+    for (key, app) in &live.applications {
+        if !backup.applications.contains_key(key) {
+            diff.applications_added.push(app.clone());
+        }
+    }
+    for (key, app) in &backup.applications {
+        if !live.applications.contains_key(key) {
+            diff.applications_removed.push(app.clone());
+        }
+    }
+
+    let by_profile = |a: &DrsApplication, b: &DrsApplication| {
+        a.profile_name.cmp(&b.profile_name).then_with(|| a.executable.cmp(&b.executable))
+    };
+    diff.applications_added.sort_by(by_profile);
+    diff.applications_removed.sort_by(by_profile);
+
+    diff
+}
+
+/// Compare the live DRS store against a backup file without modifying either
+pub fn diff_against_backup(path: &str) -> Result<BackupDiff, NvApiError> {
+    let live = take_snapshot()?;
+
+    let session_id = open_backup_readonly(path)?;
+    let backup = with_backup_session(session_id, take_snapshot);
+    close_backup(session_id);
+
+    Ok(diff_snapshots(&live, &backup?))
+}
+
+fn apply_entry(entry: &BackupRestoreEntry) -> Result<(), NvApiError> {
+    match entry {
+        BackupRestoreEntry::Profile { profile_name } => {
+            create_profile_if_not_exists(profile_name)?;
+        }
+        BackupRestoreEntry::Application { profile_name, executable, name } => {
+            let (profile_handle, _created) = create_profile_if_not_exists(profile_name)?;
+            let already_there = find_application(executable)
+                .is_ok_and(|(handle, _app)| handle == profile_handle);
+            if !already_there {
+                create_application(profile_handle, executable, name, None)?;
+            }
+        }
+        BackupRestoreEntry::ShadowPlay { profile_name, blacklisted } => {
+            let value = if *blacklisted { SHADOWPLAY_DISABLED } else { SHADOWPLAY_ENABLED };
+            set_dword_setting(find_profile_by_name(profile_name)?, SHADOWPLAY_SETTING_ID, value)?;
+        }
+    }
+    Ok(())
+}
+
+/// Restore the chosen differences in one batch: either every entry is saved, or none are.
+/// Profiles are created before applications, and both before ShadowPlay is set.
+pub fn apply_backup_entries(entries: &[BackupRestoreEntry]) -> Result<u32, NvApiError> {
+    let phase = |entry: &BackupRestoreEntry| match entry {
+        BackupRestoreEntry::Profile { .. } => 0,
+        BackupRestoreEntry::Application { .. } => 1,
+        BackupRestoreEntry::ShadowPlay { .. } => 2,
+    };
+    let mut ordered: Vec<&BackupRestoreEntry> = entries.iter().collect();
+    ordered.sort_by_key(|e| phase(e));
+
+    if let Err(e) = ordered.into_iter().try_for_each(apply_entry) {
+        // Drop the partially applied batch from the session
+        let _ = reload_settings();
+        return Err(e);
+    }
+
+    flush()?;
+    Ok(entries.len() as u32)
+}
+
+#[cfg(all(test, target_os = "windows"))]
+mod tests {
+    use super::*;
+    use crate::nvapi::mock::{self, Profile, Store};
+
+    const BACKUP_PATH: &str = "C:\\Backups\\drs-backup.nip";
+
+    fn live_store() -> Store {
+        Store::default()
+            .with_profile(Profile::new("Backup Both")
+                .with_application("backup-both.exe")
+                .with_dword(SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED))
+            .with_profile(Profile::new("Backup Live Only").with_application("backup-live.exe"))
+    }
+
+    fn backup_store() -> Store {
+        Store::default()
+            .with_profile(Profile::new("Backup Both")
+                .with_application("backup-both.exe")
+                .with_application("backup-restored.exe"))
+            .with_profile(Profile::new("Backup File Only").with_application("backup-file.exe"))
+    }
+
+    fn executables(apps: &[DrsApplication]) -> Vec<&str> {
+        apps.iter().map(|a| a.executable.as_str()).collect()
+    }
+
+    #[test]
+    fn the_diff_lists_each_side_and_leaves_both_alone() {
+        let _serial = mock::serial();
+        mock::install(live_store());
+        mock::write_file(BACKUP_PATH, backup_store());
+
+        let diff = diff_against_backup(BACKUP_PATH).unwrap();
+        assert_eq!(diff.profiles_only_in_live, ["Backup Live Only"]);
+        assert_eq!(diff.profiles_only_in_backup, ["Backup File Only"]);
+        assert_eq!(executables(&diff.applications_added), ["backup-live.exe"]);
+        assert_eq!(executables(&diff.applications_removed), ["backup-restored.exe", "backup-file.exe"]);
+
+        let both = diff.shadowplay_differences.iter()
+            .find(|d| d.profile_name == "Backup Both")
+            .expect("ShadowPlay differs on the shared profile");
+        assert_eq!((both.live, both.backup), (Some(true), Some(false)));
+
+        assert_eq!(mock::saves(), 0);
+        assert!(mock::disk().profile("Backup File Only").is_none());
+    }
+
+    #[test]
+    fn an_unreadable_backup_fails_the_diff() {
+        let _serial = mock::serial();
+        mock::install(live_store());
+
+        assert!(matches!(
+            diff_against_backup("C:\\Backups\\missing.nip"),
+            Err(NvApiError::LoadSettingsFailed(_)),
+        ));
+    }
+
+    #[test]
+    fn restored_entries_are_applied_in_phases_and_saved_once() {
+        let _serial = mock::serial();
+        mock::install(live_store());
+
+        // ShadowPlay and the application come first but need the profile created before them
+        let entries = [
+            BackupRestoreEntry::ShadowPlay { profile_name: "Backup File Only".to_string(), blacklisted: true },
+            BackupRestoreEntry::Application {
+                profile_name: "Backup File Only".to_string(),
+                executable: "backup-file.exe".to_string(),
+                name: "Backup File".to_string(),
+            },
+            BackupRestoreEntry::Application {
+                profile_name: "Backup Both".to_string(),
+                executable: "backup-restored.exe".to_string(),
+                name: String::new(),
+            },
+            BackupRestoreEntry::ShadowPlay { profile_name: "Backup Both".to_string(), blacklisted: false },
+            BackupRestoreEntry::Profile { profile_name: "Backup File Only".to_string() },
+        ];
+        assert_eq!(apply_backup_entries(&entries).unwrap(), 5);
+        assert_eq!(mock::saves(), 1);
+
+        let disk = mock::disk();
+        let restored = disk.profile("Backup File Only").expect("profile restored");
+        assert_eq!(restored.applications[0].name, "backup-file.exe");
+        assert_eq!(restored.dword(SHADOWPLAY_SETTING_ID), Some(SHADOWPLAY_DISABLED));
+
+        let both = disk.profile("Backup Both").unwrap();
+        assert_eq!(both.applications.len(), 2);
+        assert_eq!(both.dword(SHADOWPLAY_SETTING_ID), Some(SHADOWPLAY_ENABLED));
+    }
+
+    #[test]
+    fn a_failing_entry_discards_the_whole_batch() {
+        let _serial = mock::serial();
+        mock::install(live_store());
+
+        let entries = [
+            BackupRestoreEntry::Profile { profile_name: "Backup Rolled Back".to_string() },
+            BackupRestoreEntry::ShadowPlay { profile_name: "Backup Nowhere".to_string(), blacklisted: true },
+        ];
+        assert!(matches!(apply_backup_entries(&entries), Err(NvApiError::ProfileNotFound(_))));
+
+        assert_eq!(mock::saves(), 0);
+        // Dropped from the session as well, not just left unsaved
+        assert!(find_profile_by_name("Backup Rolled Back").is_err());
+    }
+}
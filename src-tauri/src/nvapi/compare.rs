@@ -0,0 +1,230 @@
+//! Profile comparison
+//!
+//! Puts two profiles' DRS state side by side, for working out why one game
+//! captures and a nearly identical one doesn't. Either operand may be the base
+//! profile, named by an empty string. The diff is pure over two snapshots read
+//! with the settings enumeration. No lock is held across NVAPI calls, so the
+//! snapshots are read back to back and read again if a mutation bumped the
+//! session generation in between. If DRS is still changing after the last
+//! attempt the comparison is returned marked as not consistent.
+
+use std::collections::{BTreeMap, HashSet};
+use super::error::NvApiError;
+use super::applications::enumerate_applications;
+use super::ffi::{SHADOWPLAY_DISABLED, SHADOWPLAY_ENABLED, SHADOWPLAY_SETTING_ID};
+use super::profiles::{find_profile_by_name, get_base_profile};
+use super::resolve::executable_key;
+use super::session::generation;
+use super::settings::get_all_settings;
+use super::types::{DrsApplication, DrsSetting, EffectiveShadowPlay, ProfileComparison, SettingDifference, ShadowPlaySource};
+
+/// Reads attempted before giving up on a consistent pair of snapshots
+const SNAPSHOT_ATTEMPTS: usize = 2;
+
+/// One profile's settings and applications
+#[derive(Debug, Clone, Default)]
+pub struct ProfileSnapshot {
+    /// Empty for the base profile
+    pub name: String,
+    pub settings: Vec<DrsSetting>,
+    pub applications: Vec<DrsApplication>,
+}
+
+fn stored_shadowplay(snapshot: &ProfileSnapshot) -> Option<u32> {
+    snapshot.settings.iter()
+        .find(|s| s.setting_id == SHADOWPLAY_SETTING_ID)
+        .and_then(|s| s.dword_value)
+}
+
+/// The profile's own value, else the base profile's, else the driver default
+pub fn effective_shadowplay(profile: &ProfileSnapshot, base: &ProfileSnapshot) -> EffectiveShadowPlay {
+    let stored = stored_shadowplay(profile);
+    let (effective, source) = match (stored, stored_shadowplay(base)) {
+        (Some(value), _) => (value, ShadowPlaySource::Profile),
+        (None, Some(value)) => (value, ShadowPlaySource::BaseProfile),
+        (None, None) => (SHADOWPLAY_ENABLED, ShadowPlaySource::DriverDefault),
+    };
+    EffectiveShadowPlay { stored, effective, disabled: effective == SHADOWPLAY_DISABLED, source }
+}
+
+/// Compare two snapshots taken from one DRS state. Settings are keyed by ID
+/// and applications by executable name, ignoring case; every list comes out in
+/// a stable order.
+pub fn compare(a: &ProfileSnapshot, b: &ProfileSnapshot, base: &ProfileSnapshot) -> ProfileComparison {
+    let settings_a: BTreeMap<u32, &DrsSetting> = a.settings.iter().map(|s| (s.setting_id, s)).collect();
+    let settings_b: BTreeMap<u32, &DrsSetting> = b.settings.iter().map(|s| (s.setting_id, s)).collect();
+
+    let only = |from: &BTreeMap<u32, &DrsSetting>, other: &BTreeMap<u32, &DrsSetting>| -> Vec<DrsSetting> {
+        from.iter()
+            .filter(|(id, _)| !other.contains_key(id))
+            .map(|(_, s)| (*s).clone())
+            .collect()
+    };
+
+    let setting_differences = settings_a.iter()
+        .filter_map(|(id, sa)| {
+            let sb = settings_b.get(id)?;
+            let differs = sa.setting_type != sb.setting_type
+                || sa.dword_value != sb.dword_value
+                || sa.value != sb.value;
+            differs.then(|| SettingDifference {
+                setting_id: *id,
+                name: if sa.name.is_empty() { sb.name.clone() } else { sa.name.clone() },
+                value_a: sa.value.clone(),
+                value_b: sb.value.clone(),
+                predefined_a: sa.is_current_predefined,
+                predefined_b: sb.is_current_predefined,
+            })
+        })
+        .collect();
+
+    let keys = |apps: &[DrsApplication]| -> HashSet<String> {
+        apps.iter().map(|app| executable_key(&app.executable)).collect()
+    };
+    let (keys_a, keys_b) = (keys(&a.applications), keys(&b.applications));
+    let only_apps = |apps: &[DrsApplication], other: &HashSet<String>| -> Vec<DrsApplication> {
+        let mut only: Vec<DrsApplication> = apps.iter()
+            .filter(|app| !other.contains(&executable_key(&app.executable)))
+            .cloned()
+            .collect();
+        only.sort_by(|x, y| executable_key(&x.executable).cmp(&executable_key(&y.executable)));
+        only
+    };
+
+    ProfileComparison {
+        profile_a: a.name.clone(),
+        profile_b: b.name.clone(),
+        settings_only_in_a: only(&settings_a, &settings_b),
+        settings_only_in_b: only(&settings_b, &settings_a),
+        setting_differences,
+        applications_only_in_a: only_apps(&a.applications, &keys_b),
+        applications_only_in_b: only_apps(&b.applications, &keys_a),
+        shadowplay_a: effective_shadowplay(a, base),
+        shadowplay_b: effective_shadowplay(b, base),
+        consistent: true,
+    }
+}
+
+/// Read a profile's settings and applications; an empty name reads the base profile
+pub fn take_snapshot(name: &str) -> Result<ProfileSnapshot, NvApiError> {
+    let handle = if name.is_empty() { get_base_profile()? } else { find_profile_by_name(name)? };
+    Ok(ProfileSnapshot {
+        name: name.to_string(),
+        settings: get_all_settings(handle)?,
+        applications: enumerate_applications(handle, name)?,
+    })
+}
+
+/// Compare two profiles as they are in the current session
+pub fn compare_profiles(profile_a: &str, profile_b: &str) -> Result<ProfileComparison, NvApiError> {
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        let started = generation();
+        let a = take_snapshot(profile_a)?;
+        let b = take_snapshot(profile_b)?;
+        let base = match (profile_a.is_empty(), profile_b.is_empty()) {
+            (true, _) => a.clone(),
+            (_, true) => b.clone(),
+            _ => take_snapshot("")?,
+        };
+        let consistent = generation() == started;
+        if consistent || attempt == SNAPSHOT_ATTEMPTS {
+            return Ok(ProfileComparison { consistent, ..compare(&a, &b, &base) });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nvapi::types::SettingValue;
+
+    fn dword(setting_id: u32, value: u32) -> DrsSetting {
+        DrsSetting {
+            setting_id,
+            name: String::new(),
+            setting_type: 0,
+            dword_value: Some(value),
+            is_current_predefined: false,
+            value: Some(SettingValue::Dword { value }),
+        }
+    }
+
+    fn app(executable: &str) -> DrsApplication {
+        DrsApplication {
+            name: String::new(),
+            executable: executable.to_string(),
+            profile_name: String::new(),
+            is_predefined: false,
+            is_blacklisted: false,
+            blacklist_origin: None,
+            provenance: None,
+            match_mode: None,
+        }
+    }
+
+    fn snapshot(name: &str, settings: Vec<DrsSetting>, applications: Vec<DrsApplication>) -> ProfileSnapshot {
+        ProfileSnapshot { name: name.to_string(), settings, applications }
+    }
+
+    fn ids(settings: &[DrsSetting]) -> Vec<u32> {
+        settings.iter().map(|s| s.setting_id).collect()
+    }
+
+    fn executables(apps: &[DrsApplication]) -> Vec<&str> {
+        apps.iter().map(|a| a.executable.as_str()).collect()
+    }
+
+    #[test]
+    fn settings_are_split_by_side_and_value() {
+        let a = snapshot("A", vec![dword(3, 1), dword(1, 1), dword(2, 5)], vec![]);
+        let b = snapshot("B", vec![dword(2, 6), dword(4, 1), dword(1, 1)], vec![]);
+        let result = compare(&a, &b, &ProfileSnapshot::default());
+
+        assert_eq!(ids(&result.settings_only_in_a), [3]);
+        assert_eq!(ids(&result.settings_only_in_b), [4]);
+        assert_eq!(result.setting_differences.len(), 1);
+        let difference = &result.setting_differences[0];
+        assert_eq!(difference.setting_id, 2);
+        assert_eq!(difference.value_a, Some(SettingValue::Dword { value: 5 }));
+        assert_eq!(difference.value_b, Some(SettingValue::Dword { value: 6 }));
+        assert!(result.consistent);
+    }
+
+    #[test]
+    fn identical_profiles_have_no_differences() {
+        let a = snapshot("A", vec![dword(1, 1)], vec![app("game.exe")]);
+        let result = compare(&a, &a, &ProfileSnapshot::default());
+        assert!(result.settings_only_in_a.is_empty() && result.settings_only_in_b.is_empty());
+        assert!(result.setting_differences.is_empty());
+        assert!(result.applications_only_in_a.is_empty() && result.applications_only_in_b.is_empty());
+    }
+
+    #[test]
+    fn applications_match_ignoring_case_and_come_out_sorted() {
+        let a = snapshot("A", vec![], vec![app("Zeta.exe"), app("GAME.EXE"), app("alpha.exe")]);
+        let b = snapshot("B", vec![], vec![app("game.exe"), app("beta.exe")]);
+        let result = compare(&a, &b, &ProfileSnapshot::default());
+        assert_eq!(executables(&result.applications_only_in_a), ["alpha.exe", "Zeta.exe"]);
+        assert_eq!(executables(&result.applications_only_in_b), ["beta.exe"]);
+    }
+
+    #[test]
+    fn shadowplay_falls_back_to_base_then_driver_default() {
+        let base = snapshot("", vec![dword(SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED)], vec![]);
+        let own = snapshot("A", vec![dword(SHADOWPLAY_SETTING_ID, SHADOWPLAY_ENABLED)], vec![]);
+        let inherits = snapshot("B", vec![], vec![]);
+        let result = compare(&own, &inherits, &base);
+
+        assert_eq!(result.shadowplay_a.source, ShadowPlaySource::Profile);
+        assert!(!result.shadowplay_a.disabled);
+        assert_eq!(result.shadowplay_b.source, ShadowPlaySource::BaseProfile);
+        assert_eq!(result.shadowplay_b.stored, None);
+        assert!(result.shadowplay_b.disabled);
+
+        let default = effective_shadowplay(&inherits, &ProfileSnapshot::default());
+        assert_eq!(default.source, ShadowPlaySource::DriverDefault);
+        assert_eq!(default.effective, SHADOWPLAY_ENABLED);
+    }
+}
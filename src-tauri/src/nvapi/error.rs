@@ -17,8 +17,12 @@ pub enum NvApiError {
     #[error("Failed to load settings: {0}")]
     LoadSettingsFailed(i32),
 
-    #[error("Failed to save settings: {0}")]
-    SaveSettingsFailed(i32),
+    #[error("Failed to save settings: {status}{}", blocker_hint(.probable_blocker))]
+    SaveSettingsFailed {
+        status: i32,
+        /// Running tools that may hold a DRS session with unsaved changes
+        probable_blocker: Option<String>,
+    },
 
     #[error("Profile not found: {0}")]
     ProfileNotFound(String),
@@ -29,23 +33,66 @@ pub enum NvApiError {
     #[error("Failed to create profile: {0}")]
     ProfileCreationFailed(i32),
 
+    #[error("Failed to delete profile: {0}")]
+    ProfileDeletionFailed(i32),
+
+    #[error("Failed to rename profile: {0}")]
+    ProfileRenameFailed(i32),
+
     #[error("Failed to create application: {0}")]
     ApplicationCreationFailed(i32),
 
+    #[error("Failed to update application: {0}")]
+    SetApplicationInfoFailed(i32),
+
     #[error("Failed to set setting: {0}")]
     SetSettingFailed(i32),
 
     #[error("Failed to get setting: {0}")]
     GetSettingFailed(i32),
 
+    #[error("The driver doesn't accept value {value} for setting 0x{setting_id:08X}")]
+    InvalidSettingValue { setting_id: u32, value: u32 },
+
     #[error("Function not found in NVAPI: {0}")]
     FunctionNotFound(String),
 
+    #[error("This driver's DRS API is incomplete, so changes are disabled (missing {0})")]
+    DrsApiIncomplete(String),
+
+    #[error("{feature} is not supported by this driver ({function} is missing)")]
+    UnsupportedByDriver { function: String, feature: String },
+
     #[error("NVAPI error code: {0}")]
     NvApiStatus(i32),
 
     #[error("Not supported on this platform")]
     NotSupported,
+
+    #[error("Export failed: {0}")]
+    ExportFailed(String),
+
+    #[error("Backup sessions are read-only")]
+    ReadOnlySession,
+
+    #[error("Backup session not found: {0}")]
+    BackupSessionNotFound(u32),
+
+    #[error("Operation aborted: {0}")]
+    OperationAborted(String),
+
+    #[error("Verification failed: {0}")]
+    VerificationFailed(String),
+
+    #[error("The NVIDIA driver is changing; try again once it has finished installing")]
+    DriverChanging,
+}
+
+fn blocker_hint(blocker: &Option<String>) -> String {
+    match blocker {
+        Some(names) => format!(" ({} may be holding unsaved changes; close it and retry)", names),
+        None => String::new(),
+    }
 }
 
 impl From<NvApiError> for String {
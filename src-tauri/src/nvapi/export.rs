@@ -0,0 +1,64 @@
+//! Profile export
+//!
+//! Writes DRS profiles in formats that can be restored without Nvidiot installed.
+
+use std::fmt::Write as _;
+use std::fs;
+use super::error::NvApiError;
+use super::profiles::{find_profile_by_name, get_profile_with_applications};
+use super::session::with_fresh_session;
+use super::settings::get_all_settings;
+
+/// Escape a value for use inside a double-quoted batch file argument
+fn escape_bat(value: &str) -> String {
+    value.replace('%', "%%").replace('"', "")
+}
+
+/// Build a batch file that restores the given profiles with `nvprofileupdate`.
+/// Only user-modified DWORD settings are written; other settings are noted as comments.
+pub fn build_profiles_bat(profile_names: &[String]) -> Result<String, NvApiError> {
+    let mut bat = String::new();
+    let _ = writeln!(bat, "@echo off\r");
+    let _ = writeln!(bat, "REM Generated by Nvidiot - restores DRS profile settings with nvprofileupdate\r");
+
+    for name in profile_names {
+        let (profile, applications) = get_profile_with_applications(name)?;
+        let settings = get_all_settings(find_profile_by_name(name)?)?;
+        let profile_name = escape_bat(&profile.name);
+
+        let _ = writeln!(bat, "\r");
+        let _ = writeln!(bat, "REM Profile: {}\r", profile_name);
+        for app in &applications {
+            let _ = writeln!(bat, "REM   Application: {}\r", escape_bat(&app.executable));
+        }
+
+        for setting in settings.iter().filter(|s| !s.is_current_predefined) {
+            match setting.dword_value {
+                Some(value) => {
+                    let _ = writeln!(
+                        bat,
+                        "nvprofileupdate -a \"{}\" -s 0x{:08X}=0x{:08X}\r",
+                        profile_name, setting.setting_id, value,
+                    );
+                }
+                None => {
+                    let _ = writeln!(
+                        bat,
+                        "REM Skipped non-DWORD setting 0x{:08X} ({})\r",
+                        setting.setting_id, escape_bat(&setting.name),
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(bat)
+}
+
+/// Export the given profiles as a batch file at `dest_path`.
+/// Reads the saved state through a fresh session, leaving the global one alone.
+pub fn export_profiles_as_bat(profile_names: &[String], dest_path: &str) -> Result<(), NvApiError> {
+    let bat = with_fresh_session(|_| build_profiles_bat(profile_names))?;
+    fs::write(dest_path, bat)
+        .map_err(|e| NvApiError::ExportFailed(format!("{}: {}", dest_path, e)))
+}
@@ -6,10 +6,18 @@
 #![allow(non_snake_case)]
 #![allow(dead_code)]
 
+use std::cell::RefCell;
 use std::ffi::c_void;
 use std::ptr;
-use once_cell::sync::OnceCell;
+use std::sync::atomic::{AtomicU32, Ordering};
+#[cfg(target_os = "windows")]
+use std::time::{SystemTime, UNIX_EPOCH};
+#[cfg(target_os = "windows")]
+use std::sync::RwLock;
+#[cfg(target_os = "windows")]
+use once_cell::sync::Lazy;
 use super::error::{NvApiError, NVAPI_OK};
+use super::types::{FeatureAvailability, NvApiFunctionProbe, NvApiProbe};
 
 #[cfg(target_os = "windows")]
 use libloading::{Library, Symbol};
@@ -17,6 +25,7 @@ use libloading::{Library, Symbol};
 // Type aliases for NVAPI handles
 pub type NvDRSSessionHandle = *mut c_void;
 pub type NvDRSProfileHandle = *mut c_void;
+pub type NvPhysicalGpuHandle = *mut c_void;
 
 // NVAPI function IDs (from nvapi headers)
 const NVAPI_INITIALIZE: u32 = 0x0150E828;
@@ -25,9 +34,13 @@ const NVAPI_DRS_CREATE_SESSION: u32 = 0x0694D52E;
 const NVAPI_DRS_DESTROY_SESSION: u32 = 0xDAD9CFF8;
 const NVAPI_DRS_LOAD_SETTINGS: u32 = 0x375DBD6B;
 const NVAPI_DRS_SAVE_SETTINGS: u32 = 0xFCBC7E14;
+const NVAPI_DRS_LOAD_SETTINGS_FROM_FILE_EX: u32 = 0xC63C045B;
+const NVAPI_DRS_LOAD_SETTINGS_FROM_FILE: u32 = 0xD3EDE889;
+const NVAPI_DRS_SAVE_SETTINGS_TO_FILE: u32 = 0x2BE25DF8;
 const NVAPI_DRS_GET_NUM_PROFILES: u32 = 0x1DAE4FBC;
 const NVAPI_DRS_ENUM_PROFILES: u32 = 0xBC371EE0;
 const NVAPI_DRS_GET_PROFILE_INFO: u32 = 0x61CD6FD6;
+const NVAPI_DRS_SET_PROFILE_INFO: u32 = 0x16ABD3A9;
 const NVAPI_DRS_FIND_PROFILE_BY_NAME: u32 = 0x7E4A9A0B;
 const NVAPI_DRS_CREATE_PROFILE: u32 = 0xCC176068;
 const NVAPI_DRS_DELETE_PROFILE: u32 = 0x17093206;
@@ -35,24 +48,158 @@ const NVAPI_DRS_ENUM_APPLICATIONS: u32 = 0x7FA2173A;
 const NVAPI_DRS_FIND_APPLICATION_BY_NAME: u32 = 0xEEE566B2;
 const NVAPI_DRS_CREATE_APPLICATION: u32 = 0x4347A9DE;
 const NVAPI_DRS_DELETE_APPLICATION: u32 = 0x2C694BC6;
+const NVAPI_DRS_SET_APPLICATION_INFO: u32 = 0xD02C6F8F;
 const NVAPI_DRS_GET_SETTING: u32 = 0x73BF8338;
 const NVAPI_DRS_SET_SETTING: u32 = 0x577DD202;
+const NVAPI_DRS_ENUM_SETTINGS: u32 = 0xAE3039DA;
+const NVAPI_DRS_DELETE_PROFILE_SETTING: u32 = 0xE4A26362;
+const NVAPI_DRS_ENUM_AVAILABLE_SETTING_VALUES: u32 = 0x2EC39F90;
+const NVAPI_DRS_ENUM_AVAILABLE_SETTING_IDS: u32 = 0xF020614A;
+const NVAPI_DRS_GET_SETTING_NAME_FROM_ID: u32 = 0xD61CBE6E;
 const NVAPI_DRS_GET_BASE_PROFILE: u32 = 0xDA8466A0;
+const NVAPI_SYS_GET_DRIVER_AND_BRANCH_VERSION: u32 = 0x2926AAAD;
+const NVAPI_ENUM_PHYSICAL_GPUS: u32 = 0xE5AC921F;
+const NVAPI_GPU_GET_MEMORY_INFO: u32 = 0x07F9B368;
+const NVAPI_GPU_GET_QUADRO_STATUS: u32 = 0xE332FA47;
+
+/// Declares `NvApiFunction` and `NVAPI_FUNCTION_TABLE` from one list of
+/// variant, QueryInterface ID and exported name, so the table can't miss a variant
+macro_rules! nvapi_functions {
+    ($($variant:ident => $id:ident, $name:literal;)*) => {
+        /// Every NVAPI function we query. Matches over it are exhaustive, so a function
+        /// added here can't be left out of `NvApi::bind` or `NvApi::is_loaded`.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum NvApiFunction {
+            $($variant,)*
+        }
+
+        impl NvApiFunction {
+            /// QueryInterface ID
+            pub const fn id(self) -> u32 {
+                match self {
+                    $(NvApiFunction::$variant => $id,)*
+                }
+            }
+
+            pub const fn name(self) -> &'static str {
+                match self {
+                    $(NvApiFunction::$variant => $name,)*
+                }
+            }
+        }
+
+        /// Every function we query, in declaration order. `NvApi::load` binds these
+        /// and the diagnostics probe reports on them.
+        pub const NVAPI_FUNCTION_TABLE: &[NvApiFunction] = &[
+            $(NvApiFunction::$variant,)*
+        ];
+    };
+}
+
+nvapi_functions! {
+    Initialize => NVAPI_INITIALIZE, "NvAPI_Initialize";
+    Unload => NVAPI_UNLOAD, "NvAPI_Unload";
+    DrsCreateSession => NVAPI_DRS_CREATE_SESSION, "NvAPI_DRS_CreateSession";
+    DrsDestroySession => NVAPI_DRS_DESTROY_SESSION, "NvAPI_DRS_DestroySession";
+    DrsLoadSettings => NVAPI_DRS_LOAD_SETTINGS, "NvAPI_DRS_LoadSettings";
+    DrsSaveSettings => NVAPI_DRS_SAVE_SETTINGS, "NvAPI_DRS_SaveSettings";
+    DrsLoadSettingsFromFileEx => NVAPI_DRS_LOAD_SETTINGS_FROM_FILE_EX, "NvAPI_DRS_LoadSettingsFromFileEx";
+    DrsLoadSettingsFromFile => NVAPI_DRS_LOAD_SETTINGS_FROM_FILE, "NvAPI_DRS_LoadSettingsFromFile";
+    DrsSaveSettingsToFile => NVAPI_DRS_SAVE_SETTINGS_TO_FILE, "NvAPI_DRS_SaveSettingsToFile";
+    DrsGetNumProfiles => NVAPI_DRS_GET_NUM_PROFILES, "NvAPI_DRS_GetNumProfiles";
+    DrsEnumProfiles => NVAPI_DRS_ENUM_PROFILES, "NvAPI_DRS_EnumProfiles";
+    DrsGetProfileInfo => NVAPI_DRS_GET_PROFILE_INFO, "NvAPI_DRS_GetProfileInfo";
+    DrsSetProfileInfo => NVAPI_DRS_SET_PROFILE_INFO, "NvAPI_DRS_SetProfileInfo";
+    DrsFindProfileByName => NVAPI_DRS_FIND_PROFILE_BY_NAME, "NvAPI_DRS_FindProfileByName";
+    DrsCreateProfile => NVAPI_DRS_CREATE_PROFILE, "NvAPI_DRS_CreateProfile";
+    DrsDeleteProfile => NVAPI_DRS_DELETE_PROFILE, "NvAPI_DRS_DeleteProfile";
+    DrsEnumApplications => NVAPI_DRS_ENUM_APPLICATIONS, "NvAPI_DRS_EnumApplications";
+    DrsFindApplicationByName => NVAPI_DRS_FIND_APPLICATION_BY_NAME, "NvAPI_DRS_FindApplicationByName";
+    DrsCreateApplication => NVAPI_DRS_CREATE_APPLICATION, "NvAPI_DRS_CreateApplication";
+    DrsDeleteApplication => NVAPI_DRS_DELETE_APPLICATION, "NvAPI_DRS_DeleteApplication";
+    DrsSetApplicationInfo => NVAPI_DRS_SET_APPLICATION_INFO, "NvAPI_DRS_SetApplicationInfo";
+    DrsGetSetting => NVAPI_DRS_GET_SETTING, "NvAPI_DRS_GetSetting";
+    DrsSetSetting => NVAPI_DRS_SET_SETTING, "NvAPI_DRS_SetSetting";
+    DrsEnumSettings => NVAPI_DRS_ENUM_SETTINGS, "NvAPI_DRS_EnumSettings";
+    DrsDeleteProfileSetting => NVAPI_DRS_DELETE_PROFILE_SETTING, "NvAPI_DRS_DeleteProfileSetting";
+    DrsEnumAvailableSettingValues => NVAPI_DRS_ENUM_AVAILABLE_SETTING_VALUES, "NvAPI_DRS_EnumAvailableSettingValues";
+    DrsEnumAvailableSettingIds => NVAPI_DRS_ENUM_AVAILABLE_SETTING_IDS, "NvAPI_DRS_EnumAvailableSettingIds";
+    DrsGetSettingNameFromId => NVAPI_DRS_GET_SETTING_NAME_FROM_ID, "NvAPI_DRS_GetSettingNameFromId";
+    DrsGetBaseProfile => NVAPI_DRS_GET_BASE_PROFILE, "NvAPI_DRS_GetBaseProfile";
+    SysGetDriverAndBranchVersion => NVAPI_SYS_GET_DRIVER_AND_BRANCH_VERSION, "NvAPI_SYS_GetDriverAndBranchVersion";
+    EnumPhysicalGpus => NVAPI_ENUM_PHYSICAL_GPUS, "NvAPI_EnumPhysicalGPUs";
+    GpuGetMemoryInfo => NVAPI_GPU_GET_MEMORY_INFO, "NvAPI_GPU_GetMemoryInfo";
+    GpuGetQuadroStatus => NVAPI_GPU_GET_QUADRO_STATUS, "NvAPI_GPU_GetQuadroStatus";
+}
+
+/// DRS functions every mutating operation needs. Without them a change could be
+/// made but not saved, or saved without being readable back.
+pub const MINIMAL_DRS_FUNCTIONS: &[NvApiFunction] = &[
+    NvApiFunction::DrsCreateSession,
+    NvApiFunction::DrsDestroySession,
+    NvApiFunction::DrsLoadSettings,
+    NvApiFunction::DrsSaveSettings,
+    NvApiFunction::DrsEnumProfiles,
+    NvApiFunction::DrsEnumApplications,
+    NvApiFunction::DrsCreateApplication,
+    NvApiFunction::DrsGetSetting,
+    NvApiFunction::DrsSetSetting,
+];
+
+/// User-facing features that need functions some drivers don't export, with the
+/// functions each requires
+pub const OPTIONAL_FEATURES: &[(&str, &[NvApiFunction])] = &[
+    ("open-backup", &[NvApiFunction::DrsLoadSettingsFromFileEx]),
+    ("delete-application", &[NvApiFunction::DrsDeleteApplication]),
+    ("drs-file-transfer", &[NvApiFunction::DrsLoadSettingsFromFile, NvApiFunction::DrsSaveSettingsToFile]),
+    ("setting-values", &[NvApiFunction::DrsEnumAvailableSettingValues]),
+    ("setting-catalog", &[NvApiFunction::DrsEnumAvailableSettingIds, NvApiFunction::DrsGetSettingNameFromId]),
+    ("gpu-product-line", &[NvApiFunction::EnumPhysicalGpus, NvApiFunction::GpuGetQuadroStatus]),
+    ("gpu-memory", &[NvApiFunction::EnumPhysicalGpus, NvApiFunction::GpuGetMemoryInfo]),
+    ("driver-version", &[NvApiFunction::SysGetDriverAndBranchVersion]),
+];
+
+/// Unwrap an optional function pointer, reporting a missing one as unsupported
+/// for the feature that needs it rather than as a bug
+pub fn require_optional<T>(pointer: Option<T>, function: NvApiFunction, feature: &str) -> Result<T, NvApiError> {
+    pointer.ok_or_else(|| NvApiError::UnsupportedByDriver {
+        function: function.name().to_string(),
+        feature: feature.to_string(),
+    })
+}
+
+/// Number of times NvApi::load has been attempted
+static LOAD_ATTEMPTS: AtomicU32 = AtomicU32::new(0);
 
 // Structure versions (from nvapi headers)
 pub const NVDRS_PROFILE_VER: u32 = 0x10028; // MAKE_NVAPI_VERSION(NVDRS_PROFILE, 1)
 pub const NVDRS_APPLICATION_VER: u32 = 0x30038; // MAKE_NVAPI_VERSION(NVDRS_APPLICATION, 3)
-pub const NVDRS_SETTING_VER: u32 = 0x10058; // MAKE_NVAPI_VERSION(NVDRS_SETTING, 1)
+pub const NV_DISPLAY_DRIVER_MEMORY_INFO_VER: u32 = 0x20018; // MAKE_NVAPI_VERSION(NV_DISPLAY_DRIVER_MEMORY_INFO_V2, 2)
 
 // Constants
 pub const NVAPI_UNICODE_STRING_MAX: usize = 2048;
 pub const NVAPI_SETTING_MAX_VALUES: usize = 100;
+/// Room for the IDs from one EnumAvailableSettingIds call; drivers know a few thousand
+pub const NVAPI_MAX_AVAILABLE_SETTING_IDS: usize = 8192;
+pub const NVAPI_BINARY_DATA_MAX: usize = 4096;
+pub const NVAPI_SHORT_STRING_MAX: usize = 64;
+pub const NVAPI_MAX_PHYSICAL_GPUS: usize = 64;
 
 // ShadowPlay setting
 pub const SHADOWPLAY_SETTING_ID: u32 = 0x809D5F60;
 pub const SHADOWPLAY_DISABLED: u32 = 0x10000000;
 pub const SHADOWPLAY_ENABLED: u32 = 0x08000001;
 
+// Power management mode setting (PREFERRED_PSTATE)
+pub const POWER_MANAGEMENT_SETTING_ID: u32 = 0x1057EB71;
+pub const PREFERRED_PSTATE_ADAPTIVE: u32 = 0x0;
+pub const PREFERRED_PSTATE_PREFER_MAX: u32 = 0x1;
+pub const PREFERRED_PSTATE_DRIVER_CONTROLLED: u32 = 0x2;
+pub const PREFERRED_PSTATE_PREFER_CONSISTENT_PERFORMANCE: u32 = 0x3;
+pub const PREFERRED_PSTATE_PREFER_MIN: u32 = 0x4;
+pub const PREFERRED_PSTATE_OPTIMAL_POWER: u32 = 0x5;
+pub const PREFERRED_PSTATE_DEFAULT: u32 = PREFERRED_PSTATE_OPTIMAL_POWER;
+
 /// NVDRS_PROFILE structure
 #[repr(C)]
 #[derive(Clone)]
@@ -78,6 +225,23 @@ impl Default for NvdrsProfile {
     }
 }
 
+/// One value in NVDRS_SETTING_VALUES: a DWORD, a length-prefixed binary
+/// blob or a wide string, whichever is largest sets the size
+pub const NVDRS_SETTING_VALUE_WORDS: usize = 1025;
+
+/// NVDRS_SETTING_VALUES structure. Too large for the stack; allocate it zeroed on the heap.
+#[repr(C)]
+pub struct NvdrsSettingValues {
+    pub version: u32,
+    pub num_setting_values: u32,
+    pub setting_type: u32,
+    pub default_value: [u32; NVDRS_SETTING_VALUE_WORDS],
+    pub setting_values: [[u32; NVDRS_SETTING_VALUE_WORDS]; NVAPI_SETTING_MAX_VALUES],
+}
+
+// MAKE_NVAPI_VERSION(NVDRS_SETTING_VALUES, 1)
+pub const NVDRS_SETTING_VALUES_VER: u32 = std::mem::size_of::<NvdrsSettingValues>() as u32 | (1 << 16);
+
 /// NVDRS_APPLICATION structure
 #[repr(C)]
 #[derive(Clone)]
@@ -101,6 +265,45 @@ impl Default for NvdrsApplication {
     }
 }
 
+/// NVDRS_APPLICATION_V4 structure, which adds the optional match fields.
+/// There is no signer or publisher field; entries can only be matched by
+/// name, containing folder, command line or Metro package.
+#[repr(C)]
+#[derive(Clone)]
+pub struct NvdrsApplicationV4 {
+    pub version: u32,
+    pub is_predefined: u32,
+    pub app_name: [u16; NVAPI_UNICODE_STRING_MAX],
+    pub user_friendly_name: [u16; NVAPI_UNICODE_STRING_MAX],
+    pub launcher: [u16; NVAPI_UNICODE_STRING_MAX],
+    /// A file that must sit next to the executable for the entry to match
+    pub file_in_folder: [u16; NVAPI_UNICODE_STRING_MAX],
+    /// isMetro:1, isCommandLine:1, reserved:30
+    pub flags: u32,
+    pub command_line: [u16; NVAPI_UNICODE_STRING_MAX],
+}
+
+pub const NVDRS_APPLICATION_FLAG_METRO: u32 = 1 << 0;
+pub const NVDRS_APPLICATION_FLAG_COMMAND_LINE: u32 = 1 << 1;
+
+// MAKE_NVAPI_VERSION(NVDRS_APPLICATION_V4, 4)
+pub const NVDRS_APPLICATION_VER_V4: u32 = std::mem::size_of::<NvdrsApplicationV4>() as u32 | (4 << 16);
+
+impl Default for NvdrsApplicationV4 {
+    fn default() -> Self {
+        Self {
+            version: NVDRS_APPLICATION_VER_V4,
+            is_predefined: 0,
+            app_name: [0u16; NVAPI_UNICODE_STRING_MAX],
+            user_friendly_name: [0u16; NVAPI_UNICODE_STRING_MAX],
+            launcher: [0u16; NVAPI_UNICODE_STRING_MAX],
+            file_in_folder: [0u16; NVAPI_UNICODE_STRING_MAX],
+            flags: 0,
+            command_line: [0u16; NVAPI_UNICODE_STRING_MAX],
+        }
+    }
+}
+
 /// Setting type enum
 #[repr(u32)]
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -111,12 +314,21 @@ pub enum NvdrsSettingType {
     WString = 3,
 }
 
+/// NVDRS_BINARY_SETTING structure
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct NvdrsBinarySetting {
+    pub value_length: u32,
+    pub value_data: [u8; NVAPI_BINARY_DATA_MAX],
+}
+
 /// NVDRS_SETTING_UNION
 #[repr(C)]
 #[derive(Clone, Copy)]
 pub union NvdrsSettingValue {
     pub dword_value: u32,
-    pub binary_value: [u8; NVAPI_SETTING_MAX_VALUES],
+    pub binary_value: NvdrsBinarySetting,
+    pub wsz_value: [u16; NVAPI_UNICODE_STRING_MAX],
 }
 
 impl Default for NvdrsSettingValue {
@@ -125,6 +337,18 @@ impl Default for NvdrsSettingValue {
     }
 }
 
+/// NV_DISPLAY_DRIVER_MEMORY_INFO_V2 structure; sizes are in KB
+#[repr(C)]
+#[derive(Clone, Default)]
+pub struct NvDisplayDriverMemoryInfo {
+    pub version: u32,
+    pub dedicated_video_memory: u32,
+    pub available_dedicated_video_memory: u32,
+    pub system_video_memory: u32,
+    pub shared_system_memory: u32,
+    pub cur_available_dedicated_video_memory: u32,
+}
+
 /// NVDRS_SETTING structure
 #[repr(C)]
 #[derive(Clone)]
@@ -140,6 +364,9 @@ pub struct NvdrsSetting {
     pub current_value: NvdrsSettingValue,
 }
 
+// MAKE_NVAPI_VERSION(NVDRS_SETTING, 1)
+pub const NVDRS_SETTING_VER: u32 = std::mem::size_of::<NvdrsSetting>() as u32 | (1 << 16);
+
 impl Default for NvdrsSetting {
     fn default() -> Self {
         Self {
@@ -164,47 +391,81 @@ type NvApiDrsCreateSessionFn = unsafe extern "C" fn(session: *mut NvDRSSessionHa
 type NvApiDrsDestroySessionFn = unsafe extern "C" fn(session: NvDRSSessionHandle) -> i32;
 type NvApiDrsLoadSettingsFn = unsafe extern "C" fn(session: NvDRSSessionHandle) -> i32;
 type NvApiDrsSaveSettingsFn = unsafe extern "C" fn(session: NvDRSSessionHandle) -> i32;
+type NvApiDrsLoadSettingsFromFileExFn = unsafe extern "C" fn(session: NvDRSSessionHandle, file_name: *const u16) -> i32;
+type NvApiDrsLoadSettingsFromFileFn = unsafe extern "C" fn(session: NvDRSSessionHandle, file_name: *const u16) -> i32;
+type NvApiDrsSaveSettingsToFileFn = unsafe extern "C" fn(session: NvDRSSessionHandle, file_name: *const u16) -> i32;
 type NvApiDrsGetNumProfilesFn = unsafe extern "C" fn(session: NvDRSSessionHandle, count: *mut u32) -> i32;
 type NvApiDrsEnumProfilesFn = unsafe extern "C" fn(session: NvDRSSessionHandle, index: u32, profile: *mut NvDRSProfileHandle) -> i32;
 type NvApiDrsGetProfileInfoFn = unsafe extern "C" fn(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, info: *mut NvdrsProfile) -> i32;
+type NvApiDrsSetProfileInfoFn = unsafe extern "C" fn(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, info: *mut NvdrsProfile) -> i32;
 type NvApiDrsFindProfileByNameFn = unsafe extern "C" fn(session: NvDRSSessionHandle, name: *const u16, profile: *mut NvDRSProfileHandle) -> i32;
 type NvApiDrsCreateProfileFn = unsafe extern "C" fn(session: NvDRSSessionHandle, info: *mut NvdrsProfile, profile: *mut NvDRSProfileHandle) -> i32;
+type NvApiDrsDeleteProfileFn = unsafe extern "C" fn(session: NvDRSSessionHandle, profile: NvDRSProfileHandle) -> i32;
 type NvApiDrsEnumApplicationsFn = unsafe extern "C" fn(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, start: u32, count: *mut u32, apps: *mut NvdrsApplication) -> i32;
 type NvApiDrsFindApplicationByNameFn = unsafe extern "C" fn(session: NvDRSSessionHandle, name: *const u16, profile: *mut NvDRSProfileHandle, app: *mut NvdrsApplication) -> i32;
 type NvApiDrsCreateApplicationFn = unsafe extern "C" fn(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, app: *mut NvdrsApplication) -> i32;
+type NvApiDrsDeleteApplicationFn = unsafe extern "C" fn(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, app_name: *const u16) -> i32;
+type NvApiDrsSetApplicationInfoFn = unsafe extern "C" fn(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, app: *mut NvdrsApplication) -> i32;
 type NvApiDrsGetSettingFn = unsafe extern "C" fn(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, setting_id: u32, setting: *mut NvdrsSetting) -> i32;
 type NvApiDrsSetSettingFn = unsafe extern "C" fn(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, setting: *mut NvdrsSetting) -> i32;
+type NvApiDrsEnumSettingsFn = unsafe extern "C" fn(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, start: u32, count: *mut u32, settings: *mut NvdrsSetting) -> i32;
+type NvApiDrsDeleteProfileSettingFn = unsafe extern "C" fn(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, setting_id: u32) -> i32;
+type NvApiDrsEnumAvailableSettingValuesFn = unsafe extern "C" fn(setting_id: u32, max_values: *mut u32, values: *mut NvdrsSettingValues) -> i32;
+type NvApiDrsEnumAvailableSettingIdsFn = unsafe extern "C" fn(setting_ids: *mut u32, max_count: *mut u32) -> i32;
+type NvApiDrsGetSettingNameFromIdFn = unsafe extern "C" fn(setting_id: u32, name: *mut [u16; NVAPI_UNICODE_STRING_MAX]) -> i32;
 type NvApiDrsGetBaseProfileFn = unsafe extern "C" fn(session: NvDRSSessionHandle, profile: *mut NvDRSProfileHandle) -> i32;
+type NvApiEnumPhysicalGpusFn = unsafe extern "C" fn(handles: *mut [NvPhysicalGpuHandle; NVAPI_MAX_PHYSICAL_GPUS], count: *mut u32) -> i32;
+type NvApiGpuGetMemoryInfoFn = unsafe extern "C" fn(gpu: NvPhysicalGpuHandle, info: *mut NvDisplayDriverMemoryInfo) -> i32;
+type NvApiGpuGetQuadroStatusFn = unsafe extern "C" fn(gpu: NvPhysicalGpuHandle, status: *mut u32) -> i32;
+type NvApiSysGetDriverAndBranchVersionFn = unsafe extern "C" fn(driver_version: *mut u32, branch: *mut [u8; NVAPI_SHORT_STRING_MAX]) -> i32;
 
 /// NVAPI function pointers
 #[cfg(target_os = "windows")]
 pub struct NvApi {
-    _library: Library,
+    /// None for the test backend, which has no library behind it
+    _library: Option<Library>,
     query_interface: NvApiQueryInterfaceFn,
+    loaded_at: SystemTime,
     pub initialize: Option<NvApiInitializeFn>,
     pub unload: Option<NvApiUnloadFn>,
     pub drs_create_session: Option<NvApiDrsCreateSessionFn>,
     pub drs_destroy_session: Option<NvApiDrsDestroySessionFn>,
     pub drs_load_settings: Option<NvApiDrsLoadSettingsFn>,
     pub drs_save_settings: Option<NvApiDrsSaveSettingsFn>,
+    pub drs_load_settings_from_file_ex: Option<NvApiDrsLoadSettingsFromFileExFn>,
+    pub drs_load_settings_from_file: Option<NvApiDrsLoadSettingsFromFileFn>,
+    pub drs_save_settings_to_file: Option<NvApiDrsSaveSettingsToFileFn>,
     pub drs_get_num_profiles: Option<NvApiDrsGetNumProfilesFn>,
     pub drs_enum_profiles: Option<NvApiDrsEnumProfilesFn>,
     pub drs_get_profile_info: Option<NvApiDrsGetProfileInfoFn>,
+    pub drs_set_profile_info: Option<NvApiDrsSetProfileInfoFn>,
     pub drs_find_profile_by_name: Option<NvApiDrsFindProfileByNameFn>,
     pub drs_create_profile: Option<NvApiDrsCreateProfileFn>,
+    pub drs_delete_profile: Option<NvApiDrsDeleteProfileFn>,
     pub drs_enum_applications: Option<NvApiDrsEnumApplicationsFn>,
     pub drs_find_application_by_name: Option<NvApiDrsFindApplicationByNameFn>,
     pub drs_create_application: Option<NvApiDrsCreateApplicationFn>,
+    pub drs_delete_application: Option<NvApiDrsDeleteApplicationFn>,
+    pub drs_set_application_info: Option<NvApiDrsSetApplicationInfoFn>,
     pub drs_get_setting: Option<NvApiDrsGetSettingFn>,
     pub drs_set_setting: Option<NvApiDrsSetSettingFn>,
+    pub drs_enum_settings: Option<NvApiDrsEnumSettingsFn>,
+    pub drs_delete_profile_setting: Option<NvApiDrsDeleteProfileSettingFn>,
+    pub drs_enum_available_setting_values: Option<NvApiDrsEnumAvailableSettingValuesFn>,
+    pub drs_enum_available_setting_ids: Option<NvApiDrsEnumAvailableSettingIdsFn>,
+    pub drs_get_setting_name_from_id: Option<NvApiDrsGetSettingNameFromIdFn>,
     pub drs_get_base_profile: Option<NvApiDrsGetBaseProfileFn>,
+    pub sys_get_driver_and_branch_version: Option<NvApiSysGetDriverAndBranchVersionFn>,
+    pub enum_physical_gpus: Option<NvApiEnumPhysicalGpusFn>,
+    pub gpu_get_memory_info: Option<NvApiGpuGetMemoryInfoFn>,
+    pub gpu_get_quadro_status: Option<NvApiGpuGetQuadroStatusFn>,
 }
 
 #[cfg(target_os = "windows")]
 impl NvApi {
-    fn get_fn<T>(&self, id: u32) -> Option<T> {
+    fn get_fn<T>(&self, function: NvApiFunction) -> Option<T> {
         unsafe {
-            let ptr = (self.query_interface)(id);
+            let ptr = (self.query_interface)(function.id());
             if ptr.is_null() {
                 None
             } else {
@@ -213,7 +474,91 @@ impl NvApi {
         }
     }
 
+    /// An instance with no function bound yet
+    pub(crate) fn unbound(library: Option<Library>, query_interface: NvApiQueryInterfaceFn) -> Self {
+        Self {
+            _library: library,
+            query_interface,
+            loaded_at: SystemTime::now(),
+            initialize: None,
+            unload: None,
+            drs_create_session: None,
+            drs_destroy_session: None,
+            drs_load_settings: None,
+            drs_save_settings: None,
+            drs_load_settings_from_file_ex: None,
+            drs_load_settings_from_file: None,
+            drs_save_settings_to_file: None,
+            drs_get_num_profiles: None,
+            drs_enum_profiles: None,
+            drs_get_profile_info: None,
+            drs_set_profile_info: None,
+            drs_find_profile_by_name: None,
+            drs_create_profile: None,
+            drs_delete_profile: None,
+            drs_enum_applications: None,
+            drs_find_application_by_name: None,
+            drs_create_application: None,
+            drs_delete_application: None,
+            drs_set_application_info: None,
+            drs_get_setting: None,
+            drs_set_setting: None,
+            drs_enum_settings: None,
+            drs_delete_profile_setting: None,
+            drs_enum_available_setting_values: None,
+            drs_enum_available_setting_ids: None,
+            drs_get_setting_name_from_id: None,
+            drs_get_base_profile: None,
+            sys_get_driver_and_branch_version: None,
+            enum_physical_gpus: None,
+            gpu_get_memory_info: None,
+            gpu_get_quadro_status: None,
+        }
+    }
+
+    /// Fetch one function pointer into its field, leaving it `None` when the
+    /// driver doesn't export it
+    pub(crate) fn bind(&mut self, function: NvApiFunction) {
+        match function {
+            NvApiFunction::Initialize => self.initialize = self.get_fn(function),
+            NvApiFunction::Unload => self.unload = self.get_fn(function),
+            NvApiFunction::DrsCreateSession => self.drs_create_session = self.get_fn(function),
+            NvApiFunction::DrsDestroySession => self.drs_destroy_session = self.get_fn(function),
+            NvApiFunction::DrsLoadSettings => self.drs_load_settings = self.get_fn(function),
+            NvApiFunction::DrsSaveSettings => self.drs_save_settings = self.get_fn(function),
+            NvApiFunction::DrsLoadSettingsFromFileEx => self.drs_load_settings_from_file_ex = self.get_fn(function),
+            NvApiFunction::DrsLoadSettingsFromFile => self.drs_load_settings_from_file = self.get_fn(function),
+            NvApiFunction::DrsSaveSettingsToFile => self.drs_save_settings_to_file = self.get_fn(function),
+            NvApiFunction::DrsGetNumProfiles => self.drs_get_num_profiles = self.get_fn(function),
+            NvApiFunction::DrsEnumProfiles => self.drs_enum_profiles = self.get_fn(function),
+            NvApiFunction::DrsGetProfileInfo => self.drs_get_profile_info = self.get_fn(function),
+            NvApiFunction::DrsSetProfileInfo => self.drs_set_profile_info = self.get_fn(function),
+            NvApiFunction::DrsFindProfileByName => self.drs_find_profile_by_name = self.get_fn(function),
+            NvApiFunction::DrsCreateProfile => self.drs_create_profile = self.get_fn(function),
+            NvApiFunction::DrsDeleteProfile => self.drs_delete_profile = self.get_fn(function),
+            NvApiFunction::DrsEnumApplications => self.drs_enum_applications = self.get_fn(function),
+            NvApiFunction::DrsFindApplicationByName => self.drs_find_application_by_name = self.get_fn(function),
+            NvApiFunction::DrsCreateApplication => self.drs_create_application = self.get_fn(function),
+            NvApiFunction::DrsDeleteApplication => self.drs_delete_application = self.get_fn(function),
+            NvApiFunction::DrsSetApplicationInfo => self.drs_set_application_info = self.get_fn(function),
+            NvApiFunction::DrsGetSetting => self.drs_get_setting = self.get_fn(function),
+            NvApiFunction::DrsSetSetting => self.drs_set_setting = self.get_fn(function),
+            NvApiFunction::DrsEnumSettings => self.drs_enum_settings = self.get_fn(function),
+            NvApiFunction::DrsDeleteProfileSetting => self.drs_delete_profile_setting = self.get_fn(function),
+            NvApiFunction::DrsEnumAvailableSettingValues => self.drs_enum_available_setting_values = self.get_fn(function),
+            NvApiFunction::DrsEnumAvailableSettingIds => self.drs_enum_available_setting_ids = self.get_fn(function),
+            NvApiFunction::DrsGetSettingNameFromId => self.drs_get_setting_name_from_id = self.get_fn(function),
+            NvApiFunction::DrsGetBaseProfile => self.drs_get_base_profile = self.get_fn(function),
+            NvApiFunction::SysGetDriverAndBranchVersion => self.sys_get_driver_and_branch_version = self.get_fn(function),
+            NvApiFunction::EnumPhysicalGpus => self.enum_physical_gpus = self.get_fn(function),
+            NvApiFunction::GpuGetMemoryInfo => self.gpu_get_memory_info = self.get_fn(function),
+            NvApiFunction::GpuGetQuadroStatus => self.gpu_get_quadro_status = self.get_fn(function),
+        }
+    }
+
     pub fn load() -> Result<Self, NvApiError> {
+        LOAD_ATTEMPTS.fetch_add(1, Ordering::Relaxed);
+
         unsafe {
             let library = Library::new("nvapi64.dll")
                 .map_err(|_| NvApiError::LibraryNotFound)?;
@@ -224,46 +569,11 @@ impl NvApi {
 
             let query_interface = *query_interface;
 
-            let mut api = Self {
-                _library: library,
-                query_interface,
-                initialize: None,
-                unload: None,
-                drs_create_session: None,
-                drs_destroy_session: None,
-                drs_load_settings: None,
-                drs_save_settings: None,
-                drs_get_num_profiles: None,
-                drs_enum_profiles: None,
-                drs_get_profile_info: None,
-                drs_find_profile_by_name: None,
-                drs_create_profile: None,
-                drs_enum_applications: None,
-                drs_find_application_by_name: None,
-                drs_create_application: None,
-                drs_get_setting: None,
-                drs_set_setting: None,
-                drs_get_base_profile: None,
-            };
-
-            // Load function pointers
-            api.initialize = api.get_fn(NVAPI_INITIALIZE);
-            api.unload = api.get_fn(NVAPI_UNLOAD);
-            api.drs_create_session = api.get_fn(NVAPI_DRS_CREATE_SESSION);
-            api.drs_destroy_session = api.get_fn(NVAPI_DRS_DESTROY_SESSION);
-            api.drs_load_settings = api.get_fn(NVAPI_DRS_LOAD_SETTINGS);
-            api.drs_save_settings = api.get_fn(NVAPI_DRS_SAVE_SETTINGS);
-            api.drs_get_num_profiles = api.get_fn(NVAPI_DRS_GET_NUM_PROFILES);
-            api.drs_enum_profiles = api.get_fn(NVAPI_DRS_ENUM_PROFILES);
-            api.drs_get_profile_info = api.get_fn(NVAPI_DRS_GET_PROFILE_INFO);
-            api.drs_find_profile_by_name = api.get_fn(NVAPI_DRS_FIND_PROFILE_BY_NAME);
-            api.drs_create_profile = api.get_fn(NVAPI_DRS_CREATE_PROFILE);
-            api.drs_enum_applications = api.get_fn(NVAPI_DRS_ENUM_APPLICATIONS);
-            api.drs_find_application_by_name = api.get_fn(NVAPI_DRS_FIND_APPLICATION_BY_NAME);
-            api.drs_create_application = api.get_fn(NVAPI_DRS_CREATE_APPLICATION);
-            api.drs_get_setting = api.get_fn(NVAPI_DRS_GET_SETTING);
-            api.drs_set_setting = api.get_fn(NVAPI_DRS_SET_SETTING);
-            api.drs_get_base_profile = api.get_fn(NVAPI_DRS_GET_BASE_PROFILE);
+            let mut api = Self::unbound(Some(library), query_interface);
+
+            for &function in NVAPI_FUNCTION_TABLE {
+                api.bind(function);
+            }
 
             // Initialize NVAPI
             if let Some(init) = api.initialize {
@@ -278,20 +588,116 @@ impl NvApi {
             Ok(api)
         }
     }
+
+    /// Whether QueryInterface returned a pointer for the given function.
+    /// Uses the pointers fetched in `load` rather than querying again.
+    pub fn is_loaded(&self, function: NvApiFunction) -> bool {
+        match function {
+            NvApiFunction::Initialize => self.initialize.is_some(),
+            NvApiFunction::Unload => self.unload.is_some(),
+            NvApiFunction::DrsCreateSession => self.drs_create_session.is_some(),
+            NvApiFunction::DrsDestroySession => self.drs_destroy_session.is_some(),
+            NvApiFunction::DrsLoadSettings => self.drs_load_settings.is_some(),
+            NvApiFunction::DrsSaveSettings => self.drs_save_settings.is_some(),
+            NvApiFunction::DrsLoadSettingsFromFileEx => self.drs_load_settings_from_file_ex.is_some(),
+            NvApiFunction::DrsLoadSettingsFromFile => self.drs_load_settings_from_file.is_some(),
+            NvApiFunction::DrsSaveSettingsToFile => self.drs_save_settings_to_file.is_some(),
+            NvApiFunction::DrsGetNumProfiles => self.drs_get_num_profiles.is_some(),
+            NvApiFunction::DrsEnumProfiles => self.drs_enum_profiles.is_some(),
+            NvApiFunction::DrsGetProfileInfo => self.drs_get_profile_info.is_some(),
+            NvApiFunction::DrsSetProfileInfo => self.drs_set_profile_info.is_some(),
+            NvApiFunction::DrsFindProfileByName => self.drs_find_profile_by_name.is_some(),
+            NvApiFunction::DrsCreateProfile => self.drs_create_profile.is_some(),
+            NvApiFunction::DrsDeleteProfile => self.drs_delete_profile.is_some(),
+            NvApiFunction::DrsEnumApplications => self.drs_enum_applications.is_some(),
+            NvApiFunction::DrsFindApplicationByName => self.drs_find_application_by_name.is_some(),
+            NvApiFunction::DrsCreateApplication => self.drs_create_application.is_some(),
+            NvApiFunction::DrsDeleteApplication => self.drs_delete_application.is_some(),
+            NvApiFunction::DrsSetApplicationInfo => self.drs_set_application_info.is_some(),
+            NvApiFunction::DrsGetSetting => self.drs_get_setting.is_some(),
+            NvApiFunction::DrsSetSetting => self.drs_set_setting.is_some(),
+            NvApiFunction::DrsEnumSettings => self.drs_enum_settings.is_some(),
+            NvApiFunction::DrsDeleteProfileSetting => self.drs_delete_profile_setting.is_some(),
+            NvApiFunction::DrsEnumAvailableSettingValues => self.drs_enum_available_setting_values.is_some(),
+            NvApiFunction::DrsEnumAvailableSettingIds => self.drs_enum_available_setting_ids.is_some(),
+            NvApiFunction::DrsGetSettingNameFromId => self.drs_get_setting_name_from_id.is_some(),
+            NvApiFunction::DrsGetBaseProfile => self.drs_get_base_profile.is_some(),
+            NvApiFunction::SysGetDriverAndBranchVersion => self.sys_get_driver_and_branch_version.is_some(),
+            NvApiFunction::EnumPhysicalGpus => self.enum_physical_gpus.is_some(),
+            NvApiFunction::GpuGetMemoryInfo => self.gpu_get_memory_info.is_some(),
+            NvApiFunction::GpuGetQuadroStatus => self.gpu_get_quadro_status.is_some(),
+        }
+    }
+
+    /// Whether every DRS function in the probe table resolved, optional ones included
+    pub fn is_drs_api_complete(&self) -> bool {
+        NVAPI_FUNCTION_TABLE.iter()
+            .filter(|function| function.name().starts_with("NvAPI_DRS_"))
+            .all(|&function| self.is_loaded(function))
+    }
+
+    /// Names of the functions from `MINIMAL_DRS_FUNCTIONS` this driver doesn't export
+    pub fn missing_minimal_drs_functions(&self) -> Vec<&'static str> {
+        MINIMAL_DRS_FUNCTIONS.iter()
+            .filter(|&&function| !self.is_loaded(function))
+            .map(|function| function.name())
+            .collect()
+    }
+
+    /// Whether the core DRS functions needed to change and save settings resolved
+    pub fn is_minimal_drs_api_available(&self) -> bool {
+        self.missing_minimal_drs_functions().is_empty()
+    }
 }
 
-// Global NVAPI instance
+// Global NVAPI instance. Replaced wholesale when the driver is reinstalled; a
+// replaced instance is leaked because callers may still hold references to it.
 #[cfg(target_os = "windows")]
-static NVAPI: OnceCell<Result<NvApi, NvApiError>> = OnceCell::new();
+static NVAPI: Lazy<RwLock<Option<Result<&'static NvApi, i32>>>> = Lazy::new(|| RwLock::new(None));
 
 #[cfg(target_os = "windows")]
-pub fn get_nvapi() -> Result<&'static NvApi, NvApiError> {
-    NVAPI.get_or_init(|| NvApi::load())
-        .as_ref()
-        .map_err(|e| NvApiError::InitializationFailed(match e {
-            NvApiError::InitializationFailed(code) => *code,
+fn load_leaked() -> Result<&'static NvApi, i32> {
+    NvApi::load()
+        .map(|api| &*Box::leak(Box::new(api)))
+        .map_err(|e| match e {
+            NvApiError::InitializationFailed(code) => code,
             _ => -1,
-        }))
+        })
+}
+
+#[cfg(target_os = "windows")]
+pub fn get_nvapi() -> Result<&'static NvApi, NvApiError> {
+    if super::health::is_driver_changing() {
+        return Err(NvApiError::DriverChanging);
+    }
+
+    if let Some(loaded) = *NVAPI.read().unwrap() {
+        return loaded.map_err(NvApiError::InitializationFailed);
+    }
+
+    let mut slot = NVAPI.write().unwrap();
+    let loaded = *slot.get_or_insert_with(load_leaked);
+    loaded.map_err(NvApiError::InitializationFailed)
+}
+
+/// Swap in an instance whose functions are already bound, for tests against the mock backend
+#[cfg(all(test, target_os = "windows"))]
+pub(crate) fn install_nvapi(api: NvApi) {
+    *NVAPI.write().unwrap() = Some(Ok(Box::leak(Box::new(api))));
+}
+
+/// Load nvapi64.dll again and swap it in, e.g. after a driver reinstall.
+/// Handles obtained from the previous instance must not be used afterwards.
+#[cfg(target_os = "windows")]
+pub fn reload_nvapi() -> Result<(), NvApiError> {
+    let loaded = load_leaked();
+    *NVAPI.write().unwrap() = Some(loaded);
+    loaded.map(|_| ()).map_err(NvApiError::InitializationFailed)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn reload_nvapi() -> Result<(), NvApiError> {
+    Err(NvApiError::NotSupported)
 }
 
 // Non-Windows stub
@@ -300,15 +706,542 @@ pub fn get_nvapi() -> Result<(), NvApiError> {
     Err(NvApiError::NotSupported)
 }
 
+/// Report which NVAPI functions resolved, reusing the already-loaded pointers
+#[cfg(target_os = "windows")]
+pub fn probe_functions() -> NvApiProbe {
+    let api = get_nvapi().ok();
+    let loaded_at_ms = api.and_then(|api| api.loaded_at.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64);
+    let load_attempts = LOAD_ATTEMPTS.load(Ordering::Relaxed);
+
+    NvApiProbe {
+        loaded_at_ms,
+        load_attempts,
+        reinitialized: load_attempts > 1,
+        functions: NVAPI_FUNCTION_TABLE.iter()
+            .map(|&function| NvApiFunctionProbe {
+                id: function.id(),
+                name: function.name().to_string(),
+                resolved: api.map(|api| api.is_loaded(function)).unwrap_or(false),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn probe_functions() -> NvApiProbe {
+    NvApiProbe {
+        loaded_at_ms: None,
+        load_attempts: LOAD_ATTEMPTS.load(Ordering::Relaxed),
+        reinitialized: false,
+        functions: NVAPI_FUNCTION_TABLE.iter()
+            .map(|&function| NvApiFunctionProbe {
+                id: function.id(),
+                name: function.name().to_string(),
+                resolved: false,
+            })
+            .collect(),
+    }
+}
+
+/// Installed driver version, e.g. 56694 for 566.94
+#[cfg(target_os = "windows")]
+pub fn driver_version() -> Option<u32> {
+    let api = get_nvapi().ok()?;
+    let get_version = api.sys_get_driver_and_branch_version?;
+
+    let mut version: u32 = 0;
+    let mut branch = [0u8; NVAPI_SHORT_STRING_MAX];
+    let status = unsafe { get_version(&mut version, &mut branch) };
+    (status == NVAPI_OK).then_some(version)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn driver_version() -> Option<u32> {
+    None
+}
+
+/// Dedicated video memory in use on the first GPU by all processes, in MB.
+/// NVAPI can't attribute memory to a process; NVML's gpu_processes does.
+#[cfg(target_os = "windows")]
+pub fn device_memory_used_mb() -> Option<u32> {
+    let api = get_nvapi().ok()?;
+    let enum_gpus = api.enum_physical_gpus?;
+    let get_memory_info = api.gpu_get_memory_info?;
+
+    unsafe {
+        let mut handles: [NvPhysicalGpuHandle; NVAPI_MAX_PHYSICAL_GPUS] = [ptr::null_mut(); NVAPI_MAX_PHYSICAL_GPUS];
+        let mut count: u32 = 0;
+        if enum_gpus(&mut handles, &mut count) != NVAPI_OK || count == 0 {
+            return None;
+        }
+
+        let mut info = NvDisplayDriverMemoryInfo {
+            version: NV_DISPLAY_DRIVER_MEMORY_INFO_VER,
+            ..Default::default()
+        };
+        if get_memory_info(handles[0], &mut info) != NVAPI_OK {
+            return None;
+        }
+
+        let used_kb = info.dedicated_video_memory.saturating_sub(info.cur_available_dedicated_video_memory);
+        Some(used_kb / 1024)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn device_memory_used_mb() -> Option<u32> {
+    None
+}
+
+/// Whether each physical GPU is a Quadro (workstation) part, in enumeration order.
+/// None if the GPUs can't be enumerated or the driver can't tell.
+#[cfg(target_os = "windows")]
+pub fn physical_gpu_quadro_status() -> Option<Vec<bool>> {
+    let api = get_nvapi().ok()?;
+    let enum_gpus = api.enum_physical_gpus?;
+    let get_quadro_status = api.gpu_get_quadro_status?;
+
+    unsafe {
+        let mut handles: [NvPhysicalGpuHandle; NVAPI_MAX_PHYSICAL_GPUS] = [ptr::null_mut(); NVAPI_MAX_PHYSICAL_GPUS];
+        let mut count: u32 = 0;
+        if enum_gpus(&mut handles, &mut count) != NVAPI_OK || count == 0 {
+            return None;
+        }
+
+        handles[..(count as usize).min(NVAPI_MAX_PHYSICAL_GPUS)].iter()
+            .map(|&gpu| {
+                let mut status: u32 = 0;
+                (get_quadro_status(gpu, &mut status) == NVAPI_OK).then_some(status != 0)
+            })
+            .collect()
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn physical_gpu_quadro_status() -> Option<Vec<bool>> {
+    None
+}
+
+/// Which optional features the loaded driver supports
+pub fn feature_availability() -> Vec<FeatureAvailability> {
+    #[cfg(target_os = "windows")]
+    let api = get_nvapi().ok();
+
+    OPTIONAL_FEATURES.iter()
+        .map(|&(feature, ids)| {
+            let missing_functions: Vec<String> = ids.iter()
+                .filter(|&&function| {
+                    #[cfg(target_os = "windows")]
+                    {
+                        !api.map(|api| api.is_loaded(function)).unwrap_or(false)
+                    }
+                    #[cfg(not(target_os = "windows"))]
+                    {
+                        let _ = function;
+                        true
+                    }
+                })
+                .map(|function| function.name().to_string())
+                .collect();
+            FeatureAvailability {
+                feature: feature.to_string(),
+                available: missing_functions.is_empty(),
+                missing_functions,
+            }
+        })
+        .collect()
+}
+
+/// Number of entries fetched per EnumApplications/EnumSettings call
+pub const ENUM_BATCH_SIZE: usize = 32;
+
+// Enumeration batch buffers, allocated once per thread and reused, since each
+// entry carries several 2048-wide string buffers that are costly to zero
+thread_local! {
+    static APPLICATION_BATCH: RefCell<Vec<NvdrsApplication>> = const { RefCell::new(Vec::new()) };
+    static SETTING_BATCH: RefCell<Vec<NvdrsSetting>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Run `f` with a reusable application batch buffer. Only `version` is reset
+/// between uses; NVAPI overwrites the rest of each returned entry.
+pub fn with_application_batch<T>(f: impl FnOnce(&mut [NvdrsApplication]) -> T) -> T {
+    APPLICATION_BATCH.with(|cell| {
+        let mut batch = cell.borrow_mut();
+        if batch.is_empty() {
+            *batch = vec![NvdrsApplication::default(); ENUM_BATCH_SIZE];
+        }
+        for app in batch.iter_mut() {
+            app.version = NVDRS_APPLICATION_VER;
+        }
+        f(&mut batch)
+    })
+}
+
+/// Run `f` with a reusable setting batch buffer. Only `version` is reset between uses.
+pub fn with_setting_batch<T>(f: impl FnOnce(&mut [NvdrsSetting]) -> T) -> T {
+    SETTING_BATCH.with(|cell| {
+        let mut batch = cell.borrow_mut();
+        if batch.is_empty() {
+            *batch = vec![NvdrsSetting::default(); ENUM_BATCH_SIZE];
+        }
+        for setting in batch.iter_mut() {
+            setting.version = NVDRS_SETTING_VER;
+        }
+        f(&mut batch)
+    })
+}
+
+/// Drop the batch buffers so the next enumeration allocates fresh, zeroed ones
+/// the way every call did before they were reused
+#[cfg(test)]
+pub fn reset_enum_batches() {
+    APPLICATION_BATCH.with(|cell| cell.borrow_mut().clear());
+    SETTING_BATCH.with(|cell| cell.borrow_mut().clear());
+}
+
+/// Fill every byte of the batch buffers with junk, standing in for whatever a
+/// previous enumeration left behind
+#[cfg(test)]
+pub fn scribble_enum_batches() {
+    with_application_batch(|apps| unsafe { ptr::write_bytes(apps.as_mut_ptr(), 0xA5, apps.len()) });
+    with_setting_batch(|settings| unsafe { ptr::write_bytes(settings.as_mut_ptr(), 0xA5, settings.len()) });
+}
+
 // Helper functions
 pub fn wchar_to_string(wchars: &[u16]) -> String {
+    let mut out = String::new();
+    wchar_to_string_into(wchars, &mut out);
+    out
+}
+
+/// Decode a NUL-terminated wide string into an existing String, reusing its allocation
+pub fn wchar_to_string_into(wchars: &[u16], out: &mut String) {
+    out.clear();
     let end = wchars.iter().position(|&c| c == 0).unwrap_or(wchars.len());
-    String::from_utf16_lossy(&wchars[..end])
+    out.extend(char::decode_utf16(wchars[..end].iter().copied())
+        .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER)));
 }
 
+/// Encode into a NUL-terminated wide buffer, truncating if needed. Truncation
+/// never splits a surrogate pair, so an over-long name doesn't end in half an emoji.
 pub fn string_to_wchar(s: &str, buffer: &mut [u16]) {
     let chars: Vec<u16> = s.encode_utf16().collect();
-    let len = chars.len().min(buffer.len() - 1);
+    let mut len = chars.len().min(buffer.len() - 1);
+    if len < chars.len() && len > 0 && (0xD800..0xDC00).contains(&chars[len - 1]) {
+        len -= 1;
+    }
     buffer[..len].copy_from_slice(&chars[..len]);
     buffer[len] = 0;
 }
+
+/// Whether a wide string decodes without replacement characters, i.e. the
+/// String from `wchar_to_string` can be encoded back to the same code units
+pub fn is_lossless_wchar(wchars: &[u16]) -> bool {
+    let end = wchars.iter().position(|&c| c == 0).unwrap_or(wchars.len());
+    char::decode_utf16(wchars[..end].iter().copied()).all(|c| c.is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(s: &str, capacity: usize) -> Vec<u16> {
+        let mut buffer = vec![0xFFFF; capacity];
+        string_to_wchar(s, &mut buffer);
+        buffer
+    }
+
+    // A layout mismatch would make the driver reject or misread every v4 call
+    #[test]
+    fn application_v4_matches_the_header_layout() {
+        use std::mem::{offset_of, size_of};
+
+        // sizeof(NVDRS_APPLICATION_V4) in the NVAPI headers
+        assert_eq!(size_of::<NvdrsApplicationV4>(), 0x500C);
+        assert_eq!(NVDRS_APPLICATION_VER_V4, 0x4500C);
+        assert_eq!(NvdrsApplicationV4::default().version, NVDRS_APPLICATION_VER_V4);
+
+        let string = 2 * NVAPI_UNICODE_STRING_MAX;
+        assert_eq!(offset_of!(NvdrsApplicationV4, version), 0);
+        assert_eq!(offset_of!(NvdrsApplicationV4, is_predefined), 4);
+        assert_eq!(offset_of!(NvdrsApplicationV4, app_name), 8);
+        assert_eq!(offset_of!(NvdrsApplicationV4, user_friendly_name), 8 + string);
+        assert_eq!(offset_of!(NvdrsApplicationV4, launcher), 8 + 2 * string);
+        assert_eq!(offset_of!(NvdrsApplicationV4, file_in_folder), 8 + 3 * string);
+        assert_eq!(offset_of!(NvdrsApplicationV4, flags), 8 + 4 * string);
+        assert_eq!(offset_of!(NvdrsApplicationV4, command_line), 12 + 4 * string);
+    }
+
+    #[test]
+    fn application_v4_starts_with_the_older_layout() {
+        use std::mem::offset_of;
+
+        // find_application_v4 passes the v4 struct where the older one is expected
+        assert_eq!(offset_of!(NvdrsApplicationV4, app_name), offset_of!(NvdrsApplication, app_name));
+        assert_eq!(offset_of!(NvdrsApplicationV4, user_friendly_name), offset_of!(NvdrsApplication, user_friendly_name));
+        assert_eq!(offset_of!(NvdrsApplicationV4, launcher), offset_of!(NvdrsApplication, launcher));
+        assert_eq!(offset_of!(NvdrsApplicationV4, file_in_folder), std::mem::size_of::<NvdrsApplication>());
+    }
+
+    #[test]
+    fn non_bmp_names_round_trip() {
+        let name = "Nvidiot - \u{1F3AE} Straße.exe";
+        let buffer = encode(name, NVAPI_UNICODE_STRING_MAX);
+        assert!(is_lossless_wchar(&buffer));
+        assert_eq!(wchar_to_string(&buffer), name);
+    }
+
+    #[test]
+    fn truncation_never_splits_a_surrogate_pair() {
+        // "ab" plus an emoji is four code units; three fit before the NUL
+        let buffer = encode("ab\u{1F3AE}", 4);
+        assert_eq!(buffer, [b'a' as u16, b'b' as u16, 0, 0xFFFF]);
+        assert!(is_lossless_wchar(&buffer));
+
+        let fits = encode("ab\u{1F3AE}", 5);
+        assert_eq!(wchar_to_string(&fits), "ab\u{1F3AE}");
+    }
+
+    #[test]
+    fn unpaired_surrogates_decode_lossily_and_are_detected() {
+        let stored = [b'a' as u16, 0xD83C, b'b' as u16, 0];
+        assert!(!is_lossless_wchar(&stored));
+        assert_eq!(wchar_to_string(&stored), "a\u{FFFD}b");
+    }
+
+    #[test]
+    fn decoding_stops_at_the_first_nul_and_reuses_the_buffer() {
+        let mut out = String::from("previous contents");
+        wchar_to_string_into(&[b'x' as u16, 0, b'y' as u16], &mut out);
+        assert_eq!(out, "x");
+        assert_eq!(wchar_to_string(&[b'z' as u16]), "z");
+    }
+
+    #[test]
+    fn the_function_table_has_unique_ids_and_names() {
+        let mut ids = std::collections::HashSet::new();
+        let mut names = std::collections::HashSet::new();
+        for &function in NVAPI_FUNCTION_TABLE {
+            assert!(ids.insert(function.id()), "{:?} reuses ID {:#010X}", function, function.id());
+            assert!(names.insert(function.name()), "{:?} reuses name {}", function, function.name());
+            assert!(function.name().starts_with("NvAPI_"), "{:?} is named {}", function, function.name());
+        }
+    }
+
+    #[test]
+    fn every_variant_is_in_the_table_once_in_declaration_order() {
+        for (index, &function) in NVAPI_FUNCTION_TABLE.iter().enumerate() {
+            assert_eq!(function as usize, index, "{:?} is out of place", function);
+        }
+        assert_eq!(NVAPI_FUNCTION_TABLE.len(), NvApiFunction::GpuGetQuadroStatus as usize + 1);
+        assert_eq!(
+            NVAPI_FUNCTION_TABLE.iter().find(|f| f.id() == NVAPI_DRS_GET_SETTING),
+            Some(&NvApiFunction::DrsGetSetting)
+        );
+    }
+
+    /// Features that degrade when `function` is missing, decided per variant.
+    /// The match is exhaustive, so a newly declared function has to be placed
+    /// here before the tests compile.
+    fn features_needing(function: NvApiFunction) -> &'static [&'static str] {
+        use NvApiFunction::*;
+        match function {
+            // The minimal set and the rest of the core DRS surface: without them
+            // calls fail as FunctionNotFound rather than reporting a feature
+            Initialize | Unload | DrsCreateSession | DrsDestroySession | DrsLoadSettings | DrsSaveSettings
+            | DrsGetNumProfiles | DrsEnumProfiles | DrsGetProfileInfo | DrsSetProfileInfo | DrsFindProfileByName
+            | DrsCreateProfile | DrsDeleteProfile | DrsEnumApplications | DrsCreateApplication
+            | DrsSetApplicationInfo | DrsGetSetting | DrsSetSetting | DrsEnumSettings | DrsDeleteProfileSetting
+            | DrsGetBaseProfile => &[],
+            // The v4 lookup falls back to the v3 one, which requires it
+            DrsFindApplicationByName => &[],
+            DrsLoadSettingsFromFileEx => &["open-backup"],
+            DrsDeleteApplication => &["delete-application"],
+            DrsLoadSettingsFromFile | DrsSaveSettingsToFile => &["drs-file-transfer"],
+            DrsEnumAvailableSettingValues => &["setting-values"],
+            DrsEnumAvailableSettingIds | DrsGetSettingNameFromId => &["setting-catalog"],
+            SysGetDriverAndBranchVersion => &["driver-version"],
+            EnumPhysicalGpus => &["gpu-product-line", "gpu-memory"],
+            GpuGetQuadroStatus => &["gpu-product-line"],
+            GpuGetMemoryInfo => &["gpu-memory"],
+        }
+    }
+
+    fn features_of(function: NvApiFunction) -> Vec<&'static str> {
+        OPTIONAL_FEATURES.iter()
+            .filter(|(_, functions)| functions.contains(&function))
+            .map(|&(feature, _)| feature)
+            .collect()
+    }
+
+    #[test]
+    fn optional_features_only_list_functions_outside_the_minimal_set() {
+        let mut seen = std::collections::HashSet::new();
+        for &(feature, functions) in OPTIONAL_FEATURES {
+            assert!(seen.insert(feature), "{} is listed twice", feature);
+            assert!(!functions.is_empty(), "{} needs no function", feature);
+            for function in functions {
+                assert!(NVAPI_FUNCTION_TABLE.contains(function));
+                assert!(!MINIMAL_DRS_FUNCTIONS.contains(function), "{} lists minimal {:?}", feature, function);
+            }
+        }
+    }
+
+    #[test]
+    fn optional_features_match_the_per_function_classification() {
+        for &function in NVAPI_FUNCTION_TABLE {
+            let mut listed = features_of(function);
+            let mut expected = features_needing(function).to_vec();
+            listed.sort();
+            expected.sort();
+            assert_eq!(listed, expected, "OPTIONAL_FEATURES disagrees about {:?}", function);
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    thread_local! {
+        static RESOLVED_ID: std::cell::Cell<Option<u32>> = const { std::cell::Cell::new(None) };
+        static MISSING_ID: std::cell::Cell<Option<u32>> = const { std::cell::Cell::new(None) };
+    }
+
+    /// Resolves only the ID set in `RESOLVED_ID`, or every ID when it's unset,
+    /// except the one set in `MISSING_ID`
+    #[cfg(target_os = "windows")]
+    unsafe extern "C" fn resolve(id: u32) -> *mut c_void {
+        if MISSING_ID.with(|missing| missing.get()) == Some(id) {
+            return ptr::null_mut();
+        }
+        match RESOLVED_ID.with(|resolved| resolved.get()) {
+            Some(only) if only != id => ptr::null_mut(),
+            _ => ptr::NonNull::<u8>::dangling().as_ptr().cast(),
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    fn bind_all() -> NvApi {
+        let mut api = NvApi::unbound(None, resolve);
+        for &function in NVAPI_FUNCTION_TABLE {
+            api.bind(function);
+        }
+        api
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn each_function_binds_into_its_own_field() {
+        for &function in NVAPI_FUNCTION_TABLE {
+            RESOLVED_ID.with(|resolved| resolved.set(Some(function.id())));
+            let api = bind_all();
+            for &other in NVAPI_FUNCTION_TABLE {
+                assert_eq!(api.is_loaded(other), other == function, "binding {:?} affected {:?}", function, other);
+            }
+        }
+        RESOLVED_ID.with(|resolved| resolved.set(None));
+
+        let api = bind_all();
+        assert!(NVAPI_FUNCTION_TABLE.iter().all(|&function| api.is_loaded(function)));
+        assert!(api.is_drs_api_complete());
+        assert!(api.is_minimal_drs_api_available());
+    }
+
+    #[cfg(target_os = "windows")]
+    #[test]
+    fn completeness_and_the_minimal_set_agree_about_each_missing_function() {
+        for &function in NVAPI_FUNCTION_TABLE {
+            MISSING_ID.with(|missing| missing.set(Some(function.id())));
+            let api = bind_all();
+            let minimal = MINIMAL_DRS_FUNCTIONS.contains(&function);
+            let drs = function.name().starts_with("NvAPI_DRS_");
+
+            assert_eq!(api.is_minimal_drs_api_available(), !minimal, "without {:?}", function);
+            assert_eq!(api.missing_minimal_drs_functions(), if minimal { vec![function.name()] } else { vec![] });
+            // A missing minimal function always leaves the DRS API incomplete
+            assert_eq!(api.is_drs_api_complete(), !drs, "without {:?}", function);
+            assert!(!minimal || drs, "{:?} is minimal but not a DRS function", function);
+        }
+        MISSING_ID.with(|missing| missing.set(None));
+    }
+}
+
+#[cfg(all(test, target_os = "windows"))]
+mod mock_tests {
+    use super::*;
+    use crate::nvapi::applications::delete_application;
+    use crate::nvapi::mock::{self, Profile, Store};
+    use crate::nvapi::profiles::find_profile_by_name;
+    use crate::nvapi::session::{close_backup, load_settings_from_file, open_backup_readonly, save_settings_to_file};
+    use crate::nvapi::settings::available_settings;
+
+    const DRS_FILE: &str = "C:\\Backups\\require-optional.nip";
+
+    /// The call through which each function guarded by `require_optional` is reached
+    const GUARDED_CALLS: &[(NvApiFunction, &str, fn() -> Result<(), NvApiError>)] = &[
+        (NvApiFunction::DrsLoadSettingsFromFileEx, "open-backup", || open_backup_readonly(DRS_FILE).map(|id| {
+            close_backup(id);
+        })),
+        (NvApiFunction::DrsDeleteApplication, "delete-application", || {
+            delete_application(find_profile_by_name("Require Optional")?, "require-optional.exe")
+        }),
+        (NvApiFunction::DrsLoadSettingsFromFile, "drs-file-transfer", || load_settings_from_file(DRS_FILE)),
+        (NvApiFunction::DrsSaveSettingsToFile, "drs-file-transfer", || save_settings_to_file(DRS_FILE)),
+        (NvApiFunction::DrsEnumAvailableSettingIds, "setting-catalog", || available_settings().map(|_| ())),
+        (NvApiFunction::DrsGetSettingNameFromId, "setting-catalog", || available_settings().map(|_| ())),
+    ];
+
+    fn install_without(missing: &[NvApiFunction]) {
+        mock::install_without(
+            Store::default().with_profile(Profile::new("Require Optional").with_application("require-optional.exe")),
+            missing,
+        );
+        mock::write_file(DRS_FILE, Store::default());
+    }
+
+    fn unavailable_features() -> Vec<(String, Vec<String>)> {
+        feature_availability().into_iter()
+            .filter(|f| !f.available)
+            .map(|f| (f.feature, f.missing_functions))
+            .collect()
+    }
+
+    #[test]
+    fn a_missing_guarded_function_is_reported_as_unsupported_for_its_feature() {
+        let _serial = mock::serial();
+        for &(function, feature, call) in GUARDED_CALLS {
+            assert!(OPTIONAL_FEATURES.iter().any(|&(f, functions)| f == feature && functions.contains(&function)));
+
+            install_without(&[function]);
+            match call() {
+                Err(NvApiError::UnsupportedByDriver { function: reported, feature: reported_feature }) => {
+                    assert_eq!(reported, function.name());
+                    assert_eq!(reported_feature, feature);
+                }
+                other => panic!("without {:?}: {:?}", function, other),
+            }
+            let unavailable = unavailable_features();
+            let (_, missing) = unavailable.iter()
+                .find(|(f, _)| f == feature)
+                .unwrap_or_else(|| panic!("{} still available without {:?}", feature, function));
+            assert_eq!(missing, &vec![function.name().to_string()]);
+
+            // Bound, the same call gets past the guard
+            install_without(&[]);
+            assert!(!matches!(call(), Err(NvApiError::UnsupportedByDriver { .. })), "{:?} is bound", function);
+        }
+    }
+
+    #[test]
+    fn availability_follows_the_bound_functions() {
+        let _serial = mock::serial();
+        install_without(&[]);
+        // The mock never binds the setting-value and GPU queries
+        let unavailable: Vec<String> = unavailable_features().into_iter().map(|(f, _)| f).collect();
+        assert_eq!(unavailable, ["setting-values", "gpu-product-line", "gpu-memory"]);
+
+        install_without(&[NvApiFunction::SysGetDriverAndBranchVersion]);
+        assert!(unavailable_features().iter().any(|(f, missing)| {
+            f == "driver-version" && missing == &vec!["NvAPI_SYS_GetDriverAndBranchVersion".to_string()]
+        }));
+        assert_eq!(driver_version(), None);
+    }
+}
@@ -0,0 +1,97 @@
+//! Profile GPU-support masks
+//!
+//! NVDRS_PROFILE.gpuSupport says which GPU product lines a profile applies to.
+//! On a system mixing GeForce and Quadro parts a profile can exist and still
+//! be ignored for the GPU that renders the game, which looks like a blacklist
+//! that doesn't work. Decoding and encoding are pure; the product lines of the
+//! installed GPUs are read once and kept, since they can't change while running.
+
+use std::sync::Mutex;
+use super::ffi::physical_gpu_quadro_status;
+use super::types::GpuProductLine;
+
+/// NVDRS_GPU_SUPPORT bits
+pub const GPU_SUPPORT_GEFORCE: u32 = 1 << 0;
+pub const GPU_SUPPORT_QUADRO: u32 = 1 << 1;
+pub const GPU_SUPPORT_NVS: u32 = 1 << 2;
+
+/// Mask Nvidiot sets on the profiles it creates
+pub const GPU_SUPPORT_ALL: u32 = GPU_SUPPORT_GEFORCE | GPU_SUPPORT_QUADRO | GPU_SUPPORT_NVS;
+
+const PRODUCT_LINE_BITS: &[(GpuProductLine, u32)] = &[
+    (GpuProductLine::Geforce, GPU_SUPPORT_GEFORCE),
+    (GpuProductLine::Quadro, GPU_SUPPORT_QUADRO),
+    (GpuProductLine::Nvs, GPU_SUPPORT_NVS),
+];
+
+/// Product lines a mask covers, in bit order. Unknown bits are ignored.
+pub fn decode_gpu_support(mask: u32) -> Vec<GpuProductLine> {
+    PRODUCT_LINE_BITS.iter()
+        .filter(|&&(_, bit)| mask & bit != 0)
+        .map(|&(line, _)| line)
+        .collect()
+}
+
+/// Mask covering exactly the given product lines
+pub fn encode_gpu_support(lines: &[GpuProductLine]) -> u32 {
+    PRODUCT_LINE_BITS.iter()
+        .filter(|(line, _)| lines.contains(line))
+        .fold(0, |mask, &(_, bit)| mask | bit)
+}
+
+/// Whether a mask covers every installed GPU. A mask that excludes any of them
+/// may be skipped for the one rendering the game, so this errs towards false.
+pub fn covers_all(mask: u32, installed: &[GpuProductLine]) -> bool {
+    let required = encode_gpu_support(installed);
+    mask & required == required
+}
+
+static INSTALLED: Mutex<Option<Vec<GpuProductLine>>> = Mutex::new(None);
+
+/// Product line of each installed GPU, or None if the driver can't say.
+/// Only a successful read is kept, so a driver that loads late is asked again.
+pub fn installed_product_lines() -> Option<Vec<GpuProductLine>> {
+    let mut installed = INSTALLED.lock().unwrap();
+    if installed.is_none() {
+        // Quadro status also covers NVS parts; the driver has no separate query for them
+        *installed = physical_gpu_quadro_status().map(|statuses| {
+            statuses.into_iter()
+                .map(|is_quadro| if is_quadro { GpuProductLine::Quadro } else { GpuProductLine::Geforce })
+                .collect()
+        });
+    }
+    installed.clone()
+}
+
+/// Whether a profile with this mask applies to the installed GPUs; None if they are unknown
+pub fn applies_to_current_gpu(mask: u32) -> Option<bool> {
+    installed_product_lines().map(|installed| covers_all(mask, &installed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use GpuProductLine::{Geforce, Nvs, Quadro};
+
+    #[test]
+    fn masks_decode_in_bit_order_ignoring_unknown_bits() {
+        assert_eq!(decode_gpu_support(0), []);
+        assert_eq!(decode_gpu_support(GPU_SUPPORT_NVS | GPU_SUPPORT_GEFORCE), [Geforce, Nvs]);
+        assert_eq!(decode_gpu_support(GPU_SUPPORT_ALL | 1 << 7), [Geforce, Quadro, Nvs]);
+    }
+
+    #[test]
+    fn encoding_round_trips() {
+        assert_eq!(encode_gpu_support(&[Quadro, Geforce, Quadro]), GPU_SUPPORT_GEFORCE | GPU_SUPPORT_QUADRO);
+        assert_eq!(encode_gpu_support(&decode_gpu_support(GPU_SUPPORT_ALL)), GPU_SUPPORT_ALL);
+        assert_eq!(encode_gpu_support(&[]), 0);
+    }
+
+    #[test]
+    fn a_mask_must_cover_every_installed_line() {
+        assert!(covers_all(GPU_SUPPORT_GEFORCE, &[Geforce, Geforce]));
+        assert!(!covers_all(GPU_SUPPORT_GEFORCE, &[Geforce, Quadro]));
+        assert!(covers_all(GPU_SUPPORT_ALL, &[Geforce, Quadro]));
+        assert!(covers_all(0, &[]));
+    }
+}
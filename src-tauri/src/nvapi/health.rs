@@ -0,0 +1,132 @@
+//! Driver change detection
+//!
+//! Installing an NVIDIA driver replaces nvapi64.dll underneath us, after which
+//! calls return garbage or "not initialized" statuses. A burst of such statuses
+//! flips the backend into a driver-changing state: `get_nvapi` fails fast with
+//! `DriverChanging`, watchers skip their ticks, and a recovery task reloads the
+//! library and session until one attempt succeeds.
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use once_cell::sync::Lazy;
+use tauri::AppHandle;
+use super::error::{NVAPI_API_NOT_INITIALIZED, NVAPI_INVALID_HANDLE, NVAPI_LIBRARY_NOT_FOUND, NVAPI_OK};
+
+/// Emitted when the backend enters the driver-changing state
+pub const DRIVER_CHANGE_DETECTED_EVENT: &str = "driver-change-detected";
+/// Emitted once the library and session were reloaded
+pub const DRIVER_CHANGE_RECOVERED_EVENT: &str = "driver-change-recovered";
+
+/// Suspicious statuses within this window count towards a burst
+const BURST_WINDOW: Duration = Duration::from_secs(10);
+const BURST_THRESHOLD: usize = 3;
+
+/// Lowest status code NVAPI defines; anything outside [this, 0] is garbage
+const LOWEST_KNOWN_STATUS: i32 = -250;
+
+#[cfg(target_os = "windows")]
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+#[cfg(target_os = "windows")]
+const RECOVERY_INTERVAL: Duration = Duration::from_secs(5);
+
+static DRIVER_CHANGING: AtomicBool = AtomicBool::new(false);
+static RECENT: Lazy<Mutex<VecDeque<Instant>>> = Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// Whether the backend is waiting for a driver change to finish
+pub fn is_driver_changing() -> bool {
+    DRIVER_CHANGING.load(Ordering::SeqCst)
+}
+
+/// Statuses that mean the library or its handles went away under us
+fn is_suspicious(status: i32) -> bool {
+    matches!(status, NVAPI_INVALID_HANDLE | NVAPI_API_NOT_INITIALIZED | NVAPI_LIBRARY_NOT_FOUND)
+        || !(LOWEST_KNOWN_STATUS..=NVAPI_OK).contains(&status)
+}
+
+/// Record the status of an FFI call and return it unchanged. Call sites on the
+/// common command paths pass their statuses through this.
+pub fn checked(status: i32) -> i32 {
+    if !is_suspicious(status) {
+        return status;
+    }
+
+    let now = Instant::now();
+    let mut recent = RECENT.lock().unwrap();
+    while recent.front().is_some_and(|t| now.duration_since(*t) > BURST_WINDOW) {
+        recent.pop_front();
+    }
+    recent.push_back(now);
+
+    if recent.len() >= BURST_THRESHOLD {
+        recent.clear();
+        DRIVER_CHANGING.store(true, Ordering::SeqCst);
+    }
+    status
+}
+
+/// Reload the library, replace the session and drop caches built from the old one
+#[cfg(target_os = "windows")]
+fn try_recover() -> bool {
+    use super::{ffi, session, settings};
+
+    // Handles from the old library are invalid; forget them without destroying
+    session::abandon_sessions();
+
+    // get_nvapi refuses while the flag is set, so reload directly
+    if ffi::reload_nvapi().is_err() {
+        return false;
+    }
+
+    DRIVER_CHANGING.store(false, Ordering::SeqCst);
+    if session::recreate_global_session().is_err() {
+        DRIVER_CHANGING.store(true, Ordering::SeqCst);
+        return false;
+    }
+
+    settings::clear_profile_status_cache();
+    session::bump_generation();
+    true
+}
+
+/// Start the task that reports driver changes and recovers from them
+#[cfg(target_os = "windows")]
+pub fn start_watcher(app: &AppHandle) {
+    use tauri::{Emitter, Manager};
+    use crate::journal::{self, JournalCategory, JournalSeverity};
+    use crate::tasks::TaskSupervisor;
+
+    let supervisor = app.state::<TaskSupervisor>();
+    let app = app.clone();
+    supervisor.spawn("driver-watcher", move |token| {
+        let mut reported = false;
+        let mut next_attempt = Instant::now();
+
+        while token.sleep(WATCH_INTERVAL) {
+            if !is_driver_changing() {
+                continue;
+            }
+            if !reported {
+                journal::append(JournalCategory::Watcher, JournalSeverity::Warning, "driver.changeDetected", ());
+                let _ = app.emit(DRIVER_CHANGE_DETECTED_EVENT, ());
+                reported = true;
+                next_attempt = Instant::now() + RECOVERY_INTERVAL;
+            }
+            if Instant::now() < next_attempt {
+                continue;
+            }
+
+            if try_recover() {
+                journal::append(JournalCategory::Recovery, JournalSeverity::Info, "driver.changeRecovered", ());
+                let _ = app.emit(DRIVER_CHANGE_RECOVERED_EVENT, ());
+                reported = false;
+            } else {
+                next_attempt = Instant::now() + RECOVERY_INTERVAL;
+            }
+        }
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn start_watcher(_app: &AppHandle) {}
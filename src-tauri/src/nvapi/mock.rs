@@ -0,0 +1,756 @@
+//! In-memory DRS backend for tests
+//!
+//! Stands in for nvapi64.dll so the command layer can be driven end to end
+//! without a driver. `install` seeds the on-disk store and swaps an `NvApi`
+//! whose DRS entry points are the functions below into the global slot.
+//! Sessions work on a copy of the store: LoadSettings copies it in and
+//! SaveSettings copies it back, like the driver. Status codes follow the
+//! driver for the cases the crate branches on. Loading from and saving to a
+//! file work on in-memory files seeded with `write_file`; the setting catalog
+//! is whatever `set_setting_catalog` gave it. Available setting values and the
+//! GPU queries are left unbound, so those features report as unsupported.
+//!
+//! Tests that share the backend hold `serial` for their whole run. A few
+//! driver misbehaviours can be switched on per test, such as another tool
+//! rewriting a value right after our save, or a call answering with a chosen
+//! status instead of doing its work.
+
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use super::error::{
+    NVAPI_END_ENUMERATION, NVAPI_ERROR, NVAPI_EXECUTABLE_ALREADY_IN_USE, NVAPI_EXECUTABLE_NOT_FOUND,
+    NVAPI_INCOMPATIBLE_STRUCT_VERSION, NVAPI_INVALID_ARGUMENT, NVAPI_INVALID_HANDLE, NVAPI_OK,
+    NVAPI_PROFILE_NAME_IN_USE, NVAPI_PROFILE_NOT_FOUND, NVAPI_SETTING_NOT_FOUND,
+};
+use super::ffi::{
+    install_nvapi, string_to_wchar, wchar_to_string, NvApi, NvApiFunction, NvDRSProfileHandle, NvDRSSessionHandle,
+    NvdrsApplication, NvdrsProfile, NvdrsSetting, NvdrsSettingValue, NVAPI_SHORT_STRING_MAX,
+    ENUM_BATCH_SIZE, NVAPI_UNICODE_STRING_MAX, NVDRS_APPLICATION_VER, NVDRS_PROFILE_VER, NVDRS_SETTING_VER,
+};
+use super::session::recreate_global_session;
+
+/// Driver version the mock reports
+pub const MOCK_DRIVER_VERSION: u32 = 55000;
+
+/// Handle of the base profile; other profiles get ids after it
+const BASE_PROFILE_ID: usize = 1;
+
+/// NVDRS_SETTING_LOCATION values
+const CURRENT_PROFILE_LOCATION: u32 = 0;
+const GLOBAL_PROFILE_LOCATION: u32 = 1;
+
+#[derive(Clone)]
+pub struct Application {
+    pub name: String,
+    pub friendly_name: String,
+    pub launcher: String,
+    pub is_predefined: bool,
+}
+
+#[derive(Clone)]
+pub struct Setting {
+    pub id: u32,
+    pub setting_type: u32,
+    pub value: NvdrsSettingValue,
+    pub is_predefined: bool,
+}
+
+#[derive(Clone)]
+pub struct Profile {
+    id: usize,
+    pub name: String,
+    pub is_predefined: bool,
+    pub gpu_support: u32,
+    pub applications: Vec<Application>,
+    pub settings: Vec<Setting>,
+}
+
+impl Profile {
+    pub fn new(name: &str) -> Self {
+        Self {
+            id: 0,
+            name: name.to_string(),
+            is_predefined: false,
+            gpu_support: 0,
+            applications: Vec::new(),
+            settings: Vec::new(),
+        }
+    }
+
+    /// Mark the profile, its applications and its settings as shipped by NVIDIA
+    pub fn predefined(mut self) -> Self {
+        self.is_predefined = true;
+        self.applications.iter_mut().for_each(|a| a.is_predefined = true);
+        self.settings.iter_mut().for_each(|s| s.is_predefined = true);
+        self
+    }
+
+    pub fn with_application(mut self, executable: &str) -> Self {
+        self.applications.push(Application {
+            name: executable.to_string(),
+            friendly_name: String::new(),
+            launcher: String::new(),
+            is_predefined: self.is_predefined,
+        });
+        self
+    }
+
+    pub fn with_dword(mut self, id: u32, value: u32) -> Self {
+        self.settings.push(Setting { id, setting_type: 0, value: NvdrsSettingValue { dword_value: value }, is_predefined: self.is_predefined });
+        self
+    }
+
+    pub fn dword(&self, id: u32) -> Option<u32> {
+        self.settings.iter()
+            .find(|s| s.id == id)
+            .map(|s| unsafe { s.value.dword_value })
+    }
+
+    fn set_dword(&mut self, id: u32, value: u32) {
+        self.settings.retain(|s| s.id != id);
+        self.settings.push(Setting { id, setting_type: 0, value: NvdrsSettingValue { dword_value: value }, is_predefined: false });
+    }
+
+    fn application(&self, name: &str) -> Option<usize> {
+        self.applications.iter().position(|a| a.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// One loaded copy of the settings: the base profile and the others in enumeration order
+#[derive(Clone)]
+pub struct Store {
+    pub base: Profile,
+    pub profiles: Vec<Profile>,
+    next_id: usize,
+}
+
+impl Default for Store {
+    fn default() -> Self {
+        Self {
+            base: Profile { id: BASE_PROFILE_ID, ..Profile::new("Base Profile").predefined() },
+            profiles: Vec::new(),
+            next_id: BASE_PROFILE_ID + 1,
+        }
+    }
+}
+
+impl Store {
+    /// A store the size of a large real one: `profiles` profiles with up to two
+    /// applications and three settings each, and every 500th with enough of both
+    /// to span several enumeration batches. Executables alternate between long
+    /// paths and short names, so a stale tail left in a reused buffer would show.
+    pub fn synthetic(profiles: usize) -> Self {
+        (0..profiles).fold(Store::default(), |store, i| {
+            let (apps, settings) = if i % 500 == 0 { (ENUM_BATCH_SIZE * 2 + 5, ENUM_BATCH_SIZE + 3) } else { (i % 3, i % 4) };
+            let profile = (0..apps).fold(Profile::new(&format!("Synthetic {}", i)), |p, a| {
+                let exe = if a % 2 == 0 {
+                    format!("C:\\Program Files\\Some Publisher\\Game {}\\Binaries\\Win64\\game-{}.exe", i, a)
+                } else {
+                    format!("g{}-{}.exe", i, a)
+                };
+                p.with_application(&exe)
+            });
+            store.with_profile((0..settings).fold(profile, |p, s| p.with_dword(0x1000_0000 + s as u32, (i * 7 + s) as u32)))
+        })
+    }
+
+    pub fn with_profile(mut self, mut profile: Profile) -> Self {
+        profile.id = self.next_id;
+        self.next_id += 1;
+        self.profiles.push(profile);
+        self
+    }
+
+    pub fn profile(&self, name: &str) -> Option<&Profile> {
+        self.profiles.iter().find(|p| p.name.eq_ignore_ascii_case(name))
+    }
+
+    fn by_handle(&mut self, handle: NvDRSProfileHandle) -> Option<&mut Profile> {
+        let id = handle as usize;
+        if id == BASE_PROFILE_ID {
+            return Some(&mut self.base);
+        }
+        self.profiles.iter_mut().find(|p| p.id == id)
+    }
+}
+
+#[derive(Default)]
+struct Backend {
+    disk: Store,
+    sessions: HashMap<usize, Store>,
+    next_session: usize,
+    saves: u32,
+    /// (profile, setting, value) written to disk after every save
+    rewrites: Vec<(String, u32, u32)>,
+    /// CreateApplication reports success without adding the entry
+    drop_attach: bool,
+    /// Statuses returned without doing anything, by function
+    injected: HashMap<NvApiFunction, i32>,
+    /// DRS files by the path they were loaded from or saved to
+    files: HashMap<String, Store>,
+    /// Calls of the enumeration and lookup entry points, by function
+    calls: HashMap<NvApiFunction, u32>,
+    /// Settings the driver knows about, by ID and name, in enumeration order
+    catalog: Vec<(u32, String)>,
+}
+
+static BACKEND: Lazy<Mutex<Backend>> = Lazy::new(|| Mutex::new(Backend::default()));
+
+/// The backend, session and caches are process-wide
+static SERIAL: Mutex<()> = Mutex::new(());
+
+/// Hold for the whole of a test that installs the backend
+pub fn serial() -> std::sync::MutexGuard<'static, ()> {
+    SERIAL.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn backend() -> std::sync::MutexGuard<'static, Backend> {
+    // A failed assertion in one test must not poison the backend for the rest
+    BACKEND.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Replace the on-disk store, bind the mock as the loaded NVAPI and open a new
+/// global session over it
+pub fn install(disk: Store) {
+    install_without(disk, &[]);
+}
+
+/// Like `install`, with `missing` left unbound as on a driver that doesn't export them
+pub fn install_without(disk: Store, missing: &[NvApiFunction]) {
+    *backend() = Backend { disk, next_session: 1, ..Backend::default() };
+
+    let mut api = NvApi::unbound(None, no_function);
+    api.initialize = Some(initialize);
+    api.unload = Some(initialize);
+    api.drs_create_session = Some(create_session);
+    api.drs_destroy_session = Some(destroy_session);
+    api.drs_load_settings = Some(load_settings);
+    api.drs_save_settings = Some(save_settings);
+    api.drs_load_settings_from_file_ex = Some(load_settings_from_file);
+    api.drs_load_settings_from_file = Some(load_settings_from_file);
+    api.drs_save_settings_to_file = Some(save_settings_to_file);
+    api.drs_get_num_profiles = Some(get_num_profiles);
+    api.drs_enum_profiles = Some(enum_profiles);
+    api.drs_get_profile_info = Some(get_profile_info);
+    api.drs_set_profile_info = Some(set_profile_info);
+    api.drs_find_profile_by_name = Some(find_profile_by_name);
+    api.drs_create_profile = Some(create_profile);
+    api.drs_delete_profile = Some(delete_profile);
+    api.drs_enum_applications = Some(enum_applications);
+    api.drs_find_application_by_name = Some(find_application_by_name);
+    api.drs_create_application = Some(create_application);
+    api.drs_delete_application = Some(delete_application);
+    api.drs_set_application_info = Some(set_application_info);
+    api.drs_get_setting = Some(get_setting);
+    api.drs_set_setting = Some(set_setting);
+    api.drs_enum_settings = Some(enum_settings);
+    api.drs_delete_profile_setting = Some(delete_profile_setting);
+    api.drs_enum_available_setting_ids = Some(enum_available_setting_ids);
+    api.drs_get_setting_name_from_id = Some(get_setting_name_from_id);
+    api.drs_get_base_profile = Some(get_base_profile);
+    api.sys_get_driver_and_branch_version = Some(get_driver_and_branch_version);
+    // Binding through `no_function` resolves nothing, which clears the pointer
+    for &function in missing {
+        api.bind(function);
+    }
+    install_nvapi(api);
+
+    recreate_global_session().expect("mock session");
+}
+
+/// The store as last saved
+pub fn disk() -> Store {
+    backend().disk.clone()
+}
+
+/// Number of successful SaveSettings calls since `install`
+pub fn saves() -> u32 {
+    backend().saves
+}
+
+/// After every later save, store `value` for `setting_id` in `profile`, as if
+/// another tool rewrote it. Sessions see it once they reload.
+pub fn rewrite_after_save(profile: &str, setting_id: u32, value: u32) {
+    backend().rewrites.push((profile.to_string(), setting_id, value));
+}
+
+/// Make CreateApplication report success but add nothing, as one driver does
+pub fn drop_application_attach() {
+    backend().drop_attach = true;
+}
+
+/// Calls of `function` since `install`. Only EnumProfiles, EnumApplications
+/// and FindApplicationByName are counted.
+pub fn calls(function: NvApiFunction) -> u32 {
+    backend().calls.get(&function).copied().unwrap_or(0)
+}
+
+fn count_call(function: NvApiFunction) {
+    *backend().calls.entry(function).or_insert(0) += 1;
+}
+
+/// Replace the settings the driver reports as available. Empty after `install`.
+pub fn set_setting_catalog(settings: Vec<(u32, String)>) {
+    backend().catalog = settings;
+}
+
+/// Put a DRS file holding `store` at `path`, for LoadSettingsFromFile(Ex)
+pub fn write_file(path: &str, store: Store) {
+    backend().files.insert(path.to_string(), store);
+}
+
+/// The DRS file at `path`, if one was written or saved there
+pub fn file(path: &str) -> Option<Store> {
+    backend().files.get(path).cloned()
+}
+
+/// Make every later call of `function` return `status` and leave its outputs
+/// untouched. Honoured by the setting and profile info reads, LoadSettings and
+/// SaveSettings.
+pub fn inject_status(function: NvApiFunction, status: i32) {
+    backend().injected.insert(function, status);
+}
+
+fn injected(function: NvApiFunction) -> Option<i32> {
+    backend().injected.get(&function).copied()
+}
+
+/// Run `f` on a session's store, or report an unknown session
+fn with_session(session: NvDRSSessionHandle, f: impl FnOnce(&mut Store) -> i32) -> i32 {
+    match backend().sessions.get_mut(&(session as usize)) {
+        Some(store) => f(store),
+        None => NVAPI_INVALID_HANDLE,
+    }
+}
+
+/// Run `f` on one profile of a session's store
+fn with_profile(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, f: impl FnOnce(&mut Profile) -> i32) -> i32 {
+    with_session(session, |store| match store.by_handle(profile) {
+        Some(profile) => f(profile),
+        None => NVAPI_INVALID_ARGUMENT,
+    })
+}
+
+unsafe fn read_wide(name: *const u16) -> String {
+    let mut len = 0;
+    while len < NVAPI_UNICODE_STRING_MAX && *name.add(len) != 0 {
+        len += 1;
+    }
+    wchar_to_string(std::slice::from_raw_parts(name, len))
+}
+
+fn handle(id: usize) -> *mut c_void {
+    id as *mut c_void
+}
+
+unsafe extern "C" fn no_function(_id: u32) -> *mut c_void {
+    std::ptr::null_mut()
+}
+
+unsafe extern "C" fn initialize() -> i32 {
+    NVAPI_OK
+}
+
+unsafe extern "C" fn create_session(session: *mut NvDRSSessionHandle) -> i32 {
+    let mut backend = backend();
+    let id = backend.next_session;
+    backend.next_session += 1;
+    backend.sessions.insert(id, Store::default());
+    *session = handle(id);
+    NVAPI_OK
+}
+
+unsafe extern "C" fn destroy_session(session: NvDRSSessionHandle) -> i32 {
+    match backend().sessions.remove(&(session as usize)) {
+        Some(_) => NVAPI_OK,
+        None => NVAPI_INVALID_HANDLE,
+    }
+}
+
+unsafe extern "C" fn load_settings(session: NvDRSSessionHandle) -> i32 {
+    if let Some(status) = injected(NvApiFunction::DrsLoadSettings) {
+        return status;
+    }
+    let mut backend = backend();
+    let disk = backend.disk.clone();
+    match backend.sessions.get_mut(&(session as usize)) {
+        Some(store) => {
+            *store = disk;
+            NVAPI_OK
+        }
+        None => NVAPI_INVALID_HANDLE,
+    }
+}
+
+unsafe extern "C" fn save_settings(session: NvDRSSessionHandle) -> i32 {
+    if let Some(status) = injected(NvApiFunction::DrsSaveSettings) {
+        return status;
+    }
+    let mut backend = backend();
+    match backend.sessions.get(&(session as usize)).cloned() {
+        Some(mut store) => {
+            for (name, id, value) in &backend.rewrites {
+                if let Some(p) = store.profiles.iter_mut().find(|p| p.name.eq_ignore_ascii_case(name)) {
+                    p.set_dword(*id, *value);
+                }
+            }
+            backend.disk = store;
+            backend.saves += 1;
+            NVAPI_OK
+        }
+        None => NVAPI_INVALID_HANDLE,
+    }
+}
+
+/// Both LoadSettingsFromFile variants: the file replaces the session's store
+unsafe extern "C" fn load_settings_from_file(session: NvDRSSessionHandle, file_name: *const u16) -> i32 {
+    let path = read_wide(file_name);
+    let mut backend = backend();
+    let Some(file) = backend.files.get(&path).cloned() else {
+        return NVAPI_ERROR;
+    };
+    match backend.sessions.get_mut(&(session as usize)) {
+        Some(store) => {
+            *store = file;
+            NVAPI_OK
+        }
+        None => NVAPI_INVALID_HANDLE,
+    }
+}
+
+unsafe extern "C" fn save_settings_to_file(session: NvDRSSessionHandle, file_name: *const u16) -> i32 {
+    let path = read_wide(file_name);
+    let mut backend = backend();
+    match backend.sessions.get(&(session as usize)).cloned() {
+        Some(store) => {
+            backend.files.insert(path, store);
+            NVAPI_OK
+        }
+        None => NVAPI_INVALID_HANDLE,
+    }
+}
+
+unsafe extern "C" fn get_num_profiles(session: NvDRSSessionHandle, count: *mut u32) -> i32 {
+    with_session(session, |store| {
+        *count = store.profiles.len() as u32;
+        NVAPI_OK
+    })
+}
+
+unsafe extern "C" fn enum_profiles(session: NvDRSSessionHandle, index: u32, profile: *mut NvDRSProfileHandle) -> i32 {
+    count_call(NvApiFunction::DrsEnumProfiles);
+    with_session(session, |store| match store.profiles.get(index as usize) {
+        Some(p) => {
+            *profile = handle(p.id);
+            NVAPI_OK
+        }
+        None => NVAPI_END_ENUMERATION,
+    })
+}
+
+unsafe extern "C" fn get_profile_info(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, info: *mut NvdrsProfile) -> i32 {
+    if let Some(status) = injected(NvApiFunction::DrsGetProfileInfo) {
+        return status;
+    }
+    if (*info).version != NVDRS_PROFILE_VER {
+        return NVAPI_INCOMPATIBLE_STRUCT_VERSION;
+    }
+    with_profile(session, profile, |p| {
+        string_to_wchar(&p.name, &mut (*info).profile_name);
+        (*info).gpu_support = p.gpu_support;
+        (*info).is_predefined = p.is_predefined as u32;
+        (*info).num_of_apps = p.applications.len() as u32;
+        (*info).num_of_settings = p.settings.len() as u32;
+        NVAPI_OK
+    })
+}
+
+unsafe extern "C" fn set_profile_info(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, info: *mut NvdrsProfile) -> i32 {
+    if (*info).version != NVDRS_PROFILE_VER {
+        return NVAPI_INCOMPATIBLE_STRUCT_VERSION;
+    }
+    let name = wchar_to_string(&(*info).profile_name);
+    with_session(session, |store| {
+        let id = profile as usize;
+        if store.profiles.iter().any(|p| p.id != id && p.name.eq_ignore_ascii_case(&name)) {
+            return NVAPI_PROFILE_NAME_IN_USE;
+        }
+        match store.by_handle(profile) {
+            Some(p) => {
+                p.name = name;
+                p.gpu_support = (*info).gpu_support;
+                NVAPI_OK
+            }
+            None => NVAPI_INVALID_ARGUMENT,
+        }
+    })
+}
+
+unsafe extern "C" fn find_profile_by_name(session: NvDRSSessionHandle, name: *const u16, profile: *mut NvDRSProfileHandle) -> i32 {
+    let name = read_wide(name);
+    with_session(session, |store| match store.profile(&name) {
+        Some(p) => {
+            *profile = handle(p.id);
+            NVAPI_OK
+        }
+        None => NVAPI_PROFILE_NOT_FOUND,
+    })
+}
+
+unsafe extern "C" fn create_profile(session: NvDRSSessionHandle, info: *mut NvdrsProfile, profile: *mut NvDRSProfileHandle) -> i32 {
+    if (*info).version != NVDRS_PROFILE_VER {
+        return NVAPI_INCOMPATIBLE_STRUCT_VERSION;
+    }
+    let name = wchar_to_string(&(*info).profile_name);
+    with_session(session, |store| {
+        if store.profile(&name).is_some() {
+            return NVAPI_PROFILE_NAME_IN_USE;
+        }
+        let created = Profile { gpu_support: (*info).gpu_support, ..Profile::new(&name) };
+        *store = std::mem::take(store).with_profile(created);
+        *profile = handle(store.next_id - 1);
+        NVAPI_OK
+    })
+}
+
+unsafe extern "C" fn delete_profile(session: NvDRSSessionHandle, profile: NvDRSProfileHandle) -> i32 {
+    with_session(session, |store| {
+        let before = store.profiles.len();
+        store.profiles.retain(|p| p.id != profile as usize);
+        if store.profiles.len() == before { NVAPI_PROFILE_NOT_FOUND } else { NVAPI_OK }
+    })
+}
+
+fn write_application(app: &Application, out: &mut NvdrsApplication) {
+    out.is_predefined = app.is_predefined as u32;
+    string_to_wchar(&app.name, &mut out.app_name);
+    string_to_wchar(&app.friendly_name, &mut out.user_friendly_name);
+    string_to_wchar(&app.launcher, &mut out.launcher);
+}
+
+unsafe extern "C" fn enum_applications(
+    session: NvDRSSessionHandle,
+    profile: NvDRSProfileHandle,
+    start: u32,
+    count: *mut u32,
+    apps: *mut NvdrsApplication,
+) -> i32 {
+    count_call(NvApiFunction::DrsEnumApplications);
+    with_profile(session, profile, |p| {
+        let remaining = p.applications.get(start as usize..).unwrap_or_default();
+        if remaining.is_empty() {
+            *count = 0;
+            return NVAPI_END_ENUMERATION;
+        }
+        let filled = remaining.len().min(*count as usize);
+        for (i, app) in remaining[..filled].iter().enumerate() {
+            write_application(app, &mut *apps.add(i));
+        }
+        *count = filled as u32;
+        NVAPI_OK
+    })
+}
+
+unsafe extern "C" fn find_application_by_name(
+    session: NvDRSSessionHandle,
+    name: *const u16,
+    profile: *mut NvDRSProfileHandle,
+    app: *mut NvdrsApplication,
+) -> i32 {
+    count_call(NvApiFunction::DrsFindApplicationByName);
+    // Only the v3 layout is understood, so match-field probes are refused
+    if (*app).version != NVDRS_APPLICATION_VER {
+        return NVAPI_INCOMPATIBLE_STRUCT_VERSION;
+    }
+    let name = read_wide(name);
+    with_session(session, |store| {
+        for p in &store.profiles {
+            if let Some(index) = p.application(&name) {
+                write_application(&p.applications[index], &mut *app);
+                *profile = handle(p.id);
+                return NVAPI_OK;
+            }
+        }
+        NVAPI_EXECUTABLE_NOT_FOUND
+    })
+}
+
+unsafe extern "C" fn create_application(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, app: *mut NvdrsApplication) -> i32 {
+    if (*app).version != NVDRS_APPLICATION_VER {
+        return NVAPI_INCOMPATIBLE_STRUCT_VERSION;
+    }
+    let created = Application {
+        name: wchar_to_string(&(*app).app_name),
+        friendly_name: wchar_to_string(&(*app).user_friendly_name),
+        launcher: wchar_to_string(&(*app).launcher),
+        is_predefined: false,
+    };
+    let drop_attach = backend().drop_attach;
+    with_session(session, |store| {
+        if store.profiles.iter().any(|p| p.application(&created.name).is_some()) {
+            return NVAPI_EXECUTABLE_ALREADY_IN_USE;
+        }
+        match store.by_handle(profile) {
+            Some(_) if drop_attach => NVAPI_OK,
+            Some(p) => {
+                p.applications.push(created);
+                NVAPI_OK
+            }
+            None => NVAPI_INVALID_ARGUMENT,
+        }
+    })
+}
+
+unsafe extern "C" fn delete_application(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, app_name: *const u16) -> i32 {
+    let name = read_wide(app_name);
+    with_profile(session, profile, |p| match p.application(&name) {
+        Some(index) => {
+            p.applications.remove(index);
+            NVAPI_OK
+        }
+        None => NVAPI_EXECUTABLE_NOT_FOUND,
+    })
+}
+
+unsafe extern "C" fn set_application_info(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, app: *mut NvdrsApplication) -> i32 {
+    if (*app).version != NVDRS_APPLICATION_VER {
+        return NVAPI_INCOMPATIBLE_STRUCT_VERSION;
+    }
+    let name = wchar_to_string(&(*app).app_name);
+    with_profile(session, profile, |p| match p.application(&name) {
+        Some(index) => {
+            let entry = &mut p.applications[index];
+            entry.friendly_name = wchar_to_string(&(*app).user_friendly_name);
+            entry.launcher = wchar_to_string(&(*app).launcher);
+            NVAPI_OK
+        }
+        None => NVAPI_EXECUTABLE_NOT_FOUND,
+    })
+}
+
+fn write_setting(setting: &Setting, location: u32, out: &mut NvdrsSetting) {
+    // The mock keeps no setting names, but like the driver it writes every field
+    string_to_wchar("", &mut out.setting_name);
+    out.setting_id = setting.id;
+    out.setting_type = setting.setting_type;
+    out.setting_location = location;
+    out.is_current_predefined = setting.is_predefined as u32;
+    out.is_predefined_valid = setting.is_predefined as u32;
+    out.predefined_value = setting.value;
+    out.current_value = setting.value;
+}
+
+unsafe extern "C" fn get_setting(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, setting_id: u32, setting: *mut NvdrsSetting) -> i32 {
+    if let Some(status) = injected(NvApiFunction::DrsGetSetting) {
+        return status;
+    }
+    if (*setting).version != NVDRS_SETTING_VER {
+        return NVAPI_INCOMPATIBLE_STRUCT_VERSION;
+    }
+    with_session(session, |store| {
+        let base_value = store.base.settings.iter().find(|s| s.id == setting_id).cloned();
+        let Some(p) = store.by_handle(profile) else {
+            return NVAPI_INVALID_ARGUMENT;
+        };
+        // Like the driver, a profile without its own value reports the base profile's
+        let found = match p.settings.iter().find(|s| s.id == setting_id) {
+            Some(own) => Some((own.clone(), CURRENT_PROFILE_LOCATION)),
+            None => base_value.map(|s| (s, GLOBAL_PROFILE_LOCATION)),
+        };
+        match found {
+            Some((s, location)) => {
+                write_setting(&s, location, &mut *setting);
+                NVAPI_OK
+            }
+            None => NVAPI_SETTING_NOT_FOUND,
+        }
+    })
+}
+
+unsafe extern "C" fn set_setting(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, setting: *mut NvdrsSetting) -> i32 {
+    if (*setting).version != NVDRS_SETTING_VER {
+        return NVAPI_INCOMPATIBLE_STRUCT_VERSION;
+    }
+    let written = Setting {
+        id: (*setting).setting_id,
+        setting_type: (*setting).setting_type,
+        value: (*setting).current_value,
+        is_predefined: false,
+    };
+    with_profile(session, profile, |p| {
+        p.settings.retain(|s| s.id != written.id);
+        p.settings.push(written);
+        NVAPI_OK
+    })
+}
+
+unsafe extern "C" fn enum_settings(
+    session: NvDRSSessionHandle,
+    profile: NvDRSProfileHandle,
+    start: u32,
+    count: *mut u32,
+    settings: *mut NvdrsSetting,
+) -> i32 {
+    if let Some(status) = injected(NvApiFunction::DrsEnumSettings) {
+        return status;
+    }
+    with_profile(session, profile, |p| {
+        let remaining = p.settings.get(start as usize..).unwrap_or_default();
+        if remaining.is_empty() {
+            *count = 0;
+            return NVAPI_END_ENUMERATION;
+        }
+        let filled = remaining.len().min(*count as usize);
+        for (i, s) in remaining[..filled].iter().enumerate() {
+            write_setting(s, CURRENT_PROFILE_LOCATION, &mut *settings.add(i));
+        }
+        *count = filled as u32;
+        NVAPI_OK
+    })
+}
+
+unsafe extern "C" fn enum_available_setting_ids(setting_ids: *mut u32, max_count: *mut u32) -> i32 {
+    let backend = backend();
+    let filled = backend.catalog.len().min(*max_count as usize);
+    for (i, (id, _)) in backend.catalog[..filled].iter().enumerate() {
+        *setting_ids.add(i) = *id;
+    }
+    *max_count = filled as u32;
+    NVAPI_OK
+}
+
+unsafe extern "C" fn get_setting_name_from_id(setting_id: u32, name: *mut [u16; NVAPI_UNICODE_STRING_MAX]) -> i32 {
+    match backend().catalog.iter().find(|(id, _)| *id == setting_id) {
+        Some((_, setting_name)) => {
+            string_to_wchar(setting_name, &mut *name);
+            NVAPI_OK
+        }
+        None => NVAPI_SETTING_NOT_FOUND,
+    }
+}
+
+unsafe extern "C" fn delete_profile_setting(session: NvDRSSessionHandle, profile: NvDRSProfileHandle, setting_id: u32) -> i32 {
+    with_profile(session, profile, |p| {
+        let before = p.settings.len();
+        p.settings.retain(|s| s.id != setting_id);
+        if p.settings.len() == before { NVAPI_SETTING_NOT_FOUND } else { NVAPI_OK }
+    })
+}
+
+unsafe extern "C" fn get_base_profile(session: NvDRSSessionHandle, profile: *mut NvDRSProfileHandle) -> i32 {
+    with_session(session, |_| {
+        *profile = handle(BASE_PROFILE_ID);
+        NVAPI_OK
+    })
+}
+
+unsafe extern "C" fn get_driver_and_branch_version(driver_version: *mut u32, branch: *mut [u8; NVAPI_SHORT_STRING_MAX]) -> i32 {
+    *driver_version = MOCK_DRIVER_VERSION;
+    let branch = &mut *branch;
+    let name = b"r550_00";
+    branch[..name.len()].copy_from_slice(name);
+    branch[name.len()] = 0;
+    NVAPI_OK
+}
@@ -5,6 +5,20 @@ pub mod types;
 pub mod profiles;
 pub mod applications;
 pub mod settings;
+pub mod export;
+pub mod resolve;
+pub mod backup;
+pub mod compare;
+pub mod scope;
+pub mod reconcile;
+pub mod partition;
+pub mod health;
+pub mod statuses;
+pub mod naming;
+pub mod gpu;
+pub mod verify;
+#[cfg(all(test, target_os = "windows"))]
+pub mod mock;
 
 pub use error::NvApiError;
 pub use types::*;
@@ -0,0 +1,188 @@
+//! Names of profiles created by Nvidiot
+//!
+//! New profiles are named from the user's template in the app config.
+//! `{exe}` is replaced with the executable's file name and `{name}` with the
+//! file name without its extension.
+//!
+//! Some drivers can't find a profile again by name when the name is very long,
+//! ends in dots or spaces, or holds characters outside the basic plane, which
+//! strands its settings. Rendered names are sanitized and cut to a safe length,
+//! with a hash of the full name appended so long names stay distinct. The
+//! result only depends on the template and executable, so blacklisting the same
+//! executable again finds the same profile.
+
+use super::resolve::basename;
+
+/// Template used until the user picks another one
+pub const DEFAULT_PROFILE_NAME_TEMPLATE: &str = "Nvidiot - {exe}";
+
+const PLACEHOLDERS: &[&str] = &["exe", "name"];
+
+/// Longest profile name every driver finds again reliably, in characters.
+/// The driver stores up to 2047 UTF-16 units, but lookups fail well before that.
+const MAX_PROFILE_NAME_CHARS: usize = 100;
+
+/// Characters some drivers store but don't match on lookup
+const REPLACED_CHARS: &[char] = &['\\', '/', ':', '*', '?', '"', '<', '>', '|'];
+
+/// Stable across builds and platforms, unlike `DefaultHasher`
+fn fnv1a(text: &str) -> u32 {
+    text.bytes().fold(0x811C9DC5u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(0x01000193))
+}
+
+/// Make a rendered name safe to look up: control characters are dropped,
+/// awkward ones replaced, whitespace runs collapsed, and trailing dots and
+/// spaces trimmed. Names over the safe length keep their start plus a hash of
+/// the whole sanitized name.
+pub fn sanitize_profile_name(name: &str) -> String {
+    let replaced: String = name.chars()
+        .filter(|c| !c.is_control())
+        .map(|c| if REPLACED_CHARS.contains(&c) || c.len_utf16() > 1 { '_' } else { c })
+        .collect();
+    let collapsed = replaced.split_whitespace().collect::<Vec<_>>().join(" ");
+    let trimmed = collapsed.trim_end_matches(['.', ' ']);
+
+    // Nothing usable left, e.g. an executable named only with dots
+    if trimmed.is_empty() {
+        return format!("Nvidiot ~{:08x}", fnv1a(name));
+    }
+    if trimmed.chars().count() <= MAX_PROFILE_NAME_CHARS {
+        return trimmed.to_string();
+    }
+    let suffix = format!(" ~{:08x}", fnv1a(trimmed));
+    let kept: String = trimmed.chars().take(MAX_PROFILE_NAME_CHARS - suffix.len()).collect();
+    format!("{}{}", kept.trim_end_matches(['.', ' ']), suffix)
+}
+
+/// Whether a name survives `sanitize_profile_name` unchanged
+pub fn is_safe_profile_name(name: &str) -> bool {
+    !name.is_empty() && sanitize_profile_name(name) == name
+}
+
+/// Check that a template only uses known placeholders, that its braces
+/// balance, and that it names the executable so profiles don't collide
+pub fn validate_template(template: &str) -> Result<(), String> {
+    let mut rest = template;
+    let mut placeholders = 0;
+
+    while let Some(open) = rest.find(['{', '}']) {
+        if rest[open..].starts_with('}') {
+            return Err("Unmatched '}' in profile name template".to_string());
+        }
+        let after = &rest[open + 1..];
+        let close = after.find('}').ok_or("Unmatched '{' in profile name template")?;
+        let placeholder = &after[..close];
+        if !PLACEHOLDERS.contains(&placeholder) {
+            return Err(format!(
+                "Unknown placeholder '{{{}}}' in profile name template; use {{exe}} or {{name}}",
+                placeholder
+            ));
+        }
+        placeholders += 1;
+        rest = &after[close + 1..];
+    }
+
+    if placeholders == 0 {
+        return Err("Profile name template must contain {exe} or {name}".to_string());
+    }
+    Ok(())
+}
+
+/// Render a validated template for an executable, sanitized with `sanitize_profile_name`
+pub fn render_profile_name(template: &str, executable: &str) -> String {
+    let exe = basename(executable);
+    let name = match exe.rfind('.') {
+        Some(dot) if dot > 0 => &exe[..dot],
+        _ => exe,
+    };
+    sanitize_profile_name(&template.replace("{exe}", exe).replace("{name}", name))
+}
+
+/// Template for the dedicated profile an executable is moved to when another
+/// tool keeps rewriting its own profile. It isn't user-configurable, so it
+/// can't collide with names rendered from the user's template.
+pub const OVERRIDE_PROFILE_NAME_TEMPLATE: &str = "Nvidiot override - {exe}";
+
+/// Name of the dedicated override profile for `executable`
+pub fn override_profile_name(executable: &str) -> String {
+    render_profile_name(OVERRIDE_PROFILE_NAME_TEMPLATE, executable)
+}
+
+/// Name for a new profile holding `executable`, using the configured template
+pub fn profile_name_for(executable: &str) -> String {
+    let template = crate::config::get().profile_naming.template;
+    if validate_template(&template).is_ok() {
+        render_profile_name(&template, executable)
+    } else {
+        render_profile_name(DEFAULT_PROFILE_NAME_TEMPLATE, executable)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanitizing_replaces_drops_collapses_and_trims() {
+        assert_eq!(sanitize_profile_name("Nvidiot - a:b*c?.exe"), "Nvidiot - a_b_c_.exe");
+        assert_eq!(sanitize_profile_name("Nvidiot -  \u{7}game \n  x.exe"), "Nvidiot - game x.exe");
+        assert_eq!(sanitize_profile_name("Nvidiot - game. . "), "Nvidiot - game");
+        assert_eq!(sanitize_profile_name("Nvidiot - \u{1F3AE}.exe"), "Nvidiot - _.exe");
+        assert_eq!(sanitize_profile_name("Nvidiot - Straße.exe"), "Nvidiot - Straße.exe");
+    }
+
+    #[test]
+    fn names_with_nothing_usable_get_a_stable_stand_in() {
+        let name = sanitize_profile_name(" ...");
+        assert!(name.starts_with("Nvidiot ~"));
+        assert_eq!(name, sanitize_profile_name(" ..."));
+        assert_ne!(name, sanitize_profile_name("...."));
+    }
+
+    #[test]
+    fn long_names_are_cut_and_stay_distinct() {
+        let a = format!("Nvidiot - {}a.exe", "x".repeat(200));
+        let b = format!("Nvidiot - {}b.exe", "x".repeat(200));
+        let (safe_a, safe_b) = (sanitize_profile_name(&a), sanitize_profile_name(&b));
+
+        assert_eq!(safe_a.chars().count(), MAX_PROFILE_NAME_CHARS);
+        assert!(safe_a.starts_with("Nvidiot - xxx"));
+        assert_ne!(safe_a, safe_b);
+        assert_eq!(safe_a, sanitize_profile_name(&a));
+        assert!(is_safe_profile_name(&safe_a));
+    }
+
+    #[test]
+    fn override_profiles_are_named_apart_from_the_default_template() {
+        let name = override_profile_name("C:\\Games\\game.exe");
+        assert_eq!(name, "Nvidiot override - game.exe");
+        assert_ne!(name, render_profile_name(DEFAULT_PROFILE_NAME_TEMPLATE, "game.exe"));
+        assert!(is_safe_profile_name(&name));
+    }
+
+    #[test]
+    fn safe_names_are_those_sanitizing_keeps() {
+        assert!(is_safe_profile_name("Nvidiot - game.exe"));
+        assert!(!is_safe_profile_name(""));
+        assert!(!is_safe_profile_name("Nvidiot - game.exe."));
+        assert!(!is_safe_profile_name("Nvidiot - C:\\game.exe"));
+    }
+
+    #[test]
+    fn templates_need_known_balanced_placeholders() {
+        assert!(validate_template(DEFAULT_PROFILE_NAME_TEMPLATE).is_ok());
+        assert!(validate_template("{name} ({exe})").is_ok());
+        assert!(validate_template("Nvidiot").unwrap_err().contains("must contain"));
+        assert!(validate_template("{path}").unwrap_err().contains("'{path}'"));
+        assert!(validate_template("{exe").unwrap_err().contains("Unmatched '{'"));
+        assert!(validate_template("exe} {name}").unwrap_err().contains("Unmatched '}'"));
+    }
+
+    #[test]
+    fn rendering_fills_in_the_file_name_and_stem() {
+        assert_eq!(render_profile_name("{name} via {exe}", "C:\\Games\\Game.exe"), "Game via Game.exe");
+        assert_eq!(render_profile_name("Nvidiot - {name}", "launcher"), "Nvidiot - launcher");
+        assert_eq!(render_profile_name("Nvidiot - {name}", ".hidden"), "Nvidiot - .hidden");
+        assert_eq!(render_profile_name("{exe}", "a/b.c.exe"), "b.c.exe");
+    }
+}
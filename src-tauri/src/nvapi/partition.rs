@@ -0,0 +1,87 @@
+//! Predefined application partition
+//!
+//! NVIDIA's predefined profiles hold tens of thousands of application entries
+//! that only change with the driver. Their applications are cached on disk per
+//! driver version, so normal refreshes only enumerate user profiles and any
+//! predefined profile whose application count no longer matches the cache.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use crate::storage::{self, StorageError};
+use super::types::DrsApplication;
+
+const PREDEFINED_FILE: &str = "predefined-applications.json";
+
+/// Applications of one predefined profile as last enumerated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedProfile {
+    pub application_count: u32,
+    pub applications: Vec<DrsApplication>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PredefinedPartition {
+    pub driver_version: u32,
+    /// Number of predefined profiles when the partition was built
+    pub profile_count: u32,
+    pub profiles: HashMap<String, CachedProfile>,
+    /// When any cached profile was last enumerated, in ms since the epoch
+    pub built_at_ms: u64,
+}
+
+static PARTITION: Lazy<Mutex<Option<PredefinedPartition>>> = Lazy::new(|| Mutex::new(None));
+
+/// Load the predefined partition from app data
+pub fn load() -> Result<(), StorageError> {
+    if let Some(partition) = storage::load::<PredefinedPartition>(PREDEFINED_FILE)? {
+        *PARTITION.lock().unwrap() = Some(partition);
+    }
+    Ok(())
+}
+
+/// Take the cached partition if it was built for this driver and the same set
+/// of predefined profiles. A mismatch discards it.
+pub fn take_valid(driver_version: u32, profile_count: u32) -> Option<PredefinedPartition> {
+    let mut partition = PARTITION.lock().unwrap();
+    let valid = partition.as_ref()
+        .is_some_and(|p| p.driver_version == driver_version && p.profile_count == profile_count);
+    if valid { partition.take() } else { None }
+}
+
+/// Store a partition, persisting it only if its contents changed
+pub fn store(partition: PredefinedPartition, changed: bool) {
+    if changed {
+        storage::save_or_journal(PREDEFINED_FILE, &partition);
+    }
+    *PARTITION.lock().unwrap() = Some(partition);
+}
+
+/// Drop the cached partition, in memory and on disk, if it was built for a
+/// different driver. Returns whether it was dropped.
+pub fn discard_if_stale(driver_version: u32) -> bool {
+    let mut partition = PARTITION.lock().unwrap();
+    if partition.as_ref().map_or(true, |p| p.driver_version == driver_version) {
+        return false;
+    }
+    *partition = None;
+    if let Ok(path) = storage::data_path(PREDEFINED_FILE) {
+        let _ = std::fs::remove_file(path);
+    }
+    true
+}
+
+/// When the partition in memory was last rebuilt, if there is one
+pub fn built_at_ms() -> Option<u64> {
+    PARTITION.lock().unwrap().as_ref().map(|p| p.built_at_ms)
+}
+
+/// Number of application entries held in the partition
+pub fn cached_application_count() -> usize {
+    PARTITION.lock().unwrap().as_ref()
+        .map(|p| p.profiles.values().map(|c| c.applications.len()).sum())
+        .unwrap_or(0)
+}
@@ -2,13 +2,28 @@
 //!
 //! Handles enumerating, creating, and finding profiles.
 
+#[cfg(target_os = "windows")]
+use std::collections::HashSet;
 use super::error::{NvApiError, NVAPI_OK, NVAPI_END_ENUMERATION, NVAPI_PROFILE_NOT_FOUND};
+use super::health::checked;
+use super::statuses::{classify, ReadCall, ReadStatus};
 use super::ffi::{
-    get_nvapi, wchar_to_string, string_to_wchar,
-    NvDRSProfileHandle, NvdrsProfile, NVDRS_PROFILE_VER,
+    get_nvapi, is_lossless_wchar, wchar_to_string, wchar_to_string_into, string_to_wchar,
+    NvDRSProfileHandle, NvdrsApplication, NvdrsProfile, NvdrsSetting, NvdrsSettingType, NVDRS_PROFILE_VER,
+    NVAPI_UNICODE_STRING_MAX,
 };
-use super::session::get_session;
-use super::types::DrsProfile;
+use super::session::{get_session, get_session_mut, save_settings, record_mutation, with_fresh_session};
+use super::settings::{get_all_settings, get_cached_shadowplay_status, set_dword_setting};
+use super::applications::enumerate_applications;
+#[cfg(target_os = "windows")]
+use super::gpu::{covers_all, decode_gpu_support, installed_product_lines, GPU_SUPPORT_ALL};
+#[cfg(target_os = "windows")]
+use super::types::GpuProductLine;
+use super::types::{DrsProfile, DrsApplication, ProfileDeletion, ProfileError, ProfileSortField, ProfileWarning, SkippedProfile, ValidationReport};
+use crate::journal::{self, JournalCategory, JournalSeverity};
+
+/// Name prefix of profiles created by Nvidiot
+pub const NVIDIOT_PROFILE_PREFIX: &str = "Nvidiot - ";
 
 /// Get the total number of profiles
 #[cfg(target_os = "windows")]
@@ -21,7 +36,7 @@ pub fn get_profile_count() -> Result<u32, NvApiError> {
 
     unsafe {
         let mut count: u32 = 0;
-        let status = get_num(session, &mut count);
+        let status = checked(get_num(session, &mut count));
         if status != NVAPI_OK {
             return Err(NvApiError::NvApiStatus(status));
         }
@@ -35,8 +50,64 @@ pub fn get_profile_count() -> Result<u32, NvApiError> {
 }
 
 /// Enumerate all profiles
-#[cfg(target_os = "windows")]
 pub fn enumerate_profiles() -> Result<Vec<DrsProfile>, NvApiError> {
+    enumerate_profiles_with_status(false)
+}
+
+/// Enumerate profiles in a stable order instead of driver order
+pub fn get_profiles_sorted(
+    sort_by: ProfileSortField,
+    ascending: bool,
+    include_predefined: bool,
+) -> Result<Vec<DrsProfile>, NvApiError> {
+    let mut profiles = enumerate_profiles()?;
+    if !include_predefined {
+        profiles.retain(|p| !p.is_predefined);
+    }
+
+    profiles.sort_by(|a, b| {
+        let ordering = match sort_by {
+            ProfileSortField::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            ProfileSortField::ApplicationCount => a.application_count.cmp(&b.application_count),
+            ProfileSortField::SettingsCount => a.settings_count.cmp(&b.settings_count),
+        };
+        let ordering = if ascending { ordering } else { ordering.reverse() };
+        // Break ties by name so equal counts don't fall back to driver order
+        ordering.then_with(|| a.name.cmp(&b.name))
+    });
+
+    Ok(profiles)
+}
+
+/// Build a DrsProfile from driver info. `managed` holds the profile names from the
+/// manifest and `installed_gpus` the product lines of the installed GPUs, if known.
+#[cfg(target_os = "windows")]
+fn to_drs_profile(
+    name: String,
+    info: &NvdrsProfile,
+    is_blacklisted: Option<bool>,
+    managed: &HashSet<String>,
+    installed_gpus: Option<&[GpuProductLine]>,
+) -> DrsProfile {
+    let is_predefined = info.is_predefined != 0;
+    let user_created_by_nvidiot = name.starts_with(NVIDIOT_PROFILE_PREFIX) || managed.contains(&name);
+    DrsProfile {
+        name,
+        is_predefined,
+        application_count: info.num_of_apps,
+        settings_count: info.num_of_settings,
+        is_blacklisted,
+        user_can_delete: !is_predefined,
+        user_can_modify: !is_predefined,
+        user_created_by_nvidiot,
+        gpu_support: decode_gpu_support(info.gpu_support),
+        applies_to_current_gpu: installed_gpus.map(|installed| covers_all(info.gpu_support, installed)),
+    }
+}
+
+/// Enumerate all profiles, optionally including each profile's ShadowPlay status
+#[cfg(target_os = "windows")]
+pub fn enumerate_profiles_with_status(include_shadowplay: bool) -> Result<Vec<DrsProfile>, NvApiError> {
     let api = get_nvapi()?;
     let session = get_session()?;
 
@@ -45,13 +116,17 @@ pub fn enumerate_profiles() -> Result<Vec<DrsProfile>, NvApiError> {
     let get_profile_info = api.drs_get_profile_info
         .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_GetProfileInfo".to_string()))?;
 
+    let managed = crate::manifest::managed_profile_names();
+    let installed_gpus = installed_product_lines();
     let mut profiles = Vec::new();
     let mut index: u32 = 0;
+    // Reused across iterations; only the version needs resetting before each call
+    let mut profile_info = NvdrsProfile::default();
 
     unsafe {
         loop {
             let mut profile_handle: NvDRSProfileHandle = std::ptr::null_mut();
-            let status = enum_profiles(session, index, &mut profile_handle);
+            let status = checked(enum_profiles(session, index, &mut profile_handle));
 
             if status == NVAPI_END_ENUMERATION {
                 break;
@@ -61,15 +136,18 @@ pub fn enumerate_profiles() -> Result<Vec<DrsProfile>, NvApiError> {
             }
 
             // Get profile info
-            let mut profile_info = NvdrsProfile::default();
+            profile_info.version = NVDRS_PROFILE_VER;
             let status = get_profile_info(session, profile_handle, &mut profile_info);
 
             if status == NVAPI_OK {
-                profiles.push(DrsProfile {
-                    name: wchar_to_string(&profile_info.profile_name),
-                    is_predefined: profile_info.is_predefined != 0,
-                    application_count: profile_info.num_of_apps,
-                });
+                let name = wchar_to_string(&profile_info.profile_name);
+                let is_blacklisted = if include_shadowplay {
+                    get_cached_shadowplay_status(profile_handle, &name, profile_info.is_predefined != 0)
+                } else {
+                    None
+                };
+
+                profiles.push(to_drs_profile(name, &profile_info, is_blacklisted, &managed, installed_gpus.as_deref()));
             }
 
             index += 1;
@@ -80,10 +158,141 @@ pub fn enumerate_profiles() -> Result<Vec<DrsProfile>, NvApiError> {
 }
 
 #[cfg(not(target_os = "windows"))]
-pub fn enumerate_profiles() -> Result<Vec<DrsProfile>, NvApiError> {
+pub fn enumerate_profiles_with_status(_include_shadowplay: bool) -> Result<Vec<DrsProfile>, NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Get profile info for a handle
+#[cfg(target_os = "windows")]
+pub fn get_profile_info_by_handle(profile_handle: NvDRSProfileHandle) -> Result<DrsProfile, NvApiError> {
+    let api = get_nvapi()?;
+    let session = get_session()?;
+
+    let get_profile_info = api.drs_get_profile_info
+        .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_GetProfileInfo".to_string()))?;
+
+    unsafe {
+        let mut profile_info = NvdrsProfile::default();
+        let status = checked(get_profile_info(session, profile_handle, &mut profile_info));
+        match classify(ReadCall::GetProfileInfo, status) {
+            ReadStatus::Present => {}
+            // The handle no longer names a profile, typically after a reload
+            ReadStatus::Absent => return Err(NvApiError::ProfileNotFound(format!("{:p}", profile_handle))),
+            ReadStatus::Failed => return Err(NvApiError::NvApiStatus(status)),
+        }
+
+        let name = wchar_to_string(&profile_info.profile_name);
+        let installed_gpus = installed_product_lines();
+        Ok(to_drs_profile(name, &profile_info, None, &crate::manifest::managed_profile_names(), installed_gpus.as_deref()))
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn get_profile_info_by_handle(_profile_handle: NvDRSProfileHandle) -> Result<DrsProfile, NvApiError> {
     Err(NvApiError::NotSupported)
 }
 
+/// Get a profile and the applications registered to it
+pub fn get_profile_with_applications(name: &str) -> Result<(DrsProfile, Vec<DrsApplication>), NvApiError> {
+    let profile_handle = find_profile_by_name(name)?;
+    let profile = get_profile_info_by_handle(profile_handle)?;
+    let applications = enumerate_applications(profile_handle, &profile.name)?;
+    Ok((profile, applications))
+}
+
+/// Estimate how many bytes a profile takes in the DRS database, using the driver's
+/// struct sizes. This is a relative measure of bloat, not the real on-disk size.
+pub fn estimate_profile_size(profile_name: &str) -> Result<u64, NvApiError> {
+    let profile_handle = find_profile_by_name(profile_name)?;
+    let settings = get_all_settings(profile_handle)?;
+    let applications = enumerate_applications(profile_handle, profile_name)?;
+
+    let header = std::mem::size_of::<NvdrsProfile>() as u64;
+    let settings_size = settings.len() as u64 * std::mem::size_of::<NvdrsSetting>() as u64;
+    let applications_size = applications.len() as u64 * std::mem::size_of::<NvdrsApplication>() as u64;
+    Ok(header + settings_size + applications_size)
+}
+
+/// Check every user profile for inconsistencies, e.g. after a driver update or import.
+/// Count mismatches are warnings; empty executables, unknown setting types and
+/// names that fill the whole name buffer are errors.
+/// Runs in a fresh session so in-flight state in the global one isn't read or disturbed.
+pub fn validate_all_profiles() -> Result<ValidationReport, NvApiError> {
+    with_fresh_session(|_| validate_loaded_profiles())
+}
+
+fn validate_loaded_profiles() -> Result<ValidationReport, NvApiError> {
+    let mut report = ValidationReport {
+        ok_profiles: 0,
+        warning_profiles: Vec::new(),
+        error_profiles: Vec::new(),
+    };
+
+    for profile in enumerate_profiles()?.into_iter().filter(|p| !p.is_predefined) {
+        let mut warnings = Vec::new();
+        let mut errors = Vec::new();
+
+        // A name this long left no room for the terminator, so it may be truncated
+        if profile.name.encode_utf16().count() >= NVAPI_UNICODE_STRING_MAX - 1 {
+            errors.push(format!("Name is {} characters or longer", NVAPI_UNICODE_STRING_MAX - 1));
+        }
+
+        let profile_handle = match find_profile_by_name(&profile.name) {
+            Ok(handle) => handle,
+            Err(e) => {
+                errors.push(format!("Cannot be looked up by name: {}", e));
+                report.error_profiles.push(ProfileError { profile_name: profile.name, issues: errors });
+                continue;
+            }
+        };
+
+        match enumerate_applications(profile_handle, &profile.name) {
+            Ok(applications) => {
+                if applications.len() as u32 != profile.application_count {
+                    warnings.push(format!(
+                        "Reports {} applications but {} were enumerated",
+                        profile.application_count, applications.len(),
+                    ));
+                }
+                let empty = applications.iter().filter(|a| a.executable.trim().is_empty()).count();
+                if empty > 0 {
+                    errors.push(format!("{} application(s) with an empty executable name", empty));
+                }
+            }
+            Err(e) => errors.push(format!("Applications could not be enumerated: {}", e)),
+        }
+
+        match get_all_settings(profile_handle) {
+            Ok(settings) => {
+                if settings.len() as u32 != profile.settings_count {
+                    warnings.push(format!(
+                        "Reports {} settings but {} were enumerated",
+                        profile.settings_count, settings.len(),
+                    ));
+                }
+                for setting in settings.iter().filter(|s| s.setting_type > NvdrsSettingType::WString as u32) {
+                    errors.push(format!(
+                        "Setting 0x{:08X} has invalid type {}",
+                        setting.setting_id, setting.setting_type,
+                    ));
+                }
+            }
+            Err(e) => errors.push(format!("Settings could not be enumerated: {}", e)),
+        }
+
+        if !errors.is_empty() {
+            errors.extend(warnings);
+            report.error_profiles.push(ProfileError { profile_name: profile.name, issues: errors });
+        } else if !warnings.is_empty() {
+            report.warning_profiles.push(ProfileWarning { profile_name: profile.name, issues: warnings });
+        } else {
+            report.ok_profiles += 1;
+        }
+    }
+
+    Ok(report)
+}
+
 /// Find a profile by name
 #[cfg(target_os = "windows")]
 pub fn find_profile_by_name(name: &str) -> Result<NvDRSProfileHandle, NvApiError> {
@@ -98,9 +307,16 @@ pub fn find_profile_by_name(name: &str) -> Result<NvDRSProfileHandle, NvApiError
 
     unsafe {
         let mut profile_handle: NvDRSProfileHandle = std::ptr::null_mut();
-        let status = find_fn(session, wide_name.as_ptr(), &mut profile_handle);
+        let status = checked(find_fn(session, wide_name.as_ptr(), &mut profile_handle));
 
         if status == NVAPI_PROFILE_NOT_FOUND {
+            // A name with unpaired surrogates only survives decoding as U+FFFD, so
+            // the driver can't find it by that name; match the decoded form instead
+            if name.contains(char::REPLACEMENT_CHARACTER) {
+                if let Some(handle) = find_profile_by_decoded_name(name)? {
+                    return Ok(handle);
+                }
+            }
             return Err(NvApiError::ProfileNotFound(name.to_string()));
         }
         if status != NVAPI_OK {
@@ -111,6 +327,48 @@ pub fn find_profile_by_name(name: &str) -> Result<NvDRSProfileHandle, NvApiError
     }
 }
 
+/// Scan for a profile whose stored name isn't valid UTF-16 but decodes to `name`
+#[cfg(target_os = "windows")]
+fn find_profile_by_decoded_name(name: &str) -> Result<Option<NvDRSProfileHandle>, NvApiError> {
+    let api = get_nvapi()?;
+    let session = get_session()?;
+
+    let enum_profiles = api.drs_enum_profiles
+        .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_EnumProfiles".to_string()))?;
+    let get_profile_info = api.drs_get_profile_info
+        .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_GetProfileInfo".to_string()))?;
+
+    let mut profile_info = NvdrsProfile::default();
+    // Every profile's name is decoded just to compare it, so one buffer serves them all
+    let mut decoded = String::new();
+    let mut index: u32 = 0;
+
+    unsafe {
+        loop {
+            let mut profile_handle: NvDRSProfileHandle = std::ptr::null_mut();
+            let status = enum_profiles(session, index, &mut profile_handle);
+            if status == NVAPI_END_ENUMERATION {
+                return Ok(None);
+            }
+            index += 1;
+            if status != NVAPI_OK {
+                continue;
+            }
+
+            profile_info.version = NVDRS_PROFILE_VER;
+            if get_profile_info(session, profile_handle, &mut profile_info) != NVAPI_OK
+                || is_lossless_wchar(&profile_info.profile_name)
+            {
+                continue;
+            }
+            wchar_to_string_into(&profile_info.profile_name, &mut decoded);
+            if decoded == name {
+                return Ok(Some(profile_handle));
+            }
+        }
+    }
+}
+
 #[cfg(not(target_os = "windows"))]
 pub fn find_profile_by_name(_name: &str) -> Result<NvDRSProfileHandle, NvApiError> {
     Err(NvApiError::NotSupported)
@@ -120,13 +378,15 @@ pub fn find_profile_by_name(_name: &str) -> Result<NvDRSProfileHandle, NvApiErro
 #[cfg(target_os = "windows")]
 pub fn create_profile(name: &str) -> Result<NvDRSProfileHandle, NvApiError> {
     let api = get_nvapi()?;
-    let session = get_session()?;
+    let session = get_session_mut()?;
 
     let create_fn = api.drs_create_profile
         .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_CreateProfile".to_string()))?;
 
+    // Cover every product line explicitly; the driver default may leave some out
     let mut profile_info = NvdrsProfile {
         version: NVDRS_PROFILE_VER,
+        gpu_support: GPU_SUPPORT_ALL,
         ..Default::default()
     };
     string_to_wchar(name, &mut profile_info.profile_name);
@@ -139,6 +399,7 @@ pub fn create_profile(name: &str) -> Result<NvDRSProfileHandle, NvApiError> {
             return Err(NvApiError::ProfileCreationFailed(status));
         }
 
+        record_mutation(format!("Created profile '{}'", name));
         Ok(profile_handle)
     }
 }
@@ -148,6 +409,218 @@ pub fn create_profile(_name: &str) -> Result<NvDRSProfileHandle, NvApiError> {
     Err(NvApiError::NotSupported)
 }
 
+/// Create a profile with initial DWORD settings, saving once.
+/// If any setting fails the profile is deleted again so no half-configured profile is left.
+pub fn create_profile_with_settings(name: &str, settings: &[(u32, u32)]) -> Result<NvDRSProfileHandle, NvApiError> {
+    let profile_handle = create_profile(name)?;
+
+    for &(setting_id, value) in settings {
+        if let Err(e) = set_dword_setting(profile_handle, setting_id, value) {
+            if let Err(rollback) = delete_profile(name) {
+                journal::append(JournalCategory::Mutation, JournalSeverity::Warning, "profile.rollback_failed", serde_json::json!({
+                    "profileName": name,
+                    "error": rollback.to_string(),
+                }));
+            }
+            return Err(e);
+        }
+    }
+
+    save_settings()?;
+    Ok(profile_handle)
+}
+
+/// Find a profile by name, creating it if it doesn't exist.
+/// Returns the handle and `true` if the profile was created.
+pub fn create_profile_if_not_exists(name: &str) -> Result<(NvDRSProfileHandle, bool), NvApiError> {
+    match find_profile_by_name(name) {
+        Ok(handle) => Ok((handle, false)),
+        Err(NvApiError::ProfileNotFound(_)) => Ok((create_profile(name)?, true)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Delete a profile by name
+#[cfg(target_os = "windows")]
+pub fn delete_profile(name: &str) -> Result<(), NvApiError> {
+    let api = get_nvapi()?;
+    let session = get_session_mut()?;
+
+    let delete_fn = api.drs_delete_profile
+        .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_DeleteProfile".to_string()))?;
+
+    let profile_handle = find_profile_by_name(name)?;
+
+    unsafe {
+        let status = delete_fn(session, profile_handle);
+
+        if status != NVAPI_OK {
+            return Err(NvApiError::ProfileDeletionFailed(status));
+        }
+    }
+
+    record_mutation(format!("Deleted profile '{}'", name));
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn delete_profile(_name: &str) -> Result<(), NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Rename a profile in the loaded session. The caller saves.
+#[cfg(target_os = "windows")]
+pub fn rename_profile(name: &str, new_name: &str) -> Result<(), NvApiError> {
+    let api = get_nvapi()?;
+
+    let get_profile_info = api.drs_get_profile_info
+        .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_GetProfileInfo".to_string()))?;
+    let set_profile_info = api.drs_set_profile_info
+        .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_SetProfileInfo".to_string()))?;
+
+    let profile_handle = find_profile_by_name(name)?;
+    let session = get_session_mut()?;
+
+    unsafe {
+        let mut profile_info = NvdrsProfile::default();
+        let status = get_profile_info(session, profile_handle, &mut profile_info);
+        if status != NVAPI_OK {
+            return Err(NvApiError::NvApiStatus(status));
+        }
+
+        profile_info.profile_name = [0u16; NVAPI_UNICODE_STRING_MAX];
+        string_to_wchar(new_name, &mut profile_info.profile_name);
+
+        let status = set_profile_info(session, profile_handle, &mut profile_info);
+        if status != NVAPI_OK {
+            return Err(NvApiError::ProfileRenameFailed(status));
+        }
+    }
+
+    record_mutation(format!("Renamed profile '{}' to '{}'", name, new_name));
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn rename_profile(_name: &str, _new_name: &str) -> Result<(), NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Most fuzzy matches returned by `find_profile_fuzzy`
+const FUZZY_MATCH_LIMIT: usize = 5;
+
+/// Edit distance between two strings, counted in chars
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// Similarity from 0 (nothing in common) to 1 (equal ignoring case)
+fn similarity(query: &str, name: &str) -> f32 {
+    let (query, name) = (query.to_lowercase(), name.to_lowercase());
+    let longest = query.chars().count().max(name.chars().count());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(&query, &name) as f32 / longest as f32
+}
+
+/// User profiles whose names are closest to `query`, best first, for
+/// "did you mean" suggestions after a ProfileNotFound
+pub fn find_profile_fuzzy(query: &str) -> Result<Vec<(DrsProfile, f32)>, NvApiError> {
+    let mut scored: Vec<(DrsProfile, f32)> = enumerate_profiles()?
+        .into_iter()
+        .filter(|p| !p.is_predefined)
+        .map(|p| {
+            let score = similarity(query, &p.name);
+            (p, score)
+        })
+        .collect();
+
+    scored.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.name.cmp(&b.0.name)));
+    scored.truncate(FUZZY_MATCH_LIMIT);
+    Ok(scored)
+}
+
+/// Get all profiles created by Nvidiot: those with the default prefix, plus
+/// manifest-tracked profiles named from a custom template
+pub fn get_nvidiot_profiles() -> Result<Vec<DrsProfile>, NvApiError> {
+    Ok(enumerate_profiles()?
+        .into_iter()
+        .filter(|p| !p.is_predefined && p.user_created_by_nvidiot)
+        .collect())
+}
+
+/// Delete the given profiles and save once, journaling each name before it is
+/// deleted and the outcome under `code`. A failed save reloads the session so
+/// the deletions aren't left staged for the next save, and the manifest
+/// forgets the profiles that are gone.
+fn delete_profiles(profiles: Vec<DrsProfile>, code: &str) -> Result<ProfileDeletion, NvApiError> {
+    use super::session::reload_settings;
+
+    let mut deletion = ProfileDeletion { deleted: 0, failed: Vec::new() };
+    let mut deleted_names = Vec::new();
+
+    for profile in profiles {
+        journal::append(JournalCategory::Mutation, JournalSeverity::Info, "profile.deleting", serde_json::json!({
+            "profileName": profile.name,
+            "operation": code,
+        }));
+        match delete_profile(&profile.name) {
+            Ok(()) => {
+                deletion.deleted += 1;
+                deleted_names.push(profile.name);
+            }
+            Err(e) => deletion.failed.push(SkippedProfile { profile_name: profile.name, reason: e.to_string() }),
+        }
+    }
+
+    if let Err(e) = save_settings() {
+        let _ = reload_settings();
+        journal::append(JournalCategory::Mutation, JournalSeverity::Error, code, serde_json::json!({
+            "deleted": 0,
+            "error": e.to_string(),
+        }));
+        return Err(e);
+    }
+    if let Err(e) = crate::manifest::remove_profiles(&deleted_names) {
+        journal::append(JournalCategory::Storage, JournalSeverity::Warning, "manifest.remove_failed", serde_json::json!({
+            "error": e.to_string(),
+        }));
+    }
+    journal::append(JournalCategory::Mutation, journal::severity_for(!deletion.failed.is_empty()), code, serde_json::json!({
+        "deleted": deletion.deleted,
+        "failed": deletion.failed.iter().map(|f| &f.profile_name).collect::<Vec<_>>(),
+    }));
+    Ok(deletion)
+}
+
+/// Delete every profile created by Nvidiot
+pub fn delete_nvidiot_profiles() -> Result<ProfileDeletion, NvApiError> {
+    delete_profiles(get_nvidiot_profiles()?, "profiles.nvidiot_deleted")
+}
+
+/// Delete every user-created profile, leaving only NVIDIA's predefined ones.
+/// `confirm` must be true.
+pub fn delete_all_user_profiles(confirm: bool) -> Result<ProfileDeletion, NvApiError> {
+    if !confirm {
+        return Err(NvApiError::OperationAborted("Confirmation required".to_string()));
+    }
+    let profiles = enumerate_profiles()?.into_iter().filter(|p| !p.is_predefined).collect();
+    delete_profiles(profiles, "profiles.factory_reset")
+}
+
 /// Get the base profile (applies to all applications by default)
 #[cfg(target_os = "windows")]
 pub fn get_base_profile() -> Result<NvDRSProfileHandle, NvApiError> {
@@ -173,3 +646,25 @@ pub fn get_base_profile() -> Result<NvDRSProfileHandle, NvApiError> {
 pub fn get_base_profile() -> Result<NvDRSProfileHandle, NvApiError> {
     Err(NvApiError::NotSupported)
 }
+
+#[cfg(all(test, target_os = "windows"))]
+mod mock_tests {
+    use super::*;
+    use crate::nvapi::error::NVAPI_ERROR;
+    use crate::nvapi::ffi::NvApiFunction;
+    use crate::nvapi::mock::{self, Profile, Store};
+    use crate::nvapi::session::is_dirty;
+
+    #[test]
+    fn a_failed_save_leaves_no_deletion_staged() {
+        let _serial = mock::serial();
+        mock::install(Store::default()
+            .with_profile(Profile::new("Reset Kept"))
+            .with_profile(Profile::new("Reset Shipped").predefined()));
+        mock::inject_status(NvApiFunction::DrsSaveSettings, NVAPI_ERROR);
+
+        assert!(delete_all_user_profiles(true).is_err());
+        assert!(!is_dirty());
+        assert!(find_profile_by_name("Reset Kept").is_ok());
+    }
+}
@@ -0,0 +1,120 @@
+//! Profile reconciliation
+//!
+//! Keeps one Nvidiot profile per executable: finds existing profiles to reuse
+//! before creating a new one, and folds profiles named by older versions
+//! ("Nvidiot-game.exe") into the current naming scheme.
+
+use super::error::NvApiError;
+use super::ffi::SHADOWPLAY_SETTING_ID;
+use super::applications::{create_application, delete_application, enumerate_applications, get_all_applications};
+use super::profiles::{create_profile_if_not_exists, delete_profile, enumerate_profiles, find_profile_by_name, NVIDIOT_PROFILE_PREFIX};
+use super::naming::profile_name_for;
+use super::resolve::basename;
+use super::session::{flush, reload_settings};
+use super::settings::{get_all_settings, set_dword_setting};
+use super::types::{LegacyMigration, SkippedProfile};
+
+/// Find a user profile that already holds an entry for this executable under any
+/// path or name, preferring one that is already blacklisted
+pub fn find_reusable_profile(executable: &str) -> Result<Option<String>, NvApiError> {
+    let target = basename(executable);
+
+    let mut candidates: Vec<_> = get_all_applications()?
+        .into_iter()
+        .filter(|app| !app.is_predefined)
+        .filter(|app| basename(&app.executable).eq_ignore_ascii_case(target))
+        .collect();
+
+    // Stable sort: blacklisted first, otherwise keep driver order
+    candidates.sort_by_key(|app| !app.is_blacklisted);
+    Ok(candidates.into_iter().next().map(|app| app.profile_name))
+}
+
+/// Executable a legacy-named Nvidiot profile was created for, or None if the name
+/// is current or unrelated
+pub fn legacy_profile_executable(profile_name: &str) -> Option<&str> {
+    if profile_name.starts_with(NVIDIOT_PROFILE_PREFIX) {
+        return None;
+    }
+
+    let prefix = profile_name.get(..7)?;
+    if !prefix.eq_ignore_ascii_case("Nvidiot") {
+        return None;
+    }
+
+    let rest = &profile_name[7..];
+    let executable = rest.trim_start_matches([' ', '-', '_']);
+    // Require a separator so "NvidiotHelper" isn't treated as ours
+    if executable.is_empty() || executable.len() == rest.len() {
+        return None;
+    }
+    Some(executable)
+}
+
+fn migrate_profile(legacy_name: &str, executable: &str) -> Result<Option<String>, NvApiError> {
+    let legacy_handle = find_profile_by_name(legacy_name)?;
+    let settings = get_all_settings(legacy_handle)?;
+
+    let unknown: Vec<String> = settings.iter()
+        .filter(|s| !s.is_current_predefined && s.setting_id != SHADOWPLAY_SETTING_ID)
+        .map(|s| format!("0x{:08X}", s.setting_id))
+        .collect();
+    if !unknown.is_empty() {
+        return Ok(Some(format!("Has settings Nvidiot doesn't manage: {}", unknown.join(", "))));
+    }
+
+    let shadowplay = settings.iter()
+        .find(|s| s.setting_id == SHADOWPLAY_SETTING_ID && !s.is_current_predefined)
+        .and_then(|s| s.dword_value);
+    let applications = enumerate_applications(legacy_handle, legacy_name)?;
+
+    let target_name = profile_name_for(executable);
+    let (target_handle, _created) = create_profile_if_not_exists(&target_name)?;
+
+    for app in &applications {
+        // An executable can only belong to one profile, so remove it before re-adding
+        delete_application(legacy_handle, &app.executable)?;
+        create_application(target_handle, &app.executable, &target_name, None)?;
+    }
+    if let Some(value) = shadowplay {
+        set_dword_setting(target_handle, SHADOWPLAY_SETTING_ID, value)?;
+    }
+    delete_profile(legacy_name)?;
+
+    Ok(None)
+}
+
+/// Merge legacy-named Nvidiot profiles into profiles named from the current template in one batch.
+/// Profiles with settings we don't recognize are reported and left alone.
+pub fn migrate_legacy_profiles() -> Result<LegacyMigration, NvApiError> {
+    let mut result = LegacyMigration {
+        migrated: Vec::new(),
+        skipped: Vec::new(),
+    };
+
+    let legacy: Vec<(String, String)> = enumerate_profiles()?
+        .into_iter()
+        .filter(|p| !p.is_predefined)
+        .filter_map(|p| {
+            let executable = legacy_profile_executable(&p.name)?.to_string();
+            Some((p.name, executable))
+        })
+        .collect();
+
+    for (name, executable) in legacy {
+        match migrate_profile(&name, &executable) {
+            Ok(None) => result.migrated.push(name),
+            Ok(Some(reason)) => result.skipped.push(SkippedProfile { profile_name: name, reason }),
+            Err(e) => {
+                // Drop the partial batch so nothing is half-moved
+                let _ = reload_settings();
+                return Err(e);
+            }
+        }
+    }
+
+    if !result.migrated.is_empty() {
+        flush()?;
+    }
+    Ok(result)
+}
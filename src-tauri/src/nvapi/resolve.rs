@@ -0,0 +1,265 @@
+//! Executable resolution diagnostics
+//!
+//! Runs every strategy we use to match an executable to a DRS entry and
+//! records which ones matched and why the others didn't.
+
+use super::error::NvApiError;
+use super::applications::{find_application, get_all_applications};
+use super::profiles::get_profile_info_by_handle;
+use super::types::{DrsApplication, ResolutionStep, ResolutionTrace};
+
+/// Data shared between matchers, loaded on first use
+struct MatchContext {
+    all_applications: Option<Result<Vec<DrsApplication>, String>>,
+}
+
+impl MatchContext {
+    fn all_applications(&mut self) -> Result<&[DrsApplication], String> {
+        let apps = self.all_applications
+            .get_or_insert_with(|| get_all_applications().map_err(|e| e.to_string()));
+        match apps {
+            Ok(apps) => Ok(apps),
+            Err(e) => Err(e.clone()),
+        }
+    }
+}
+
+/// Result of a single matcher: the matched profile name, or why it failed
+type MatchOutcome = Result<String, String>;
+
+type Matcher = fn(&str, &mut MatchContext) -> MatchOutcome;
+
+/// Matching strategies, in the order they are tried
+const MATCHERS: &[(&str, Matcher)] = &[
+    ("exact-name", match_exact_name),
+    ("basename", match_basename),
+    ("case-insensitive", match_case_insensitive),
+    ("path-qualified-entry", match_path_qualified),
+];
+
+/// Strip any directory components from an executable path
+pub fn basename(executable: &str) -> &str {
+    executable.rsplit(['\\', '/']).next().unwrap_or(executable)
+}
+
+/// Case-folded key for comparing executable names. Only ASCII is folded:
+/// Unicode lowercasing can change a name's length or merge distinct names,
+/// so non-ASCII characters are kept exactly as registered.
+pub fn executable_key(executable: &str) -> String {
+    executable.to_ascii_lowercase()
+}
+
+fn find_profile_name(executable: &str) -> MatchOutcome {
+    match find_application(executable) {
+        Ok((profile_handle, _app)) => get_profile_info_by_handle(profile_handle)
+            .map(|p| p.name)
+            .map_err(|e| format!("found but profile info unavailable: {}", e)),
+        Err(NvApiError::ApplicationNotFound(_)) => Err(format!("no application named '{}'", executable)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// The driver's own lookup with the input as given
+fn match_exact_name(executable: &str, _ctx: &mut MatchContext) -> MatchOutcome {
+    find_profile_name(executable)
+}
+
+/// The driver's lookup with directories stripped from a full path
+fn match_basename(executable: &str, _ctx: &mut MatchContext) -> MatchOutcome {
+    let name = basename(executable);
+    if name == executable {
+        return Err("input is not a path".to_string());
+    }
+    find_profile_name(name)
+}
+
+/// Scan all entries ignoring case, for drivers whose lookup is case-sensitive
+fn match_case_insensitive(executable: &str, ctx: &mut MatchContext) -> MatchOutcome {
+    let name = basename(executable);
+    ctx.all_applications()?
+        .iter()
+        .find(|app| app.executable.eq_ignore_ascii_case(name))
+        .map(|app| app.profile_name.clone())
+        .ok_or_else(|| format!("no entry matches '{}' ignoring case", name))
+}
+
+/// Scan for entries registered with a directory, e.g. `bin\game.exe`
+fn match_path_qualified(executable: &str, ctx: &mut MatchContext) -> MatchOutcome {
+    let name = executable_key(basename(executable));
+    ctx.all_applications()?
+        .iter()
+        .filter(|app| basename(&app.executable) != app.executable)
+        .find(|app| executable_key(basename(&app.executable)) == name)
+        .map(|app| format!("{} (entry '{}')", app.profile_name, app.executable))
+        .ok_or_else(|| format!("no path-qualified entry ends with '{}'", name))
+}
+
+/// Run every matcher against an executable and collect the trace
+pub fn resolve_application(executable: &str) -> ResolutionTrace {
+    let mut ctx = MatchContext { all_applications: None };
+    let mut steps = Vec::with_capacity(MATCHERS.len());
+
+    for (strategy, matcher) in MATCHERS {
+        let step = match matcher(executable, &mut ctx) {
+            Ok(profile_name) => ResolutionStep {
+                strategy: strategy.to_string(),
+                matched: true,
+                detail: format!("matched profile {}", profile_name),
+                profile_name: Some(profile_name),
+            },
+            Err(reason) => ResolutionStep {
+                strategy: strategy.to_string(),
+                matched: false,
+                profile_name: None,
+                detail: reason,
+            },
+        };
+        steps.push(step);
+    }
+
+    let resolved_profile = steps.iter().find_map(|s| s.profile_name.clone());
+
+    ResolutionTrace {
+        executable: executable.to_string(),
+        steps,
+        resolved_profile,
+        running_process_ids: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn executable_keys_fold_only_ascii() {
+        assert_eq!(executable_key("GAME.Exe"), "game.exe");
+        assert_eq!(executable_key("ÉCRAN.EXE"), "Écran.exe");
+        assert_ne!(executable_key("STRASSE.exe"), executable_key("Straße.exe"));
+    }
+
+    #[test]
+    fn basenames_split_on_either_separator() {
+        assert_eq!(basename("C:\\Games\\bin/game.exe"), "game.exe");
+        assert_eq!(basename("game.exe"), "game.exe");
+    }
+
+    fn entry(executable: &str, profile_name: &str) -> DrsApplication {
+        DrsApplication {
+            name: executable.to_string(),
+            executable: executable.to_string(),
+            profile_name: profile_name.to_string(),
+            is_predefined: false,
+            is_blacklisted: false,
+            blacklist_origin: None,
+            provenance: None,
+            match_mode: None,
+        }
+    }
+
+    fn context(entries: Vec<DrsApplication>) -> MatchContext {
+        MatchContext { all_applications: Some(Ok(entries)) }
+    }
+
+    #[test]
+    fn strategies_run_cheapest_first() {
+        let order: Vec<&str> = MATCHERS.iter().map(|(strategy, _)| *strategy).collect();
+        assert_eq!(order, ["exact-name", "basename", "case-insensitive", "path-qualified-entry"]);
+    }
+
+    #[test]
+    fn basename_matching_skips_inputs_that_are_not_paths() {
+        // Rejected before any driver lookup, so no backend is needed
+        let mut ctx = context(Vec::new());
+        assert_eq!(match_basename("game.exe", &mut ctx), Err("input is not a path".to_string()));
+    }
+
+    #[test]
+    fn case_insensitive_matching_compares_the_basename_ignoring_case() {
+        let mut ctx = context(vec![entry("other.exe", "Other"), entry("Game.EXE", "Game")]);
+        assert_eq!(match_case_insensitive("C:\\Games\\game.exe", &mut ctx), Ok("Game".to_string()));
+        assert_eq!(
+            match_case_insensitive("missing.exe", &mut ctx),
+            Err("no entry matches 'missing.exe' ignoring case".to_string())
+        );
+    }
+
+    #[test]
+    fn path_qualified_matching_only_considers_entries_with_a_directory() {
+        let mut ctx = context(vec![entry("game.exe", "Plain"), entry("bin\\Game.exe", "Nested")]);
+        assert_eq!(
+            match_path_qualified("D:\\Game\\game.exe", &mut ctx),
+            Ok("Nested (entry 'bin\\Game.exe')".to_string())
+        );
+
+        let mut plain_only = context(vec![entry("game.exe", "Plain")]);
+        assert_eq!(
+            match_path_qualified("game.exe", &mut plain_only),
+            Err("no path-qualified entry ends with 'game.exe'".to_string())
+        );
+    }
+
+    #[test]
+    fn scanning_matchers_report_a_failed_enumeration() {
+        let mut ctx = MatchContext { all_applications: Some(Err("enumeration failed".to_string())) };
+        assert_eq!(match_case_insensitive("game.exe", &mut ctx), Err("enumeration failed".to_string()));
+        assert_eq!(match_path_qualified("game.exe", &mut ctx), Err("enumeration failed".to_string()));
+    }
+}
+
+#[cfg(all(test, target_os = "windows"))]
+mod mock_tests {
+    use super::*;
+    use crate::nvapi::mock::{self, Profile, Store};
+
+    fn install() {
+        mock::install(Store::default()
+            .with_profile(Profile::new("Resolve Exact").with_application("resolve-exact.exe"))
+            .with_profile(Profile::new("Resolve Nested").with_application("bin\\resolve-nested.exe")));
+    }
+
+    fn no_context() -> MatchContext {
+        MatchContext { all_applications: None }
+    }
+
+    #[test]
+    fn exact_name_matching_uses_the_driver_lookup() {
+        let _serial = mock::serial();
+        install();
+
+        assert_eq!(match_exact_name("resolve-exact.exe", &mut no_context()), Ok("Resolve Exact".to_string()));
+        assert_eq!(
+            match_exact_name("resolve-absent.exe", &mut no_context()),
+            Err("no application named 'resolve-absent.exe'".to_string())
+        );
+        // A full path is looked up as given; the mock matches whole names only
+        assert!(match_exact_name("C:\\Games\\resolve-exact.exe", &mut no_context()).is_err());
+    }
+
+    #[test]
+    fn basename_matching_looks_up_the_file_name_of_a_path() {
+        let _serial = mock::serial();
+        install();
+
+        assert_eq!(
+            match_basename("C:\\Games\\resolve-exact.exe", &mut no_context()),
+            Ok("Resolve Exact".to_string())
+        );
+    }
+
+    #[test]
+    fn the_trace_records_every_strategy_and_the_first_match() {
+        let _serial = mock::serial();
+        install();
+
+        let trace = resolve_application("C:\\Games\\resolve-nested.exe");
+        let matched: Vec<(&str, bool)> = trace.steps.iter().map(|s| (s.strategy.as_str(), s.matched)).collect();
+        assert_eq!(matched, [
+            ("exact-name", false),
+            ("basename", false),
+            ("case-insensitive", false),
+            ("path-qualified-entry", true),
+        ]);
+        assert!(trace.resolved_profile.unwrap().starts_with("Resolve Nested"));
+    }
+}
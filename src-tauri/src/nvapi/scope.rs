@@ -0,0 +1,189 @@
+//! DRS store scope detection
+//!
+//! NVAPI doesn't say whether a save lands in the machine-wide store or a per-user one.
+//! We find out by comparing the store files' modification times around each save.
+
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use once_cell::sync::Lazy;
+use super::types::{DrsScope, DrsStoreFile, DrsStoreInfo, ScopeExplanation};
+
+const STORE_FILE_NAMES: &[&str] = &["nvdrsdb0.bin", "nvdrsdb1.bin"];
+
+/// Scope detected after the most recent save
+static LAST_SAVE_SCOPE: Lazy<Mutex<DrsScope>> = Lazy::new(|| Mutex::new(DrsScope::Unknown));
+
+/// A store file we know how to look for
+#[derive(Debug, Clone)]
+pub struct StoreCandidate {
+    pub path: PathBuf,
+    pub scope: DrsScope,
+}
+
+/// Known store file locations. The machine store lives under ProgramData; some driver
+/// configurations keep a per-user copy under LocalAppData instead.
+pub fn store_candidates() -> Vec<StoreCandidate> {
+    let roots = [
+        ("ProgramData", DrsScope::Machine),
+        ("LOCALAPPDATA", DrsScope::UserOnly),
+    ];
+
+    roots.iter()
+        .filter_map(|(var, scope)| std::env::var(var).ok().map(|root| (root, *scope)))
+        .flat_map(|(root, scope)| {
+            let dir = PathBuf::from(root).join("NVIDIA Corporation").join("Drs");
+            STORE_FILE_NAMES.iter().map(move |name| StoreCandidate {
+                path: dir.join(name),
+                scope,
+            })
+        })
+        .collect()
+}
+
+/// Modification time of each candidate, None if it doesn't exist
+pub fn mtimes(candidates: &[StoreCandidate]) -> Vec<Option<SystemTime>> {
+    candidates.iter()
+        .map(|c| std::fs::metadata(&c.path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// Decide which store a save touched from the mtimes taken before and after it.
+/// A machine store change wins over a per-user one.
+pub fn changed_scope(
+    candidates: &[StoreCandidate],
+    before: &[Option<SystemTime>],
+    after: &[Option<SystemTime>],
+) -> DrsScope {
+    let changed = |scope: DrsScope| {
+        candidates.iter().zip(before.iter().zip(after))
+            .any(|(c, (b, a))| c.scope == scope && a.is_some() && a != b)
+    };
+
+    if changed(DrsScope::Machine) {
+        DrsScope::Machine
+    } else if changed(DrsScope::UserOnly) {
+        DrsScope::UserOnly
+    } else {
+        DrsScope::Unknown
+    }
+}
+
+/// Capture store mtimes before a save
+pub fn begin_save() -> (Vec<StoreCandidate>, Vec<Option<SystemTime>>) {
+    let candidates = store_candidates();
+    let before = mtimes(&candidates);
+    (candidates, before)
+}
+
+/// Record which store a completed save touched
+pub fn finish_save(candidates: &[StoreCandidate], before: &[Option<SystemTime>]) {
+    let after = mtimes(candidates);
+    *LAST_SAVE_SCOPE.lock().unwrap() = changed_scope(candidates, before, &after);
+}
+
+/// Scope detected after the most recent save
+pub fn last_save_scope() -> DrsScope {
+    *LAST_SAVE_SCOPE.lock().unwrap()
+}
+
+/// Known store files and the scope of the last save
+pub fn get_drs_store_info() -> DrsStoreInfo {
+    let candidates = store_candidates();
+    let files = candidates.iter()
+        .zip(mtimes(&candidates))
+        .map(|(c, modified)| DrsStoreFile {
+            path: c.path.display().to_string(),
+            scope: c.scope,
+            exists: modified.is_some() || c.path.exists(),
+            modified_ms: modified
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64),
+        })
+        .collect();
+
+    DrsStoreInfo {
+        files,
+        last_save_scope: last_save_scope(),
+    }
+}
+
+/// Plain-language summary of where changes are landing, for support conversations
+pub fn get_scope_explanation() -> ScopeExplanation {
+    let info = get_drs_store_info();
+    let explanation = match info.last_save_scope {
+        DrsScope::Machine => "The last save changed the machine-wide driver store, so it applies to every account on this PC.",
+        DrsScope::UserOnly => "The last save changed a per-user driver store, so it only applies to the account that made it. Other accounts on this PC keep their own settings.",
+        DrsScope::Unknown => "No store file changed on the last save, or nothing has been saved yet since Nvidiot started. Save a change and check again.",
+    };
+
+    ScopeExplanation {
+        scope: info.last_save_scope,
+        explanation: explanation.to_string(),
+        paths: info.files.into_iter().filter(|f| f.exists).map(|f| f.path).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn candidate(path: PathBuf, scope: DrsScope) -> StoreCandidate {
+        StoreCandidate { path, scope }
+    }
+
+    fn at(seconds: u64) -> Option<SystemTime> {
+        Some(UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    /// A machine and a per-user store, as fingerprints that don't need to exist
+    fn both_stores() -> Vec<StoreCandidate> {
+        vec![
+            candidate(PathBuf::from("machine\\nvdrsdb0.bin"), DrsScope::Machine),
+            candidate(PathBuf::from("user\\nvdrsdb0.bin"), DrsScope::UserOnly),
+        ]
+    }
+
+    #[test]
+    fn a_changed_machine_store_wins_over_a_per_user_one() {
+        let stores = both_stores();
+        let before = [at(10), at(10)];
+        assert_eq!(changed_scope(&stores, &before, &[at(20), at(20)]), DrsScope::Machine);
+        assert_eq!(changed_scope(&stores, &before, &[at(10), at(20)]), DrsScope::UserOnly);
+        assert_eq!(changed_scope(&stores, &before, &before), DrsScope::Unknown);
+    }
+
+    #[test]
+    fn a_store_created_by_the_save_counts_but_a_deleted_one_does_not() {
+        let stores = both_stores();
+        assert_eq!(changed_scope(&stores, &[None, at(10)], &[at(20), at(10)]), DrsScope::Machine);
+        assert_eq!(changed_scope(&stores, &[at(10), at(10)], &[None, at(10)]), DrsScope::Unknown);
+        assert_eq!(changed_scope(&stores, &[None, None], &[None, None]), DrsScope::Unknown);
+    }
+
+    #[test]
+    fn mtimes_follow_the_files_on_disk() {
+        let dir = std::env::temp_dir().join(format!("nvidiot-scope-tests-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let user_store = dir.join("nvdrsdb1.bin");
+        std::fs::write(&user_store, b"drs").unwrap();
+        let stores = vec![
+            candidate(dir.join("missing-nvdrsdb0.bin"), DrsScope::Machine),
+            candidate(user_store.clone(), DrsScope::UserOnly),
+        ];
+
+        let before = mtimes(&stores);
+        assert!(before[0].is_none());
+        assert!(before[1].is_some());
+
+        // Stands in for the driver writing the per-user store during a save
+        let saved_at = before[1].unwrap() + Duration::from_secs(5);
+        std::fs::File::options().write(true).open(&user_store).unwrap().set_modified(saved_at).unwrap();
+        let after = mtimes(&stores);
+        assert_eq!(after[1], Some(saved_at));
+        assert_eq!(changed_scope(&stores, &before, &after), DrsScope::UserOnly);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
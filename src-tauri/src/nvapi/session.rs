@@ -1,15 +1,29 @@
 //! DRS Session management
 //!
 //! Handles creating, loading, saving, and destroying DRS sessions.
+//!
+//! Besides the global session, read-only sessions can be opened from backup
+//! files. Each lives behind its own lock and is selected for the current thread
+//! with `with_backup_session`, so the regular enumeration code reads from it
+//! without taking a session parameter.
 
-use std::sync::Mutex;
-use once_cell::sync::OnceCell;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use once_cell::sync::{Lazy, OnceCell};
 use super::error::NvApiError;
 #[cfg(target_os = "windows")]
 use super::error::NVAPI_OK;
+#[cfg(target_os = "windows")]
+use super::health::checked;
 use super::ffi::NvDRSSessionHandle;
+use super::types::{Freshness, FreshnessSource};
+use crate::manifest::now_ms;
 #[cfg(target_os = "windows")]
-use super::ffi::get_nvapi;
+use super::ffi::{
+    get_nvapi, require_optional, string_to_wchar, NvApiFunction, NVAPI_UNICODE_STRING_MAX,
+};
 
 /// Wrapper for NvDRSSessionHandle that implements Send + Sync
 /// SAFETY: NVAPI session handles are safe to use from multiple threads
@@ -23,6 +37,127 @@ unsafe impl Sync for SessionHandle {}
 /// Global DRS session handle with mutex for thread safety
 static DRS_SESSION: OnceCell<Mutex<SessionHandle>> = OnceCell::new();
 
+/// Descriptions of mutations made since the last successful save or reload.
+/// Non-empty means the session is dirty.
+static PENDING_MUTATIONS: Lazy<Mutex<Vec<String>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// Outcome of the most recent save, None until one is attempted
+static LAST_SAVE_OK: Lazy<Mutex<Option<bool>>> = Lazy::new(|| Mutex::new(None));
+
+/// Identifier of a read-only session opened from a backup file
+pub type SessionId = u32;
+
+/// A session loaded from a backup file, destroyed when the last reference drops
+struct BackupSession(SessionHandle);
+
+#[cfg(target_os = "windows")]
+impl Drop for BackupSession {
+    fn drop(&mut self) {
+        if let Ok(api) = get_nvapi() {
+            if let Some(destroy) = api.drs_destroy_session {
+                unsafe {
+                    destroy(self.0.0);
+                }
+            }
+        }
+    }
+}
+
+static BACKUP_SESSIONS: Lazy<Mutex<HashMap<SessionId, Arc<Mutex<BackupSession>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_SESSION_ID: AtomicU32 = AtomicU32::new(1);
+
+thread_local! {
+    /// Backup session selected for the current thread, if any
+    static SESSION_OVERRIDE: Cell<Option<NvDRSSessionHandle>> = const { Cell::new(None) };
+    /// Global session locked by `with_session_held` on the current thread
+    #[cfg(target_os = "windows")]
+    static HELD_SESSION: Cell<Option<NvDRSSessionHandle>> = const { Cell::new(None) };
+    /// Finds processes that may be blocking a save; replaced by tests that count scans
+    #[cfg(target_os = "windows")]
+    static BLOCKER_LOOKUP: Cell<fn() -> Vec<String>> =
+        const { Cell::new(crate::windows::processes::find_drs_session_holders as fn() -> Vec<String>) };
+}
+
+/// Times the global session lock was taken, for tests that count acquisitions
+#[cfg(target_os = "windows")]
+static SESSION_LOCKS: AtomicU64 = AtomicU64::new(0);
+
+/// Incremented on every DRS mutation, save, and reload so caches can detect staleness
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// When the current generation began, in ms since the epoch; 0 until the first bump
+static GENERATION_STARTED_AT_MS: AtomicU64 = AtomicU64::new(0);
+
+/// Startup time, standing in for the start of generation 0
+static STARTED_AT_MS: Lazy<u64> = Lazy::new(now_ms);
+
+/// Current session generation
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::SeqCst)
+}
+
+/// Mark all cached DRS state as stale
+pub fn bump_generation() {
+    // Stamp first, so a reader that sees the new generation never pairs it with the old start
+    GENERATION_STARTED_AT_MS.store(now_ms(), Ordering::SeqCst);
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+/// When the current generation began. Caches keyed by generation were built no earlier.
+pub fn generation_started_at_ms() -> u64 {
+    match GENERATION_STARTED_AT_MS.load(Ordering::SeqCst) {
+        0 => *STARTED_AT_MS,
+        started => started,
+    }
+}
+
+/// Freshness of a response read now. `cache_built_at_ms` is when the oldest
+/// contributing cache was built, or None if everything was read live.
+pub fn freshness(cache_built_at_ms: Option<u64>) -> Freshness {
+    let session_generation = generation();
+    match cache_built_at_ms {
+        Some(built_at) => Freshness {
+            session_generation,
+            cache_built_at_ms: Some(built_at),
+            cache_age_ms: Some(now_ms().saturating_sub(built_at)),
+            source: FreshnessSource::Cache,
+        },
+        None => Freshness {
+            session_generation,
+            cache_built_at_ms: None,
+            cache_age_ms: None,
+            source: FreshnessSource::Live,
+        },
+    }
+}
+
+/// Record a successful unsaved mutation. Also bumps the generation.
+pub fn record_mutation(description: String) {
+    PENDING_MUTATIONS.lock().unwrap().push(description);
+    bump_generation();
+}
+
+/// Whether there are mutations that haven't been saved yet
+pub fn is_dirty() -> bool {
+    !PENDING_MUTATIONS.lock().unwrap().is_empty()
+}
+
+/// Descriptions of the mutations that haven't been saved yet
+pub fn pending_mutations() -> Vec<String> {
+    PENDING_MUTATIONS.lock().unwrap().clone()
+}
+
+/// Whether the last save succeeded, or None if nothing was saved yet
+pub fn last_save_ok() -> Option<bool> {
+    *LAST_SAVE_OK.lock().unwrap()
+}
+
+/// Forget pending mutations without saving them
+pub fn clear_dirty() {
+    PENDING_MUTATIONS.lock().unwrap().clear();
+}
+
 /// Create a new DRS session and load settings
 #[cfg(target_os = "windows")]
 pub fn create_session() -> Result<NvDRSSessionHandle, NvApiError> {
@@ -36,12 +171,12 @@ pub fn create_session() -> Result<NvDRSSessionHandle, NvApiError> {
     unsafe {
         let mut handle: NvDRSSessionHandle = std::ptr::null_mut();
 
-        let status = create_session(&mut handle);
+        let status = checked(create_session(&mut handle));
         if status != NVAPI_OK {
             return Err(NvApiError::SessionCreationFailed(status));
         }
 
-        let status = load_settings(handle);
+        let status = checked(load_settings(handle));
         if status != NVAPI_OK {
             // Clean up on failure
             if let Some(destroy) = api.drs_destroy_session {
@@ -59,16 +194,71 @@ pub fn create_session() -> Result<NvDRSSessionHandle, NvApiError> {
     Err(NvApiError::NotSupported)
 }
 
-/// Get or create the global DRS session
+/// Get or create the global DRS session, or the backup session selected for this thread
 #[cfg(target_os = "windows")]
 pub fn get_session() -> Result<NvDRSSessionHandle, NvApiError> {
+    if let Some(handle) = SESSION_OVERRIDE.with(|s| s.get()).or_else(|| HELD_SESSION.with(|s| s.get())) {
+        return Ok(handle);
+    }
+
+    let guard = lock_global_session()?;
+    Ok(guard.0)
+}
+
+#[cfg(target_os = "windows")]
+fn lock_global_session() -> Result<std::sync::MutexGuard<'static, SessionHandle>, NvApiError> {
     let mutex = DRS_SESSION.get_or_try_init(|| {
         let handle = create_session()?;
         Ok::<_, NvApiError>(Mutex::new(SessionHandle(handle)))
     })?;
 
-    let guard = mutex.lock().unwrap();
-    Ok(guard.0)
+    SESSION_LOCKS.fetch_add(1, Ordering::SeqCst);
+    Ok(mutex.lock().unwrap())
+}
+
+/// Clears the thread's held session, even if the closure panics
+#[cfg(target_os = "windows")]
+struct HeldGuard;
+
+#[cfg(target_os = "windows")]
+impl Drop for HeldGuard {
+    fn drop(&mut self) {
+        HELD_SESSION.with(|s| s.set(None));
+    }
+}
+
+/// Run `f` with the global session locked once for its whole duration, so a
+/// pass of many DRS reads neither re-takes the lock per call nor interleaves
+/// with other threads. Nested calls, and calls while a backup or fresh
+/// session is selected, just run `f`.
+#[cfg(target_os = "windows")]
+pub fn with_session_held<T, F>(f: F) -> Result<T, NvApiError>
+where
+    F: FnOnce() -> Result<T, NvApiError>,
+{
+    if SESSION_OVERRIDE.with(|s| s.get()).is_some() || HELD_SESSION.with(|s| s.get()).is_some() {
+        return f();
+    }
+
+    let guard = lock_global_session()?;
+    HELD_SESSION.with(|s| s.set(Some(guard.0)));
+    let _held = HeldGuard;
+
+    f()
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn with_session_held<T, F>(f: F) -> Result<T, NvApiError>
+where
+    F: FnOnce() -> Result<T, NvApiError>,
+{
+    f()
+}
+
+/// Times the global session lock has been taken since startup
+#[cfg(all(test, target_os = "windows"))]
+pub fn session_lock_count() -> u64 {
+    SESSION_LOCKS.load(Ordering::SeqCst)
 }
 
 #[cfg(not(target_os = "windows"))]
@@ -76,22 +266,234 @@ pub fn get_session() -> Result<NvDRSSessionHandle, NvApiError> {
     Err(NvApiError::NotSupported)
 }
 
+/// Whether this thread is currently reading from a backup session
+pub fn is_read_only() -> bool {
+    SESSION_OVERRIDE.with(|s| s.get()).is_some()
+}
+
+/// Get the session for an operation that modifies DRS state.
+/// Fails while a backup session is selected.
+pub fn get_session_mut() -> Result<NvDRSSessionHandle, NvApiError> {
+    if is_read_only() {
+        return Err(NvApiError::ReadOnlySession);
+    }
+    #[cfg(target_os = "windows")]
+    {
+        // Refuse to mutate when the change couldn't be saved or read back
+        let missing = get_nvapi()?.missing_minimal_drs_functions();
+        if !missing.is_empty() {
+            return Err(NvApiError::DrsApiIncomplete(missing.join(", ")));
+        }
+    }
+    get_session()
+}
+
+/// Open a DRS backup file into its own session for read-only browsing
+#[cfg(target_os = "windows")]
+pub fn open_backup_readonly(path: &str) -> Result<SessionId, NvApiError> {
+    let api = get_nvapi()?;
+
+    let create_session = api.drs_create_session
+        .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_CreateSession".to_string()))?;
+    let load_from_file = require_optional(api.drs_load_settings_from_file_ex, NvApiFunction::DrsLoadSettingsFromFileEx, "open-backup")?;
+
+    let mut file_name = [0u16; NVAPI_UNICODE_STRING_MAX];
+    string_to_wchar(path, &mut file_name);
+
+    let session = unsafe {
+        let mut handle: NvDRSSessionHandle = std::ptr::null_mut();
+
+        let status = create_session(&mut handle);
+        if status != NVAPI_OK {
+            return Err(NvApiError::SessionCreationFailed(status));
+        }
+
+        // Wrap immediately so the handle is destroyed if loading fails
+        let session = BackupSession(SessionHandle(handle));
+
+        let status = load_from_file(handle, file_name.as_ptr());
+        if status != NVAPI_OK {
+            return Err(NvApiError::LoadSettingsFailed(status));
+        }
+
+        session
+    };
+
+    let id = NEXT_SESSION_ID.fetch_add(1, Ordering::SeqCst);
+    BACKUP_SESSIONS.lock().unwrap().insert(id, Arc::new(Mutex::new(session)));
+    Ok(id)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn open_backup_readonly(_path: &str) -> Result<SessionId, NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Close a backup session. Returns false if the id was unknown.
+pub fn close_backup(id: SessionId) -> bool {
+    BACKUP_SESSIONS.lock().unwrap().remove(&id).is_some()
+}
+
+/// Close every open backup session
+pub fn close_all_backups() {
+    BACKUP_SESSIONS.lock().unwrap().clear();
+}
+
+/// Close backup sessions and destroy the global session. Called last on exit,
+/// after background tasks have stopped; unsaved changes are discarded.
+#[cfg(target_os = "windows")]
+pub fn shutdown() {
+    close_all_backups();
+
+    let Some(mutex) = DRS_SESSION.get() else {
+        return;
+    };
+    let mut guard = mutex.lock().unwrap();
+    if guard.0.is_null() {
+        return;
+    }
+    if let Ok(api) = get_nvapi() {
+        if let Some(destroy) = api.drs_destroy_session {
+            unsafe {
+                destroy(guard.0);
+            }
+        }
+    }
+    guard.0 = std::ptr::null_mut();
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn shutdown() {
+    close_all_backups();
+}
+
+/// Forget every session without destroying it, for when the library that
+/// created them is gone. Unsaved changes are lost with them.
+pub fn abandon_sessions() {
+    // Backup sessions skip destruction while get_nvapi refuses during a driver change
+    close_all_backups();
+    if let Some(mutex) = DRS_SESSION.get() {
+        mutex.lock().unwrap().0 = std::ptr::null_mut();
+    }
+    clear_dirty();
+}
+
+/// Replace the global session with a new one from the current library
+#[cfg(target_os = "windows")]
+pub fn recreate_global_session() -> Result<(), NvApiError> {
+    let handle = create_session()?;
+    match DRS_SESSION.get() {
+        Some(mutex) => mutex.lock().unwrap().0 = handle,
+        None => {
+            let _ = DRS_SESSION.set(Mutex::new(SessionHandle(handle)));
+        }
+    }
+    // The new session starts from disk, so nothing is staged any more
+    clear_dirty();
+    bump_generation();
+    Ok(())
+}
+
+/// Clears the thread's session override, even if the closure panics
+struct OverrideGuard;
+
+impl Drop for OverrideGuard {
+    fn drop(&mut self) {
+        SESSION_OVERRIDE.with(|s| s.set(None));
+    }
+}
+
+/// Run `f` with a backup session selected for the current thread.
+/// Anything that needs `get_session_mut` fails inside `f`.
+pub fn with_backup_session<T, F>(id: SessionId, f: F) -> Result<T, NvApiError>
+where
+    F: FnOnce() -> Result<T, NvApiError>,
+{
+    let session = BACKUP_SESSIONS.lock().unwrap()
+        .get(&id)
+        .cloned()
+        .ok_or(NvApiError::BackupSessionNotFound(id))?;
+
+    let guard = session.lock().unwrap();
+    SESSION_OVERRIDE.with(|s| s.set(Some(guard.0.0)));
+    let _override = OverrideGuard;
+
+    f()
+}
+
+/// Destroys a temporary session and restores the thread's previous override,
+/// even if the closure panics
+#[cfg(target_os = "windows")]
+struct FreshSession {
+    handle: NvDRSSessionHandle,
+    previous: Option<NvDRSSessionHandle>,
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for FreshSession {
+    fn drop(&mut self) {
+        SESSION_OVERRIDE.with(|s| s.set(self.previous));
+        if let Ok(api) = get_nvapi() {
+            if let Some(destroy) = api.drs_destroy_session {
+                unsafe {
+                    destroy(self.handle);
+                }
+            }
+        }
+    }
+}
+
+/// Run `f` against a temporary session loaded from disk, then destroy it
+/// whatever `f` returns. The session is also selected for the current thread,
+/// so helpers that call `get_session` read from it; like backup sessions it is
+/// read-only, and the global session's loaded state is never touched.
+#[cfg(target_os = "windows")]
+pub fn with_fresh_session<T, F>(f: F) -> Result<T, NvApiError>
+where
+    F: FnOnce(NvDRSSessionHandle) -> Result<T, NvApiError>,
+{
+    let handle = create_session()?;
+    let previous = SESSION_OVERRIDE.with(|s| s.replace(Some(handle)));
+    let _session = FreshSession { handle, previous };
+
+    f(handle)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn with_fresh_session<T, F>(_f: F) -> Result<T, NvApiError>
+where
+    F: FnOnce(NvDRSSessionHandle) -> Result<T, NvApiError>,
+{
+    Err(NvApiError::NotSupported)
+}
+
 /// Save current settings to disk
 #[cfg(target_os = "windows")]
 pub fn save_settings() -> Result<(), NvApiError> {
     let api = get_nvapi()?;
-    let session = get_session()?;
+    let session = get_session_mut()?;
 
     let save_fn = api.drs_save_settings
         .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_SaveSettings".to_string()))?;
 
+    let (store_candidates, store_mtimes) = super::scope::begin_save();
+
     unsafe {
-        let status = save_fn(session);
+        let status = checked(save_fn(session));
+        *LAST_SAVE_OK.lock().unwrap() = Some(status == NVAPI_OK);
         if status != NVAPI_OK {
-            return Err(NvApiError::SaveSettingsFailed(status));
+            // Only worth a process scan once a save has actually failed
+            let holders = BLOCKER_LOOKUP.with(|lookup| lookup.get())();
+            return Err(NvApiError::SaveSettingsFailed {
+                status,
+                probable_blocker: (!holders.is_empty()).then(|| holders.join(", ")),
+            });
         }
     }
 
+    super::scope::finish_save(&store_candidates, &store_mtimes);
+    clear_dirty();
+    bump_generation();
     Ok(())
 }
 
@@ -104,18 +506,21 @@ pub fn save_settings() -> Result<(), NvApiError> {
 #[cfg(target_os = "windows")]
 pub fn reload_settings() -> Result<(), NvApiError> {
     let api = get_nvapi()?;
-    let session = get_session()?;
+    let session = get_session_mut()?;
 
     let load_fn = api.drs_load_settings
         .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_LoadSettings".to_string()))?;
 
     unsafe {
-        let status = load_fn(session);
+        let status = checked(load_fn(session));
         if status != NVAPI_OK {
             return Err(NvApiError::LoadSettingsFailed(status));
         }
     }
 
+    // Reloading drops unsaved changes from the session
+    clear_dirty();
+    bump_generation();
     Ok(())
 }
 
@@ -124,6 +529,70 @@ pub fn reload_settings() -> Result<(), NvApiError> {
     Err(NvApiError::NotSupported)
 }
 
+/// Replace the global session's settings with those in a DRS file. Unsaved
+/// changes are dropped; nothing reaches the driver store until the next save.
+#[cfg(target_os = "windows")]
+pub fn load_settings_from_file(path: &str) -> Result<(), NvApiError> {
+    let api = get_nvapi()?;
+    let session = get_session_mut()?;
+
+    let load_fn = require_optional(api.drs_load_settings_from_file, NvApiFunction::DrsLoadSettingsFromFile, "drs-file-transfer")?;
+
+    let mut file_name = [0u16; NVAPI_UNICODE_STRING_MAX];
+    string_to_wchar(path, &mut file_name);
+
+    unsafe {
+        let status = checked(load_fn(session, file_name.as_ptr()));
+        if status != NVAPI_OK {
+            return Err(NvApiError::LoadSettingsFailed(status));
+        }
+    }
+
+    // The file replaces the session's unsaved changes, and isn't in the driver store until saved
+    clear_dirty();
+    record_mutation(format!("Loaded settings from '{}'", path));
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn load_settings_from_file(_path: &str) -> Result<(), NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Write the current session's settings, including unsaved changes, to a DRS file.
+/// The driver's own store is left untouched.
+#[cfg(target_os = "windows")]
+pub fn save_settings_to_file(path: &str) -> Result<(), NvApiError> {
+    let api = get_nvapi()?;
+    let session = get_session()?;
+
+    let save_fn = require_optional(api.drs_save_settings_to_file, NvApiFunction::DrsSaveSettingsToFile, "drs-file-transfer")?;
+
+    let mut file_name = [0u16; NVAPI_UNICODE_STRING_MAX];
+    string_to_wchar(path, &mut file_name);
+
+    unsafe {
+        let status = checked(save_fn(session, file_name.as_ptr()));
+        if status != NVAPI_OK {
+            return Err(NvApiError::SaveSettingsFailed { status, probable_blocker: None });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn save_settings_to_file(_path: &str) -> Result<(), NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Save settings and immediately reload them, so the session reflects exactly what
+/// the driver accepted. NVAPI has no public flush call (a `NvAPI_DRS_FlushSettings`
+/// is sometimes mentioned but isn't exported), so this is the consistency point.
+pub fn flush() -> Result<(), NvApiError> {
+    save_settings()?;
+    reload_settings()
+}
+
 /// Check if NVAPI is available
 pub fn check_nvapi() -> Result<(), NvApiError> {
     #[cfg(target_os = "windows")]
@@ -136,3 +605,127 @@ pub fn check_nvapi() -> Result<(), NvApiError> {
         Err(NvApiError::NotSupported)
     }
 }
+
+#[cfg(all(test, target_os = "windows"))]
+mod tests {
+    use super::*;
+    use crate::nvapi::error::NVAPI_ERROR;
+    use crate::nvapi::ffi::{NvApiFunction, SHADOWPLAY_DISABLED, SHADOWPLAY_SETTING_ID};
+    use crate::nvapi::mock::{self, Profile, Store};
+    use crate::nvapi::profiles::find_profile_by_name;
+    use crate::nvapi::settings::set_dword_setting;
+
+    const DRS_FILE: &str = "C:\\Backups\\session-dirty.nip";
+
+    /// Install a store with one profile and change a setting in it without saving
+    fn install_with_unsaved_change(profile_name: &str) {
+        mock::install(Store::default().with_profile(Profile::new(profile_name)));
+        clear_dirty();
+        let profile = find_profile_by_name(profile_name).unwrap();
+        set_dword_setting(profile, SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED).unwrap();
+    }
+
+    #[test]
+    fn a_mutation_marks_the_session_dirty_until_it_is_saved() {
+        let _serial = mock::serial();
+        install_with_unsaved_change("Dirty Saved");
+        assert!(is_dirty());
+        assert_eq!(pending_mutations().len(), 1);
+
+        save_settings().unwrap();
+        assert!(!is_dirty());
+        assert_eq!(last_save_ok(), Some(true));
+        assert_eq!(mock::disk().profile("Dirty Saved").unwrap().dword(SHADOWPLAY_SETTING_ID), Some(SHADOWPLAY_DISABLED));
+    }
+
+    #[test]
+    fn a_failed_save_keeps_the_changes_pending() {
+        let _serial = mock::serial();
+        install_with_unsaved_change("Dirty Failed");
+        mock::inject_status(NvApiFunction::DrsSaveSettings, NVAPI_ERROR);
+
+        assert!(matches!(save_settings(), Err(NvApiError::SaveSettingsFailed { status: NVAPI_ERROR, .. })));
+        assert!(is_dirty());
+        assert_eq!(last_save_ok(), Some(false));
+    }
+
+    static BLOCKER_LOOKUPS: AtomicU64 = AtomicU64::new(0);
+
+    fn counting_blocker_lookup() -> Vec<String> {
+        BLOCKER_LOOKUPS.fetch_add(1, Ordering::SeqCst);
+        vec!["nvidiaProfileInspector.exe".to_string()]
+    }
+
+    /// Swap in a lookup that counts its calls, restoring the real one afterwards
+    fn with_counted_blocker_lookup(f: impl FnOnce()) -> u64 {
+        let real = BLOCKER_LOOKUP.with(|lookup| lookup.replace(counting_blocker_lookup));
+        let before = BLOCKER_LOOKUPS.load(Ordering::SeqCst);
+        f();
+        BLOCKER_LOOKUP.with(|lookup| lookup.set(real));
+        BLOCKER_LOOKUPS.load(Ordering::SeqCst) - before
+    }
+
+    #[test]
+    fn a_successful_save_never_looks_for_blockers() {
+        let _serial = mock::serial();
+        install_with_unsaved_change("Blocker Lookup Saved");
+
+        let lookups = with_counted_blocker_lookup(|| save_settings().unwrap());
+        assert_eq!(lookups, 0);
+    }
+
+    #[test]
+    fn a_failed_save_looks_for_blockers_once_and_names_them() {
+        let _serial = mock::serial();
+        install_with_unsaved_change("Blocker Lookup Failed");
+        mock::inject_status(NvApiFunction::DrsSaveSettings, NVAPI_ERROR);
+
+        let lookups = with_counted_blocker_lookup(|| {
+            match save_settings() {
+                Err(NvApiError::SaveSettingsFailed { probable_blocker, .. }) => {
+                    assert_eq!(probable_blocker.as_deref(), Some("nvidiaProfileInspector.exe"));
+                }
+                other => panic!("expected a failed save, got {:?}", other),
+            }
+        });
+        assert_eq!(lookups, 1);
+    }
+
+    #[test]
+    fn reloading_drops_the_pending_changes() {
+        let _serial = mock::serial();
+        install_with_unsaved_change("Dirty Reloaded");
+
+        reload_settings().unwrap();
+        assert!(!is_dirty());
+        assert_eq!(mock::saves(), 0);
+    }
+
+    #[test]
+    fn loading_a_drs_file_replaces_the_pending_changes_and_stays_dirty() {
+        let _serial = mock::serial();
+        install_with_unsaved_change("Dirty Before Load");
+        mock::write_file(DRS_FILE, Store::default().with_profile(Profile::new("Dirty From File")));
+
+        load_settings_from_file(DRS_FILE).unwrap();
+        assert_eq!(pending_mutations(), [format!("Loaded settings from '{}'", DRS_FILE)]);
+        assert!(find_profile_by_name("Dirty From File").is_ok());
+        // Nothing reaches the driver store until the next save
+        assert_eq!(mock::saves(), 0);
+        assert!(mock::disk().profile("Dirty From File").is_none());
+        clear_dirty();
+    }
+
+    #[test]
+    fn a_file_that_cannot_be_loaded_leaves_the_pending_changes() {
+        let _serial = mock::serial();
+        install_with_unsaved_change("Dirty Load Failed");
+
+        assert!(matches!(
+            load_settings_from_file("C:\\Backups\\missing.nip"),
+            Err(NvApiError::LoadSettingsFailed(NVAPI_ERROR)),
+        ));
+        assert_eq!(pending_mutations().len(), 1);
+        clear_dirty();
+    }
+}
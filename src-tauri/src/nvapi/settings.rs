@@ -2,19 +2,46 @@
 //!
 //! Handles getting and setting DRS settings, particularly the ShadowPlay blacklist.
 
+use std::collections::HashMap;
+use std::sync::{Mutex, RwLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use once_cell::sync::Lazy;
 use super::error::{NvApiError, NVAPI_OK, NVAPI_SETTING_NOT_FOUND};
+use super::health::checked;
+use super::statuses::{classify, ReadCall, ReadStatus};
+use crate::journal::{self, JournalCategory, JournalSeverity};
 use super::ffi::{
-    get_nvapi, NvDRSProfileHandle, NvdrsSetting, NVDRS_SETTING_VER,
+    get_nvapi, require_optional, wchar_to_string, with_setting_batch, NvApiFunction, NvDRSProfileHandle, NvdrsSetting,
+    NvdrsSettingType, NvdrsSettingValue, NvdrsSettingValues,
+    NVDRS_SETTING_VER, NVDRS_SETTING_VALUES_VER, NVAPI_SETTING_MAX_VALUES, NVAPI_MAX_AVAILABLE_SETTING_IDS, NVAPI_UNICODE_STRING_MAX,
     SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED, SHADOWPLAY_ENABLED,
+    POWER_MANAGEMENT_SETTING_ID, PREFERRED_PSTATE_ADAPTIVE, PREFERRED_PSTATE_PREFER_MAX, PREFERRED_PSTATE_DRIVER_CONTROLLED,
+    PREFERRED_PSTATE_PREFER_CONSISTENT_PERFORMANCE, PREFERRED_PSTATE_PREFER_MIN, PREFERRED_PSTATE_OPTIMAL_POWER,
+    PREFERRED_PSTATE_DEFAULT,
 };
-use super::session::{get_session, save_settings};
+use super::session::{get_session, get_session_mut, is_read_only, save_settings, generation, record_mutation};
 use super::applications::find_application;
-use super::profiles::{find_profile_by_name, create_profile};
-use super::types::BlacklistResult;
+use super::profiles::{
+    enumerate_profiles, find_profile_by_name, create_profile_if_not_exists, get_base_profile, get_profile_info_by_handle,
+};
+use super::naming::{override_profile_name, profile_name_for};
+use super::reconcile::find_reusable_profile;
+use super::resolve::executable_key;
+use super::scope::last_save_scope;
+use super::types::{
+    AvailableSetting, BlacklistOrigin, BlacklistQuery, BlacklistResult, BlacklistWarning, CacheStats, DrsScope, DrsSetting, PowerMode, PowerModeStatus, ProcessBlacklistStatus, SettingValue, StatusSource,
+};
 
-/// Get a DWORD setting value from a profile
-#[cfg(target_os = "windows")]
+/// Get a DWORD setting value from a profile. A setting with nothing stored is
+/// reported as `GetSettingFailed(NVAPI_SETTING_NOT_FOUND)`; see `find_dword_setting`.
 pub fn get_dword_setting(profile_handle: NvDRSProfileHandle, setting_id: u32) -> Result<u32, NvApiError> {
+    find_dword_setting(profile_handle, setting_id)?
+        .ok_or(NvApiError::GetSettingFailed(NVAPI_SETTING_NOT_FOUND))
+}
+
+/// A setting as stored in a profile, or None when nothing is stored for it
+#[cfg(target_os = "windows")]
+fn read_setting(profile_handle: NvDRSProfileHandle, setting_id: u32) -> Result<Option<NvdrsSetting>, NvApiError> {
     let api = get_nvapi()?;
     let session = get_session()?;
 
@@ -25,21 +52,23 @@ pub fn get_dword_setting(profile_handle: NvDRSProfileHandle, setting_id: u32) ->
         let mut setting = NvdrsSetting::default();
         setting.version = NVDRS_SETTING_VER;
 
-        let status = get_setting(session, profile_handle, setting_id, &mut setting);
-
-        if status == NVAPI_SETTING_NOT_FOUND {
-            return Err(NvApiError::GetSettingFailed(status));
+        let status = checked(get_setting(session, profile_handle, setting_id, &mut setting));
+        match classify(ReadCall::GetSetting, status) {
+            ReadStatus::Present => Ok(Some(setting)),
+            ReadStatus::Absent => Ok(None),
+            ReadStatus::Failed => Err(NvApiError::GetSettingFailed(status)),
         }
-        if status != NVAPI_OK {
-            return Err(NvApiError::GetSettingFailed(status));
-        }
-
-        Ok(setting.current_value.dword_value)
     }
 }
 
+/// A DWORD setting value from a profile, or None when nothing is stored for it
+#[cfg(target_os = "windows")]
+pub fn find_dword_setting(profile_handle: NvDRSProfileHandle, setting_id: u32) -> Result<Option<u32>, NvApiError> {
+    Ok(read_setting(profile_handle, setting_id)?.map(|setting| unsafe { setting.current_value.dword_value }))
+}
+
 #[cfg(not(target_os = "windows"))]
-pub fn get_dword_setting(_profile_handle: NvDRSProfileHandle, _setting_id: u32) -> Result<u32, NvApiError> {
+pub fn find_dword_setting(_profile_handle: NvDRSProfileHandle, _setting_id: u32) -> Result<Option<u32>, NvApiError> {
     Err(NvApiError::NotSupported)
 }
 
@@ -47,7 +76,7 @@ pub fn get_dword_setting(_profile_handle: NvDRSProfileHandle, _setting_id: u32)
 #[cfg(target_os = "windows")]
 pub fn set_dword_setting(profile_handle: NvDRSProfileHandle, setting_id: u32, value: u32) -> Result<(), NvApiError> {
     let api = get_nvapi()?;
-    let session = get_session()?;
+    let session = get_session_mut()?;
 
     let set_setting = api.drs_set_setting
         .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_SetSetting".to_string()))?;
@@ -59,103 +88,625 @@ pub fn set_dword_setting(profile_handle: NvDRSProfileHandle, setting_id: u32, va
         setting.setting_type = 0; // DWORD
         setting.current_value.dword_value = value;
 
-        let status = set_setting(session, profile_handle, &mut setting);
+        let status = checked(set_setting(session, profile_handle, &mut setting));
 
         if status != NVAPI_OK {
             return Err(NvApiError::SetSettingFailed(status));
         }
     }
 
+    record_mutation(format!("Set setting 0x{:08X} to 0x{:08X}", setting_id, value));
+    if setting_id == SHADOWPLAY_SETTING_ID {
+        record_shadowplay_write(profile_handle, value);
+    }
     Ok(())
 }
 
+/// Remember a ShadowPlay write for revert loop detection, for every executable
+/// in the profile. Every write path ends here, so our own changes are never
+/// mistaken for another tool undoing one.
+#[cfg(target_os = "windows")]
+fn record_shadowplay_write(profile_handle: NvDRSProfileHandle, value: u32) {
+    let Ok(applications) = super::applications::enumerate_applications(profile_handle, "") else {
+        return;
+    };
+    for app in &applications {
+        crate::revertloop::record_write(&app.executable, value == SHADOWPLAY_DISABLED);
+    }
+}
+
 #[cfg(not(target_os = "windows"))]
 pub fn set_dword_setting(_profile_handle: NvDRSProfileHandle, _setting_id: u32, _value: u32) -> Result<(), NvApiError> {
     Err(NvApiError::NotSupported)
 }
 
-/// Check if ShadowPlay is disabled for a profile
+/// A profile's own value for a DWORD setting, or None when it has no override
+/// and the value comes from predefined data, the base profile or the driver default
 #[cfg(target_os = "windows")]
-pub fn get_shadowplay_status(profile_handle: NvDRSProfileHandle) -> Result<bool, NvApiError> {
-    match get_dword_setting(profile_handle, SHADOWPLAY_SETTING_ID) {
-        Ok(value) => Ok(value == SHADOWPLAY_DISABLED),
-        Err(NvApiError::GetSettingFailed(_)) => {
-            // Setting not found means default (enabled)
-            Ok(false)
+pub fn get_dword_override(profile_handle: NvDRSProfileHandle, setting_id: u32) -> Result<Option<u32>, NvApiError> {
+    /// NVDRS_CURRENT_PROFILE_LOCATION
+    const CURRENT_PROFILE_LOCATION: u32 = 0;
+
+    let api = get_nvapi()?;
+    let session = get_session()?;
+
+    let get_setting = api.drs_get_setting
+        .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_GetSetting".to_string()))?;
+
+    unsafe {
+        let mut setting = NvdrsSetting::default();
+        setting.version = NVDRS_SETTING_VER;
+
+        let status = checked(get_setting(session, profile_handle, setting_id, &mut setting));
+        match classify(ReadCall::GetSetting, status) {
+            ReadStatus::Present => {}
+            ReadStatus::Absent => return Ok(None),
+            ReadStatus::Failed => return Err(NvApiError::GetSettingFailed(status)),
         }
-        Err(e) => Err(e),
+
+        let is_override = setting.setting_location == CURRENT_PROFILE_LOCATION && setting.is_current_predefined == 0;
+        Ok(is_override.then_some(setting.current_value.dword_value))
     }
 }
 
+#[cfg(not(target_os = "windows"))]
+pub fn get_dword_override(_profile_handle: NvDRSProfileHandle, _setting_id: u32) -> Result<Option<u32>, NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Remove a profile's own value for a setting, restoring the predefined or default value.
+/// A setting the profile doesn't override is left alone.
+#[cfg(target_os = "windows")]
+pub fn delete_setting(profile_handle: NvDRSProfileHandle, setting_id: u32) -> Result<(), NvApiError> {
+    let api = get_nvapi()?;
+    let session = get_session_mut()?;
+
+    let delete_fn = api.drs_delete_profile_setting
+        .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_DeleteProfileSetting".to_string()))?;
+
+    let status = unsafe { checked(delete_fn(session, profile_handle, setting_id)) };
+    if status == NVAPI_SETTING_NOT_FOUND {
+        return Ok(());
+    }
+    if status != NVAPI_OK {
+        return Err(NvApiError::SetSettingFailed(status));
+    }
+
+    record_mutation(format!("Deleted setting 0x{:08X}", setting_id));
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn delete_setting(_profile_handle: NvDRSProfileHandle, _setting_id: u32) -> Result<(), NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Decode a setting value by its type. String settings are stored as UTF-16.
+fn decode_value(setting_type: u32, value: &NvdrsSettingValue) -> SettingValue {
+    // SAFETY: every bit pattern of each union member is valid
+    match setting_type {
+        t if t == NvdrsSettingType::Dword as u32 => SettingValue::Dword { value: unsafe { value.dword_value } },
+        t if t == NvdrsSettingType::String as u32 || t == NvdrsSettingType::WString as u32 => {
+            SettingValue::String { value: wchar_to_string(unsafe { &value.wsz_value }) }
+        }
+        _ => {
+            let binary = unsafe { &value.binary_value };
+            // A driver reporting more than fits is clamped to the buffer
+            let len = (binary.value_length as usize).min(binary.value_data.len());
+            SettingValue::Binary { bytes: binary.value_data[..len].to_vec() }
+        }
+    }
+}
+
+/// Enumerate all settings stored in a profile
+#[cfg(target_os = "windows")]
+pub fn get_all_settings(profile_handle: NvDRSProfileHandle) -> Result<Vec<DrsSetting>, NvApiError> {
+    let api = get_nvapi()?;
+    let session = get_session()?;
+
+    let enum_settings = api.drs_enum_settings
+        .ok_or_else(|| NvApiError::FunctionNotFound("NvAPI_DRS_EnumSettings".to_string()))?;
+
+    let mut settings = Vec::new();
+    let mut start_index: u32 = 0;
+
+    with_setting_batch(|batch| unsafe {
+        loop {
+            let mut count = batch.len() as u32;
+            let status = enum_settings(session, profile_handle, start_index, &mut count, batch.as_mut_ptr());
+            match classify(ReadCall::EnumSettings, status) {
+                ReadStatus::Present => {}
+                ReadStatus::Absent => break,
+                ReadStatus::Failed => return Err(NvApiError::GetSettingFailed(status)),
+            }
+            if count == 0 {
+                break;
+            }
+
+            for setting in &batch[..count as usize] {
+                let is_dword = setting.setting_type == NvdrsSettingType::Dword as u32;
+                settings.push(DrsSetting {
+                    setting_id: setting.setting_id,
+                    name: wchar_to_string(&setting.setting_name),
+                    setting_type: setting.setting_type,
+                    dword_value: if is_dword { Some(setting.current_value.dword_value) } else { None },
+                    is_current_predefined: setting.is_current_predefined != 0,
+                    value: Some(decode_value(setting.setting_type, &setting.current_value)),
+                });
+            }
+
+            start_index += count;
+        }
+
+        Ok(())
+    })?;
+
+    Ok(settings)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn get_all_settings(_profile_handle: NvDRSProfileHandle) -> Result<Vec<DrsSetting>, NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Check if ShadowPlay is disabled for a profile
+#[cfg(target_os = "windows")]
+pub fn get_shadowplay_status(profile_handle: NvDRSProfileHandle) -> Result<bool, NvApiError> {
+    // Nothing stored means the default (enabled)
+    Ok(find_dword_setting(profile_handle, SHADOWPLAY_SETTING_ID)? == Some(SHADOWPLAY_DISABLED))
+}
+
 #[cfg(not(target_os = "windows"))]
 pub fn get_shadowplay_status(_profile_handle: NvDRSProfileHandle) -> Result<bool, NvApiError> {
     Err(NvApiError::NotSupported)
 }
 
-/// Blacklist an application (disable ShadowPlay for it)
+/// Who disabled ShadowPlay on a profile, or None if it isn't disabled. NVIDIA
+/// ships a few predefined profiles (mostly anti-cheat titles) with capture
+/// disabled, and those report the current value as the predefined one.
+pub fn classify_blacklist_origin(
+    value: Option<u32>,
+    is_current_predefined: bool,
+    profile_is_predefined: bool,
+) -> Option<BlacklistOrigin> {
+    if value != Some(SHADOWPLAY_DISABLED) {
+        return None;
+    }
+    Some(match (profile_is_predefined, is_current_predefined) {
+        (true, true) => BlacklistOrigin::Nvidia,
+        (true, false) => BlacklistOrigin::UserOverride,
+        (false, _) => BlacklistOrigin::User,
+    })
+}
+
+/// Read and classify who disabled ShadowPlay on a profile
 #[cfg(target_os = "windows")]
-pub fn blacklist_application(executable: &str) -> Result<BlacklistResult, NvApiError> {
-    // Try to find existing application
-    match find_application(executable) {
-        Ok((profile_handle, _app)) => {
-            // Application exists, set the ShadowPlay setting
-            set_dword_setting(profile_handle, SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED)?;
-            save_settings()?;
+pub fn get_blacklist_origin(profile_handle: NvDRSProfileHandle, profile_is_predefined: bool) -> Result<Option<BlacklistOrigin>, NvApiError> {
+    let setting = read_setting(profile_handle, SHADOWPLAY_SETTING_ID)?;
+    let value = setting.as_ref().map(|s| unsafe { s.current_value.dword_value });
+    let is_current_predefined = setting.as_ref().is_some_and(|s| s.is_current_predefined != 0);
+    Ok(classify_blacklist_origin(value, is_current_predefined, profile_is_predefined))
+}
 
-            Ok(BlacklistResult {
-                success: true,
-                executable: executable.to_string(),
-                message: "Application blacklisted successfully".to_string(),
-            })
+#[cfg(not(target_os = "windows"))]
+pub fn get_blacklist_origin(_profile_handle: NvDRSProfileHandle, _profile_is_predefined: bool) -> Result<Option<BlacklistOrigin>, NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Cached ShadowPlay status for a profile, valid for one session generation
+struct CachedStatus {
+    shadowplay: Option<bool>,
+    /// Only the profile status cache fills this in
+    origin: Option<BlacklistOrigin>,
+    generation: u64,
+}
+
+/// Profile name -> ShadowPlay status, shared by all listing commands
+static PROFILE_STATUS_CACHE: Lazy<Mutex<HashMap<String, CachedStatus>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static PROFILE_STATUS_HITS: AtomicU64 = AtomicU64::new(0);
+static PROFILE_STATUS_MISSES: AtomicU64 = AtomicU64::new(0);
+
+/// ShadowPlay status and its origin for a profile, read at most once per session
+/// generation. A single GetSetting answers both: disabled exactly when there's an origin.
+fn cached_profile_status(
+    profile_handle: NvDRSProfileHandle,
+    profile_name: &str,
+    profile_is_predefined: bool,
+) -> (Option<bool>, Option<BlacklistOrigin>) {
+    let read = || match get_blacklist_origin(profile_handle, profile_is_predefined) {
+        Ok(origin) => (Some(origin.is_some()), origin),
+        Err(_) => (None, None),
+    };
+
+    // Backup sessions share profile names with the live one, so keep them out of the cache
+    if is_read_only() {
+        return read();
+    }
+
+    let current = generation();
+
+    if let Some(cached) = PROFILE_STATUS_CACHE.lock().unwrap().get(profile_name) {
+        if cached.generation == current {
+            PROFILE_STATUS_HITS.fetch_add(1, Ordering::Relaxed);
+            return (cached.shadowplay, cached.origin);
+        }
+    }
+
+    PROFILE_STATUS_MISSES.fetch_add(1, Ordering::Relaxed);
+    let (shadowplay, origin) = read();
+
+    PROFILE_STATUS_CACHE.lock().unwrap().insert(profile_name.to_string(), CachedStatus {
+        shadowplay,
+        origin,
+        generation: current,
+    });
+
+    (shadowplay, origin)
+}
+
+/// Get ShadowPlay status for a profile, fetching it at most once per session generation
+pub fn get_cached_shadowplay_status(profile_handle: NvDRSProfileHandle, profile_name: &str, profile_is_predefined: bool) -> Option<bool> {
+    cached_profile_status(profile_handle, profile_name, profile_is_predefined).0
+}
+
+/// Who disabled ShadowPlay on a profile, from the same cache as its status
+pub fn get_cached_blacklist_origin(profile_handle: NvDRSProfileHandle, profile_name: &str, profile_is_predefined: bool) -> Option<BlacklistOrigin> {
+    cached_profile_status(profile_handle, profile_name, profile_is_predefined).1
+}
+
+/// Hit/miss counters and size of the profile status cache
+pub fn profile_status_cache_stats() -> CacheStats {
+    let current = generation();
+    let cache = PROFILE_STATUS_CACHE.lock().unwrap();
+    let entries = cache.values()
+        .filter(|c| c.generation == current)
+        .count() as u64;
+    // Stale entries still hold memory until overwritten, so count all of them
+    let approx_bytes = cache.keys()
+        .map(|name| (std::mem::size_of::<(String, CachedStatus)>() + name.len()) as u64)
+        .sum();
+
+    CacheStats {
+        name: "profile-status".to_string(),
+        hits: PROFILE_STATUS_HITS.load(Ordering::Relaxed),
+        misses: PROFILE_STATUS_MISSES.load(Ordering::Relaxed),
+        negative_hits: 0,
+        entries,
+        approx_bytes,
+    }
+}
+
+/// Number of profiles with ShadowPlay disabled among statuses cached for the
+/// current generation, or None if nothing is cached yet. Never reads DRS.
+pub fn cached_blacklisted_profile_count() -> Option<u32> {
+    let current = generation();
+    let cache = PROFILE_STATUS_CACHE.lock().unwrap();
+    let mut fresh = cache.values().filter(|c| c.generation == current).peekable();
+    fresh.peek()?;
+    Some(fresh.filter(|c| c.shadowplay == Some(true)).count() as u32)
+}
+
+/// Drop every cached profile status; they are refetched on demand
+pub fn clear_profile_status_cache() {
+    PROFILE_STATUS_CACHE.lock().unwrap().clear();
+}
+
+/// Executables kept by the point-query cache before it is cleared and refilled
+const EXECUTABLE_STATUS_LIMIT: usize = 4096;
+
+/// executable_key -> blacklist status, filled by point queries and process listings.
+/// Readers share the lock, and writers only hold it to insert, never across an NVAPI call.
+static EXECUTABLE_STATUS_CACHE: Lazy<RwLock<HashMap<String, CachedStatus>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+static EXECUTABLE_STATUS_HITS: AtomicU64 = AtomicU64::new(0);
+static EXECUTABLE_STATUS_MISSES: AtomicU64 = AtomicU64::new(0);
+
+fn remember_executable_status(executable: &str, blacklisted: Option<bool>, generation: u64) {
+    let mut cache = EXECUTABLE_STATUS_CACHE.write().unwrap();
+    if cache.len() >= EXECUTABLE_STATUS_LIMIT {
+        cache.clear();
+    }
+    cache.insert(executable_key(executable), CachedStatus { shadowplay: blacklisted, origin: None, generation });
+}
+
+/// Whether one executable is blacklisted, for overlays and scripts that poll.
+///
+/// A warm answer comes from the executable status cache without touching NVAPI.
+/// A cold one costs a single FindApplicationByName and GetSetting, never an
+/// enumeration. Cached answers are kept for the session generation they were
+/// read in, so any mutation, save or reload made by Nvidiot invalidates them at
+/// once. Changes written by other tools show up only after the session is
+/// reloaded, the same staleness window as every other cached status.
+pub fn is_blacklisted(executable: &str) -> Result<BlacklistQuery, NvApiError> {
+    let current = generation();
+
+    if !is_read_only() {
+        if let Some(cached) = EXECUTABLE_STATUS_CACHE.read().unwrap().get(&executable_key(executable)) {
+            if cached.generation == current {
+                EXECUTABLE_STATUS_HITS.fetch_add(1, Ordering::Relaxed);
+                return Ok(BlacklistQuery { blacklisted: cached.shadowplay, source: StatusSource::Cache, generation: current });
+            }
+        }
+    }
+
+    EXECUTABLE_STATUS_MISSES.fetch_add(1, Ordering::Relaxed);
+    let blacklisted = match find_application(executable) {
+        Ok((profile_handle, _app)) => Some(get_shadowplay_status(profile_handle)?),
+        Err(NvApiError::ApplicationNotFound(_)) => None,
+        Err(e) => return Err(e),
+    };
+
+    // Tagged with the generation from before the lookup, so a mutation made meanwhile makes it stale
+    if !is_read_only() {
+        remember_executable_status(executable, blacklisted, current);
+    }
+    Ok(BlacklistQuery { blacklisted, source: StatusSource::Live, generation: current })
+}
+
+/// ShadowPlay value stored in an executable's profile, read live; None if the
+/// profile stores nothing or the executable has no DRS entry. The executable's
+/// cached status is refreshed with the result.
+pub fn read_executable_shadowplay(executable: &str) -> Result<Option<u32>, NvApiError> {
+    let current = generation();
+    let (found, value) = match find_application(executable) {
+        Ok((profile_handle, _app)) => (true, find_dword_setting(profile_handle, SHADOWPLAY_SETTING_ID)?),
+        Err(NvApiError::ApplicationNotFound(_)) => (false, None),
+        Err(e) => return Err(e),
+    };
+
+    if !is_read_only() {
+        remember_executable_status(executable, found.then_some(value == Some(SHADOWPLAY_DISABLED)), current);
+    }
+    Ok(value)
+}
+
+/// Hit/miss counters and size of the executable status cache
+pub fn executable_status_cache_stats() -> CacheStats {
+    let current = generation();
+    let cache = EXECUTABLE_STATUS_CACHE.read().unwrap();
+    let entries = cache.values()
+        .filter(|c| c.generation == current)
+        .count() as u64;
+    let approx_bytes = cache.keys()
+        .map(|key| (std::mem::size_of::<(String, CachedStatus)>() + key.len()) as u64)
+        .sum();
+
+    CacheStats {
+        name: "executable-status".to_string(),
+        hits: EXECUTABLE_STATUS_HITS.load(Ordering::Relaxed),
+        misses: EXECUTABLE_STATUS_MISSES.load(Ordering::Relaxed),
+        negative_hits: 0,
+        entries,
+        approx_bytes,
+    }
+}
+
+/// Drop every cached executable status
+pub fn clear_executable_status_cache() {
+    EXECUTABLE_STATUS_CACHE.write().unwrap().clear();
+}
+
+/// Apply a DWORD setting to every profile, skipping predefined ones unless asked.
+/// Failures on individual profiles are journaled as warnings and skipped.
+/// Returns the number of profiles updated.
+pub fn apply_to_all_profiles(setting_id: u32, value: u32, include_predefined: bool) -> Result<u32, NvApiError> {
+    let profiles = enumerate_profiles()?;
+    let mut updated: u32 = 0;
+
+    for profile in profiles.iter().filter(|p| include_predefined || !p.is_predefined) {
+        let result = find_profile_by_name(&profile.name)
+            .and_then(|handle| set_dword_setting(handle, setting_id, value));
+
+        match result {
+            Ok(()) => updated += 1,
+            Err(e) => journal::append(JournalCategory::Mutation, JournalSeverity::Warning, "setting.apply_failed", serde_json::json!({
+                "settingId": setting_id,
+                "profileName": profile.name,
+                "error": e.to_string(),
+            })),
         }
+    }
+
+    save_settings()?;
+    Ok(updated)
+}
+
+/// Apply a DWORD setting to every user-created profile
+pub fn apply_to_all_user_profiles(setting_id: u32, value: u32) -> Result<u32, NvApiError> {
+    apply_to_all_profiles(setting_id, value, false)
+}
+
+/// Where `find_or_create_application_profile` found an executable's profile
+#[cfg(target_os = "windows")]
+enum ApplicationProfile {
+    /// The executable already had an entry
+    Existing,
+    /// Added to a profile that holds the executable under another path
+    Reused(String),
+    /// Added to a newly created profile
+    Created(String),
+}
+
+/// Profile the driver uses for an executable, adding the executable to a profile
+/// if it has no entry. Nothing is saved; the caller saves after its own changes.
+#[cfg(target_os = "windows")]
+fn find_or_create_application_profile(executable: &str) -> Result<(NvDRSProfileHandle, ApplicationProfile), NvApiError> {
+    match find_application(executable) {
+        Ok((profile_handle, _app)) => Ok((profile_handle, ApplicationProfile::Existing)),
         Err(NvApiError::ApplicationNotFound(_)) => {
-            // Application not in DRS, need to create a profile for it
-            let profile_name = format!("Nvidiot - {}", executable);
-
-            // Try to find or create the profile
-            let profile_handle = match find_profile_by_name(&profile_name) {
-                Ok(handle) => handle,
-                Err(NvApiError::ProfileNotFound(_)) => {
-                    create_profile(&profile_name)?
-                }
-                Err(e) => return Err(e),
-            };
+            // A profile recorded in the manifest is found by its stored name rather
+            // than by rendering the name again, which may use a different template
+            let recorded = crate::manifest::get(executable)
+                .and_then(|entry| find_profile_by_name(&entry.profile_name).ok().map(|h| (entry.profile_name, h)));
 
-            // Add application to profile
-            super::applications::create_application(profile_handle, executable, &profile_name)?;
+            // Otherwise reuse a profile that already holds this executable under another
+            // path, so the same game from two launchers doesn't get two profiles
+            let (profile_name, profile_handle, reused) = match recorded {
+                Some((name, handle)) => (name, handle, true),
+                None => match find_reusable_profile(executable)? {
+                    Some(name) => {
+                        let handle = find_profile_by_name(&name)?;
+                        (name, handle, true)
+                    }
+                    None => {
+                        let name = profile_name_for(executable);
+                        let (handle, _created) = create_profile_if_not_exists(&name)?;
+                        (name, handle, false)
+                    }
+                },
+            };
 
-            // Set the blacklist setting
-            set_dword_setting(profile_handle, SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED)?;
-            save_settings()?;
+            // Add application to profile, offering the signer if one is already known
+            let signer = crate::metadata::cached_signer(executable);
+            super::applications::create_application(profile_handle, executable, &profile_name, signer.as_deref())?;
 
-            Ok(BlacklistResult {
-                success: true,
-                executable: executable.to_string(),
-                message: format!("Created profile '{}' and blacklisted application", profile_name),
-            })
+            let origin = if reused {
+                ApplicationProfile::Reused(profile_name)
+            } else {
+                ApplicationProfile::Created(profile_name)
+            };
+            Ok((profile_handle, origin))
         }
         Err(e) => Err(e),
     }
 }
 
+/// Blacklist an application (disable ShadowPlay for it)
+#[cfg(target_os = "windows")]
+pub fn blacklist_application(executable: &str) -> Result<BlacklistResult, NvApiError> {
+    let (profile_handle, origin) = find_or_create_application_profile(executable)?;
+
+    // Set the blacklist setting
+    set_dword_setting(profile_handle, SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED)?;
+    save_settings()?;
+
+    let (message, affected_profile_name) = match origin {
+        ApplicationProfile::Existing => (
+            "Application blacklisted successfully".to_string(),
+            get_profile_info_by_handle(profile_handle).ok().map(|p| p.name),
+        ),
+        ApplicationProfile::Reused(name) => (
+            format!("Added to existing profile '{}' and blacklisted application", name),
+            Some(name),
+        ),
+        ApplicationProfile::Created(name) => (
+            format!("Created profile '{}' and blacklisted application", name),
+            Some(name),
+        ),
+    };
+
+    Ok(BlacklistResult {
+        success: true,
+        executable: executable.to_string(),
+        message,
+        affected_profile_name,
+        scope: last_save_scope(),
+        previous_status: None,
+        warning: None,
+    })
+}
+
 #[cfg(not(target_os = "windows"))]
 pub fn blacklist_application(_executable: &str) -> Result<BlacklistResult, NvApiError> {
     Err(NvApiError::NotSupported)
 }
 
+/// Move an executable into a dedicated profile of its own and blacklist it
+/// there. A syncing tool that keeps rewriting the game's profile usually
+/// leaves a profile it doesn't know about alone. Returns the profile's name,
+/// or None if the executable is already in it.
+#[cfg(target_os = "windows")]
+pub fn blacklist_in_override_profile(executable: &str) -> Result<Option<String>, NvApiError> {
+    use super::applications::move_application_to_profile;
+    use super::session::reload_settings;
+
+    let profile_name = override_profile_name(executable);
+    let (current, _app) = find_application(executable)?;
+    if get_profile_info_by_handle(current)?.name == profile_name {
+        return Ok(None);
+    }
+
+    let profile_handle = move_application_to_profile(executable, &profile_name)?;
+    let applied = set_dword_setting(profile_handle, SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED)
+        .and_then(|_| save_settings());
+    if let Err(e) = applied {
+        let _ = reload_settings();
+        return Err(e);
+    }
+    Ok(Some(profile_name))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn blacklist_in_override_profile(_executable: &str) -> Result<Option<String>, NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Blacklist an application in the loaded session without saving, for callers
+/// that save a batch of changes at once
+#[cfg(target_os = "windows")]
+pub fn stage_blacklist(executable: &str) -> Result<(), NvApiError> {
+    let (profile_handle, _origin) = find_or_create_application_profile(executable)?;
+    set_dword_setting(profile_handle, SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn stage_blacklist(_executable: &str) -> Result<(), NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Unblacklist an application in the loaded session without saving. Returns
+/// false if it has no DRS entry, so there was nothing to change.
+#[cfg(target_os = "windows")]
+pub fn stage_unblacklist(executable: &str) -> Result<bool, NvApiError> {
+    match find_application(executable) {
+        Ok((profile_handle, _app)) => {
+            set_dword_setting(profile_handle, SHADOWPLAY_SETTING_ID, SHADOWPLAY_ENABLED)?;
+            Ok(true)
+        }
+        Err(NvApiError::ApplicationNotFound(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn stage_unblacklist(_executable: &str) -> Result<bool, NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
 /// Unblacklist an application (enable ShadowPlay for it)
 #[cfg(target_os = "windows")]
 pub fn unblacklist_application(executable: &str) -> Result<BlacklistResult, NvApiError> {
     match find_application(executable) {
         Ok((profile_handle, _app)) => {
+            let profile = get_profile_info_by_handle(profile_handle).ok();
+            // Read before the change: afterwards the current value is no longer the predefined one
+            let origin = profile.as_ref()
+                .and_then(|p| get_blacklist_origin(profile_handle, p.is_predefined).ok().flatten());
+
             // Set the ShadowPlay setting to enabled
             set_dword_setting(profile_handle, SHADOWPLAY_SETTING_ID, SHADOWPLAY_ENABLED)?;
             save_settings()?;
 
+            let profile_name = profile.map(|p| p.name);
+            let (message, warning) = match (origin, &profile_name) {
+                (Some(BlacklistOrigin::Nvidia), Some(name)) => (
+                    format!(
+                        "Application unblacklisted, but NVIDIA's predefined profile '{}' disables capture and a driver \
+                         update may restore it; a separate profile for just this executable overrides it durably",
+                        name
+                    ),
+                    Some(BlacklistWarning::NvidiaPredefined { profile_name: name.clone() }),
+                ),
+                _ => ("Application unblacklisted successfully".to_string(), None),
+            };
+
             Ok(BlacklistResult {
                 success: true,
                 executable: executable.to_string(),
-                message: "Application unblacklisted successfully".to_string(),
+                message,
+                affected_profile_name: profile_name,
+                scope: last_save_scope(),
+                previous_status: None,
+                warning,
             })
         }
         Err(NvApiError::ApplicationNotFound(_)) => {
@@ -164,6 +715,10 @@ pub fn unblacklist_application(executable: &str) -> Result<BlacklistResult, NvAp
                 success: false,
                 executable: executable.to_string(),
                 message: "Application not found in driver settings".to_string(),
+                affected_profile_name: None,
+                scope: DrsScope::Unknown,
+                previous_status: None,
+                warning: None,
             })
         }
         Err(e) => Err(e),
@@ -174,3 +729,374 @@ pub fn unblacklist_application(executable: &str) -> Result<BlacklistResult, NvAp
 pub fn unblacklist_application(_executable: &str) -> Result<BlacklistResult, NvApiError> {
     Err(NvApiError::NotSupported)
 }
+
+/// Whether ShadowPlay is currently disabled for an executable. Applications
+/// without a DRS entry follow the base profile.
+pub fn get_effective_shadowplay_status(executable: &str) -> Result<bool, NvApiError> {
+    match find_application(executable) {
+        Ok((profile_handle, _app)) => get_shadowplay_status(profile_handle),
+        Err(NvApiError::ApplicationNotFound(_)) => get_shadowplay_status(get_base_profile()?),
+        Err(e) => Err(e),
+    }
+}
+
+/// Blacklist an application if ShadowPlay is enabled for it, otherwise unblacklist it
+pub fn toggle_shadowplay(executable: &str) -> Result<BlacklistResult, NvApiError> {
+    let was_blacklisted = get_effective_shadowplay_status(executable)?;
+    let mut result = if was_blacklisted {
+        unblacklist_application(executable)?
+    } else {
+        blacklist_application(executable)?
+    };
+    result.previous_status = Some(was_blacklisted);
+    Ok(result)
+}
+
+/// Values the driver accepts for a DWORD setting. None if the driver can't
+/// enumerate them, in which case callers fall back to their own list.
+#[cfg(target_os = "windows")]
+pub fn available_setting_values(setting_id: u32) -> Result<Option<Vec<u32>>, NvApiError> {
+    let api = get_nvapi()?;
+    let Some(enum_values) = api.drs_enum_available_setting_values else {
+        return Ok(None);
+    };
+
+    // Over 400 KB, so it lives in a zeroed heap buffer rather than on the stack
+    let mut buffer = vec![0u32; std::mem::size_of::<NvdrsSettingValues>() / std::mem::size_of::<u32>()];
+    let values = buffer.as_mut_ptr() as *mut NvdrsSettingValues;
+
+    unsafe {
+        (*values).version = NVDRS_SETTING_VALUES_VER;
+        let mut count = NVAPI_SETTING_MAX_VALUES as u32;
+
+        let status = checked(enum_values(setting_id, &mut count, values));
+        if status == NVAPI_SETTING_NOT_FOUND {
+            return Ok(None);
+        }
+        if status != NVAPI_OK {
+            return Err(NvApiError::GetSettingFailed(status));
+        }
+        if (*values).setting_type != NvdrsSettingType::Dword as u32 {
+            return Ok(None);
+        }
+
+        let count = (count as usize).min(NVAPI_SETTING_MAX_VALUES);
+        Ok(Some((&(*values).setting_values)[..count].iter().map(|value| value[0]).collect()))
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn available_setting_values(_setting_id: u32) -> Result<Option<Vec<u32>>, NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Every setting the driver knows about, with its name, in the driver's order
+#[cfg(target_os = "windows")]
+pub fn available_settings() -> Result<Vec<AvailableSetting>, NvApiError> {
+    let api = get_nvapi()?;
+    let enum_ids = require_optional(api.drs_enum_available_setting_ids, NvApiFunction::DrsEnumAvailableSettingIds, "setting-catalog")?;
+    let get_name = require_optional(api.drs_get_setting_name_from_id, NvApiFunction::DrsGetSettingNameFromId, "setting-catalog")?;
+
+    let mut ids = vec![0u32; NVAPI_MAX_AVAILABLE_SETTING_IDS];
+    let mut count = ids.len() as u32;
+    unsafe {
+        let status = checked(enum_ids(ids.as_mut_ptr(), &mut count));
+        if status != NVAPI_OK {
+            return Err(NvApiError::GetSettingFailed(status));
+        }
+    }
+    ids.truncate((count as usize).min(NVAPI_MAX_AVAILABLE_SETTING_IDS));
+
+    let mut name = [0u16; NVAPI_UNICODE_STRING_MAX];
+    Ok(ids.into_iter()
+        .map(|setting_id| {
+            let status = unsafe { checked(get_name(setting_id, &mut name)) };
+            AvailableSetting {
+                setting_id,
+                name: (status == NVAPI_OK).then(|| wchar_to_string(&name)).filter(|n| !n.is_empty()),
+            }
+        })
+        .collect())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn available_settings() -> Result<Vec<AvailableSetting>, NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Driver value of a power management mode
+pub fn power_mode_value(mode: PowerMode) -> u32 {
+    match mode {
+        PowerMode::Adaptive => PREFERRED_PSTATE_ADAPTIVE,
+        PowerMode::PreferMaximumPerformance => PREFERRED_PSTATE_PREFER_MAX,
+        PowerMode::DriverControlled => PREFERRED_PSTATE_DRIVER_CONTROLLED,
+        PowerMode::PreferConsistentPerformance => PREFERRED_PSTATE_PREFER_CONSISTENT_PERFORMANCE,
+        PowerMode::PreferMinimumPower => PREFERRED_PSTATE_PREFER_MIN,
+        PowerMode::OptimalPower => PREFERRED_PSTATE_OPTIMAL_POWER,
+    }
+}
+
+/// Power management mode for a driver value, or None for values this version doesn't know
+pub fn power_mode_from_value(value: u32) -> Option<PowerMode> {
+    match value {
+        PREFERRED_PSTATE_ADAPTIVE => Some(PowerMode::Adaptive),
+        PREFERRED_PSTATE_PREFER_MAX => Some(PowerMode::PreferMaximumPerformance),
+        PREFERRED_PSTATE_DRIVER_CONTROLLED => Some(PowerMode::DriverControlled),
+        PREFERRED_PSTATE_PREFER_CONSISTENT_PERFORMANCE => Some(PowerMode::PreferConsistentPerformance),
+        PREFERRED_PSTATE_PREFER_MIN => Some(PowerMode::PreferMinimumPower),
+        PREFERRED_PSTATE_OPTIMAL_POWER => Some(PowerMode::OptimalPower),
+        _ => None,
+    }
+}
+
+fn power_mode_status(executable: &str, profile_handle: NvDRSProfileHandle) -> Result<PowerModeStatus, NvApiError> {
+    let value = find_dword_setting(profile_handle, POWER_MANAGEMENT_SETTING_ID)?.unwrap_or(PREFERRED_PSTATE_DEFAULT);
+
+    Ok(PowerModeStatus {
+        executable: executable.to_string(),
+        mode: power_mode_from_value(value),
+        value,
+        profile_name: get_profile_info_by_handle(profile_handle).ok().map(|p| p.name),
+        is_override: get_dword_override(profile_handle, POWER_MANAGEMENT_SETTING_ID)?.is_some(),
+    })
+}
+
+/// Power management mode the driver uses for an executable. Applications
+/// without a DRS entry follow the base profile.
+pub fn get_power_mode(executable: &str) -> Result<PowerModeStatus, NvApiError> {
+    let profile_handle = match find_application(executable) {
+        Ok((profile_handle, _app)) => profile_handle,
+        Err(NvApiError::ApplicationNotFound(_)) => get_base_profile()?,
+        Err(e) => return Err(e),
+    };
+    power_mode_status(executable, profile_handle)
+}
+
+/// Set an executable's power management mode, creating its profile entry if
+/// needed. The mode is checked against the values the driver reports when it can.
+#[cfg(target_os = "windows")]
+pub fn set_power_mode(executable: &str, mode: PowerMode) -> Result<PowerModeStatus, NvApiError> {
+    let value = power_mode_value(mode);
+    // Drivers that can't enumerate the values accept every mode in the enum
+    let accepted = available_setting_values(POWER_MANAGEMENT_SETTING_ID).ok().flatten().filter(|v| !v.is_empty());
+    if accepted.is_some_and(|accepted| !accepted.contains(&value)) {
+        return Err(NvApiError::InvalidSettingValue { setting_id: POWER_MANAGEMENT_SETTING_ID, value });
+    }
+
+    let (profile_handle, _origin) = find_or_create_application_profile(executable)?;
+    set_dword_setting(profile_handle, POWER_MANAGEMENT_SETTING_ID, value)?;
+    save_settings()?;
+
+    power_mode_status(executable, profile_handle)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn set_power_mode(_executable: &str, _mode: PowerMode) -> Result<PowerModeStatus, NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+/// Blacklist status of every process with a visible window. Each executable is
+/// looked up once and each profile's setting read once, however many processes share them.
+#[cfg(target_os = "windows")]
+pub fn get_running_process_blacklist_status() -> Result<Vec<ProcessBlacklistStatus>, NvApiError> {
+    // Fail up front rather than reporting every process as unknown
+    get_session()?;
+    let current = generation();
+
+    let mut by_executable: HashMap<String, (Option<bool>, Option<String>)> = HashMap::new();
+    let mut by_profile: HashMap<usize, (Option<bool>, Option<String>)> = HashMap::new();
+
+    let statuses = crate::windows::get_running_processes().into_iter()
+        .map(|process| {
+            let (is_blacklisted, profile_name) = by_executable
+                .entry(executable_key(&process.process_name))
+                .or_insert_with(|| match find_application(&process.process_name) {
+                    Ok((profile_handle, _)) => by_profile
+                        .entry(profile_handle as usize)
+                        .or_insert_with(|| (
+                            get_shadowplay_status(profile_handle).ok(),
+                            get_profile_info_by_handle(profile_handle).ok().map(|p| p.name),
+                        ))
+                        .clone(),
+                    Err(_) => (None, None),
+                })
+                .clone();
+
+            ProcessBlacklistStatus {
+                process_name: process.process_name,
+                process_id: process.process_id,
+                is_blacklisted,
+                profile_name,
+            }
+        })
+        .collect();
+
+    // Warm the point-query cache with what was just read
+    for (executable, (is_blacklisted, _)) in &by_executable {
+        if is_blacklisted.is_some() {
+            remember_executable_status(executable, *is_blacklisted, current);
+        }
+    }
+    Ok(statuses)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn get_running_process_blacklist_status() -> Result<Vec<ProcessBlacklistStatus>, NvApiError> {
+    Err(NvApiError::NotSupported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nvapi::ffi::{string_to_wchar, NvdrsBinarySetting, NVAPI_BINARY_DATA_MAX, NVAPI_UNICODE_STRING_MAX};
+
+    #[test]
+    fn long_strings_decode_whole() {
+        let text = "C:\\Program Files (x86)\\Some Publisher\\Some Game\\Binaries\\Win64\\game.exe";
+        let mut wsz_value = [0u16; NVAPI_UNICODE_STRING_MAX];
+        string_to_wchar(text, &mut wsz_value);
+        let value = NvdrsSettingValue { wsz_value };
+        assert_eq!(
+            decode_value(NvdrsSettingType::WString as u32, &value),
+            SettingValue::String { value: text.to_string() },
+        );
+    }
+
+    #[test]
+    fn binary_values_keep_their_reported_length() {
+        let mut value_data = [0u8; NVAPI_BINARY_DATA_MAX];
+        value_data[..3].copy_from_slice(&[7, 0, 0]);
+        let value = NvdrsSettingValue { binary_value: NvdrsBinarySetting { value_length: 3, value_data } };
+        // Trailing zeros within the length are part of the value
+        assert_eq!(decode_value(NvdrsSettingType::Binary as u32, &value), SettingValue::Binary { bytes: vec![7, 0, 0] });
+
+        let value = NvdrsSettingValue { binary_value: NvdrsBinarySetting { value_length: u32::MAX, value_data } };
+        match decode_value(NvdrsSettingType::Binary as u32, &value) {
+            SettingValue::Binary { bytes } => assert_eq!(bytes.len(), NVAPI_BINARY_DATA_MAX),
+            other => panic!("decoded as {:?}", other),
+        }
+    }
+
+    #[test]
+    fn setting_struct_matches_the_header_size() {
+        // sizeof(NVDRS_SETTING_V1) in the NVAPI headers
+        assert_eq!(NVDRS_SETTING_VER, 0x13020);
+    }
+}
+
+#[cfg(all(test, target_os = "windows"))]
+mod mock_tests {
+    use super::*;
+    use crate::nvapi::applications::get_application_info;
+    use crate::nvapi::mock::{self, Profile, Store};
+
+    fn origin_of(executable: &str) -> Option<BlacklistOrigin> {
+        get_application_info(executable).unwrap().blacklist_origin
+    }
+
+    #[test]
+    fn capture_disabled_in_nvidia_data_is_nvidia_origin() {
+        let _serial = mock::serial();
+        mock::install(Store::default().with_profile(
+            Profile::new("Origin Anticheat")
+                .with_application("origin-anticheat.exe")
+                .with_dword(SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED)
+                .predefined(),
+        ));
+        assert_eq!(origin_of("origin-anticheat.exe"), Some(BlacklistOrigin::Nvidia));
+    }
+
+    #[test]
+    fn a_blacklist_on_a_predefined_profile_is_a_user_override() {
+        let _serial = mock::serial();
+        mock::install(Store::default().with_profile(
+            Profile::new("Origin Shipped").with_application("origin-shipped.exe").predefined(),
+        ));
+        assert_eq!(origin_of("origin-shipped.exe"), None);
+
+        blacklist_application("origin-shipped.exe").unwrap();
+        assert_eq!(origin_of("origin-shipped.exe"), Some(BlacklistOrigin::UserOverride));
+    }
+
+    #[test]
+    fn a_blacklist_on_a_user_profile_is_user_origin() {
+        let _serial = mock::serial();
+        mock::install(Store::default().with_profile(Profile::new("Origin Own").with_application("origin-own.exe")));
+
+        blacklist_application("origin-own.exe").unwrap();
+        assert_eq!(origin_of("origin-own.exe"), Some(BlacklistOrigin::User));
+    }
+
+    #[test]
+    fn unblacklisting_an_nvidia_disabled_title_warns() {
+        let _serial = mock::serial();
+        mock::install(Store::default()
+            .with_profile(
+                Profile::new("Origin Anticheat")
+                    .with_application("origin-anticheat.exe")
+                    .with_dword(SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED)
+                    .predefined(),
+            )
+            .with_profile(
+                Profile::new("Origin Own")
+                    .with_application("origin-own.exe")
+                    .with_dword(SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED),
+            ));
+
+        let result = unblacklist_application("origin-anticheat.exe").unwrap();
+        assert!(result.success);
+        assert_eq!(result.warning, Some(BlacklistWarning::NvidiaPredefined { profile_name: "Origin Anticheat".to_string() }));
+        assert!(result.message.contains("predefined profile 'Origin Anticheat'"), "{}", result.message);
+        assert_eq!(mock::disk().profile("Origin Anticheat").unwrap().dword(SHADOWPLAY_SETTING_ID), Some(SHADOWPLAY_ENABLED));
+
+        let result = unblacklist_application("origin-own.exe").unwrap();
+        assert_eq!(result.warning, None);
+        assert_eq!(result.message, "Application unblacklisted successfully");
+    }
+
+    #[test]
+    fn an_override_profile_takes_the_executable_out_of_its_shared_profile() {
+        let _serial = mock::serial();
+        mock::install(Store::default().with_profile(
+            Profile::new("Synced Game")
+                .with_application("synced-game.exe")
+                .with_application("synced-launcher.exe")
+                .predefined(),
+        ));
+
+        let profile_name = blacklist_in_override_profile("synced-game.exe").unwrap().expect("moved");
+        assert_eq!(profile_name, "Nvidiot override - synced-game.exe");
+
+        let disk = mock::disk();
+        let shared = disk.profile("Synced Game").unwrap();
+        assert_eq!(shared.applications.iter().map(|a| a.name.as_str()).collect::<Vec<_>>(), ["synced-launcher.exe"]);
+        assert_eq!(shared.dword(SHADOWPLAY_SETTING_ID), None);
+        let own = disk.profile(&profile_name).unwrap();
+        assert_eq!(own.applications.iter().map(|a| a.name.as_str()).collect::<Vec<_>>(), ["synced-game.exe"]);
+        assert_eq!(own.dword(SHADOWPLAY_SETTING_ID), Some(SHADOWPLAY_DISABLED));
+        assert_eq!(get_application_info("synced-game.exe").unwrap().profile_name, profile_name);
+
+        // Escalating again leaves the store alone
+        let saves = mock::saves();
+        assert_eq!(blacklist_in_override_profile("synced-game.exe").unwrap(), None);
+        assert_eq!(mock::saves(), saves);
+    }
+    #[test]
+    fn every_shadowplay_write_is_remembered_for_revert_loop_detection() {
+        use crate::manifest::now_ms;
+        use crate::revertloop::awaiting_check;
+
+        let _serial = mock::serial();
+        mock::install(Store::default()
+            .with_profile(Profile::new("Loop Staged").with_application("loop-staged.exe"))
+            .with_profile(Profile::new("Loop Shared").with_application("loop-a.exe").with_application("loop-b.exe")));
+
+        stage_blacklist("loop-staged.exe").unwrap();
+        set_dword_setting(find_profile_by_name("Loop Shared").unwrap(), SHADOWPLAY_SETTING_ID, SHADOWPLAY_ENABLED).unwrap();
+
+        let pending = awaiting_check(60 * 1000, now_ms());
+        assert!(pending.contains(&("loop-staged.exe".to_string(), true)));
+        assert!(pending.contains(&("loop-a.exe".to_string(), false)));
+        assert!(pending.contains(&("loop-b.exe".to_string(), false)));
+    }
+}
@@ -0,0 +1,229 @@
+//! What statuses from DRS reads mean
+//!
+//! Some drivers answer reads of the base profile or a freshly created profile
+//! with END_ENUMERATION or a bare NVAPI_ERROR where the headers document
+//! SETTING_NOT_FOUND, and those used to reach the UI as failures. Each read
+//! call site classifies its status through the tables here instead: absent
+//! means nothing is stored and the default applies, failed is a genuine error.
+//! A status in neither table is journaled once, counted for diagnostics, and
+//! treated as absent, so an unfamiliar driver shows defaults rather than errors.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use super::error::{
+    NVAPI_API_NOT_INITIALIZED, NVAPI_END_ENUMERATION, NVAPI_ERROR, NVAPI_INCOMPATIBLE_STRUCT_VERSION,
+    NVAPI_INVALID_ARGUMENT, NVAPI_INVALID_HANDLE, NVAPI_LIBRARY_NOT_FOUND, NVAPI_NO_IMPLEMENTATION,
+    NVAPI_NVIDIA_DEVICE_NOT_FOUND, NVAPI_OK, NVAPI_PROFILE_NOT_FOUND, NVAPI_SETTING_NOT_FOUND,
+};
+use super::types::UnclassifiedStatus;
+use crate::journal::{self, JournalCategory, JournalSeverity};
+
+/// DRS read calls whose statuses are classified
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReadCall {
+    GetSetting,
+    EnumSettings,
+    GetProfileInfo,
+}
+
+impl ReadCall {
+    fn name(self) -> &'static str {
+        match self {
+            ReadCall::GetSetting => "NvAPI_DRS_GetSetting",
+            ReadCall::EnumSettings => "NvAPI_DRS_EnumSettings",
+            ReadCall::GetProfileInfo => "NvAPI_DRS_GetProfileInfo",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadStatus {
+    Present,
+    Absent,
+    Failed,
+}
+
+/// Statuses that mean the call itself went wrong, whichever read it was
+const FAILURE_STATUSES: &[i32] = &[
+    NVAPI_LIBRARY_NOT_FOUND,
+    NVAPI_NO_IMPLEMENTATION,
+    NVAPI_API_NOT_INITIALIZED,
+    NVAPI_INVALID_ARGUMENT,
+    NVAPI_NVIDIA_DEVICE_NOT_FOUND,
+    NVAPI_INVALID_HANDLE,
+    NVAPI_INCOMPATIBLE_STRUCT_VERSION,
+];
+
+/// Statuses that mean "nothing stored here" for each read
+const ABSENT_STATUSES: &[(ReadCall, &[i32])] = &[
+    // END_ENUMERATION and NVAPI_ERROR come back for unset settings on the base
+    // profile and on profiles created in the current session
+    (ReadCall::GetSetting, &[NVAPI_SETTING_NOT_FOUND, NVAPI_END_ENUMERATION, NVAPI_ERROR]),
+    (ReadCall::EnumSettings, &[NVAPI_END_ENUMERATION, NVAPI_SETTING_NOT_FOUND]),
+    (ReadCall::GetProfileInfo, &[NVAPI_PROFILE_NOT_FOUND, NVAPI_END_ENUMERATION]),
+];
+
+/// Occurrences of statuses missing from both tables, by call
+static UNCLASSIFIED: Lazy<Mutex<HashMap<(ReadCall, i32), u64>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Classify the status of a DRS read
+pub fn classify(call: ReadCall, status: i32) -> ReadStatus {
+    if status == NVAPI_OK {
+        return ReadStatus::Present;
+    }
+    if FAILURE_STATUSES.contains(&status) {
+        return ReadStatus::Failed;
+    }
+    let absent = ABSENT_STATUSES.iter()
+        .any(|(table_call, statuses)| *table_call == call && statuses.contains(&status));
+    if absent {
+        return ReadStatus::Absent;
+    }
+
+    let mut unclassified = UNCLASSIFIED.lock().unwrap();
+    let count = unclassified.entry((call, status)).or_insert(0);
+    if *count == 0 {
+        journal::append(JournalCategory::Watcher, JournalSeverity::Warning, "driver.unclassified_status", serde_json::json!({
+            "status": status,
+            "call": call.name(),
+        }));
+    }
+    *count += 1;
+    ReadStatus::Absent
+}
+
+/// Unclassified statuses seen so far, most frequent first
+pub fn unclassified_statuses() -> Vec<UnclassifiedStatus> {
+    let mut statuses: Vec<UnclassifiedStatus> = UNCLASSIFIED.lock().unwrap().iter()
+        .map(|(&(call, status), &count)| UnclassifiedStatus { function: call.name().to_string(), status, count })
+        .collect();
+    statuses.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.function.cmp(&b.function)));
+    statuses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ok_is_present_and_call_failures_fail_for_every_read() {
+        for call in [ReadCall::GetSetting, ReadCall::EnumSettings, ReadCall::GetProfileInfo] {
+            assert_eq!(classify(call, NVAPI_OK), ReadStatus::Present);
+            assert_eq!(classify(call, NVAPI_INVALID_HANDLE), ReadStatus::Failed);
+            assert_eq!(classify(call, NVAPI_INCOMPATIBLE_STRUCT_VERSION), ReadStatus::Failed);
+        }
+    }
+
+    #[test]
+    fn absent_statuses_depend_on_the_call() {
+        assert_eq!(classify(ReadCall::GetSetting, NVAPI_SETTING_NOT_FOUND), ReadStatus::Absent);
+        assert_eq!(classify(ReadCall::GetSetting, NVAPI_END_ENUMERATION), ReadStatus::Absent);
+        assert_eq!(classify(ReadCall::GetSetting, NVAPI_ERROR), ReadStatus::Absent);
+        assert_eq!(classify(ReadCall::EnumSettings, NVAPI_END_ENUMERATION), ReadStatus::Absent);
+        assert_eq!(classify(ReadCall::GetProfileInfo, NVAPI_PROFILE_NOT_FOUND), ReadStatus::Absent);
+    }
+
+    #[test]
+    fn unfamiliar_statuses_count_as_absent_and_are_tallied() {
+        // Far outside the NVAPI range, so no other test touches this entry
+        let status = -90_001;
+        assert_eq!(classify(ReadCall::EnumSettings, status), ReadStatus::Absent);
+        assert_eq!(classify(ReadCall::EnumSettings, status), ReadStatus::Absent);
+
+        let tallied = unclassified_statuses().into_iter()
+            .find(|s| s.status == status)
+            .expect("tallied");
+        assert_eq!(tallied.function, "NvAPI_DRS_EnumSettings");
+        assert_eq!(tallied.count, 2);
+    }
+}
+
+#[cfg(all(test, target_os = "windows"))]
+mod mock_tests {
+    use super::*;
+    use crate::nvapi::error::NvApiError;
+    use crate::nvapi::ffi::{NvApiFunction, NvDRSProfileHandle};
+    use crate::nvapi::mock::{self, Profile, Store};
+    use crate::nvapi::profiles::{find_profile_by_name, get_profile_info_by_handle};
+    use crate::nvapi::settings::{find_dword_setting, get_all_settings, get_dword_override};
+
+    const SETTING_ID: u32 = 0x10A0_0001;
+
+    /// A profile storing `SETTING_ID`, with `function` answering `status` from now on
+    fn profile_answering(function: NvApiFunction, status: i32) -> NvDRSProfileHandle {
+        mock::install(Store::default().with_profile(Profile::new("Status Reads").with_dword(SETTING_ID, 1)));
+        let profile = find_profile_by_name("Status Reads").unwrap();
+        mock::inject_status(function, status);
+        profile
+    }
+
+    #[test]
+    fn get_setting_absent_statuses_read_as_nothing_stored() {
+        let _serial = mock::serial();
+        for status in [NVAPI_SETTING_NOT_FOUND, NVAPI_END_ENUMERATION, NVAPI_ERROR] {
+            let profile = profile_answering(NvApiFunction::DrsGetSetting, status);
+            assert_eq!(find_dword_setting(profile, SETTING_ID).unwrap(), None, "status {}", status);
+            assert_eq!(get_dword_override(profile, SETTING_ID).unwrap(), None, "status {}", status);
+        }
+    }
+
+    #[test]
+    fn get_setting_call_failures_are_errors() {
+        let _serial = mock::serial();
+        for status in [NVAPI_INCOMPATIBLE_STRUCT_VERSION, NVAPI_INVALID_ARGUMENT, NVAPI_NO_IMPLEMENTATION] {
+            let profile = profile_answering(NvApiFunction::DrsGetSetting, status);
+            assert!(
+                matches!(find_dword_setting(profile, SETTING_ID), Err(NvApiError::GetSettingFailed(s)) if s == status),
+                "status {}", status
+            );
+            assert!(matches!(get_dword_override(profile, SETTING_ID), Err(NvApiError::GetSettingFailed(_))));
+        }
+    }
+
+    #[test]
+    fn enum_settings_absent_statuses_read_as_an_empty_profile() {
+        let _serial = mock::serial();
+        for status in [NVAPI_END_ENUMERATION, NVAPI_SETTING_NOT_FOUND] {
+            let profile = profile_answering(NvApiFunction::DrsEnumSettings, status);
+            assert!(get_all_settings(profile).unwrap().is_empty(), "status {}", status);
+        }
+
+        let profile = profile_answering(NvApiFunction::DrsEnumSettings, NVAPI_INCOMPATIBLE_STRUCT_VERSION);
+        assert!(matches!(
+            get_all_settings(profile),
+            Err(NvApiError::GetSettingFailed(NVAPI_INCOMPATIBLE_STRUCT_VERSION)),
+        ));
+    }
+
+    #[test]
+    fn profile_info_absent_statuses_report_the_profile_as_gone() {
+        let _serial = mock::serial();
+        for status in [NVAPI_PROFILE_NOT_FOUND, NVAPI_END_ENUMERATION] {
+            let profile = profile_answering(NvApiFunction::DrsGetProfileInfo, status);
+            assert!(matches!(get_profile_info_by_handle(profile), Err(NvApiError::ProfileNotFound(_))), "status {}", status);
+        }
+
+        let profile = profile_answering(NvApiFunction::DrsGetProfileInfo, NVAPI_INVALID_ARGUMENT);
+        assert!(matches!(get_profile_info_by_handle(profile), Err(NvApiError::NvApiStatus(NVAPI_INVALID_ARGUMENT))));
+    }
+
+    #[test]
+    fn an_unfamiliar_get_setting_status_reads_as_unset_and_is_tallied() {
+        let _serial = mock::serial();
+        mock::install(Store::default().with_profile(Profile::new("Status Reads").with_dword(SETTING_ID, 1)));
+        let profile = find_profile_by_name("Status Reads").unwrap();
+        assert_eq!(find_dword_setting(profile, SETTING_ID).unwrap(), Some(1));
+
+        // Within NVAPI's range, so the driver-change detector ignores it, but in neither table
+        let status = -201;
+        mock::inject_status(NvApiFunction::DrsGetSetting, status);
+        assert_eq!(find_dword_setting(profile, SETTING_ID).unwrap(), None);
+
+        let tallied = unclassified_statuses().into_iter()
+            .find(|s| s.status == status)
+            .expect("tallied");
+        assert_eq!(tallied.function, "NvAPI_DRS_GetSetting");
+        assert!(tallied.count >= 1);
+    }
+}
@@ -7,6 +7,71 @@ pub struct DrsProfile {
     pub name: String,
     pub is_predefined: bool,
     pub application_count: u32,
+    pub settings_count: u32,
+    /// ShadowPlay status, only populated when requested
+    pub is_blacklisted: Option<bool>,
+    /// Predefined profiles ship with the driver and can't be deleted
+    pub user_can_delete: bool,
+    pub user_can_modify: bool,
+    /// Created by Nvidiot: named with the default prefix or tracked in the manifest
+    pub user_created_by_nvidiot: bool,
+    /// GPU product lines the profile applies to, decoded from its gpuSupport mask
+    pub gpu_support: Vec<GpuProductLine>,
+    /// Whether the mask covers every installed GPU; None when they can't be detected
+    pub applies_to_current_gpu: Option<bool>,
+}
+
+/// GPU product line, as used by a profile's gpuSupport mask
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GpuProductLine {
+    Geforce,
+    Quadro,
+    Nvs,
+}
+
+/// A profile with minor inconsistencies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileWarning {
+    pub profile_name: String,
+    pub issues: Vec<String>,
+}
+
+/// A profile with at least one integrity error; issues include any warnings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileError {
+    pub profile_name: String,
+    pub issues: Vec<String>,
+}
+
+/// Result of checking every user profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationReport {
+    pub ok_profiles: u32,
+    pub warning_profiles: Vec<ProfileWarning>,
+    pub error_profiles: Vec<ProfileError>,
+}
+
+/// Field to sort profiles by
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProfileSortField {
+    Name,
+    ApplicationCount,
+    SettingsCount,
+}
+
+/// Field to sort a profile's applications by
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ApplicationSortField {
+    ExecutableName,
+    FriendlyName,
+    BlacklistStatus,
+    IsPredefined,
 }
 
 /// An application registered in a DRS profile
@@ -18,6 +83,147 @@ pub struct DrsApplication {
     pub profile_name: String,
     pub is_predefined: bool,
     pub is_blacklisted: bool,
+    /// Who disabled ShadowPlay for the entry's profile; None if it isn't disabled or wasn't read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blacklist_origin: Option<BlacklistOrigin>,
+    /// When Nvidiot created and last changed the entry; None for unmanaged entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// How DRS matches the entry; None when the driver lacks the v4 fields or it wasn't read.
+    /// Only the get_application_info command reads it: enumerations and lookups leave it None,
+    /// since reading it takes a separate v4 lookup per entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub match_mode: Option<ApplicationMatchMode>,
+}
+
+/// What an application entry is matched on besides its executable name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ApplicationMatchMode {
+    /// Executable name only
+    Name,
+    /// Name plus a file that must exist in the same folder
+    FileInFolder,
+    /// Name plus the process command line
+    CommandLine,
+    /// A Metro/UWP package
+    Metro,
+}
+
+/// Why an entry asked to match on its signer was matched by name instead
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SignerMatchFallback {
+    /// The driver rejects NVDRS_APPLICATION_V4, so no match field can be set
+    MatchFieldsUnsupported,
+    /// The driver accepts the v4 match fields, but none of them holds a signer
+    NoSignerField,
+}
+
+/// How a newly created application entry is matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApplicationCreated {
+    pub match_mode: ApplicationMatchMode,
+    /// Set when a signer was given but the entry fell back to name matching
+    pub signer_fallback: Option<SignerMatchFallback>,
+}
+
+/// Who disabled ShadowPlay on a profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BlacklistOrigin {
+    /// Shipped that way in an NVIDIA predefined profile
+    Nvidia,
+    /// Set by a user or tool on top of an NVIDIA predefined profile
+    UserOverride,
+    /// Set on a user-created profile
+    User,
+}
+
+/// Caveat attached to an otherwise successful blacklist operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BlacklistWarning {
+    /// Capture was re-enabled on a profile NVIDIA ships with it disabled. Driver
+    /// updates may restore the predefined value; a user profile holding just this
+    /// executable overrides it durably.
+    NvidiaPredefined { profile_name: String },
+}
+
+/// What last changed a managed entry
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ModifiedBy {
+    #[default]
+    Manual,
+    Rule { name: String },
+    Import,
+    Watchdog,
+    /// Applied from the "fix my game" wizard
+    Wizard,
+}
+
+/// Creation and modification history of a managed entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Provenance {
+    pub created_at_ms: u64,
+    pub last_modified_at_ms: u64,
+    pub last_modified_by: ModifiedBy,
+}
+
+/// Field to sort managed entries by
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ManagedSortField {
+    Created,
+    Modified,
+}
+
+/// A window rectangle in logical (96 DPI) pixels
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowRect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+/// A setting stored in a DRS profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrsSetting {
+    pub setting_id: u32,
+    pub name: String,
+    pub setting_type: u32,
+    /// Current value for DWORD settings
+    pub dword_value: Option<u32>,
+    /// Whether the current value is the driver's predefined value
+    pub is_current_predefined: bool,
+    /// Current value decoded by the setting's type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<SettingValue>,
+}
+
+/// A setting the driver knows about, whether or not any profile stores it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailableSetting {
+    pub setting_id: u32,
+    /// None when the driver has no name for the ID
+    pub name: Option<String>,
+}
+
+/// A setting value decoded by its type
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SettingValue {
+    Dword { value: u32 },
+    /// The bytes within the length the driver reports
+    Binary { bytes: Vec<u8> },
+    String { value: String },
 }
 
 /// A running process on the system
@@ -28,9 +234,138 @@ pub struct RunningProcess {
     pub window_title: String,
     pub process_id: u32,
     pub executable_path: Option<String>,
+    /// Signing publisher, only when a signature check is already cached
+    pub signer: Option<String>,
+    pub architecture: Option<ProcessArchitecture>,
     pub has_drs_profile: bool,
     pub profile_name: Option<String>,
     pub is_blacklisted: Option<bool>,
+    pub dpi: Option<u32>,
+    /// Working set in MB, only populated when requested
+    pub memory_usage_mb: Option<u32>,
+    /// CPU usage across all cores, only populated when requested
+    pub cpu_usage_percent: Option<f32>,
+    /// Whether the process has work on an NVIDIA GPU; None unless requested or if NVML is unavailable
+    pub uses_nvidia_gpu: Option<bool>,
+    /// GPU memory used by the process, when NVML can attribute it
+    pub gpu_memory_mb: Option<u64>,
+    /// Whether Nvidiot can query the process; elevated processes are listed with what ToolHelp reports
+    pub accessibility: ProcessAccessibility,
+}
+
+/// Whether a process can be opened for querying
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ProcessAccessibility {
+    Accessible,
+    /// The process runs elevated (or protected) and Nvidiot doesn't
+    RequiresElevation,
+    NotFound,
+}
+
+/// Binary format a process runs as
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProcessArchitecture {
+    X86,
+    X64,
+    Arm64,
+    Arm32,
+}
+
+/// A program launched when the user signs in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupProgram {
+    pub name: String,
+    /// Executable from the Run command line, or the file in the Startup folder
+    pub executable_path: String,
+    /// Run key the entry came from, or the Startup folder path
+    pub registry_key: String,
+}
+
+/// A program registered in the Windows uninstall keys
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstalledProgram {
+    pub display_name: String,
+    pub install_location: Option<String>,
+    pub display_icon: Option<String>,
+    /// Candidate game executables found under the install location
+    pub executables: Vec<String>,
+    /// Installed under a known game library folder (Steam, Epic, GOG, ...)
+    pub is_likely_game: bool,
+    /// Executables that already have a DRS entry; None when the scan skipped the DRS join
+    pub in_drs: Option<Vec<String>>,
+}
+
+/// Payload of the installed-scan-progress event
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanProgress {
+    /// Install folders walked so far
+    pub scanned: usize,
+    pub total: usize,
+}
+
+/// What a .url shortcut launches
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ShortcutTarget {
+    Steam { app_id: u32 },
+    Epic {
+        app_name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        catalog_item_id: Option<String>,
+    },
+    /// file:// URL to a local executable
+    File { path: String },
+    Other { url: String },
+}
+
+/// How a dropped path led to the executable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ResolutionPath {
+    /// The path was the executable
+    Direct,
+    ShellLink,
+    SteamManifest,
+    EpicManifest,
+    /// A .url pointing straight at a local file
+    FileUrl,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolvedExecutable {
+    /// The path that was dropped
+    pub source: String,
+    pub resolution: ResolutionPath,
+    /// Full path of the game executable
+    pub executable_path: String,
+    /// Name DRS matches on
+    pub executable: String,
+    /// Parsed shortcut target, for .url files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<ShortcutTarget>,
+}
+
+/// A dropped path and the result of blacklisting what it resolved to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AddedExecutable {
+    pub resolved: ResolvedExecutable,
+    pub result: BlacklistResult,
+}
+
+/// Base filter for the running process list
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum FilterType {
+    /// Hide the built-in list of shell processes
+    Default,
+    IncludeAll,
+    /// Also hide common system tools and shell window classes
+    ExcludeSystem,
 }
 
 /// The currently focused application
@@ -43,6 +378,45 @@ pub struct FocusApplication {
     pub is_in_drs: bool,
     pub profile_name: Option<String>,
     pub is_blacklisted: Option<bool>,
+    pub dpi: Option<u32>,
+    pub window_rect: Option<WindowRect>,
+    /// Device name of the monitor the window is on, e.g. `\\.\DISPLAY1`
+    pub monitor_device_name: Option<String>,
+}
+
+/// A connected monitor
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    pub device_name: String,
+    pub is_primary: bool,
+    /// Monitor bounds in logical pixels
+    pub rect: WindowRect,
+}
+
+/// Blacklist status of a running process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessBlacklistStatus {
+    pub process_name: String,
+    pub process_id: u32,
+    /// None when the executable has no DRS entry or its status couldn't be read
+    pub is_blacklisted: Option<bool>,
+    pub profile_name: Option<String>,
+}
+
+/// Focused application with resource usage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusApplicationExtended {
+    #[serde(flatten)]
+    pub focus: FocusApplication,
+    /// CPU usage across all cores over one sample interval
+    pub cpu_usage_percent: Option<f32>,
+    /// GPU memory used by the process, when NVML can attribute it
+    pub gpu_memory_mb: Option<u64>,
+    /// Dedicated video memory in use on the first GPU by every process together
+    pub device_memory_used_mb: Option<u32>,
 }
 
 /// Result of a blacklist operation
@@ -52,6 +426,60 @@ pub struct BlacklistResult {
     pub success: bool,
     pub executable: String,
     pub message: String,
+    /// DRS profile that was modified, if any
+    pub affected_profile_name: Option<String>,
+    /// Which store the save landed in
+    pub scope: DrsScope,
+    /// Whether the application was blacklisted before the change, when the caller checked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_status: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<BlacklistWarning>,
+}
+
+/// Where a point blacklist query got its answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StatusSource {
+    Cache,
+    Live,
+}
+
+/// Answer to `is_blacklisted` for one executable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlacklistQuery {
+    /// None if the executable has no DRS entry
+    pub blacklisted: Option<bool>,
+    pub source: StatusSource,
+    /// Session generation the answer was read in
+    pub generation: u64,
+}
+
+/// Driver power management mode for an application (PREFERRED_PSTATE)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PowerMode {
+    Adaptive,
+    PreferMaximumPerformance,
+    DriverControlled,
+    PreferConsistentPerformance,
+    PreferMinimumPower,
+    OptimalPower,
+}
+
+/// Power management mode in effect for an executable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PowerModeStatus {
+    pub executable: String,
+    /// None if the driver reports a value this version doesn't know
+    pub mode: Option<PowerMode>,
+    pub value: u32,
+    /// Profile the value applies through; the base profile when the executable has no entry
+    pub profile_name: Option<String>,
+    /// Whether the profile sets the value itself rather than inheriting it
+    pub is_override: bool,
 }
 
 /// NVAPI connection status
@@ -60,4 +488,314 @@ pub struct BlacklistResult {
 pub struct NvApiStatus {
     pub available: bool,
     pub error: Option<String>,
+    pub is_windows_game_mode_enabled: bool,
+}
+
+/// Windows' own gaming features that can interact with ShadowPlay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowsGamingState {
+    pub game_mode_enabled: bool,
+    pub game_dvr_enabled: bool,
+    /// Set when Game DVR and ShadowPlay are both capturing
+    pub warning: Option<String>,
+}
+
+/// Whether a single NVAPI function ID resolved via QueryInterface
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NvApiFunctionProbe {
+    pub id: u32,
+    pub name: String,
+    pub resolved: bool,
+}
+
+/// Results of probing the NVAPI interface table
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NvApiProbe {
+    /// When NVAPI was first loaded (milliseconds since the Unix epoch)
+    pub loaded_at_ms: Option<u64>,
+    pub load_attempts: u32,
+    pub reinitialized: bool,
+    pub functions: Vec<NvApiFunctionProbe>,
+}
+
+/// Whether a driver-dependent feature can be used
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureAvailability {
+    pub feature: String,
+    pub available: bool,
+    /// NVAPI functions the driver doesn't export that the feature needs
+    pub missing_functions: Vec<String>,
+}
+
+/// Whether a response was read from DRS or (partly) served from a cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FreshnessSource {
+    Cache,
+    Live,
+}
+
+/// How current a snapshot is, for telling backend staleness from frontend staleness
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Freshness {
+    pub session_generation: u64,
+    /// When the oldest cache that contributed was built; None for live reads
+    pub cache_built_at_ms: Option<u64>,
+    pub cache_age_ms: Option<u64>,
+    pub source: FreshnessSource,
+}
+
+/// Hit/miss counters for an in-memory cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub name: String,
+    pub hits: u64,
+    pub misses: u64,
+    /// Lookups answered from a remembered miss; only negative caches count these
+    pub negative_hits: u64,
+    pub entries: u64,
+    /// Rough memory held by the cache: per-entry struct size plus owned string bytes
+    pub approx_bytes: u64,
+}
+
+/// Diagnostics report for support requests
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostics {
+    pub nvapi: NvApiStatus,
+    pub probe: NvApiProbe,
+    pub session_generation: u64,
+    /// Largest first
+    pub caches: Vec<CacheStats>,
+    pub total_cache_bytes: u64,
+    /// DRS read statuses not yet classified as absent or failed, most frequent first
+    pub unclassified_statuses: Vec<UnclassifiedStatus>,
+    /// Supervised background tasks still running, oldest first
+    pub background_tasks: Vec<String>,
+}
+
+/// One executable checked after a batch save
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifiedEntry {
+    pub executable: String,
+    pub expected: u32,
+    /// Value stored in the executable's profile; None if nothing is stored or it has no DRS entry
+    pub actual: Option<u32>,
+    pub verified: bool,
+    /// Set when the value couldn't be read
+    pub error: Option<String>,
+}
+
+/// Result of checking every executable a batch touched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchVerification {
+    pub verified_at_ms: u64,
+    pub mismatches: u32,
+    pub entries: Vec<VerifiedEntry>,
+}
+
+/// A DRS read status seen in the wild that isn't classified yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnclassifiedStatus {
+    pub function: String,
+    pub status: i32,
+    pub count: u64,
+}
+
+/// Outcome of one executable matching strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionStep {
+    pub strategy: String,
+    pub matched: bool,
+    pub profile_name: Option<String>,
+    pub detail: String,
+}
+
+/// Trace of how an executable was (or wasn't) matched to DRS and running processes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResolutionTrace {
+    pub executable: String,
+    pub steps: Vec<ResolutionStep>,
+    pub resolved_profile: Option<String>,
+    pub running_process_ids: Vec<u32>,
+}
+
+/// DRS state found on first launch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FirstRunScan {
+    /// Blacklisted applications not managed by Nvidiot
+    pub external: Vec<DrsApplication>,
+    /// Number of blacklisted applications already managed by Nvidiot
+    pub managed_count: u32,
+}
+
+/// ShadowPlay status of a profile that differs between the live store and a backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShadowPlayDifference {
+    pub profile_name: String,
+    /// None when the profile doesn't exist or has no ShadowPlay setting
+    pub live: Option<bool>,
+    pub backup: Option<bool>,
+}
+
+/// Differences between the live DRS store and a backup file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackupDiff {
+    pub profiles_only_in_live: Vec<String>,
+    pub profiles_only_in_backup: Vec<String>,
+    /// Applications in the live store but not in the backup
+    pub applications_added: Vec<DrsApplication>,
+    /// Applications in the backup but not in the live store
+    pub applications_removed: Vec<DrsApplication>,
+    pub shadowplay_differences: Vec<ShadowPlayDifference>,
+}
+
+/// What the driver applies for ShadowPlay on a profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ShadowPlaySource {
+    /// The profile stores its own value
+    Profile,
+    /// Inherited from the base profile
+    BaseProfile,
+    /// Nothing stored anywhere; the driver captures
+    DriverDefault,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveShadowPlay {
+    /// Value stored in the profile itself
+    pub stored: Option<u32>,
+    pub effective: u32,
+    pub disabled: bool,
+    pub source: ShadowPlaySource,
+}
+
+/// A setting both profiles store, with different values
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingDifference {
+    pub setting_id: u32,
+    pub name: String,
+    pub value_a: Option<SettingValue>,
+    pub value_b: Option<SettingValue>,
+    pub predefined_a: bool,
+    pub predefined_b: bool,
+}
+
+/// Side-by-side DRS state of two profiles; an empty name is the base profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileComparison {
+    pub profile_a: String,
+    pub profile_b: String,
+    pub settings_only_in_a: Vec<DrsSetting>,
+    pub settings_only_in_b: Vec<DrsSetting>,
+    pub setting_differences: Vec<SettingDifference>,
+    pub applications_only_in_a: Vec<DrsApplication>,
+    pub applications_only_in_b: Vec<DrsApplication>,
+    pub shadowplay_a: EffectiveShadowPlay,
+    pub shadowplay_b: EffectiveShadowPlay,
+    /// False when DRS kept changing while the profiles were read, so the two
+    /// sides may come from different states. Compare again to get a clean read.
+    pub consistent: bool,
+}
+
+/// A single difference to restore from a backup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BackupRestoreEntry {
+    /// Recreate a profile that only exists in the backup
+    #[serde(rename_all = "camelCase")]
+    Profile { profile_name: String },
+    /// Re-add an application to a profile, creating the profile if needed
+    #[serde(rename_all = "camelCase")]
+    Application { profile_name: String, executable: String, name: String },
+    /// Set a profile's ShadowPlay status to the backup's value
+    #[serde(rename_all = "camelCase")]
+    ShadowPlay { profile_name: String, blacklisted: bool },
+}
+
+/// Payload of the unsaved-changes event emitted when closing with unsaved DRS changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnsavedChanges {
+    pub operations: Vec<String>,
+}
+
+/// Which DRS store a change landed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DrsScope {
+    UserOnly,
+    Machine,
+    Unknown,
+}
+
+/// A DRS store file on disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrsStoreFile {
+    pub path: String,
+    pub scope: DrsScope,
+    pub exists: bool,
+    pub modified_ms: Option<u64>,
+}
+
+/// Known DRS store files and where the last save went
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DrsStoreInfo {
+    pub files: Vec<DrsStoreFile>,
+    pub last_save_scope: DrsScope,
+}
+
+/// Detected scope of changes with the facts behind it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScopeExplanation {
+    pub scope: DrsScope,
+    pub explanation: String,
+    pub paths: Vec<String>,
+}
+
+/// A profile left untouched by a batch operation, with the reason
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedProfile {
+    pub profile_name: String,
+    pub reason: String,
+}
+
+/// Outcome of deleting a set of profiles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileDeletion {
+    pub deleted: u32,
+    /// Profiles that are still there, with the error that kept them
+    pub failed: Vec<SkippedProfile>,
+}
+
+/// Outcome of merging legacy-named Nvidiot profiles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LegacyMigration {
+    /// Legacy profile names that were merged and deleted
+    pub migrated: Vec<String>,
+    pub skipped: Vec<SkippedProfile>,
 }
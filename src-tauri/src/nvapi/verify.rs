@@ -0,0 +1,127 @@
+//! Batch verification after a save
+//!
+//! Checking each entry right after its own save costs a save per entry, far
+//! too slow for a 50-entry import. Batch operations stage every change, save
+//! once, then pass `verify_batch` the ShadowPlay value each executable should
+//! now hold. It reloads the session once, so reads reflect the store the
+//! driver uses rather than what we staged, then reads every executable in one
+//! pass and refreshes their cached statuses on the way.
+
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use crate::journal::{self, JournalCategory, JournalSeverity};
+use crate::manifest::now_ms;
+use super::error::NvApiError;
+use super::ffi::SHADOWPLAY_ENABLED;
+use super::session::reload_settings;
+use super::settings::read_executable_shadowplay;
+use super::types::{BatchVerification, VerifiedEntry};
+
+/// Emitted with the BatchVerification after a batch is checked
+pub const BATCH_VERIFIED_EVENT: &str = "batch-verified";
+
+/// Time and mismatch count of the most recent verification
+static LAST_VERIFICATION: Mutex<Option<(u64, u32)>> = Mutex::new(None);
+
+/// Compare what is stored against the expected value; nothing stored means the default (enabled)
+fn check(executable: &str, expected: u32) -> VerifiedEntry {
+    match read_executable_shadowplay(executable) {
+        Ok(actual) => VerifiedEntry {
+            executable: executable.to_string(),
+            expected,
+            actual,
+            verified: actual.unwrap_or(SHADOWPLAY_ENABLED) == expected,
+            error: None,
+        },
+        Err(e) => VerifiedEntry {
+            executable: executable.to_string(),
+            expected,
+            actual: None,
+            verified: false,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Verify a saved batch: one reload, then one read per (executable, expected ShadowPlay value).
+/// Call it only after a successful save, since the reload drops unsaved changes.
+pub fn verify_batch(expectations: &[(String, u32)]) -> Result<BatchVerification, NvApiError> {
+    reload_settings()?;
+
+    let entries: Vec<VerifiedEntry> = expectations.iter()
+        .map(|(executable, expected)| check(executable, *expected))
+        .collect();
+    let mismatches = entries.iter().filter(|e| !e.verified).count() as u32;
+    let verified_at_ms = now_ms();
+
+    *LAST_VERIFICATION.lock().unwrap() = Some((verified_at_ms, mismatches));
+    Ok(BatchVerification { verified_at_ms, mismatches, entries })
+}
+
+/// Time and mismatch count of the most recent verification, if any ran
+pub fn last_verification() -> Option<(u64, u32)> {
+    *LAST_VERIFICATION.lock().unwrap()
+}
+
+/// Tell the frontend how a batch verified
+pub fn emit_summary(app: &AppHandle, verification: &BatchVerification) {
+    let severity = if verification.mismatches > 0 { JournalSeverity::Warning } else { JournalSeverity::Info };
+    journal::append(JournalCategory::Verification, severity, "batch.verified", serde_json::json!({
+        "entries": verification.entries.len(),
+        "mismatches": verification.mismatches,
+    }));
+    let _ = app.emit(BATCH_VERIFIED_EVENT, verification);
+}
+
+#[cfg(all(test, target_os = "windows"))]
+mod tests {
+    use super::*;
+    use crate::nvapi::ffi::{SHADOWPLAY_DISABLED, SHADOWPLAY_SETTING_ID};
+    use crate::nvapi::mock::{self, Profile, Store};
+    use crate::nvapi::session::save_settings;
+    use crate::nvapi::settings::stage_blacklist;
+
+    #[test]
+    fn values_changed_after_the_save_are_flagged_per_entry() {
+        let _serial = mock::serial();
+        mock::install(Store::default()
+            .with_profile(Profile::new("Verify Kept").with_application("verify-kept.exe"))
+            .with_profile(Profile::new("Verify Rewritten").with_application("verify-rewritten.exe")));
+        // Another tool flips one entry back as soon as we save
+        mock::rewrite_after_save("Verify Rewritten", SHADOWPLAY_SETTING_ID, SHADOWPLAY_ENABLED);
+
+        stage_blacklist("verify-kept.exe").unwrap();
+        stage_blacklist("verify-rewritten.exe").unwrap();
+        save_settings().unwrap();
+
+        let verification = verify_batch(&[
+            ("verify-kept.exe".to_string(), SHADOWPLAY_DISABLED),
+            ("verify-rewritten.exe".to_string(), SHADOWPLAY_DISABLED),
+            // No entry at all reads as the default
+            ("verify-absent.exe".to_string(), SHADOWPLAY_ENABLED),
+        ]).unwrap();
+
+        let flags: Vec<(&str, bool)> = verification.entries.iter()
+            .map(|e| (e.executable.as_str(), e.verified))
+            .collect();
+        assert_eq!(flags, [("verify-kept.exe", true), ("verify-rewritten.exe", false), ("verify-absent.exe", true)]);
+        assert_eq!(verification.entries[1].actual, Some(SHADOWPLAY_ENABLED));
+        assert_eq!(verification.entries[2].actual, None);
+        assert!(verification.entries.iter().all(|e| e.error.is_none()));
+        assert_eq!(verification.mismatches, 1);
+        assert_eq!(last_verification(), Some((verification.verified_at_ms, 1)));
+    }
+
+    #[test]
+    fn a_clean_batch_has_no_mismatches() {
+        let _serial = mock::serial();
+        mock::install(Store::default().with_profile(Profile::new("Verify Clean").with_application("verify-clean.exe")));
+
+        stage_blacklist("verify-clean.exe").unwrap();
+        save_settings().unwrap();
+
+        let verification = verify_batch(&[("verify-clean.exe".to_string(), SHADOWPLAY_DISABLED)]).unwrap();
+        assert_eq!(verification.mismatches, 0);
+        assert!(verification.entries[0].verified);
+    }
+}
@@ -0,0 +1,264 @@
+//! Capture panic button
+//!
+//! Before showing something sensitive a streamer wants all capture off at
+//! once and everything put back afterwards. `panic_disable_capture` records
+//! the base profile's ShadowPlay override and every profile overriding it to
+//! ENABLED, persists that record before touching the driver, then disables
+//! ShadowPlay in all of them with one save. `restore_capture_state` puts each
+//! value back (deleting the base override if it didn't exist before), reads
+//! every restoration back, and keeps only what didn't verify in the record so
+//! a retry finishes the job. A profile deleted since the panic has nothing
+//! left to restore; it is reported and dropped from the record. Panicking
+//! twice keeps the first record, and the record survives restarts, so the
+//! original state is never lost. Both transitions are also reachable from the
+//! tray menu and the optional panic hotkey, and show in the tray tooltip.
+
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use crate::journal::{self, JournalCategory, JournalSeverity};
+use crate::manifest::now_ms;
+use crate::nvapi::ffi::{SHADOWPLAY_DISABLED, SHADOWPLAY_ENABLED, SHADOWPLAY_SETTING_ID};
+use crate::nvapi::profiles::{enumerate_profiles, find_profile_by_name, get_base_profile};
+use crate::nvapi::session::{reload_settings, save_settings};
+use crate::nvapi::settings::{clear_profile_status_cache, delete_setting, get_dword_override, set_dword_setting};
+use crate::nvapi::NvApiError;
+use crate::storage::{self, StorageError};
+use crate::tray;
+
+const PANIC_FILE: &str = "panic-restore.json";
+
+/// Emitted when capture was disabled by the panic button
+pub const CAPTURE_PANIC_EVENT: &str = "capture-panic";
+/// Emitted when a restore finished, fully or not
+pub const CAPTURE_RESTORED_EVENT: &str = "capture-restored";
+
+/// A profile's ShadowPlay override before the panic
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedOverride {
+    pub profile_name: String,
+    pub value: u32,
+}
+
+/// What a restore has to put back
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PanicRecord {
+    pub taken_at_ms: u64,
+    /// Whether the base profile still has to be restored
+    pub base_pending: bool,
+    /// Base profile override before the panic; None means there was none and it is deleted on restore
+    pub base_value: Option<u32>,
+    pub overrides: Vec<SavedOverride>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PanicState {
+    pub engaged: bool,
+    pub record: Option<PanicRecord>,
+}
+
+/// How one value was put back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Restoration {
+    /// None for the base profile
+    pub profile_name: Option<String>,
+    pub restored_value: Option<u32>,
+    pub verified: bool,
+    /// The profile was deleted since the panic, so nothing was left to restore. Not kept pending.
+    pub profile_deleted: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreReport {
+    pub restorations: Vec<Restoration>,
+    /// Whatever didn't verify and stays pending for another restore; None once everything is back
+    pub remaining: Option<PanicRecord>,
+}
+
+static RECORD: Lazy<Mutex<Option<PanicRecord>>> = Lazy::new(|| Mutex::new(None));
+
+/// Load a pending restore left by a previous run
+pub fn load() -> Result<(), StorageError> {
+    *RECORD.lock().unwrap() = storage::load::<Option<PanicRecord>>(PANIC_FILE)?.flatten();
+    Ok(())
+}
+
+fn persist(record: &Option<PanicRecord>) -> Result<(), String> {
+    storage::save(PANIC_FILE, record).map_err(|e| e.to_string())
+}
+
+/// Whether capture is held off by the panic button
+pub fn state() -> PanicState {
+    let record = RECORD.lock().unwrap().clone();
+    PanicState { engaged: record.is_some(), record }
+}
+
+/// Profiles, other than the base, overriding ShadowPlay to enabled
+fn enabled_overrides() -> Result<Vec<SavedOverride>, NvApiError> {
+    let mut overrides = Vec::new();
+    for profile in enumerate_profiles()? {
+        let handle = find_profile_by_name(&profile.name)?;
+        if get_dword_override(handle, SHADOWPLAY_SETTING_ID)? == Some(SHADOWPLAY_ENABLED) {
+            overrides.push(SavedOverride { profile_name: profile.name, value: SHADOWPLAY_ENABLED });
+        }
+    }
+    Ok(overrides)
+}
+
+/// Write DISABLED to the base profile and the given profiles, then save once
+fn disable(overrides: &[SavedOverride]) -> Result<(), NvApiError> {
+    set_dword_setting(get_base_profile()?, SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED)?;
+    for saved in overrides {
+        set_dword_setting(find_profile_by_name(&saved.profile_name)?, SHADOWPLAY_SETTING_ID, SHADOWPLAY_DISABLED)?;
+    }
+    save_settings()
+}
+
+/// Disable ShadowPlay everywhere right now. While a restore is pending the
+/// original record is kept and only the disabled values are re-applied.
+pub fn panic_disable_capture(app: &AppHandle) -> Result<PanicState, String> {
+    let result = disable_everywhere(app);
+    match &result {
+        Ok(state) => tray::notify(app, &format!(
+            "Capture disabled in the base profile and {} other profiles",
+            state.record.as_ref().map_or(0, |r| r.overrides.len()),
+        )),
+        Err(e) => tray::notify(app, e),
+    }
+    result
+}
+
+fn disable_everywhere(app: &AppHandle) -> Result<PanicState, String> {
+    let mut current = RECORD.lock().unwrap();
+    let record = match current.clone() {
+        Some(record) => record,
+        None => {
+            let record = PanicRecord {
+                taken_at_ms: now_ms(),
+                base_pending: true,
+                base_value: get_dword_override(get_base_profile().map_err(|e| e.to_string())?, SHADOWPLAY_SETTING_ID)
+                    .map_err(|e| e.to_string())?,
+                overrides: enabled_overrides().map_err(|e| e.to_string())?,
+            };
+            // Persisted first so a crash mid-way can still be restored
+            persist(&Some(record.clone()))?;
+            *current = Some(record.clone());
+            record
+        }
+    };
+
+    if let Err(e) = disable(&record.overrides) {
+        let _ = reload_settings();
+        return Err(format!("Failed to disable capture: {}", e));
+    }
+    clear_profile_status_cache();
+
+    journal::append(JournalCategory::Mutation, JournalSeverity::Warning, "capture.panic", serde_json::json!({
+        "profiles": record.overrides.len(),
+    }));
+    let state = PanicState { engaged: true, record: Some(record) };
+    let _ = app.emit(CAPTURE_PANIC_EVENT, &state);
+    Ok(state)
+}
+
+/// What a profile's override reads as after the save, compared with what was restored
+fn verify(profile_name: Option<&str>, expected: Option<u32>) -> Result<bool, NvApiError> {
+    let handle = match profile_name {
+        Some(name) => find_profile_by_name(name)?,
+        None => get_base_profile()?,
+    };
+    Ok(get_dword_override(handle, SHADOWPLAY_SETTING_ID)? == expected)
+}
+
+/// Put back what the panic button changed. Without a pending record nothing is done.
+pub fn restore_capture_state(app: &AppHandle) -> Result<RestoreReport, String> {
+    let result = restore_everything(app);
+    match &result {
+        Ok(RestoreReport { remaining: None, .. }) => tray::notify(app, "Capture restored"),
+        Ok(RestoreReport { remaining: Some(remaining), .. }) => tray::notify(app, &format!(
+            "Capture partly restored; {} values are still pending",
+            remaining.overrides.len() + usize::from(remaining.base_pending),
+        )),
+        Err(e) => tray::notify(app, e),
+    }
+    result
+}
+
+/// Panic while capture is on, restore while the panic button holds it off
+pub fn toggle(app: &AppHandle) -> Result<(), String> {
+    if state().engaged {
+        restore_capture_state(app).map(|_| ())
+    } else {
+        panic_disable_capture(app).map(|_| ())
+    }
+}
+
+fn restore_everything(app: &AppHandle) -> Result<RestoreReport, String> {
+    let mut current = RECORD.lock().unwrap();
+    let Some(record) = current.clone() else {
+        return Ok(RestoreReport { restorations: Vec::new(), remaining: None });
+    };
+
+    // Stage every value, remembering which ones could be written
+    let mut staged: Vec<(Option<String>, Option<u32>, Result<(), NvApiError>)> = Vec::new();
+    if record.base_pending {
+        let result = get_base_profile().and_then(|base| match record.base_value {
+            Some(value) => set_dword_setting(base, SHADOWPLAY_SETTING_ID, value),
+            None => delete_setting(base, SHADOWPLAY_SETTING_ID),
+        });
+        staged.push((None, record.base_value, result));
+    }
+    for saved in &record.overrides {
+        let result = find_profile_by_name(&saved.profile_name)
+            .and_then(|handle| set_dword_setting(handle, SHADOWPLAY_SETTING_ID, saved.value));
+        staged.push((Some(saved.profile_name.clone()), Some(saved.value), result));
+    }
+
+    if let Err(e) = save_settings() {
+        let _ = reload_settings();
+        return Err(format!("Failed to restore capture: {}", e));
+    }
+    // Read back what the driver stored, not what the session holds
+    let _ = reload_settings();
+    clear_profile_status_cache();
+
+    let mut remaining = PanicRecord { taken_at_ms: record.taken_at_ms, base_value: record.base_value, ..Default::default() };
+    let restorations: Vec<Restoration> = staged.into_iter()
+        .map(|(profile_name, restored_value, result)| {
+            let checked = result.and_then(|()| verify(profile_name.as_deref(), restored_value));
+            let (verified, profile_deleted, error) = match checked {
+                Ok(true) => (true, false, None),
+                Ok(false) => (false, false, Some("The driver did not keep the restored value".to_string())),
+                Err(e @ NvApiError::ProfileNotFound(_)) => (false, true, Some(e.to_string())),
+                Err(e) => (false, false, Some(e.to_string())),
+            };
+            if !verified && !profile_deleted {
+                match &profile_name {
+                    Some(name) => remaining.overrides.push(SavedOverride { profile_name: name.clone(), value: restored_value.unwrap_or(SHADOWPLAY_ENABLED) }),
+                    None => remaining.base_pending = true,
+                }
+            }
+            Restoration { profile_name, restored_value, verified, profile_deleted, error }
+        })
+        .collect();
+
+    let remaining = (remaining.base_pending || !remaining.overrides.is_empty()).then_some(remaining);
+    persist(&remaining)?;
+    *current = remaining.clone();
+
+    let report = RestoreReport { restorations, remaining };
+    journal::append(JournalCategory::Mutation, journal::severity_for(report.remaining.is_some()), "capture.restored", serde_json::json!({
+        "restored": report.restorations.iter().filter(|r| r.verified).count(),
+        "deleted": report.restorations.iter().filter(|r| r.profile_deleted).count(),
+        "remaining": report.restorations.iter().filter(|r| !r.verified && !r.profile_deleted).count(),
+    }));
+    let _ = app.emit(CAPTURE_RESTORED_EVENT, &report);
+    Ok(report)
+}
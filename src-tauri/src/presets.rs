@@ -0,0 +1,317 @@
+//! Shareable rule presets
+//!
+//! A preset bundles rules with a metadata block. Installing one is two-step: the
+//! first call returns what would change, the second merges it. Rules already
+//! present (by fingerprint) are skipped and rules that contradict an existing one
+//! are reported instead of installed.
+
+use std::cmp::Ordering;
+use std::fs;
+use serde::{Deserialize, Serialize};
+use crate::rules::{self, NewRule, Rule, RuleAction, RuleCondition};
+
+/// Descriptive block of a preset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetMetadata {
+    pub name: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub description: String,
+    /// Oldest Nvidiot version the preset works with, e.g. "0.2.0"
+    #[serde(default)]
+    pub min_app_version: Option<String>,
+}
+
+/// A set of rules with metadata, as shared between users
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RulePreset {
+    pub metadata: PresetMetadata,
+    pub rules: Vec<NewRule>,
+    /// Names of templates the preset expects; carried through but not resolved
+    #[serde(default)]
+    pub templates: Vec<String>,
+}
+
+/// A preset rule that contradicts an existing rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetConflict {
+    pub incoming: NewRule,
+    pub existing_rule_id: u32,
+    pub existing_action: RuleAction,
+}
+
+/// What installing a preset does, or did
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetInstallPlan {
+    pub metadata: PresetMetadata,
+    pub to_add: Vec<NewRule>,
+    /// Names of preset rules identical to existing ones
+    pub duplicates: Vec<String>,
+    pub conflicts: Vec<PresetConflict>,
+    pub templates: Vec<String>,
+    /// Whether the rules in to_add were actually added
+    pub installed: bool,
+}
+
+/// Text a rule is matched on: executable and condition, ignoring case
+fn match_key(executable: &str, condition: &RuleCondition) -> String {
+    match condition {
+        RuleCondition::TitleMatches { pattern, while_focused_only } => format!(
+            "{}|title:{}|focused:{}",
+            executable.to_lowercase(),
+            pattern.to_lowercase(),
+            while_focused_only,
+        ),
+        RuleCondition::SignerMatches { substring } => format!(
+            "{}|signer:{}",
+            executable.to_lowercase(),
+            substring.to_lowercase(),
+        ),
+    }
+}
+
+/// Stable identity of a rule's behavior, independent of its name and id
+pub fn fingerprint(executable: &str, condition: &RuleCondition, action: RuleAction, revert_on_exit: bool) -> String {
+    format!("{}|{:?}|revert:{}", match_key(executable, condition), action, revert_on_exit)
+}
+
+fn rule_fingerprint(rule: &Rule) -> String {
+    fingerprint(&rule.executable, &rule.condition, rule.action, rule.revert_on_exit)
+}
+
+fn new_rule_fingerprint(rule: &NewRule) -> String {
+    fingerprint(&rule.executable, &rule.condition, rule.action, rule.revert_on_exit)
+}
+
+/// Compare dotted version strings numerically; missing parts count as zero
+fn version_at_least(current: &str, required: &str) -> bool {
+    let parse = |v: &str| -> Vec<u32> {
+        v.split('.').map(|p| p.trim().parse().unwrap_or(0)).collect()
+    };
+    let (current, required) = (parse(current), parse(required));
+    let len = current.len().max(required.len());
+    let part = |v: &[u32], i: usize| v.get(i).copied().unwrap_or(0);
+
+    for i in 0..len {
+        match part(&current, i).cmp(&part(&required, i)) {
+            Ordering::Equal => continue,
+            ordering => return ordering.is_gt(),
+        }
+    }
+    true
+}
+
+fn validate(preset: &RulePreset) -> Result<(), String> {
+    if preset.metadata.name.trim().is_empty() {
+        return Err("Preset has no name".to_string());
+    }
+
+    if let Some(required) = &preset.metadata.min_app_version {
+        let current = env!("CARGO_PKG_VERSION");
+        if !version_at_least(current, required) {
+            return Err(format!("Preset requires Nvidiot {} or newer (this is {})", required, current));
+        }
+    }
+
+    for rule in &preset.rules {
+        if rule.executable.trim().is_empty() {
+            return Err(format!("Rule '{}' has no executable", rule.name));
+        }
+        match &rule.condition {
+            RuleCondition::TitleMatches { pattern, .. } if pattern.trim().is_empty() => {
+                return Err(format!("Rule '{}' has an empty title pattern", rule.name));
+            }
+            RuleCondition::SignerMatches { substring } if substring.trim().is_empty() => {
+                return Err(format!("Rule '{}' has an empty signer", rule.name));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Work out what a preset would add given the existing rules
+pub fn plan_merge(preset: &RulePreset, existing: &[Rule]) -> PresetInstallPlan {
+    let mut plan = PresetInstallPlan {
+        metadata: preset.metadata.clone(),
+        to_add: Vec::new(),
+        duplicates: Vec::new(),
+        conflicts: Vec::new(),
+        templates: preset.templates.clone(),
+        installed: false,
+    };
+
+    for incoming in &preset.rules {
+        let incoming_fingerprint = new_rule_fingerprint(incoming);
+        let already_planned = plan.to_add.iter().any(|r| new_rule_fingerprint(r) == incoming_fingerprint);
+
+        if already_planned || existing.iter().any(|r| rule_fingerprint(r) == incoming_fingerprint) {
+            plan.duplicates.push(incoming.name.clone());
+            continue;
+        }
+
+        let key = match_key(&incoming.executable, &incoming.condition);
+        let conflict = existing.iter()
+            .find(|r| match_key(&r.executable, &r.condition) == key && r.action != incoming.action);
+        if let Some(existing_rule) = conflict {
+            plan.conflicts.push(PresetConflict {
+                incoming: incoming.clone(),
+                existing_rule_id: existing_rule.id,
+                existing_action: existing_rule.action,
+            });
+            continue;
+        }
+
+        plan.to_add.push(incoming.clone());
+    }
+
+    plan
+}
+
+/// Parse a preset from inline JSON or from a file path
+fn read_preset(path_or_json: &str) -> Result<RulePreset, String> {
+    let trimmed = path_or_json.trim();
+    let json = if trimmed.starts_with('{') {
+        trimmed.to_string()
+    } else {
+        fs::read_to_string(trimmed).map_err(|e| format!("Failed to read {}: {}", trimmed, e))?
+    };
+
+    serde_json::from_str(&json).map_err(|e| format!("Invalid preset: {}", e))
+}
+
+/// Validate a preset and plan its merge; with `confirm`, also add the planned rules
+pub fn install_preset(path_or_json: &str, confirm: bool) -> Result<PresetInstallPlan, String> {
+    let preset = read_preset(path_or_json)?;
+    validate(&preset)?;
+
+    let mut plan = plan_merge(&preset, &rules::get_rules());
+    if confirm {
+        for rule in &plan.to_add {
+            rules::add_rule(rule.clone())?;
+        }
+        plan.installed = true;
+    }
+
+    Ok(plan)
+}
+
+/// Serialize the current rules as a preset
+pub fn export_preset(metadata: PresetMetadata) -> Result<String, String> {
+    let preset = RulePreset {
+        metadata,
+        rules: rules::get_rules().into_iter()
+            .map(|r| NewRule {
+                name: r.name,
+                executable: r.executable,
+                condition: r.condition,
+                action: r.action,
+                revert_on_exit: r.revert_on_exit,
+            })
+            .collect(),
+        templates: Vec::new(),
+    };
+
+    serde_json::to_string_pretty(&preset).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn title(pattern: &str) -> RuleCondition {
+        RuleCondition::TitleMatches { pattern: pattern.to_string(), while_focused_only: false }
+    }
+
+    fn new_rule(name: &str, executable: &str, condition: RuleCondition, action: RuleAction) -> NewRule {
+        NewRule { name: name.to_string(), executable: executable.to_string(), condition, action, revert_on_exit: true }
+    }
+
+    fn existing(id: u32, rule: NewRule) -> Rule {
+        Rule {
+            id,
+            name: rule.name,
+            executable: rule.executable,
+            condition: rule.condition,
+            action: rule.action,
+            revert_on_exit: rule.revert_on_exit,
+            enabled: true,
+            active: false,
+            suspended: false,
+        }
+    }
+
+    fn preset(rules: Vec<NewRule>) -> RulePreset {
+        RulePreset {
+            metadata: PresetMetadata {
+                name: "Streaming".to_string(),
+                author: String::new(),
+                description: String::new(),
+                min_app_version: None,
+            },
+            rules,
+            templates: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn versions_compare_numerically_part_by_part() {
+        assert!(version_at_least("0.10.0", "0.9.3"));
+        assert!(version_at_least("1.2", "1.2.0"));
+        assert!(!version_at_least("1.2.0", "1.2.1"));
+        assert!(!version_at_least("0.9", "1"));
+    }
+
+    #[test]
+    fn fingerprints_ignore_case_and_name_but_not_behavior() {
+        let a = fingerprint("Game.exe", &title("Ranked"), RuleAction::Blacklist, true);
+        assert_eq!(a, fingerprint("game.EXE", &title("RANKED"), RuleAction::Blacklist, true));
+        assert_ne!(a, fingerprint("game.exe", &title("ranked"), RuleAction::Blacklist, false));
+        assert_ne!(a, fingerprint("game.exe", &title("ranked"), RuleAction::Unblacklist, true));
+        assert_ne!(a, fingerprint("game.exe", &RuleCondition::SignerMatches { substring: "ranked".to_string() }, RuleAction::Blacklist, true));
+    }
+
+    #[test]
+    fn merging_skips_duplicates_and_reports_contradictions() {
+        let current = [
+            existing(1, new_rule("Mine", "game.exe", title("ranked"), RuleAction::Blacklist)),
+            existing(2, new_rule("Other", "other.exe", title("menu"), RuleAction::Blacklist)),
+        ];
+        let incoming = preset(vec![
+            new_rule("Same as mine", "GAME.exe", title("Ranked"), RuleAction::Blacklist),
+            new_rule("Opposite", "other.exe", title("menu"), RuleAction::Unblacklist),
+            new_rule("New", "new.exe", title("match"), RuleAction::Blacklist),
+            new_rule("New again", "new.exe", title("match"), RuleAction::Blacklist),
+        ]);
+        let plan = plan_merge(&incoming, &current);
+
+        assert_eq!(plan.to_add.iter().map(|r| r.name.as_str()).collect::<Vec<_>>(), ["New"]);
+        assert_eq!(plan.duplicates, ["Same as mine", "New again"]);
+        assert_eq!(plan.conflicts.len(), 1);
+        assert_eq!(plan.conflicts[0].existing_rule_id, 2);
+        assert_eq!(plan.conflicts[0].existing_action, RuleAction::Blacklist);
+        assert!(!plan.installed);
+    }
+
+    #[test]
+    fn presets_need_a_name_executables_and_non_empty_conditions() {
+        assert!(validate(&preset(vec![new_rule("Ok", "game.exe", title("ranked"), RuleAction::Blacklist)])).is_ok());
+
+        let mut unnamed = preset(Vec::new());
+        unnamed.metadata.name = "  ".to_string();
+        assert!(validate(&unnamed).is_err());
+
+        assert!(validate(&preset(vec![new_rule("No exe", " ", title("ranked"), RuleAction::Blacklist)])).is_err());
+        assert!(validate(&preset(vec![new_rule("No title", "game.exe", title(" "), RuleAction::Blacklist)])).is_err());
+
+        let mut too_new = preset(Vec::new());
+        too_new.metadata.min_app_version = Some("999.0".to_string());
+        assert!(validate(&too_new).unwrap_err().contains("999.0"));
+    }
+}
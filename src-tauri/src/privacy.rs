@@ -0,0 +1,149 @@
+//! Privacy mode
+//!
+//! Window titles often carry document names, chat contents or URLs. With
+//! privacy mode on, a title is replaced by the process's display name right
+//! where it is read from Windows. The process filter still sees the real
+//! title, which never leaves the enumeration. As a second line, journal params,
+//! events and the diagnostics report pass through `redact` on their way out,
+//! which replaces every `windowTitle` field at any depth, so a title that
+//! reaches a new payload some other way still can't leave. Title-based
+//! rules and title search can't work on a replaced title, so they are off
+//! while the mode is on and reported as unavailable in the backend capabilities.
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+use crate::config;
+use crate::nvapi::types::FeatureAvailability;
+
+/// Key a window title is serialized under
+const TITLE_KEY: &str = "windowTitle";
+
+/// Key whose value a title is replaced by, when it sits beside one
+const PROCESS_NAME_KEY: &str = "processName";
+
+/// Stand-in for a title with no process name beside it
+const REDACTED: &str = "[redacted]";
+
+/// Capability entry for everything that reads window titles
+pub const WINDOW_TITLES_FEATURE: &str = "window-titles";
+
+pub fn enabled() -> bool {
+    config::get().privacy_mode
+}
+
+/// What a title is replaced with: the executable name without its extension
+pub fn display_name(process_name: &str) -> String {
+    let name = process_name.rsplit(['\\', '/']).next().unwrap_or(process_name);
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if extension.eq_ignore_ascii_case("exe") && !stem.is_empty() => stem.to_string(),
+        _ => name.to_string(),
+    }
+}
+
+/// The title as it may leave window enumeration. Every title read from
+/// Windows passes through here before it is stored in a returned struct.
+pub fn sanitize_title(process_name: &str, title: String) -> String {
+    if enabled() { display_name(process_name) } else { title }
+}
+
+/// Replace every window title in a serialized value with the display name of
+/// the process beside it, or a fixed marker if there is none
+pub fn strip_titles(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            if fields.contains_key(TITLE_KEY) {
+                let replacement = fields.get(PROCESS_NAME_KEY)
+                    .and_then(Value::as_str)
+                    .map_or_else(|| REDACTED.to_string(), display_name);
+                fields.insert(TITLE_KEY.to_string(), Value::String(replacement));
+            }
+            fields.values_mut().for_each(strip_titles);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_titles),
+        _ => {}
+    }
+}
+
+/// Serialize a value on its way out of the backend, without titles in privacy mode
+pub fn redact<T: Serialize>(value: &T) -> Value {
+    let mut value = serde_json::to_value(value).unwrap_or_default();
+    if enabled() {
+        strip_titles(&mut value);
+    }
+    value
+}
+
+/// Emit an event whose payload may carry a window title
+pub fn emit<T: Serialize>(app: &AppHandle, event: &str, payload: &T) {
+    let _ = app.emit(event, redact(payload));
+}
+
+/// Whether title rules and title search are available
+pub fn window_titles_capability() -> FeatureAvailability {
+    FeatureAvailability {
+        feature: WINDOW_TITLES_FEATURE.to_string(),
+        available: !enabled(),
+        missing_functions: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn display_name_drops_the_path_and_exe_extension() {
+        assert_eq!(display_name("C:\\Games\\Witcher3.exe"), "Witcher3");
+        assert_eq!(display_name("/opt/game/Game.EXE"), "Game");
+        assert_eq!(display_name("launcher.bin"), "launcher.bin");
+        assert_eq!(display_name(".exe"), ".exe");
+        assert_eq!(display_name("notepad"), "notepad");
+    }
+
+    #[test]
+    fn titles_are_replaced_at_any_depth() {
+        let mut value = json!({
+            "processName": "chrome.exe",
+            "windowTitle": "Inbox (3) - mail",
+            "children": [{ "processName": "code.exe", "windowTitle": "secret.txt - Editor" }],
+            "params": { "focus": { "windowTitle": "Bank statement.pdf" } },
+        });
+        strip_titles(&mut value);
+
+        assert_eq!(value["windowTitle"], "chrome");
+        assert_eq!(value["children"][0]["windowTitle"], "code");
+        assert_eq!(value["params"]["focus"]["windowTitle"], REDACTED);
+    }
+
+    #[test]
+    fn no_title_text_survives_in_diagnostics_output() {
+        let titles = ["Inbox (3) - mail", "secret.txt - Editor", "Bank statement.pdf"];
+        let mut diagnostics = json!({
+            "sessionGeneration": 4,
+            "journal": [
+                { "code": "rule.triggered", "params": { "processName": "chrome.exe", "windowTitle": titles[0] } },
+                { "code": "focus.changed", "params": { "windows": [
+                    { "processName": "code.exe", "windowTitle": titles[1] },
+                    { "windowTitle": titles[2] },
+                ] } },
+            ],
+        });
+        strip_titles(&mut diagnostics);
+
+        let output = diagnostics.to_string();
+        for title in titles {
+            assert!(!output.contains(title), "'{}' leaked into {}", title, output);
+        }
+        assert!(output.contains("rule.triggered"));
+    }
+
+    #[test]
+    fn values_without_titles_are_untouched() {
+        let original = json!({ "processName": "game.exe", "title": "Settings", "items": [1, 2] });
+        let mut value = original.clone();
+        strip_titles(&mut value);
+        assert_eq!(value, original);
+    }
+}
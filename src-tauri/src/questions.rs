@@ -0,0 +1,320 @@
+//! Pending questions for the user
+//!
+//! Background subsystems sometimes need a decision while the window may be
+//! hidden. They push a `Question` into the `QuestionQueue` held in managed
+//! state; the queue emits `question-pending` with the new count, and the
+//! frontend lists questions and answers them by id. Answers are routed to the
+//! `QuestionHandler` registered for the question's kind, so a new subsystem
+//! only registers a handler at setup. Questions that expire unanswered are
+//! dropped, their handler is told, and the queue keeps a short history. The
+//! pending count is also shown on the tray icon.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::manifest::now_ms;
+
+/// Emitted whenever a question is added
+pub const QUESTION_PENDING_EVENT: &str = "question-pending";
+/// Emitted when questions are answered or expire, with the remaining count
+pub const QUESTIONS_CHANGED_EVENT: &str = "questions-changed";
+
+/// Resolved questions kept for `history`
+const HISTORY_LIMIT: usize = 100;
+
+/// How often expired questions are swept while none are being read
+const EXPIRY_SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+pub type QuestionId = u64;
+
+/// A question waiting for the user. `payload` is defined by the subsystem that asked.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Question {
+    pub id: QuestionId,
+    pub kind: String,
+    pub payload: serde_json::Value,
+    pub created_ms: u64,
+    pub expires_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionPending {
+    pub question: Question,
+    pub pending_count: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QuestionResolution {
+    Answered,
+    Expired,
+}
+
+/// A question that left the queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuestionHistoryEntry {
+    pub id: QuestionId,
+    pub kind: String,
+    pub created_ms: u64,
+    pub resolved_ms: u64,
+    pub resolution: QuestionResolution,
+}
+
+/// Receives the answers to one kind of question
+pub trait QuestionHandler: Send + Sync {
+    /// Apply the user's response. An error leaves the question pending.
+    fn answer(&self, app: &AppHandle, question: &Question, response: serde_json::Value) -> Result<(), String>;
+
+    /// Called once when the question expires unanswered
+    fn expired(&self, _app: &AppHandle, _question: &Question) {}
+}
+
+#[derive(Default)]
+struct QueueState {
+    pending: Vec<Question>,
+    handlers: HashMap<String, Arc<dyn QuestionHandler>>,
+    history: VecDeque<QuestionHistoryEntry>,
+    next_id: QuestionId,
+}
+
+impl QueueState {
+    fn push(&mut self, kind: &str, payload: serde_json::Value, now: u64, ttl: Duration) -> Question {
+        self.next_id += 1;
+        let question = Question {
+            id: self.next_id,
+            kind: kind.to_string(),
+            payload,
+            created_ms: now,
+            expires_ms: now + ttl.as_millis() as u64,
+        };
+        self.pending.push(question.clone());
+        question
+    }
+
+    /// Remove the questions expired at `now`, recording them in the history
+    fn take_expired(&mut self, now: u64) -> Vec<Question> {
+        let (expired, pending): (Vec<_>, Vec<_>) = self.pending.drain(..).partition(|q| q.expires_ms <= now);
+        self.pending = pending;
+        for question in &expired {
+            self.resolve(question, QuestionResolution::Expired);
+        }
+        expired
+    }
+
+    /// The pending question `id` and the handler for its kind
+    fn route(&self, id: QuestionId) -> Result<(Question, Arc<dyn QuestionHandler>), String> {
+        let question = self.pending.iter()
+            .find(|q| q.id == id)
+            .cloned()
+            .ok_or_else(|| format!("Question {} has expired or does not exist", id))?;
+        let handler = self.handlers.get(&question.kind).cloned()
+            .ok_or_else(|| format!("No handler for '{}' questions", question.kind))?;
+        Ok((question, handler))
+    }
+
+    /// Remove an answered question, recording it in the history
+    fn complete(&mut self, question: &Question) {
+        self.pending.retain(|q| q.id != question.id);
+        self.resolve(question, QuestionResolution::Answered);
+    }
+
+    fn resolve(&mut self, question: &Question, resolution: QuestionResolution) {
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(QuestionHistoryEntry {
+            id: question.id,
+            kind: question.kind.clone(),
+            created_ms: question.created_ms,
+            resolved_ms: now_ms(),
+            resolution,
+        });
+    }
+}
+
+/// Queue of questions, held in Tauri managed state
+#[derive(Default)]
+pub struct QuestionQueue {
+    state: Mutex<QueueState>,
+}
+
+impl QuestionQueue {
+    /// Route answers to questions of `kind` to `handler`, replacing any earlier one
+    pub fn register_handler(&self, kind: &str, handler: Arc<dyn QuestionHandler>) {
+        self.state.lock().unwrap().handlers.insert(kind.to_string(), handler);
+    }
+
+    /// Queue a question that expires after `ttl`
+    pub fn ask(&self, app: &AppHandle, kind: &str, payload: serde_json::Value, ttl: Duration) -> QuestionId {
+        let (question, pending_count) = {
+            let mut state = self.state.lock().unwrap();
+            let question = state.push(kind, payload, now_ms(), ttl);
+            (question, state.pending.len() as u32)
+        };
+
+        let id = question.id;
+        crate::tray::set_pending_questions(app, pending_count);
+        let _ = app.emit(QUESTION_PENDING_EVENT, QuestionPending { question, pending_count });
+        id
+    }
+
+    /// Drop expired questions, telling their handlers. Handlers run outside the lock.
+    pub fn prune_expired(&self, app: &AppHandle) {
+        let expired: Vec<(Question, Option<Arc<dyn QuestionHandler>>)> = {
+            let mut state = self.state.lock().unwrap();
+            state.take_expired(now_ms()).into_iter()
+                .map(|q| {
+                    let handler = state.handlers.get(&q.kind).cloned();
+                    (q, handler)
+                })
+                .collect()
+        };
+        if expired.is_empty() {
+            return;
+        }
+
+        for (question, handler) in &expired {
+            if let Some(handler) = handler {
+                handler.expired(app, question);
+            }
+        }
+        let remaining = self.pending_count();
+        crate::tray::set_pending_questions(app, remaining);
+        let _ = app.emit(QUESTIONS_CHANGED_EVENT, remaining);
+    }
+
+    /// Questions still waiting for an answer, oldest first
+    pub fn pending(&self, app: &AppHandle) -> Vec<Question> {
+        self.prune_expired(app);
+        self.state.lock().unwrap().pending.clone()
+    }
+
+    pub fn pending_count(&self) -> u32 {
+        self.state.lock().unwrap().pending.len() as u32
+    }
+
+    /// Answer a question. It leaves the queue only if its handler accepts the response.
+    pub fn answer(&self, app: &AppHandle, id: QuestionId, response: serde_json::Value) -> Result<(), String> {
+        self.prune_expired(app);
+
+        let (question, handler) = self.state.lock().unwrap().route(id)?;
+
+        handler.answer(app, &question, response)?;
+
+        let mut state = self.state.lock().unwrap();
+        state.complete(&question);
+        let remaining = state.pending.len() as u32;
+        drop(state);
+
+        crate::tray::set_pending_questions(app, remaining);
+        let _ = app.emit(QUESTIONS_CHANGED_EVENT, remaining);
+        Ok(())
+    }
+
+    /// Recently answered or expired questions, oldest first
+    pub fn history(&self) -> Vec<QuestionHistoryEntry> {
+        self.state.lock().unwrap().history.iter().cloned().collect()
+    }
+}
+
+/// Start the background task that drops expired questions while nobody reads the queue
+pub fn start_expiry_sweeper(app: &AppHandle) {
+    use crate::tasks::TaskSupervisor;
+
+    let supervisor = app.state::<TaskSupervisor>();
+    let app = app.clone();
+    supervisor.spawn("question-expiry", move |token| {
+        while token.sleep(EXPIRY_SWEEP_INTERVAL) {
+            app.state::<QuestionQueue>().prune_expired(&app);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TTL: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn questions_get_increasing_ids_and_an_expiry() {
+        let mut state = QueueState::default();
+        let first = state.push("import-conflicts", serde_json::Value::Null, 1_000, TTL);
+        let second = state.push("import-conflicts", serde_json::Value::Null, 1_000, TTL);
+        assert_eq!((first.id, second.id), (1, 2));
+        assert_eq!(first.expires_ms, 1_100);
+        assert_eq!(state.pending.len(), 2);
+    }
+
+    #[test]
+    fn expired_questions_leave_the_queue_and_enter_the_history() {
+        let mut state = QueueState::default();
+        state.push("early", serde_json::Value::Null, 0, TTL);
+        state.push("late", serde_json::Value::Null, 50, TTL);
+
+        assert!(state.take_expired(99).is_empty());
+        let expired = state.take_expired(100);
+        assert_eq!(expired.iter().map(|q| q.kind.as_str()).collect::<Vec<_>>(), ["early"]);
+        assert_eq!(state.pending.iter().map(|q| q.kind.as_str()).collect::<Vec<_>>(), ["late"]);
+        assert_eq!(state.history.len(), 1);
+        assert_eq!(state.history[0].resolution, QuestionResolution::Expired);
+    }
+
+    struct Ignore;
+
+    impl QuestionHandler for Ignore {
+        fn answer(&self, _app: &AppHandle, _question: &Question, _response: serde_json::Value) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn answers_are_routed_to_the_handler_for_the_question_kind() {
+        let mut state = QueueState::default();
+        let (conflicts, restore): (Arc<dyn QuestionHandler>, Arc<dyn QuestionHandler>) = (Arc::new(Ignore), Arc::new(Ignore));
+        state.handlers.insert("import-conflicts".to_string(), conflicts.clone());
+        state.handlers.insert("restore".to_string(), restore.clone());
+        let first = state.push("import-conflicts", serde_json::Value::Null, 0, TTL);
+        let second = state.push("restore", serde_json::Value::Null, 0, TTL);
+        let orphan = state.push("unregistered", serde_json::Value::Null, 0, TTL);
+
+        let (question, handler) = state.route(second.id).unwrap();
+        assert_eq!(question.id, second.id);
+        assert!(Arc::ptr_eq(&handler, &restore));
+        assert!(Arc::ptr_eq(&state.route(first.id).unwrap().1, &conflicts));
+
+        assert_eq!(state.route(orphan.id).err().unwrap(), "No handler for 'unregistered' questions");
+        assert_eq!(state.route(99).err().unwrap(), "Question 99 has expired or does not exist");
+    }
+
+    #[test]
+    fn answered_questions_leave_the_queue_and_enter_the_history() {
+        let mut state = QueueState::default();
+        state.handlers.insert("kind".to_string(), Arc::new(Ignore));
+        let answered = state.push("kind", serde_json::Value::Null, 0, TTL);
+        state.push("kind", serde_json::Value::Null, 0, TTL);
+
+        state.complete(&answered);
+        assert_eq!(state.pending.iter().map(|q| q.id).collect::<Vec<_>>(), [2]);
+        assert_eq!(state.history.len(), 1);
+        assert_eq!((state.history[0].id, state.history[0].resolution), (answered.id, QuestionResolution::Answered));
+        assert!(state.route(answered.id).is_err());
+    }
+
+    #[test]
+    fn the_history_keeps_the_newest_entries() {
+        let mut state = QueueState::default();
+        for _ in 0..HISTORY_LIMIT + 5 {
+            let question = state.push("kind", serde_json::Value::Null, 0, TTL);
+            state.resolve(&question, QuestionResolution::Answered);
+        }
+        assert_eq!(state.history.len(), HISTORY_LIMIT);
+        assert_eq!(state.history.front().unwrap().id, 6);
+        assert_eq!(state.history.back().unwrap().id, (HISTORY_LIMIT + 5) as QuestionId);
+    }
+}
@@ -0,0 +1,344 @@
+//! Revert loop detection
+//!
+//! The NVIDIA App's settings sync can rewrite a profile within a minute of
+//! Nvidiot changing it, and blacklisting again only restarts the tug-of-war.
+//! Every ShadowPlay write Nvidiot makes is remembered per executable by the
+//! settings layer's single write path, and a
+//! watcher compares recently written executables against a fresh read of the
+//! driver store. When the value was flipped back soon after our write often
+//! enough, the executable is flagged in the manifest and the user is told what
+//! is going on. With `escalate_to_override_profile` set, a blacklist caught in
+//! a loop is also moved into a dedicated profile of its own, which the syncing
+//! tool is less likely to touch. Detection is pure over the recorded events.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use crate::config::{self, RevertLoopDetection};
+use crate::journal::{self, JournalCategory, JournalSeverity};
+use crate::manifest::{self, now_ms};
+use crate::nvapi::resolve::executable_key;
+
+/// Emitted when an executable is first flagged
+pub const REVERT_LOOP_DETECTED_EVENT: &str = "revert-loop-detected";
+
+/// Events older than this are forgotten
+const HISTORY_MS: u64 = 24 * 60 * 60 * 1000;
+/// Most events kept per executable
+const MAX_EVENTS: usize = 32;
+
+const GUIDANCE: &str = "Another tool keeps restoring this game's ShadowPlay setting shortly after Nvidiot changes it. \
+    This is usually the NVIDIA App syncing its own game settings: turn off automatic optimization for the game in \
+    the NVIDIA App, then blacklist it again.";
+
+const ESCALATED_GUIDANCE: &str = "Another tool keeps restoring this game's ShadowPlay setting shortly after Nvidiot \
+    changes it, so Nvidiot moved the game into a profile of its own and blacklisted it there. If it is still undone, \
+    turn off automatic optimization for the game in the NVIDIA App.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LoopEventKind {
+    /// Nvidiot wrote the setting
+    Wrote,
+    /// The driver store was found holding the other value
+    Reverted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoopEvent {
+    pub at_ms: u64,
+    pub kind: LoopEventKind,
+    /// Whether ShadowPlay was disabled by the write, or found disabled by the check
+    pub disabled: bool,
+}
+
+/// Payload of the revert-loop-detected event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertLoopDetected {
+    pub executable: String,
+    /// Quick reverts seen in the last day
+    pub reverts: u32,
+    pub window_minutes: u32,
+    /// Override profile the executable was moved to, when escalation is enabled and succeeded
+    pub escalated_to: Option<String>,
+    pub guidance: String,
+}
+
+/// Whether a flagged loop should be escalated: only blacklists that keep
+/// being undone, and only when the user opted in
+pub fn should_escalate(settings: &RevertLoopDetection, written_disabled: bool) -> bool {
+    settings.escalate_to_override_profile && written_disabled
+}
+
+/// Reverts that undid one of our writes within `window_ms` of it. A write
+/// followed by another write (the user toggling twice) is never a revert.
+pub fn count_quick_reverts(events: &[LoopEvent], window_ms: u64) -> u32 {
+    let mut last_write: Option<&LoopEvent> = None;
+    let mut reverts = 0;
+    for event in events {
+        match event.kind {
+            LoopEventKind::Wrote => last_write = Some(event),
+            LoopEventKind::Reverted => {
+                let quick = last_write.is_some_and(|w| {
+                    w.disabled != event.disabled && event.at_ms.saturating_sub(w.at_ms) <= window_ms
+                });
+                if quick {
+                    reverts += 1;
+                }
+                // Only the first revert after a write counts against it
+                last_write = None;
+            }
+        }
+    }
+    reverts
+}
+
+/// Whether the events show a revert loop under the given settings
+pub fn is_revert_loop(events: &[LoopEvent], settings: &RevertLoopDetection) -> bool {
+    count_quick_reverts(events, settings.window_minutes as u64 * 60 * 1000) >= settings.min_reverts.max(1)
+}
+
+struct History {
+    /// Display name as last written, with its events oldest first
+    executables: HashMap<String, (String, Vec<LoopEvent>)>,
+}
+
+static HISTORY: Lazy<Mutex<History>> = Lazy::new(|| Mutex::new(History { executables: HashMap::new() }));
+
+fn push(events: &mut Vec<LoopEvent>, event: LoopEvent) {
+    events.push(event);
+    let cutoff = event.at_ms.saturating_sub(HISTORY_MS);
+    events.retain(|e| e.at_ms >= cutoff);
+    if events.len() > MAX_EVENTS {
+        events.drain(..events.len() - MAX_EVENTS);
+    }
+}
+
+/// Remember a ShadowPlay write Nvidiot made
+pub fn record_write(executable: &str, disabled: bool) {
+    let mut history = HISTORY.lock().unwrap();
+    let (name, events) = history.executables
+        .entry(executable_key(executable))
+        .or_insert_with(|| (executable.to_string(), Vec::new()));
+    *name = executable.to_string();
+    push(events, LoopEvent { at_ms: now_ms(), kind: LoopEventKind::Wrote, disabled });
+}
+
+/// Executables whose last event is a write still inside the window, with the value written
+pub(crate) fn awaiting_check(window_ms: u64, now: u64) -> Vec<(String, bool)> {
+    HISTORY.lock().unwrap().executables.values()
+        .filter_map(|(name, events)| {
+            let last = events.last()?;
+            (last.kind == LoopEventKind::Wrote && now.saturating_sub(last.at_ms) <= window_ms)
+                .then(|| (name.clone(), last.disabled))
+        })
+        .collect()
+}
+
+/// Record a revert and return the executable's events if that completed a loop
+fn record_revert(executable: &str, disabled: bool, settings: &RevertLoopDetection) -> Option<Vec<LoopEvent>> {
+    let mut history = HISTORY.lock().unwrap();
+    let (_, events) = history.executables.get_mut(&executable_key(executable))?;
+    push(events, LoopEvent { at_ms: now_ms(), kind: LoopEventKind::Reverted, disabled });
+    is_revert_loop(events, settings).then(|| events.clone())
+}
+
+/// Move a blacklisted executable into its override profile, returning the
+/// profile's name. Failures are journaled and leave the executable where it was.
+#[cfg(target_os = "windows")]
+fn escalate(executable: &str) -> Option<String> {
+    use crate::nvapi::settings::blacklist_in_override_profile;
+    use crate::nvapi::types::ModifiedBy;
+
+    match blacklist_in_override_profile(executable) {
+        Ok(Some(profile_name)) => {
+            manifest::record_executable_or_journal(executable, false, ModifiedBy::Watchdog);
+            journal::append(JournalCategory::Mutation, JournalSeverity::Info, "revertLoop.escalated", serde_json::json!({
+                "executable": executable,
+                "profileName": profile_name,
+            }));
+            Some(profile_name)
+        }
+        Ok(None) => None,
+        Err(e) => {
+            journal::append(JournalCategory::Mutation, JournalSeverity::Warning, "revertLoop.escalationFailed", serde_json::json!({
+                "executable": executable,
+                "error": e.to_string(),
+            }));
+            None
+        }
+    }
+}
+
+/// Compare recently written executables against the driver store and flag loops
+#[cfg(target_os = "windows")]
+fn check(app: &AppHandle) {
+    use tauri::Emitter;
+    use crate::nvapi::ffi::SHADOWPLAY_DISABLED;
+    use crate::nvapi::session::with_fresh_session;
+    use crate::nvapi::settings::read_executable_shadowplay;
+
+    let settings = config::get().revert_loop_detection;
+    let window_ms = settings.window_minutes as u64 * 60 * 1000;
+    let pending = awaiting_check(window_ms, now_ms());
+    if pending.is_empty() {
+        return;
+    }
+
+    // A fresh session sees what other tools saved without touching ours
+    let Ok(observed) = with_fresh_session(|_| {
+        Ok(pending.into_iter()
+            .filter_map(|(executable, disabled)| {
+                let value = read_executable_shadowplay(&executable).ok()?;
+                Some((executable, disabled, value == Some(SHADOWPLAY_DISABLED)))
+            })
+            .collect::<Vec<_>>())
+    }) else {
+        return;
+    };
+
+    for (executable, written, found) in observed {
+        if written == found {
+            continue;
+        }
+        let Some(events) = record_revert(&executable, found, &settings) else {
+            continue;
+        };
+        if !manifest::flag_revert_loop(&executable) {
+            continue;
+        }
+        let escalated_to = if should_escalate(&settings, written) { escalate(&executable) } else { None };
+        let detected = RevertLoopDetected {
+            reverts: count_quick_reverts(&events, window_ms),
+            window_minutes: settings.window_minutes,
+            guidance: if escalated_to.is_some() { ESCALATED_GUIDANCE } else { GUIDANCE }.to_string(),
+            escalated_to,
+            executable,
+        };
+        journal::append(JournalCategory::Watcher, JournalSeverity::Warning, "revertLoop.detected", &detected);
+        let _ = app.emit(REVERT_LOOP_DETECTED_EVENT, detected);
+    }
+}
+
+/// Start the task that checks recent writes for reverts
+#[cfg(target_os = "windows")]
+pub fn start_watcher(app: &AppHandle) {
+    use tauri::Manager;
+    use crate::automation;
+    use crate::nvapi::health::is_driver_changing;
+    use crate::tasks::TaskSupervisor;
+
+    let supervisor = app.state::<TaskSupervisor>();
+    let app = app.clone();
+    supervisor.spawn("revert-loop-watcher", move |token| {
+        while token.sleep(std::time::Duration::from_secs(config::get().revert_loop_detection.check_interval_secs.max(1) as u64)) {
+            // Escalation moves DRS entries, so a pause skips the whole check
+            if !automation::is_paused() && !is_driver_changing() {
+                check(&app);
+            }
+        }
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn start_watcher(_app: &AppHandle) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: u64 = 60 * 1000;
+
+    fn wrote(minute: u64, disabled: bool) -> LoopEvent {
+        LoopEvent { at_ms: minute * MINUTE, kind: LoopEventKind::Wrote, disabled }
+    }
+
+    fn reverted(minute: u64, disabled: bool) -> LoopEvent {
+        LoopEvent { at_ms: minute * MINUTE, kind: LoopEventKind::Reverted, disabled }
+    }
+
+    /// `cycles` blacklists, each flipped back `delay` minutes later
+    fn tug_of_war(cycles: u64, delay: u64) -> Vec<LoopEvent> {
+        (0..cycles)
+            .flat_map(|i| [wrote(i * 10, true), reverted(i * 10 + delay, false)])
+            .collect()
+    }
+
+    fn settings(window_minutes: u32, min_reverts: u32) -> RevertLoopDetection {
+        RevertLoopDetection { window_minutes, min_reverts, ..RevertLoopDetection::default() }
+    }
+
+    #[test]
+    fn reverts_inside_the_window_count() {
+        assert_eq!(count_quick_reverts(&tug_of_war(3, 1), 5 * MINUTE), 3);
+        // Exactly at the window's edge is still quick
+        assert_eq!(count_quick_reverts(&tug_of_war(2, 5), 5 * MINUTE), 2);
+    }
+
+    #[test]
+    fn reverts_outside_the_window_do_not_count() {
+        assert_eq!(count_quick_reverts(&tug_of_war(3, 6), 5 * MINUTE), 0);
+        assert!(!is_revert_loop(&tug_of_war(5, 6), &settings(5, 3)));
+    }
+
+    #[test]
+    fn a_write_followed_by_another_write_is_not_a_revert() {
+        // The user blacklisting and unblacklisting by hand
+        let toggling = [wrote(0, true), wrote(1, false), wrote(2, true), wrote(3, false)];
+        assert_eq!(count_quick_reverts(&toggling, 5 * MINUTE), 0);
+        assert!(!is_revert_loop(&toggling, &settings(5, 1)));
+    }
+
+    #[test]
+    fn a_check_that_finds_our_own_value_is_not_a_revert() {
+        assert_eq!(count_quick_reverts(&[wrote(0, true), reverted(1, true)], 5 * MINUTE), 0);
+    }
+
+    #[test]
+    fn only_the_first_revert_after_a_write_counts() {
+        let events = [wrote(0, true), reverted(1, false), reverted(2, false), reverted(3, false)];
+        assert_eq!(count_quick_reverts(&events, 5 * MINUTE), 1);
+    }
+
+    #[test]
+    fn the_loop_is_flagged_at_the_threshold() {
+        let detection = settings(5, 3);
+        assert!(!is_revert_loop(&tug_of_war(2, 1), &detection));
+        assert!(is_revert_loop(&tug_of_war(3, 1), &detection));
+        assert!(is_revert_loop(&tug_of_war(4, 1), &detection));
+    }
+
+    #[test]
+    fn only_undone_blacklists_escalate_and_only_when_enabled() {
+        let enabled = RevertLoopDetection { escalate_to_override_profile: true, ..RevertLoopDetection::default() };
+        assert!(should_escalate(&enabled, true));
+        // An unblacklist being undone has nothing to move into an override profile
+        assert!(!should_escalate(&enabled, false));
+        assert!(!should_escalate(&RevertLoopDetection::default(), true));
+    }
+
+    #[test]
+    fn a_zero_threshold_still_needs_one_revert() {
+        let detection = settings(5, 0);
+        assert!(!is_revert_loop(&[wrote(0, true)], &detection));
+        assert!(is_revert_loop(&tug_of_war(1, 1), &detection));
+    }
+
+    #[test]
+    fn history_is_trimmed_by_age_and_count() {
+        let mut events = Vec::new();
+        push(&mut events, LoopEvent { at_ms: 0, kind: LoopEventKind::Wrote, disabled: true });
+        push(&mut events, LoopEvent { at_ms: HISTORY_MS + 1, kind: LoopEventKind::Wrote, disabled: true });
+        assert_eq!(events.len(), 1);
+
+        for i in 0..(MAX_EVENTS as u64 * 2) {
+            push(&mut events, LoopEvent { at_ms: HISTORY_MS + 2 + i, kind: LoopEventKind::Wrote, disabled: true });
+        }
+        assert_eq!(events.len(), MAX_EVENTS);
+        assert_eq!(events.last().unwrap().at_ms, HISTORY_MS + 1 + MAX_EVENTS as u64 * 2);
+    }
+}
@@ -0,0 +1,198 @@
+//! Temporary unblacklisting
+//!
+//! Unblacklisting an application to record it manually is usually meant to be
+//! short-lived. A temporary unblacklist stores its expiry, and a background task
+//! blacklists the application again when it elapses. Expiries are persisted, so
+//! one that passed while we were closed is applied on the next start.
+//!
+//! A revert never overrides a manual decision: if the application was
+//! blacklisted again by other means, the revert is skipped and reported.
+
+use std::sync::Mutex;
+#[cfg(target_os = "windows")]
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use crate::journal::{self, JournalCategory, JournalSeverity};
+use crate::manifest::{self, now_ms};
+use crate::nvapi::applications::get_application_info;
+use crate::nvapi::settings::{blacklist_application, unblacklist_application};
+use crate::nvapi::types::ModifiedBy;
+use crate::storage::{self, StorageError};
+
+const REVERTS_FILE: &str = "pending-reverts.json";
+
+/// Emitted when an application is temporarily unblacklisted
+pub const REVERT_SCHEDULED_EVENT: &str = "revert-scheduled";
+/// Emitted when a pending revert comes due, whatever its outcome
+pub const REVERT_COMPLETED_EVENT: &str = "revert-completed";
+
+#[cfg(target_os = "windows")]
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A blacklist waiting to be re-applied
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingRevert {
+    pub executable: String,
+    pub created_ms: u64,
+    pub expires_ms: u64,
+}
+
+/// What happened when a revert came due
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RevertOutcome {
+    Reverted,
+    /// The application was already blacklisted again, so nothing was changed
+    SkippedManualChange,
+    Failed { error: String },
+}
+
+/// Payload of the revert-completed event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevertCompleted {
+    pub executable: String,
+    pub outcome: RevertOutcome,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct RevertStore {
+    pending: Vec<PendingRevert>,
+}
+
+static REVERTS: Lazy<Mutex<RevertStore>> = Lazy::new(|| Mutex::new(RevertStore::default()));
+
+/// Load pending reverts from app data
+pub fn load() -> Result<(), StorageError> {
+    if let Some(store) = storage::load::<RevertStore>(REVERTS_FILE)? {
+        *REVERTS.lock().unwrap() = store;
+    }
+    Ok(())
+}
+
+/// Pending reverts, soonest first
+pub fn get_pending_reverts() -> Vec<PendingRevert> {
+    let mut pending = REVERTS.lock().unwrap().pending.clone();
+    pending.sort_by_key(|r| r.expires_ms);
+    pending
+}
+
+/// Unblacklist an executable and blacklist it again after `duration_minutes`.
+/// Scheduling again for the same executable replaces the earlier expiry.
+pub fn temporarily_unblacklist(app: &AppHandle, executable: &str, duration_minutes: u32) -> Result<PendingRevert, String> {
+    if duration_minutes == 0 {
+        return Err("Duration must be at least one minute".to_string());
+    }
+
+    // An executable without a DRS entry was never unblacklisted, so there is nothing to revert
+    let result = unblacklist_application(executable).map_err(|e| e.to_string())?;
+    if !result.success {
+        return Err(result.message);
+    }
+    manifest::touch(executable, ModifiedBy::Manual);
+
+    let now = now_ms();
+    let revert = PendingRevert {
+        executable: executable.to_string(),
+        created_ms: now,
+        expires_ms: now + u64::from(duration_minutes) * 60 * 1000,
+    };
+
+    let mut store = REVERTS.lock().unwrap();
+    store.pending.retain(|r| !r.executable.eq_ignore_ascii_case(executable));
+    store.pending.push(revert.clone());
+    storage::save(REVERTS_FILE, &*store)?;
+    drop(store);
+
+    journal::append(JournalCategory::Mutation, JournalSeverity::Info, "revert.scheduled", &revert);
+    let _ = app.emit(REVERT_SCHEDULED_EVENT, revert.clone());
+    Ok(revert)
+}
+
+/// Drop a pending revert without touching the application's current state
+pub fn cancel_revert(executable: &str) -> Result<bool, StorageError> {
+    let mut store = REVERTS.lock().unwrap();
+    let before = store.pending.len();
+    store.pending.retain(|r| !r.executable.eq_ignore_ascii_case(executable));
+    if store.pending.len() == before {
+        return Ok(false);
+    }
+    storage::save(REVERTS_FILE, &*store)?;
+    Ok(true)
+}
+
+/// Re-apply the blacklist unless the user already did
+fn revert(executable: &str) -> RevertOutcome {
+    match get_application_info(executable) {
+        Ok(info) if info.is_blacklisted => return RevertOutcome::SkippedManualChange,
+        Ok(_) => {}
+        Err(e) => return RevertOutcome::Failed { error: e.to_string() },
+    }
+
+    match blacklist_application(executable) {
+        Ok(_) => {
+            manifest::touch(executable, ModifiedBy::Watchdog);
+            RevertOutcome::Reverted
+        }
+        Err(e) => RevertOutcome::Failed { error: e.to_string() },
+    }
+}
+
+/// Apply every revert whose expiry has passed
+pub fn process_due(app: &AppHandle) {
+    let now = now_ms();
+    let due: Vec<PendingRevert> = {
+        let mut store = REVERTS.lock().unwrap();
+        let (due, remaining) = store.pending.drain(..).partition(|r| r.expires_ms <= now);
+        store.pending = remaining;
+        due
+    };
+    if due.is_empty() {
+        return;
+    }
+
+    for pending in &due {
+        let completed = RevertCompleted {
+            executable: pending.executable.clone(),
+            outcome: revert(&pending.executable),
+        };
+        let failed = matches!(completed.outcome, RevertOutcome::Failed { .. });
+        journal::append(JournalCategory::Mutation, journal::severity_for(failed), "revert.completed", &completed);
+        let _ = app.emit(REVERT_COMPLETED_EVENT, completed);
+    }
+
+    let store = REVERTS.lock().unwrap();
+    storage::save_or_journal(REVERTS_FILE, &*store);
+}
+
+/// Start the background task that applies reverts as they come due.
+/// Reverts that expired while the app was closed are applied on the first tick.
+#[cfg(target_os = "windows")]
+pub fn start_scheduler(app: &AppHandle) {
+    use tauri::Manager;
+    use crate::automation;
+    use crate::tasks::TaskSupervisor;
+
+    let supervisor = app.state::<TaskSupervisor>();
+    let app = app.clone();
+    supervisor.spawn("revert-scheduler", move |token| {
+        // Due reverts wait until the driver is back, or automation resumes,
+        // rather than failing or being dropped
+        let ready = || !automation::is_paused() && !crate::nvapi::health::is_driver_changing();
+        if ready() {
+            process_due(&app);
+        }
+        while token.sleep(POLL_INTERVAL) {
+            if ready() {
+                process_due(&app);
+            }
+        }
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn start_scheduler(_app: &AppHandle) {}
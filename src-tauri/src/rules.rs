@@ -0,0 +1,736 @@
+//! Automation rules
+//!
+//! Rules flip ShadowPlay for an executable when their condition starts holding,
+//! and optionally revert it when the condition stops holding. Whether a rule's
+//! action is currently applied is persisted, so after a crash the next
+//! evaluation still sees the rule as active and reverts it.
+//!
+//! Automatic profile creation is rate limited per rule and globally. A rule that
+//! hits a limit is suspended until resumed, and windows whose executables live in
+//! denied directories are never acted on.
+//!
+//! Signer conditions need an Authenticode check, which is slow, so it goes
+//! through the metadata queue and the rule is skipped until the result lands.
+
+use std::sync::Mutex;
+#[cfg(target_os = "windows")]
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use crate::config::{self, RuleLimits};
+use crate::journal::{self, JournalCategory, JournalSeverity};
+use crate::manifest;
+use crate::metadata::{self, MetadataFields, MetadataKind};
+use crate::privacy;
+#[cfg(target_os = "windows")]
+use crate::automation;
+use crate::nvapi::applications::find_application;
+use crate::nvapi::error::NvApiError;
+use crate::nvapi::settings;
+use crate::nvapi::types::ModifiedBy;
+use crate::storage::{self, StorageError};
+
+const RULES_FILE: &str = "rules.json";
+
+/// Emitted when a rule's condition is entered or left
+pub const RULE_TRIGGERED_EVENT: &str = "rule-triggered";
+/// Emitted when the focused window's title changes
+pub const FOCUS_TITLE_CHANGED_EVENT: &str = "focus-title-changed";
+/// Emitted when a rule is suspended for exceeding a creation quota
+pub const RULE_SUSPENDED_EVENT: &str = "rule-suspended";
+
+/// Rolling window for creation quotas
+const QUOTA_WINDOW_MS: u64 = 60 * 60 * 1000;
+
+#[cfg(target_os = "windows")]
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Condition evaluated continuously against open windows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RuleCondition {
+    /// The executable's window title contains `pattern` (case-insensitive)
+    #[serde(rename_all = "camelCase")]
+    TitleMatches { pattern: String, while_focused_only: bool },
+    /// A running copy of the executable is signed by a publisher whose name
+    /// contains `substring` (case-insensitive)
+    SignerMatches { substring: String },
+}
+
+/// Action applied when a rule's condition is entered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleAction {
+    Blacklist,
+    Unblacklist,
+}
+
+impl RuleAction {
+    pub fn inverse(self) -> Self {
+        match self {
+            RuleAction::Blacklist => RuleAction::Unblacklist,
+            RuleAction::Unblacklist => RuleAction::Blacklist,
+        }
+    }
+}
+
+/// Rule definition as supplied by the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewRule {
+    pub name: String,
+    pub executable: String,
+    pub condition: RuleCondition,
+    pub action: RuleAction,
+    /// Apply the inverse action when the condition stops holding
+    pub revert_on_exit: bool,
+}
+
+/// A stored automation rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    pub id: u32,
+    pub name: String,
+    pub executable: String,
+    pub condition: RuleCondition,
+    pub action: RuleAction,
+    pub revert_on_exit: bool,
+    pub enabled: bool,
+    /// Whether the rule's action is currently applied
+    pub active: bool,
+    /// Set when the rule exceeded a creation quota; cleared by resume_rule
+    #[serde(default)]
+    pub suspended: bool,
+}
+
+/// A profile created by a rule, kept for quota accounting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreationRecord {
+    pub rule_id: u32,
+    pub at_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct RuleStore {
+    next_id: u32,
+    rules: Vec<Rule>,
+    /// Creations within the quota window, persisted so limits survive restarts
+    creations: Vec<CreationRecord>,
+}
+
+static RULES: Lazy<Mutex<RuleStore>> = Lazy::new(|| Mutex::new(RuleStore::default()));
+
+/// A window as seen by the rules engine
+#[derive(Debug, Clone)]
+pub struct WindowObservation {
+    pub process_name: String,
+    pub window_title: String,
+    pub executable_path: Option<String>,
+    /// Publisher of the executable; only filled in when a signer rule needs it
+    pub signer: Option<SignerState>,
+}
+
+/// What is known about a window's executable signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignerState {
+    /// The check is queued and hasn't finished
+    Pending,
+    Unsigned,
+    Signed(String),
+}
+
+/// Change in whether a rule's condition holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Transition {
+    Entered,
+    Left,
+}
+
+/// Payload of the rule-triggered event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleTriggered {
+    pub rule_id: u32,
+    pub rule_name: String,
+    pub executable: String,
+    pub transition: Transition,
+    /// Action taken, if any
+    pub action: Option<RuleAction>,
+    pub error: Option<String>,
+}
+
+/// Quota a rule ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QuotaScope {
+    Rule,
+    Global,
+}
+
+/// Payload of the rule-suspended event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleSuspended {
+    pub rule_id: u32,
+    pub rule_name: String,
+    pub executable: String,
+    pub scope: QuotaScope,
+    /// Creations allowed per hour by the exceeded quota
+    pub limit: u32,
+}
+
+/// Payload of the focus-title-changed event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusTitleChanged {
+    pub process_name: String,
+    pub window_title: String,
+}
+
+/// Whether a rule's condition holds for the observed windows, or None if it
+/// can't be decided until a pending signature check finishes
+pub fn condition_holds(rule: &Rule, focused: Option<&WindowObservation>, windows: &[WindowObservation]) -> Option<bool> {
+    match &rule.condition {
+        // Titles are replaced by process names in privacy mode, so a match would be meaningless
+        RuleCondition::TitleMatches { .. } if privacy::enabled() => Some(false),
+        RuleCondition::TitleMatches { pattern, while_focused_only } => {
+            let pattern = pattern.to_lowercase();
+            let matches = |w: &WindowObservation| {
+                w.process_name.eq_ignore_ascii_case(&rule.executable)
+                    && w.window_title.to_lowercase().contains(&pattern)
+            };
+
+            Some(if *while_focused_only {
+                focused.is_some_and(matches)
+            } else {
+                windows.iter().any(matches)
+            })
+        }
+        RuleCondition::SignerMatches { substring } => {
+            let substring = substring.to_lowercase();
+            let mut pending = false;
+
+            for w in windows.iter().filter(|w| w.process_name.eq_ignore_ascii_case(&rule.executable)) {
+                match &w.signer {
+                    Some(SignerState::Signed(subject)) if subject.to_lowercase().contains(&substring) => {
+                        return Some(true);
+                    }
+                    Some(SignerState::Pending) => pending = true,
+                    _ => {}
+                }
+            }
+
+            if pending { None } else { Some(false) }
+        }
+    }
+}
+
+/// Fill in signer state for windows a signer rule targets, queueing checks
+/// for executables that haven't been verified yet
+fn attach_signers(app: &AppHandle, rules: &[Rule], windows: &mut [WindowObservation]) {
+    let targets: Vec<&str> = rules.iter()
+        .filter(|r| r.enabled && !r.suspended && matches!(r.condition, RuleCondition::SignerMatches { .. }))
+        .map(|r| r.executable.as_str())
+        .collect();
+    if targets.is_empty() {
+        return;
+    }
+
+    for w in windows.iter_mut() {
+        if !targets.iter().any(|t| w.process_name.eq_ignore_ascii_case(t)) {
+            continue;
+        }
+        let Some(path) = w.executable_path.clone() else {
+            continue;
+        };
+
+        let lookup = metadata::lookup(app, &[path], MetadataKind::Signer).remove(0);
+        w.signer = Some(match lookup.fields {
+            _ if lookup.pending => SignerState::Pending,
+            Some(MetadataFields::Signer(signer)) => SignerState::Signed(signer.subject),
+            _ => SignerState::Unsigned,
+        });
+    }
+}
+
+/// Compute the transition for a rule given whether it was active and whether its condition holds now
+pub fn transition(active: bool, holds: bool) -> Option<Transition> {
+    match (active, holds) {
+        (false, true) => Some(Transition::Entered),
+        (true, false) => Some(Transition::Left),
+        _ => None,
+    }
+}
+
+/// Prune the stored creation history outside the watcher tick. Returns the count removed.
+pub fn prune_history() -> Result<usize, StorageError> {
+    let mut store = RULES.lock().unwrap();
+    let before = store.creations.len();
+    prune_creations(&mut store.creations, manifest::now_ms());
+    let removed = before - store.creations.len();
+    if removed > 0 {
+        storage::save(RULES_FILE, &*store)?;
+    }
+    Ok(removed)
+}
+
+/// Drop creation records that fell out of the quota window
+pub fn prune_creations(creations: &mut Vec<CreationRecord>, now_ms: u64) {
+    creations.retain(|c| now_ms.saturating_sub(c.at_ms) < QUOTA_WINDOW_MS);
+}
+
+/// Check whether one more creation by a rule would exceed a quota.
+/// Expects records already pruned to the quota window.
+pub fn exceeded_quota(creations: &[CreationRecord], rule_id: u32, limits: &RuleLimits) -> Option<(QuotaScope, u32)> {
+    let rule_count = creations.iter().filter(|c| c.rule_id == rule_id).count() as u32;
+    if rule_count >= limits.per_rule_per_hour {
+        return Some((QuotaScope::Rule, limits.per_rule_per_hour));
+    }
+    if creations.len() as u32 >= limits.global_per_hour {
+        return Some((QuotaScope::Global, limits.global_per_hour));
+    }
+    None
+}
+
+/// Expand `%VAR%` references; unknown variables are left as-is
+pub(crate) fn expand_env_vars(value: &str) -> String {
+    let mut result = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find('%') {
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('%') else {
+            break;
+        };
+
+        result.push_str(&rest[..start]);
+        let name = &after[..end];
+        match std::env::var(name) {
+            Ok(expanded) => result.push_str(&expanded),
+            Err(_) => {
+                result.push('%');
+                result.push_str(name);
+                result.push('%');
+            }
+        }
+        rest = &after[end + 1..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+fn normalize_path(path: &str) -> String {
+    path.replace('/', "\\").trim_end_matches('\\').to_lowercase()
+}
+
+/// Whether a path lies inside one of the denied directories
+pub fn is_denied_path(path: &str, denied_directories: &[String]) -> bool {
+    let path = normalize_path(path);
+
+    denied_directories.iter().any(|dir| {
+        let expanded = expand_env_vars(dir);
+        // A variable that isn't set would otherwise match nothing useful
+        if expanded.contains('%') {
+            return false;
+        }
+
+        let dir = normalize_path(&expanded);
+        !dir.is_empty()
+            && path.len() > dir.len()
+            && path.starts_with(&dir)
+            && path.as_bytes()[dir.len()] == b'\\'
+    })
+}
+
+fn will_create_profile(executable: &str) -> bool {
+    matches!(find_application(executable), Err(NvApiError::ApplicationNotFound(_)))
+}
+
+fn apply_action(executable: &str, action: RuleAction, rule_name: &str) -> Result<(), String> {
+    let by = ModifiedBy::Rule { name: rule_name.to_string() };
+    match action {
+        RuleAction::Blacklist => {
+            settings::blacklist_application(executable).map_err(|e| e.to_string())?;
+            manifest::record_executable(executable, false, by)
+        }
+        RuleAction::Unblacklist => {
+            settings::unblacklist_application(executable).map_err(|e| e.to_string())?;
+            manifest::touch(executable, by);
+            Ok(())
+        }
+    }
+}
+
+/// Load rules from app data
+pub fn load() -> Result<(), StorageError> {
+    if let Some(store) = storage::load::<RuleStore>(RULES_FILE)? {
+        *RULES.lock().unwrap() = store;
+    }
+    Ok(())
+}
+
+/// Get all rules
+pub fn get_rules() -> Vec<Rule> {
+    RULES.lock().unwrap().rules.clone()
+}
+
+/// Add a rule
+pub fn add_rule(new_rule: NewRule) -> Result<Rule, StorageError> {
+    let mut store = RULES.lock().unwrap();
+    store.next_id += 1;
+
+    let rule = Rule {
+        id: store.next_id,
+        name: new_rule.name,
+        executable: new_rule.executable,
+        condition: new_rule.condition,
+        action: new_rule.action,
+        revert_on_exit: new_rule.revert_on_exit,
+        enabled: true,
+        active: false,
+        suspended: false,
+    };
+    store.rules.push(rule.clone());
+    storage::save(RULES_FILE, &*store)?;
+    Ok(rule)
+}
+
+/// Remove a rule, reverting its action first if it is active
+pub fn remove_rule(id: u32) -> Result<bool, String> {
+    let mut store = RULES.lock().unwrap();
+    let Some(index) = store.rules.iter().position(|r| r.id == id) else {
+        return Ok(false);
+    };
+
+    let rule = &store.rules[index];
+    if rule.active && rule.revert_on_exit {
+        apply_action(&rule.executable, rule.action.inverse(), &rule.name)?;
+    }
+
+    store.rules.remove(index);
+    storage::save(RULES_FILE, &*store)?;
+    Ok(true)
+}
+
+/// Enable or disable a rule
+pub fn set_rule_enabled(id: u32, enabled: bool) -> Result<Option<Rule>, StorageError> {
+    let mut store = RULES.lock().unwrap();
+    let Some(rule) = store.rules.iter_mut().find(|r| r.id == id) else {
+        return Ok(None);
+    };
+
+    rule.enabled = enabled;
+    let rule = rule.clone();
+    storage::save(RULES_FILE, &*store)?;
+    Ok(Some(rule))
+}
+
+/// Get rules suspended by the circuit breaker
+pub fn get_suspended_rules() -> Vec<Rule> {
+    RULES.lock().unwrap().rules.iter()
+        .filter(|r| r.suspended)
+        .cloned()
+        .collect()
+}
+
+/// Clear a rule's suspension so it is evaluated again
+pub fn resume_rule(id: u32) -> Result<Option<Rule>, StorageError> {
+    let mut store = RULES.lock().unwrap();
+    let Some(rule) = store.rules.iter_mut().find(|r| r.id == id) else {
+        return Ok(None);
+    };
+
+    rule.suspended = false;
+    let rule = rule.clone();
+    storage::save(RULES_FILE, &*store)?;
+    Ok(Some(rule))
+}
+
+/// Evaluate all enabled rules once and apply their transitions
+pub fn evaluate(app: &AppHandle, focused: Option<&WindowObservation>, windows: &[WindowObservation]) {
+    let limits = config::get().rule_limits;
+    let now = manifest::now_ms();
+
+    let allowed = |w: &&WindowObservation| {
+        !w.executable_path.as_deref()
+            .is_some_and(|p| is_denied_path(p, &limits.denied_directories))
+    };
+    let focused = focused.filter(allowed);
+    let mut windows: Vec<WindowObservation> = windows.iter().filter(allowed).cloned().collect();
+
+    let mut guard = RULES.lock().unwrap();
+    let store = &mut *guard;
+    attach_signers(app, &store.rules, &mut windows);
+    let before = store.creations.len();
+    prune_creations(&mut store.creations, now);
+    let mut changed = store.creations.len() != before;
+
+    for rule in store.rules.iter_mut().filter(|r| r.enabled && !r.suspended) {
+        if is_denied_path(&rule.executable, &limits.denied_directories) {
+            continue;
+        }
+
+        // Deferred until the signature check lands; a later tick picks it up
+        let Some(holds) = condition_holds(rule, focused, &windows) else {
+            continue;
+        };
+        let Some(transition) = transition(rule.active, holds) else {
+            continue;
+        };
+
+        let action = match transition {
+            Transition::Entered => Some(rule.action),
+            Transition::Left if rule.revert_on_exit => Some(rule.action.inverse()),
+            Transition::Left => None,
+        };
+
+        let creates = action == Some(RuleAction::Blacklist) && will_create_profile(&rule.executable);
+        if creates {
+            if let Some((scope, limit)) = exceeded_quota(&store.creations, rule.id, &limits) {
+                rule.suspended = true;
+                changed = true;
+                let suspended = RuleSuspended {
+                    rule_id: rule.id,
+                    rule_name: rule.name.clone(),
+                    executable: rule.executable.clone(),
+                    scope,
+                    limit,
+                };
+                journal::append(JournalCategory::Rule, JournalSeverity::Warning, "rule.suspended", &suspended);
+                let _ = app.emit(RULE_SUSPENDED_EVENT, suspended);
+                continue;
+            }
+        }
+
+        let error = action.and_then(|a| apply_action(&rule.executable, a, &rule.name).err());
+
+        // On failure keep the old state so the transition is retried next tick
+        if error.is_none() {
+            rule.active = transition == Transition::Entered;
+            changed = true;
+            if creates {
+                store.creations.push(CreationRecord { rule_id: rule.id, at_ms: now });
+            }
+        }
+
+        let triggered = RuleTriggered {
+            rule_id: rule.id,
+            rule_name: rule.name.clone(),
+            executable: rule.executable.clone(),
+            transition,
+            action,
+            error,
+        };
+        journal::append(JournalCategory::Rule, journal::severity_for(triggered.error.is_some()), "rule.triggered", &triggered);
+        let _ = app.emit(RULE_TRIGGERED_EVENT, triggered);
+    }
+
+    if changed {
+        storage::save_or_journal(RULES_FILE, &*store);
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn needs_all_windows() -> bool {
+    RULES.lock().unwrap().rules.iter().any(|r| {
+        r.enabled && matches!(
+            r.condition,
+            RuleCondition::TitleMatches { while_focused_only: false, .. } | RuleCondition::SignerMatches { .. }
+        )
+    })
+}
+
+/// Start the background watcher that feeds focus changes to the rules engine
+#[cfg(target_os = "windows")]
+pub fn start_watcher(app: &AppHandle) {
+    use tauri::Manager;
+    use crate::tasks::TaskSupervisor;
+    use crate::windows::{get_focus_application, get_running_processes};
+    use crate::windows::processes::get_process_info;
+
+    let supervisor = app.state::<TaskSupervisor>();
+    let app = app.clone();
+    supervisor.spawn("rules-watcher", move |token| {
+        let mut last_focus: Option<(String, String)> = None;
+
+        while token.sleep(POLL_INTERVAL) {
+            if automation::is_paused() || crate::nvapi::health::is_driver_changing() {
+                continue;
+            }
+
+            let focused = get_focus_application().map(|f| WindowObservation {
+                executable_path: get_process_info(f.process_id).and_then(|(_, path)| path),
+                process_name: f.process_name,
+                window_title: f.window_title,
+                signer: None,
+            });
+
+            if let Some(f) = &focused {
+                let current = (f.process_name.clone(), f.window_title.clone());
+                if last_focus.as_ref() != Some(&current) {
+                    privacy::emit(&app, FOCUS_TITLE_CHANGED_EVENT, &FocusTitleChanged {
+                        process_name: current.0.clone(),
+                        window_title: current.1.clone(),
+                    });
+                    last_focus = Some(current);
+                }
+            }
+
+            let windows = if needs_all_windows() {
+                get_running_processes().into_iter()
+                    .map(|p| WindowObservation {
+                        process_name: p.process_name,
+                        window_title: p.window_title,
+                        executable_path: p.executable_path,
+                        signer: None,
+                    })
+                    .collect()
+            } else {
+                Vec::new()
+            };
+
+            evaluate(&app, focused.as_ref(), &windows);
+        }
+
+        // Persist suspension and quota state before exit
+        let store = RULES.lock().unwrap();
+        storage::save_or_journal(RULES_FILE, &*store);
+    });
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn start_watcher(_app: &AppHandle) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(condition: RuleCondition) -> Rule {
+        Rule {
+            id: 1,
+            name: "test".to_string(),
+            executable: "game.exe".to_string(),
+            condition,
+            action: RuleAction::Blacklist,
+            revert_on_exit: true,
+            enabled: true,
+            active: false,
+            suspended: false,
+        }
+    }
+
+    fn title_rule(pattern: &str, while_focused_only: bool) -> Rule {
+        rule(RuleCondition::TitleMatches { pattern: pattern.to_string(), while_focused_only })
+    }
+
+    fn window(process_name: &str, window_title: &str) -> WindowObservation {
+        WindowObservation {
+            process_name: process_name.to_string(),
+            window_title: window_title.to_string(),
+            executable_path: None,
+            signer: None,
+        }
+    }
+
+    #[test]
+    fn titles_match_ignoring_case_on_the_rule_executable_only() {
+        let rule = title_rule("Ranked", false);
+        let windows = [window("other.exe", "Ranked match"), window("GAME.EXE", "Lobby - RANKED queue")];
+        assert_eq!(condition_holds(&rule, None, &windows), Some(true));
+        assert_eq!(condition_holds(&rule, None, &windows[..1]), Some(false));
+        assert_eq!(condition_holds(&rule, None, &[]), Some(false));
+    }
+
+    #[test]
+    fn focused_only_rules_look_at_the_focused_window() {
+        let rule = title_rule("ranked", true);
+        let background = window("game.exe", "Ranked");
+        let focused = window("game.exe", "Main menu");
+
+        assert_eq!(condition_holds(&rule, Some(&focused), std::slice::from_ref(&background)), Some(false));
+        assert_eq!(condition_holds(&rule, Some(&background), &[]), Some(true));
+        assert_eq!(condition_holds(&rule, None, &[background]), Some(false));
+    }
+
+    #[test]
+    fn signer_rules_wait_for_pending_checks_unless_a_copy_already_matches() {
+        let rule = rule(RuleCondition::SignerMatches { substring: "valve".to_string() });
+        let signed = |signer: SignerState| WindowObservation { signer: Some(signer), ..window("game.exe", "") };
+
+        let matching = signed(SignerState::Signed("Valve Corp.".to_string()));
+        let pending = signed(SignerState::Pending);
+        let unsigned = signed(SignerState::Unsigned);
+        let other = WindowObservation { signer: Some(SignerState::Signed("Valve Corp.".to_string())), ..window("other.exe", "") };
+
+        assert_eq!(condition_holds(&rule, None, &[pending.clone(), matching]), Some(true));
+        assert_eq!(condition_holds(&rule, None, &[unsigned.clone(), pending]), None);
+        assert_eq!(condition_holds(&rule, None, &[unsigned, other]), Some(false));
+    }
+
+    #[test]
+    fn transitions_happen_only_on_a_change() {
+        assert_eq!(transition(false, true), Some(Transition::Entered));
+        assert_eq!(transition(true, false), Some(Transition::Left));
+        assert_eq!(transition(true, true), None);
+        assert_eq!(transition(false, false), None);
+    }
+
+    fn creations(rule_ids: &[u32], at_ms: u64) -> Vec<CreationRecord> {
+        rule_ids.iter().map(|&rule_id| CreationRecord { rule_id, at_ms }).collect()
+    }
+
+    #[test]
+    fn creations_outside_the_quota_window_are_pruned() {
+        let now = 10 * QUOTA_WINDOW_MS;
+        let mut records = creations(&[1], now - QUOTA_WINDOW_MS);
+        records.extend(creations(&[2], now - QUOTA_WINDOW_MS + 1));
+        records.extend(creations(&[3], now + 5));
+        prune_creations(&mut records, now);
+        assert_eq!(records.iter().map(|c| c.rule_id).collect::<Vec<_>>(), [2, 3]);
+    }
+
+    #[test]
+    fn the_rule_quota_is_checked_before_the_global_one() {
+        let limits = RuleLimits { per_rule_per_hour: 2, global_per_hour: 3, denied_directories: Vec::new() };
+        assert_eq!(exceeded_quota(&creations(&[1], 0), 1, &limits), None);
+        assert_eq!(exceeded_quota(&creations(&[1, 1, 2], 0), 1, &limits), Some((QuotaScope::Rule, 2)));
+        assert_eq!(exceeded_quota(&creations(&[2, 3, 4], 0), 1, &limits), Some((QuotaScope::Global, 3)));
+        assert_eq!(exceeded_quota(&creations(&[2, 3], 0), 1, &limits), None);
+    }
+
+    #[test]
+    fn environment_variables_expand_and_unknown_ones_stay() {
+        std::env::set_var("NVIDIOT_TEST_EXPAND", "C:\\Tools");
+        assert_eq!(expand_env_vars("%NVIDIOT_TEST_EXPAND%\\bin"), "C:\\Tools\\bin");
+        assert_eq!(expand_env_vars("%NVIDIOT_TEST_UNSET%\\bin"), "%NVIDIOT_TEST_UNSET%\\bin");
+        assert_eq!(expand_env_vars("100% sure"), "100% sure");
+    }
+
+    #[test]
+    fn denied_directories_match_whole_path_components() {
+        std::env::set_var("NVIDIOT_TEST_DENIED", "C:\\Windows");
+        let denied = vec![
+            "%NVIDIOT_TEST_DENIED%".to_string(),
+            "D:/Tools/".to_string(),
+            "%NVIDIOT_TEST_MISSING%\\Apps".to_string(),
+        ];
+
+        assert!(is_denied_path("c:\\windows\\system32\\app.exe", &denied));
+        assert!(is_denied_path("D:\\TOOLS\\capture.exe", &denied));
+        assert!(!is_denied_path("C:\\WindowsApps\\game.exe", &denied));
+        assert!(!is_denied_path("C:\\Windows", &denied));
+        assert!(!is_denied_path("%NVIDIOT_TEST_MISSING%\\Apps\\game.exe", &denied));
+    }
+
+    #[test]
+    fn actions_invert() {
+        assert_eq!(RuleAction::Blacklist.inverse(), RuleAction::Unblacklist);
+        assert_eq!(RuleAction::Unblacklist.inverse(), RuleAction::Blacklist);
+    }
+}
@@ -0,0 +1,124 @@
+//! Status summary for external monitoring
+//!
+//! A small, versioned snapshot meant to be scraped by scripts. Its field names
+//! are an external contract: add fields under a new version, never rename them.
+//! Every field comes from counters and caches, so a summary never triggers a DRS
+//! enumeration; values that need a cold cache are null.
+
+use serde::{Deserialize, Serialize};
+use crate::automation;
+use crate::manifest;
+use crate::metadata;
+use crate::nvapi::{ffi, session, settings, verify};
+
+/// Bumped whenever the summary's shape changes
+pub const STATUS_SUMMARY_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NvApiHealth {
+    Ok,
+    Error,
+}
+
+/// Most recent check of DRS against what Nvidiot expects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerificationSummary {
+    pub time: u64,
+    pub drifted: u32,
+}
+
+/// Field names are snake_case on purpose; they are part of the external format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusSummary {
+    pub version: u32,
+    pub nvapi: NvApiHealth,
+    pub session_generation: u64,
+    /// Profiles with ShadowPlay disabled, from the profile status cache
+    pub blacklisted_count: Option<u32>,
+    pub automation_paused: bool,
+    pub last_save_ok: Option<bool>,
+    pub last_verification: Option<VerificationSummary>,
+    pub worker_queue_depth: usize,
+    /// Managed executables another tool keeps reverting; added in version 2
+    pub revert_loops: u32,
+}
+
+/// Build the summary from counters and caches only
+pub fn get_status_summary() -> StatusSummary {
+    StatusSummary {
+        version: STATUS_SUMMARY_VERSION,
+        nvapi: if ffi::get_nvapi().is_ok() { NvApiHealth::Ok } else { NvApiHealth::Error },
+        session_generation: session::generation(),
+        blacklisted_count: settings::cached_blacklisted_profile_count(),
+        automation_paused: automation::is_paused(),
+        last_save_ok: session::last_save_ok(),
+        last_verification: verify::last_verification()
+            .map(|(time, drifted)| VerificationSummary { time, drifted }),
+        worker_queue_depth: metadata::queue_depth(),
+        revert_loops: manifest::entries().iter().filter(|e| e.revert_loop_detected).count() as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Changing this output breaks scripts scraping the summary
+    #[test]
+    fn the_external_format_is_stable() {
+        let summary = StatusSummary {
+            version: STATUS_SUMMARY_VERSION,
+            nvapi: NvApiHealth::Ok,
+            session_generation: 7,
+            blacklisted_count: None,
+            automation_paused: false,
+            last_save_ok: Some(true),
+            last_verification: Some(VerificationSummary { time: 1_700_000_000_000, drifted: 1 }),
+            worker_queue_depth: 3,
+            revert_loops: 0,
+        };
+        assert_eq!(serde_json::to_value(&summary).unwrap(), json!({
+            "version": 2,
+            "nvapi": "ok",
+            "session_generation": 7,
+            "blacklisted_count": null,
+            "automation_paused": false,
+            "last_save_ok": true,
+            "last_verification": { "time": 1_700_000_000_000u64, "drifted": 1 },
+            "worker_queue_depth": 3,
+            "revert_loops": 0,
+        }));
+        assert_eq!(serde_json::to_value(NvApiHealth::Error).unwrap(), json!("error"));
+    }
+}
+
+#[cfg(all(test, target_os = "windows"))]
+mod mock_tests {
+    use super::*;
+    use crate::nvapi::ffi::SHADOWPLAY_DISABLED;
+    use crate::nvapi::mock::{self, Profile, Store};
+    use crate::nvapi::session::save_settings;
+    use crate::nvapi::settings::stage_blacklist;
+    use crate::nvapi::verify::verify_batch;
+
+    #[test]
+    fn the_last_batch_verification_is_reported() {
+        let _serial = mock::serial();
+        mock::install(Store::default()
+            .with_profile(Profile::new("Status Kept").with_application("status-kept.exe"))
+            .with_profile(Profile::new("Status Drifted").with_application("status-drifted.exe")));
+
+        stage_blacklist("status-kept.exe").unwrap();
+        save_settings().unwrap();
+        let verification = verify_batch(&[
+            ("status-kept.exe".to_string(), SHADOWPLAY_DISABLED),
+            ("status-drifted.exe".to_string(), SHADOWPLAY_DISABLED),
+        ]).unwrap();
+
+        let reported = get_status_summary().last_verification.expect("a verification ran");
+        assert_eq!(reported.time, verification.verified_at_ms);
+        assert_eq!(reported.drifted, 1);
+    }
+}
@@ -0,0 +1,452 @@
+//! Crash-resilient persistence for app-data files
+//!
+//! Stores are written to a `.tmp` sibling, fsynced, then renamed over the target.
+//! The previous good copy is kept as `.bak` and used when the primary fails to parse.
+//! A primary that is corrupt with no usable backup is moved aside as
+//! `.corrupt-<ms>` and the store starts from its defaults, so a bad file never
+//! keeps the app from starting.
+//!
+//! Writers of the same file serialize through a per-file lock held across the
+//! whole write, so a save that returns Ok has put its own bytes on disk and a
+//! failed write is reported to the caller that made it. Saves are mostly made
+//! from async commands, so a save on a runtime worker first hands the worker's
+//! other tasks off before it waits for the lock and the disk.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use once_cell::sync::{Lazy, OnceCell};
+use tokio::runtime::{Handle, RuntimeFlavor};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use thiserror::Error;
+use crate::journal::{self, JournalCategory, JournalSeverity};
+use crate::manifest::now_ms;
+
+/// Event emitted when a store was restored from its backup copy or reset to defaults
+pub const STORAGE_RECOVERED_EVENT: &str = "storage-recovered";
+
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("App data directory is not available: {0}")]
+    DataDirUnavailable(String),
+
+    #[error("Storage has not been initialized")]
+    NotInitialized,
+
+    #[error("Failed to access {0}: {1}")]
+    Io(String, std::io::Error),
+
+    #[error("Failed to serialize {0}: {1}")]
+    Serialize(String, serde_json::Error),
+}
+
+impl From<StorageError> for String {
+    fn from(err: StorageError) -> String {
+        err.to_string()
+    }
+}
+
+/// Payload of the storage-recovered event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StorageRecovered {
+    pub file: String,
+    /// Where the unreadable file was moved when no usable backup existed and
+    /// the store was reset to its defaults; None when the backup was used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quarantined_as: Option<String>,
+}
+
+/// Outcome of reading a store file
+#[derive(Debug)]
+enum Loaded<T> {
+    /// Neither the file nor its backup exists
+    Missing,
+    Primary(T),
+    /// The primary was unreadable and the backup was put back in its place
+    FromBackup(T),
+    /// Nothing usable; the bad file was moved here, if the move worked
+    Quarantined(Option<PathBuf>),
+}
+
+struct StorageContext {
+    /// None when set up by tests, which have no app to emit through
+    app: Option<AppHandle>,
+    data_dir: PathBuf,
+}
+
+static STORAGE: OnceCell<StorageContext> = OnceCell::new();
+
+/// One lock per file, held across its write. The map lock is only held to
+/// look a file's lock up, never across file I/O.
+static FILE_LOCKS: Lazy<Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn file_lock(path: &Path) -> Arc<tokio::sync::Mutex<()>> {
+    FILE_LOCKS.lock().unwrap()
+        .entry(path.to_path_buf())
+        .or_default()
+        .clone()
+}
+
+/// Resolve and create the app data directory. Called once from the Tauri setup hook.
+pub fn init(app: &AppHandle) -> Result<(), StorageError> {
+    let data_dir = app.path().app_data_dir()
+        .map_err(|e| StorageError::DataDirUnavailable(e.to_string()))?;
+    fs::create_dir_all(&data_dir)
+        .map_err(|e| StorageError::Io(data_dir.display().to_string(), e))?;
+
+    let _ = STORAGE.set(StorageContext {
+        app: Some(app.clone()),
+        data_dir,
+    });
+    Ok(())
+}
+
+/// Use `data_dir` for every store, for tests that run without an app
+#[cfg(test)]
+pub fn init_at(data_dir: PathBuf) {
+    let _ = STORAGE.set(StorageContext { app: None, data_dir });
+}
+
+/// Full path of a store file inside the app data directory
+pub fn data_path(file_name: &str) -> Result<PathBuf, StorageError> {
+    let ctx = STORAGE.get().ok_or(StorageError::NotInitialized)?;
+    Ok(ctx.data_dir.join(file_name))
+}
+
+fn sibling(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(extension);
+    path.with_file_name(name)
+}
+
+fn read_json<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let bytes = fs::read(path).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+fn io_err(path: &Path, e: std::io::Error) -> StorageError {
+    StorageError::Io(path.display().to_string(), e)
+}
+
+/// Write to `.tmp`, fsync, keep the current file as `.bak`, then rename into place
+fn write_atomically(path: &Path, bytes: &[u8]) -> Result<(), StorageError> {
+    let tmp_path = sibling(path, "tmp");
+    let bak_path = sibling(path, "bak");
+
+    {
+        let mut file = File::create(&tmp_path).map_err(|e| io_err(&tmp_path, e))?;
+        file.write_all(bytes).map_err(|e| io_err(&tmp_path, e))?;
+        file.sync_all().map_err(|e| io_err(&tmp_path, e))?;
+    }
+
+    if path.exists() {
+        fs::rename(path, &bak_path).map_err(|e| io_err(&bak_path, e))?;
+    }
+    fs::rename(&tmp_path, path).map_err(|e| io_err(path, e))?;
+
+    Ok(())
+}
+
+/// Write bytes to a file once any other save of it has finished
+fn save_bytes(path: &Path, bytes: Vec<u8>) -> Result<(), StorageError> {
+    let lock = file_lock(path);
+    let write = || {
+        let _guard = lock.blocking_lock();
+        write_atomically(path, &bytes)
+    };
+
+    match Handle::try_current().map(|handle| handle.runtime_flavor()) {
+        // On a worker, move its queued tasks to another thread before blocking
+        Ok(RuntimeFlavor::MultiThread) => tokio::task::block_in_place(write),
+        // A single-threaded runtime can't be blocked in place, so wait on a thread of our own
+        Ok(_) => std::thread::scope(|scope| scope.spawn(write).join().unwrap_or_else(|e| std::panic::resume_unwind(e))),
+        Err(_) => write(),
+    }
+}
+
+/// Atomically write a value to a path, keeping the previous version as `.bak`
+fn save_at<T: Serialize>(path: &Path, value: &T) -> Result<(), StorageError> {
+    let bytes = serde_json::to_vec_pretty(value)
+        .map_err(|e| StorageError::Serialize(path.display().to_string(), e))?;
+    save_bytes(path, bytes)
+}
+
+/// Atomically write a store file, keeping the previous version as `.bak`
+pub fn save<T: Serialize>(file_name: &str, value: &T) -> Result<(), StorageError> {
+    save_at(&data_path(file_name)?, value)
+}
+
+/// Save state whose caller can't act on a failure, such as a cache or a
+/// history that is written again on the next change. Failures are journaled.
+pub fn save_or_journal<T: Serialize>(file_name: &str, value: &T) {
+    if let Err(e) = save(file_name, value) {
+        journal::append(JournalCategory::Storage, JournalSeverity::Warning, "storage.save_failed", serde_json::json!({
+            "file": file_name,
+            "error": e.to_string(),
+        }));
+    }
+}
+
+/// Move an unreadable file out of the way, returning where it went
+fn quarantine(path: &Path, at_ms: u64) -> Option<PathBuf> {
+    let target = sibling(path, &format!("corrupt-{}", at_ms));
+    fs::rename(path, &target).ok().map(|_| target)
+}
+
+/// Read a store file, falling back to its `.bak` copy when the primary is
+/// missing or corrupt. With neither usable, the bad files are quarantined.
+fn load_at<T: DeserializeOwned>(path: &Path, at_ms: u64) -> Loaded<T> {
+    let bak_path = sibling(path, "bak");
+
+    if let Some(value) = read_json(path) {
+        return Loaded::Primary(value);
+    }
+
+    if !path.exists() && !bak_path.exists() {
+        return Loaded::Missing;
+    }
+
+    if let Some(value) = read_json::<T>(&bak_path) {
+        // Put the good copy back in place so the next save doesn't rotate
+        // the corrupted file into the backup slot
+        if let Ok(bytes) = fs::read(&bak_path) {
+            let tmp_path = sibling(path, "tmp");
+            if fs::write(&tmp_path, bytes).is_ok() {
+                let _ = fs::rename(&tmp_path, path);
+            }
+        }
+        return Loaded::FromBackup(value);
+    }
+
+    // A corrupt backup would otherwise outlive the next save
+    if bak_path.exists() {
+        quarantine(&bak_path, at_ms);
+    }
+    if path.exists() {
+        Loaded::Quarantined(quarantine(path, at_ms))
+    } else {
+        Loaded::Quarantined(None)
+    }
+}
+
+/// Load a store file, falling back to its `.bak` copy when the primary is missing or corrupt.
+/// Returns `Ok(None)` when neither file exists, and also when neither is usable:
+/// the bad file is then moved aside, and the recovery is journaled and announced.
+pub fn load<T: DeserializeOwned>(file_name: &str) -> Result<Option<T>, StorageError> {
+    let path = data_path(file_name)?;
+    match load_at(&path, now_ms()) {
+        Loaded::Missing => Ok(None),
+        Loaded::Primary(value) => Ok(Some(value)),
+        Loaded::FromBackup(value) => {
+            notify_recovered(file_name, None);
+            Ok(Some(value))
+        }
+        Loaded::Quarantined(moved_to) => {
+            let moved_to = moved_to.and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()));
+            notify_recovered(file_name, Some(moved_to.unwrap_or_default()));
+            Ok(None)
+        }
+    }
+}
+
+fn notify_recovered(file_name: &str, quarantined_as: Option<String>) {
+    let recovered = StorageRecovered {
+        file: file_name.to_string(),
+        quarantined_as,
+    };
+    let (severity, code) = match recovered.quarantined_as {
+        Some(_) => (JournalSeverity::Error, "storage.quarantined"),
+        None => (JournalSeverity::Warning, "storage.recovered"),
+    };
+    journal::append(JournalCategory::Recovery, severity, code, &recovered);
+    if let Some(app) = STORAGE.get().and_then(|ctx| ctx.app.as_ref()) {
+        let _ = app.emit(STORAGE_RECOVERED_EVENT, recovered);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Store {
+        items: Vec<u32>,
+    }
+
+    /// Fresh empty directory for one test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nvidiot-storage-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn store(items: &[u32]) -> Store {
+        Store { items: items.to_vec() }
+    }
+
+    #[test]
+    fn missing_file_loads_as_missing() {
+        let path = test_dir("missing").join("store.json");
+        assert!(matches!(load_at::<Store>(&path, 1), Loaded::Missing));
+    }
+
+    #[test]
+    fn save_keeps_previous_version_as_backup() {
+        let path = test_dir("rotate").join("store.json");
+        save_at(&path, &store(&[1])).unwrap();
+        save_at(&path, &store(&[2])).unwrap();
+
+        assert_eq!(read_json::<Store>(&path), Some(store(&[2])));
+        assert_eq!(read_json::<Store>(&sibling(&path, "bak")), Some(store(&[1])));
+        assert!(!sibling(&path, "tmp").exists());
+    }
+
+    #[test]
+    fn truncated_file_falls_back_to_backup() {
+        let path = test_dir("truncated").join("store.json");
+        save_at(&path, &store(&[1, 2])).unwrap();
+        save_at(&path, &store(&[1, 2, 3])).unwrap();
+        let full = fs::read(&path).unwrap();
+        fs::write(&path, &full[..full.len() / 2]).unwrap();
+
+        match load_at::<Store>(&path, 1) {
+            Loaded::FromBackup(value) => assert_eq!(value, store(&[1, 2])),
+            other => panic!("expected the backup, got {:?}", other),
+        }
+        // The backup was put back in place
+        assert_eq!(read_json::<Store>(&path), Some(store(&[1, 2])));
+    }
+
+    #[test]
+    fn truncated_file_without_backup_is_quarantined() {
+        let dir = test_dir("no-backup");
+        let path = dir.join("store.json");
+        fs::write(&path, b"{\"items\": [1, 2").unwrap();
+
+        match load_at::<Store>(&path, 42) {
+            Loaded::Quarantined(Some(moved_to)) => {
+                assert_eq!(moved_to, dir.join("store.json.corrupt-42"));
+                assert!(moved_to.exists());
+            }
+            other => panic!("expected quarantine, got {:?}", other),
+        }
+        assert!(!path.exists());
+        // Starting over from defaults works
+        save_at(&path, &store(&[7])).unwrap();
+        assert!(matches!(load_at::<Store>(&path, 43), Loaded::Primary(value) if value == store(&[7])));
+    }
+
+    #[test]
+    fn corrupt_backup_is_quarantined_too() {
+        let dir = test_dir("bad-backup");
+        let path = dir.join("store.json");
+        fs::write(&path, b"").unwrap();
+        fs::write(sibling(&path, "bak"), b"not json").unwrap();
+
+        assert!(matches!(load_at::<Store>(&path, 5), Loaded::Quarantined(Some(_))));
+        assert!(!sibling(&path, "bak").exists());
+        assert!(dir.join("store.json.bak.corrupt-5").exists());
+    }
+
+    #[test]
+    fn missing_primary_uses_backup() {
+        let path = test_dir("backup-only").join("store.json");
+        save_at(&path, &store(&[3])).unwrap();
+        save_at(&path, &store(&[4])).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(load_at::<Store>(&path, 1), Loaded::FromBackup(value) if value == store(&[3])));
+    }
+
+    /// Run `saves` saves of distinct values from each of eight threads and
+    /// return every save's result
+    fn save_concurrently(path: &Path, saves: u32) -> Vec<Result<(), StorageError>> {
+        let handles: Vec<_> = (0..8u32)
+            .map(|i| {
+                let path = path.to_path_buf();
+                std::thread::spawn(move || {
+                    (0..saves).map(|j| save_at(&path, &store(&[i, j]))).collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+    }
+
+    #[test]
+    fn concurrent_saves_each_report_their_own_write() {
+        let path = test_dir("concurrent").join("store.json");
+        let results = save_concurrently(&path, 20);
+
+        assert_eq!(results.len(), 160);
+        assert!(results.iter().all(|r| r.is_ok()));
+        // The last write to finish is on disk, whole
+        let Loaded::Primary(value) = load_at::<Store>(&path, 1) else {
+            panic!("expected a readable primary");
+        };
+        assert_eq!(value.items.len(), 2);
+        assert!(!sibling(&path, "tmp").exists());
+    }
+
+    #[test]
+    fn concurrent_failing_saves_all_report_the_failure() {
+        // The directory is never created, so every write fails
+        let path = test_dir("unwritable").join("missing").join("store.json");
+        let results = save_concurrently(&path, 5);
+
+        assert_eq!(results.len(), 40);
+        assert!(results.iter().all(|r| matches!(r, Err(StorageError::Io(..)))));
+    }
+
+    #[test]
+    fn save_holds_the_file_lock_across_the_write() {
+        let path = test_dir("lock").join("store.json");
+        let lock = file_lock(&path);
+        let guard = lock.blocking_lock();
+
+        let writer = {
+            let path = path.clone();
+            std::thread::spawn(move || save_at(&path, &store(&[9])))
+        };
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        assert!(!path.exists(), "save wrote while another writer held the file");
+
+        drop(guard);
+        writer.join().unwrap().unwrap();
+        assert_eq!(read_json::<Store>(&path), Some(store(&[9])));
+    }
+
+    #[test]
+    fn a_save_waiting_on_a_runtime_worker_leaves_other_tasks_running() {
+        let path = test_dir("runtime").join("store.json");
+        let runtime = tokio::runtime::Builder::new_multi_thread().worker_threads(1).build().unwrap();
+        let lock = file_lock(&path);
+        let guard = lock.blocking_lock();
+
+        let (started_tx, started) = std::sync::mpsc::channel();
+        let save = {
+            let path = path.clone();
+            runtime.spawn(async move {
+                started_tx.send(()).unwrap();
+                save_at(&path, &store(&[5]))
+            })
+        };
+        started.recv().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(50));
+
+        // The only worker is waiting in the save, yet another task still runs
+        let (ran_tx, ran) = std::sync::mpsc::channel();
+        runtime.spawn(async move { ran_tx.send(()).unwrap() });
+        ran.recv_timeout(std::time::Duration::from_secs(5)).expect("the runtime stalled behind the save");
+        assert!(!path.exists());
+
+        drop(guard);
+        runtime.block_on(save).unwrap().unwrap();
+        assert_eq!(read_json::<Store>(&path), Some(store(&[5])));
+    }
+}
@@ -0,0 +1,193 @@
+//! Background task supervision
+//!
+//! Every long-running thread is spawned through the `TaskSupervisor` held in
+//! Tauri managed state. On exit the supervisor cancels every task, then joins
+//! them newest-first with a per-task timeout, so subsystems get a chance to
+//! unhook and flush before the DRS session is torn down.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use crate::journal::{self, JournalCategory, JournalSeverity};
+
+/// How long shutdown waits for each task to finish
+pub const TASK_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Longest a cancellable sleep goes without checking its token
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Shared flag a task polls to learn it should stop
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Sleep for `duration` unless cancelled first. Returns false if cancelled.
+    pub fn sleep(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        loop {
+            if self.is_cancelled() {
+                return false;
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return true;
+            }
+            thread::sleep((deadline - now).min(CANCEL_CHECK_INTERVAL));
+        }
+    }
+}
+
+struct Task {
+    name: String,
+    token: CancellationToken,
+    handle: JoinHandle<()>,
+}
+
+/// Registry of running background tasks
+#[derive(Default)]
+pub struct TaskSupervisor {
+    tasks: Mutex<Vec<Task>>,
+    shutting_down: AtomicBool,
+}
+
+impl TaskSupervisor {
+    /// Spawn a named task. The closure must return promptly once its token is
+    /// cancelled, doing its own cleanup first. Returns None during shutdown.
+    pub fn spawn<F>(&self, name: &str, f: F) -> Option<CancellationToken>
+    where
+        F: FnOnce(CancellationToken) + Send + 'static,
+    {
+        if self.shutting_down.load(Ordering::SeqCst) {
+            return None;
+        }
+
+        let token = CancellationToken::default();
+        let task_token = token.clone();
+        let handle = thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || f(task_token))
+            .ok()?;
+
+        let mut tasks = self.tasks.lock().unwrap();
+        // Short-lived tasks (e.g. queue workers) would otherwise pile up
+        tasks.retain(|t| !t.handle.is_finished());
+        tasks.push(Task { name: name.to_string(), token: token.clone(), handle });
+        Some(token)
+    }
+
+    /// Names of tasks still running, oldest first
+    pub fn running(&self) -> Vec<String> {
+        self.tasks.lock().unwrap().iter()
+            .filter(|t| !t.handle.is_finished())
+            .map(|t| t.name.clone())
+            .collect()
+    }
+
+    /// Cancel every task, then join them newest-first, waiting at most `timeout`
+    /// for each. Returns the names of tasks that didn't stop in time; those
+    /// threads are left detached.
+    pub fn shutdown(&self, timeout: Duration) -> Vec<String> {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        let tasks = std::mem::take(&mut *self.tasks.lock().unwrap());
+
+        for task in &tasks {
+            task.token.cancel();
+        }
+
+        let mut timed_out = Vec::new();
+        for task in tasks.into_iter().rev() {
+            let deadline = Instant::now() + timeout;
+            while !task.handle.is_finished() && Instant::now() < deadline {
+                thread::sleep(Duration::from_millis(10));
+            }
+
+            if task.handle.is_finished() {
+                if task.handle.join().is_err() {
+                    journal::append(JournalCategory::Task, JournalSeverity::Error, "task.panicked", serde_json::json!({
+                        "task": task.name,
+                    }));
+                }
+            } else {
+                journal::append(JournalCategory::Task, JournalSeverity::Warning, "task.stop_timed_out", serde_json::json!({
+                    "task": task.name,
+                    "timeoutMs": timeout.as_millis() as u64,
+                }));
+                timed_out.push(task.name);
+            }
+        }
+
+        timed_out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_cancelled_sleep_returns_early() {
+        let token = CancellationToken::default();
+        assert!(token.sleep(Duration::from_millis(1)));
+
+        token.cancel();
+        let started = Instant::now();
+        assert!(!token.sleep(Duration::from_secs(10)));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn shutdown_cancels_and_joins_cooperative_tasks() {
+        let supervisor = TaskSupervisor::default();
+        let stopped = Arc::new(AtomicBool::new(false));
+        let flag = stopped.clone();
+        supervisor.spawn("cooperative", move |token| {
+            while token.sleep(Duration::from_millis(5)) {}
+            flag.store(true, Ordering::SeqCst);
+        });
+
+        assert!(supervisor.shutdown(Duration::from_secs(5)).is_empty());
+        assert!(stopped.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn tasks_that_ignore_cancellation_are_reported() {
+        let supervisor = TaskSupervisor::default();
+        supervisor.spawn("stubborn", |_token| thread::sleep(Duration::from_millis(500)));
+        supervisor.spawn("cooperative", |token| while token.sleep(Duration::from_millis(5)) {});
+
+        assert_eq!(supervisor.shutdown(Duration::from_millis(20)), ["stubborn"]);
+    }
+
+    #[test]
+    fn running_lists_live_tasks_until_shutdown() {
+        let supervisor = TaskSupervisor::default();
+        supervisor.spawn("done", |_token| {});
+        supervisor.spawn("watcher", |token| while token.sleep(Duration::from_millis(5)) {});
+        supervisor.spawn("scheduler", |token| while token.sleep(Duration::from_millis(5)) {});
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while supervisor.running().len() > 2 && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(supervisor.running(), ["watcher", "scheduler"]);
+
+        assert!(supervisor.shutdown(Duration::from_secs(5)).is_empty());
+        assert!(supervisor.running().is_empty());
+    }
+
+    #[test]
+    fn nothing_is_spawned_once_shutdown_started() {
+        let supervisor = TaskSupervisor::default();
+        supervisor.shutdown(TASK_SHUTDOWN_TIMEOUT);
+        assert!(supervisor.spawn("late", |_token| {}).is_none());
+    }
+}
@@ -0,0 +1,597 @@
+//! Blacklist export and import
+//!
+//! Imports are conflict-aware. Every export and import records the state we
+//! wrote per executable; an entry whose current DRS state differs from both the
+//! import file and that record was changed by hand or by another tool since, so
+//! it is returned as a conflict instead of being overwritten. The caller then
+//! finishes the import with a pending token and a resolution per conflict.
+//! Applied changes are staged and saved together, then verified as one batch.
+
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Mutex;
+use std::time::Duration;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+use crate::journal::{self, JournalCategory};
+use crate::limits;
+use crate::manifest::{self, now_ms};
+use crate::nvapi::applications::get_application_info;
+use crate::nvapi::error::NvApiError;
+use crate::nvapi::ffi::{SHADOWPLAY_DISABLED, SHADOWPLAY_ENABLED};
+use crate::nvapi::resolve::executable_key;
+use crate::nvapi::session::{reload_settings, save_settings};
+use crate::nvapi::settings::{stage_blacklist, stage_unblacklist};
+use crate::nvapi::types::{BatchVerification, ModifiedBy};
+use crate::nvapi::verify::{self, verify_batch};
+use crate::questions::{Question, QuestionHandler, QuestionQueue};
+use crate::storage::{self, StorageError};
+
+const HISTORY_FILE: &str = "blacklist-history.json";
+
+/// How long a pending import waits for resolutions before it is dropped
+const PENDING_IMPORT_TTL_MS: u64 = 5 * 60 * 1000;
+
+/// Question kind asking how to resolve an import's conflicts
+pub const IMPORT_CONFLICTS_QUESTION: &str = "import-conflicts";
+/// Emitted with the ImportOutcome when a conflicts question is answered
+pub const IMPORT_RESOLVED_EVENT: &str = "import-resolved";
+
+/// One executable in a blacklist file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlacklistFileEntry {
+    pub executable: String,
+    pub blacklisted: bool,
+}
+
+/// On-disk blacklist export
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BlacklistFile {
+    pub entries: Vec<BlacklistFileEntry>,
+}
+
+/// Merge only touches entries in the file; Replace also unblacklists managed
+/// entries the file leaves out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportMode {
+    Merge,
+    Replace,
+}
+
+/// An entry changed outside Nvidiot since we last wrote it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportConflict {
+    pub executable: String,
+    pub current: bool,
+    pub imported: bool,
+    /// State we last exported or imported for this executable
+    pub recorded: bool,
+}
+
+/// How blacklist content passed in directly is encoded
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContentEncoding {
+    #[default]
+    Raw,
+    Base64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportResolution {
+    KeepLocal,
+    TakeImported,
+}
+
+/// Result of an import call. With conflicts, nothing was applied and `token`
+/// must be passed back with resolutions to finish.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportOutcome {
+    pub token: Option<u64>,
+    pub conflicts: Vec<ImportConflict>,
+    /// Number of entries whose state was changed
+    pub applied: u32,
+    pub failed: Vec<String>,
+    /// How the applied entries read back after the save; None if nothing was applied
+    /// or the check itself failed
+    pub verification: Option<BatchVerification>,
+    /// Why the read-back check couldn't run
+    pub verification_error: Option<String>,
+}
+
+/// Last state we wrote, keyed by lowercased executable
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct History {
+    entries: HashMap<String, bool>,
+}
+
+/// A planned change of one executable's state
+#[derive(Debug, Clone)]
+struct PlannedChange {
+    executable: String,
+    target: bool,
+}
+
+struct PendingImport {
+    changes: Vec<PlannedChange>,
+    conflicts: Vec<ImportConflict>,
+    expires_at_ms: u64,
+}
+
+static HISTORY: Lazy<Mutex<History>> = Lazy::new(|| Mutex::new(History::default()));
+static PENDING_IMPORTS: Lazy<Mutex<HashMap<u64, PendingImport>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+static NEXT_TOKEN: Lazy<Mutex<u64>> = Lazy::new(|| Mutex::new(1));
+
+/// Load the recorded history from app data
+pub fn load() -> Result<(), StorageError> {
+    if let Some(history) = storage::load::<History>(HISTORY_FILE)? {
+        *HISTORY.lock().unwrap() = history;
+    }
+    Ok(())
+}
+
+pub fn record_history(states: impl IntoIterator<Item = (String, bool)>) {
+    let mut history = HISTORY.lock().unwrap();
+    for (executable, blacklisted) in states {
+        history.entries.insert(executable_key(executable), blacklisted);
+    }
+    storage::save_or_journal(HISTORY_FILE, &*history);
+}
+
+/// Lowercased executables we have a recorded state for
+pub fn recorded_executables() -> Vec<String> {
+    HISTORY.lock().unwrap().entries.keys().cloned().collect()
+}
+
+/// Drop an executable's recorded state
+pub fn forget(executable: &str) {
+    let mut history = HISTORY.lock().unwrap();
+    if history.entries.remove(&executable_key(executable)).is_some() {
+        storage::save_or_journal(HISTORY_FILE, &*history);
+    }
+}
+
+/// Current blacklist state; executables without a DRS entry are not blacklisted
+fn current_state(executable: &str) -> Result<bool, NvApiError> {
+    match get_application_info(executable) {
+        Ok(app) => Ok(app.is_blacklisted),
+        Err(NvApiError::ApplicationNotFound(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Export the blacklist state of every managed executable and record it as history
+pub fn export_blacklist(dest_path: &str) -> Result<BlacklistFile, String> {
+    let mut file = BlacklistFile::default();
+    for entry in manifest::entries() {
+        let blacklisted = current_state(&entry.executable).map_err(|e| e.to_string())?;
+        file.entries.push(BlacklistFileEntry { executable: entry.executable, blacklisted });
+    }
+
+    let json = serde_json::to_string_pretty(&file).map_err(|e| e.to_string())?;
+    fs::write(dest_path, json).map_err(|e| format!("Failed to write {}: {}", dest_path, e))?;
+
+    record_history(file.entries.iter().map(|e| (e.executable.clone(), e.blacklisted)));
+    Ok(file)
+}
+
+/// Split the changes an import would make into safe changes and conflicts.
+/// `current` and `recorded` look up an executable's live and last-written state.
+fn detect_conflicts(
+    targets: &[BlacklistFileEntry],
+    current: impl Fn(&str) -> Result<bool, NvApiError>,
+    recorded: impl Fn(&str) -> Option<bool>,
+) -> Result<(Vec<PlannedChange>, Vec<ImportConflict>), NvApiError> {
+    let mut changes = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for target in targets {
+        let now = current(&target.executable)?;
+        if now == target.blacklisted {
+            continue;
+        }
+
+        match recorded(&target.executable) {
+            // Differs from both the file and what we last wrote: changed externally
+            Some(last) if last != now => conflicts.push(ImportConflict {
+                executable: target.executable.clone(),
+                current: now,
+                imported: target.blacklisted,
+                recorded: last,
+            }),
+            _ => changes.push(PlannedChange {
+                executable: target.executable.clone(),
+                target: target.blacklisted,
+            }),
+        }
+    }
+
+    Ok((changes, conflicts))
+}
+
+/// Entries that would be reported as conflicts if imported now
+pub fn find_conflicts(targets: &[BlacklistFileEntry]) -> Result<Vec<ImportConflict>, String> {
+    let history = HISTORY.lock().unwrap().entries.clone();
+    detect_conflicts(targets, current_state, |exe| history.get(&executable_key(exe)).copied())
+        .map(|(_, conflicts)| conflicts)
+        .map_err(|e| e.to_string())
+}
+
+fn parse_file(json: &str) -> Result<BlacklistFile, String> {
+    serde_json::from_str(json).map_err(|e| format!("Invalid blacklist file: {}", e))
+}
+
+fn read_file(path: &str) -> Result<BlacklistFile, String> {
+    let json = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    parse_file(&json)
+}
+
+/// Decode standard base64, ignoring whitespace and padding
+fn decode_base64(input: &str) -> Result<Vec<u8>, String> {
+    fn value(c: u8) -> Option<u32> {
+        match c {
+            b'A'..=b'Z' => Some((c - b'A') as u32),
+            b'a'..=b'z' => Some((c - b'a') as u32 + 26),
+            b'0'..=b'9' => Some((c - b'0') as u32 + 52),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None,
+        }
+    }
+
+    let mut out = Vec::with_capacity(input.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in input.bytes().filter(|c| !c.is_ascii_whitespace() && *c != b'=') {
+        let v = value(c).ok_or_else(|| format!("Invalid base64 character '{}'", c as char))?;
+        buffer = (buffer << 6) | v;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Ok(out)
+}
+
+/// Entries the import should end up with; Replace adds an unblacklist for every
+/// managed executable the file doesn't mention
+fn import_targets(file: BlacklistFile, mode: ImportMode) -> Vec<BlacklistFileEntry> {
+    let mut targets = file.entries;
+    if mode == ImportMode::Replace {
+        for entry in manifest::entries() {
+            if !targets.iter().any(|t| t.executable.eq_ignore_ascii_case(&entry.executable)) {
+                targets.push(BlacklistFileEntry { executable: entry.executable, blacklisted: false });
+            }
+        }
+    }
+    targets
+}
+
+fn apply_changes(changes: &[PlannedChange]) -> ImportOutcome {
+    let mut outcome = ImportOutcome { token: None, conflicts: Vec::new(), applied: 0, failed: Vec::new(), verification: None, verification_error: None };
+    let mut staged = Vec::new();
+
+    for change in changes {
+        let result = if change.target {
+            stage_blacklist(&change.executable)
+        } else {
+            // Without a DRS entry it's already not blacklisted
+            stage_unblacklist(&change.executable).map(|_| ())
+        };
+        match result {
+            Ok(()) => staged.push(change),
+            Err(e) => outcome.failed.push(format!("{}: {}", change.executable, e)),
+        }
+    }
+    if staged.is_empty() {
+        return outcome;
+    }
+
+    if let Err(e) = save_settings() {
+        // Don't leave half an import in the session
+        let _ = reload_settings();
+        outcome.failed.extend(staged.iter().map(|c| format!("{}: {}", c.executable, e)));
+        return outcome;
+    }
+
+    let expectations: Vec<(String, u32)> = staged.iter()
+        .map(|c| (c.executable.clone(), if c.target { SHADOWPLAY_DISABLED } else { SHADOWPLAY_ENABLED }))
+        .collect();
+    match verify_batch(&expectations) {
+        Ok(verification) => outcome.verification = Some(verification),
+        Err(e) => outcome.verification_error = Some(e.to_string()),
+    }
+
+    let mut written = Vec::new();
+    for change in staged {
+        manifest::touch(&change.executable, ModifiedBy::Import);
+        outcome.applied += 1;
+        written.push((change.executable.clone(), change.target));
+    }
+    record_history(written);
+    outcome
+}
+
+/// Emit the verification summary of an import that applied anything
+pub fn announce_verification(app: &AppHandle, outcome: &ImportOutcome) {
+    if let Some(verification) = &outcome.verification {
+        verify::emit_summary(app, verification);
+    }
+}
+
+fn prune_expired(pending: &mut HashMap<u64, PendingImport>) {
+    let now = now_ms();
+    pending.retain(|_, p| p.expires_at_ms > now);
+}
+
+/// Import a blacklist file. Applies it directly when nothing conflicts, otherwise
+/// holds it under a token and returns the conflicts for `resolve_import`.
+pub fn import_blacklist(path: &str, mode: ImportMode) -> Result<ImportOutcome, String> {
+    import_file(read_file(path)?, mode)
+}
+
+/// Import blacklist content read by the caller, for when the frontend can read
+/// the file but a path isn't accessible to the backend
+pub fn import_blacklist_from_str(content: &str, encoding: ContentEncoding, mode: ImportMode) -> Result<ImportOutcome, String> {
+    let file = match encoding {
+        ContentEncoding::Raw => parse_file(content)?,
+        ContentEncoding::Base64 => {
+            let bytes = decode_base64(content)?;
+            let json = String::from_utf8(bytes).map_err(|e| format!("Blacklist content is not UTF-8: {}", e))?;
+            parse_file(&json)?
+        }
+    };
+    import_file(file, mode)
+}
+
+fn import_file(file: BlacklistFile, mode: ImportMode) -> Result<ImportOutcome, String> {
+    // A file is an argument list like any other, whichever way it arrived
+    limits::check_batch("entries", file.entries.len())?;
+    for entry in &file.entries {
+        limits::check_name("executable", &entry.executable)?;
+    }
+    let targets = import_targets(file, mode);
+    let history = HISTORY.lock().unwrap().entries.clone();
+    let (changes, conflicts) = detect_conflicts(
+        &targets,
+        current_state,
+        |exe| history.get(&executable_key(exe)).copied(),
+    )
+    .map_err(|e| e.to_string())?;
+
+    if conflicts.is_empty() {
+        return Ok(apply_changes(&changes));
+    }
+
+    let token = {
+        let mut next = NEXT_TOKEN.lock().unwrap();
+        let token = *next;
+        *next += 1;
+        token
+    };
+    let mut pending = PENDING_IMPORTS.lock().unwrap();
+    prune_expired(&mut pending);
+    pending.insert(token, PendingImport {
+        changes,
+        conflicts: conflicts.clone(),
+        expires_at_ms: now_ms() + PENDING_IMPORT_TTL_MS,
+    });
+
+    Ok(ImportOutcome { token: Some(token), conflicts, applied: 0, failed: Vec::new(), verification: None, verification_error: None })
+}
+
+/// Finish a pending import. Conflicts without a resolution keep the local state.
+pub fn resolve_import(token: u64, resolutions: HashMap<String, ImportResolution>) -> Result<ImportOutcome, String> {
+    let pending = {
+        let mut pending = PENDING_IMPORTS.lock().unwrap();
+        prune_expired(&mut pending);
+        pending.remove(&token)
+            .ok_or_else(|| format!("Import {} has expired or does not exist", token))?
+    };
+
+    let mut changes = pending.changes;
+    for conflict in pending.conflicts {
+        let resolution = resolutions.iter()
+            .find(|(exe, _)| exe.eq_ignore_ascii_case(&conflict.executable))
+            .map(|(_, r)| *r)
+            .unwrap_or(ImportResolution::KeepLocal);
+        if resolution == ImportResolution::TakeImported {
+            changes.push(PlannedChange {
+                executable: conflict.executable,
+                target: conflict.imported,
+            });
+        }
+    }
+
+    // Skip anything that already reached its target while the import was pending
+    changes.retain(|c| current_state(&c.executable).map_or(true, |now| now != c.target));
+    Ok(apply_changes(&changes))
+}
+
+/// Drop a pending import without applying it
+pub fn cancel_import(token: u64) -> bool {
+    PENDING_IMPORTS.lock().unwrap().remove(&token).is_some()
+}
+
+/// Queue a question for an import held back by conflicts, so it can be finished
+/// even if the window was closed to the tray. The payload is the ImportOutcome.
+pub fn ask_about_conflicts(app: &AppHandle, outcome: &ImportOutcome) {
+    if outcome.token.is_none() {
+        return;
+    }
+    let Ok(payload) = serde_json::to_value(outcome) else {
+        return;
+    };
+    app.state::<QuestionQueue>().ask(app, IMPORT_CONFLICTS_QUESTION, payload, Duration::from_millis(PENDING_IMPORT_TTL_MS));
+}
+
+/// Journal an import that changed the store under `code`. An outcome still
+/// waiting on conflicts changed nothing and is journaled once resolved.
+pub fn journal_outcome(code: &str, outcome: &ImportOutcome) {
+    if outcome.token.is_some() {
+        return;
+    }
+    journal::append(JournalCategory::Import, journal::severity_for(!outcome.failed.is_empty() || outcome.verification_error.is_some()), code, serde_json::json!({
+        "applied": outcome.applied,
+        "failed": outcome.failed,
+        "verificationError": outcome.verification_error,
+    }));
+}
+
+/// Finishes the pending import with the resolutions given as the response
+pub struct ImportConflictsHandler;
+
+impl ImportConflictsHandler {
+    fn token(question: &Question) -> Option<u64> {
+        question.payload.get("token")?.as_u64()
+    }
+}
+
+impl QuestionHandler for ImportConflictsHandler {
+    fn answer(&self, app: &AppHandle, question: &Question, response: serde_json::Value) -> Result<(), String> {
+        let token = Self::token(question).ok_or("Question has no import token")?;
+        let resolutions: HashMap<String, ImportResolution> = serde_json::from_value(response)
+            .map_err(|e| format!("Invalid resolutions: {}", e))?;
+        let outcome = resolve_import(token, resolutions)?;
+        announce_verification(app, &outcome);
+        journal_outcome("import.resolved", &outcome);
+        let _ = app.emit(IMPORT_RESOLVED_EVENT, outcome);
+        Ok(())
+    }
+
+    fn expired(&self, _app: &AppHandle, question: &Question) {
+        if let Some(token) = Self::token(question) {
+            cancel_import(token);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(executable: &str, blacklisted: bool) -> BlacklistFileEntry {
+        BlacklistFileEntry { executable: executable.to_string(), blacklisted }
+    }
+
+    /// Hold a pending import under a token no other test uses
+    fn hold(token: u64, conflicts: Vec<ImportConflict>, expires_at_ms: u64) {
+        PENDING_IMPORTS.lock().unwrap().insert(token, PendingImport { changes: Vec::new(), conflicts, expires_at_ms });
+    }
+
+    #[test]
+    fn entries_are_classified_against_the_live_and_recorded_state() {
+        let live: HashMap<&str, bool> = [("same.exe", true), ("new.exe", false), ("ours.exe", false), ("theirs.exe", false)].into();
+        let recorded: HashMap<&str, bool> = [("ours.exe", false), ("theirs.exe", true)].into();
+        let targets = [target("same.exe", true), target("new.exe", true), target("ours.exe", true), target("theirs.exe", true)];
+
+        let (changes, conflicts) = detect_conflicts(
+            &targets,
+            |exe| Ok(live[exe]),
+            |exe| recorded.get(exe).copied(),
+        ).unwrap();
+
+        // Already in the imported state: nothing to do
+        let changed: Vec<&str> = changes.iter().map(|c| c.executable.as_str()).collect();
+        assert_eq!(changed, ["new.exe", "ours.exe"]);
+        assert!(changes.iter().all(|c| c.target));
+
+        // Unblacklisted since we last wrote it: someone else's change
+        assert_eq!(conflicts.len(), 1);
+        let conflict = &conflicts[0];
+        assert_eq!(conflict.executable, "theirs.exe");
+        assert_eq!((conflict.current, conflict.imported, conflict.recorded), (false, true, true));
+    }
+
+    #[test]
+    fn a_failed_state_read_fails_the_classification() {
+        let result = detect_conflicts(&[target("broken.exe", true)], |_| Err(NvApiError::NotSupported), |_| None);
+        assert!(matches!(result, Err(NvApiError::NotSupported)));
+    }
+
+    #[test]
+    fn expired_imports_are_pruned_and_refused() {
+        let token = u64::MAX - 1;
+        hold(token, Vec::new(), now_ms().saturating_sub(1));
+
+        let error = resolve_import(token, HashMap::new()).unwrap_err();
+        assert!(error.contains("expired"), "{}", error);
+        assert!(!PENDING_IMPORTS.lock().unwrap().contains_key(&token));
+    }
+
+    #[test]
+    fn a_token_finishes_one_import_only() {
+        let token = u64::MAX - 2;
+        let conflict = ImportConflict { executable: "kept.exe".to_string(), current: false, imported: true, recorded: true };
+        hold(token, vec![conflict], now_ms() + PENDING_IMPORT_TTL_MS);
+
+        // An unresolved conflict keeps the local state, so nothing is applied
+        let outcome = resolve_import(token, HashMap::new()).unwrap();
+        assert_eq!(outcome.applied, 0);
+        assert!(outcome.failed.is_empty());
+
+        assert!(resolve_import(token, HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn a_cancelled_token_cannot_be_resolved() {
+        let token = u64::MAX - 3;
+        hold(token, Vec::new(), now_ms() + PENDING_IMPORT_TTL_MS);
+
+        assert!(cancel_import(token));
+        assert!(!cancel_import(token));
+        assert!(resolve_import(token, HashMap::new()).is_err());
+    }
+    #[test]
+    fn base64_decodes_padded_and_unpadded_input() {
+        assert_eq!(decode_base64("bnZpZGlvdA==").unwrap(), b"nvidiot");
+        assert_eq!(decode_base64("bnZpZGlvdA").unwrap(), b"nvidiot");
+        assert_eq!(decode_base64("bnZpZGk=").unwrap(), b"nvidi");
+        assert_eq!(decode_base64("bnZpZGk").unwrap(), b"nvidi");
+        assert_eq!(decode_base64("bnZpZGlv").unwrap(), b"nvidio");
+        assert_eq!(decode_base64("+/8=").unwrap(), [0xfb, 0xff]);
+        assert_eq!(decode_base64("").unwrap(), b"");
+    }
+
+    #[test]
+    fn base64_ignores_embedded_whitespace() {
+        assert_eq!(decode_base64(" bnZp\r\nZGlv\tdA==\n").unwrap(), b"nvidiot");
+    }
+
+    #[test]
+    fn base64_refuses_invalid_characters() {
+        assert_eq!(decode_base64("bnZp-GlvdA==").unwrap_err(), "Invalid base64 character '-'");
+        // The URL-safe alphabet isn't standard base64
+        assert_eq!(decode_base64("bnZp_GlvdA").unwrap_err(), "Invalid base64 character '_'");
+        assert!(import_blacklist_from_str("not base64!", ContentEncoding::Base64, ImportMode::Merge).is_err());
+    }
+}
+
+#[cfg(all(test, target_os = "windows"))]
+mod mock_tests {
+    use super::*;
+    use crate::nvapi::ffi::SHADOWPLAY_SETTING_ID;
+    use crate::nvapi::mock::{self, Profile, Store};
+
+    /// `{"entries":[{"executable":"b64-game.exe","blacklisted":true}]}`, wrapped like a mail client would
+    const ENCODED: &str = "eyJlbnRyaWVzIjpbeyJleGVjdXRhYmxlIjoiYjY0LWdhbWUuZXhlIiwiYmxh\r\nY2tsaXN0ZWQiOnRydWV9XX0=";
+
+    #[test]
+    fn base64_content_round_trips_through_an_import() {
+        let _serial = mock::serial();
+        mock::install(Store::default().with_profile(Profile::new("B64 Game").with_application("b64-game.exe")));
+
+        let outcome = import_blacklist_from_str(ENCODED, ContentEncoding::Base64, ImportMode::Merge).unwrap();
+        assert_eq!(outcome.token, None);
+        assert_eq!(outcome.applied, 1);
+        assert!(outcome.failed.is_empty());
+        assert_eq!(mock::disk().profile("B64 Game").unwrap().dword(SHADOWPLAY_SETTING_ID), Some(SHADOWPLAY_DISABLED));
+    }
+}
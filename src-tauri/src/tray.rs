@@ -0,0 +1,118 @@
+//! Tray icon
+//!
+//! Keeps the panic button one click away while the window is hidden behind a
+//! game. The tooltip shows the latest capture transition, so a streamer can
+//! check at a glance whether capture is held off. Questions waiting for an
+//! answer are counted in the tooltip and, where the platform shows one, in
+//! the tray title next to the icon. A checkable item pauses all automation
+//! and follows the header's pause button.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, OnceLock};
+use tauri::menu::{CheckMenuItem, Menu, MenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::AppHandle;
+use crate::automation;
+use crate::panicbutton;
+
+const TRAY_ID: &str = "main";
+const PANIC_ITEM: &str = "panic-disable-capture";
+const RESTORE_ITEM: &str = "restore-capture-state";
+const PAUSE_ITEM: &str = "pause-automation";
+
+/// Latest capture transition shown in the tooltip
+static STATUS: Mutex<Option<String>> = Mutex::new(None);
+static PENDING_QUESTIONS: AtomicU32 = AtomicU32::new(0);
+/// Kept so pausing from the window updates the check mark
+static PAUSE: OnceLock<CheckMenuItem<tauri::Wry>> = OnceLock::new();
+
+fn tooltip(status: Option<&str>, pending_questions: u32) -> String {
+    let mut text = match status {
+        Some(status) => format!("nvidiot: {}", status),
+        None => "nvidiot".to_string(),
+    };
+    match pending_questions {
+        0 => {}
+        1 => text.push_str(" (1 question waiting)"),
+        n => text.push_str(&format!(" ({} questions waiting)", n)),
+    }
+    text
+}
+
+fn current_tooltip() -> String {
+    tooltip(STATUS.lock().unwrap().as_deref(), PENDING_QUESTIONS.load(Ordering::SeqCst))
+}
+
+/// Add the tray icon with the panic button's two actions and the pause switch
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let panic = MenuItem::with_id(app, PANIC_ITEM, "Disable capture now", true, None::<&str>)?;
+    let restore = MenuItem::with_id(app, RESTORE_ITEM, "Restore capture", true, None::<&str>)?;
+    let pause = CheckMenuItem::with_id(app, PAUSE_ITEM, "Pause automation", true, automation::is_paused(), None::<&str>)?;
+    let menu = Menu::with_items(app, &[&panic, &restore, &pause])?;
+    let _ = PAUSE.set(pause);
+
+    if panicbutton::state().engaged {
+        *STATUS.lock().unwrap() = Some("capture disabled by the panic button".to_string());
+    }
+
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip(current_tooltip())
+        .on_menu_event(|app, event| {
+            // Failures are shown in the tooltip by the panic button itself
+            match event.id.as_ref() {
+                PANIC_ITEM => { let _ = panicbutton::panic_disable_capture(app); }
+                RESTORE_ITEM => { let _ = panicbutton::restore_capture_state(app); }
+                PAUSE_ITEM => {
+                    // The menu toggles the check itself; put it back if persisting fails
+                    if automation::set_paused(app, !automation::is_paused()).is_err() {
+                        set_paused(automation::is_paused());
+                    }
+                }
+                _ => {}
+            }
+        });
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone());
+    }
+    builder.build(app)?;
+    Ok(())
+}
+
+/// Show a capture transition in the tray tooltip. Only the tooltip text is
+/// kept before `init`.
+pub fn notify(app: &AppHandle, message: &str) {
+    *STATUS.lock().unwrap() = Some(message.to_string());
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(current_tooltip()));
+    }
+}
+
+/// Check or uncheck the pause item. Does nothing before `init`.
+pub fn set_paused(paused: bool) {
+    if let Some(item) = PAUSE.get() {
+        let _ = item.set_checked(paused);
+    }
+}
+
+/// Badge the tray with the number of questions waiting for an answer
+pub fn set_pending_questions(app: &AppHandle, count: u32) {
+    PENDING_QUESTIONS.store(count, Ordering::SeqCst);
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(current_tooltip()));
+        let _ = tray.set_title((count > 0).then(|| count.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_tooltip_counts_waiting_questions() {
+        assert_eq!(tooltip(None, 0), "nvidiot");
+        assert_eq!(tooltip(Some("Capture restored"), 0), "nvidiot: Capture restored");
+        assert_eq!(tooltip(None, 1), "nvidiot (1 question waiting)");
+        assert_eq!(tooltip(Some("Capture restored"), 3), "nvidiot: Capture restored (3 questions waiting)");
+    }
+}
@@ -0,0 +1,175 @@
+//! Display and DPI helpers
+//!
+//! Converts window measurements from physical pixels to logical (96 DPI) units
+//! and identifies the monitors windows are shown on.
+
+use crate::nvapi::types::{MonitorInfo, WindowRect};
+
+#[cfg(target_os = "windows")]
+use windows::{
+    core::BOOL,
+    Win32::Foundation::{HWND, LPARAM, RECT},
+    Win32::Graphics::Gdi::{
+        EnumDisplayMonitors, GetMonitorInfoW, MonitorFromWindow, HDC, HMONITOR,
+        MONITORINFO, MONITORINFOEXW, MONITORINFOF_PRIMARY, MONITOR_DEFAULTTONEAREST,
+    },
+    Win32::UI::HiDpi::{GetDpiForMonitor, GetDpiForSystem, GetDpiForWindow, MDT_EFFECTIVE_DPI},
+    Win32::UI::WindowsAndMessaging::{GetDesktopWindow, GetShellWindow, GetWindowRect, GetWindowThreadProcessId},
+};
+
+/// DPI that corresponds to 100% scaling
+pub const DEFAULT_DPI: u32 = 96;
+
+/// Get the DPI for a window, falling back to the system DPI
+#[cfg(target_os = "windows")]
+pub fn get_dpi_for_window(hwnd: HWND) -> u32 {
+    unsafe {
+        // GetDpiForWindow returns 0 for invalid windows
+        let dpi = GetDpiForWindow(hwnd);
+        if dpi != 0 {
+            return dpi;
+        }
+
+        let dpi = GetDpiForSystem();
+        if dpi != 0 { dpi } else { DEFAULT_DPI }
+    }
+}
+
+/// Get a window's rect in logical pixels
+#[cfg(target_os = "windows")]
+pub fn get_logical_window_rect(hwnd: HWND, dpi: u32) -> Option<WindowRect> {
+    unsafe {
+        let mut rect = RECT::default();
+        GetWindowRect(hwnd, &mut rect).ok()?;
+        Some(to_logical(rect, dpi))
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn to_logical(rect: RECT, dpi: u32) -> WindowRect {
+    let dpi = if dpi == 0 { DEFAULT_DPI } else { dpi } as i64;
+    let scale = |v: i32| ((v as i64) * DEFAULT_DPI as i64 / dpi) as i32;
+
+    WindowRect {
+        left: scale(rect.left),
+        top: scale(rect.top),
+        right: scale(rect.right),
+        bottom: scale(rect.bottom),
+    }
+}
+
+/// Read a monitor's device name, primary flag and bounds in logical pixels
+#[cfg(target_os = "windows")]
+fn monitor_info(monitor: HMONITOR) -> Option<MonitorInfo> {
+    unsafe {
+        let mut info = MONITORINFOEXW::default();
+        info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+        if !GetMonitorInfoW(monitor, &mut info as *mut MONITORINFOEXW as *mut MONITORINFO).as_bool() {
+            return None;
+        }
+
+        let len = info.szDevice.iter().position(|&c| c == 0).unwrap_or(info.szDevice.len());
+        let (mut dpi_x, mut dpi_y) = (0, 0);
+        let dpi = match GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) {
+            Ok(()) => dpi_x,
+            Err(_) => DEFAULT_DPI,
+        };
+
+        Some(MonitorInfo {
+            device_name: String::from_utf16_lossy(&info.szDevice[..len]),
+            is_primary: info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0,
+            rect: to_logical(info.monitorInfo.rcMonitor, dpi),
+        })
+    }
+}
+
+/// Get the device name (e.g. `\\.\DISPLAY1`) of the monitor a window is mostly on
+#[cfg(target_os = "windows")]
+pub fn get_monitor_name_for_window(hwnd: HWND) -> Option<String> {
+    let monitor = unsafe { MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST) };
+    if monitor.is_invalid() {
+        return None;
+    }
+    monitor_info(monitor).map(|m| m.device_name)
+}
+
+/// Get the device name of the monitor showing the foreground window
+#[cfg(target_os = "windows")]
+pub fn get_foreground_window_monitor_name() -> Option<String> {
+    super::focus::get_foreground_window_hwnd().and_then(get_monitor_name_for_window)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn get_foreground_window_monitor_name() -> Option<String> {
+    None
+}
+
+/// List connected monitors in enumeration order
+#[cfg(target_os = "windows")]
+pub fn list_monitors() -> Vec<MonitorInfo> {
+    unsafe extern "system" fn collect(monitor: HMONITOR, _hdc: HDC, _rect: *mut RECT, data: LPARAM) -> BOOL {
+        let monitors = &mut *(data.0 as *mut Vec<MonitorInfo>);
+        if let Some(info) = monitor_info(monitor) {
+            monitors.push(info);
+        }
+        BOOL(1)
+    }
+
+    let mut monitors: Vec<MonitorInfo> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(None, None, Some(collect), LPARAM(&mut monitors as *mut _ as isize));
+    }
+    monitors
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn list_monitors() -> Vec<MonitorInfo> {
+    Vec::new()
+}
+
+/// Whether a window covers its whole monitor, as exclusive and borderless
+/// fullscreen games do. The desktop and shell windows never count.
+#[cfg(target_os = "windows")]
+pub fn is_window_fullscreen(hwnd: HWND) -> bool {
+    unsafe {
+        if hwnd == GetDesktopWindow() || hwnd == GetShellWindow() {
+            return false;
+        }
+
+        let mut rect = RECT::default();
+        if GetWindowRect(hwnd, &mut rect).is_err() {
+            return false;
+        }
+        let monitor = MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST);
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !GetMonitorInfoW(monitor, &mut info).as_bool() {
+            return false;
+        }
+
+        let screen = info.rcMonitor;
+        rect.left <= screen.left && rect.top <= screen.top && rect.right >= screen.right && rect.bottom >= screen.bottom
+    }
+}
+
+/// Whether the foreground window is fullscreen
+#[cfg(target_os = "windows")]
+pub fn is_foreground_fullscreen() -> bool {
+    super::focus::get_foreground_window_hwnd().is_some_and(is_window_fullscreen)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn is_foreground_fullscreen() -> bool {
+    false
+}
+
+/// Process id of the foreground window if that window is fullscreen
+#[cfg(target_os = "windows")]
+pub fn foreground_fullscreen_process_id() -> Option<u32> {
+    let hwnd = super::focus::get_foreground_window_hwnd().filter(|&hwnd| is_window_fullscreen(hwnd))?;
+    let mut process_id = 0u32;
+    unsafe { GetWindowThreadProcessId(hwnd, Some(&mut process_id)) };
+    (process_id != 0).then_some(process_id)
+}
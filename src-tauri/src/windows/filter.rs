@@ -0,0 +1,151 @@
+//! Process list filtering
+//!
+//! Decides which windows show up in the running process list. A base filter is
+//! chosen with `FilterType`; user-added process names and window classes are
+//! excluded on top of it.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+use once_cell::sync::Lazy;
+use crate::nvapi::types::FilterType;
+
+/// Decides whether a window's process belongs in the process list
+pub trait ProcessFilter {
+    fn should_include(&self, name: &str, class: &str, title: &str) -> bool;
+}
+
+/// Shell and session processes that are never games
+const SYSTEM_PROCESSES: &[&str] = &[
+    "explorer.exe",
+    "searchhost.exe",
+    "shellexperiencehost.exe",
+    "startmenuexperiencehost.exe",
+    "textinputhost.exe",
+    "applicationframehost.exe",
+    "systemsettings.exe",
+    "runtimebroker.exe",
+    "dwm.exe",
+    "csrss.exe",
+    "winlogon.exe",
+    "services.exe",
+    "lsass.exe",
+    "svchost.exe",
+];
+
+/// Further Windows and driver tools hidden by `ExcludeSystem`
+const EXTENDED_SYSTEM_PROCESSES: &[&str] = &[
+    "taskmgr.exe",
+    "mmc.exe",
+    "control.exe",
+    "lockapp.exe",
+    "securityhealthsystray.exe",
+    "ctfmon.exe",
+    "nvcontainer.exe",
+    "nvidia share.exe",
+    "nvidia overlay.exe",
+];
+
+/// NVIDIA tools that open their own DRS session. While one has unsaved changes
+/// our saves can fail until it is closed.
+pub const DRS_WRITING_TOOLS: &[&str] = &[
+    "nvcplui.exe",
+    "nvidiaprofileinspector.exe",
+    "nvidia app.exe",
+];
+
+/// Processes that host the NVIDIA overlay. ShadowPlay can't record while none runs.
+pub const NVIDIA_OVERLAY_PROCESSES: &[&str] = &[
+    "nvidia overlay.exe",
+    "nvidia share.exe",
+];
+
+/// Window classes of shell surfaces hidden by `ExcludeSystem`
+const SYSTEM_WINDOW_CLASSES: &[&str] = &[
+    "shell_traywnd",
+    "progman",
+    "workerw",
+    "windows.ui.core.corewindow",
+];
+
+fn in_list(list: &[&str], value: &str) -> bool {
+    list.iter().any(|entry| entry.eq_ignore_ascii_case(value))
+}
+
+/// Hides the built-in list of shell processes
+pub struct DefaultFilter;
+
+impl ProcessFilter for DefaultFilter {
+    fn should_include(&self, name: &str, _class: &str, _title: &str) -> bool {
+        !in_list(SYSTEM_PROCESSES, name)
+    }
+}
+
+/// Shows every titled, visible window
+pub struct IncludeAllFilter;
+
+impl ProcessFilter for IncludeAllFilter {
+    fn should_include(&self, _name: &str, _class: &str, _title: &str) -> bool {
+        true
+    }
+}
+
+/// Hides shell processes, common system tools and shell window classes
+pub struct ExcludeSystemFilter;
+
+impl ProcessFilter for ExcludeSystemFilter {
+    fn should_include(&self, name: &str, class: &str, _title: &str) -> bool {
+        !in_list(SYSTEM_PROCESSES, name)
+            && !in_list(EXTENDED_SYSTEM_PROCESSES, name)
+            && !in_list(SYSTEM_WINDOW_CLASSES, class)
+    }
+}
+
+/// A base filter plus user-added exclusions
+pub struct ConfigurableFilter {
+    base: Box<dyn ProcessFilter + Send>,
+    /// Lowercased process names to hide
+    excluded_names: HashSet<String>,
+    /// Lowercased window classes to hide
+    excluded_classes: HashSet<String>,
+}
+
+impl ProcessFilter for ConfigurableFilter {
+    fn should_include(&self, name: &str, class: &str, title: &str) -> bool {
+        !self.excluded_names.contains(&name.to_lowercase())
+            && !self.excluded_classes.contains(&class.to_lowercase())
+            && self.base.should_include(name, class, title)
+    }
+}
+
+static PROCESS_FILTER: Lazy<Mutex<ConfigurableFilter>> = Lazy::new(|| {
+    Mutex::new(ConfigurableFilter {
+        base: Box::new(DefaultFilter),
+        excluded_names: HashSet::new(),
+        excluded_classes: HashSet::new(),
+    })
+});
+
+/// Whether the active filter keeps this window's process
+pub fn should_include(name: &str, class: &str, title: &str) -> bool {
+    PROCESS_FILTER.lock().unwrap().should_include(name, class, title)
+}
+
+/// Replace the base filter, keeping user-added exclusions
+pub fn set_process_filter(filter_type: FilterType) {
+    let base: Box<dyn ProcessFilter + Send> = match filter_type {
+        FilterType::Default => Box::new(DefaultFilter),
+        FilterType::IncludeAll => Box::new(IncludeAllFilter),
+        FilterType::ExcludeSystem => Box::new(ExcludeSystemFilter),
+    };
+    PROCESS_FILTER.lock().unwrap().base = base;
+}
+
+/// Hide processes with this executable name. Returns false if already hidden.
+pub fn add_process_name_filter(name: &str) -> bool {
+    PROCESS_FILTER.lock().unwrap().excluded_names.insert(name.trim().to_lowercase())
+}
+
+/// Hide windows of this class. Returns false if already hidden.
+pub fn add_window_class_filter(class: &str) -> bool {
+    PROCESS_FILTER.lock().unwrap().excluded_classes.insert(class.trim().to_lowercase())
+}
@@ -2,7 +2,7 @@
 //!
 //! Uses Windows API to detect the currently focused application.
 
-use crate::nvapi::types::FocusApplication;
+use crate::nvapi::types::{FocusApplication, FocusApplicationExtended};
 
 #[cfg(target_os = "windows")]
 use windows::{
@@ -13,21 +13,60 @@ use windows::{
 };
 
 #[cfg(target_os = "windows")]
-use crate::nvapi::applications::find_application;
+use crate::nvapi::applications::get_application_info;
 #[cfg(target_os = "windows")]
-use crate::nvapi::settings::get_shadowplay_status;
-#[cfg(target_os = "windows")]
-use crate::nvapi::ffi::wchar_to_string;
+use super::display::{get_dpi_for_window, get_logical_window_rect, get_monitor_name_for_window};
 
 /// Get the currently focused application
 #[cfg(target_os = "windows")]
 pub fn get_focus_application() -> Option<FocusApplication> {
-    unsafe {
-        let hwnd: HWND = GetForegroundWindow();
-        if hwnd.0.is_null() {
-            return None;
-        }
+    get_foreground_window_hwnd().and_then(get_focus_from_hwnd)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn get_focus_application() -> Option<FocusApplication> {
+    None
+}
 
+/// Get the focused application with its CPU usage and GPU memory in use, both
+/// its own and the whole GPU's. Blocks for one CPU sample interval.
+#[cfg(target_os = "windows")]
+pub fn get_focus_application_extended() -> Option<FocusApplicationExtended> {
+    let focus = get_focus_application()?;
+    let cpu_usage_percent = super::processes::get_process_cpu_percent(&[focus.process_id])
+        .get(&focus.process_id)
+        .copied();
+    let gpu_memory_mb = super::nvml::gpu_processes()
+        .and_then(|processes| processes.get(&focus.process_id).copied().flatten());
+
+    Some(FocusApplicationExtended {
+        focus,
+        cpu_usage_percent,
+        gpu_memory_mb,
+        device_memory_used_mb: crate::nvapi::ffi::device_memory_used_mb(),
+    })
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn get_focus_application_extended() -> Option<FocusApplicationExtended> {
+    None
+}
+
+/// Get the foreground window, or None if no window has focus
+#[cfg(target_os = "windows")]
+pub fn get_foreground_window_hwnd() -> Option<HWND> {
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.0.is_null() {
+        None
+    } else {
+        Some(hwnd)
+    }
+}
+
+/// Get focus application details for a specific window
+#[cfg(target_os = "windows")]
+pub fn get_focus_from_hwnd(hwnd: HWND) -> Option<FocusApplication> {
+    unsafe {
         // Get process ID
         let mut process_id: u32 = 0;
         GetWindowThreadProcessId(hwnd, Some(&mut process_id));
@@ -41,6 +80,11 @@ pub fn get_focus_application() -> Option<FocusApplication> {
         let title_len = GetWindowTextW(hwnd, &mut title_buffer);
         let window_title = String::from_utf16_lossy(&title_buffer[..title_len as usize]);
 
+        // Get window placement in logical pixels
+        let dpi = get_dpi_for_window(hwnd);
+        let window_rect = get_logical_window_rect(hwnd, dpi);
+        let monitor_device_name = get_monitor_name_for_window(hwnd);
+
         // Get process name
         let process_name = get_process_name(process_id).unwrap_or_default();
 
@@ -49,32 +93,25 @@ pub fn get_focus_application() -> Option<FocusApplication> {
         }
 
         // Check if this application is in DRS
-        let (is_in_drs, profile_name, is_blacklisted) = match find_application(&process_name) {
-            Ok((profile_handle, _app)) => {
-                // Get profile name by looking up the profile info
-                let profile_name = get_profile_name_from_handle(profile_handle);
-                let is_blacklisted = get_shadowplay_status(profile_handle).ok();
-                (true, profile_name, is_blacklisted)
-            }
+        let (is_in_drs, profile_name, is_blacklisted) = match get_application_info(&process_name) {
+            Ok(app) => (true, Some(app.profile_name), Some(app.is_blacklisted)),
             Err(_) => (false, None, None),
         };
 
         Some(FocusApplication {
+            window_title: crate::privacy::sanitize_title(&process_name, window_title),
             process_name,
-            window_title,
             process_id,
             is_in_drs,
             profile_name,
             is_blacklisted,
+            dpi: Some(dpi),
+            window_rect,
+            monitor_device_name,
         })
     }
 }
 
-#[cfg(not(target_os = "windows"))]
-pub fn get_focus_application() -> Option<FocusApplication> {
-    None
-}
-
 #[cfg(target_os = "windows")]
 fn get_process_name(pid: u32) -> Option<String> {
     unsafe {
@@ -88,24 +125,3 @@ fn get_process_name(pid: u32) -> Option<String> {
         }
     }
 }
-
-#[cfg(target_os = "windows")]
-fn get_profile_name_from_handle(profile_handle: crate::nvapi::ffi::NvDRSProfileHandle) -> Option<String> {
-    use crate::nvapi::ffi::{get_nvapi, NvdrsProfile};
-    use crate::nvapi::session::get_session;
-    use crate::nvapi::error::NVAPI_OK;
-
-    let api = get_nvapi().ok()?;
-    let session = get_session().ok()?;
-    let get_profile_info = api.drs_get_profile_info?;
-
-    unsafe {
-        let mut profile_info = NvdrsProfile::default();
-        let status = get_profile_info(session, profile_handle, &mut profile_info);
-        if status == NVAPI_OK {
-            Some(wchar_to_string(&profile_info.profile_name))
-        } else {
-            None
-        }
-    }
-}
@@ -0,0 +1,45 @@
+//! Windows gaming features
+//!
+//! Reads the per-user Game Mode and Game DVR switches, which can compete with
+//! ShadowPlay for capture and hotkeys.
+
+#[cfg(target_os = "windows")]
+use windows::{
+    core::{w, PCWSTR},
+    Win32::System::Registry::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD},
+};
+
+/// Read a DWORD from HKEY_CURRENT_USER, or None if it is missing or not a DWORD
+#[cfg(target_os = "windows")]
+fn read_hkcu_dword(subkey: PCWSTR, value: PCWSTR) -> Option<u32> {
+    unsafe {
+        let mut data: u32 = 0;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        let status = RegGetValueW(
+            HKEY_CURRENT_USER,
+            subkey,
+            value,
+            RRF_RT_REG_DWORD,
+            None,
+            Some(&mut data as *mut u32 as *mut _),
+            Some(&mut size),
+        );
+        if status.is_ok() { Some(data) } else { None }
+    }
+}
+
+/// Whether Windows Game Mode is on. Windows treats a missing value as enabled.
+#[cfg(target_os = "windows")]
+pub fn is_windows_game_mode_enabled() -> bool {
+    read_hkcu_dword(w!("Software\\Microsoft\\GameBar"), w!("AutoGameModeEnabled"))
+        .map(|v| v != 0)
+        .unwrap_or(true)
+}
+
+/// Whether Game DVR background capture is on
+#[cfg(target_os = "windows")]
+pub fn is_game_dvr_enabled() -> bool {
+    read_hkcu_dword(w!("System\\GameConfigStore"), w!("GameDVR_Enabled"))
+        .map(|v| v != 0)
+        .unwrap_or(false)
+}
@@ -0,0 +1,270 @@
+//! Executable icons
+//!
+//! Extracts the first large icon embedded in an executable and returns it as
+//! a PNG data URL the frontend can put straight into an <img>. The PNG is
+//! written uncompressed (stored deflate blocks): icons are small, and this
+//! keeps an image codec out of the dependencies.
+
+#[cfg(target_os = "windows")]
+use windows::{
+    core::PCWSTR,
+    Win32::Graphics::Gdi::{
+        DeleteObject, GetDC, GetDIBits, GetObjectW, ReleaseDC, BITMAP, BITMAPINFO, BITMAPINFOHEADER, BI_RGB,
+        DIB_RGB_COLORS, HBITMAP,
+    },
+    Win32::UI::Shell::ExtractIconExW,
+    Win32::UI::WindowsAndMessaging::{DestroyIcon, GetIconInfo, HICON, ICONINFO},
+};
+
+const PNG_SIGNATURE: &[u8] = &[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// Largest payload of one stored deflate block
+const STORED_BLOCK_MAX: usize = 0xFFFF;
+
+const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in bytes {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+fn push_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend_from_slice(&crc.to_be_bytes());
+}
+
+/// zlib stream of `raw` in stored (uncompressed) blocks
+fn zlib_stored(raw: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = raw.chunks(STORED_BLOCK_MAX).peekable();
+    if blocks.peek().is_none() {
+        out.extend_from_slice(&[1, 0, 0, 0xFF, 0xFF]);
+    }
+    while let Some(block) = blocks.next() {
+        out.push(u8::from(blocks.peek().is_none()));
+        let len = block.len() as u16;
+        out.extend_from_slice(&len.to_le_bytes());
+        out.extend_from_slice(&(!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend_from_slice(&adler32(raw).to_be_bytes());
+    out
+}
+
+/// Encode 8-bit RGBA pixels, rows top to bottom, as a PNG
+pub fn encode_png(width: u32, height: u32, rgba: &[u8]) -> Vec<u8> {
+    let row = width as usize * 4;
+    let mut raw = Vec::with_capacity((row + 1) * height as usize);
+    for line in rgba.chunks(row).take(height as usize) {
+        // Filter type 0: the row as is
+        raw.push(0);
+        raw.extend_from_slice(line);
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&width.to_be_bytes());
+    header.extend_from_slice(&height.to_be_bytes());
+    // 8 bits per channel, RGBA, default compression, filtering and no interlace
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    push_chunk(&mut png, b"IHDR", &header);
+    push_chunk(&mut png, b"IDAT", &zlib_stored(&raw));
+    push_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+pub fn base64(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for group in bytes.chunks(3) {
+        let b = [group[0], *group.get(1).unwrap_or(&0), *group.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for (i, shift) in [18, 12, 6, 0].into_iter().enumerate() {
+            if i <= group.len() {
+                out.push(BASE64_ALPHABET[(n >> shift) as usize & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Turn the BGRA rows GDI returns into RGBA. Icons without an alpha channel
+/// come back fully transparent, so those are made opaque.
+fn bgra_to_rgba(mut pixels: Vec<u8>) -> Vec<u8> {
+    let has_alpha = pixels.chunks(4).any(|p| p[3] != 0);
+    for pixel in pixels.chunks_mut(4) {
+        pixel.swap(0, 2);
+        if !has_alpha {
+            pixel[3] = 0xFF;
+        }
+    }
+    pixels
+}
+
+/// Read a bitmap as 32-bit BGRA rows, top to bottom
+#[cfg(target_os = "windows")]
+unsafe fn read_bitmap(bitmap: HBITMAP) -> Option<(u32, u32, Vec<u8>)> {
+    // Monochrome icons have no color bitmap
+    if bitmap.is_invalid() {
+        return None;
+    }
+    let mut header = BITMAP::default();
+    let size = std::mem::size_of::<BITMAP>() as i32;
+    if GetObjectW(bitmap.into(), size, Some(&mut header as *mut BITMAP as *mut _)) == 0 {
+        return None;
+    }
+    let (width, height) = (header.bmWidth.unsigned_abs(), header.bmHeight.unsigned_abs());
+
+    let mut info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: width as i32,
+            // Negative height asks for top-down rows
+            biHeight: -(height as i32),
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut pixels = vec![0u8; width as usize * height as usize * 4];
+    let dc = GetDC(None);
+    let lines = GetDIBits(dc, bitmap, 0, height, Some(pixels.as_mut_ptr() as *mut _), &mut info, DIB_RGB_COLORS);
+    ReleaseDC(None, dc);
+    (lines == height as i32).then_some((width, height, pixels))
+}
+
+#[cfg(target_os = "windows")]
+unsafe fn icon_to_png(icon: HICON) -> Option<Vec<u8>> {
+    let mut info = ICONINFO::default();
+    GetIconInfo(icon, &mut info).ok()?;
+    let pixels = read_bitmap(info.hbmColor);
+    let _ = DeleteObject(info.hbmColor.into());
+    let _ = DeleteObject(info.hbmMask.into());
+
+    let (width, height, bgra) = pixels?;
+    Some(encode_png(width, height, &bgra_to_rgba(bgra)))
+}
+
+/// The executable's first large icon as a PNG data URL, or None when it has none
+#[cfg(target_os = "windows")]
+pub fn get_icon_data_url(path: &str) -> Option<String> {
+    let wide_path: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+
+    unsafe {
+        let mut icon = HICON::default();
+        if ExtractIconExW(PCWSTR(wide_path.as_ptr()), 0, Some(&mut icon), None, 1) == 0 || icon.is_invalid() {
+            return None;
+        }
+        let png = icon_to_png(icon);
+        let _ = DestroyIcon(icon);
+        png.map(|png| format!("data:image/png;base64,{}", base64(&png)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Concatenated payloads of the stored blocks in a zlib stream, checking the framing
+    fn inflate_stored(zlib: &[u8]) -> Vec<u8> {
+        assert_eq!(&zlib[..2], &[0x78, 0x01]);
+        let mut at = 2;
+        let mut raw = Vec::new();
+        loop {
+            let last = zlib[at] == 1;
+            let len = u16::from_le_bytes([zlib[at + 1], zlib[at + 2]]);
+            let nlen = u16::from_le_bytes([zlib[at + 3], zlib[at + 4]]);
+            assert_eq!(len, !nlen);
+            raw.extend_from_slice(&zlib[at + 5..at + 5 + len as usize]);
+            at += 5 + len as usize;
+            if last {
+                break;
+            }
+        }
+        assert_eq!(&zlib[at..], &adler32(&raw).to_be_bytes());
+        raw
+    }
+
+    /// (type, data) of each chunk, checking lengths and CRCs
+    fn chunks(png: &[u8]) -> Vec<(String, Vec<u8>)> {
+        assert_eq!(&png[..8], PNG_SIGNATURE);
+        let mut at = 8;
+        let mut chunks = Vec::new();
+        while at < png.len() {
+            let len = u32::from_be_bytes(png[at..at + 4].try_into().unwrap()) as usize;
+            let body = &png[at + 4..at + 8 + len];
+            let crc = u32::from_be_bytes(png[at + 8 + len..at + 12 + len].try_into().unwrap());
+            assert_eq!(crc, crc32(body));
+            chunks.push((String::from_utf8(body[..4].to_vec()).unwrap(), body[4..].to_vec()));
+            at += 12 + len;
+        }
+        chunks
+    }
+
+    #[test]
+    fn checksums_match_known_values() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    #[test]
+    fn base64_pads_partial_groups() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"M"), "TQ==");
+        assert_eq!(base64(b"Ma"), "TWE=");
+        assert_eq!(base64(b"Man"), "TWFu");
+        assert_eq!(base64(&[0xFB, 0xFF]), "+/8=");
+    }
+
+    #[test]
+    fn a_png_holds_each_row_behind_a_filter_byte() {
+        let rgba = [1, 2, 3, 4, 5, 6, 7, 8];
+        let chunks = chunks(&encode_png(1, 2, &rgba));
+        let kinds: Vec<&str> = chunks.iter().map(|(kind, _)| kind.as_str()).collect();
+        assert_eq!(kinds, ["IHDR", "IDAT", "IEND"]);
+        assert_eq!(chunks[0].1, [0, 0, 0, 1, 0, 0, 0, 2, 8, 6, 0, 0, 0]);
+        assert_eq!(inflate_stored(&chunks[1].1), [0, 1, 2, 3, 4, 0, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn large_images_span_several_stored_blocks() {
+        let (width, height) = (200u32, 100u32);
+        let rgba: Vec<u8> = (0..width * height * 4).map(|i| i as u8).collect();
+        let chunks = chunks(&encode_png(width, height, &rgba));
+        let raw = inflate_stored(&chunks[1].1);
+        assert!(raw.len() > STORED_BLOCK_MAX);
+        let rows: Vec<&[u8]> = raw.chunks(width as usize * 4 + 1).collect();
+        assert_eq!(rows.len(), height as usize);
+        assert!(rows.iter().zip(rgba.chunks(width as usize * 4)).all(|(row, line)| row[0] == 0 && &row[1..] == line));
+    }
+
+    #[test]
+    fn pixels_are_swizzled_and_opaque_without_alpha() {
+        assert_eq!(bgra_to_rgba(vec![1, 2, 3, 0, 4, 5, 6, 0]), [3, 2, 1, 0xFF, 6, 5, 4, 0xFF]);
+        assert_eq!(bgra_to_rgba(vec![1, 2, 3, 0, 4, 5, 6, 9]), [3, 2, 1, 0, 6, 5, 4, 9]);
+    }
+}
@@ -0,0 +1,192 @@
+//! Keyboard input injection
+//!
+//! Sends key presses to another process's window, e.g. to trigger ShadowPlay's
+//! recording toggle (Alt+F9 by default) from the manager UI.
+
+#[cfg(target_os = "windows")]
+use windows::{
+    core::BOOL,
+    Win32::Foundation::{CloseHandle, HANDLE, HWND, LPARAM},
+    Win32::Security::{GetTokenInformation, TokenElevation, TOKEN_ELEVATION, TOKEN_QUERY},
+    Win32::System::Threading::{
+        AttachThreadInput, GetCurrentProcess, GetCurrentThreadId, OpenProcess, OpenProcessToken,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    },
+    Win32::System::SystemInformation::GetTickCount,
+    Win32::UI::Input::KeyboardAndMouse::{
+        GetLastInputInfo, SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS, KEYEVENTF_KEYUP,
+        LASTINPUTINFO, VIRTUAL_KEY,
+    },
+    Win32::UI::WindowsAndMessaging::{EnumWindows, GetWindowThreadProcessId, IsWindowVisible, SetForegroundWindow},
+};
+
+/// Virtual key code of the Alt key, the modifier of ShadowPlay's default hotkeys
+pub const VK_MENU: u32 = 0x12;
+/// Virtual key code of F9; Alt+F9 toggles ShadowPlay recording by default
+pub const VK_F9: u32 = 0x78;
+
+/// Whether a process token is elevated. None if the token can't be queried.
+#[cfg(target_os = "windows")]
+unsafe fn is_token_elevated(process: HANDLE) -> Option<bool> {
+    let mut token = HANDLE::default();
+    OpenProcessToken(process, TOKEN_QUERY, &mut token).ok()?;
+
+    let mut elevation = TOKEN_ELEVATION::default();
+    let mut returned: u32 = 0;
+    let result = GetTokenInformation(
+        token,
+        TokenElevation,
+        Some(&mut elevation as *mut _ as *mut _),
+        std::mem::size_of::<TOKEN_ELEVATION>() as u32,
+        &mut returned,
+    );
+    let _ = CloseHandle(token);
+
+    result.ok()?;
+    Some(elevation.TokenIsElevated != 0)
+}
+
+/// Whether a process runs elevated. None if it can't be opened or queried,
+/// which for most processes also means it's running at a higher integrity level.
+#[cfg(target_os = "windows")]
+pub fn is_process_elevated(pid: u32) -> Option<bool> {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        let elevated = is_token_elevated(handle);
+        let _ = CloseHandle(handle);
+        elevated
+    }
+}
+
+/// Whether Nvidiot itself runs elevated
+#[cfg(target_os = "windows")]
+pub fn is_current_process_elevated() -> bool {
+    unsafe { is_token_elevated(GetCurrentProcess()).unwrap_or(false) }
+}
+
+/// Callback data for finding a process's main window
+#[cfg(target_os = "windows")]
+struct FindWindowData {
+    process_id: u32,
+    hwnd: Option<HWND>,
+}
+
+#[cfg(target_os = "windows")]
+unsafe extern "system" fn find_window_callback(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let data = &mut *(lparam.0 as *mut FindWindowData);
+
+    if !IsWindowVisible(hwnd).as_bool() {
+        return BOOL(1);
+    }
+
+    let mut process_id: u32 = 0;
+    GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+    if process_id == data.process_id {
+        data.hwnd = Some(hwnd);
+        return BOOL(0); // Stop enumeration
+    }
+
+    BOOL(1)
+}
+
+/// First visible top-level window of a process
+#[cfg(target_os = "windows")]
+fn find_process_window(pid: u32) -> Option<HWND> {
+    let mut data = FindWindowData {
+        process_id: pid,
+        hwnd: None,
+    };
+
+    unsafe {
+        let _ = EnumWindows(Some(find_window_callback), LPARAM(&mut data as *mut _ as isize));
+    }
+
+    data.hwnd
+}
+
+#[cfg(target_os = "windows")]
+fn key_input(virtual_key: u32, flags: KEYBD_EVENT_FLAGS) -> INPUT {
+    INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(virtual_key as u16),
+                wScan: 0,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    }
+}
+
+/// Press and release a key in a process's window, holding the given modifiers.
+/// Refuses to inject into elevated processes unless Nvidiot is elevated too.
+#[cfg(target_os = "windows")]
+pub fn send_key_combo_to_pid(pid: u32, modifiers: &[u32], virtual_key: u32) -> Result<(), String> {
+    // UIPI would drop the input anyway; fail loudly instead of silently
+    if !is_current_process_elevated() && is_process_elevated(pid).unwrap_or(true) {
+        return Err(format!("Process {} is elevated or inaccessible; run Nvidiot as administrator to send it input", pid));
+    }
+
+    let hwnd = find_process_window(pid).ok_or_else(|| format!("Process {} has no visible window", pid))?;
+
+    let mut inputs: Vec<INPUT> = modifiers.iter()
+        .map(|&vk| key_input(vk, KEYBD_EVENT_FLAGS(0)))
+        .collect();
+    inputs.push(key_input(virtual_key, KEYBD_EVENT_FLAGS(0)));
+    inputs.push(key_input(virtual_key, KEYEVENTF_KEYUP));
+    inputs.extend(modifiers.iter().rev().map(|&vk| key_input(vk, KEYEVENTF_KEYUP)));
+
+    unsafe {
+        let target_thread = GetWindowThreadProcessId(hwnd, None);
+        let current_thread = GetCurrentThreadId();
+        let attached = target_thread != current_thread
+            && AttachThreadInput(current_thread, target_thread, true).as_bool();
+
+        let _ = SetForegroundWindow(hwnd);
+        let sent = SendInput(&inputs, std::mem::size_of::<INPUT>() as i32);
+
+        if attached {
+            let _ = AttachThreadInput(current_thread, target_thread, false);
+        }
+
+        if sent as usize != inputs.len() {
+            return Err(format!("Only {} of {} key events were delivered", sent, inputs.len()));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn send_key_combo_to_pid(_pid: u32, _modifiers: &[u32], _virtual_key: u32) -> Result<(), String> {
+    Err("Not supported on this platform".to_string())
+}
+
+/// Press and release a single key in a process's window
+pub fn send_keypress_to_pid(pid: u32, virtual_key: u32) -> Result<(), String> {
+    send_key_combo_to_pid(pid, &[], virtual_key)
+}
+
+/// Time since the last keyboard or mouse input in this session
+#[cfg(target_os = "windows")]
+pub fn idle_duration() -> Option<std::time::Duration> {
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+    unsafe {
+        if !GetLastInputInfo(&mut info).as_bool() {
+            return None;
+        }
+        // Both are 32-bit tick counts; wrapping_sub handles the 49-day rollover
+        let idle_ms = GetTickCount().wrapping_sub(info.dwTime);
+        Some(std::time::Duration::from_millis(idle_ms as u64))
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn idle_duration() -> Option<std::time::Duration> {
+    None
+}
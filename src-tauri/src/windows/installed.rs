@@ -0,0 +1,368 @@
+//! Installed programs
+//!
+//! Lists software from the uninstall registry keys and finds the executables
+//! under each install location, so games can be added to DRS without browsing
+//! for them. A scan reads the registry, walks the install folders on a few
+//! threads, then joins every executable found with DRS in one pass under a
+//! single session lock, so the walk never waits on NVAPI.
+
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use crate::nvapi::NvApiError;
+use crate::nvapi::applications::join_executables;
+use crate::nvapi::types::InstalledProgram;
+
+#[cfg(target_os = "windows")]
+use windows::{
+    core::{PCWSTR, PWSTR},
+    Win32::Foundation::ERROR_SUCCESS,
+    Win32::System::Registry::{
+        RegCloseKey, RegEnumKeyExW, RegOpenKeyExW, RegQueryValueExW, HKEY, HKEY_CURRENT_USER,
+        HKEY_LOCAL_MACHINE, KEY_READ, REG_EXPAND_SZ, REG_SZ, REG_VALUE_TYPE,
+    },
+};
+
+const UNINSTALL_KEYS: &[&str] = &[
+    "SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\Uninstall",
+    "SOFTWARE\\WOW6432Node\\Microsoft\\Windows\\CurrentVersion\\Uninstall",
+];
+
+/// Install folders of game stores and launchers, lowercased
+const GAME_LIBRARY_PATTERNS: &[&str] = &[
+    "\\steamapps\\common\\",
+    "\\epic games\\",
+    "\\gog galaxy\\games\\",
+    "\\gog games\\",
+    "\\ubisoft game launcher\\games\\",
+    "\\ea games\\",
+    "\\riot games\\",
+    "\\xboxgames\\",
+    "\\battle.net\\",
+];
+
+/// Executable name fragments that are never the game itself, lowercased
+const NON_GAME_EXECUTABLE_PATTERNS: &[&str] = &[
+    "unins",
+    "setup",
+    "install",
+    "redist",
+    "vcredist",
+    "dxsetup",
+    "crash",
+    "report",
+    "update",
+    "helper",
+    "uploader",
+    "cefprocess",
+    "webhelper",
+];
+
+/// Event emitted as install folders are walked
+pub const INSTALLED_SCAN_PROGRESS_EVENT: &str = "installed-scan-progress";
+
+/// Directory levels searched below the install location
+pub(super) const SCAN_DEPTH: usize = 2;
+/// Install folders walked at once; the walks are IO-bound
+const SCAN_WORKERS: usize = 4;
+/// Most executables listed per program
+const MAX_EXECUTABLES: usize = 20;
+
+fn is_game_library_path(path: &str) -> bool {
+    let path = path.to_lowercase();
+    GAME_LIBRARY_PATTERNS.iter().any(|pattern| path.contains(pattern))
+}
+
+fn is_candidate_executable(path: &Path) -> bool {
+    let Some(name) = path.file_name().map(|n| n.to_string_lossy().to_lowercase()) else {
+        return false;
+    };
+    name.ends_with(".exe") && !NON_GAME_EXECUTABLE_PATTERNS.iter().any(|pattern| name.contains(pattern))
+}
+
+/// Collect candidate executables under `dir`, shallowest first
+pub(super) fn scan_executables(dir: &Path, depth: usize, found: &mut Vec<String>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    let mut subdirs = Vec::new();
+    for entry in entries.filter_map(|e| e.ok()) {
+        if found.len() >= MAX_EXECUTABLES {
+            return;
+        }
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => subdirs.push(path),
+            Ok(t) if t.is_file() && is_candidate_executable(&path) => {
+                found.push(path.to_string_lossy().to_string());
+            }
+            _ => {}
+        }
+    }
+
+    if depth > 0 {
+        for subdir in subdirs {
+            scan_executables(&subdir, depth - 1, found);
+        }
+    }
+}
+
+/// Strip the quotes and trailing separators the uninstall keys often carry
+fn clean_path(value: &str) -> Option<String> {
+    let value = value.trim().trim_matches('"').trim_end_matches('\\');
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Read a string value from an open key
+#[cfg(target_os = "windows")]
+pub(super) unsafe fn read_string(key: HKEY, name: &str) -> Option<String> {
+    let name: Vec<u16> = name.encode_utf16().chain(std::iter::once(0)).collect();
+    let mut data = [0u16; 2048];
+    let mut data_len = (data.len() * 2) as u32;
+    let mut value_type = REG_VALUE_TYPE::default();
+
+    let status = RegQueryValueExW(
+        key,
+        PCWSTR(name.as_ptr()),
+        None,
+        Some(&mut value_type),
+        Some(data.as_mut_ptr() as *mut u8),
+        Some(&mut data_len),
+    );
+    if status != ERROR_SUCCESS || (value_type != REG_SZ && value_type != REG_EXPAND_SZ) {
+        return None;
+    }
+
+    let chars = &data[..(data_len as usize / 2).min(data.len())];
+    let end = chars.iter().position(|&c| c == 0).unwrap_or(chars.len());
+    let value = String::from_utf16_lossy(&chars[..end]);
+    (!value.trim().is_empty()).then_some(value)
+}
+
+/// Read every program under one uninstall key
+#[cfg(target_os = "windows")]
+fn read_uninstall_key(root: HKEY, path: &str) -> Vec<InstalledProgram> {
+    let mut programs = Vec::new();
+    let subkey: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+
+    unsafe {
+        let mut key = HKEY::default();
+        if RegOpenKeyExW(root, PCWSTR(subkey.as_ptr()), None, KEY_READ, &mut key) != ERROR_SUCCESS {
+            return programs;
+        }
+
+        let mut index = 0;
+        loop {
+            let mut name = [0u16; 256];
+            let mut name_len = name.len() as u32;
+            let status = RegEnumKeyExW(key, index, Some(PWSTR(name.as_mut_ptr())), &mut name_len, None, None, None, None);
+            if status != ERROR_SUCCESS {
+                break;
+            }
+            index += 1;
+
+            let mut entry = HKEY::default();
+            if RegOpenKeyExW(key, PCWSTR(name.as_ptr()), None, KEY_READ, &mut entry) != ERROR_SUCCESS {
+                continue;
+            }
+
+            // Updates and hidden components have no display name
+            if let Some(display_name) = read_string(entry, "DisplayName") {
+                let install_location = read_string(entry, "InstallLocation").as_deref().and_then(clean_path);
+                // DisplayIcon is often "path,index"
+                let display_icon = read_string(entry, "DisplayIcon")
+                    .as_deref()
+                    .map(|icon| icon.rsplit_once(',').map_or(icon, |(path, _)| path))
+                    .and_then(clean_path);
+
+                programs.push(InstalledProgram {
+                    display_name: display_name.trim().to_string(),
+                    is_likely_game: install_location.as_deref().is_some_and(|l| is_game_library_path(&format!("{}\\", l))),
+                    install_location,
+                    display_icon,
+                    executables: Vec::new(),
+                    in_drs: None,
+                });
+            }
+
+            let _ = RegCloseKey(entry);
+        }
+
+        let _ = RegCloseKey(key);
+    }
+
+    programs
+}
+
+/// Read installed programs from the machine and per-user uninstall keys, one
+/// entry per display name. Executables are left empty for `scan_install_locations`.
+#[cfg(target_os = "windows")]
+pub fn read_installed_programs() -> Vec<InstalledProgram> {
+    let mut programs: Vec<InstalledProgram> = Vec::new();
+    let sources = UNINSTALL_KEYS.iter()
+        .map(|path| (HKEY_LOCAL_MACHINE, *path))
+        .chain(std::iter::once((HKEY_CURRENT_USER, UNINSTALL_KEYS[0])));
+
+    for (root, path) in sources {
+        for program in read_uninstall_key(root, path) {
+            if !programs.iter().any(|p| p.display_name.eq_ignore_ascii_case(&program.display_name)) {
+                programs.push(program);
+            }
+        }
+    }
+
+    programs.sort_by_key(|p| p.display_name.to_lowercase());
+    programs
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn read_installed_programs() -> Vec<InstalledProgram> {
+    Vec::new()
+}
+
+/// Installed programs with the candidate executables under each install location
+pub fn list_installed_programs() -> Vec<InstalledProgram> {
+    let mut programs = read_installed_programs();
+    scan_install_locations(&mut programs, |_, _| {});
+    programs
+}
+
+/// Fill each program's executables from its install location, walking up to
+/// `SCAN_WORKERS` folders at once. `on_progress` gets (scanned, total) after
+/// each program, from whichever worker finished it.
+pub fn scan_install_locations(programs: &mut [InstalledProgram], on_progress: impl Fn(usize, usize) + Sync) {
+    let total = programs.len();
+    let locations: Vec<Option<String>> = programs.iter().map(|p| p.install_location.clone()).collect();
+    let found: Vec<Mutex<Vec<String>>> = locations.iter().map(|_| Mutex::new(Vec::new())).collect();
+    let next = AtomicUsize::new(0);
+    let scanned = AtomicUsize::new(0);
+
+    std::thread::scope(|scope| {
+        for _ in 0..SCAN_WORKERS.min(total) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(location) = locations.get(index) else {
+                    return;
+                };
+                if let Some(location) = location {
+                    scan_executables(Path::new(location), SCAN_DEPTH, &mut found[index].lock().unwrap());
+                }
+                on_progress(scanned.fetch_add(1, Ordering::Relaxed) + 1, total);
+            });
+        }
+    });
+
+    for (program, executables) in programs.iter_mut().zip(found) {
+        program.executables = executables.into_inner().unwrap();
+    }
+}
+
+/// Walk the programs' install folders, then, with `join`, look every executable
+/// found up in DRS in one pass and fill `in_drs`. Without the join nothing
+/// touches NVAPI, so the scan works when it is unavailable.
+pub fn scan_installed(
+    mut programs: Vec<InstalledProgram>,
+    join: bool,
+    on_progress: impl Fn(usize, usize) + Sync,
+) -> Result<Vec<InstalledProgram>, NvApiError> {
+    scan_install_locations(&mut programs, on_progress);
+    if !join {
+        return Ok(programs);
+    }
+
+    let all: Vec<String> = programs.iter().flat_map(|p| p.executables.iter().cloned()).collect();
+    let known = join_executables(&all)?;
+    for program in &mut programs {
+        program.in_drs = Some(program.executables.iter().filter(|exe| known.contains_key(*exe)).cloned().collect());
+    }
+    Ok(programs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use crate::nvapi::mock::{self, Profile, Store};
+    use crate::nvapi::session::session_lock_count;
+
+    /// Fresh install folder holding empty files with the given names
+    fn install_dir(name: &str, files: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nvidiot-installed-{}-{}", std::process::id(), name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        for file in files {
+            std::fs::write(dir.join(file), b"").unwrap();
+        }
+        dir
+    }
+
+    fn program(name: &str, location: Option<&Path>) -> InstalledProgram {
+        InstalledProgram {
+            display_name: name.to_string(),
+            install_location: location.map(|l| l.to_string_lossy().to_string()),
+            display_icon: None,
+            executables: Vec::new(),
+            is_likely_game: false,
+            in_drs: None,
+        }
+    }
+
+    fn names(executables: &[String]) -> Vec<String> {
+        let mut names: Vec<String> = executables.iter()
+            .map(|e| Path::new(e).file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Two programs with install folders of their own for `test`, and one without
+    fn programs(test: &str) -> Vec<InstalledProgram> {
+        let first = install_dir(&format!("{}-first", test), &["scan-known.exe", "scan-new.exe", "unins000.exe", "readme.txt"]);
+        let second = install_dir(&format!("{}-second", test), &["scan-other.exe"]);
+        vec![
+            program("Scan First", Some(&first)),
+            program("Scan Nowhere", None),
+            program("Scan Second", Some(&second)),
+        ]
+    }
+
+    #[test]
+    fn the_walk_fills_each_program_and_reports_every_folder() {
+        let reports = Mutex::new(Vec::new());
+        let mut programs = programs("walk");
+        scan_install_locations(&mut programs, |scanned, total| reports.lock().unwrap().push((scanned, total)));
+
+        assert_eq!(names(&programs[0].executables), ["scan-known.exe", "scan-new.exe"]);
+        assert!(programs[1].executables.is_empty());
+        assert_eq!(names(&programs[2].executables), ["scan-other.exe"]);
+
+        let mut reports = reports.into_inner().unwrap();
+        reports.sort();
+        assert_eq!(reports, [(1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn the_join_takes_the_session_lock_once() {
+        let _serial = mock::serial();
+        mock::install(Store::default().with_profile(Profile::new("Scan Known").with_application("scan-known.exe")));
+        let locks = session_lock_count();
+
+        let scanned = scan_installed(programs("join"), true, |_, _| {}).unwrap();
+        assert_eq!(session_lock_count() - locks, 1);
+        assert_eq!(scanned[0].in_drs.as_deref().map(names), Some(vec!["scan-known.exe".to_string()]));
+        assert_eq!(scanned[2].in_drs, Some(Vec::new()));
+    }
+
+    #[test]
+    fn without_the_join_nvapi_is_never_touched() {
+        let _serial = mock::serial();
+        mock::install(Store::default());
+        let locks = session_lock_count();
+
+        let scanned = scan_installed(programs("no-join"), false, |_, _| {}).unwrap();
+        assert_eq!(session_lock_count(), locks);
+        assert!(scanned.iter().all(|p| p.in_drs.is_none()));
+        assert_eq!(names(&scanned[0].executables), ["scan-known.exe", "scan-new.exe"]);
+    }
+}
@@ -1,5 +1,21 @@
+pub mod display;
+pub mod filter;
 pub mod focus;
+pub mod gaming;
+pub mod icon;
+pub mod input;
+pub mod installed;
+pub mod nvml;
 pub mod processes;
+pub mod shortcut;
+pub mod signature;
+pub mod startup;
+pub mod version;
 
-pub use focus::get_focus_application;
-pub use processes::get_running_processes;
+pub use focus::{get_focus_application, get_focus_application_extended, get_foreground_window_hwnd, get_focus_from_hwnd};
+pub use processes::{check_process_accessible, get_process_architecture, get_running_processes, get_running_processes_with, get_process_memory_mb, search_running_processes};
+pub use display::{get_dpi_for_window, get_foreground_window_monitor_name, is_foreground_fullscreen, list_monitors};
+pub use gaming::{is_windows_game_mode_enabled, is_game_dvr_enabled};
+pub use startup::list_startup_programs;
+pub use installed::list_installed_programs;
+pub use input::{idle_duration, send_keypress_to_pid, send_key_combo_to_pid};
@@ -0,0 +1,177 @@
+//! NVML process attribution
+//!
+//! Loads nvml.dll to find which processes have work on an NVIDIA GPU. NVML is
+//! optional: old drivers and machines without a GPU don't have it, so every
+//! failure here is reported as "unknown" rather than as an error. The library is
+//! loaded and initialized once and kept for the life of the process, like NVAPI.
+
+use std::collections::HashMap;
+
+#[cfg(target_os = "windows")]
+use std::ffi::c_void;
+#[cfg(target_os = "windows")]
+use once_cell::sync::Lazy;
+#[cfg(target_os = "windows")]
+use libloading::Library;
+
+#[cfg(target_os = "windows")]
+type NvmlDevice = *mut c_void;
+
+#[cfg(target_os = "windows")]
+const NVML_SUCCESS: i32 = 0;
+#[cfg(target_os = "windows")]
+const NVML_ERROR_INSUFFICIENT_SIZE: i32 = 7;
+/// Reported for per-process memory under WDDM, where NVML can't attribute it
+#[cfg(target_os = "windows")]
+const NVML_VALUE_NOT_AVAILABLE: u64 = u64::MAX;
+
+/// Processes fetched per call before retrying with the count NVML asks for
+#[cfg(target_os = "windows")]
+const INITIAL_PROCESS_CAPACITY: usize = 64;
+
+/// nvmlProcessInfo_t (shared by the _v2 and _v3 process queries)
+#[cfg(target_os = "windows")]
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+struct NvmlProcessInfo {
+    pid: u32,
+    used_gpu_memory: u64,
+    gpu_instance_id: u32,
+    compute_instance_id: u32,
+}
+
+#[cfg(target_os = "windows")]
+type NvmlInitFn = unsafe extern "C" fn() -> i32;
+#[cfg(target_os = "windows")]
+type NvmlDeviceGetCountFn = unsafe extern "C" fn(count: *mut u32) -> i32;
+#[cfg(target_os = "windows")]
+type NvmlDeviceGetHandleByIndexFn = unsafe extern "C" fn(index: u32, device: *mut NvmlDevice) -> i32;
+#[cfg(target_os = "windows")]
+type NvmlDeviceGetRunningProcessesFn = unsafe extern "C" fn(device: NvmlDevice, count: *mut u32, infos: *mut NvmlProcessInfo) -> i32;
+
+#[cfg(target_os = "windows")]
+struct Nvml {
+    _library: Library,
+    device_get_count: NvmlDeviceGetCountFn,
+    device_get_handle_by_index: NvmlDeviceGetHandleByIndexFn,
+    /// Compute then graphics queries; either may be missing on older drivers
+    running_process_queries: Vec<NvmlDeviceGetRunningProcessesFn>,
+}
+
+// Function pointers into a library that is never unloaded
+#[cfg(target_os = "windows")]
+unsafe impl Send for Nvml {}
+#[cfg(target_os = "windows")]
+unsafe impl Sync for Nvml {}
+
+#[cfg(target_os = "windows")]
+impl Nvml {
+    fn load() -> Option<Self> {
+        unsafe {
+            let library = Library::new("nvml.dll")
+                .or_else(|_| {
+                    let program_files = std::env::var("ProgramW6432").unwrap_or_else(|_| "C:\\Program Files".to_string());
+                    Library::new(format!("{}\\NVIDIA Corporation\\NVSMI\\nvml.dll", program_files))
+                })
+                .ok()?;
+
+            let init = *library.get::<NvmlInitFn>(b"nvmlInit_v2\0").ok()?;
+            let device_get_count = *library.get::<NvmlDeviceGetCountFn>(b"nvmlDeviceGetCount_v2\0").ok()?;
+            let device_get_handle_by_index =
+                *library.get::<NvmlDeviceGetHandleByIndexFn>(b"nvmlDeviceGetHandleByIndex_v2\0").ok()?;
+
+            let mut running_process_queries = Vec::new();
+            for names in [
+                [&b"nvmlDeviceGetComputeRunningProcesses_v3\0"[..], &b"nvmlDeviceGetComputeRunningProcesses_v2\0"[..]],
+                [&b"nvmlDeviceGetGraphicsRunningProcesses_v3\0"[..], &b"nvmlDeviceGetGraphicsRunningProcesses_v2\0"[..]],
+            ] {
+                if let Some(query) = names.iter()
+                    .find_map(|name| library.get::<NvmlDeviceGetRunningProcessesFn>(name).ok().map(|f| *f))
+                {
+                    running_process_queries.push(query);
+                }
+            }
+            if running_process_queries.is_empty() || init() != NVML_SUCCESS {
+                return None;
+            }
+
+            Some(Self {
+                _library: library,
+                device_get_count,
+                device_get_handle_by_index,
+                running_process_queries,
+            })
+        }
+    }
+
+    fn devices(&self) -> Vec<NvmlDevice> {
+        unsafe {
+            let mut count: u32 = 0;
+            if (self.device_get_count)(&mut count) != NVML_SUCCESS {
+                return Vec::new();
+            }
+            (0..count)
+                .filter_map(|index| {
+                    let mut device: NvmlDevice = std::ptr::null_mut();
+                    ((self.device_get_handle_by_index)(index, &mut device) == NVML_SUCCESS).then_some(device)
+                })
+                .collect()
+        }
+    }
+
+    fn running_processes(&self, query: NvmlDeviceGetRunningProcessesFn, device: NvmlDevice) -> Vec<NvmlProcessInfo> {
+        let mut infos = vec![NvmlProcessInfo::default(); INITIAL_PROCESS_CAPACITY];
+        unsafe {
+            let mut count = infos.len() as u32;
+            let mut status = query(device, &mut count, infos.as_mut_ptr());
+            if status == NVML_ERROR_INSUFFICIENT_SIZE {
+                // Processes can start between calls, so leave some headroom
+                infos = vec![NvmlProcessInfo::default(); count as usize + INITIAL_PROCESS_CAPACITY];
+                count = infos.len() as u32;
+                status = query(device, &mut count, infos.as_mut_ptr());
+            }
+            if status != NVML_SUCCESS {
+                return Vec::new();
+            }
+            infos.truncate(count as usize);
+        }
+        infos
+    }
+}
+
+#[cfg(target_os = "windows")]
+static NVML: Lazy<Option<Nvml>> = Lazy::new(Nvml::load);
+
+/// Processes with compute or graphics work on any NVIDIA GPU, mapped to the GPU
+/// memory they use in MB when NVML can attribute it. None if NVML is unavailable.
+#[cfg(target_os = "windows")]
+pub fn gpu_processes() -> Option<HashMap<u32, Option<u64>>> {
+    let nvml = NVML.as_ref()?;
+
+    let mut processes: HashMap<u32, Option<u64>> = HashMap::new();
+    for device in nvml.devices() {
+        // A process doing both compute and graphics work is listed twice with the same usage
+        let mut on_device: HashMap<u32, Option<u64>> = HashMap::new();
+        for &query in &nvml.running_process_queries {
+            for info in nvml.running_processes(query, device) {
+                let memory_mb = (info.used_gpu_memory != NVML_VALUE_NOT_AVAILABLE)
+                    .then(|| info.used_gpu_memory / (1024 * 1024));
+                let entry = on_device.entry(info.pid).or_insert(None);
+                *entry = (*entry).max(memory_mb);
+            }
+        }
+
+        for (pid, memory_mb) in on_device {
+            let total = processes.entry(pid).or_insert(None);
+            if let Some(mb) = memory_mb {
+                *total = Some(total.unwrap_or(0) + mb);
+            }
+        }
+    }
+    Some(processes)
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn gpu_processes() -> Option<HashMap<u32, Option<u64>>> {
+    None
+}
@@ -2,24 +2,38 @@
 //!
 //! Lists all running processes with visible windows.
 
-use crate::nvapi::types::RunningProcess;
+use crate::nvapi::types::{ProcessAccessibility, ProcessArchitecture, RunningProcess};
 use std::collections::HashMap;
+#[cfg(target_os = "windows")]
+use std::time::{Duration, Instant};
 
 #[cfg(target_os = "windows")]
 use windows::{
     core::{BOOL, PWSTR},
-    Win32::Foundation::{HWND, LPARAM},
+    Win32::Foundation::{CloseHandle, ERROR_ACCESS_DENIED, FILETIME, HWND, LPARAM},
+    Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+    },
     Win32::UI::WindowsAndMessaging::{
-        EnumWindows, GetWindowTextW, GetWindowThreadProcessId, IsWindowVisible,
+        EnumWindows, GetClassNameW, GetWindowTextW, GetWindowThreadProcessId, IsWindowVisible,
+    },
+    Win32::System::Threading::{
+        GetProcessTimes, IsWow64Process2, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION, QueryFullProcessImageNameW,
+        PROCESS_NAME_FORMAT,
+    },
+    Win32::System::SystemInformation::{
+        IMAGE_FILE_MACHINE, IMAGE_FILE_MACHINE_AMD64, IMAGE_FILE_MACHINE_ARM64, IMAGE_FILE_MACHINE_ARMNT,
+        IMAGE_FILE_MACHINE_I386, IMAGE_FILE_MACHINE_UNKNOWN,
     },
-    Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION, QueryFullProcessImageNameW, PROCESS_NAME_FORMAT},
-    Win32::System::ProcessStatus::GetModuleBaseNameW,
+    Win32::System::ProcessStatus::{GetModuleBaseNameW, GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS},
 };
 
 #[cfg(target_os = "windows")]
-use crate::nvapi::applications::find_application;
+use crate::nvapi::applications::get_application_info;
 #[cfg(target_os = "windows")]
-use crate::nvapi::settings::get_shadowplay_status;
+use super::display::get_dpi_for_window;
+#[cfg(target_os = "windows")]
+use super::filter::should_include;
 
 /// Data collected during window enumeration
 #[cfg(target_os = "windows")]
@@ -28,6 +42,8 @@ struct ProcessInfo {
     process_name: String,
     window_title: String,
     executable_path: Option<String>,
+    dpi: u32,
+    accessibility: ProcessAccessibility,
 }
 
 /// Callback data for EnumWindows
@@ -72,26 +88,41 @@ unsafe extern "system" fn enum_windows_callback(hwnd: HWND, lparam: LPARAM) -> B
         return BOOL(1);
     }
 
-    // Get process info
-    if let Some((process_name, executable_path)) = get_process_info(process_id) {
-        // Skip system processes
-        if is_system_process(&process_name) {
+    // Get process info. Processes we can't open are still listed, by their snapshot name,
+    // so the UI can explain why they can't be managed.
+    let info = match get_process_info(process_id) {
+        Some((name, path)) => Some((name, path, ProcessAccessibility::Accessible)),
+        None => match check_process_accessible(process_id) {
+            ProcessAccessibility::RequiresElevation => snapshot_process_name(process_id)
+                .map(|name| (name, None, ProcessAccessibility::RequiresElevation)),
+            _ => None,
+        },
+    };
+    if let Some((process_name, executable_path, accessibility)) = info {
+        let mut class_buffer = [0u16; 256];
+        let class_len = GetClassNameW(hwnd, &mut class_buffer);
+        let window_class = String::from_utf16_lossy(&class_buffer[..class_len.max(0) as usize]);
+
+        if !should_include(&process_name, &window_class, &window_title) {
             return BOOL(1);
         }
 
         data.processes.insert(process_id, ProcessInfo {
             process_id,
+            window_title: crate::privacy::sanitize_title(&process_name, window_title),
             process_name,
-            window_title,
             executable_path,
+            dpi: get_dpi_for_window(hwnd),
+            accessibility,
         });
     }
 
     BOOL(1) // Continue enumeration
 }
 
+/// Get a process's name and, when accessible, its full executable path
 #[cfg(target_os = "windows")]
-fn get_process_info(pid: u32) -> Option<(String, Option<String>)> {
+pub fn get_process_info(pid: u32) -> Option<(String, Option<String>)> {
     unsafe {
         let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
 
@@ -120,30 +151,202 @@ fn get_process_info(pid: u32) -> Option<(String, Option<String>)> {
     }
 }
 
+/// Whether a process can be opened for querying. Access denied means it runs
+/// elevated or protected; any other failure means it is gone.
 #[cfg(target_os = "windows")]
-fn is_system_process(name: &str) -> bool {
-    let name_lower = name.to_lowercase();
-    matches!(name_lower.as_str(),
-        "explorer.exe" |
-        "searchhost.exe" |
-        "shellexperiencehost.exe" |
-        "startmenuexperiencehost.exe" |
-        "textinputhost.exe" |
-        "applicationframehost.exe" |
-        "systemsettings.exe" |
-        "runtimebroker.exe" |
-        "dwm.exe" |
-        "csrss.exe" |
-        "winlogon.exe" |
-        "services.exe" |
-        "lsass.exe" |
-        "svchost.exe"
-    )
+pub fn check_process_accessible(pid: u32) -> ProcessAccessibility {
+    unsafe {
+        match OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) {
+            Ok(handle) => {
+                let _ = CloseHandle(handle);
+                ProcessAccessibility::Accessible
+            }
+            Err(e) if e.code() == ERROR_ACCESS_DENIED.to_hresult() => ProcessAccessibility::RequiresElevation,
+            Err(_) => ProcessAccessibility::NotFound,
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn check_process_accessible(_pid: u32) -> ProcessAccessibility {
+    ProcessAccessibility::NotFound
+}
+
+/// Executable name of a process from a ToolHelp snapshot, which needs no access to the process
+#[cfg(target_os = "windows")]
+fn snapshot_process_name(pid: u32) -> Option<String> {
+    unsafe {
+        let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0).ok()?;
+
+        let mut entry = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+        let mut name = None;
+        let mut next = Process32FirstW(snapshot, &mut entry);
+        while next.is_ok() {
+            if entry.th32ProcessID == pid {
+                let len = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(entry.szExeFile.len());
+                name = Some(String::from_utf16_lossy(&entry.szExeFile[..len]));
+                break;
+            }
+            next = Process32NextW(snapshot, &mut entry);
+        }
+        let _ = CloseHandle(snapshot);
+        name
+    }
+}
+
+/// Get a process's working set size in MB
+#[cfg(target_os = "windows")]
+pub fn get_process_memory_mb(pid: u32) -> Option<u32> {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+
+        let mut counters = PROCESS_MEMORY_COUNTERS {
+            cb: std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32,
+            ..Default::default()
+        };
+        let result = GetProcessMemoryInfo(handle, &mut counters, counters.cb);
+        let _ = CloseHandle(handle);
+
+        result.ok()?;
+        Some((counters.WorkingSetSize / (1024 * 1024)) as u32)
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn get_process_memory_mb(_pid: u32) -> Option<u32> {
+    None
+}
+
+/// Interval between the two CPU time samples
+#[cfg(target_os = "windows")]
+const CPU_SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Binary architecture a process runs as. Under emulation this is the
+/// process's own format, e.g. X64 for an x64 game on ARM64 Windows.
+#[cfg(target_os = "windows")]
+pub fn get_process_architecture(pid: u32) -> Option<ProcessArchitecture> {
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+
+        let mut process_machine = IMAGE_FILE_MACHINE_UNKNOWN;
+        let mut native_machine = IMAGE_FILE_MACHINE_UNKNOWN;
+        let result = IsWow64Process2(handle, &mut process_machine, Some(&mut native_machine));
+        let _ = CloseHandle(handle);
+        result.ok()?;
+
+        // UNKNOWN means the process isn't under WOW64, so it runs natively
+        let machine = if process_machine == IMAGE_FILE_MACHINE_UNKNOWN { native_machine } else { process_machine };
+        machine_architecture(machine)
+    }
 }
 
-/// Get all running processes with visible windows
 #[cfg(target_os = "windows")]
+fn machine_architecture(machine: IMAGE_FILE_MACHINE) -> Option<ProcessArchitecture> {
+    match machine {
+        IMAGE_FILE_MACHINE_I386 => Some(ProcessArchitecture::X86),
+        IMAGE_FILE_MACHINE_AMD64 => Some(ProcessArchitecture::X64),
+        IMAGE_FILE_MACHINE_ARM64 => Some(ProcessArchitecture::Arm64),
+        IMAGE_FILE_MACHINE_ARMNT => Some(ProcessArchitecture::Arm32),
+        _ => None,
+    }
+}
+
+/// Total kernel + user time of a process in 100ns units
+#[cfg(target_os = "windows")]
+fn get_process_cpu_time(pid: u32) -> Option<u64> {
+    let to_u64 = |t: FILETIME| ((t.dwHighDateTime as u64) << 32) | t.dwLowDateTime as u64;
+
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+
+        let mut creation = FILETIME::default();
+        let mut exit = FILETIME::default();
+        let mut kernel = FILETIME::default();
+        let mut user = FILETIME::default();
+        let result = GetProcessTimes(handle, &mut creation, &mut exit, &mut kernel, &mut user);
+        let _ = CloseHandle(handle);
+
+        result.ok()?;
+        Some(to_u64(kernel) + to_u64(user))
+    }
+}
+
+/// CPU usage per process over one sample interval, as a percentage of all cores.
+/// Samples every pid together so the call blocks for a single interval.
+#[cfg(target_os = "windows")]
+pub(crate) fn get_process_cpu_percent(pids: &[u32]) -> HashMap<u32, f32> {
+    let first: Vec<(u32, u64)> = pids.iter()
+        .filter_map(|&pid| get_process_cpu_time(pid).map(|t| (pid, t)))
+        .collect();
+    let start = Instant::now();
+
+    std::thread::sleep(CPU_SAMPLE_INTERVAL);
+
+    let elapsed_100ns = start.elapsed().as_nanos() as f64 / 100.0;
+    let cores = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1) as f64;
+
+    first.into_iter()
+        .filter_map(|(pid, before)| {
+            let after = get_process_cpu_time(pid)?;
+            let busy = after.saturating_sub(before) as f64;
+            Some((pid, (busy / (elapsed_100ns * cores) * 100.0) as f32))
+        })
+        .collect()
+}
+
+/// Names of running processes from `names`, windowed or not
+#[cfg(target_os = "windows")]
+fn find_running(names: &[&str]) -> Vec<String> {
+    let mut found: Vec<String> = Vec::new();
+    unsafe {
+        let Ok(snapshot) = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) else {
+            return found;
+        };
+
+        let mut entry = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+        let mut next = Process32FirstW(snapshot, &mut entry);
+        while next.is_ok() {
+            let len = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(entry.szExeFile.len());
+            let name = String::from_utf16_lossy(&entry.szExeFile[..len]);
+            if names.iter().any(|wanted| wanted.eq_ignore_ascii_case(&name))
+                && !found.iter().any(|f| f.eq_ignore_ascii_case(&name))
+            {
+                found.push(name);
+            }
+            next = Process32NextW(snapshot, &mut entry);
+        }
+        let _ = CloseHandle(snapshot);
+    }
+    found
+}
+
+/// Names of running processes from `DRS_WRITING_TOOLS`, windowed or not
+#[cfg(target_os = "windows")]
+pub fn find_drs_session_holders() -> Vec<String> {
+    find_running(super::filter::DRS_WRITING_TOOLS)
+}
+
+/// Whether the NVIDIA overlay that ShadowPlay records through is running
+#[cfg(target_os = "windows")]
+pub fn is_nvidia_overlay_running() -> bool {
+    !find_running(super::filter::NVIDIA_OVERLAY_PROCESSES).is_empty()
+}
+
+/// Get all running processes with visible windows
 pub fn get_running_processes() -> Vec<RunningProcess> {
+    get_running_processes_with(false, false)
+}
+
+/// Get all running processes with visible windows, optionally with memory and CPU usage
+/// and NVIDIA GPU attribution. Including usage blocks for one CPU sample interval.
+#[cfg(target_os = "windows")]
+pub fn get_running_processes_with(include_memory: bool, include_gpu_usage: bool) -> Vec<RunningProcess> {
     let mut data = EnumData {
         processes: HashMap::new(),
     };
@@ -155,54 +358,91 @@ pub fn get_running_processes() -> Vec<RunningProcess> {
         );
     }
 
+    let cpu_usage = if include_memory {
+        let pids: Vec<u32> = data.processes.keys().copied().collect();
+        get_process_cpu_percent(&pids)
+    } else {
+        HashMap::new()
+    };
+
+    let gpu_processes = if include_gpu_usage { super::nvml::gpu_processes() } else { None };
+
     // Convert to RunningProcess and check DRS status
     data.processes
         .into_values()
         .map(|info| {
-            let (has_drs_profile, profile_name, is_blacklisted) = match find_application(&info.process_name) {
-                Ok((profile_handle, _app)) => {
-                    let profile_name = get_profile_name_from_handle(profile_handle);
-                    let is_blacklisted = get_shadowplay_status(profile_handle).ok();
-                    (true, profile_name, is_blacklisted)
-                }
+            let (has_drs_profile, profile_name, is_blacklisted) = match get_application_info(&info.process_name) {
+                Ok(app) => (true, Some(app.profile_name), Some(app.is_blacklisted)),
                 Err(_) => (false, None, None),
             };
 
+            let signer = info.executable_path.as_deref().and_then(crate::metadata::cached_signer);
+            let gpu_usage = gpu_processes.as_ref().map(|gpu| gpu.get(&info.process_id).copied());
+
             RunningProcess {
                 process_name: info.process_name,
                 window_title: info.window_title,
                 process_id: info.process_id,
                 executable_path: info.executable_path,
+                signer,
+                architecture: get_process_architecture(info.process_id),
                 has_drs_profile,
                 profile_name,
                 is_blacklisted,
+                dpi: Some(info.dpi),
+                memory_usage_mb: if include_memory { get_process_memory_mb(info.process_id) } else { None },
+                cpu_usage_percent: cpu_usage.get(&info.process_id).copied(),
+                uses_nvidia_gpu: gpu_usage.map(|usage| usage.is_some()),
+                gpu_memory_mb: gpu_usage.flatten().flatten(),
+                accessibility: info.accessibility,
             }
         })
         .collect()
 }
 
 #[cfg(not(target_os = "windows"))]
-pub fn get_running_processes() -> Vec<RunningProcess> {
+pub fn get_running_processes_with(_include_memory: bool, _include_gpu_usage: bool) -> Vec<RunningProcess> {
     Vec::new()
 }
 
-#[cfg(target_os = "windows")]
-fn get_profile_name_from_handle(profile_handle: crate::nvapi::ffi::NvDRSProfileHandle) -> Option<String> {
-    use crate::nvapi::ffi::{get_nvapi, wchar_to_string, NvdrsProfile};
-    use crate::nvapi::session::get_session;
-    use crate::nvapi::error::NVAPI_OK;
-
-    let api = get_nvapi().ok()?;
-    let session = get_session().ok()?;
-    let get_profile_info = api.drs_get_profile_info?;
-
-    unsafe {
-        let mut profile_info = NvdrsProfile::default();
-        let status = get_profile_info(session, profile_handle, &mut profile_info);
-        if status == NVAPI_OK {
-            Some(wchar_to_string(&profile_info.profile_name))
-        } else {
-            None
+/// How well a process matches a lowercased query; lower is better, None is no match
+fn search_rank(process: &RunningProcess, query: &str, search_title: bool, search_name: bool) -> Option<u8> {
+    let name = process.process_name.to_lowercase();
+    if search_name {
+        let stem = name.strip_suffix(".exe").unwrap_or(&name);
+        if name == query || stem == query {
+            return Some(0);
+        }
+        if name.starts_with(query) {
+            return Some(1);
         }
+        if name.contains(query) {
+            return Some(2);
+        }
+    }
+    if search_title && process.window_title.to_lowercase().contains(query) {
+        return Some(2);
+    }
+    None
+}
+
+/// Filter running processes by name and/or window title, case-insensitively.
+/// Exact name matches come first, then prefix matches, then anything containing the query.
+/// An empty query returns every process.
+pub fn search_running_processes(query: &str, search_title: bool, search_name: bool) -> Vec<RunningProcess> {
+    // Titles are replaced by process names in privacy mode
+    let search_title = search_title && !crate::privacy::enabled();
+    let processes = get_running_processes();
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return processes;
     }
+
+    let mut ranked: Vec<(u8, RunningProcess)> = processes.into_iter()
+        .filter_map(|p| search_rank(&p, &query, search_title, search_name).map(|rank| (rank, p)))
+        .collect();
+    ranked.sort_by(|(a_rank, a), (b_rank, b)| {
+        a_rank.cmp(b_rank).then_with(|| a.process_name.to_lowercase().cmp(&b.process_name.to_lowercase()))
+    });
+    ranked.into_iter().map(|(_, p)| p).collect()
 }
@@ -0,0 +1,455 @@
+//! Shortcuts dropped onto the window
+//!
+//! Desktop shortcuts to games are rarely the executable itself: Steam and Epic
+//! write .url files with a launcher URL, and other installers write .lnk
+//! files. `resolve_path` follows either to the game's executable: .lnk through
+//! the shell link object, steam:// through the app manifests of the Steam
+//! libraries, and com.epicgames.launcher:// through the Epic launcher's install
+//! manifests. Parsing of the shortcut and manifest formats is pure; only the
+//! lookups touch the file system and registry.
+
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use thiserror::Error;
+use crate::nvapi::types::{ResolutionPath, ResolvedExecutable, ShortcutTarget};
+use super::installed::{scan_executables, SCAN_DEPTH};
+
+#[cfg(target_os = "windows")]
+use windows::{
+    core::{Interface, PCWSTR},
+    Win32::Foundation::ERROR_SUCCESS,
+    Win32::Storage::FileSystem::WIN32_FIND_DATAW,
+    Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, IPersistFile, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED, STGM_READ,
+    },
+    Win32::System::Registry::{RegCloseKey, RegOpenKeyExW, HKEY, HKEY_CURRENT_USER, KEY_READ},
+    Win32::UI::Shell::{IShellLinkW, ShellLink},
+};
+
+/// Epic launcher install manifests, relative to %ProgramData%
+const EPIC_MANIFEST_DIR: &str = "Epic\\EpicGamesLauncher\\Data\\Manifests";
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ShortcutError {
+    /// The shortcut was read but its target couldn't be mapped to an installed executable
+    #[error("unresolvable-shortcut: {target}: {reason}")]
+    UnresolvableShortcut { target: String, reason: String },
+
+    #[error("Cannot read '{path}': {reason}")]
+    Unreadable { path: String, reason: String },
+
+    #[error("'{0}' is not an executable, .lnk or .url file")]
+    UnsupportedFile(String),
+}
+
+/// The URL of an Internet Shortcut: the `URL=` key of its [InternetShortcut] section
+pub fn parse_url_shortcut(contents: &str) -> Option<String> {
+    let mut in_section = false;
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_section = line.eq_ignore_ascii_case("[InternetShortcut]");
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once('=') {
+            if key.trim().eq_ignore_ascii_case("URL") && !value.trim().is_empty() {
+                return Some(value.trim().to_string());
+            }
+        }
+    }
+    None
+}
+
+/// Decode %XX escapes, leaving malformed ones as they are
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Strip a case-insensitive prefix
+fn strip_prefix_ignore_case<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    value.get(..prefix.len())
+        .filter(|head| head.eq_ignore_ascii_case(prefix))
+        .map(|_| &value[prefix.len()..])
+}
+
+/// Classify a shortcut URL. Steam writes `steam://rungameid/<id>` (older
+/// shortcuts `steam://run/<id>`); Epic writes
+/// `com.epicgames.launcher://apps/<namespace>%3A<catalog item>%3A<app name>?action=launch`
+/// or, from older launchers, `com.epicgames.launcher://apps/<app name>?action=launch`.
+pub fn parse_shortcut_target(url: &str) -> ShortcutTarget {
+    let url = url.trim();
+    let steam = strip_prefix_ignore_case(url, "steam://rungameid/")
+        .or_else(|| strip_prefix_ignore_case(url, "steam://run/"));
+    if let Some(rest) = steam {
+        let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+        // Non-Steam game shortcuts carry a 64-bit id that has no manifest
+        if let Ok(app_id) = digits.parse::<u32>() {
+            return ShortcutTarget::Steam { app_id };
+        }
+    }
+
+    if let Some(rest) = strip_prefix_ignore_case(url, "com.epicgames.launcher://apps/") {
+        let ids = percent_decode(rest.split(['?', '#']).next().unwrap_or_default());
+        let parts: Vec<&str> = ids.trim_end_matches('/').split(':').collect();
+        let app_name = parts.last().copied().unwrap_or_default();
+        if !app_name.is_empty() {
+            return ShortcutTarget::Epic {
+                app_name: app_name.to_string(),
+                catalog_item_id: (parts.len() == 3).then(|| parts[1].to_string()),
+            };
+        }
+    }
+
+    if let Some(rest) = strip_prefix_ignore_case(url, "file:///") {
+        return ShortcutTarget::File { path: percent_decode(rest).replace('/', "\\") };
+    }
+    ShortcutTarget::Other { url: url.to_string() }
+}
+
+/// Every value following the quoted key `key` in a Valve KeyValues file such
+/// as libraryfolders.vdf or appmanifest_<id>.acf, unescaped
+pub fn vdf_values(contents: &str, key: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = contents.chars();
+    while let Some(c) = chars.next() {
+        if c != '"' {
+            continue;
+        }
+        let mut token = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => break,
+                '\\' => token.extend(chars.next()),
+                _ => token.push(c),
+            }
+        }
+        tokens.push(token);
+    }
+
+    tokens.windows(2)
+        .filter(|pair| pair[0].eq_ignore_ascii_case(key))
+        .map(|pair| pair[1].clone())
+        .collect()
+}
+
+/// The fields of an Epic install manifest (.item) that locate the game
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EpicManifest {
+    pub app_name: String,
+    #[serde(default)]
+    pub catalog_item_id: String,
+    pub install_location: String,
+    #[serde(default)]
+    pub launch_executable: String,
+}
+
+impl EpicManifest {
+    fn matches(&self, app_name: &str, catalog_item_id: Option<&str>) -> bool {
+        self.app_name.eq_ignore_ascii_case(app_name)
+            || catalog_item_id.is_some_and(|id| !id.is_empty() && self.catalog_item_id.eq_ignore_ascii_case(id))
+    }
+
+    /// Full path of the launch executable, if the manifest names one
+    pub fn executable_path(&self) -> Option<String> {
+        let relative = self.launch_executable.trim().trim_start_matches(['/', '\\']);
+        (!relative.is_empty()).then(|| {
+            format!("{}\\{}", self.install_location.trim_end_matches(['/', '\\']), relative.replace('/', "\\"))
+        })
+    }
+}
+
+pub fn parse_epic_manifest(contents: &str) -> Option<EpicManifest> {
+    serde_json::from_str(contents).ok()
+}
+
+/// The candidate most likely to be the game: the one named after its install
+/// folder, otherwise the shallowest
+pub fn pick_candidate(install_dir: &str, candidates: &[String]) -> Option<String> {
+    let folder: String = install_dir.chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase();
+    let stem = |path: &String| {
+        Path::new(path).file_stem()
+            .map(|s| s.to_string_lossy().chars().filter(|c| c.is_alphanumeric()).collect::<String>().to_lowercase())
+            .unwrap_or_default()
+    };
+    candidates.iter()
+        .find(|c| !folder.is_empty() && stem(c) == folder)
+        .or_else(|| candidates.iter().min_by_key(|c| c.matches(['\\', '/']).count()))
+        .cloned()
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path).file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_else(|| path.to_string())
+}
+
+fn unresolvable(target: &ShortcutTarget, reason: impl Into<String>) -> ShortcutError {
+    let target = match target {
+        ShortcutTarget::Steam { app_id } => format!("steam app {}", app_id),
+        ShortcutTarget::Epic { app_name, .. } => format!("epic app {}", app_name),
+        ShortcutTarget::File { path } => path.clone(),
+        ShortcutTarget::Other { url } => url.clone(),
+    };
+    ShortcutError::UnresolvableShortcut { target, reason: reason.into() }
+}
+
+/// Read a string value from a registry key
+#[cfg(target_os = "windows")]
+fn read_registry_string(root: HKEY, path: &str, name: &str) -> Option<String> {
+    let subkey: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        let mut key = HKEY::default();
+        if RegOpenKeyExW(root, PCWSTR(subkey.as_ptr()), None, KEY_READ, &mut key) != ERROR_SUCCESS {
+            return None;
+        }
+        let value = super::installed::read_string(key, name);
+        let _ = RegCloseKey(key);
+        value
+    }
+}
+
+/// Steam's install folder, where the first library lives
+#[cfg(target_os = "windows")]
+fn steam_root() -> Option<PathBuf> {
+    read_registry_string(HKEY_CURRENT_USER, "Software\\Valve\\Steam", "SteamPath")
+        .map(|path| PathBuf::from(path.replace('/', "\\")))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn steam_root() -> Option<PathBuf> {
+    None
+}
+
+/// Every Steam library folder, the install folder first
+fn steam_libraries() -> Vec<PathBuf> {
+    let Some(root) = steam_root() else {
+        return Vec::new();
+    };
+    let mut libraries = vec![root.clone()];
+    let vdf = std::fs::read_to_string(root.join("steamapps").join("libraryfolders.vdf")).unwrap_or_default();
+    for path in vdf_values(&vdf, "path").into_iter().map(PathBuf::from) {
+        if !libraries.iter().any(|l| l.to_string_lossy().eq_ignore_ascii_case(&path.to_string_lossy())) {
+            libraries.push(path);
+        }
+    }
+    libraries
+}
+
+fn resolve_steam(target: &ShortcutTarget, app_id: u32) -> Result<String, ShortcutError> {
+    let libraries = steam_libraries();
+    if libraries.is_empty() {
+        return Err(unresolvable(target, "Steam is not installed"));
+    }
+    for library in libraries {
+        let steamapps = library.join("steamapps");
+        let Ok(acf) = std::fs::read_to_string(steamapps.join(format!("appmanifest_{}.acf", app_id))) else {
+            continue;
+        };
+        let Some(install_dir) = vdf_values(&acf, "installdir").into_iter().next() else {
+            return Err(unresolvable(target, "the app manifest has no install folder"));
+        };
+        let mut candidates = Vec::new();
+        scan_executables(&steamapps.join("common").join(&install_dir), SCAN_DEPTH, &mut candidates);
+        return pick_candidate(&install_dir, &candidates)
+            .ok_or_else(|| unresolvable(target, format!("no executable found in '{}'", install_dir)));
+    }
+    Err(unresolvable(target, "the game is not installed in any Steam library"))
+}
+
+fn resolve_epic(target: &ShortcutTarget, app_name: &str, catalog_item_id: Option<&str>) -> Result<String, ShortcutError> {
+    let program_data = std::env::var("ProgramData").unwrap_or_else(|_| "C:\\ProgramData".to_string());
+    let Ok(entries) = std::fs::read_dir(Path::new(&program_data).join(EPIC_MANIFEST_DIR)) else {
+        return Err(unresolvable(target, "the Epic Games Launcher is not installed"));
+    };
+    let manifest = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|e| e.eq_ignore_ascii_case("item")))
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .filter_map(|contents| parse_epic_manifest(&contents))
+        .find(|m| m.matches(app_name, catalog_item_id))
+        .ok_or_else(|| unresolvable(target, "the game is not installed in the Epic Games Launcher"))?;
+
+    if let Some(path) = manifest.executable_path().filter(|p| Path::new(p).is_file()) {
+        return Ok(path);
+    }
+    let mut candidates = Vec::new();
+    scan_executables(Path::new(&manifest.install_location), SCAN_DEPTH, &mut candidates);
+    pick_candidate(&file_name(&manifest.install_location), &candidates)
+        .ok_or_else(|| unresolvable(target, format!("no executable found in '{}'", manifest.install_location)))
+}
+
+/// Target path of a .lnk file through the shell link object
+#[cfg(target_os = "windows")]
+fn read_shell_link(path: &str) -> Result<String, ShortcutError> {
+    let unreadable = |e: windows::core::Error| ShortcutError::Unreadable { path: path.to_string(), reason: e.message() };
+    let wide: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+    unsafe {
+        // Already initialized on this thread is fine
+        let _ = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        let link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER).map_err(unreadable)?;
+        link.cast::<IPersistFile>().map_err(unreadable)?
+            .Load(PCWSTR(wide.as_ptr()), STGM_READ)
+            .map_err(unreadable)?;
+
+        let mut target = [0u16; 1024];
+        let mut find_data = WIN32_FIND_DATAW::default();
+        link.GetPath(&mut target, &mut find_data, 0).map_err(unreadable)?;
+        let end = target.iter().position(|&c| c == 0).unwrap_or(target.len());
+        Ok(String::from_utf16_lossy(&target[..end]))
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn read_shell_link(path: &str) -> Result<String, ShortcutError> {
+    Err(ShortcutError::Unreadable { path: path.to_string(), reason: "shell links are only readable on Windows".to_string() })
+}
+
+fn resolved(source: &str, resolution: ResolutionPath, executable_path: String, target: Option<ShortcutTarget>) -> ResolvedExecutable {
+    ResolvedExecutable {
+        source: source.to_string(),
+        resolution,
+        executable: file_name(&executable_path),
+        executable_path,
+        target,
+    }
+}
+
+/// Follow a dropped executable, .lnk or .url to the game executable
+pub fn resolve_path(path: &str) -> Result<ResolvedExecutable, ShortcutError> {
+    let path = path.trim().trim_matches('"');
+    let extension = Path::new(path).extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    match extension.as_str() {
+        "exe" => Ok(resolved(path, ResolutionPath::Direct, path.to_string(), None)),
+        "lnk" => {
+            let target = read_shell_link(path)?;
+            // Advertised (MSI) shortcuts have no file target
+            if !target.to_lowercase().ends_with(".exe") {
+                let target = ShortcutTarget::File { path: target };
+                return Err(unresolvable(&target, "the shortcut does not point to an executable"));
+            }
+            Ok(resolved(path, ResolutionPath::ShellLink, target, None))
+        }
+        "url" => {
+            let contents = std::fs::read_to_string(path)
+                .map_err(|e| ShortcutError::Unreadable { path: path.to_string(), reason: e.to_string() })?;
+            let url = parse_url_shortcut(&contents).ok_or_else(|| ShortcutError::Unreadable {
+                path: path.to_string(),
+                reason: "no URL in the shortcut".to_string(),
+            })?;
+            let target = parse_shortcut_target(&url);
+            let (resolution, executable_path) = match &target {
+                ShortcutTarget::Steam { app_id } => (ResolutionPath::SteamManifest, resolve_steam(&target, *app_id)?),
+                ShortcutTarget::Epic { app_name, catalog_item_id } => {
+                    (ResolutionPath::EpicManifest, resolve_epic(&target, app_name, catalog_item_id.as_deref())?)
+                }
+                ShortcutTarget::File { path } if path.to_lowercase().ends_with(".exe") => (ResolutionPath::FileUrl, path.clone()),
+                ShortcutTarget::File { .. } | ShortcutTarget::Other { .. } => {
+                    return Err(unresolvable(&target, "only Steam, Epic and executable shortcuts can be followed"));
+                }
+            };
+            Ok(resolved(path, resolution, executable_path, Some(target)))
+        }
+        _ => Err(ShortcutError::UnsupportedFile(path.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_is_read_from_the_internet_shortcut_section() {
+        let contents = "[{000214A0-0000-0000-C000-000000000046}]\r\nURL=ignored\r\n[InternetShortcut]\r\nIDList=\r\nURL=steam://rungameid/570\r\nIconIndex=0\r\n";
+        assert_eq!(parse_url_shortcut(contents), Some("steam://rungameid/570".to_string()));
+        assert_eq!(parse_url_shortcut("[InternetShortcut]\nurl = file:///C:/Games/a.exe \n"), Some("file:///C:/Games/a.exe".to_string()));
+        assert_eq!(parse_url_shortcut("[InternetShortcut]\nURL=\n"), None);
+        assert_eq!(parse_url_shortcut("URL=steam://rungameid/570\n"), None);
+    }
+
+    #[test]
+    fn steam_urls_give_the_app_id() {
+        assert_eq!(parse_shortcut_target("steam://rungameid/570"), ShortcutTarget::Steam { app_id: 570 });
+        assert_eq!(parse_shortcut_target("STEAM://run/1091500/"), ShortcutTarget::Steam { app_id: 1091500 });
+        // Non-Steam game ids don't fit in 32 bits and have no manifest
+        assert_eq!(
+            parse_shortcut_target("steam://rungameid/12345678901234567890"),
+            ShortcutTarget::Other { url: "steam://rungameid/12345678901234567890".to_string() },
+        );
+    }
+
+    #[test]
+    fn epic_urls_give_the_app_name_and_catalog_item() {
+        assert_eq!(
+            parse_shortcut_target("com.epicgames.launcher://apps/fn%3A4fe75bbc5a674f4f9b356b5c90567da5%3AFortnite?action=launch&silent=true"),
+            ShortcutTarget::Epic { app_name: "Fortnite".to_string(), catalog_item_id: Some("4fe75bbc5a674f4f9b356b5c90567da5".to_string()) },
+        );
+        assert_eq!(
+            parse_shortcut_target("com.epicgames.launcher://apps/Sugar?action=launch"),
+            ShortcutTarget::Epic { app_name: "Sugar".to_string(), catalog_item_id: None },
+        );
+    }
+
+    #[test]
+    fn file_urls_become_windows_paths() {
+        assert_eq!(
+            parse_shortcut_target("file:///C:/Program%20Files/Game/game.exe"),
+            ShortcutTarget::File { path: "C:\\Program Files\\Game\\game.exe".to_string() },
+        );
+        assert_eq!(
+            parse_shortcut_target("https://example.com"),
+            ShortcutTarget::Other { url: "https://example.com".to_string() },
+        );
+    }
+
+    #[test]
+    fn malformed_escapes_are_kept() {
+        assert_eq!(percent_decode("a%2"), "a%2");
+        assert_eq!(percent_decode("a%zz%41"), "a%zzA");
+    }
+
+    #[test]
+    fn vdf_values_follow_their_key() {
+        let contents = "\"libraryfolders\"\n{\n\t\"0\"\n\t{\n\t\t\"path\"\t\t\"C:\\\\Program Files (x86)\\\\Steam\"\n\t}\n\t\"1\"\n\t{\n\t\t\"PATH\"\t\t\"D:\\\\SteamLibrary\"\n\t}\n}\n";
+        assert_eq!(vdf_values(contents, "path"), vec!["C:\\Program Files (x86)\\Steam", "D:\\SteamLibrary"]);
+    }
+
+    #[test]
+    fn epic_manifest_locates_the_launch_executable() {
+        let manifest = parse_epic_manifest(r#"{"AppName":"Sugar","CatalogItemId":"abc","InstallLocation":"D:\\Epic\\Game\\","LaunchExecutable":"Bin/Game.exe"}"#).unwrap();
+        assert!(manifest.matches("sugar", None));
+        assert!(manifest.matches("other", Some("ABC")));
+        assert_eq!(manifest.executable_path(), Some("D:\\Epic\\Game\\Bin\\Game.exe".to_string()));
+        assert!(parse_epic_manifest("not json").is_none());
+    }
+
+    #[test]
+    fn candidate_named_after_the_folder_wins() {
+        let candidates = vec![
+            "C:\\Games\\Hades\\x64\\Hades.exe".to_string(),
+            "C:\\Games\\Hades\\Launcher.exe".to_string(),
+        ];
+        assert_eq!(pick_candidate("Hades", &candidates), Some(candidates[0].clone()));
+        assert_eq!(pick_candidate("Other Game", &candidates), Some(candidates[1].clone()));
+        assert_eq!(pick_candidate("Hades", &[]), None);
+    }
+}
@@ -0,0 +1,85 @@
+//! Authenticode signatures
+//!
+//! Verifies an executable's embedded signature with WinVerifyTrust and reads
+//! the signing certificate's subject. Revocation isn't checked so lookups stay
+//! offline; this identifies a publisher, it is not a security decision.
+
+#[cfg(target_os = "windows")]
+use windows::{
+    core::PCWSTR,
+    Win32::Foundation::{HANDLE, HWND},
+    Win32::Security::Cryptography::{CertGetNameStringW, CERT_NAME_SIMPLE_DISPLAY_TYPE},
+    Win32::Security::WinTrust::{
+        WTHelperGetProvSignerFromChain, WTHelperProvDataFromStateData, WinVerifyTrust,
+        WINTRUST_ACTION_GENERIC_VERIFY_V2, WINTRUST_DATA, WINTRUST_DATA_0, WINTRUST_FILE_INFO,
+        WTD_CHOICE_FILE, WTD_REVOKE_NONE, WTD_STATEACTION_CLOSE, WTD_STATEACTION_VERIFY, WTD_UI_NONE,
+    },
+};
+
+/// Subject name of the certificate that signed `path`, or None if the file is
+/// unsigned or its signature doesn't verify
+#[cfg(target_os = "windows")]
+pub fn get_signer_subject(path: &str) -> Option<String> {
+    let wide_path: Vec<u16> = path.encode_utf16().chain(std::iter::once(0)).collect();
+
+    unsafe {
+        let mut file_info = WINTRUST_FILE_INFO {
+            cbStruct: std::mem::size_of::<WINTRUST_FILE_INFO>() as u32,
+            pcwszFilePath: PCWSTR(wide_path.as_ptr()),
+            ..Default::default()
+        };
+        let mut data = WINTRUST_DATA {
+            cbStruct: std::mem::size_of::<WINTRUST_DATA>() as u32,
+            dwUIChoice: WTD_UI_NONE,
+            fdwRevocationChecks: WTD_REVOKE_NONE,
+            dwUnionChoice: WTD_CHOICE_FILE,
+            Anonymous: WINTRUST_DATA_0 { pFile: &mut file_info },
+            dwStateAction: WTD_STATEACTION_VERIFY,
+            ..Default::default()
+        };
+        let mut action = WINTRUST_ACTION_GENERIC_VERIFY_V2;
+
+        let status = WinVerifyTrust(HWND::default(), &mut action, &mut data as *mut _ as *mut _);
+        let subject = if status == 0 {
+            read_signer_subject(data.hWVTStateData)
+        } else {
+            None
+        };
+
+        // Release the state WinVerifyTrust allocated for the verify call
+        data.dwStateAction = WTD_STATEACTION_CLOSE;
+        let _ = WinVerifyTrust(HWND::default(), &mut action, &mut data as *mut _ as *mut _);
+
+        subject
+    }
+}
+
+/// Read the leaf certificate's display name from verified trust state
+#[cfg(target_os = "windows")]
+unsafe fn read_signer_subject(state: HANDLE) -> Option<String> {
+    let provider = WTHelperProvDataFromStateData(state);
+    if provider.is_null() {
+        return None;
+    }
+    let signer = WTHelperGetProvSignerFromChain(provider, 0, false, 0);
+    if signer.is_null() || (*signer).csCertChain == 0 || (*signer).pasCertChain.is_null() {
+        return None;
+    }
+    let cert = (*(*signer).pasCertChain).pCert;
+    if cert.is_null() {
+        return None;
+    }
+
+    let mut name = [0u16; 512];
+    let len = CertGetNameStringW(cert, CERT_NAME_SIMPLE_DISPLAY_TYPE, 0, None, Some(&mut name));
+    // The length includes the terminator; 1 means an empty name
+    if len <= 1 {
+        return None;
+    }
+    Some(String::from_utf16_lossy(&name[..len as usize - 1]))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn get_signer_subject(_path: &str) -> Option<String> {
+    None
+}
@@ -0,0 +1,146 @@
+//! Startup programs
+//!
+//! Lists what Windows launches at sign-in from the Run registry keys and the
+//! user's Startup folder.
+
+use crate::nvapi::types::StartupProgram;
+use crate::rules::expand_env_vars;
+
+#[cfg(target_os = "windows")]
+use windows::{
+    core::{PCWSTR, PWSTR},
+    Win32::Foundation::ERROR_SUCCESS,
+    Win32::System::Com::CoTaskMemFree,
+    Win32::System::Registry::{
+        RegCloseKey, RegEnumValueW, RegOpenKeyExW, HKEY, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, KEY_READ,
+        REG_EXPAND_SZ, REG_SZ,
+    },
+    Win32::UI::Shell::{FOLDERID_Startup, SHGetKnownFolderPath, KF_FLAG_DEFAULT},
+};
+
+const RUN_KEY: &str = "Software\\Microsoft\\Windows\\CurrentVersion\\Run";
+
+/// Pull the executable path out of a Run command line, e.g.
+/// `"C:\Program Files\Steam\steam.exe" -silent` or `C:\Tools\app.exe /min`
+fn command_executable(command: &str) -> String {
+    let command = expand_env_vars(command.trim());
+    if let Some(quoted) = command.strip_prefix('"') {
+        return quoted.split('"').next().unwrap_or(quoted).to_string();
+    }
+
+    // Unquoted paths may contain spaces, so cut after ".exe" when present
+    let lower = command.to_lowercase();
+    match lower.find(".exe") {
+        Some(end) => command[..end + 4].to_string(),
+        None => command.split_whitespace().next().unwrap_or("").to_string(),
+    }
+}
+
+/// Read the string values of a Run key
+#[cfg(target_os = "windows")]
+fn read_run_key(root: HKEY, root_name: &str) -> Vec<StartupProgram> {
+    let mut programs = Vec::new();
+    let subkey: Vec<u16> = RUN_KEY.encode_utf16().chain(std::iter::once(0)).collect();
+
+    unsafe {
+        let mut key = HKEY::default();
+        if RegOpenKeyExW(root, PCWSTR(subkey.as_ptr()), None, KEY_READ, &mut key) != ERROR_SUCCESS {
+            return programs;
+        }
+
+        let mut index = 0;
+        loop {
+            let mut name = [0u16; 512];
+            let mut name_len = name.len() as u32;
+            let mut data = [0u16; 2048];
+            let mut data_len = (data.len() * 2) as u32;
+            let mut value_type = 0u32;
+
+            let status = RegEnumValueW(
+                key,
+                index,
+                Some(PWSTR(name.as_mut_ptr())),
+                &mut name_len,
+                None,
+                Some(&mut value_type),
+                Some(data.as_mut_ptr() as *mut u8),
+                Some(&mut data_len),
+            );
+            if status != ERROR_SUCCESS {
+                // ERROR_NO_MORE_ITEMS, or a value too large for the buffer; stop either way
+                break;
+            }
+            index += 1;
+
+            if value_type != REG_SZ.0 && value_type != REG_EXPAND_SZ.0 {
+                continue;
+            }
+
+            let chars = &data[..(data_len as usize / 2).min(data.len())];
+            let end = chars.iter().position(|&c| c == 0).unwrap_or(chars.len());
+            let command = String::from_utf16_lossy(&chars[..end]);
+            let executable_path = command_executable(&command);
+            if executable_path.is_empty() {
+                continue;
+            }
+
+            programs.push(StartupProgram {
+                name: String::from_utf16_lossy(&name[..name_len as usize]),
+                executable_path,
+                registry_key: format!("{}\\{}", root_name, RUN_KEY),
+            });
+        }
+
+        let _ = RegCloseKey(key);
+    }
+
+    programs
+}
+
+/// Items in the user's Startup folder. Shortcuts are listed by their .lnk path.
+#[cfg(target_os = "windows")]
+fn read_startup_folder() -> Vec<StartupProgram> {
+    let folder = unsafe {
+        let Ok(path) = SHGetKnownFolderPath(&FOLDERID_Startup, KF_FLAG_DEFAULT, None) else {
+            return Vec::new();
+        };
+        let folder = path.to_string().unwrap_or_default();
+        CoTaskMemFree(Some(path.0 as *const _));
+        folder
+    };
+
+    let Ok(entries) = std::fs::read_dir(&folder) else {
+        return Vec::new();
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().map(|t| t.is_file()).unwrap_or(false))
+        .filter_map(|entry| {
+            let path = entry.path();
+            // desktop.ini sits in every shell folder
+            if path.file_name()?.to_string_lossy().eq_ignore_ascii_case("desktop.ini") {
+                return None;
+            }
+            Some(StartupProgram {
+                name: path.file_stem()?.to_string_lossy().to_string(),
+                executable_path: path.to_string_lossy().to_string(),
+                registry_key: folder.clone(),
+            })
+        })
+        .collect()
+}
+
+/// List programs that start with Windows, from HKCU and HKLM Run keys and the Startup folder
+#[cfg(target_os = "windows")]
+pub fn list_startup_programs() -> Vec<StartupProgram> {
+    let mut programs = read_run_key(HKEY_CURRENT_USER, "HKEY_CURRENT_USER");
+    programs.extend(read_run_key(HKEY_LOCAL_MACHINE, "HKEY_LOCAL_MACHINE"));
+    programs.extend(read_startup_folder());
+    programs
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn list_startup_programs() -> Vec<StartupProgram> {
+    Vec::new()
+}
@@ -0,0 +1,77 @@
+//! Executable version resources
+//!
+//! Reads the VS_VERSIONINFO string table that Explorer shows under Details.
+
+use crate::metadata::VersionInfo;
+
+#[cfg(target_os = "windows")]
+use windows::{
+    core::PCWSTR,
+    Win32::Storage::FileSystem::{GetFileVersionInfoSizeW, GetFileVersionInfoW, VerQueryValueW},
+};
+
+#[cfg(target_os = "windows")]
+fn to_wide(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// Look up a value in a loaded version block, returning the raw bytes
+#[cfg(target_os = "windows")]
+unsafe fn query_value<'a>(block: &'a [u8], sub_block: &str) -> Option<&'a [u8]> {
+    let wide = to_wide(sub_block);
+    let mut ptr: *mut std::ffi::c_void = std::ptr::null_mut();
+    let mut len: u32 = 0;
+    if !VerQueryValueW(block.as_ptr() as *const _, PCWSTR(wide.as_ptr()), &mut ptr, &mut len).as_bool() || ptr.is_null() {
+        return None;
+    }
+    Some(std::slice::from_raw_parts(ptr as *const u8, len as usize))
+}
+
+/// Read a string from the block's first language/codepage table.
+/// VerQueryValueW reports string lengths in characters, including the terminator.
+#[cfg(target_os = "windows")]
+unsafe fn query_string(block: &[u8], table: &str, name: &str) -> Option<String> {
+    let raw = query_value(block, &format!("\\StringFileInfo\\{}\\{}", table, name))?;
+    let chars = std::slice::from_raw_parts(raw.as_ptr() as *const u16, raw.len());
+    let end = chars.iter().position(|&c| c == 0).unwrap_or(chars.len());
+    let value = String::from_utf16_lossy(&chars[..end]).trim().to_string();
+    (!value.is_empty()).then_some(value)
+}
+
+/// Read product name, description, company and version from an executable
+#[cfg(target_os = "windows")]
+pub fn get_version_info(path: &str) -> Option<VersionInfo> {
+    let wide_path = to_wide(path);
+
+    unsafe {
+        let size = GetFileVersionInfoSizeW(PCWSTR(wide_path.as_ptr()), None);
+        if size == 0 {
+            return None;
+        }
+
+        let mut block = vec![0u8; size as usize];
+        GetFileVersionInfoW(PCWSTR(wide_path.as_ptr()), None, size, block.as_mut_ptr() as *mut _).ok()?;
+
+        // Translation entries are (language, codepage) u16 pairs; fall back to US English / Unicode
+        let table = query_value(&block, "\\VarFileInfo\\Translation")
+            .filter(|t| t.len() >= 4)
+            .map(|t| {
+                let language = u16::from_le_bytes([t[0], t[1]]);
+                let codepage = u16::from_le_bytes([t[2], t[3]]);
+                format!("{:04x}{:04x}", language, codepage)
+            })
+            .unwrap_or_else(|| "040904b0".to_string());
+
+        Some(VersionInfo {
+            product_name: query_string(&block, &table, "ProductName"),
+            file_description: query_string(&block, &table, "FileDescription"),
+            company_name: query_string(&block, &table, "CompanyName"),
+            file_version: query_string(&block, &table, "FileVersion"),
+        })
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn get_version_info(_path: &str) -> Option<VersionInfo> {
+    None
+}
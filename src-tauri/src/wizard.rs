@@ -0,0 +1,362 @@
+//! "Fix my game" guided flow
+//!
+//! `diagnose_game` gathers the capture facts for a game plus the other
+//! executables installed next to it, and `recommend` turns them into actions.
+//! Recommendations are pure over the gathered facts. `apply_recommendations`
+//! regenerates them, runs the chosen subset as one batch with a single save,
+//! verifies the batch, and journals and records each executable in the
+//! manifest like the blacklist command does, so a wizard change can be audited
+//! and undone like any other.
+
+use serde::{Deserialize, Serialize};
+use crate::capture::{self, CaptureExplanation, CaptureFacts, CaptureVerdict};
+use crate::journal::{self, JournalCategory};
+use crate::manifest;
+use crate::nvapi::ffi::SHADOWPLAY_DISABLED;
+use crate::nvapi::resolve::{basename, executable_key};
+use crate::nvapi::session::{reload_settings, save_settings};
+use crate::nvapi::settings::stage_blacklist;
+use crate::nvapi::types::{BatchVerification, ModifiedBy};
+use crate::nvapi::verify::verify_batch;
+
+/// What the wizard can suggest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RecommendedAction {
+    BlacklistExecutable { executable: String },
+    /// Launchers often host their own overlay window that ShadowPlay hooks too
+    BlacklistLauncher { launcher: String },
+    /// DRS changes only apply from the next launch
+    RestartGame { process_ids: Vec<u32> },
+    /// Windows Game DVR captures independently and has to be turned off in Windows
+    DisableGameDvr,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Recommendation {
+    /// Stable for the same facts, so the UI can send back its selection
+    pub id: String,
+    pub action: RecommendedAction,
+    pub reason: String,
+    /// Whether `apply_recommendations` performs it; the others are instructions for the user
+    pub automatic: bool,
+}
+
+/// Another executable installed with the game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SiblingExecutable {
+    pub executable: String,
+    /// None if it has no DRS entry or couldn't be read
+    pub blacklisted: Option<bool>,
+}
+
+/// Everything the recommendations depend on
+#[derive(Debug, Clone)]
+pub struct GameFacts {
+    pub capture: CaptureFacts,
+    pub siblings: Vec<SiblingExecutable>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameDiagnosis {
+    pub explanation: CaptureExplanation,
+    pub siblings: Vec<SiblingExecutable>,
+    pub recommendations: Vec<Recommendation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ActionOutcome {
+    Applied,
+    Failed,
+    /// Left to the user
+    Manual,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionResult {
+    pub id: String,
+    pub action: RecommendedAction,
+    pub outcome: ActionOutcome,
+    pub message: String,
+}
+
+fn is_launcher(executable: &str) -> bool {
+    executable_key(basename(executable)).contains("launcher")
+}
+
+/// Recommend actions for a game. Pure: the same facts always give the same list.
+pub fn recommend(facts: &GameFacts) -> Vec<Recommendation> {
+    let explanation = capture::compose(&facts.capture);
+    let mut recommendations = Vec::new();
+
+    if explanation.verdict != CaptureVerdict::WillNotCapture {
+        recommendations.push(Recommendation {
+            id: "blacklist-executable".to_string(),
+            action: RecommendedAction::BlacklistExecutable { executable: facts.capture.executable.clone() },
+            reason: "ShadowPlay can still capture this game".to_string(),
+            automatic: true,
+        });
+    }
+
+    let game_key = executable_key(basename(&facts.capture.executable));
+    for sibling in &facts.siblings {
+        if sibling.blacklisted == Some(true)
+            || !is_launcher(&sibling.executable)
+            || executable_key(basename(&sibling.executable)) == game_key
+        {
+            continue;
+        }
+        recommendations.push(Recommendation {
+            id: format!("blacklist-launcher:{}", executable_key(basename(&sibling.executable))),
+            action: RecommendedAction::BlacklistLauncher { launcher: sibling.executable.clone() },
+            reason: format!("{} is installed with the game and may show the overlay", basename(&sibling.executable)),
+            automatic: true,
+        });
+    }
+
+    if !recommendations.is_empty() && !facts.capture.running_process_ids.is_empty() {
+        recommendations.push(Recommendation {
+            id: "restart-game".to_string(),
+            action: RecommendedAction::RestartGame { process_ids: facts.capture.running_process_ids.clone() },
+            reason: "The game is running; driver changes apply from its next launch".to_string(),
+            automatic: false,
+        });
+    }
+
+    if facts.capture.game_dvr_enabled == Some(true) {
+        recommendations.push(Recommendation {
+            id: "disable-game-dvr".to_string(),
+            action: RecommendedAction::DisableGameDvr,
+            reason: "Windows Game DVR is on and can cause the same stutter".to_string(),
+            automatic: false,
+        });
+    }
+
+    recommendations
+}
+
+/// Full path of the game, from the argument or a running process
+fn executable_path(facts: &CaptureFacts) -> Option<String> {
+    if facts.executable.contains(['\\', '/']) {
+        return Some(facts.executable.clone());
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        crate::windows::get_running_processes().into_iter()
+            .find(|p| facts.running_process_ids.contains(&p.process_id))
+            .and_then(|p| p.executable_path)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        None
+    }
+}
+
+/// Executables of the installed program whose install folder holds the game
+#[cfg(target_os = "windows")]
+fn find_siblings(path: &str) -> Vec<SiblingExecutable> {
+    let path = path.to_lowercase();
+    let program = crate::windows::list_installed_programs().into_iter()
+        .filter(|p| {
+            p.install_location.as_deref()
+                .is_some_and(|location| path.starts_with(&format!("{}\\", location.to_lowercase())))
+        })
+        // The innermost install folder is the game's own
+        .max_by_key(|p| p.install_location.as_ref().map_or(0, |l| l.len()));
+
+    program.map(|p| p.executables).unwrap_or_default()
+        .into_iter()
+        .filter(|exe| exe.to_lowercase() != path)
+        .map(|executable| {
+            let blacklisted = crate::nvapi::settings::is_blacklisted(basename(&executable))
+                .ok()
+                .and_then(|q| q.blacklisted);
+            SiblingExecutable { executable, blacklisted }
+        })
+        .collect()
+}
+
+#[cfg(not(target_os = "windows"))]
+fn find_siblings(_path: &str) -> Vec<SiblingExecutable> {
+    Vec::new()
+}
+
+/// Gather the facts for an executable name, path, or running pid
+pub fn gather(executable_or_pid: &str) -> GameFacts {
+    let capture = capture::gather(executable_or_pid);
+    let siblings = executable_path(&capture).map(|path| find_siblings(&path)).unwrap_or_default();
+    GameFacts { capture, siblings }
+}
+
+/// Diagnose a game and recommend how to keep the overlay away from it
+pub fn diagnose_game(executable_or_pid: &str) -> GameDiagnosis {
+    let facts = gather(executable_or_pid);
+    GameDiagnosis {
+        explanation: capture::compose(&facts.capture),
+        recommendations: recommend(&facts),
+        siblings: facts.siblings,
+    }
+}
+
+fn target_executable(action: &RecommendedAction) -> Option<&str> {
+    match action {
+        RecommendedAction::BlacklistExecutable { executable } => Some(executable),
+        RecommendedAction::BlacklistLauncher { launcher } => Some(basename(launcher)),
+        RecommendedAction::RestartGame { .. } | RecommendedAction::DisableGameDvr => None,
+    }
+}
+
+/// Journal each blacklist the batch attempted under the blacklist command's
+/// code. Manual recommendations changed nothing and aren't journaled.
+fn journal_results(results: &[ActionResult]) {
+    for result in results.iter().filter(|r| r.outcome != ActionOutcome::Manual) {
+        let Some(executable) = target_executable(&result.action) else {
+            continue;
+        };
+        let applied = result.outcome == ActionOutcome::Applied;
+        journal::append(JournalCategory::Mutation, journal::severity_for(!applied), "application.blacklisted", serde_json::json!({
+            "success": applied,
+            "executable": executable,
+            "message": result.message,
+            "modifiedBy": ModifiedBy::Wizard,
+        }));
+    }
+}
+
+/// Apply the selected recommendations as one batch. Recommendations are
+/// regenerated from fresh facts, so ids that no longer apply are refused.
+pub fn apply_recommendations(executable_or_pid: &str, selected: &[String]) -> Result<(Vec<ActionResult>, Option<BatchVerification>), String> {
+    let recommendations = recommend(&gather(executable_or_pid));
+    let chosen: Vec<Recommendation> = selected.iter()
+        .map(|id| {
+            recommendations.iter()
+                .find(|r| &r.id == id)
+                .cloned()
+                .ok_or_else(|| format!("Recommendation '{}' no longer applies; diagnose the game again", id))
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut results: Vec<ActionResult> = chosen.into_iter()
+        .map(|r| {
+            let (outcome, message) = match target_executable(&r.action) {
+                Some(executable) if r.automatic => match stage_blacklist(executable) {
+                    Ok(()) => (ActionOutcome::Applied, "Blacklisted".to_string()),
+                    Err(e) => (ActionOutcome::Failed, e.to_string()),
+                },
+                _ => (ActionOutcome::Manual, r.reason.clone()),
+            };
+            ActionResult { id: r.id, action: r.action, outcome, message }
+        })
+        .collect();
+
+    if !results.iter().any(|r| r.outcome == ActionOutcome::Applied) {
+        journal_results(&results);
+        return Ok((results, None));
+    }
+    if let Err(e) = save_settings() {
+        // Don't leave half a batch in the session
+        let _ = reload_settings();
+        return Err(e.to_string());
+    }
+    // The ShadowPlay writes were already remembered for revert loop detection
+    // by the settings layer; what was saved is journaled before verifying
+    journal_results(&results);
+
+    let expectations: Vec<(String, u32)> = results.iter()
+        .filter(|r| r.outcome == ActionOutcome::Applied)
+        .filter_map(|r| target_executable(&r.action))
+        .map(|executable| (executable.to_string(), SHADOWPLAY_DISABLED))
+        .collect();
+    let verification = verify_batch(&expectations).map_err(|e| format!("Saved, but the changes could not be verified: {}", e))?;
+
+    let applied = results.iter_mut().filter(|r| r.outcome == ActionOutcome::Applied);
+    for (result, entry) in applied.zip(&verification.entries) {
+        if entry.verified {
+            manifest::record_executable_or_journal(&entry.executable, false, ModifiedBy::Wizard);
+            continue;
+        }
+        result.outcome = ActionOutcome::Failed;
+        result.message = match &entry.error {
+            Some(e) => format!("Saved, but the change could not be verified: {}", e),
+            None => "Saved, but the driver still reports ShadowPlay enabled".to_string(),
+        };
+    }
+    Ok((results, Some(verification)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::SettingLookup;
+
+    fn facts(profile_value: Option<u32>, siblings: &[(&str, Option<bool>)]) -> GameFacts {
+        GameFacts {
+            capture: CaptureFacts {
+                executable: "Game.exe".to_string(),
+                profile: profile_value.map(|value| ("Game".to_string(), SettingLookup::Set(value))),
+                profile_gpu_support: Vec::new(),
+                profile_applies_to_gpu: None,
+                lookup_error: None,
+                base_profile: SettingLookup::NotSet,
+                running_process_ids: Vec::new(),
+                overlay_running: None,
+                fullscreen: None,
+                game_dvr_enabled: None,
+                sibling_profile: None,
+            },
+            siblings: siblings.iter()
+                .map(|&(executable, blacklisted)| SiblingExecutable { executable: executable.to_string(), blacklisted })
+                .collect(),
+        }
+    }
+
+    fn ids(recommendations: &[Recommendation]) -> Vec<&str> {
+        recommendations.iter().map(|r| r.id.as_str()).collect()
+    }
+
+    #[test]
+    fn a_capturing_game_is_blacklisted_and_a_blocked_one_is_left_alone() {
+        assert_eq!(ids(&recommend(&facts(None, &[]))), ["blacklist-executable"]);
+        assert!(recommend(&facts(Some(SHADOWPLAY_DISABLED), &[])).is_empty());
+    }
+
+    #[test]
+    fn only_unblocked_launchers_beside_the_game_are_suggested() {
+        let gathered = facts(Some(SHADOWPLAY_DISABLED), &[
+            ("C:\\Games\\Game\\GameLauncher.exe", Some(false)),
+            ("C:\\Games\\Game\\OtherLauncher.exe", Some(true)),
+            ("C:\\Games\\Game\\Crashpad.exe", None),
+            ("C:\\Games\\Game\\launcher.exe", None),
+        ]);
+        let recommendations = recommend(&gathered);
+
+        assert_eq!(ids(&recommendations), ["blacklist-launcher:gamelauncher.exe", "blacklist-launcher:launcher.exe"]);
+        assert!(recommendations.iter().all(|r| r.automatic));
+        assert_eq!(target_executable(&recommendations[0].action), Some("GameLauncher.exe"));
+    }
+
+    #[test]
+    fn restart_and_game_dvr_are_manual_steps() {
+        let mut gathered = facts(None, &[]);
+        gathered.capture.running_process_ids = vec![7];
+        gathered.capture.game_dvr_enabled = Some(true);
+        let recommendations = recommend(&gathered);
+
+        assert_eq!(ids(&recommendations), ["blacklist-executable", "restart-game", "disable-game-dvr"]);
+        assert!(!recommendations[1].automatic && !recommendations[2].automatic);
+        assert_eq!(target_executable(&recommendations[1].action), None);
+    }
+
+    #[test]
+    fn a_running_game_with_nothing_to_change_needs_no_restart() {
+        let mut gathered = facts(Some(SHADOWPLAY_DISABLED), &[]);
+        gathered.capture.running_process_ids = vec![7];
+        assert!(recommend(&gathered).is_empty());
+    }
+}
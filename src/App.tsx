@@ -1,20 +1,31 @@
 import { useState, useEffect, useCallback } from "react";
+import { listen } from "@tauri-apps/api/event";
 import "./App.css";
 import type {
   RunningProcess,
   DrsApplication,
   FocusApplication,
   NvApiStatus,
+  UnsavedChanges,
+  AutomationState,
+  BlacklistSnapshot,
 } from "./types";
 import {
   checkNvApiStatus,
   getRunningProcesses,
   getAllApplications,
+  getBlacklistSnapshot,
+  getTotalApplicationCount,
   getFocusApplication,
   blacklistApplication,
   unblacklistApplication,
   createProfile,
   reloadSettings,
+  getAutomationState,
+  setAutomationPaused,
+  saveAndExit,
+  discardAndExit,
+  cancelExit,
 } from "./utils/invoke";
 
 // Icons as SVG components
@@ -36,16 +47,27 @@ const RefreshIcon = () => (
 
 type Tab = "running" | "drs";
 
+/**
+ * Log how long after the page loaded the blacklist view got its applications,
+ * so cold starts served from the snapshot can be compared with live ones
+ */
+function logStartupTiming(source: string) {
+  console.info(`Blacklist view rendered from ${source} ${Math.round(performance.now())} ms after start`);
+}
+
 function App() {
   const [nvApiStatus, setNvApiStatus] = useState<NvApiStatus | null>(null);
   const [activeTab, setActiveTab] = useState<Tab>("running");
   const [searchQuery, setSearchQuery] = useState("");
   const [runningProcesses, setRunningProcesses] = useState<RunningProcess[]>([]);
   const [drsApplications, setDrsApplications] = useState<DrsApplication[]>([]);
+  const [totalApplications, setTotalApplications] = useState<number | null>(null);
   const [focusApp, setFocusApp] = useState<FocusApplication | null>(null);
   const [loading, setLoading] = useState(true);
   const [refreshing, setRefreshing] = useState(false);
   const [pendingActions, setPendingActions] = useState<Set<string>>(new Set());
+  const [automationPaused, setAutomationPausedState] = useState(false);
+  const [driverChanging, setDriverChanging] = useState(false);
   const [toast, setToast] = useState<{ message: string; type: "success" | "error" } | null>(null);
 
   const showToast = useCallback((message: string, type: "success" | "error") => {
@@ -60,10 +82,70 @@ function App() {
     });
   }, []);
 
-  // Load data when NVAPI is available
+  // Restore automation pause state and catch up once on resume
   useEffect(() => {
-    if (nvApiStatus?.available) {
+    getAutomationState()
+      .then(state => setAutomationPausedState(state.paused))
+      .catch(() => {});
+
+    // The tray's pause item changes the state outside this window
+    const changed = listen<AutomationState>("automation-state-changed", event => {
+      setAutomationPausedState(event.payload.paused);
+    });
+    const unlisten = listen("automation-resumed", () => {
       loadData();
+    });
+    return () => {
+      changed.then(fn => fn());
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  // Show a banner while the NVIDIA driver is being reinstalled
+  useEffect(() => {
+    const detected = listen("driver-change-detected", () => setDriverChanging(true));
+    const recovered = listen("driver-change-recovered", () => {
+      setDriverChanging(false);
+      loadData();
+    });
+    return () => {
+      detected.then(fn => fn());
+      recovered.then(fn => fn());
+    };
+  }, []);
+
+  // Ask what to do with unsaved DRS changes when the window is closed
+  useEffect(() => {
+    const unlisten = listen<UnsavedChanges>("unsaved-changes", event => {
+      const list = event.payload.operations.join("\n");
+      if (window.confirm(`Save these unsaved changes before closing?\n\n${list}`)) {
+        saveAndExit().catch(err => showToast(String(err), "error"));
+      } else if (window.confirm("Close without saving?")) {
+        discardAndExit();
+      } else {
+        cancelExit();
+      }
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, [showToast]);
+
+  // Replace a stale cold-start snapshot once the background enumeration finishes
+  useEffect(() => {
+    const unlisten = listen<BlacklistSnapshot>("blacklist-snapshot", event => {
+      setDrsApplications(event.payload.applications);
+      logStartupTiming("live enumeration");
+    });
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
+
+  // Load data when NVAPI is available, rendering the first time from the cold-start snapshot
+  useEffect(() => {
+    if (nvApiStatus?.available) {
+      loadData(true);
     } else {
       setLoading(false);
     }
@@ -71,7 +153,7 @@ function App() {
 
   // Poll focus application every second
   useEffect(() => {
-    if (!nvApiStatus?.available) return;
+    if (!nvApiStatus?.available || automationPaused) return;
 
     const pollFocus = async () => {
       try {
@@ -85,24 +167,25 @@ function App() {
     pollFocus();
     const interval = setInterval(pollFocus, 1000);
     return () => clearInterval(interval);
-  }, [nvApiStatus?.available]);
+  }, [nvApiStatus?.available, automationPaused]);
 
   // Auto-refresh process list every 10 seconds
   useEffect(() => {
-    if (!nvApiStatus?.available) return;
+    if (!nvApiStatus?.available || automationPaused) return;
 
     const interval = setInterval(() => {
       loadRunningProcesses();
     }, 10000);
     return () => clearInterval(interval);
-  }, [nvApiStatus?.available]);
+  }, [nvApiStatus?.available, automationPaused]);
 
-  const loadData = async () => {
+  const loadData = async (fromSnapshot = false) => {
     setLoading(true);
     try {
       await Promise.all([
         loadRunningProcesses(),
-        loadDrsApplications(),
+        fromSnapshot ? loadSnapshot() : loadDrsApplications(),
+        loadTotalApplications(),
       ]);
     } finally {
       setLoading(false);
@@ -127,6 +210,25 @@ function App() {
     }
   };
 
+  const loadSnapshot = async () => {
+    try {
+      const snapshot = await getBlacklistSnapshot();
+      setDrsApplications(snapshot.applications);
+      logStartupTiming(snapshot.stale ? "cached snapshot" : "live enumeration");
+    } catch (e) {
+      console.error("Failed to load the blacklist snapshot:", e);
+      await loadDrsApplications();
+    }
+  };
+
+  const loadTotalApplications = async () => {
+    try {
+      setTotalApplications(await getTotalApplicationCount());
+    } catch (e) {
+      console.error("Failed to count DRS applications:", e);
+    }
+  };
+
   const handleRefresh = async () => {
     setRefreshing(true);
     try {
@@ -137,6 +239,15 @@ function App() {
     }
   };
 
+  const handleToggleAutomation = async () => {
+    try {
+      const state = await setAutomationPaused(!automationPaused);
+      setAutomationPausedState(state.paused);
+    } catch (e) {
+      showToast(`Failed to change automation state: ${e}`, "error");
+    }
+  };
+
   const handleToggleBlacklist = useCallback(async (executable: string, isCurrentlyBlacklisted: boolean | null) => {
     setPendingActions(prev => new Set(prev).add(executable));
     try {
@@ -214,6 +325,14 @@ function App() {
         </div>
 
         <div style={{ display: "flex", alignItems: "center", gap: 12 }}>
+          <button
+            className="refresh-btn"
+            onClick={handleToggleAutomation}
+            title={automationPaused ? "Resume automation" : "Pause automation"}
+          >
+            {automationPaused ? "▶" : "❚❚"}
+          </button>
+
           <button
             className={`refresh-btn ${refreshing ? "spinning" : ""}`}
             onClick={handleRefresh}
@@ -292,6 +411,11 @@ function App() {
             DRS Applications
             <span className="tab-count">{drsApplications.length}</span>
           </button>
+          {totalApplications !== null && (
+            <span style={{ marginLeft: "auto", alignSelf: "center", fontSize: 12, opacity: 0.7 }}>
+              Total applications managed: {totalApplications}
+            </span>
+          )}
         </div>
 
         {/* Search Bar */}
@@ -319,6 +443,12 @@ function App() {
           </div>
         )}
 
+        {driverChanging && (
+          <div className="error-banner">
+            <strong>Driver update in progress:</strong> NVIDIA settings are unavailable until it finishes.
+          </div>
+        )}
+
         {/* Content */}
         {loading ? (
           <div className="loading-container">
@@ -346,6 +476,14 @@ function App() {
                     <div className="app-title">{process.windowTitle}</div>
                   </div>
                   <div className="app-meta">
+                    {process.accessibility === "requiresElevation" && (
+                      <div
+                        className="app-profile-badge requires-elevation"
+                        title="This process runs as administrator; restart Nvidiot as administrator to manage it"
+                      >
+                        Elevated
+                      </div>
+                    )}
                     {process.hasDrsProfile ? (
                       <>
                         <div className="app-profile-badge has-profile">
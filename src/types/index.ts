@@ -4,24 +4,139 @@ export interface DrsProfile {
   name: string;
   isPredefined: boolean;
   applicationCount: number;
+  settingsCount: number;
+  isBlacklisted: boolean | null;
+  userCanDelete: boolean;
+  userCanModify: boolean;
+  userCreatedByNvidiot: boolean;
+  gpuSupport: GpuProductLine[];
+  /** null when the installed GPUs can't be detected */
+  appliesToCurrentGpu: boolean | null;
 }
 
+export type GpuProductLine = "geforce" | "quadro" | "nvs";
+
+export type ProfileSortField = "name" | "applicationCount" | "settingsCount";
+
+export type ApplicationSortField =
+  | "executableName"
+  | "friendlyName"
+  | "blacklistStatus"
+  | "isPredefined";
+
 export interface DrsApplication {
   name: string;
   executable: string;
   profileName: string;
   isPredefined: boolean;
   isBlacklisted: boolean;
+  /** Who disabled ShadowPlay for the profile; absent when it isn't disabled */
+  blacklistOrigin?: BlacklistOrigin;
+  /** Present only for entries Nvidiot manages */
+  provenance?: Provenance;
+  /** Only read by get_application_info; absent from lists and when the driver lacks the match fields */
+  matchMode?: ApplicationMatchMode;
+}
+
+export type ApplicationMatchMode = "name" | "fileInFolder" | "commandLine" | "metro";
+
+/** Why an entry asked to match on its signer is matched by name instead */
+export type SignerMatchFallback = "matchFieldsUnsupported" | "noSignerField";
+
+export interface ApplicationCreated {
+  matchMode: ApplicationMatchMode;
+  signerFallback: SignerMatchFallback | null;
+}
+
+export type BlacklistOrigin = "nvidia" | "userOverride" | "user";
+
+/** Also the payload of the "blacklist-snapshot" event */
+export interface BlacklistSnapshot {
+  applications: DrsApplication[];
+  /** Served from the previous run; a live snapshot follows as an event */
+  stale: boolean;
+  takenAtMs: number;
+}
+
+export type BlacklistWarning = { type: "nvidiaPredefined"; profileName: string };
+
+export type ModifiedBy =
+  | { type: "manual" }
+  | { type: "rule"; name: string }
+  | { type: "import" }
+  | { type: "watchdog" }
+  | { type: "wizard" };
+
+export interface Provenance {
+  createdAtMs: number;
+  lastModifiedAtMs: number;
+  lastModifiedBy: ModifiedBy;
+}
+
+export type ManagedSortField = "created" | "modified";
+
+export interface ManagedEntry {
+  executable: string;
+  profileName: string;
+  createdAtMs: number;
+  adopted: boolean;
+  lastModifiedAtMs: number;
+  lastModifiedBy: ModifiedBy;
+  /** Another tool kept undoing our change soon after it was made */
+  revertLoopDetected: boolean;
 }
 
+export interface ProfileRename {
+  from: string;
+  to: string;
+}
+
+export interface ProfileRenameFailure {
+  profileName: string;
+  error: string;
+}
+
+export interface ProfileRenameReport {
+  renamed: ProfileRename[];
+  collisions: ProfileRename[];
+  failed: ProfileRenameFailure[];
+  unchanged: number;
+}
+
+export interface WindowRect {
+  left: number;
+  top: number;
+  right: number;
+  bottom: number;
+}
+
+export type ProcessArchitecture = "X86" | "X64" | "Arm64" | "Arm32";
+
 export interface RunningProcess {
   processName: string;
   windowTitle: string;
   processId: number;
   executablePath: string | null;
+  signer: string | null;
+  architecture: ProcessArchitecture | null;
   hasDrsProfile: boolean;
   profileName: string | null;
   isBlacklisted: boolean | null;
+  dpi: number | null;
+  memoryUsageMb: number | null;
+  cpuUsagePercent: number | null;
+  usesNvidiaGpu: boolean | null;
+  gpuMemoryMb: number | null;
+  accessibility: ProcessAccessibility;
+}
+
+export type ProcessAccessibility = "accessible" | "requiresElevation" | "notFound";
+
+export interface ProcessBlacklistStatus {
+  processName: string;
+  processId: number;
+  isBlacklisted: boolean | null;
+  profileName: string | null;
 }
 
 export interface FocusApplication {
@@ -31,15 +146,874 @@ export interface FocusApplication {
   isInDrs: boolean;
   profileName: string | null;
   isBlacklisted: boolean | null;
+  dpi: number | null;
+  windowRect: WindowRect | null;
+  monitorDeviceName: string | null;
+}
+
+export interface MonitorInfo {
+  deviceName: string;
+  isPrimary: boolean;
+  rect: WindowRect;
+}
+
+export interface FocusApplicationExtended extends FocusApplication {
+  cpuUsagePercent: number | null;
+  /** GPU memory used by the focused process, when NVML can attribute it */
+  gpuMemoryMb: number | null;
+  /** Dedicated video memory in use on the first GPU by every process */
+  deviceMemoryUsedMb: number | null;
 }
 
 export interface BlacklistResult {
   success: boolean;
   executable: string;
   message: string;
+  affectedProfileName: string | null;
+  scope: DrsScope;
+  /** Whether the application was blacklisted before a toggle */
+  previousStatus?: boolean;
+  warning?: BlacklistWarning;
+}
+
+export type StatusSource = "cache" | "live";
+
+export interface BlacklistQuery {
+  /** null if the executable has no DRS entry */
+  blacklisted: boolean | null;
+  source: StatusSource;
+  generation: number;
+}
+
+export type PowerMode =
+  | "adaptive"
+  | "preferMaximumPerformance"
+  | "driverControlled"
+  | "preferConsistentPerformance"
+  | "preferMinimumPower"
+  | "optimalPower";
+
+export interface PowerModeStatus {
+  executable: string;
+  /** null if the driver reports a value this version doesn't know */
+  mode: PowerMode | null;
+  value: number;
+  /** The base profile when the executable has no entry */
+  profileName: string | null;
+  isOverride: boolean;
+}
+
+export interface SkippedProfile {
+  profileName: string;
+  reason: string;
+}
+
+export interface ProfileDeletion {
+  deleted: number;
+  /** Profiles that are still there, with the error that kept them */
+  failed: SkippedProfile[];
+}
+
+export interface LegacyMigration {
+  migrated: string[];
+  skipped: SkippedProfile[];
 }
 
 export interface NvApiStatus {
   available: boolean;
   error: string | null;
+  isWindowsGameModeEnabled: boolean;
+}
+
+export interface WindowsGamingState {
+  gameModeEnabled: boolean;
+  gameDvrEnabled: boolean;
+  warning: string | null;
+}
+
+export interface StorageRecoveredEvent {
+  file: string;
+  /** Set when no usable backup existed: the bad file was moved here and the store reset to defaults */
+  quarantinedAs?: string;
+}
+
+export interface NvApiFunctionProbe {
+  id: number;
+  name: string;
+  resolved: boolean;
+}
+
+export interface NvApiProbe {
+  loadedAtMs: number | null;
+  loadAttempts: number;
+  reinitialized: boolean;
+  functions: NvApiFunctionProbe[];
+}
+
+export type FreshnessSource = "cache" | "live";
+
+export interface Freshness {
+  sessionGeneration: number;
+  /** When the oldest contributing cache was built; null for live reads */
+  cacheBuiltAtMs: number | null;
+  cacheAgeMs: number | null;
+  source: FreshnessSource;
+}
+
+export type JournalCategory =
+  | "mutation"
+  | "rule"
+  | "watcher"
+  | "verification"
+  | "recovery"
+  | "import"
+  | "storage"
+  | "task";
+
+export type JournalSeverity = "info" | "warning" | "error";
+
+/** One thing the backend did this session; payload of the journal-appended event */
+export interface JournalEntry {
+  seq: number;
+  atMs: number;
+  category: JournalCategory;
+  severity: JournalSeverity;
+  /** Message template, e.g. "rule.triggered" */
+  code: string;
+  params: unknown;
+}
+
+export interface JournalPage {
+  entries: JournalEntry[];
+  /** Pass back as sinceSeq to get only newer entries */
+  latestSeq: number;
+  /** Entries after sinceSeq were dropped before they were read */
+  truncated: boolean;
+}
+
+export interface CacheStats {
+  name: string;
+  hits: number;
+  misses: number;
+  /** Lookups answered from a remembered miss; only negative caches count these */
+  negativeHits: number;
+  entries: number;
+  approxBytes: number;
+}
+
+export interface Diagnostics {
+  nvapi: NvApiStatus;
+  probe: NvApiProbe;
+  sessionGeneration: number;
+  caches: CacheStats[];
+  totalCacheBytes: number;
+  /** DRS read statuses not yet classified as absent or failed */
+  unclassifiedStatuses: UnclassifiedStatus[];
+  /** Supervised background tasks still running, oldest first */
+  backgroundTasks: string[];
+}
+
+export interface UnclassifiedStatus {
+  function: string;
+  status: number;
+  count: number;
+}
+
+export interface AutomationSubsystem {
+  name: string;
+  active: boolean;
+}
+
+export interface AutomationState {
+  paused: boolean;
+  subsystems: AutomationSubsystem[];
+}
+
+/** A setting the driver knows about, whether or not any profile stores it */
+export interface AvailableSetting {
+  settingId: number;
+  name: string | null;
+}
+
+export interface DrsSetting {
+  settingId: number;
+  name: string;
+  settingType: number;
+  dwordValue: number | null;
+  isCurrentPredefined: boolean;
+  /** Current value decoded by the setting's type */
+  value?: SettingValue;
+}
+
+export type SettingValue =
+  | { type: "dword"; value: number }
+  | { type: "binary"; bytes: number[] }
+  | { type: "string"; value: string };
+
+export interface ResolutionStep {
+  strategy: string;
+  matched: boolean;
+  profileName: string | null;
+  detail: string;
+}
+
+export interface ResolutionTrace {
+  executable: string;
+  steps: ResolutionStep[];
+  resolvedProfile: string | null;
+  runningProcessIds: number[];
+}
+
+export interface FirstRunScan {
+  external: DrsApplication[];
+  managedCount: number;
+}
+
+export type RuleCondition =
+  | {
+      type: "titleMatches";
+      pattern: string;
+      whileFocusedOnly: boolean;
+    }
+  | {
+      type: "signerMatches";
+      substring: string;
+    };
+
+export type RuleAction = "blacklist" | "unblacklist";
+
+export interface NewRule {
+  name: string;
+  executable: string;
+  condition: RuleCondition;
+  action: RuleAction;
+  revertOnExit: boolean;
+}
+
+export interface Rule extends NewRule {
+  id: number;
+  enabled: boolean;
+  active: boolean;
+  suspended: boolean;
+}
+
+export interface RuleLimits {
+  perRulePerHour: number;
+  globalPerHour: number;
+  deniedDirectories: string[];
+}
+
+export interface CacheLimits {
+  metadataEntries: number;
+  iconBytes: number;
+}
+
+export interface RevertLoopDetection {
+  windowMinutes: number;
+  minReverts: number;
+  checkIntervalSecs: number;
+  /** Move a blacklist that keeps being undone into a dedicated override profile */
+  escalateToOverrideProfile: boolean;
+}
+
+/** Payload of the revert-loop-detected event */
+export interface RevertLoopDetected {
+  executable: string;
+  reverts: number;
+  windowMinutes: number;
+  /** Override profile the executable was moved to, if escalated */
+  escalatedTo: string | null;
+  guidance: string;
+}
+
+export interface BulkLimits {
+  maxProfiles: number;
+}
+
+export interface CommandLimits {
+  mutationsPerSecond: number;
+  mutationBurst: number;
+  maxBatchItems: number;
+  maxNameChars: number;
+  maxPathChars: number;
+  maxContentChars: number;
+}
+
+export interface BackendCapabilities {
+  features: FeatureAvailability[];
+  limits: CommandLimits;
+}
+
+/** Leading kind of an error message from a command-layer limit */
+export type LimitErrorKind = "rate-limited" | "too-many-items" | "argument-too-long";
+
+export interface ProfileFilter {
+  /** Case-insensitive substring of the profile name */
+  nameContains?: string | null;
+  /** Predefined profiles are only included when this is false */
+  onlyUserProfiles?: boolean;
+  onlyWithApps?: boolean;
+}
+
+export interface BulkProfileChange {
+  profileName: string;
+  /** The profile's own value before the change; null means no override */
+  previousValue: number | null;
+  error: string | null;
+}
+
+export interface BulkSetResult {
+  operationId: number | null;
+  dryRun: boolean;
+  settingId: number;
+  value: number;
+  changes: BulkProfileChange[];
+}
+
+export interface BulkOperation {
+  id: number;
+  settingId: number;
+  value: number;
+  appliedMs: number;
+  changes: BulkProfileChange[];
+  undone: boolean;
+}
+
+export interface ProfileNaming {
+  /** `{exe}` is the executable's file name, `{name}` the same without extension */
+  template: string;
+}
+
+export interface RuleSuspended {
+  ruleId: number;
+  ruleName: string;
+  executable: string;
+  scope: "rule" | "global";
+  limit: number;
+}
+
+export interface RuleTriggered {
+  ruleId: number;
+  ruleName: string;
+  executable: string;
+  transition: "entered" | "left";
+  action: RuleAction | null;
+  error: string | null;
+}
+
+export interface PendingRevert {
+  executable: string;
+  createdMs: number;
+  expiresMs: number;
+}
+
+export type RevertOutcome =
+  | { type: "reverted" }
+  | { type: "skippedManualChange" }
+  | { type: "failed"; error: string };
+
+export interface RevertCompleted {
+  executable: string;
+  outcome: RevertOutcome;
+}
+
+export interface FocusTitleChanged {
+  processName: string;
+  windowTitle: string;
+}
+
+export interface ShadowPlayDifference {
+  profileName: string;
+  live: boolean | null;
+  backup: boolean | null;
+}
+
+export interface BackupDiff {
+  profilesOnlyInLive: string[];
+  profilesOnlyInBackup: string[];
+  applicationsAdded: DrsApplication[];
+  applicationsRemoved: DrsApplication[];
+  shadowplayDifferences: ShadowPlayDifference[];
+}
+
+export type ShadowPlaySource = "profile" | "baseProfile" | "driverDefault";
+
+export interface EffectiveShadowPlay {
+  stored: number | null;
+  effective: number;
+  disabled: boolean;
+  source: ShadowPlaySource;
+}
+
+export interface SettingDifference {
+  settingId: number;
+  name: string;
+  valueA: SettingValue | null;
+  valueB: SettingValue | null;
+  predefinedA: boolean;
+  predefinedB: boolean;
+}
+
+/** An empty profile name is the base profile */
+export interface ProfileComparison {
+  profileA: string;
+  profileB: string;
+  settingsOnlyInA: DrsSetting[];
+  settingsOnlyInB: DrsSetting[];
+  settingDifferences: SettingDifference[];
+  applicationsOnlyInA: DrsApplication[];
+  applicationsOnlyInB: DrsApplication[];
+  shadowplayA: EffectiveShadowPlay;
+  shadowplayB: EffectiveShadowPlay;
+  /** False when DRS changed while the profiles were read; the sides may not match one state */
+  consistent: boolean;
+}
+
+export type BackupRestoreEntry =
+  | { type: "profile"; profileName: string }
+  | { type: "application"; profileName: string; executable: string; name: string }
+  | { type: "shadowPlay"; profileName: string; blacklisted: boolean };
+
+export interface UnsavedChanges {
+  operations: string[];
+}
+
+export type DrsScope = "userOnly" | "machine" | "unknown";
+
+export interface DrsStoreFile {
+  path: string;
+  scope: DrsScope;
+  exists: boolean;
+  modifiedMs: number | null;
+}
+
+export interface DrsStoreInfo {
+  files: DrsStoreFile[];
+  lastSaveScope: DrsScope;
+}
+
+export interface ScopeExplanation {
+  scope: DrsScope;
+  explanation: string;
+  paths: string[];
+}
+
+export interface PresetMetadata {
+  name: string;
+  author: string;
+  description: string;
+  minAppVersion: string | null;
+}
+
+export interface PresetConflict {
+  incoming: NewRule;
+  existingRuleId: number;
+  existingAction: RuleAction;
+}
+
+export interface PresetInstallPlan {
+  metadata: PresetMetadata;
+  toAdd: NewRule[];
+  duplicates: string[];
+  conflicts: PresetConflict[];
+  templates: string[];
+  installed: boolean;
+}
+
+export interface BlacklistFileEntry {
+  executable: string;
+  blacklisted: boolean;
+}
+
+export interface BlacklistFile {
+  entries: BlacklistFileEntry[];
+}
+
+export type ImportMode = "Merge" | "Replace";
+
+export type ContentEncoding = "Raw" | "Base64";
+
+export type ImportResolution = "KeepLocal" | "TakeImported";
+
+export interface ImportConflict {
+  executable: string;
+  current: boolean;
+  imported: boolean;
+  recorded: boolean;
+}
+
+export interface ImportOutcome {
+  token: number | null;
+  conflicts: ImportConflict[];
+  applied: number;
+  failed: string[];
+  /** How the applied entries read back after the save */
+  verification: BatchVerification | null;
+  /** Why the read-back check couldn't run */
+  verificationError: string | null;
+}
+
+export interface SavedOverride {
+  profileName: string;
+  value: number;
+}
+
+/** What a capture restore has to put back */
+export interface PanicRecord {
+  takenAtMs: number;
+  basePending: boolean;
+  /** Base profile override before the panic; null means it is deleted on restore */
+  baseValue: number | null;
+  overrides: SavedOverride[];
+}
+
+/** Payload of the "capture-panic" event */
+export interface PanicState {
+  engaged: boolean;
+  record: PanicRecord | null;
+}
+
+export interface Restoration {
+  /** null for the base profile */
+  profileName: string | null;
+  restoredValue: number | null;
+  verified: boolean;
+  /** The profile was deleted since the panic; not kept pending */
+  profileDeleted: boolean;
+  error: string | null;
+}
+
+/** A global hotkey as virtual key codes */
+export interface PanicHotkey {
+  virtualKey: number;
+  /** Alt, Ctrl, Shift or Windows keys; at least one is required */
+  modifiers: number[];
+}
+
+/** Payload of the "capture-restored" event */
+export interface RestoreReport {
+  restorations: Restoration[];
+  /** Still pending after a partial restore */
+  remaining: PanicRecord | null;
+}
+
+export interface NpiImportOptions {
+  /** Import every profile and DWORD setting, not only capture settings */
+  importAll?: boolean;
+  /** Write imported ShadowPlay values that conflict with changes made outside Nvidiot */
+  takeImportedConflicts?: boolean;
+}
+
+/** Payload of the "npi-import-progress" event */
+export interface NpiImportProgress {
+  profilesRead: number;
+  profilesMatched: number;
+  done: boolean;
+}
+
+export interface NpiProfileResult {
+  profileName: string;
+  created: boolean;
+  executables: ExecutableStatus[];
+  settingsWritten: number;
+  settingsSkipped: number;
+  conflicts: ImportConflict[];
+  errors: string[];
+}
+
+export interface NpiImportReport {
+  profilesRead: number;
+  profiles: NpiProfileResult[];
+  saved: boolean;
+  saveError: string | null;
+  verification: BatchVerification | null;
+  /** Why the written ShadowPlay values couldn't be read back */
+  verificationError: string | null;
+  /** Disabled executables that couldn't be recorded as managed */
+  manifestErrors: string[];
+}
+
+export interface VerifiedEntry {
+  executable: string;
+  expected: number;
+  /** Stored value; null if nothing is stored or there is no DRS entry */
+  actual: number | null;
+  verified: boolean;
+  error: string | null;
+}
+
+/** Payload of the "batch-verified" event */
+export interface BatchVerification {
+  verifiedAtMs: number;
+  mismatches: number;
+  entries: VerifiedEntry[];
+}
+
+export type MetadataKind = "Version" | "Pe" | "Signer" | "Icon";
+
+export interface VersionInfo {
+  productName: string | null;
+  fileDescription: string | null;
+  companyName: string | null;
+  fileVersion: string | null;
+}
+
+export interface PeInfo {
+  machine: string;
+  is64Bit: boolean;
+  isGui: boolean;
+  linkTimestamp: number;
+}
+
+export interface SignerInfo {
+  subject: string;
+}
+
+export interface IconInfo {
+  /** PNG data URL */
+  dataUrl: string;
+}
+
+export type MetadataFields =
+  | ({ kind: "Version" } & VersionInfo)
+  | ({ kind: "Pe" } & PeInfo)
+  | ({ kind: "Signer" } & SignerInfo)
+  | ({ kind: "Icon" } & IconInfo);
+
+export interface MetadataLookup {
+  key: string;
+  kind: MetadataKind;
+  fields: MetadataFields | null;
+  pending: boolean;
+}
+
+export interface MetadataReadyEvent {
+  key: string;
+  kind: MetadataKind;
+  fields: MetadataFields | null;
+}
+
+export type OverrideOwner = "Nvidiot" | "External" | "Predefined";
+
+export interface CaptureOverride {
+  profileName: string;
+  executables: string[];
+  settingId: number;
+  settingName: string;
+  value: number | null;
+  owner: OverrideOwner;
+}
+
+export interface CaptureAudit {
+  managed: CaptureOverride[];
+  external: CaptureOverride[];
+  predefined: CaptureOverride[];
+}
+
+export interface FeatureAvailability {
+  feature: string;
+  available: boolean;
+  missingFunctions: string[];
+}
+
+export interface StartupProgram {
+  name: string;
+  executablePath: string;
+  registryKey: string;
+}
+
+export interface InstalledProgram {
+  displayName: string;
+  installLocation: string | null;
+  displayIcon: string | null;
+  executables: string[];
+  isLikelyGame: boolean;
+  /** Executables that already have a DRS entry; null when the scan skipped the DRS join */
+  inDrs: string[] | null;
+}
+
+/** Payload of the "installed-scan-progress" event */
+export interface ScanProgress {
+  scanned: number;
+  total: number;
+}
+
+/** What a dropped .url shortcut launches */
+export type ShortcutTarget =
+  | { type: "steam"; appId: number }
+  | { type: "epic"; appName: string; catalogItemId?: string }
+  | { type: "file"; path: string }
+  | { type: "other"; url: string };
+
+export type ResolutionPath = "direct" | "shellLink" | "steamManifest" | "epicManifest" | "fileUrl";
+
+export interface ResolvedExecutable {
+  /** The path that was dropped */
+  source: string;
+  resolution: ResolutionPath;
+  executablePath: string;
+  /** Name DRS matches on */
+  executable: string;
+  target?: ShortcutTarget;
+}
+
+export interface AddedExecutable {
+  resolved: ResolvedExecutable;
+  result: BlacklistResult;
+}
+
+export interface ProfileWarning {
+  profileName: string;
+  issues: string[];
+}
+
+export interface ProfileError {
+  profileName: string;
+  issues: string[];
+}
+
+export interface ValidationReport {
+  okProfiles: number;
+  warningProfiles: ProfileWarning[];
+  errorProfiles: ProfileError[];
+}
+
+export type CaptureVerdict = "WillCapture" | "WillNotCapture" | "Unknown";
+
+export type FactEffect = "Allows" | "Blocks" | "Neutral";
+
+export interface CaptureFact {
+  source: string;
+  detail: string;
+  effect: FactEffect;
+}
+
+export interface SuggestedComparison {
+  profileA: string;
+  profileB: string;
+}
+
+export interface CaptureExplanation {
+  executable: string;
+  verdict: CaptureVerdict;
+  facts: CaptureFact[];
+  /** Profiles worth passing to compareProfiles */
+  compareWith?: SuggestedComparison;
+}
+
+export type RecommendedAction =
+  | { type: "blacklistExecutable"; executable: string }
+  | { type: "blacklistLauncher"; launcher: string }
+  | { type: "restartGame"; processIds: number[] }
+  | { type: "disableGameDvr" };
+
+export interface Recommendation {
+  id: string;
+  action: RecommendedAction;
+  reason: string;
+  /** Whether applyRecommendations performs it; the others are for the user */
+  automatic: boolean;
+}
+
+export interface SiblingExecutable {
+  executable: string;
+  blacklisted: boolean | null;
+}
+
+export interface GameDiagnosis {
+  explanation: CaptureExplanation;
+  siblings: SiblingExecutable[];
+  recommendations: Recommendation[];
+}
+
+export type ActionOutcome = "applied" | "failed" | "manual";
+
+export interface ActionResult {
+  id: string;
+  action: RecommendedAction;
+  outcome: ActionOutcome;
+  message: string;
+}
+
+export type AttachStatus =
+  | { type: "attached" }
+  | { type: "alreadyAttached" }
+  | { type: "inOtherProfile"; profileName: string }
+  | { type: "missing" }
+  | { type: "failed"; error: string };
+
+export interface ExecutableStatus {
+  executable: string;
+  status: AttachStatus;
+}
+
+export interface ManagedGameStatus {
+  name: string;
+  profileName: string;
+  createdAtMs: number;
+  executables: ExecutableStatus[];
+}
+
+export type FilterType = "Default" | "IncludeAll" | "ExcludeSystem";
+
+// Field names are snake_case to match the external monitoring format
+export interface StatusSummary {
+  version: number;
+  nvapi: "ok" | "error";
+  session_generation: number;
+  blacklisted_count: number | null;
+  automation_paused: boolean;
+  last_save_ok: boolean | null;
+  last_verification: { time: number; drifted: number } | null;
+  worker_queue_depth: number;
+  /** Added in version 2 */
+  revert_loops: number;
+}
+
+export type ChunkMessage<T> =
+  | { type: "chunk"; index: number; items: T[] }
+  | { type: "done"; chunks: number; total: number; freshness: Freshness };
+
+export type MaintenanceCost = "low" | "medium" | "high";
+
+export type MaintenanceOutcome =
+  | { type: "completed"; summary: string }
+  | { type: "cancelled" }
+  | { type: "failed"; error: string };
+
+export interface MaintenanceJobReport {
+  name: string;
+  estimatedCost: MaintenanceCost;
+  lastCompletedMs: number | null;
+  lastRunMs: number | null;
+  lastOutcome: MaintenanceOutcome | null;
+}
+
+export interface MaintenanceReport {
+  running: boolean;
+  jobs: MaintenanceJobReport[];
+}
+
+export interface Question {
+  id: number;
+  kind: string;
+  /** Shape depends on `kind`; "import-conflicts" carries an ImportOutcome */
+  payload: unknown;
+  createdMs: number;
+  expiresMs: number;
+}
+
+export interface QuestionPending {
+  question: Question;
+  pendingCount: number;
+}
+
+export type QuestionResolution = "answered" | "expired";
+
+export interface QuestionHistoryEntry {
+  id: number;
+  kind: string;
+  createdMs: number;
+  resolvedMs: number;
+  resolution: QuestionResolution;
 }
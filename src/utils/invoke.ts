@@ -1,34 +1,402 @@
-import { invoke } from "@tauri-apps/api/core";
+import { Channel, invoke } from "@tauri-apps/api/core";
 import type {
+  ApplicationCreated,
   DrsProfile,
+  ProfileSortField,
+  ApplicationSortField,
   DrsApplication,
+  DrsSetting,
+  AvailableSetting,
+  BackupDiff,
+  ProfileComparison,
+  BackupRestoreEntry,
   RunningProcess,
+  ProcessAccessibility,
+  ProcessArchitecture,
+  ProcessBlacklistStatus,
   FocusApplication,
+  FocusApplicationExtended,
+  MonitorInfo,
   BlacklistResult,
   NvApiStatus,
+  Diagnostics,
+  AutomationState,
+  ResolutionTrace,
+  FirstRunScan,
+  NewRule,
+  Rule,
+  RuleLimits,
+  CacheLimits,
+  ProfileNaming,
+  BlacklistQuery,
+  PowerMode,
+  PowerModeStatus,
+  BulkLimits,
+  RevertLoopDetection,
+  ProfileFilter,
+  BulkProfileChange,
+  BulkSetResult,
+  BulkOperation,
+  ProfileRenameReport,
+  DrsStoreInfo,
+  ScopeExplanation,
+  PresetMetadata,
+  PresetInstallPlan,
+  LegacyMigration,
+  ProfileDeletion,
+  WindowsGamingState,
+  BlacklistFile,
+  ImportMode,
+  ContentEncoding,
+  ImportResolution,
+  ImportOutcome,
+  NpiImportOptions,
+  NpiImportReport,
+  PanicState,
+  PanicHotkey,
+  RestoreReport,
+  MetadataKind,
+  MetadataLookup,
+  CaptureAudit,
+  BackendCapabilities,
+  CommandLimits,
+  LimitErrorKind,
+  StartupProgram,
+  InstalledProgram,
+  AddedExecutable,
+  ValidationReport,
+  CaptureExplanation,
+  GameDiagnosis,
+  ActionResult,
+  ExecutableStatus,
+  ManagedGameStatus,
+  FilterType,
+  PendingRevert,
+  StatusSummary,
+  Freshness,
+  JournalPage,
+  BlacklistSnapshot,
+  ManagedEntry,
+  ManagedSortField,
+  ChunkMessage,
+  MaintenanceReport,
+  Question,
+  QuestionHistoryEntry,
 } from "../types";
 
-export async function getProfiles(): Promise<DrsProfile[]> {
-  return invoke<DrsProfile[]>("get_profiles");
+/** Invoke a list command in chunked mode and reassemble the chunks */
+async function invokeChunked<T>(
+  command: string,
+  args: Record<string, unknown> = {}
+): Promise<T[]> {
+  const chunks: T[][] = [];
+  // Assigned in the callback, so keep TypeScript from narrowing it to null
+  let summary = null as { chunks: number; total: number } | null;
+  const channel = new Channel<ChunkMessage<T>>();
+  channel.onmessage = (message) => {
+    if (message.type === "chunk") {
+      chunks[message.index] = message.items;
+    } else {
+      summary = message;
+    }
+  };
+
+  await invoke<T[]>(command, { ...args, channel });
+
+  const items = chunks.flat();
+  if (
+    !summary ||
+    summary.chunks !== chunks.length ||
+    summary.total !== items.length
+  ) {
+    throw new Error(`Incomplete chunked response from ${command}`);
+  }
+  return items;
+}
+
+export async function getProfiles(
+  includeShadowplay = false
+): Promise<DrsProfile[]> {
+  return invoke<DrsProfile[]>("get_profiles", { includeShadowplay });
+}
+
+export async function getProfilesChunked(
+  includeShadowplay = false
+): Promise<DrsProfile[]> {
+  return invokeChunked<DrsProfile>("get_profiles", { includeShadowplay });
+}
+
+export async function findProfileFuzzy(query: string): Promise<DrsProfile[]> {
+  return invoke<DrsProfile[]>("find_profile_fuzzy", { query });
+}
+
+export async function getProfilesSorted(
+  sortBy: ProfileSortField,
+  ascending = true,
+  includePredefined = true
+): Promise<DrsProfile[]> {
+  return invoke<DrsProfile[]>("get_profiles_sorted", {
+    sortBy,
+    ascending,
+    includePredefined,
+  });
+}
+
+export async function listProfileApplicationsSorted(
+  profileName: string,
+  sortBy: ApplicationSortField,
+  ascending = true
+): Promise<DrsApplication[]> {
+  return invoke<DrsApplication[]>("list_profile_applications_sorted", {
+    profileName,
+    sortBy,
+    ascending,
+  });
 }
 
 export async function getAllApplications(): Promise<DrsApplication[]> {
   return invoke<DrsApplication[]>("get_all_applications");
 }
 
-export async function getRunningProcesses(): Promise<RunningProcess[]> {
-  return invoke<RunningProcess[]>("get_running_processes");
+export async function getAllApplicationsChunked(): Promise<DrsApplication[]> {
+  return invokeChunked<DrsApplication>("get_all_applications");
+}
+
+/**
+ * Applications for the blacklist view. Right after startup the answer may be
+ * stale; a live one then follows as a "blacklist-snapshot" event.
+ */
+export async function getBlacklistSnapshot(): Promise<BlacklistSnapshot> {
+  return invoke<BlacklistSnapshot>("get_blacklist_snapshot");
+}
+
+export async function getAllApplicationsGrouped(): Promise<
+  Record<string, DrsApplication[]>
+> {
+  return invoke<Record<string, DrsApplication[]>>(
+    "get_all_applications_grouped"
+  );
+}
+
+export async function getAllApplicationsDetailed(
+  includeSettings = false
+): Promise<DrsApplication[]> {
+  return invoke<DrsApplication[]>("get_all_applications_detailed", {
+    includeSettings,
+  });
+}
+
+export async function getAllApplicationsDetailedChunked(
+  includeSettings = false
+): Promise<DrsApplication[]> {
+  return invokeChunked<DrsApplication>("get_all_applications_detailed", {
+    includeSettings,
+  });
+}
+
+export async function getAvailableSettings(): Promise<AvailableSetting[]> {
+  return invoke<AvailableSetting[]>("get_available_settings");
+}
+
+export async function getAvailableSettingsChunked(): Promise<AvailableSetting[]> {
+  return invokeChunked<AvailableSetting>("get_available_settings");
+}
+
+export async function getTotalApplicationCount(): Promise<number> {
+  return invoke<number>("get_total_application_count");
+}
+
+export async function refreshPredefinedCache(): Promise<number> {
+  return invoke<number>("refresh_predefined_cache");
+}
+
+export async function getApplicationInfo(
+  executable: string
+): Promise<DrsApplication> {
+  return invoke<DrsApplication>("get_application_info", { executable });
+}
+
+/** Every DRS entry matching each executable's basename; unknown ones are omitted */
+export async function joinExecutables(
+  executables: string[]
+): Promise<Record<string, DrsApplication[]>> {
+  return invoke<Record<string, DrsApplication[]>>("join_executables", {
+    executables,
+  });
+}
+
+export async function setApplicationLauncher(
+  executable: string,
+  launcher: string
+): Promise<void> {
+  return invoke("set_application_launcher", { executable, launcher });
+}
+
+export async function getRunningProcesses(
+  includeMemory = false,
+  includeGpuUsage = false
+): Promise<RunningProcess[]> {
+  return invoke<RunningProcess[]>("get_running_processes", {
+    includeMemory,
+    includeGpuUsage,
+  });
+}
+
+export async function checkProcessAccessible(
+  processId: number
+): Promise<ProcessAccessibility> {
+  return invoke<ProcessAccessibility>("check_process_accessible", {
+    processId,
+  });
+}
+
+export async function getRunningProcessBlacklistStatus(): Promise<ProcessBlacklistStatus[]> {
+  return invoke<ProcessBlacklistStatus[]>("get_running_process_blacklist_status");
+}
+
+export async function getProcessArchitecture(
+  processId: number
+): Promise<ProcessArchitecture | null> {
+  return invoke<ProcessArchitecture | null>("get_process_architecture", {
+    processId,
+  });
+}
+
+export async function searchRunningProcesses(
+  query: string,
+  searchTitle = true,
+  searchName = true
+): Promise<RunningProcess[]> {
+  return invoke<RunningProcess[]>("search_running_processes", {
+    query,
+    searchTitle,
+    searchName,
+  });
+}
+
+export async function setProcessFilter(filterType: FilterType): Promise<void> {
+  return invoke("set_process_filter", { filterType });
+}
+
+export async function addProcessNameFilter(name: string): Promise<boolean> {
+  return invoke<boolean>("add_process_name_filter", { name });
+}
+
+export async function addWindowClassFilter(className: string): Promise<boolean> {
+  return invoke<boolean>("add_window_class_filter", { class: className });
+}
+
+export async function injectHotkeyToProcess(
+  processId: number,
+  virtualKey: number,
+  modifiers?: number[]
+): Promise<void> {
+  return invoke("inject_hotkey_to_process", {
+    processId,
+    virtualKey,
+    modifiers,
+  });
+}
+
+export async function toggleRecordingInProcess(
+  processId: number
+): Promise<void> {
+  return invoke("toggle_recording_in_process", { processId });
+}
+
+export async function getExecutableMetadata(
+  paths: string[],
+  kind: MetadataKind
+): Promise<MetadataLookup[]> {
+  return invoke<MetadataLookup[]>("get_executable_metadata", { paths, kind });
+}
+
+export async function getStartupPrograms(): Promise<StartupProgram[]> {
+  return invoke<StartupProgram[]>("get_startup_programs");
+}
+
+/**
+ * Scan installed programs, reporting "installed-scan-progress" as folders are
+ * walked. With `join` false DRS isn't consulted, so it works without NVAPI.
+ */
+export async function listInstalledPrograms(
+  notInDrs = false,
+  join = true
+): Promise<InstalledProgram[]> {
+  return invoke<InstalledProgram[]>("list_installed_programs", {
+    join,
+    notInDrs,
+  });
+}
+
+/**
+ * Blacklist the game behind a dropped executable, .lnk or Steam/Epic .url.
+ * Shortcuts that can't be followed fail with "unresolvable-shortcut: <target>: <reason>".
+ */
+export async function addExecutableFromPath(path: string): Promise<AddedExecutable> {
+  return invoke<AddedExecutable>("add_executable_from_path", { path });
 }
 
 export async function getFocusApplication(): Promise<FocusApplication | null> {
   return invoke<FocusApplication | null>("get_focus_application");
 }
 
+export async function getFocusApplicationExtended(): Promise<FocusApplicationExtended | null> {
+  return invoke<FocusApplicationExtended | null>("get_focus_application_extended");
+}
+
+export async function getFocusMonitor(): Promise<string | null> {
+  return invoke<string | null>("get_focus_monitor");
+}
+
+export async function listMonitors(): Promise<MonitorInfo[]> {
+  return invoke<MonitorInfo[]>("list_monitors");
+}
+
 export async function createProfile(
   executable: string,
-  profileName: string
-): Promise<void> {
-  return invoke("create_profile", { executable, profileName });
+  profileName: string,
+  verify = true
+): Promise<ApplicationCreated> {
+  return invoke<ApplicationCreated>("create_profile", { executable, profileName, verify });
+}
+
+export async function createProfileWithSettings(
+  profileName: string,
+  settings: [number, number][]
+): Promise<DrsProfile> {
+  return invoke<DrsProfile>("create_profile_with_settings", {
+    profileName,
+    settings,
+  });
+}
+
+export async function ensureProfileExists(profileName: string): Promise<boolean> {
+  return invoke<boolean>("ensure_profile_exists", { profileName });
+}
+
+export async function getNvidiotCreatedProfiles(): Promise<DrsProfile[]> {
+  return invoke<DrsProfile[]>("get_nvidiot_created_profiles");
+}
+
+export async function estimateProfileSize(profileName: string): Promise<number> {
+  return invoke<number>("estimate_profile_size", { profileName });
+}
+
+export async function deleteAllNvidiotProfiles(): Promise<ProfileDeletion> {
+  return invoke<ProfileDeletion>("delete_all_nvidiot_profiles");
+}
+
+export async function factoryResetDrsProfiles(confirm: boolean): Promise<ProfileDeletion> {
+  return invoke<ProfileDeletion>("factory_reset_drs_profiles", { confirm });
+}
+
+export async function migrateLegacyProfiles(): Promise<LegacyMigration> {
+  return invoke<LegacyMigration>("migrate_legacy_profiles");
+}
+
+export async function validateDrsDatabase(): Promise<ValidationReport> {
+  return invoke<ValidationReport>("validate_drs_database");
 }
 
 export async function blacklistApplication(
@@ -43,10 +411,514 @@ export async function unblacklistApplication(
   return invoke<BlacklistResult>("unblacklist_application", { executable });
 }
 
+export async function isBlacklisted(
+  executable: string
+): Promise<BlacklistQuery> {
+  return invoke<BlacklistQuery>("is_blacklisted", { executable });
+}
+
+export async function getPowerMode(
+  executable: string
+): Promise<PowerModeStatus> {
+  return invoke<PowerModeStatus>("get_power_mode", { executable });
+}
+
+export async function setPowerMode(
+  executable: string,
+  mode: PowerMode
+): Promise<PowerModeStatus> {
+  return invoke<PowerModeStatus>("set_power_mode", { executable, mode });
+}
+
+export async function toggleShadowplayForApplication(
+  executable: string
+): Promise<BlacklistResult> {
+  return invoke<BlacklistResult>("toggle_shadowplay_for_application", {
+    executable,
+  });
+}
+
+export async function toggleShadowplayForProcess(
+  processId: number
+): Promise<BlacklistResult> {
+  return invoke<BlacklistResult>("toggle_shadowplay_for_process", {
+    processId,
+  });
+}
+
+export async function temporarilyUnblacklist(
+  executable: string,
+  durationMinutes: number
+): Promise<PendingRevert> {
+  return invoke<PendingRevert>("temporarily_unblacklist", {
+    executable,
+    durationMinutes,
+  });
+}
+
+export async function getPendingReverts(): Promise<PendingRevert[]> {
+  return invoke<PendingRevert[]>("get_pending_reverts");
+}
+
+export async function cancelRevert(executable: string): Promise<boolean> {
+  return invoke<boolean>("cancel_revert", { executable });
+}
+
+export async function applySettingToAllProfiles(
+  settingId: number,
+  value: number,
+  includePredefined = false
+): Promise<number> {
+  return invoke<number>("apply_setting_to_all_profiles", {
+    settingId,
+    value,
+    includePredefined,
+  });
+}
+
+export async function bulkSetSetting(
+  filter: ProfileFilter,
+  settingId: number,
+  value: number,
+  dryRun: boolean
+): Promise<BulkSetResult> {
+  return invoke<BulkSetResult>("bulk_set_setting", {
+    filter,
+    settingId,
+    value,
+    dryRun,
+  });
+}
+
+export async function undoBulkSetting(
+  operationId: number
+): Promise<BulkProfileChange[]> {
+  return invoke<BulkProfileChange[]>("undo_bulk_setting", { operationId });
+}
+
+export async function getBulkHistory(): Promise<BulkOperation[]> {
+  return invoke<BulkOperation[]>("get_bulk_history");
+}
+
+export async function getBulkLimits(): Promise<BulkLimits> {
+  return invoke<BulkLimits>("get_bulk_limits");
+}
+
+export async function setBulkLimits(limits: BulkLimits): Promise<BulkLimits> {
+  return invoke<BulkLimits>("set_bulk_limits", { limits });
+}
+
+export async function getRevertLoopDetection(): Promise<RevertLoopDetection> {
+  return invoke<RevertLoopDetection>("get_revert_loop_detection");
+}
+
+export async function setRevertLoopDetection(settings: RevertLoopDetection): Promise<RevertLoopDetection> {
+  return invoke<RevertLoopDetection>("set_revert_loop_detection", { settings });
+}
+
+export async function exportProfilesAsBat(
+  profileNames: string[],
+  destPath: string
+): Promise<void> {
+  return invoke("export_profiles_as_bat", { profileNames, destPath });
+}
+
+export async function resolveApplication(
+  executable: string
+): Promise<ResolutionTrace> {
+  return invoke<ResolutionTrace>("resolve_application", { executable });
+}
+
+export async function explainCapture(
+  executableOrPid: string
+): Promise<CaptureExplanation> {
+  return invoke<CaptureExplanation>("explain_capture", { executableOrPid });
+}
+
+export async function diagnoseGame(executable: string): Promise<GameDiagnosis> {
+  return invoke<GameDiagnosis>("diagnose_game", { executable });
+}
+
+export async function applyRecommendations(
+  executable: string,
+  selectedActions: string[]
+): Promise<ActionResult[]> {
+  return invoke<ActionResult[]>("apply_recommendations", {
+    executable,
+    selectedActions,
+  });
+}
+
+export async function registerGame(
+  name: string,
+  executables: string[]
+): Promise<ManagedGameStatus> {
+  return invoke<ManagedGameStatus>("register_game", { name, executables });
+}
+
+export async function getManagedEntry(name: string): Promise<ManagedGameStatus> {
+  return invoke<ManagedGameStatus>("get_managed_entry", { name });
+}
+
+export async function addExecutableToEntry(
+  name: string,
+  executable: string
+): Promise<ExecutableStatus> {
+  return invoke<ExecutableStatus>("add_executable_to_entry", { name, executable });
+}
+
+export async function removeExecutableFromEntry(
+  name: string,
+  executable: string
+): Promise<boolean> {
+  return invoke<boolean>("remove_executable_from_entry", { name, executable });
+}
+
 export async function checkNvApiStatus(): Promise<NvApiStatus> {
   return invoke<NvApiStatus>("check_nvapi_status");
 }
 
+export async function isNvApiDrsComplete(): Promise<boolean> {
+  return invoke<boolean>("is_nvapi_drs_complete");
+}
+
+export async function isNvApiMinimalReady(): Promise<boolean> {
+  return invoke<boolean>("is_nvapi_minimal_ready");
+}
+
+export async function getWindowsGamingState(): Promise<WindowsGamingState> {
+  return invoke<WindowsGamingState>("get_windows_gaming_state");
+}
+
+export async function getDiagnostics(): Promise<Diagnostics> {
+  return invoke<Diagnostics>("get_diagnostics");
+}
+
+export async function getStatusSummary(): Promise<StatusSummary> {
+  return invoke<StatusSummary>("get_status_summary");
+}
+
+export async function getFreshness(): Promise<Freshness> {
+  return invoke<Freshness>("get_freshness");
+}
+
+/** Activity journal entries after sinceSeq, or every entry still kept */
+export async function getJournal(sinceSeq?: number): Promise<JournalPage> {
+  return invoke<JournalPage>("get_journal", { sinceSeq });
+}
+
+export async function clearCaches(): Promise<void> {
+  return invoke("clear_caches");
+}
+
 export async function reloadSettings(): Promise<void> {
   return invoke("reload_settings");
 }
+
+export async function retrySave(): Promise<void> {
+  return invoke("retry_save");
+}
+
+export async function runMaintenanceNow(): Promise<MaintenanceReport> {
+  return invoke<MaintenanceReport>("run_maintenance_now");
+}
+
+export async function getMaintenanceReport(): Promise<MaintenanceReport> {
+  return invoke<MaintenanceReport>("get_maintenance_report");
+}
+
+export async function loadDrsFromFile(path: string): Promise<void> {
+  return invoke("load_drs_from_file", { path });
+}
+
+export async function saveDrsToFile(path: string): Promise<void> {
+  return invoke("save_drs_to_file", { path });
+}
+
+export async function flushSettings(): Promise<void> {
+  return invoke("flush_settings");
+}
+
+export async function firstRunScan(): Promise<FirstRunScan | null> {
+  return invoke<FirstRunScan | null>("first_run_scan");
+}
+
+export async function adoptExternalEntries(
+  executables: string[]
+): Promise<number> {
+  return invoke<number>("adopt_external_entries", { executables });
+}
+
+export async function getManagedEntries(
+  sortBy?: ManagedSortField,
+  ascending = false
+): Promise<ManagedEntry[]> {
+  return invoke<ManagedEntry[]>("get_managed_entries", { sortBy, ascending });
+}
+
+export async function auditCaptureSettings(): Promise<CaptureAudit> {
+  return invoke<CaptureAudit>("audit_capture_settings");
+}
+
+export async function claimOverride(executable: string): Promise<void> {
+  return invoke("claim_override", { executable });
+}
+
+export async function releaseOverride(executable: string): Promise<boolean> {
+  return invoke<boolean>("release_override", { executable });
+}
+
+export async function setAutomationPaused(
+  paused: boolean
+): Promise<AutomationState> {
+  return invoke<AutomationState>("set_automation_paused", { paused });
+}
+
+export async function getAutomationState(): Promise<AutomationState> {
+  return invoke<AutomationState>("get_automation_state");
+}
+
+export async function getRules(): Promise<Rule[]> {
+  return invoke<Rule[]>("get_rules");
+}
+
+export async function addRule(rule: NewRule): Promise<Rule> {
+  return invoke<Rule>("add_rule", { rule });
+}
+
+export async function removeRule(ruleId: number): Promise<boolean> {
+  return invoke<boolean>("remove_rule", { ruleId });
+}
+
+export async function setRuleEnabled(
+  ruleId: number,
+  enabled: boolean
+): Promise<Rule | null> {
+  return invoke<Rule | null>("set_rule_enabled", { ruleId, enabled });
+}
+
+export async function getSuspendedRules(): Promise<Rule[]> {
+  return invoke<Rule[]>("get_suspended_rules");
+}
+
+export async function resumeRule(ruleId: number): Promise<Rule | null> {
+  return invoke<Rule | null>("resume_rule", { ruleId });
+}
+
+export async function getRuleLimits(): Promise<RuleLimits> {
+  return invoke<RuleLimits>("get_rule_limits");
+}
+
+export async function setRuleLimits(limits: RuleLimits): Promise<RuleLimits> {
+  return invoke<RuleLimits>("set_rule_limits", { limits });
+}
+
+export async function getCacheLimits(): Promise<CacheLimits> {
+  return invoke<CacheLimits>("get_cache_limits");
+}
+
+export async function setCacheLimits(
+  limits: CacheLimits
+): Promise<CacheLimits> {
+  return invoke<CacheLimits>("set_cache_limits", { limits });
+}
+
+export async function getProfileNaming(): Promise<ProfileNaming> {
+  return invoke<ProfileNaming>("get_profile_naming");
+}
+
+export async function setProfileNaming(
+  naming: ProfileNaming
+): Promise<ProfileNaming> {
+  return invoke<ProfileNaming>("set_profile_naming", { naming });
+}
+
+export async function renameManagedProfiles(): Promise<ProfileRenameReport> {
+  return invoke<ProfileRenameReport>("rename_managed_profiles");
+}
+
+export async function openBackupReadonly(path: string): Promise<number> {
+  return invoke<number>("open_backup_readonly", { path });
+}
+
+export async function closeBackup(sessionId: number): Promise<boolean> {
+  return invoke<boolean>("close_backup", { sessionId });
+}
+
+export async function getBackupProfiles(
+  sessionId: number,
+  includeShadowplay = false
+): Promise<DrsProfile[]> {
+  return invoke<DrsProfile[]>("get_backup_profiles", {
+    sessionId,
+    includeShadowplay,
+  });
+}
+
+export async function getBackupApplications(
+  sessionId: number
+): Promise<DrsApplication[]> {
+  return invoke<DrsApplication[]>("get_backup_applications", { sessionId });
+}
+
+export async function getBackupProfileSettings(
+  sessionId: number,
+  profileName: string
+): Promise<DrsSetting[]> {
+  return invoke<DrsSetting[]>("get_backup_profile_settings", {
+    sessionId,
+    profileName,
+  });
+}
+
+export async function diffAgainstBackup(path: string): Promise<BackupDiff> {
+  return invoke<BackupDiff>("diff_against_backup", { path });
+}
+
+/** Compare two profiles; pass an empty name for the base profile */
+export async function compareProfiles(profileA: string, profileB: string): Promise<ProfileComparison> {
+  return invoke<ProfileComparison>("compare_profiles", { profileA, profileB });
+}
+
+export async function applyBackupEntries(
+  entries: BackupRestoreEntry[]
+): Promise<number> {
+  return invoke<number>("apply_backup_entries", { entries });
+}
+
+export async function saveAndExit(): Promise<void> {
+  return invoke("save_and_exit");
+}
+
+export async function discardAndExit(): Promise<void> {
+  return invoke("discard_and_exit");
+}
+
+export async function cancelExit(): Promise<string[]> {
+  return invoke<string[]>("cancel_exit");
+}
+
+export async function getBackendCapabilities(): Promise<BackendCapabilities> {
+  return invoke<BackendCapabilities>("get_backend_capabilities");
+}
+
+/** Whether window titles are replaced with process names */
+export async function getPrivacyMode(): Promise<boolean> {
+  return invoke<boolean>("get_privacy_mode");
+}
+
+export async function setPrivacyMode(enabled: boolean): Promise<boolean> {
+  return invoke<boolean>("set_privacy_mode", { enabled });
+}
+
+export async function getCommandLimits(): Promise<CommandLimits> {
+  return invoke<CommandLimits>("get_command_limits");
+}
+
+export async function setCommandLimits(limits: CommandLimits): Promise<CommandLimits> {
+  return invoke<CommandLimits>("set_command_limits", { limits });
+}
+
+const LIMIT_ERROR_KINDS: LimitErrorKind[] = ["rate-limited", "too-many-items", "argument-too-long"];
+
+/** Which command-layer limit refused a call, or null for any other error */
+export function limitErrorKind(error: unknown): LimitErrorKind | null {
+  const message = String(error);
+  return LIMIT_ERROR_KINDS.find((kind) => message.startsWith(`${kind}:`)) ?? null;
+}
+
+export async function getDrsStoreInfo(): Promise<DrsStoreInfo> {
+  return invoke<DrsStoreInfo>("get_drs_store_info");
+}
+
+export async function getScopeExplanation(): Promise<ScopeExplanation> {
+  return invoke<ScopeExplanation>("get_scope_explanation");
+}
+
+export async function installPreset(
+  pathOrJson: string,
+  confirm = false
+): Promise<PresetInstallPlan> {
+  return invoke<PresetInstallPlan>("install_preset", { pathOrJson, confirm });
+}
+
+export async function exportRulesPreset(
+  metadata: PresetMetadata
+): Promise<string> {
+  return invoke<string>("export_rules_preset", { metadata });
+}
+
+export async function exportBlacklist(destPath: string): Promise<BlacklistFile> {
+  return invoke<BlacklistFile>("export_blacklist", { destPath });
+}
+
+export async function importBlacklist(
+  path: string,
+  mode: ImportMode
+): Promise<ImportOutcome> {
+  return invoke<ImportOutcome>("import_blacklist", { path, mode });
+}
+
+export async function importBlacklistFromJson(
+  content: string,
+  mode: ImportMode,
+  encoding: ContentEncoding = "Raw"
+): Promise<ImportOutcome> {
+  return invoke<ImportOutcome>("import_blacklist_from_json", {
+    content,
+    encoding,
+    mode,
+  });
+}
+
+export async function resolveImport(
+  token: number,
+  resolutions: Record<string, ImportResolution>
+): Promise<ImportOutcome> {
+  return invoke<ImportOutcome>("resolve_import", { token, resolutions });
+}
+
+export async function cancelImport(token: number): Promise<boolean> {
+  return invoke<boolean>("cancel_import", { token });
+}
+
+/** Import an nvidiaProfileInspector full-configuration export; progress arrives as "npi-import-progress" */
+export async function importNpiConfig(path: string, options: NpiImportOptions = {}): Promise<NpiImportReport> {
+  return invoke<NpiImportReport>("import_npi_config", { path, options });
+}
+
+/** Disable ShadowPlay everywhere now; calling it again keeps the original restore point */
+export async function panicDisableCapture(): Promise<PanicState> {
+  return invoke<PanicState>("panic_disable_capture");
+}
+
+export async function restoreCaptureState(): Promise<RestoreReport> {
+  return invoke<RestoreReport>("restore_capture_state");
+}
+
+export async function getPanicState(): Promise<PanicState> {
+  return invoke<PanicState>("get_panic_state");
+}
+
+export async function getPanicHotkey(): Promise<PanicHotkey | null> {
+  return invoke<PanicHotkey | null>("get_panic_hotkey");
+}
+
+export async function setPanicHotkey(hotkey: PanicHotkey | null): Promise<PanicHotkey | null> {
+  return invoke<PanicHotkey | null>("set_panic_hotkey", { hotkey });
+}
+
+export async function getPendingQuestions(): Promise<Question[]> {
+  return invoke<Question[]>("get_pending_questions");
+}
+
+export async function answerQuestion(
+  id: number,
+  response: unknown
+): Promise<void> {
+  return invoke("answer_question", { id, response });
+}
+
+export async function getQuestionHistory(): Promise<QuestionHistoryEntry[]> {
+  return invoke<QuestionHistoryEntry[]>("get_question_history");
+}